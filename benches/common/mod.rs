@@ -0,0 +1,50 @@
+//! Synthetic topologies shared by the benchmarks
+
+use bgpsimulator::as_graphs::as_graph::{ASBuilder, ASGraph, ASN};
+
+/// Deterministic xorshift so runs are comparable across commits
+pub struct XorShift(pub u64);
+
+impl XorShift {
+    pub fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+/// Tier-1 clique, a transit layer and stubs, with roughly CAIDA's proportions
+pub fn build_topology(num_ases: usize) -> ASGraph {
+    let mut rng = XorShift(0x2545F4914F6CDD1D);
+    let num_tier_1 = 15;
+    let num_transit = num_ases / 7;
+    let mut builders: Vec<ASBuilder> = (1..=num_ases as ASN).map(ASBuilder::new).collect();
+
+    let link = |builders: &mut Vec<ASBuilder>, provider: usize, customer: usize| {
+        if !builders[customer].provider_asns.contains(&(provider as ASN + 1)) {
+            builders[provider].customer_asns.push(customer as ASN + 1);
+            builders[customer].provider_asns.push(provider as ASN + 1);
+        }
+    };
+
+    for (a, builder) in builders.iter_mut().take(num_tier_1).enumerate() {
+        builder.tier_1 = true;
+        builder.peer_asns = (1..=num_tier_1 as ASN).filter(|&b| b != a as ASN + 1).collect();
+    }
+    // Transit ASes buy from tier-1s or from earlier (higher) transit ASes
+    for customer in num_tier_1..num_tier_1 + num_transit {
+        for _ in 0..1 + rng.next(3) {
+            link(&mut builders, rng.next(customer), customer);
+        }
+    }
+    for customer in num_tier_1 + num_transit..num_ases {
+        for _ in 0..1 + rng.next(2) {
+            link(&mut builders, num_tier_1 + rng.next(num_transit), customer);
+        }
+    }
+
+    let mut as_graph = ASGraph::build(builders);
+    as_graph.assign_as_propagation_rank();
+    as_graph
+}
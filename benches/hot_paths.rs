@@ -0,0 +1,165 @@
+//! Criterion benchmarks for the propagation and validation hot paths
+//!
+//! Run with `cargo bench --bench hot_paths [-- <filter>]`, e.g. `-- roa_lookup`. Criterion keeps
+//! the previous run's results under target/criterion and reports the change against them.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::net::Ipv4Addr;
+use std::thread;
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use ipnetwork::{IpNetwork, Ipv4Network};
+
+use bgpsimulator::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsimulator::route_validator::{ROA, RouteValidator};
+use bgpsimulator::simulation_engine::{PrefixId, PrefixTable, SimulationEngine};
+use bgpsimulator::simulation_framework::scenarios::SubprefixHijack;
+use bgpsimulator::simulation_framework::ScenarioTrait;
+
+mod common;
+
+use common::{build_topology, XorShift};
+
+const NUM_ROAS: usize = 50_000;
+const NUM_THREADS: usize = 4;
+
+fn hijack(as_graph: &ASGraph) -> SubprefixHijack {
+    let num_ases = as_graph.len() as ASN;
+    SubprefixHijack::new(HashSet::from([num_ases - 1]), HashSet::from([num_ases]))
+}
+
+fn propagated_engine<'a>(as_graph: &'a ASGraph, scenario: &SubprefixHijack) -> SimulationEngine<'a> {
+    let mut engine = SimulationEngine::new(as_graph);
+    scenario.setup_engine(&mut engine, &mut RouteValidator::new());
+    engine.run(3);
+    engine
+}
+
+/// Setup and full propagation of a subprefix hijack
+fn bench_propagation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("propagation");
+    group.sample_size(10).measurement_time(Duration::from_secs(20));
+    for num_ases in [1_000, 10_000, 80_000] {
+        let as_graph = build_topology(num_ases);
+        let scenario = hijack(&as_graph);
+        group.throughput(Throughput::Elements(num_ases as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(num_ases), &as_graph, |b, as_graph| {
+            b.iter_batched(
+                || SimulationEngine::new(as_graph),
+                |mut engine| {
+                    scenario.setup_engine(&mut engine, &mut RouteValidator::new());
+                    engine.run(3);
+                    engine
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn random_ipv4_prefix(rng: &mut XorShift, min_len: u8, max_len: u8) -> IpNetwork {
+    let prefix_len = min_len + rng.next((max_len - min_len + 1) as usize) as u8;
+    let addr = (rng.next(u32::MAX as usize) as u32) & (u32::MAX << (32 - prefix_len));
+    IpNetwork::V4(Ipv4Network::new(Ipv4Addr::from(addr), prefix_len).unwrap())
+}
+
+/// ROV lookups through the trie, from the validator's cache, which threads share, and precomputed
+fn bench_roa_lookup(c: &mut Criterion) {
+    let mut rng = XorShift(0x9E3779B97F4A7C15);
+    let mut route_validator = RouteValidator::new();
+    route_validator.add_roas((0..NUM_ROAS)
+        .map(|_| {
+            let prefix = random_ipv4_prefix(&mut rng, 8, 24);
+            let max_length = prefix.prefix() + rng.next(3) as u8;
+            ROA::new(prefix, 1 + rng.next(80_000) as ASN, Some(max_length))
+        })
+        .collect());
+    // More distinct queries than the cache holds, so each one walks the trie
+    let queries: Vec<(IpNetwork, ASN)> = (0..20_000)
+        .map(|_| (random_ipv4_prefix(&mut rng, 8, 24), 1 + rng.next(80_000) as ASN))
+        .collect();
+
+    let mut group = c.benchmark_group("roa_lookup");
+    for (name, queries) in [("uncached", &queries[..]), ("cached", &queries[..1_000])] {
+        group.throughput(Throughput::Elements(queries.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for (prefix, origin) in queries {
+                    black_box(route_validator.get_roa_outcome(prefix, *origin));
+                }
+            })
+        });
+    }
+    // The same lookups precomputed, as scenarios do for the routes they seed
+    let mut frozen_validator = route_validator.clone();
+    frozen_validator.freeze(Arc::new(PrefixTable::new()), queries[..1_000].iter().copied());
+    group.throughput(Throughput::Elements(1_000));
+    group.bench_function("frozen", |b| {
+        b.iter(|| {
+            for (prefix, origin) in &queries[..1_000] {
+                black_box(frozen_validator.get_roa_outcome(prefix, *origin));
+            }
+        })
+    });
+    // The same lookups by prefix id, as ROV adopters make them during propagation, skipping the prefix hash
+    let frozen = frozen_validator.frozen().unwrap();
+    let frozen_ids: Vec<(PrefixId, IpNetwork, ASN)> = queries[..1_000].iter()
+        .map(|(prefix, origin)| (frozen.prefix_id(prefix).unwrap(), *prefix, *origin))
+        .collect();
+    group.bench_function("frozen_by_id", |b| {
+        b.iter(|| {
+            for (prefix_id, prefix, origin) in &frozen_ids {
+                black_box(frozen_validator.get_roa_outcome_by_id(*prefix_id, prefix, *origin));
+            }
+        })
+    });
+    // Cached lookups from several threads at once, as parallel propagation would make them
+    let cached_queries = &queries[..1_000];
+    let lookups_per_thread = 10 * cached_queries.len();
+    group.throughput(Throughput::Elements((NUM_THREADS * lookups_per_thread) as u64));
+    group.bench_function(format!("cached_{}_threads", NUM_THREADS), |b| {
+        b.iter(|| {
+            thread::scope(|scope| {
+                for _ in 0..NUM_THREADS {
+                    scope.spawn(|| {
+                        for (prefix, origin) in cached_queries.iter().cycle().take(lookups_per_thread) {
+                            black_box(route_validator.get_roa_outcome(prefix, *origin));
+                        }
+                    });
+                }
+            })
+        })
+    });
+    group.finish();
+}
+
+/// Choosing the best route among each AS's RIBs-in after propagation
+fn bench_best_path_selection(c: &mut Criterion) {
+    let as_graph = build_topology(10_000);
+    let scenario = hijack(&as_graph);
+    let engine = propagated_engine(&as_graph, &scenario);
+    let prefix = scenario.legitimate_prefix;
+    // Only ASes with a choice to make
+    let choices: Vec<ASN> = engine.policy_store.iter()
+        .filter(|(_, policy)| policy.ribs_in.best_candidates(&prefix).count() > 1)
+        .map(|(&asn, _)| asn)
+        .collect();
+
+    let mut group = c.benchmark_group("best_path_selection");
+    group.throughput(Throughput::Elements(choices.len() as u64));
+    group.bench_function("get_best_ann_for_prefix", |b| {
+        b.iter(|| {
+            for asn in &choices {
+                let policy = engine.policy_store.get(asn).unwrap();
+                black_box(policy.get_best_ann_for_prefix(&prefix, as_graph.get(asn).unwrap()));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_propagation, bench_roa_lookup, bench_best_path_selection);
+criterion_main!(benches);
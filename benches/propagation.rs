@@ -0,0 +1,87 @@
+//! Propagation benchmark on a synthetic CAIDA-scale topology
+//!
+//! Run with `cargo bench --bench propagation [-- <num_ases>] [--local-rib-only]`. Reports wall time and
+//! heap allocations for building the graph and for each trial, which is where announcement copies show up.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use bgpsimulator::as_graphs::as_graph::ASN;
+use bgpsimulator::route_validator::RouteValidator;
+use bgpsimulator::simulation_engine::{PolicyStorageMode, SimulationEngine};
+use bgpsimulator::simulation_framework::scenarios::SubprefixHijack;
+use bgpsimulator::simulation_framework::ScenarioTrait;
+
+mod common;
+
+use common::build_topology;
+
+/// System allocator that counts allocations and bytes allocated
+struct CountingAllocator;
+
+static NUM_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        NUM_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES_ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn main() {
+    let num_ases: usize = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(20_000);
+    let storage_mode = if std::env::args().any(|arg| arg == "--local-rib-only") {
+        PolicyStorageMode::LocalRibOnly
+    } else {
+        PolicyStorageMode::Full
+    };
+
+    let allocations_before = NUM_ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let as_graph = build_topology(num_ases);
+    println!(
+        "built {} ASes in {:.3}s, {} allocations",
+        num_ases,
+        start.elapsed().as_secs_f64(),
+        NUM_ALLOCATIONS.load(Ordering::Relaxed) - allocations_before,
+    );
+    let victim = num_ases as ASN;
+    let attacker = num_ases as ASN - 1;
+    let scenario = SubprefixHijack::new(HashSet::from([attacker]), HashSet::from([victim]));
+
+    for trial in 0..3 {
+        let mut engine = SimulationEngine::new(&as_graph).with_storage_mode(storage_mode);
+        let mut route_validator = RouteValidator::new();
+
+        let allocations_before = NUM_ALLOCATIONS.load(Ordering::Relaxed);
+        let bytes_before = BYTES_ALLOCATED.load(Ordering::Relaxed);
+        let start = Instant::now();
+
+        scenario.setup_engine(&mut engine, &mut route_validator);
+        engine.run(3);
+
+        println!(
+            "trial {} ({:?}): {} ASes in {:.3}s, {} allocations, {:.1} MB allocated",
+            trial,
+            storage_mode,
+            num_ases,
+            start.elapsed().as_secs_f64(),
+            NUM_ALLOCATIONS.load(Ordering::Relaxed) - allocations_before,
+            (BYTES_ALLOCATED.load(Ordering::Relaxed) - bytes_before) as f64 / 1e6,
+        );
+    }
+}
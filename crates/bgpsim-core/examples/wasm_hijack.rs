@@ -0,0 +1,95 @@
+//! Small hijack simulation for teaching in the browser, driven by `wasm_hijack.html`
+//!
+//! Build without file IO and copy the module next to the page:
+//!
+//!   cargo build -p bgpsim-core --example wasm_hijack --target wasm32-unknown-unknown --release --no-default-features
+//!   cp target/wasm32-unknown-unknown/release/examples/wasm_hijack.wasm crates/bgpsim-core/examples/
+//!   python3 -m http.server -d crates/bgpsim-core/examples
+//!
+//! The exports take and return plain integers, so the page needs no bindings
+use std::net::{IpAddr, Ipv4Addr};
+use std::str::FromStr;
+
+use bgpsim_core::as_graphs::as_graph::{ASBuilder, ASGraph, ASN};
+use bgpsim_core::shared::{Relationships, Timestamps};
+use bgpsim_core::simulation_engine::{Announcement, Prefix, SimulationEngine};
+
+const VICTIM_ASN: ASN = 7;
+const VICTIM_PREFIX: &str = "1.2.0.0/16";
+const SUBPREFIX: &str = "1.2.3.0/24";
+const DEST_IP_ADDR: IpAddr = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+
+///   1 (Tier-1) ---peer--- 2 (Tier-1)
+///    /    \                /    \
+///   3      4 ---peer--- 5       6
+///   |                           |
+///   7 (victim)                  8
+fn as_graph() -> ASGraph {
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_peers(vec![2]).with_customers(vec![3, 4]),
+        ASBuilder::new(2).as_tier_1().with_peers(vec![1]).with_customers(vec![5, 6]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![7]),
+        ASBuilder::new(4).with_providers(vec![1]).with_peers(vec![5]),
+        ASBuilder::new(5).with_providers(vec![2]).with_peers(vec![4]),
+        ASBuilder::new(6).with_providers(vec![2]).with_customers(vec![8]),
+        ASBuilder::new(7).with_providers(vec![3]),
+        ASBuilder::new(8).with_providers(vec![6]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    as_graph
+}
+
+fn sorted_asns(as_graph: &ASGraph) -> Vec<ASN> {
+    let mut asns: Vec<ASN> = as_graph.asns().collect();
+    asns.sort_unstable();
+    asns
+}
+
+#[no_mangle]
+pub extern "C" fn num_ases() -> u32 {
+    as_graph().len() as u32
+}
+
+/// ASN of the `index`th AS in ASN order, or 0 past the end
+#[no_mangle]
+pub extern "C" fn asn_at(index: u32) -> u32 {
+    sorted_asns(&as_graph()).get(index as usize).copied().unwrap_or(0)
+}
+
+#[no_mangle]
+pub extern "C" fn victim_asn() -> u32 {
+    VICTIM_ASN
+}
+
+/// Run a prefix hijack (or subprefix hijack if `subprefix` is nonzero) by `attacker_asn` against AS 7
+///
+/// Returns a bit mask of the ASes whose route to 1.2.3.4 leads to the attacker, bit `i` being the
+/// AS at `asn_at(i)`
+#[no_mangle]
+pub extern "C" fn run_hijack(attacker_asn: u32, subprefix: u32) -> u32 {
+    let as_graph = as_graph();
+    if attacker_asn == VICTIM_ASN || as_graph.get(&attacker_asn).is_none() {
+        return 0;
+    }
+    let victim_prefix = Prefix::from_str(VICTIM_PREFIX).unwrap();
+    let hijacked_prefix = if subprefix != 0 { Prefix::from_str(SUBPREFIX).unwrap() } else { victim_prefix };
+    let origin = |asn: ASN, prefix: Prefix| {
+        (asn, Announcement::new_with_path(prefix, vec![], asn, Relationships::Origin, Timestamps::VICTIM))
+    };
+
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.setup(vec![origin(VICTIM_ASN, victim_prefix), origin(attacker_asn, hijacked_prefix)]);
+    engine.run(10);
+
+    sorted_asns(&as_graph).iter().enumerate()
+        .filter(|(_, asn)| {
+            **asn == attacker_asn || engine.policy_store.get(asn)
+                .and_then(|policy| {
+                    policy.local_rib.values()
+                        .filter(|ann| !ann.withdraw && ann.prefix.contains(DEST_IP_ADDR))
+                        .max_by_key(|ann| ann.prefix.prefix())
+                })
+                .is_some_and(|route| route.as_path.contains(&attacker_asn))
+        })
+        .fold(0, |mask, (index, _)| mask | (1 << index))
+}
@@ -0,0 +1,860 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use smallvec::SmallVec;
+
+use crate::error::Error;
+use crate::shared::Relationships;
+
+use super::as_metadata::{ASMetadata, RIR};
+
+pub type ASN = u32;
+
+/// Position of an AS in the graph's storage
+pub type ASIndex = u32;
+
+/// Handle to a neighboring AS
+/// Carries the ASN for cheap comparisons and the index for O(1) lookup via `ASGraph::get_by_index`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ASRef {
+    pub asn: ASN,
+    pub index: ASIndex,
+}
+
+/// ASNs of one kind of neighbor, inline for the handful most ASes have
+pub type NeighborASNs = SmallVec<[ASN; 4]>;
+
+/// Neighbor handles of one kind while relationships are being changed
+type NeighborRefs = SmallVec<[ASRef; 4]>;
+
+/// Peers, providers and customers of one AS, in that order
+type NeighborLists = [NeighborRefs; 3];
+
+/// AS struct with index-based handles to its neighbors
+/// Neighbor lists are slices of one adjacency arena shared by the whole graph, see `ASGraph`
+#[derive(Clone)]
+pub struct AS {
+    pub asn: ASN,
+    pub index: ASIndex,
+    adjacency: Arc<[ASRef]>,
+    /// Start of this AS's peers, providers and customers in `adjacency`, and the end of its customers
+    neighbor_offsets: [u32; 4],
+    pub tier_1: bool,
+    pub ixp: bool,
+    pub provider_cone_asns: HashSet<ASN>,
+    pub propagation_rank: Option<u32>,
+    customer_cone_size: usize,
+}
+
+/// Builder struct used during AS graph construction
+#[derive(Debug, Clone)]
+pub struct ASBuilder {
+    pub asn: ASN,
+    pub peer_asns: NeighborASNs,
+    pub provider_asns: NeighborASNs,
+    pub customer_asns: NeighborASNs,
+    pub tier_1: bool,
+    pub ixp: bool,
+}
+
+/// AS Graph that owns all AS objects in a single arena
+///
+/// Neighbor handles live in a second, CSR-style arena: each AS's peers, providers and customers are
+/// contiguous, and ASes are laid out by propagation rank so the engine's fan-out walks it in order.
+/// Changing relationships re-lays out the arena, which is O(edges) like the rank recomputation it comes with
+#[derive(Debug, Clone, Default)]
+pub struct ASGraph {
+    ases: Vec<AS>,
+    asn_to_index: HashMap<ASN, ASIndex>,
+    pending_builders: Vec<ASBuilder>,
+    pub propagation_ranks: Vec<Vec<ASN>>,
+    /// Country, RIR and organization of the ASes they are known for
+    metadata: HashMap<ASN, ASMetadata>,
+}
+
+impl ASGraph {
+    /// Create a new empty AS graph
+    pub fn new() -> Self {
+        ASGraph {
+            ases: Vec::new(),
+            asn_to_index: HashMap::new(),
+            pending_builders: Vec::new(),
+            propagation_ranks: Vec::new(),
+            metadata: HashMap::new(),
+        }
+    }
+    
+    /// Build AS graph from relationship data
+    /// Neighbor ASNs that don't have a builder of their own are dropped
+    pub fn build(builders: Vec<ASBuilder>) -> ASGraph {
+        let mut as_graph = ASGraph::new();
+        
+        // First pass: create all AS objects with empty neighbor vectors
+        for builder in builders {
+            as_graph.add_as_from_builder(builder);
+        }
+        
+        // Second pass: resolve neighbor ASNs into handles
+        as_graph.resolve_pending_builders();
+        
+        as_graph
+    }
+    
+    fn resolve_pending_builders(&mut self) {
+        let mut lists = self.neighbor_lists();
+        for builder in std::mem::take(&mut self.pending_builders) {
+            if let Some(&index) = self.asn_to_index.get(&builder.asn) {
+                lists[index as usize] = [
+                    self.resolve_refs(&builder.peer_asns),
+                    self.resolve_refs(&builder.provider_asns),
+                    self.resolve_refs(&builder.customer_asns),
+                ];
+            }
+        }
+        self.set_neighbor_lists(lists);
+        self.assign_customer_cone_sizes();
+    }
+    
+    /// Copy of every AS's neighbor lists, by storage index, for changing relationships
+    fn neighbor_lists(&self) -> Vec<NeighborLists> {
+        self.ases.iter()
+            .map(|as_obj| [as_obj.peers().into(), as_obj.providers().into(), as_obj.customers().into()])
+            .collect()
+    }
+    
+    /// Lay out a new adjacency arena, with ASes ordered by propagation rank (unranked ASes last)
+    fn set_neighbor_lists(&mut self, lists: Vec<NeighborLists>) {
+        let mut order: Vec<usize> = (0..self.ases.len()).collect();
+        order.sort_by_key(|&index| (self.ases[index].propagation_rank.unwrap_or(u32::MAX), index));
+        
+        let mut adjacency = Vec::with_capacity(lists.iter().flatten().map(|list| list.len()).sum());
+        let mut offsets = vec![[0u32; 4]; self.ases.len()];
+        for index in order {
+            for (list_offset, list) in offsets[index].iter_mut().zip(&lists[index]) {
+                *list_offset = adjacency.len() as u32;
+                adjacency.extend_from_slice(list);
+            }
+            offsets[index][3] = adjacency.len() as u32;
+        }
+        
+        let adjacency: Arc<[ASRef]> = adjacency.into();
+        for (as_obj, neighbor_offsets) in self.ases.iter_mut().zip(offsets) {
+            as_obj.adjacency = adjacency.clone();
+            as_obj.neighbor_offsets = neighbor_offsets;
+        }
+    }
+    
+    fn resolve_refs(&self, asns: &[ASN]) -> NeighborRefs {
+        asns.iter()
+            .filter_map(|asn| self.asn_to_index.get(asn).map(|&index| ASRef { asn: *asn, index }))
+            .collect()
+    }
+    
+    /// Get an AS by ASN
+    pub fn get(&self, asn: &ASN) -> Option<&AS> {
+        self.asn_to_index.get(asn).map(|&index| &self.ases[index as usize])
+    }
+    
+    /// Get a mutable AS by ASN
+    pub fn get_mut(&mut self, asn: &ASN) -> Option<&mut AS> {
+        self.asn_to_index.get(asn).map(|&index| &mut self.ases[index as usize])
+    }
+    
+    /// Get an AS by its storage index (O(1), used for neighbor traversal)
+    pub fn get_by_index(&self, index: ASIndex) -> &AS {
+        &self.ases[index as usize]
+    }
+    
+    /// Resolve a neighbor handle into the neighboring AS
+    pub fn resolve(&self, as_ref: &ASRef) -> &AS {
+        self.get_by_index(as_ref.index)
+    }
+    
+    /// Check whether an ASN is in the graph
+    pub fn contains(&self, asn: &ASN) -> bool {
+        self.asn_to_index.contains_key(asn)
+    }
+    
+    /// Iterate over all AS objects
+    pub fn iter(&self) -> impl Iterator<Item = &AS> {
+        self.ases.iter()
+    }
+    
+    /// Iterate over all ASNs
+    pub fn asns(&self) -> impl Iterator<Item = ASN> + '_ {
+        self.ases.iter().map(|as_obj| as_obj.asn)
+    }
+    
+    /// Number of ASes in the graph
+    pub fn len(&self) -> usize {
+        self.ases.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.ases.is_empty()
+    }
+    
+    /// Check for cycles in the AS graph
+    pub fn check_for_cycles(&self) -> Result<(), Error> {
+        for as_obj in self.iter() {
+            // Check for self-loops
+            let self_loop = as_obj.providers().iter().chain(as_obj.customers()).any(|neighbor| neighbor.asn == as_obj.asn);
+            if self_loop {
+                return Err(Error::GraphCycle(vec![vec![as_obj.asn]]));
+            }
+        }
+        
+        let cycles = self.find_provider_cycles();
+        if !cycles.is_empty() {
+            return Err(Error::GraphCycle(cycles));
+        }
+        Ok(())
+    }
+    
+    /// Get the groups of ASes that are (indirectly) providers of each other, each sorted by ASN
+    pub fn find_provider_cycles(&self) -> Vec<Vec<ASN>> {
+        let mut cycles: Vec<Vec<ASN>> = self.provider_sccs().into_iter()
+            .filter(|scc| {
+                scc.len() > 1 || self.ases[scc[0]].providers().iter().any(|p| p.index as usize == scc[0])
+            })
+            .map(|scc| {
+                let mut asns: Vec<ASN> = scc.into_iter().map(|index| self.ases[index].asn).collect();
+                asns.sort_unstable();
+                asns
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+    
+    /// Strongly connected components of the provider graph, as storage indices
+    /// Every component comes after the components its members get transit from
+    fn provider_sccs(&self) -> Vec<Vec<usize>> {
+        // Iterative Tarjan, since CAIDA graphs are deep enough to overflow the stack
+        let num_ases = self.ases.len();
+        let mut order = vec![usize::MAX; num_ases];
+        let mut low_link = vec![0; num_ases];
+        let mut on_stack = vec![false; num_ases];
+        let mut stack = Vec::new();
+        let mut sccs = Vec::new();
+        let mut next_order = 0;
+        // (AS index, position of the next provider to visit)
+        let mut frames: Vec<(usize, usize)> = Vec::new();
+        
+        for root in 0..num_ases {
+            if order[root] != usize::MAX {
+                continue;
+            }
+            order[root] = next_order;
+            low_link[root] = next_order;
+            next_order += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            frames.push((root, 0));
+            
+            while let Some(&(index, position)) = frames.last() {
+                let providers = self.ases[index].providers();
+                if position < providers.len() {
+                    frames.last_mut().unwrap().1 += 1;
+                    let provider = providers[position].index as usize;
+                    if order[provider] == usize::MAX {
+                        order[provider] = next_order;
+                        low_link[provider] = next_order;
+                        next_order += 1;
+                        stack.push(provider);
+                        on_stack[provider] = true;
+                        frames.push((provider, 0));
+                    } else if on_stack[provider] {
+                        low_link[index] = low_link[index].min(order[provider]);
+                    }
+                    continue;
+                }
+                
+                frames.pop();
+                if let Some(&(parent, _)) = frames.last() {
+                    low_link[parent] = low_link[parent].min(low_link[index]);
+                }
+                if low_link[index] == order[index] {
+                    let mut scc = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        scc.push(member);
+                        if member == index {
+                            break;
+                        }
+                    }
+                    sccs.push(scc);
+                }
+            }
+        }
+        
+        sccs
+    }
+    
+    /// Assign propagation ranks to ASes
+    /// ASes without providers get rank 0 and every other AS ranks one below its lowest-ranked provider
+    /// Provider cycles (see `find_provider_cycles`) are collapsed, so all ASes in a cycle share a rank
+    pub fn assign_as_propagation_rank(&mut self) {
+        let sccs = self.provider_sccs();
+        let mut scc_of = vec![0; self.ases.len()];
+        for (scc_id, scc) in sccs.iter().enumerate() {
+            for &index in scc {
+                scc_of[index] = scc_id;
+            }
+        }
+        
+        // Providers' components always come first, so their ranks are known
+        let mut scc_ranks = vec![0u32; sccs.len()];
+        for (scc_id, scc) in sccs.iter().enumerate() {
+            scc_ranks[scc_id] = scc.iter()
+                .flat_map(|&index| self.ases[index].providers().iter())
+                .map(|provider| scc_of[provider.index as usize])
+                .filter(|&provider_scc| provider_scc != scc_id)
+                .map(|provider_scc| scc_ranks[provider_scc] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+        
+        // Apply ranks to AS objects
+        let mut propagation_ranks: Vec<Vec<ASN>> = Vec::new();
+        for (index, as_obj) in self.ases.iter_mut().enumerate() {
+            let rank = scc_ranks[scc_of[index]];
+            as_obj.propagation_rank = Some(rank);
+            if propagation_ranks.len() <= rank as usize {
+                propagation_ranks.resize(rank as usize + 1, Vec::new());
+            }
+            propagation_ranks[rank as usize].push(as_obj.asn);
+        }
+        if propagation_ranks.is_empty() {
+            propagation_ranks.push(Vec::new());
+        }
+        self.propagation_ranks = propagation_ranks;
+        self.set_neighbor_lists(self.neighbor_lists());
+    }
+    
+    /// Get every AS reachable from `asn` by following customer links, excluding `asn` itself
+    pub fn customer_cone_asns(&self, asn: &ASN) -> HashSet<ASN> {
+        let mut cone = HashSet::new();
+        let mut stack: Vec<&AS> = self.get(asn).into_iter().collect();
+        while let Some(as_obj) = stack.pop() {
+            for customer in as_obj.customers() {
+                if customer.asn != *asn && cone.insert(customer.asn) {
+                    stack.push(self.resolve(customer));
+                }
+            }
+        }
+        cone
+    }
+    
+    /// Compute the customer cone size of every AS
+    /// Uses an explicit stack rather than recursion, since CAIDA graphs are deep enough to overflow it
+    pub fn assign_customer_cone_sizes(&mut self) {
+        // visited[i] == root + 1 marks AS i as already counted for the current root
+        let mut visited = vec![0usize; self.ases.len()];
+        let mut stack = Vec::new();
+        let mut sizes = Vec::with_capacity(self.ases.len());
+        
+        for root in 0..self.ases.len() {
+            let mark = root + 1;
+            visited[root] = mark;
+            stack.push(root);
+            let mut size = 0;
+            while let Some(index) = stack.pop() {
+                for customer in self.ases[index].customers() {
+                    let customer_index = customer.index as usize;
+                    if visited[customer_index] != mark {
+                        visited[customer_index] = mark;
+                        size += 1;
+                        stack.push(customer_index);
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+        
+        for (as_obj, size) in self.ases.iter_mut().zip(sizes) {
+            as_obj.customer_cone_size = size;
+        }
+    }
+    
+    /// Get the `n` ASNs with the largest customer cones, largest first (ties broken by ASN)
+    pub fn top_asns_by_customer_cone(&self, n: usize) -> Vec<ASN> {
+        let mut ases: Vec<&AS> = self.ases.iter().collect();
+        ases.sort_by(|a, b| b.customer_cone_size.cmp(&a.customer_cone_size).then(a.asn.cmp(&b.asn)));
+        ases.into_iter().take(n).map(|as_obj| as_obj.asn).collect()
+    }
+    
+    /// Country, RIR and organization of an AS, if known
+    pub fn metadata(&self, asn: &ASN) -> Option<&ASMetadata> {
+        self.metadata.get(asn)
+    }
+    
+    /// Attach metadata to an AS in the graph, replacing any it had
+    pub fn set_metadata(&mut self, asn: ASN, metadata: ASMetadata) -> Result<(), String> {
+        if !self.contains(&asn) {
+            return Err(format!("AS {} is not in the graph", asn));
+        }
+        self.metadata.insert(asn, metadata);
+        Ok(())
+    }
+    
+    /// Attach metadata to every AS in the graph that has some, skipping the rest
+    /// Returns how many ASes got metadata
+    pub fn add_metadata(&mut self, metadata: impl IntoIterator<Item = (ASN, ASMetadata)>) -> usize {
+        let mut num_added = 0;
+        for (asn, as_metadata) in metadata {
+            if self.contains(&asn) {
+                self.metadata.insert(asn, as_metadata);
+                num_added += 1;
+            }
+        }
+        num_added
+    }
+    
+    /// ASes whose organization is in `country` (ISO 3166 alpha-2, any case)
+    pub fn asns_in_country(&self, country: &str) -> HashSet<ASN> {
+        self.metadata.iter()
+            .filter(|(_, metadata)| metadata.country.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(country)))
+            .map(|(&asn, _)| asn)
+            .collect()
+    }
+    
+    pub fn asns_in_rir(&self, rir: RIR) -> HashSet<ASN> {
+        self.metadata.iter()
+            .filter(|(_, metadata)| metadata.rir == Some(rir))
+            .map(|(&asn, _)| asn)
+            .collect()
+    }
+    
+    /// ASes run by the organization with CAIDA ID `org_id`, i.e. its siblings
+    pub fn asns_in_org(&self, org_id: &str) -> HashSet<ASN> {
+        self.metadata.iter()
+            .filter(|(_, metadata)| metadata.org_id.as_deref() == Some(org_id))
+            .map(|(&asn, _)| asn)
+            .collect()
+    }
+    
+    /// Graph of only `asns` and the relationships among them, for quick experiments on part of a large graph
+    ///
+    /// Tier-1/IXP flags and metadata carry over and ranks and customer cones are recomputed, so an AS
+    /// whose providers were all dropped ranks like a tier-1. ASNs not in this graph are ignored
+    pub fn subgraph(&self, asns: &HashSet<ASN>) -> ASGraph {
+        let asn_list = |refs: &[ASRef]| refs.iter().map(|as_ref| as_ref.asn).collect();
+        // Neighbors outside `asns` have no builder, so `build` drops them
+        let builders = self.ases.iter()
+            .filter(|as_obj| asns.contains(&as_obj.asn))
+            .map(|as_obj| ASBuilder {
+                asn: as_obj.asn,
+                peer_asns: asn_list(as_obj.peers()),
+                provider_asns: asn_list(as_obj.providers()),
+                customer_asns: asn_list(as_obj.customers()),
+                tier_1: as_obj.tier_1,
+                ixp: as_obj.ixp,
+            })
+            .collect();
+        
+        let mut subgraph = ASGraph::build(builders);
+        subgraph.metadata = self.metadata.iter()
+            .filter(|(asn, _)| asns.contains(asn))
+            .map(|(&asn, metadata)| (asn, metadata.clone()))
+            .collect();
+        subgraph.assign_as_propagation_rank();
+        subgraph
+    }
+    
+    /// Subgraph of every AS within `radius` hops of `asns`, following relationships of any kind
+    pub fn subgraph_around(&self, asns: &HashSet<ASN>, radius: usize) -> ASGraph {
+        let mut kept: HashSet<ASN> = asns.iter().copied().filter(|asn| self.contains(asn)).collect();
+        let mut frontier: Vec<ASN> = kept.iter().copied().collect();
+        for _ in 0..radius {
+            frontier = frontier.iter()
+                .flat_map(|asn| self[self.asn_to_index[asn]].neighbors())
+                .filter(|neighbor| kept.insert(neighbor.asn))
+                .map(|neighbor| neighbor.asn)
+                .collect();
+            if frontier.is_empty() {
+                break;
+            }
+        }
+        self.subgraph(&kept)
+    }
+    
+    /// Connected subgraph of up to `num_ases` ASes, grown from a random AS by adding random neighbors
+    /// The same seed always picks the same ASes. The result is smaller when the start AS's component is
+    pub fn random_subgraph(&self, num_ases: usize, seed: u64) -> ASGraph {
+        if self.ases.is_empty() || num_ases == 0 {
+            return self.subgraph(&HashSet::new());
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let start = &self.ases[rng.gen_range(0..self.ases.len())];
+        let mut discovered = HashSet::from([start.asn]);
+        let mut frontier = vec![start.index];
+        let mut kept = HashSet::new();
+        
+        while kept.len() < num_ases && !frontier.is_empty() {
+            let index = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+            let as_obj = &self[index];
+            kept.insert(as_obj.asn);
+            for neighbor in as_obj.neighbors() {
+                if discovered.insert(neighbor.asn) {
+                    frontier.push(neighbor.index);
+                }
+            }
+        }
+        self.subgraph(&kept)
+    }
+    
+    /// Add ASN groups (for CommonASNs)
+    pub fn add_asn_groups(&mut self) {
+        // This would add special ASNs like VICTIM, ATTACKER etc.
+        // For now, we'll skip this as it's handled separately
+    }
+    
+    /// Add an AS from a builder (used for incremental construction)
+    /// Its relationships are resolved by `establish_relationships` once all builders are added
+    pub fn add_as_from_builder(&mut self, builder: ASBuilder) {
+        let index = self.ases.len() as ASIndex;
+        
+        // Create AS object
+        let as_obj = AS {
+            asn: builder.asn,
+            index,
+            // Will be populated in establish_relationships
+            adjacency: Arc::from([]),
+            neighbor_offsets: [0; 4],
+            tier_1: builder.tier_1,
+            ixp: builder.ixp,
+            propagation_rank: None,
+            provider_cone_asns: HashSet::new(),
+            customer_cone_size: 0,
+        };
+        
+        self.ases.push(as_obj);
+        self.asn_to_index.insert(builder.asn, index);
+        self.pending_builders.push(builder);
+    }
+    
+    /// Establish relationships between ASes after all have been added
+    pub fn establish_relationships(&mut self) {
+        self.resolve_pending_builders();
+        self.assign_as_propagation_rank();
+    }
+    
+    /// Add a single AS to an existing graph, linking it to the neighbors that already exist
+    /// Links are added on both sides and propagation ranks are recomputed
+    pub fn add_as(&mut self, builder: ASBuilder) -> Result<(), String> {
+        if self.contains(&builder.asn) {
+            return Err(format!("AS {} is already in the graph", builder.asn));
+        }
+        
+        let asn = builder.asn;
+        let links: Vec<(ASN, Relationships)> = builder.peer_asns.iter().map(|&n| (n, Relationships::Peers))
+            .chain(builder.provider_asns.iter().map(|&n| (n, Relationships::Providers)))
+            .chain(builder.customer_asns.iter().map(|&n| (n, Relationships::Customers)))
+            .filter(|(neighbor_asn, _)| self.contains(neighbor_asn))
+            .collect();
+        
+        self.add_as_from_builder(ASBuilder {
+            peer_asns: NeighborASNs::new(),
+            provider_asns: NeighborASNs::new(),
+            customer_asns: NeighborASNs::new(),
+            ..builder
+        });
+        self.resolve_pending_builders();
+        
+        let mut lists = self.neighbor_lists();
+        for (neighbor_asn, relationship) in links {
+            self.link(&mut lists, asn, neighbor_asn, relationship)?;
+        }
+        self.set_neighbor_lists(lists);
+        
+        self.assign_customer_cone_sizes();
+        self.assign_as_propagation_rank();
+        Ok(())
+    }
+    
+    /// Remove an AS and every link to it, returning the removed AS
+    pub fn remove_as(&mut self, asn: &ASN) -> Option<AS> {
+        let index = self.asn_to_index.remove(asn)?;
+        let removed = self.ases.swap_remove(index as usize);
+        self.metadata.remove(asn);
+        
+        // The last AS was moved into the freed slot, so its handles must be remapped
+        let moved_from = self.ases.len() as ASIndex;
+        if index != moved_from {
+            let moved = &mut self.ases[index as usize];
+            moved.index = index;
+            self.asn_to_index.insert(moved.asn, index);
+        }
+        
+        let mut lists = self.neighbor_lists();
+        for neighbors in lists.iter_mut().flatten() {
+            neighbors.retain(|n| n.asn != *asn);
+            for neighbor in neighbors.iter_mut() {
+                if neighbor.index == moved_from {
+                    neighbor.index = index;
+                }
+            }
+        }
+        self.set_neighbor_lists(lists);
+        
+        self.assign_customer_cone_sizes();
+        self.assign_as_propagation_rank();
+        Some(removed)
+    }
+    
+    /// Add (or change) the relationship between two existing ASes
+    /// `relationship` is the role of `neighbor_asn` from the point of view of `asn`,
+    /// e.g. `Relationships::Providers` makes `neighbor_asn` a provider of `asn`
+    pub fn add_relationship(&mut self, asn: ASN, neighbor_asn: ASN, relationship: Relationships) -> Result<(), String> {
+        let mut lists = self.neighbor_lists();
+        self.link(&mut lists, asn, neighbor_asn, relationship)?;
+        self.set_neighbor_lists(lists);
+        self.assign_customer_cone_sizes();
+        self.assign_as_propagation_rank();
+        Ok(())
+    }
+    
+    /// Remove the relationship between two ASes, returning whether one existed
+    pub fn remove_relationship(&mut self, asn: ASN, neighbor_asn: ASN) -> bool {
+        let mut lists = self.neighbor_lists();
+        let removed = self.unlink(&mut lists, asn, neighbor_asn);
+        if removed {
+            self.set_neighbor_lists(lists);
+            self.assign_customer_cone_sizes();
+            self.assign_as_propagation_rank();
+        }
+        removed
+    }
+
+    /// Serialize the fully built graph, including tier-1/IXP flags, ranks and provider cones
+    pub fn to_json(&self) -> serde_json::Value {
+        let asns = |refs: &[ASRef]| refs.iter().map(|as_ref| as_ref.asn).collect::<Vec<ASN>>();
+        let ases: Vec<serde_json::Value> = self.ases.iter().map(|as_obj| {
+            let mut provider_cone_asns: Vec<ASN> = as_obj.provider_cone_asns.iter().copied().collect();
+            provider_cone_asns.sort_unstable();
+            serde_json::json!({
+                "asn": as_obj.asn,
+                "peers": asns(as_obj.peers()),
+                "providers": asns(as_obj.providers()),
+                "customers": asns(as_obj.customers()),
+                "tier_1": as_obj.tier_1,
+                "ixp": as_obj.ixp,
+                "provider_cone_asns": provider_cone_asns,
+                "propagation_rank": as_obj.propagation_rank,
+            })
+        }).collect();
+
+        serde_json::json!({
+            "ases": ases,
+            "propagation_ranks": self.propagation_ranks,
+        })
+    }
+
+    /// Load a graph written by `to_json` without recomputing ranks
+    /// ASes keep their original order, so indices match the serialized graph
+    pub fn from_json(value: &serde_json::Value) -> Result<ASGraph, String> {
+        fn field<'v>(value: &'v serde_json::Value, key: &str) -> Result<&'v serde_json::Value, String> {
+            value.get(key).ok_or_else(|| format!("AS graph JSON is missing {}", key))
+        }
+        fn asn_list(value: &serde_json::Value, key: &str) -> Result<Vec<ASN>, String> {
+            serde_json::from_value(field(value, key)?.clone()).map_err(|e| format!("Invalid {}: {}", key, e))
+        }
+
+        let as_values = field(value, "ases")?.as_array().ok_or("AS graph JSON ases is not a list")?;
+        let mut builders = Vec::with_capacity(as_values.len());
+        let mut extras = Vec::with_capacity(as_values.len());
+        for as_value in as_values {
+            let asn: ASN = serde_json::from_value(field(as_value, "asn")?.clone())
+                .map_err(|e| format!("Invalid asn: {}", e))?;
+            let mut builder = ASBuilder::new(asn)
+                .with_peers(asn_list(as_value, "peers")?)
+                .with_providers(asn_list(as_value, "providers")?)
+                .with_customers(asn_list(as_value, "customers")?);
+            builder.tier_1 = field(as_value, "tier_1")?.as_bool().unwrap_or(false);
+            builder.ixp = field(as_value, "ixp")?.as_bool().unwrap_or(false);
+            builders.push(builder);
+
+            let propagation_rank: Option<u32> = serde_json::from_value(field(as_value, "propagation_rank")?.clone())
+                .map_err(|e| format!("Invalid propagation_rank for AS {}: {}", asn, e))?;
+            extras.push((asn_list(as_value, "provider_cone_asns")?, propagation_rank));
+        }
+
+        let mut as_graph = ASGraph::build(builders);
+        if as_graph.asn_to_index.len() != as_graph.len() {
+            return Err("AS graph JSON has duplicate ASNs".to_string());
+        }
+        for (as_obj, (provider_cone_asns, propagation_rank)) in as_graph.ases.iter_mut().zip(extras) {
+            as_obj.provider_cone_asns = provider_cone_asns.into_iter().collect();
+            as_obj.propagation_rank = propagation_rank;
+        }
+        as_graph.propagation_ranks = serde_json::from_value(field(value, "propagation_ranks")?.clone())
+            .map_err(|e| format!("Invalid propagation_ranks: {}", e))?;
+        as_graph.set_neighbor_lists(as_graph.neighbor_lists());
+        Ok(as_graph)
+    }
+
+    /// Link two ASes in `lists`, which the caller lays out afterwards
+    fn link(&self, lists: &mut [NeighborLists], asn: ASN, neighbor_asn: ASN, relationship: Relationships) -> Result<(), String> {
+        if asn == neighbor_asn {
+            return Err(format!("AS {} cannot have a relationship with itself", asn));
+        }
+        if !matches!(relationship, Relationships::Peers | Relationships::Providers | Relationships::Customers) {
+            return Err(format!("{} is not a relationship between two ASes", relationship));
+        }
+        let index = *self.asn_to_index.get(&asn).ok_or_else(|| format!("AS {} is not in the graph", asn))?;
+        let neighbor_index = *self.asn_to_index.get(&neighbor_asn)
+            .ok_or_else(|| format!("AS {} is not in the graph", neighbor_asn))?;
+        
+        // An AS pair has at most one relationship
+        self.unlink(lists, asn, neighbor_asn);
+        
+        Self::neighbors_mut(&mut lists[index as usize], relationship)
+            .push(ASRef { asn: neighbor_asn, index: neighbor_index });
+        Self::neighbors_mut(&mut lists[neighbor_index as usize], relationship.invert())
+            .push(ASRef { asn, index });
+        Ok(())
+    }
+    
+    /// Unlink two ASes in `lists`, which the caller lays out afterwards
+    fn unlink(&self, lists: &mut [NeighborLists], asn: ASN, neighbor_asn: ASN) -> bool {
+        let mut removed = false;
+        for (a, b) in [(asn, neighbor_asn), (neighbor_asn, asn)] {
+            if let Some(&index) = self.asn_to_index.get(&a) {
+                for neighbors in lists[index as usize].iter_mut() {
+                    let len_before = neighbors.len();
+                    neighbors.retain(|n| n.asn != b);
+                    removed |= neighbors.len() != len_before;
+                }
+            }
+        }
+        removed
+    }
+    
+    fn neighbors_mut(lists: &mut NeighborLists, relationship: Relationships) -> &mut NeighborRefs {
+        match relationship {
+            Relationships::Peers => &mut lists[0],
+            Relationships::Providers => &mut lists[1],
+            _ => &mut lists[2],
+        }
+    }
+}
+
+impl std::ops::Index<ASIndex> for ASGraph {
+    type Output = AS;
+
+    fn index(&self, index: ASIndex) -> &AS {
+        self.get_by_index(index)
+    }
+}
+
+impl ASBuilder {
+    pub fn new(asn: ASN) -> Self {
+        ASBuilder {
+            asn,
+            peer_asns: NeighborASNs::new(),
+            provider_asns: NeighborASNs::new(),
+            customer_asns: NeighborASNs::new(),
+            tier_1: false,
+            ixp: false,
+        }
+    }
+    
+    pub fn with_peers(mut self, peers: Vec<ASN>) -> Self {
+        self.peer_asns = peers.into();
+        self
+    }
+    
+    pub fn with_providers(mut self, providers: Vec<ASN>) -> Self {
+        self.provider_asns = providers.into();
+        self
+    }
+    
+    pub fn with_customers(mut self, customers: Vec<ASN>) -> Self {
+        self.customer_asns = customers.into();
+        self
+    }
+    
+    pub fn as_tier_1(mut self) -> Self {
+        self.tier_1 = true;
+        self
+    }
+    
+    pub fn from_asn_sets(
+        asn: ASN,
+        peers: HashSet<ASN>,
+        providers: HashSet<ASN>,
+        customers: HashSet<ASN>,
+    ) -> Self {
+        ASBuilder {
+            asn,
+            peer_asns: peers.into_iter().collect(),
+            provider_asns: providers.into_iter().collect(),
+            customer_asns: customers.into_iter().collect(),
+            tier_1: false,
+            ixp: false,
+        }
+    }
+}
+
+impl AS {
+    pub fn peers(&self) -> &[ASRef] {
+        self.neighbor_slice(0)
+    }
+    
+    pub fn providers(&self) -> &[ASRef] {
+        self.neighbor_slice(1)
+    }
+    
+    pub fn customers(&self) -> &[ASRef] {
+        self.neighbor_slice(2)
+    }
+    
+    fn neighbor_slice(&self, list: usize) -> &[ASRef] {
+        &self.adjacency[self.neighbor_offsets[list] as usize..self.neighbor_offsets[list + 1] as usize]
+    }
+    
+    /// Get handles to all neighbor ASes (peers + providers + customers)
+    pub fn neighbors(&self) -> impl Iterator<Item = &ASRef> {
+        self.adjacency[self.neighbor_offsets[0] as usize..self.neighbor_offsets[3] as usize].iter()
+    }
+    
+    /// Number of ASes in this AS's customer cone, excluding itself
+    pub fn customer_cone_size(&self) -> usize {
+        self.customer_cone_size
+    }
+    
+    /// Check if this AS is a stub (no customers)
+    pub fn is_stub(&self) -> bool {
+        self.customers().is_empty()
+    }
+    
+    /// Get neighbors of a specific relationship type
+    pub fn get_neighbors(&self, relationship: crate::shared::Relationships) -> &[ASRef] {
+        match relationship {
+            crate::shared::Relationships::Customers => self.customers(),
+            crate::shared::Relationships::Peers => self.peers(),
+            crate::shared::Relationships::Providers => self.providers(),
+            _ => &[],
+        }
+    }
+}
+impl std::fmt::Debug for AS {
+    /// Shows this AS's neighbors rather than the whole shared arena
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AS")
+            .field("asn", &self.asn)
+            .field("index", &self.index)
+            .field("peers", &self.peers())
+            .field("providers", &self.providers())
+            .field("customers", &self.customers())
+            .field("tier_1", &self.tier_1)
+            .field("ixp", &self.ixp)
+            .field("provider_cone_asns", &self.provider_cone_asns)
+            .field("propagation_rank", &self.propagation_rank)
+            .field("customer_cone_size", &self.customer_cone_size)
+            .finish()
+    }
+}
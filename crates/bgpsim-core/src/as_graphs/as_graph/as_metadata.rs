@@ -0,0 +1,85 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Regional Internet Registry an AS was delegated by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RIR {
+    Afrinic,
+    Apnic,
+    Arin,
+    Lacnic,
+    Ripe,
+}
+
+impl RIR {
+    pub const ALL: [RIR; 5] = [RIR::Afrinic, RIR::Apnic, RIR::Arin, RIR::Lacnic, RIR::Ripe];
+}
+
+impl FromStr for RIR {
+    type Err = String;
+
+    /// Accepts the names CAIDA's AS-to-organization data uses, e.g. `ARIN` or `RIPE NCC`, in any case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "AFRINIC" => Ok(RIR::Afrinic),
+            "APNIC" => Ok(RIR::Apnic),
+            "ARIN" => Ok(RIR::Arin),
+            "LACNIC" => Ok(RIR::Lacnic),
+            "RIPE" | "RIPE NCC" | "RIPENCC" => Ok(RIR::Ripe),
+            _ => Err(format!("Unknown RIR: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for RIR {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            RIR::Afrinic => "AFRINIC",
+            RIR::Apnic => "APNIC",
+            RIR::Arin => "ARIN",
+            RIR::Lacnic => "LACNIC",
+            RIR::Ripe => "RIPE",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Who operates an AS and where, e.g. from CAIDA's AS-to-organization dataset
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ASMetadata {
+    /// ISO 3166 alpha-2 code of the organization's country, upper case
+    pub country: Option<String>,
+    pub rir: Option<RIR>,
+    pub org_id: Option<String>,
+    pub org_name: Option<String>,
+}
+
+impl ASMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_country(mut self, country: &str) -> Self {
+        self.country = Some(country.to_ascii_uppercase());
+        self
+    }
+
+    pub fn with_rir(mut self, rir: RIR) -> Self {
+        self.rir = Some(rir);
+        self
+    }
+
+    pub fn with_org(mut self, org_id: String, org_name: Option<String>) -> Self {
+        self.org_id = Some(org_id);
+        self.org_name = org_name;
+        self
+    }
+}
+
+/// Country code as stored in `ASNGroups::Country`, e.g. `country_code("us") == Some(*b"US")`
+pub fn country_code(country: &str) -> Option<[u8; 2]> {
+    match country.trim().to_ascii_uppercase().as_bytes() {
+        &[a, b] if a.is_ascii_alphabetic() && b.is_ascii_alphabetic() => Some([a, b]),
+        _ => None,
+    }
+}
@@ -0,0 +1,5 @@
+pub mod as_graph;
+pub mod as_metadata;
+
+pub use as_graph::{AS, ASGraph, ASBuilder, ASIndex, ASRef, ASN, NeighborASNs};
+pub use as_metadata::{ASMetadata, RIR};
\ No newline at end of file
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::shared::{ASNGroups, Relationships};
+use crate::simulation_engine::{Announcement, PolicyStorageMode, Prefix, SimulationEngine};
+
+use super::as_graph::{ASGraph, ASN};
+
+/// Summary statistics of an AS graph, for sanity-checking input graphs before simulating on them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphMetrics {
+    pub num_ases: usize,
+    pub num_customer_provider_links: usize,
+    pub num_peer_links: usize,
+    pub num_tier_1: usize,
+    /// Counted as `ASNGroups` counts them, so multihomed stubs aren't stubs
+    pub num_stubs: usize,
+    pub num_multihomed: usize,
+    pub num_transit: usize,
+    /// Number of ASes by number of neighbors
+    pub degree_distribution: BTreeMap<usize, usize>,
+    /// Number of ASes by customer cone size, excluding the AS itself
+    pub customer_cone_distribution: BTreeMap<usize, usize>,
+    /// Number of ASes at each propagation rank, empty if ranks weren't assigned
+    pub rank_distribution: Vec<usize>,
+    /// Number of (AS, origin) pairs by AS hops to the origin, see `sample_path_lengths`
+    pub path_length_distribution: BTreeMap<usize, usize>,
+    /// Origins propagated from for `path_length_distribution`
+    pub num_path_origins: usize,
+}
+
+impl GraphMetrics {
+    /// Everything but the path lengths, which need propagation
+    pub fn compute(as_graph: &ASGraph) -> Self {
+        let mut metrics = GraphMetrics {
+            num_ases: as_graph.len(),
+            num_tier_1: ASNGroups::Tier1.asns(as_graph).len(),
+            num_stubs: ASNGroups::Stubs.asns(as_graph).len(),
+            num_multihomed: ASNGroups::Multihomed.asns(as_graph).len(),
+            num_transit: ASNGroups::Transit.asns(as_graph).len(),
+            rank_distribution: as_graph.propagation_ranks.iter().map(Vec::len).collect(),
+            ..Default::default()
+        };
+        for as_obj in as_graph.iter() {
+            // Each link is listed by both ends
+            metrics.num_customer_provider_links += as_obj.customers().len();
+            metrics.num_peer_links += as_obj.peers().len();
+            *metrics.degree_distribution.entry(as_obj.neighbors().count()).or_default() += 1;
+            *metrics.customer_cone_distribution.entry(as_obj.customer_cone_size()).or_default() += 1;
+        }
+        metrics.num_peer_links /= 2;
+        metrics
+    }
+
+    /// Propagate a prefix from each of up to `num_origins` random ASes and record how far every AS
+    /// that selected it is from the origin. The same seed always picks the same origins
+    ///
+    /// Needs propagation ranks, see `ASGraph::assign_as_propagation_rank`
+    pub fn sample_path_lengths(&mut self, as_graph: &ASGraph, num_origins: usize, seed: u64) {
+        let mut asns: Vec<ASN> = as_graph.asns().collect();
+        asns.sort_unstable();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let origins: Vec<ASN> = asns.choose_multiple(&mut rng, num_origins).copied().collect();
+
+        let prefix: Prefix = "1.2.0.0/16".parse().unwrap();
+        let mut engine = SimulationEngine::new(as_graph).with_storage_mode(PolicyStorageMode::LocalRibOnly);
+        for &origin in &origins {
+            engine.setup(vec![(origin, Announcement::new(prefix, origin, Relationships::Origin))]);
+            // A single fresh announcement converges in one round
+            engine.run(1);
+            for (&asn, policy) in engine.policy_store.iter() {
+                if asn == origin {
+                    continue;
+                }
+                if let Some(ann) = policy.local_rib.get(&prefix) {
+                    *self.path_length_distribution.entry(ann.as_path.len() - 1).or_default() += 1;
+                }
+            }
+        }
+        self.num_path_origins += origins.len();
+    }
+
+    pub fn mean_degree(&self) -> f64 {
+        mean(&self.degree_distribution)
+    }
+
+    /// None until path lengths are sampled
+    pub fn mean_path_length(&self) -> Option<f64> {
+        (!self.path_length_distribution.is_empty()).then(|| mean(&self.path_length_distribution))
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let distribution = |counts: &BTreeMap<usize, usize>| -> serde_json::Map<String, serde_json::Value> {
+            counts.iter().map(|(value, count)| (value.to_string(), serde_json::json!(count))).collect()
+        };
+        serde_json::json!({
+            "num_ases": self.num_ases,
+            "num_customer_provider_links": self.num_customer_provider_links,
+            "num_peer_links": self.num_peer_links,
+            "num_tier_1": self.num_tier_1,
+            "num_stubs": self.num_stubs,
+            "num_multihomed": self.num_multihomed,
+            "num_transit": self.num_transit,
+            "mean_degree": self.mean_degree(),
+            "degree_distribution": distribution(&self.degree_distribution),
+            "customer_cone_distribution": distribution(&self.customer_cone_distribution),
+            "rank_distribution": self.rank_distribution,
+            "num_path_origins": self.num_path_origins,
+            "mean_path_length": self.mean_path_length(),
+            "path_length_distribution": distribution(&self.path_length_distribution),
+        })
+    }
+}
+
+/// Mean value of a value-to-count distribution, 0 when empty
+fn mean(counts: &BTreeMap<usize, usize>) -> f64 {
+    let total: usize = counts.values().sum();
+    let sum: usize = counts.iter().map(|(value, count)| value * count).sum();
+    sum as f64 / total.max(1) as f64
+}
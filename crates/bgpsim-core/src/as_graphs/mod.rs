@@ -0,0 +1,5 @@
+pub mod as_graph;
+pub mod metrics;
+
+pub use as_graph::{AS, ASGraph, ASBuilder, ASIndex, ASMetadata, ASRef, ASN, RIR};
+pub use metrics::GraphMetrics;
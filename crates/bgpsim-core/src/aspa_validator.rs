@@ -0,0 +1,119 @@
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::as_graphs::as_graph::{ASGraph, ASN};
+
+/// Outcome of checking whether one AS attests another as its provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProviderAuth {
+    /// The customer published no ASPA
+    NoAttestation,
+    ProviderPlus,
+    NotProviderPlus,
+}
+
+/// ASPA objects, i.e. the provider set each customer AS attests
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AspaValidator {
+    attestations: HashMap<ASN, HashSet<ASN>>,
+}
+
+impl AspaValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attestations of each AS in `asns` listing its providers in `as_graph`, as ASPA adopters would publish
+    pub fn from_as_graph(as_graph: &ASGraph, asns: impl IntoIterator<Item = ASN>) -> Self {
+        let mut validator = Self::new();
+        for asn in asns {
+            if let Some(as_obj) = as_graph.get(&asn) {
+                validator.add_attestation(asn, as_obj.providers().iter().map(|provider| provider.asn));
+            }
+        }
+        validator
+    }
+
+    /// Add providers to `customer`'s attestation, creating it if needed
+    /// An attestation with no providers (AS0) means the customer has none
+    pub fn add_attestation(&mut self, customer: ASN, providers: impl IntoIterator<Item = ASN>) {
+        let entry = self.attestations.entry(customer).or_default();
+        entry.extend(providers.into_iter().filter(|&provider| provider != 0));
+    }
+
+    /// Remove `customer`'s attestation, returning whether it had one
+    pub fn remove_attestation(&mut self, customer: ASN) -> bool {
+        self.attestations.remove(&customer).is_some()
+    }
+
+    pub fn providers(&self, customer: ASN) -> Option<&HashSet<ASN>> {
+        self.attestations.get(&customer)
+    }
+
+    pub fn provider_auth(&self, customer: ASN, provider: ASN) -> ProviderAuth {
+        match self.attestations.get(&customer) {
+            None => ProviderAuth::NoAttestation,
+            Some(providers) if providers.contains(&provider) => ProviderAuth::ProviderPlus,
+            Some(_) => ProviderAuth::NotProviderPlus,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.attestations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attestations.is_empty()
+    }
+
+    /// Parse the `aspas` list of an rpki-client or Routinator JSON export
+    ///
+    /// Customers are read from `customer_asid` or `customer`, and providers from `providers` or
+    /// the older `provider_set`, as numbers, `"AS64496"` strings or `{"asid": ...}` objects
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        let aspas = value["aspas"].as_array().ok_or("ASPA JSON is missing the aspas list")?;
+        let mut validator = Self::new();
+        for aspa in aspas {
+            let customer = ["customer_asid", "customer"].iter()
+                .find_map(|key| aspa.get(*key))
+                .ok_or_else(|| format!("ASPA without a customer: {}", aspa))
+                .and_then(parse_asn)?;
+            let providers = ["providers", "provider_set"].iter()
+                .find_map(|key| aspa.get(*key))
+                .and_then(serde_json::Value::as_array)
+                .ok_or_else(|| format!("ASPA for AS {} without providers", customer))?
+                .iter()
+                .map(parse_asn)
+                .collect::<Result<Vec<ASN>, String>>()?;
+            validator.add_attestation(customer, providers);
+        }
+        Ok(validator)
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let value: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+        Self::from_json(&value)
+    }
+}
+
+fn parse_asn(value: &serde_json::Value) -> Result<ASN, String> {
+    let asn = match value {
+        serde_json::Value::Number(number) => number.as_u64().and_then(|asn| ASN::try_from(asn).ok()),
+        serde_json::Value::String(s) => s.trim_start_matches("AS").parse().ok(),
+        serde_json::Value::Object(object) => {
+            return object.get("asid")
+                .or_else(|| object.get("provider_asid"))
+                .ok_or_else(|| format!("Invalid ASN in ASPA JSON: {}", value))
+                .and_then(parse_asn);
+        }
+        _ => None,
+    };
+    asn.ok_or_else(|| format!("Invalid ASN in ASPA JSON: {}", value))
+}
@@ -0,0 +1,42 @@
+use crate::as_graphs::as_graph::ASN;
+
+/// What can go wrong loading data, configuring runs and running them, shared by the workspace crates
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Reading or writing a file failed
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Fetching a dataset failed, after retrying transient failures
+    #[error("Download failed: {0}")]
+    Download(String),
+
+    /// A file, dataset or JSON document is malformed
+    #[error("Parse error: {0}")]
+    Parse(String),
+
+    /// The AS graph has provider cycles, each listed by its ASes; a self-loop is a cycle of one
+    #[error("Provider cycles between ASes: {0:?}")]
+    GraphCycle(Vec<Vec<ASN>>),
+
+    /// A config is inconsistent with itself or with the AS graph it runs on
+    #[error("Invalid config: {0}")]
+    ConfigInvalid(String),
+
+    /// No scenario is registered under the name
+    #[error("Unknown scenario: {0}")]
+    ScenarioUnknown(String),
+
+    /// Propagation stopped early, e.g. a guarded policy broke its limits
+    #[error("Propagation failed: {0}")]
+    Propagation(String),
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        match e.classify() {
+            serde_json::error::Category::Io => Error::Io(e.into()),
+            _ => Error::Parse(e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "fs")]
+use std::fs;
+use std::path::PathBuf;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use ipnetwork::IpNetwork;
+
+use crate::as_graphs::as_graph::ASN;
+
+/// Prefix-origin pairs registered as IRR `route`/`route6` objects, e.g. from a RADB or RIR database dump
+///
+/// Unlike ROAs, route objects have no max length: like the prefix lists `bgpq4` builds from them,
+/// an announcement is only covered by an object for exactly its prefix and origin
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IrrValidator {
+    route_objects: HashMap<IpNetwork, HashSet<ASN>>,
+    /// File the route objects were loaded from, written back by `ScenarioConfig::to_json`
+    pub source: Option<PathBuf>,
+}
+
+impl IrrValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_route_object(&mut self, prefix: IpNetwork, origin: ASN) {
+        self.route_objects.entry(prefix).or_default().insert(origin);
+    }
+
+    /// Remove the route object, returning whether it was registered
+    pub fn remove_route_object(&mut self, prefix: &IpNetwork, origin: ASN) -> bool {
+        let Some(origins) = self.route_objects.get_mut(prefix) else {
+            return false;
+        };
+        let removed = origins.remove(&origin);
+        if origins.is_empty() {
+            self.route_objects.remove(prefix);
+        }
+        removed
+    }
+
+    /// Whether a route object registers `origin` for `prefix`
+    pub fn is_covered(&self, prefix: &IpNetwork, origin: ASN) -> bool {
+        self.route_objects.get(prefix).is_some_and(|origins| origins.contains(&origin))
+    }
+
+    /// Origins registered for exactly `prefix`
+    pub fn origins(&self, prefix: &IpNetwork) -> Option<&HashSet<ASN>> {
+        self.route_objects.get(prefix)
+    }
+
+    /// Number of route objects
+    pub fn len(&self) -> usize {
+        self.route_objects.values().map(HashSet::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.route_objects.is_empty()
+    }
+
+    /// Parse the `route` and `route6` objects of an RPSL database dump, ignoring every other class
+    ///
+    /// Objects are separated by blank lines; `%` and `#` comments and attributes other than the
+    /// prefix and `origin` are skipped. An object with several `origin`s registers each of them
+    pub fn from_rpsl(contents: &str) -> Result<Self, String> {
+        let mut validator = Self::new();
+        let mut prefix: Option<IpNetwork> = None;
+        let mut origins: Vec<ASN> = Vec::new();
+        let mut in_route_object = false;
+        let mut num_attributes = 0;
+        let lines = contents.lines().map(Some).chain(std::iter::once(None));
+        for (line_num, line) in lines.enumerate() {
+            let line = line.map(str::trim_end).filter(|line| !line.is_empty());
+            let Some(line) = line else {
+                // End of an object
+                if in_route_object {
+                    let prefix = prefix.take().ok_or_else(|| format!("Line {}: route object without a prefix", line_num))?;
+                    if origins.is_empty() {
+                        return Err(format!("Line {}: route object for {} without an origin", line_num, prefix));
+                    }
+                    for origin in origins.drain(..) {
+                        validator.add_route_object(prefix, origin);
+                    }
+                }
+                in_route_object = false;
+                num_attributes = 0;
+                continue;
+            };
+            if line.starts_with('%') || line.starts_with('#') {
+                continue;
+            }
+            // Continuation lines start with whitespace or `+`, and neither prefixes nor origins span lines
+            let Some((attribute, value)) = line.split_once(':').filter(|_| !line.starts_with([' ', '\t', '+'])) else {
+                continue;
+            };
+            let value = value.split('#').next().unwrap_or("").trim();
+            num_attributes += 1;
+            match attribute.trim().to_lowercase().as_str() {
+                // The first attribute names the object's class
+                "route" | "route6" if num_attributes == 1 => {
+                    in_route_object = true;
+                    prefix = Some(value.parse().map_err(|e| format!("Line {}: invalid prefix {}: {}", line_num + 1, value, e))?);
+                }
+                "origin" if in_route_object => {
+                    let origin = value.to_uppercase().trim_start_matches("AS").parse()
+                        .map_err(|_| format!("Line {}: invalid origin {}", line_num + 1, value))?;
+                    origins.push(origin);
+                }
+                _ => {}
+            }
+        }
+        Ok(validator)
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let mut validator = Self::from_rpsl(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+        validator.source = Some(path.to_path_buf());
+        Ok(validator)
+    }
+}
@@ -0,0 +1,16 @@
+//! AS graph, propagation engine and routing policies, without networking; file IO is behind the `fs` feature
+pub mod shared;
+pub mod as_graphs;
+pub mod simulation_engine;
+pub mod route_validator;
+pub mod aspa_validator;
+pub mod irr_validator;
+pub mod error;
+
+pub use as_graphs::as_graph::{AS, ASGraph, ASN};
+pub use simulation_engine::{SimulationEngine, PolicyStore, Announcement, Prefix};
+pub use shared::{CommonASNs, Outcomes, Relationships, Settings, Timestamps};
+pub use route_validator::{ROA, RouteValidator, TimedROA};
+pub use aspa_validator::{AspaValidator, ProviderAuth};
+pub use irr_validator::IrrValidator;
+pub use error::Error;
@@ -0,0 +1,702 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use lru::LruCache;
+use ipnetwork::IpNetwork;
+
+use crate::shared::{ROAValidity, ROARouted};
+use crate::as_graphs::as_graph::ASN;
+use crate::simulation_engine::prefix_table::{PrefixId, PrefixTable};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ROA {
+    pub prefix: IpNetwork,
+    pub origin: ASN,
+    pub max_length: u8,
+    pub ta: Option<String>,
+}
+
+impl ROA {
+    pub fn new(prefix: IpNetwork, origin: ASN, max_length: Option<u8>) -> Self {
+        let max_length = max_length.unwrap_or_else(|| prefix.prefix());
+        ROA {
+            prefix,
+            origin,
+            max_length,
+            ta: None,
+        }
+    }
+
+    pub fn with_ta(mut self, ta: String) -> Self {
+        self.ta = Some(ta);
+        self
+    }
+
+    pub fn is_routed(&self) -> bool {
+        self.origin != 0
+    }
+
+    pub fn is_non_routed(&self) -> bool {
+        self.origin == 0
+    }
+
+    pub fn covers_prefix(&self, prefix: &IpNetwork) -> bool {
+        match (self.prefix, prefix) {
+            (IpNetwork::V4(roa_net), IpNetwork::V4(prefix_net)) => {
+                roa_net.contains(prefix_net.ip()) && prefix_net.prefix() >= roa_net.prefix()
+            }
+            (IpNetwork::V6(roa_net), IpNetwork::V6(prefix_net)) => {
+                roa_net.contains(prefix_net.ip()) && prefix_net.prefix() >= roa_net.prefix()
+            }
+            _ => false, // IPv4 ROA doesn't cover IPv6 prefix and vice versa
+        }
+    }
+
+    pub fn get_validity(&self, prefix: &IpNetwork, origin: ASN) -> ROAValidity {
+        if !self.covers_prefix(prefix) {
+            return ROAValidity::Unknown;
+        }
+
+        let prefix_len = prefix.prefix();
+        let valid_length = prefix_len <= self.max_length;
+        let valid_origin = self.origin == origin;
+
+        match (valid_length, valid_origin) {
+            (true, true) => ROAValidity::Valid,
+            (false, true) => ROAValidity::InvalidLength,
+            (true, false) => ROAValidity::InvalidOrigin,
+            (false, false) => ROAValidity::InvalidLengthAndOrigin,
+        }
+    }
+
+    pub fn get_outcome(&self, prefix: &IpNetwork, origin: ASN) -> (ROAValidity, ROARouted) {
+        let validity = self.get_validity(prefix, origin);
+        let routed = if self.is_routed() {
+            ROARouted::Routed
+        } else {
+            ROARouted::NonRouted
+        };
+        (validity, routed)
+    }
+}
+
+/// ROA that is only published between two times, e.g. propagation rounds, for simulating RPKI churn
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TimedROA {
+    pub roa: ROA,
+    pub valid_from: u32,
+    /// First time the ROA is no longer published, None if it never expires
+    pub valid_until: Option<u32>,
+}
+
+impl TimedROA {
+    pub fn new(roa: ROA, valid_from: u32, valid_until: Option<u32>) -> Self {
+        TimedROA {
+            roa,
+            valid_from,
+            valid_until,
+        }
+    }
+
+    pub fn is_valid_at(&self, time: u32) -> bool {
+        time >= self.valid_from && self.valid_until.is_none_or(|until| time < until)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ROASNode {
+    pub prefix: Option<IpNetwork>,
+    pub roas: HashSet<ROA>,
+    pub left: Option<Box<ROASNode>>,
+    pub right: Option<Box<ROASNode>>,
+}
+
+impl ROASNode {
+    pub fn new() -> Self {
+        ROASNode {
+            prefix: None,
+            roas: HashSet::new(),
+            left: None,
+            right: None,
+        }
+    }
+}
+
+type Outcome = (ROAValidity, ROARouted);
+type OutcomeShard = Mutex<LruCache<(IpNetwork, ASN), Outcome>>;
+
+/// LRU cache of ROA outcomes split into independently locked shards by key
+///
+/// Lookups from different threads only contend when their keys land in the same shard
+struct OutcomeCache {
+    shards: Vec<OutcomeShard>,
+}
+
+impl OutcomeCache {
+    /// A power of two, so a shard is picked by the top bits of a hash
+    const NUM_SHARDS: usize = 16;
+    const CAPACITY: usize = 10_000;
+
+    fn new() -> Self {
+        let shard_capacity = (Self::CAPACITY / Self::NUM_SHARDS).try_into().unwrap();
+        OutcomeCache {
+            shards: (0..Self::NUM_SHARDS).map(|_| Mutex::new(LruCache::new(shard_capacity))).collect(),
+        }
+    }
+
+    /// Fibonacci hash of the key's bits, cheap next to the full hash the shard's `LruCache` computes
+    fn shard(&self, (prefix, origin): &(IpNetwork, ASN)) -> &OutcomeShard {
+        let addr_bits = match prefix {
+            IpNetwork::V4(network) => u32::from(network.ip()) as u64,
+            IpNetwork::V6(network) => (u128::from(network.ip()) >> 64) as u64 ^ u128::from(network.ip()) as u64,
+        };
+        let bits = addr_bits ^ (prefix.prefix() as u64) << 56 ^ (*origin as u64) << 24;
+        let index = bits.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> (64 - Self::NUM_SHARDS.trailing_zeros());
+        &self.shards[index as usize]
+    }
+
+    fn get(&self, key: &(IpNetwork, ASN)) -> Option<(ROAValidity, ROARouted)> {
+        self.shard(key).lock().unwrap().get(key).copied()
+    }
+
+    fn put(&self, key: (IpNetwork, ASN), outcome: (ROAValidity, ROARouted)) {
+        self.shard(&key).lock().unwrap().put(key, outcome);
+    }
+
+    fn clear(&mut self) {
+        for shard in &mut self.shards {
+            shard.get_mut().unwrap().clear();
+        }
+    }
+}
+
+/// ROA outcomes of a fixed set of routes, computed up front so validating them is an array lookup
+///
+/// The outcomes are keyed by the routes' `PrefixId`s in the engine's `PrefixTable`, stored flat, grouped by
+/// prefix id and sorted by origin within each group, so `get_by_id` is two array indexes and a search of the
+/// prefix's origins. Scenarios seed a handful of origins per prefix, so every validation of those routes during
+/// propagation skips hashing the prefix, the trie walk and the cache's locks
+#[derive(Debug, Clone, Default)]
+pub struct FrozenValidator {
+    table: Arc<PrefixTable>,
+    /// The frozen prefix with each id in `table`, None for prefixes that weren't frozen
+    prefixes: Vec<Option<IpNetwork>>,
+    /// Prefix `id`'s outcomes are `outcomes[offsets[id]..offsets[id + 1]]`
+    offsets: Vec<usize>,
+    outcomes: Vec<(ASN, Outcome)>,
+}
+
+impl FrozenValidator {
+    /// Outcomes of `routes`, each a prefix and its origin, under `route_validator`'s current ROAs
+    /// The prefixes are interned in `table`, normally the engine's, whose ids `get_by_id` takes
+    pub fn new(route_validator: &RouteValidator, table: Arc<PrefixTable>, routes: impl IntoIterator<Item = (IpNetwork, ASN)>) -> Self {
+        let mut routes: Vec<(PrefixId, ASN)> = routes.into_iter().map(|(prefix, origin)| (table.intern(prefix), origin)).collect();
+        routes.sort_unstable();
+        routes.dedup();
+        let num_ids = routes.last().map_or(0, |(id, _)| *id as usize + 1);
+        
+        let mut frozen = FrozenValidator {
+            prefixes: vec![None; num_ids],
+            offsets: Vec::with_capacity(num_ids + 1),
+            outcomes: Vec::with_capacity(routes.len()),
+            table,
+        };
+        let mut routes = routes.into_iter().peekable();
+        for id in 0..num_ids as PrefixId {
+            frozen.offsets.push(frozen.outcomes.len());
+            while let Some((_, origin)) = routes.next_if(|(route_id, _)| *route_id == id) {
+                let prefix = *frozen.prefixes[id as usize].get_or_insert_with(|| frozen.table.prefix(id).unwrap());
+                frozen.outcomes.push((origin, route_validator.compute_roa_outcome(&prefix, origin)));
+            }
+        }
+        frozen.offsets.push(frozen.outcomes.len());
+        frozen
+    }
+
+    /// The table whose prefix ids `get_by_id` takes
+    pub fn table(&self) -> &Arc<PrefixTable> {
+        &self.table
+    }
+
+    /// Id of a frozen prefix, for `get_by_id`
+    pub fn prefix_id(&self, prefix: &IpNetwork) -> Option<PrefixId> {
+        self.table.id(prefix).filter(|&id| self.prefix(id).is_some())
+    }
+
+    /// The frozen prefix with `prefix_id`, if any
+    pub fn prefix(&self, prefix_id: PrefixId) -> Option<IpNetwork> {
+        self.prefixes.get(prefix_id as usize).copied().flatten()
+    }
+
+    /// The precomputed outcome, or None if the route wasn't one of those frozen
+    pub fn get(&self, prefix: &IpNetwork, origin: ASN) -> Option<(ROAValidity, ROARouted)> {
+        self.get_by_id(self.prefix_id(prefix)?, origin)
+    }
+
+    /// `get` for the prefix with `prefix_id` in `table`
+    pub fn get_by_id(&self, prefix_id: PrefixId, origin: ASN) -> Option<(ROAValidity, ROARouted)> {
+        let id = prefix_id as usize;
+        let outcomes = &self.outcomes[*self.offsets.get(id)?..*self.offsets.get(id + 1)?];
+        outcomes.binary_search_by_key(&origin, |(asn, _)| *asn).ok().map(|index| outcomes[index].1)
+    }
+
+    /// The frozen routes, each a prefix and its origin, by prefix id and then origin
+    pub fn routes(&self) -> impl Iterator<Item = (IpNetwork, ASN)> + '_ {
+        self.prefixes.iter().zip(self.offsets.windows(2))
+            .filter_map(|(prefix, range)| prefix.map(|prefix| (prefix, range)))
+            .flat_map(move |(prefix, range)| self.outcomes[range[0]..range[1]].iter().map(move |(origin, _)| (prefix, *origin)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.is_empty()
+    }
+}
+
+pub struct RouteValidator {
+    root: ROASNode,
+    cache: OutcomeCache,
+    /// Scheduled ROAs, and whether each is currently in the trie
+    timed_roas: Vec<(TimedROA, bool)>,
+    /// Consulted before the trie, and recomputed whenever the ROAs change, see `freeze`
+    frozen: Option<FrozenValidator>,
+}
+
+/// Copies the ROAs and any frozen outcomes, e.g. to share with the engine's policies, starting with
+/// an empty outcome cache
+impl Clone for RouteValidator {
+    fn clone(&self) -> Self {
+        RouteValidator {
+            root: self.root.clone(),
+            timed_roas: self.timed_roas.clone(),
+            frozen: self.frozen.clone(),
+            ..RouteValidator::new()
+        }
+    }
+}
+
+impl RouteValidator {
+    pub fn new() -> Self {
+        RouteValidator {
+            root: ROASNode::new(),
+            cache: OutcomeCache::new(),
+            timed_roas: Vec::new(),
+            frozen: None,
+        }
+    }
+
+    /// Precompute the outcomes of `routes`, each a prefix and its origin, e.g. the routes a scenario seeds
+    /// keyed by their ids in `table`, normally the engine's, see `get_roa_outcome_by_id`
+    ///
+    /// Replaces any routes frozen before. Lookups of other routes still walk the trie
+    pub fn freeze(&mut self, table: Arc<PrefixTable>, routes: impl IntoIterator<Item = (IpNetwork, ASN)>) {
+        self.frozen = Some(FrozenValidator::new(self, table, routes));
+    }
+
+    pub fn frozen(&self) -> Option<&FrozenValidator> {
+        self.frozen.as_ref()
+    }
+
+    /// Drop the precomputed outcomes
+    pub fn unfreeze(&mut self) {
+        self.frozen = None;
+    }
+
+    /// Clear the outcome cache and recompute the frozen outcomes after the ROAs changed
+    fn roas_changed(&mut self) {
+        self.cache.clear();
+        if let Some(frozen) = self.frozen.take() {
+            self.frozen = Some(FrozenValidator::new(self, frozen.table.clone(), frozen.routes()));
+        }
+    }
+
+    pub fn add_roa(&mut self, roa: ROA) {
+        self.add_roas(vec![roa]);
+    }
+
+    /// Add many ROAs at once, clearing the outcome cache only once
+    pub fn add_roas(&mut self, roas: Vec<ROA>) {
+        for roa in roas {
+            self.insert_roa(roa);
+        }
+        self.roas_changed();
+    }
+
+    /// Remove a ROA, returning whether it was present
+    pub fn remove_roa(&mut self, roa: &ROA) -> bool {
+        let removed = self.remove_roa_from_trie(roa);
+        if removed {
+            self.roas_changed();
+        }
+        removed
+    }
+
+    /// Replace every ROA, e.g. with a new VRP snapshot
+    /// Scheduled ROAs are dropped too
+    pub fn replace_roas(&mut self, roas: Vec<ROA>) {
+        self.root = ROASNode::new();
+        self.timed_roas.clear();
+        self.add_roas(roas);
+    }
+
+    /// Schedule a ROA to be published and withdrawn by `advance_to`
+    /// Scheduling the same ROA as an unscheduled one means it is withdrawn along with the scheduled one
+    pub fn add_timed_roa(&mut self, timed_roa: TimedROA) {
+        self.timed_roas.push((timed_roa, false));
+    }
+
+    /// Publish the scheduled ROAs valid at `time` and withdraw the rest, returning whether any changed
+    /// Call between propagation rounds to change validation state mid-simulation
+    pub fn advance_to(&mut self, time: u32) -> bool {
+        let mut timed_roas = std::mem::take(&mut self.timed_roas);
+        let mut changed = false;
+        for (timed_roa, published) in timed_roas.iter_mut() {
+            let valid = timed_roa.is_valid_at(time);
+            if valid && !*published {
+                self.insert_roa(timed_roa.roa.clone());
+            } else if !valid && *published {
+                self.remove_roa_from_trie(&timed_roa.roa);
+            }
+            changed |= valid != *published;
+            *published = valid;
+        }
+        self.timed_roas = timed_roas;
+        if changed {
+            self.roas_changed();
+        }
+        changed
+    }
+
+    fn remove_roa_from_trie(&mut self, roa: &ROA) -> bool {
+        let (bits, prefix_len) = Self::prefix_bits(&roa.prefix);
+        let mut node = &mut self.root;
+        for index in 0..prefix_len {
+            let child = if Self::bit(bits, index) { &mut node.right } else { &mut node.left };
+            node = match child {
+                Some(child) => child,
+                None => return false,
+            };
+        }
+        node.roas.remove(roa)
+    }
+
+    fn insert_roa(&mut self, roa: ROA) {
+        let (bits, prefix_len) = Self::prefix_bits(&roa.prefix);
+        let mut node = &mut self.root;
+        for index in 0..prefix_len {
+            let child = if Self::bit(bits, index) { &mut node.right } else { &mut node.left };
+            node = child.get_or_insert_with(|| Box::new(ROASNode::new()));
+        }
+        node.prefix = Some(roa.prefix);
+        node.roas.insert(roa);
+    }
+
+    pub fn get_roa_outcome(&self, prefix: &IpNetwork, origin: ASN) -> (ROAValidity, ROARouted) {
+        if let Some(result) = self.frozen.as_ref().and_then(|frozen| frozen.get(prefix, origin)) {
+            return result;
+        }
+        self.cached_roa_outcome(prefix, origin)
+    }
+
+    /// `get_roa_outcome` for `prefix` with `prefix_id` in the table the routes were frozen with, which finds
+    /// frozen outcomes without hashing the prefix. Ids from another table fall back to the cache and the trie
+    pub fn get_roa_outcome_by_id(&self, prefix_id: PrefixId, prefix: &IpNetwork, origin: ASN) -> (ROAValidity, ROARouted) {
+        let frozen = self.frozen.as_ref().filter(|frozen| frozen.prefix(prefix_id) == Some(*prefix));
+        if let Some(result) = frozen.and_then(|frozen| frozen.get_by_id(prefix_id, origin)) {
+            return result;
+        }
+        self.cached_roa_outcome(prefix, origin)
+    }
+
+    /// The outcome from the cache, computing and caching it on a miss
+    fn cached_roa_outcome(&self, prefix: &IpNetwork, origin: ASN) -> (ROAValidity, ROARouted) {
+        if let Some(result) = self.cache.get(&(*prefix, origin)) {
+            return result;
+        }
+        let result = self.compute_roa_outcome(prefix, origin);
+        self.cache.put((*prefix, origin), result);
+        result
+    }
+
+    /// The outcome from the trie, bypassing the frozen outcomes and the cache
+    fn compute_roa_outcome(&self, prefix: &IpNetwork, origin: ASN) -> (ROAValidity, ROARouted) {
+        let relevant_roas = self.get_relevant_roas(prefix);
+        if relevant_roas.is_empty() {
+            return (ROAValidity::Unknown, ROARouted::Unknown);
+        }
+
+        // Get all outcomes and find the best validity
+        let mut outcomes: Vec<(ROAValidity, ROARouted)> = relevant_roas
+            .iter()
+            .map(|roa| roa.get_outcome(prefix, origin))
+            .collect();
+
+        // Sort by validity (lower enum value is better), then prefer routed ROAs so ties don't depend on hash order
+        outcomes.sort_by_key(|(validity, routed)| (*validity as u8, *routed as u8));
+
+        outcomes[0]
+    }
+
+    /// Whether `prefix` lies in non-routed space, i.e. every ROA covering it is an AS0 ROA
+    pub fn get_roa_routed(&self, prefix: &IpNetwork) -> ROARouted {
+        let relevant_roas = self.get_relevant_roas(prefix);
+        if relevant_roas.is_empty() {
+            ROARouted::Unknown
+        } else if relevant_roas.iter().all(ROA::is_non_routed) {
+            ROARouted::NonRouted
+        } else {
+            ROARouted::Routed
+        }
+    }
+
+    /// ROAs covering `prefix`, from the nodes on its path down the trie
+    fn get_relevant_roas(&self, prefix: &IpNetwork) -> Vec<ROA> {
+        let mut relevant_roas = Vec::new();
+        let (bits, prefix_len) = Self::prefix_bits(prefix);
+        let mut node = Some(&self.root);
+        let mut index = 0;
+
+        while let Some(current) = node {
+            relevant_roas.extend(current.roas.iter().filter(|roa| roa.covers_prefix(prefix)).cloned());
+            if index == prefix_len {
+                break;
+            }
+            let child = if Self::bit(bits, index) { &current.right } else { &current.left };
+            node = child.as_deref();
+            index += 1;
+        }
+
+        relevant_roas
+    }
+
+    /// Address bits of a prefix, left-aligned in a u128 so IPv4 and IPv6 walk the trie the same way
+    fn prefix_bits(prefix: &IpNetwork) -> (u128, usize) {
+        let bits = match prefix {
+            IpNetwork::V4(net) => u128::from(u32::from(net.ip())) << 96,
+            IpNetwork::V6(net) => u128::from(net.ip()),
+        };
+        (bits, prefix.prefix() as usize)
+    }
+
+    /// Bit `index` of a prefix, counting from the most significant
+    fn bit(bits: u128, index: usize) -> bool {
+        (bits >> (127 - index)) & 1 == 1
+    }
+}
+
+impl RouteValidator {
+    /// Get every ROA stored in the validator
+    pub fn roas(&self) -> Vec<ROA> {
+        let mut roas = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            roas.extend(node.roas.iter().cloned());
+            stack.extend(node.left.as_deref());
+            stack.extend(node.right.as_deref());
+        }
+        roas
+    }
+
+    /// Lint every stored ROA, flagging loose max lengths and ROAs that conflict with one another
+    ///
+    /// `max_length_slack` is how many bits max_length may exceed the prefix length before
+    /// the ROA is flagged as hijack-enabling (RFC 9319 recommends 0)
+    pub fn audit(&self, max_length_slack: u8) -> ROALintReport {
+        let mut roas = self.roas();
+        roas.sort_by_key(|roa| (roa.prefix.to_string(), roa.origin, roa.max_length));
+        let mut report = ROALintReport::new(roas.len());
+
+        for roa in &roas {
+            let prefix_len = roa.prefix.prefix();
+            let max_bits = match roa.prefix {
+                IpNetwork::V4(_) => 32,
+                IpNetwork::V6(_) => 128,
+            };
+
+            if roa.max_length < prefix_len || roa.max_length > max_bits {
+                report.add(roa.clone(), ROALintKind::InvalidMaxLength);
+            } else if roa.max_length - prefix_len > max_length_slack {
+                report.add(roa.clone(), ROALintKind::LooseMaxLength {
+                    slack: roa.max_length - prefix_len,
+                });
+            }
+
+            // Only ROAs at or above this ROA's prefix can overlap with it
+            for other in self.get_relevant_roas(&roa.prefix) {
+                if &other == roa || other.max_length < prefix_len {
+                    continue;
+                }
+                let same_prefix = other.prefix == roa.prefix;
+
+                if roa.is_non_routed() {
+                    if other.is_routed() {
+                        report.add(roa.clone(), ROALintKind::NonRoutedOverridden { other });
+                    }
+                } else if other.origin == roa.origin {
+                    // Report identical-prefix duplicates once, against the narrower ROA
+                    if other.max_length > roa.max_length || (!same_prefix && other.max_length == roa.max_length) {
+                        report.add(roa.clone(), ROALintKind::Redundant { other });
+                    }
+                } else if other.is_routed() && (!same_prefix || other.origin < roa.origin) {
+                    report.add(roa.clone(), ROALintKind::OriginConflict { other });
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Lint an arbitrary list of ROAs, e.g. a VRP dataset or a scenario's generated ROAs
+    pub fn audit_roas(roas: &[ROA], max_length_slack: u8) -> ROALintReport {
+        let mut validator = RouteValidator::new();
+        validator.add_roas(roas.to_vec());
+        validator.audit(max_length_slack)
+    }
+}
+
+/// Problem found while auditing a set of ROAs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ROALintKind {
+    /// max_length is shorter than the prefix or longer than the address family allows
+    InvalidMaxLength,
+    /// max_length authorizes `slack` more bits than the prefix, enabling forged-origin subprefix hijacks
+    LooseMaxLength { slack: u8 },
+    /// Another ROA with the same origin already authorizes everything this ROA does
+    Redundant { other: ROA },
+    /// Another ROA authorizes a different origin for this ROA's prefix
+    OriginConflict { other: ROA },
+    /// This AS0 ROA has no effect because a routed ROA authorizes its prefix
+    NonRoutedOverridden { other: ROA },
+}
+
+impl ROALintKind {
+    pub fn name(&self) -> &str {
+        match self {
+            ROALintKind::InvalidMaxLength => "INVALID_MAX_LENGTH",
+            ROALintKind::LooseMaxLength { .. } => "LOOSE_MAX_LENGTH",
+            ROALintKind::Redundant { .. } => "REDUNDANT",
+            ROALintKind::OriginConflict { .. } => "ORIGIN_CONFLICT",
+            ROALintKind::NonRoutedOverridden { .. } => "NON_ROUTED_OVERRIDDEN",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ROALint {
+    pub roa: ROA,
+    pub kind: ROALintKind,
+}
+
+/// Result of auditing a set of ROAs
+#[derive(Debug, Clone, Default)]
+pub struct ROALintReport {
+    pub num_roas: usize,
+    pub lints: Vec<ROALint>,
+}
+
+impl ROALintReport {
+    pub fn new(num_roas: usize) -> Self {
+        ROALintReport {
+            num_roas,
+            lints: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, roa: ROA, kind: ROALintKind) {
+        self.lints.push(ROALint { roa, kind });
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.lints.is_empty()
+    }
+
+    /// Number of lints of each kind, keyed by kind name
+    pub fn counts(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for lint in &self.lints {
+            *counts.entry(lint.kind.name().to_string()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let lints: Vec<serde_json::Value> = self.lints.iter().map(|lint| {
+            let other = match &lint.kind {
+                ROALintKind::Redundant { other }
+                | ROALintKind::OriginConflict { other }
+                | ROALintKind::NonRoutedOverridden { other } => Some(serde_json::json!({
+                    "prefix": other.prefix.to_string(),
+                    "origin": other.origin,
+                    "max_length": other.max_length,
+                })),
+                _ => None,
+            };
+            serde_json::json!({
+                "kind": lint.kind.name(),
+                "prefix": lint.roa.prefix.to_string(),
+                "origin": lint.roa.origin,
+                "max_length": lint.roa.max_length,
+                "other": other,
+            })
+        }).collect();
+
+        serde_json::json!({
+            "num_roas": self.num_roas,
+            "counts": self.counts(),
+            "lints": lints,
+        })
+    }
+}
+
+impl Default for RouteValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_roa_covers_prefix() {
+        let roa = ROA::new(
+            IpNetwork::from_str("10.0.0.0/8").unwrap(),
+            65001,
+            Some(24),
+        );
+
+        assert!(roa.covers_prefix(&IpNetwork::from_str("10.1.1.0/24").unwrap()));
+        assert!(!roa.covers_prefix(&IpNetwork::from_str("192.168.1.0/24").unwrap()));
+    }
+
+    #[test]
+    fn test_roa_validity() {
+        let roa = ROA::new(
+            IpNetwork::from_str("10.0.0.0/8").unwrap(),
+            65001,
+            Some(24),
+        );
+
+        // Valid
+        assert_eq!(
+            roa.get_validity(&IpNetwork::from_str("10.1.0.0/16").unwrap(), 65001),
+            ROAValidity::Valid
+        );
+
+        // Invalid length
+        assert_eq!(
+            roa.get_validity(&IpNetwork::from_str("10.1.1.1/32").unwrap(), 65001),
+            ROAValidity::InvalidLength
+        );
+
+        // Invalid origin
+        assert_eq!(
+            roa.get_validity(&IpNetwork::from_str("10.1.0.0/16").unwrap(), 65002),
+            ROAValidity::InvalidOrigin
+        );
+    }
+}
\ No newline at end of file
@@ -1,5 +1,8 @@
+use std::collections::HashSet;
 use std::fmt;
 
+use crate::as_graphs::as_graph::{ASGraph, ASN, RIR};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum Relationships {
@@ -45,6 +48,44 @@ pub enum ASNGroups {
     Transit,
     Input,
     Ixp,
+    /// The `n` ASes with the largest customer cones
+    TopByCustomerCone(usize),
+    /// ASes whose organization is in a country, by upper case ISO 3166 alpha-2 code, see `as_metadata::country_code`
+    /// Like `Rir`, needs metadata on the graph
+    Country([u8; 2]),
+    Rir(RIR),
+}
+
+impl ASNGroups {
+    /// Get the ASNs in this group
+    pub fn asns(&self, as_graph: &ASGraph) -> HashSet<ASN> {
+        match self {
+            ASNGroups::TopByCustomerCone(n) => return as_graph.top_asns_by_customer_cone(*n).into_iter().collect(),
+            ASNGroups::Country(code) => return as_graph.asns_in_country(&String::from_utf8_lossy(code)),
+            ASNGroups::Rir(rir) => return as_graph.asns_in_rir(*rir),
+            _ => {}
+        }
+        
+        as_graph.iter()
+            .filter(|as_obj| {
+                let no_customers = as_obj.customers().is_empty();
+                let num_upstreams = as_obj.peers().len() + as_obj.providers().len();
+                match self {
+                    ASNGroups::Tier1 | ASNGroups::Input => as_obj.tier_1,
+                    ASNGroups::Ixp => as_obj.ixp,
+                    ASNGroups::StubsOrMh => no_customers,
+                    ASNGroups::Stubs => no_customers && num_upstreams <= 1,
+                    ASNGroups::Multihomed => no_customers && num_upstreams > 1,
+                    ASNGroups::Transit => !no_customers,
+                    ASNGroups::Etc => !no_customers && !as_obj.tier_1 && !as_obj.ixp,
+                    ASNGroups::TopByCustomerCone(_) | ASNGroups::Country(_) | ASNGroups::Rir(_) => {
+                        unreachable!("handled above")
+                    }
+                }
+            })
+            .map(|as_obj| as_obj.asn)
+            .collect()
+    }
 }
 
 impl fmt::Display for ASNGroups {
@@ -58,6 +99,9 @@ impl fmt::Display for ASNGroups {
             ASNGroups::Transit => "TRANSIT",
             ASNGroups::Input => "INPUT",
             ASNGroups::Ixp => "IXP",
+            ASNGroups::TopByCustomerCone(n) => return write!(f, "TOP_{}_BY_CONE", n),
+            ASNGroups::Country(code) => return write!(f, "COUNTRY_{}", String::from_utf8_lossy(code)),
+            ASNGroups::Rir(rir) => return write!(f, "RIR_{}", rir),
         };
         write!(f, "{}", s)
     }
@@ -89,6 +133,10 @@ pub enum Settings {
     BgpisecTransitiveProConId = 20,
     ProviderConeId = 21,
     BgpisecTransitiveOnlyToCustomers = 22,
+    /// Drop customer routes without a matching IRR route object, see `IrrValidator`
+    IrrFilter = 23,
+    /// ROV that de-preferences ROA-invalid routes rather than dropping them, see `ROVMode`
+    RovDepref = 24,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -122,14 +170,47 @@ pub enum ROARouted {
     NonRouted = 2,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u8)]
-pub enum Timestamps {
-    Victim = 0,
-    Attacker = 1,
+impl fmt::Display for ROARouted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ROARouted::Routed => "ROUTED",
+            ROARouted::Unknown => "UNKNOWN",
+            ROARouted::NonRouted => "NON_ROUTED",
+        };
+        write!(f, "{}", s)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// When an announcement was originated, in arbitrary units where lower is older
+///
+/// Scenarios with one victim and one attacker wave use `VICTIM` and `ATTACKER`;
+/// others can use any values, e.g. the round an announcement is made in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Timestamps(pub u64);
+
+impl Timestamps {
+    pub const VICTIM: Timestamps = Timestamps(0);
+    pub const ATTACKER: Timestamps = Timestamps(1);
+
+    /// How long before `now` this was, or 0 if it's in the future
+    pub fn age(&self, now: Timestamps) -> u64 {
+        now.0.saturating_sub(self.0)
+    }
+}
+
+impl From<u64> for Timestamps {
+    fn from(time: u64) -> Self {
+        Timestamps(time)
+    }
+}
+
+impl fmt::Display for Timestamps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 #[repr(u8)]
 pub enum Outcomes {
     AttackerSuccess = 0,
@@ -141,6 +222,21 @@ pub enum Outcomes {
     HijackedSamePath = 6,
     HijackedButBlackholed = 7,
     HijackedButNotDetected = 8,
+    /// Traffic was dropped by a remotely triggered blackhole
+    Blackholed = 9,
+}
+
+impl Outcomes {
+    /// Whether traffic reached neither the attacker nor a legitimate origin for lack of a route or in a loop
+    pub fn is_disconnected(self) -> bool {
+        matches!(
+            self,
+            Outcomes::DisconnectedOrigin
+                | Outcomes::DisconnectedAttacker
+                | Outcomes::DisconnectedVictim
+                | Outcomes::DisconnectedNotAsSomehow
+        )
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
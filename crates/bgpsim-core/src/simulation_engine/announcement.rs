@@ -0,0 +1,518 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use crate::shared::{Relationships, Settings, Timestamps};
+use crate::as_graphs::as_graph::{AS, ASN};
+use crate::simulation_engine::as_path::ASPath;
+use crate::simulation_engine::communities::{ActionCommunity, CommunityAction};
+use crate::simulation_engine::prefix_table::{PrefixId, PrefixMap, PrefixTable};
+use crate::simulation_engine::rib_in::RIBsIn;
+use crate::simulation_engine::policy::{GuardLimits, GuardedPolicy, PolicyExtension, PolicyExtensionHandle, TieBreaker, ValidationResult, create_policy_extension, extension_handle};
+#[cfg(feature = "enum-dispatch")]
+use crate::simulation_engine::policy::PolicyKind;
+
+pub type Prefix = ipnetwork::IpNetwork;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Announcement {
+    pub prefix: Prefix,
+    pub as_path: ASPath,
+    pub next_hop_asn: ASN,
+    pub recv_relationship: Relationships,
+    pub timestamp: Timestamps,
+    pub withdraw: bool,
+    #[cfg(feature = "bgpsec")]
+    pub bgpsec_next_asn: Option<ASN>,
+    #[cfg(feature = "bgpsec")]
+    pub bgpsec_as_path: Option<Vec<ASN>>,
+    pub only_to_customers: Option<bool>,
+    #[cfg(feature = "rovpp")]
+    pub rovpp_blackhole: Option<bool>,
+    #[cfg(feature = "rost")]
+    pub rost_ids: Option<Vec<u32>>,
+    pub action_communities: Option<Vec<ActionCommunity>>,
+    /// LOCAL_PREF the receiving AS assigned; None uses its relationship preference, see `PolicyExtension::local_pref`
+    pub local_pref: Option<u32>,
+    /// MULTI_EXIT_DISC the sending AS attached, lower is preferred; None counts as 0
+    pub med: Option<u32>,
+}
+
+impl Announcement {
+    pub fn new(
+        prefix: Prefix,
+        next_hop_asn: ASN,
+        recv_relationship: Relationships,
+    ) -> Self {
+        Announcement {
+            prefix,
+            as_path: ASPath::new(),
+            next_hop_asn,
+            recv_relationship,
+            timestamp: Timestamps::VICTIM,
+            withdraw: false,
+            #[cfg(feature = "bgpsec")]
+            bgpsec_next_asn: None,
+            #[cfg(feature = "bgpsec")]
+            bgpsec_as_path: None,
+            only_to_customers: None,
+            #[cfg(feature = "rovpp")]
+            rovpp_blackhole: None,
+            #[cfg(feature = "rost")]
+            rost_ids: None,
+            action_communities: None,
+            local_pref: None,
+            med: None,
+        }
+    }
+    
+    pub fn new_with_path(
+        prefix: Prefix,
+        as_path: impl Into<ASPath>,
+        next_hop_asn: ASN,
+        recv_relationship: Relationships,
+        timestamp: Timestamps,
+    ) -> Self {
+        Announcement {
+            prefix,
+            as_path: as_path.into(),
+            next_hop_asn,
+            recv_relationship,
+            timestamp,
+            withdraw: false,
+            #[cfg(feature = "bgpsec")]
+            bgpsec_next_asn: None,
+            #[cfg(feature = "bgpsec")]
+            bgpsec_as_path: None,
+            only_to_customers: None,
+            #[cfg(feature = "rovpp")]
+            rovpp_blackhole: None,
+            #[cfg(feature = "rost")]
+            rost_ids: None,
+            action_communities: None,
+            local_pref: None,
+            med: None,
+        }
+    }
+
+    /// Attach action communities for ASes along the path to act on; an empty list attaches none
+    pub fn with_action_communities(mut self, communities: Vec<ActionCommunity>) -> Self {
+        self.action_communities = (!communities.is_empty()).then_some(communities);
+        self
+    }
+
+    /// Pad the path with `times` extra copies of its first ASN, e.g. an origin making its route less attractive
+    /// An empty seed path is padded with `next_hop_asn`, the origin, and kept as is when seeded
+    pub fn with_prepending(mut self, times: usize) -> Self {
+        if times == 0 {
+            return self;
+        }
+        let asn = self.as_path.first().copied().unwrap_or(self.next_hop_asn);
+        if self.as_path.is_empty() {
+            self.as_path = ASPath::from(vec![asn]);
+        }
+        self.as_path = (0..times).fold(self.as_path, |path, _| path.prepend(asn));
+        self
+    }
+
+    /// Action communities `asn` acts on: those addressed to it, on routes it learned from a customer
+    fn communities_for(&self, asn: ASN) -> impl Iterator<Item = &ActionCommunity> {
+        self.action_communities.iter()
+            .filter(move |_| self.recv_relationship == Relationships::Customers)
+            .flatten()
+            .filter(move |community| community.target_asn == asn)
+    }
+
+    /// Whether `asn`, holding this route, drops traffic for the prefix because of an RTBH request
+    pub fn is_blackholed_at(&self, asn: ASN) -> bool {
+        self.communities_for(asn).any(|community| community.action == CommunityAction::Blackhole)
+    }
+
+    pub fn origin(&self) -> ASN {
+        self.as_path.last().copied().unwrap_or(self.next_hop_asn)
+    }
+
+    pub fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    /// Copy of a route received from a neighbor as `asn` stores it in its local RIB
+    /// This is the only place a learned path grows: `asn` is prepended once here, and exports send the path as is
+    pub fn copy_and_process(&self, asn: ASN) -> Self {
+        debug_assert!(!self.withdraw, "AS {} storing a withdrawal for {}", asn, self.prefix);
+        debug_assert_eq!(self.as_path.first(), Some(&self.next_hop_asn),
+            "AS {} received {} with path {:?} from AS {}, which doesn't start with the sender", asn, self.prefix, self.as_path, self.next_hop_asn);
+        let mut new_ann = self.clone();
+        new_ann.as_path = new_ann.as_path.prepend(asn);
+        new_ann
+    }
+
+    /// Copy of a route held by `asn` as `asn` sends it to a neighbor
+    /// Local RIB paths already start with `asn`, so the path is shared rather than extended again;
+    /// only a seed path kept as given, see `Policy::seed_ann`, can lack it and is extended here
+    pub fn copy_for_export(&self, asn: ASN, recv_relationship: Relationships) -> Self {
+        debug_assert!(self.withdraw || self.recv_relationship == Relationships::Origin || self.as_path.first() == Some(&asn),
+            "AS {} exporting {} with path {:?}, which doesn't start with it", asn, self.prefix, self.as_path);
+        let mut new_ann = self.clone();
+        if !new_ann.withdraw && new_ann.as_path.first() != Some(&asn) {
+            new_ann.as_path = new_ann.as_path.prepend(asn);
+        }
+        #[cfg(feature = "bgpsec")]
+        if !new_ann.withdraw {
+            if let Some(ref mut bgpsec_path) = new_ann.bgpsec_as_path {
+                bgpsec_path.insert(0, asn);
+            }
+        }
+
+        new_ann.next_hop_asn = asn;
+        new_ann.recv_relationship = recv_relationship;
+        // Neither attribute crosses AS boundaries: the receiver assigns its own LOCAL_PREF and the sender its own MED
+        new_ann.local_pref = None;
+        new_ann.med = None;
+        #[cfg(feature = "bgpsec")]
+        {
+            new_ann.bgpsec_next_asn = Some(asn);
+        }
+
+        new_ann
+    }
+
+    /// Copy of a route held by `asn` as `asn` sends it to `neighbor_asn` over `send_relationship`,
+    /// after acting on the action communities addressed to `asn`
+    /// Returns None if a community asks `asn` not to export to this neighbor
+    pub fn copy_for_neighbor(&self, asn: ASN, neighbor_asn: ASN, send_relationship: Relationships) -> Option<Self> {
+        let mut new_ann = self.copy_for_export(asn, send_relationship.invert());
+
+        for community in self.communities_for(asn) {
+            match community.action {
+                CommunityAction::NoExportTo(blocked_asn) if blocked_asn == neighbor_asn => return None,
+                CommunityAction::Blackhole => return None,
+                CommunityAction::Prepend { times, to } if to == send_relationship => {
+                    new_ann.as_path = (0..times).fold(new_ann.as_path, |path, _| path.prepend(asn));
+                }
+                _ => {}
+            }
+        }
+        Some(new_ann)
+    }
+}
+
+#[derive(Debug)]
+pub struct AnnInfo {
+    pub ann: Announcement,
+    pub recv_relationship: Relationships,
+}
+
+impl AnnInfo {
+    pub fn new(ann: Announcement, recv_relationship: Relationships) -> Self {
+        AnnInfo { ann, recv_relationship }
+    }
+}
+
+pub type RIBsOut = HashMap<ASN, PrefixMap<Announcement>>;
+pub type LocalRIB = PrefixMap<Announcement>;
+
+pub struct Policy {
+    pub local_rib: LocalRIB,
+    pub recv_q: VecDeque<AnnInfo>,
+    pub ribs_in: RIBsIn,
+    pub ribs_out: RIBsOut,
+    pub settings: Settings,
+    pub asn: ASN,
+    pub extension: PolicyExtensionHandle,
+    /// LOCAL_PREF for routes from each neighbor, overriding the relationship preference
+    pub local_prefs: HashMap<ASN, u32>,
+    /// MED attached to routes exported to each neighbor
+    pub export_meds: HashMap<ASN, u32>,
+    /// Picks between routes the extension ranks equally; the engine sets it on `setup`
+    pub tie_breaker: TieBreaker,
+    /// Ids for the keys of every RIB, shared with the other policies in the store
+    pub prefix_table: Arc<PrefixTable>,
+    /// Set when something route ranking depends on, e.g. a LOCAL_PREF or the ROAs, changed after routes were
+    /// selected; the engine then reselects every prefix from the RIBs-in before processing more updates
+    pub selection_stale: bool,
+}
+
+impl Policy {
+    pub fn new(asn: ASN) -> Self {
+        Self::with_settings(asn, Settings::BaseDefense)
+    }
+    
+    pub fn with_settings(asn: ASN, settings: Settings) -> Self {
+        Self::with_prefix_table(asn, settings, Arc::new(PrefixTable::new()))
+    }
+
+    /// Policy whose RIBs use `prefix_table`, so their prefix ids match other policies on the same table
+    pub fn with_prefix_table(asn: ASN, settings: Settings, prefix_table: Arc<PrefixTable>) -> Self {
+        Policy {
+            local_rib: PrefixMap::new(prefix_table.clone()),
+            recv_q: VecDeque::new(),
+            ribs_in: RIBsIn::new(prefix_table.clone()),
+            ribs_out: HashMap::new(),
+            settings,
+            asn,
+            extension: create_policy_extension(settings),
+            local_prefs: HashMap::new(),
+            export_meds: HashMap::new(),
+            tie_breaker: TieBreaker::default(),
+            prefix_table,
+            selection_stale: false,
+        }
+    }
+
+    /// Replace the policy extension, e.g. with a third-party one
+    pub fn set_extension(&mut self, extension: impl PolicyExtension + 'static) {
+        self.extension = extension_handle(extension);
+    }
+
+    /// Name of the policy extension, e.g. `"ROV"`
+    pub fn name(&self) -> &str {
+        self.extension.name()
+    }
+
+    /// Wrap the policy extension, e.g. a third-party one, so breaking `limits` fails the run
+    pub fn guard_extension(&mut self, limits: GuardLimits) {
+        let inner = std::mem::replace(&mut self.extension, create_policy_extension(Settings::BaseDefense));
+        #[cfg(feature = "enum-dispatch")]
+        let inner: Box<dyn PolicyExtension> = match inner {
+            PolicyKind::Custom(extension) => extension,
+            kind => Box::new(kind),
+        };
+        self.set_extension(GuardedPolicy::new(inner, limits));
+    }
+
+    /// Prefer (higher) or avoid (lower) routes from `neighbor_asn`, e.g. 250 ranks a peer above other peers
+    /// but below customers, whose relationship preference is 300
+    /// Routes already received from the neighbor are updated too, and the policy is marked `selection_stale`
+    pub fn set_local_pref(&mut self, neighbor_asn: ASN, local_pref: u32) {
+        self.local_prefs.insert(neighbor_asn, local_pref);
+        for ann in self.ribs_in.neighbor_routes_mut(neighbor_asn) {
+            ann.local_pref = Some(local_pref);
+        }
+        for ann in self.local_rib.values_mut().filter(|ann| ann.next_hop_asn == neighbor_asn) {
+            ann.local_pref = Some(local_pref);
+        }
+        self.selection_stale = true;
+    }
+    
+    /// Attach `med` to every route exported to `neighbor_asn`
+    pub fn set_export_med(&mut self, neighbor_asn: ASN, med: u32) {
+        self.export_meds.insert(neighbor_asn, med);
+    }
+    
+    /// Assign the configured LOCAL_PREF to a route received from a neighbor
+    pub fn apply_local_pref(&self, ann: &mut Announcement) {
+        ann.local_pref = self.local_prefs.get(&ann.next_hop_asn).copied();
+    }
+    
+    /// Copy of a route as this AS exports it to `neighbor_asn`, with the configured MED
+    /// Returns None if an action community blocks the export, see `Announcement::copy_for_neighbor`
+    pub fn export_ann(&self, ann: &Announcement, neighbor_asn: ASN, send_relationship: Relationships) -> Option<Announcement> {
+        let mut new_ann = ann.copy_for_neighbor(self.asn, neighbor_asn, send_relationship)?;
+        new_ann.med = self.export_meds.get(&neighbor_asn).copied();
+        Some(new_ann)
+    }
+    
+    /// Clear all routing state and any per-run state of the policy extension
+    /// LOCAL_PREF and MED configuration is kept
+    pub fn reset(&mut self) {
+        self.local_rib.clear();
+        self.recv_q.clear();
+        self.ribs_in.clear();
+        self.ribs_out.clear();
+        self.extension.reset();
+        self.selection_stale = false;
+    }
+
+    pub fn receive_ann(&mut self, ann: Announcement, recv_relationship: Relationships) {
+        self.recv_q.push_back(AnnInfo::new(ann, recv_relationship));
+    }
+
+    pub fn valid_ann(&self, ann: &Announcement, recv_relationship: Relationships, as_obj: &AS) -> ValidationResult {
+        self.extension.validate_announcement(ann, recv_relationship, as_obj, None)
+    }
+
+    /// `valid_ann` for a route whose prefix has `prefix_id` in this policy's `prefix_table`
+    pub fn valid_ann_by_id(&self, ann: &Announcement, prefix_id: PrefixId, recv_relationship: Relationships, as_obj: &AS) -> ValidationResult {
+        self.extension.validate_announcement_by_id(ann, prefix_id, recv_relationship, as_obj, None)
+    }
+
+    pub fn get_best_ann_for_prefix(&self, prefix: &Prefix, as_obj: &AS) -> Option<Announcement> {
+        self.get_best_ann_for_id(self.prefix_table.id(prefix)?, as_obj)
+    }
+
+    /// `get_best_ann_for_prefix` for an already interned prefix
+    pub fn get_best_ann_for_id(&self, prefix_id: PrefixId, as_obj: &AS) -> Option<Announcement> {
+        self.ribs_in.best_candidates_by_id(prefix_id)
+            .min_by(|a, b| self.compare_anns(a, b, as_obj))
+            .cloned()
+    }
+
+    /// Best route for the prefix after `neighbor_asn`'s route for it changed in the RIBs-in, replacing `previous`
+    /// Only the changed route is compared against the installed best; the RIBs-in are scanned with
+    /// `get_best_ann_for_id` when the installed best is withdrawn or made worse, or there is none.
+    /// This assumes the other routes ranked the same as when the best was selected, which is what
+    /// `selection_stale` tracks
+    pub fn updated_best_for_id(&self, prefix_id: PrefixId, neighbor_asn: ASN, previous: Option<&Announcement>, as_obj: &AS) -> Option<Announcement> {
+        let installed_asn = match self.local_rib.get_by_id(prefix_id) {
+            Some(installed) => installed.next_hop_asn,
+            None => return self.get_best_ann_for_id(prefix_id, as_obj),
+        };
+        let updated = self.ribs_in.get_by_id(neighbor_asn, prefix_id).filter(|ann| !ann.withdraw);
+        if installed_asn != neighbor_asn {
+            // Seeded routes aren't in the RIBs-in
+            let Some(installed) = self.ribs_in.get_by_id(installed_asn, prefix_id) else {
+                return self.get_best_ann_for_id(prefix_id, as_obj);
+            };
+            return match updated {
+                // Ties go to the lower neighbor, as in the full scan
+                Some(updated) if self.compare_anns(updated, installed, as_obj)
+                    .then(neighbor_asn.cmp(&installed_asn)) == std::cmp::Ordering::Less => Some(updated.clone()),
+                _ => Some(installed.clone()),
+            };
+        }
+        match (updated, previous.filter(|ann| !ann.withdraw)) {
+            (Some(updated), Some(previous)) if self.compare_anns(updated, previous, as_obj) != std::cmp::Ordering::Greater => Some(updated.clone()),
+            _ => self.get_best_ann_for_id(prefix_id, as_obj),
+        }
+    }
+
+    /// Select between a received route and the current best using only the local RIB
+    /// An update from the current best's neighbor replaces it outright, since it implicitly withdraws the old route
+    /// Returns the new best route if the local RIB changed; withdrawals are ignored
+    pub fn update_local_rib(&mut self, ann: &Announcement, as_obj: &AS) -> Option<Announcement> {
+        if ann.withdraw {
+            return None;
+        }
+        let prefix_id = self.prefix_table.intern(ann.prefix);
+        if self.originates_id(prefix_id) {
+            return None;
+        }
+        let current = self.local_rib.get_by_id(prefix_id);
+        
+        let mut candidate = ann.copy_and_process(self.asn);
+        self.apply_local_pref(&mut candidate);
+        
+        if let Some(current) = current {
+            if *current == candidate {
+                return None;
+            }
+            if current.next_hop_asn != candidate.next_hop_asn {
+                let ordering = self.compare_anns(&candidate, current, as_obj);
+                if ordering != std::cmp::Ordering::Less {
+                    return None;
+                }
+            }
+        }
+        
+        self.local_rib.insert_by_id(prefix_id, ann.prefix, candidate.clone());
+        Some(candidate)
+    }
+
+    /// Rank two routes, `Less` meaning `ann1` is better
+    /// The default tie-breaker keeps the extension's own, so extensions that override it still apply
+    pub fn compare_anns(&self, ann1: &Announcement, ann2: &Announcement, as_obj: &AS) -> std::cmp::Ordering {
+        let rel1 = self.get_relationship(&ann1.next_hop_asn, as_obj);
+        let rel2 = self.get_relationship(&ann2.next_hop_asn, as_obj);
+        match self.tie_breaker {
+            TieBreaker::LowestNextHopAsn => self.extension.compare_announcements(ann1, ann2, rel1, rel2, as_obj),
+            tie_breaker => self.extension.compare_preference(ann1, ann2, rel1, rel2, as_obj)
+                .then_with(|| tie_breaker.compare(ann1, ann2, self.asn)),
+        }
+    }
+
+    /// Whether this AS seeded its own route for `prefix`, which no learned route replaces however short
+    pub fn originates(&self, prefix: &Prefix) -> bool {
+        self.prefix_table.id(prefix).is_some_and(|prefix_id| self.originates_id(prefix_id))
+    }
+
+    pub fn originates_id(&self, prefix_id: PrefixId) -> bool {
+        self.local_rib.get_by_id(prefix_id).is_some_and(|ann| ann.recv_relationship == Relationships::Origin && ann.next_hop_asn == self.asn)
+    }
+
+    fn get_relationship(&self, neighbor_asn: &ASN, as_obj: &AS) -> Relationships {
+        if as_obj.customers().iter().any(|as_ref| as_ref.asn == *neighbor_asn) {
+            Relationships::Customers
+        } else if as_obj.peers().iter().any(|as_ref| as_ref.asn == *neighbor_asn) {
+            Relationships::Peers
+        } else if as_obj.providers().iter().any(|as_ref| as_ref.asn == *neighbor_asn) {
+            Relationships::Providers
+        } else {
+            Relationships::Unknown
+        }
+    }
+
+
+    pub fn should_propagate(&self, ann: &Announcement, recv_relationship: Relationships) -> bool {
+        !ann.only_to_customers.unwrap_or(false) || 
+        recv_relationship == Relationships::Customers ||
+        recv_relationship == Relationships::Origin
+    }
+
+    pub fn should_propagate_to_rel(&self, ann: &Announcement, rel: Relationships) -> bool {
+        self.extension.should_propagate(ann, ann.recv_relationship, rel)
+    }
+
+    /// Originate `ann`, or withdraw the route this AS originated for its prefix
+    /// An empty seed path becomes just this AS; any other is kept as given, e.g. a forged one for tests,
+    /// and `copy_for_export` extends it with this AS if it doesn't start with it
+    pub fn seed_ann(&mut self, mut ann: Announcement) {
+        if ann.as_path.is_empty() && !ann.withdraw {
+            ann.as_path = ASPath::from(vec![self.asn]);
+        }
+        ann.next_hop_asn = self.asn;
+        ann.recv_relationship = Relationships::Origin;
+        
+        if ann.withdraw {
+            // For withdrawals, remove from local RIB
+            self.local_rib.remove(&ann.prefix);
+        } else {
+            // For announcements, put in local RIB
+            self.local_rib.insert(ann.prefix, ann);
+        }
+    }
+}
+
+pub struct PolicyStore {
+    policies: HashMap<ASN, Policy>,
+    /// Shared by every policy the store creates
+    pub prefix_table: Arc<PrefixTable>,
+}
+
+impl PolicyStore {
+    pub fn new() -> Self {
+        PolicyStore {
+            policies: HashMap::new(),
+            prefix_table: Arc::new(PrefixTable::new()),
+        }
+    }
+
+    pub fn create_policy(&mut self, asn: ASN) -> &mut Policy {
+        let prefix_table = &self.prefix_table;
+        self.policies.entry(asn)
+            .or_insert_with(|| Policy::with_prefix_table(asn, Settings::BaseDefense, prefix_table.clone()))
+    }
+
+    pub fn get(&self, asn: &ASN) -> Option<&Policy> {
+        self.policies.get(asn)
+    }
+
+    pub fn get_mut(&mut self, asn: &ASN) -> Option<&mut Policy> {
+        self.policies.get_mut(asn)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&ASN, &Policy)> {
+        self.policies.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&ASN, &mut Policy)> {
+        self.policies.iter_mut()
+    }
+}
+
+impl Default for PolicyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub use ipnetwork;
\ No newline at end of file
@@ -0,0 +1,229 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Weak};
+
+use smallvec::SmallVec;
+
+use crate::as_graphs::as_graph::ASN;
+
+/// ASNs of a path held outside an `ASPath`, inline unless the path is unusually long
+pub type PathASNs = SmallVec<[ASN; 8]>;
+
+/// Immutable AS path stored as hash-consed nodes, each holding one ASN and the rest of the path
+///
+/// Announcements are copied into ribs_in, the local RIB and ribs_out at every hop, so cloning only
+/// bumps a reference count. Prepending adds one node in front of the existing path, and identical
+/// nodes are interned, so every path through an AS shares that AS's path and memory scales with the
+/// number of unique paths rather than with announcements times path length.
+#[derive(Clone, Default)]
+pub struct ASPath(Option<Arc<PathNode>>);
+
+pub struct PathNode {
+    asn: ASN,
+    origin: ASN,
+    len: usize,
+    rest: ASPath,
+}
+
+/// Live nodes by (ASN, address of the rest of the path), per thread so interning never locks
+///
+/// A key's address can't be reused while its node is alive, since the node owns the rest of the path
+#[derive(Default)]
+struct PathInterner {
+    nodes: HashMap<(ASN, usize), Weak<PathNode>>,
+    prune_at: usize,
+}
+
+impl PathInterner {
+    const MIN_PRUNE_AT: usize = 1024;
+
+    fn intern(&mut self, asn: ASN, rest: &ASPath) -> ASPath {
+        let key = (asn, rest.addr());
+        if let Some(node) = self.nodes.get(&key).and_then(Weak::upgrade) {
+            return ASPath(Some(node));
+        }
+        let node = ASPath::new_node(asn, rest);
+        self.nodes.insert(key, Arc::downgrade(&node));
+        if self.nodes.len() >= self.prune_at {
+            self.nodes.retain(|_, node| node.strong_count() > 0);
+            self.prune_at = (self.nodes.len() * 2).max(Self::MIN_PRUNE_AT);
+        }
+        ASPath(Some(node))
+    }
+}
+
+thread_local! {
+    static INTERNER: RefCell<PathInterner> = RefCell::new(PathInterner::default());
+}
+
+impl ASPath {
+    pub fn new() -> Self {
+        ASPath::default()
+    }
+
+    fn new_node(asn: ASN, rest: &ASPath) -> Arc<PathNode> {
+        Arc::new(PathNode {
+            asn,
+            origin: rest.last().copied().unwrap_or(asn),
+            len: rest.len() + 1,
+            rest: rest.clone(),
+        })
+    }
+
+    fn addr(&self) -> usize {
+        self.0.as_ref().map_or(0, |node| Arc::as_ptr(node) as usize)
+    }
+
+    /// New path with `asn` in front of this one, sharing this one's nodes
+    pub fn prepend(&self, asn: ASN) -> ASPath {
+        // Interning is skipped while the thread is shutting down
+        INTERNER.try_with(|interner| interner.borrow_mut().intern(asn, self))
+            .unwrap_or_else(|_| ASPath(Some(Self::new_node(asn, self))))
+    }
+
+    /// Path without the first ASN
+    pub fn without_first(&self) -> ASPath {
+        self.0.as_ref().map(|node| node.rest.clone()).unwrap_or_default()
+    }
+
+    /// New path with each run of a prepended ASN collapsed to one hop, e.g. `[1, 2, 2, 2]` to `[1, 2]`
+    pub fn without_prepending(&self) -> ASPath {
+        let mut asns = self.to_path_asns();
+        asns.dedup();
+        ASPath::from(&asns[..])
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.as_ref().map_or(0, |node| node.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+
+    /// The most recent hop
+    pub fn first(&self) -> Option<&ASN> {
+        self.0.as_ref().map(|node| &node.asn)
+    }
+
+    /// The origin, without walking the path
+    pub fn last(&self) -> Option<&ASN> {
+        self.0.as_ref().map(|node| &node.origin)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&ASN> {
+        self.iter().nth(index)
+    }
+
+    pub fn contains(&self, asn: &ASN) -> bool {
+        self.iter().any(|hop| hop == asn)
+    }
+
+    /// ASNs from the most recent hop to the origin
+    pub fn iter(&self) -> ASPathIter<'_> {
+        ASPathIter { node: self.0.as_deref() }
+    }
+
+    pub fn to_vec(&self) -> Vec<ASN> {
+        self.iter().copied().collect()
+    }
+
+    /// `to_vec` without a heap allocation for typical path lengths
+    pub fn to_path_asns(&self) -> PathASNs {
+        self.iter().copied().collect()
+    }
+
+    /// Whether both paths are the same nodes, which interning makes true of equal paths built on one thread
+    pub fn ptr_eq(&self, other: &ASPath) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+pub struct ASPathIter<'a> {
+    node: Option<&'a PathNode>,
+}
+
+impl<'a> Iterator for ASPathIter<'a> {
+    type Item = &'a ASN;
+
+    fn next(&mut self) -> Option<&'a ASN> {
+        let node = self.node?;
+        self.node = node.rest.0.as_deref();
+        Some(&node.asn)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.node.map_or(0, |node| node.len);
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for ASPathIter<'_> {}
+
+impl<'a> IntoIterator for &'a ASPath {
+    type Item = &'a ASN;
+    type IntoIter = ASPathIter<'a>;
+
+    fn into_iter(self) -> ASPathIter<'a> {
+        self.iter()
+    }
+}
+
+impl From<Vec<ASN>> for ASPath {
+    fn from(asns: Vec<ASN>) -> Self {
+        ASPath::from(&asns[..])
+    }
+}
+
+impl From<&[ASN]> for ASPath {
+    fn from(asns: &[ASN]) -> Self {
+        asns.iter().rev().fold(ASPath::new(), |path, &asn| path.prepend(asn))
+    }
+}
+
+impl FromIterator<ASN> for ASPath {
+    fn from_iter<I: IntoIterator<Item = ASN>>(iter: I) -> Self {
+        ASPath::from(&iter.into_iter().collect::<PathASNs>()[..])
+    }
+}
+
+impl PartialEq for ASPath {
+    fn eq(&self, other: &ASPath) -> bool {
+        self.ptr_eq(other) || (self.len() == other.len() && self.iter().eq(other.iter()))
+    }
+}
+
+impl Eq for ASPath {}
+
+impl Hash for ASPath {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        self.iter().for_each(|asn| asn.hash(state));
+    }
+}
+
+impl PartialEq<[ASN]> for ASPath {
+    fn eq(&self, other: &[ASN]) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl PartialEq<Vec<ASN>> for ASPath {
+    fn eq(&self, other: &Vec<ASN>) -> bool {
+        *self == other[..]
+    }
+}
+
+impl PartialEq<ASPath> for Vec<ASN> {
+    fn eq(&self, other: &ASPath) -> bool {
+        *other == self[..]
+    }
+}
+
+impl fmt::Debug for ASPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
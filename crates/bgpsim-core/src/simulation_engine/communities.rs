@@ -0,0 +1,44 @@
+use crate::as_graphs::as_graph::ASN;
+use crate::shared::Relationships;
+
+/// What an action community asks the AS it is addressed to do when exporting the route
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommunityAction {
+    /// Prepend the AS's own ASN `times` extra times when exporting to neighbors of this relationship
+    Prepend { times: u8, to: Relationships },
+    /// Don't export the route to this AS
+    NoExportTo(ASN),
+    /// Remotely triggered blackholing: drop traffic for the prefix and don't export the route
+    Blackhole,
+}
+
+/// Customer-to-provider action community, e.g. "AS 3356: prepend 3x to peers"
+///
+/// Only the target AS acts on it, and only for routes it learned from a customer,
+/// as providers only offer traffic engineering communities to their customers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ActionCommunity {
+    pub target_asn: ASN,
+    pub action: CommunityAction,
+}
+
+impl ActionCommunity {
+    pub fn new(target_asn: ASN, action: CommunityAction) -> Self {
+        ActionCommunity { target_asn, action }
+    }
+
+    /// Ask `target_asn` to prepend itself `times` extra times towards neighbors of `to`
+    pub fn prepend(target_asn: ASN, times: u8, to: Relationships) -> Self {
+        ActionCommunity::new(target_asn, CommunityAction::Prepend { times, to })
+    }
+
+    /// Ask `target_asn` to blackhole traffic for the prefix (RTBH)
+    pub fn blackhole(target_asn: ASN) -> Self {
+        ActionCommunity::new(target_asn, CommunityAction::Blackhole)
+    }
+
+    /// Ask `target_asn` not to export the route to `neighbor_asn`
+    pub fn no_export_to(target_asn: ASN, neighbor_asn: ASN) -> Self {
+        ActionCommunity::new(target_asn, CommunityAction::NoExportTo(neighbor_asn))
+    }
+}
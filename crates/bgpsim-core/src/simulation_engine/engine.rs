@@ -0,0 +1,992 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::as_graphs::as_graph::{AS, ASGraph, ASN};
+use crate::aspa_validator::AspaValidator;
+use crate::irr_validator::IrrValidator;
+use crate::route_validator::RouteValidator;
+use crate::simulation_engine::{Announcement, Prefix};
+use crate::simulation_engine::announcement::{PolicyStore, AnnInfo};
+use crate::simulation_engine::observer::EngineObserver;
+use crate::simulation_engine::policy::{TieBreaker, ValidationResult};
+#[cfg(feature = "enum-dispatch")]
+use crate::simulation_engine::policy::PolicyExtension;
+use crate::simulation_engine::prefix_table::{PrefixId, PrefixMap};
+use crate::simulation_engine::round_diff::{LocalRIBSnapshot, RoundDiff};
+use crate::simulation_engine::snapshot::EngineSnapshot;
+use crate::shared::Relationships;
+
+/// Neighbors an origin exports a seeded prefix to, keyed by (origin ASN, prefix)
+/// Seeds without an entry are exported to every neighbor
+pub type ExportRestrictions = HashMap<(ASN, Prefix), HashSet<ASN>>;
+
+/// How many announcements each AS rejected, by reason
+pub type RejectionStats = HashMap<ASN, HashMap<ValidationResult, usize>>;
+
+/// Announcement queued for a neighbor, with the relationship the neighbor receives it over
+type Outgoing = (ASN, Announcement, Relationships);
+
+/// Which routing tables policies keep while propagating
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PolicyStorageMode {
+    /// RIBs-in, RIBs-out and the local RIB, as in the Python simulator's BGPPolicy
+    #[default]
+    Full,
+    /// Only the local RIB, as in the Python simulator's BGPSimplePolicy
+    /// Cuts memory and time on CAIDA-sized graphs, but withdrawals are ignored
+    LocalRibOnly,
+}
+
+/// How the engine treats announcements whose next hop isn't a neighbor of the receiving AS over the
+/// relationship they arrived on, e.g. ones queued by hand with `Policy::receive_ann`
+///
+/// Checked before the policy sees them, so it doesn't depend on the policy enforcing the first AS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NextHopVerification {
+    /// Leave them to the policy, which accepts them unless it checks the first AS itself
+    Off,
+    /// Drop them as `ValidationResult::RejectedUnresolvableNextHop`
+    #[default]
+    Reject,
+    /// Drop them like `Reject`, and fail `try_run` with every one dropped and every seed at an AS not in the graph
+    Strict,
+}
+
+impl std::fmt::Display for NextHopVerification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            NextHopVerification::Off => "Off",
+            NextHopVerification::Reject => "Reject",
+            NextHopVerification::Strict => "Strict",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::str::FromStr for NextHopVerification {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Off" => Ok(NextHopVerification::Off),
+            "Reject" => Ok(NextHopVerification::Reject),
+            "Strict" => Ok(NextHopVerification::Strict),
+            _ => Err(format!("Unknown next hop verification: {}", s)),
+        }
+    }
+}
+
+/// One of the three phases of a propagation round, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PropagationPhase {
+    /// Customers export to their providers
+    #[default]
+    Providers,
+    Peers,
+    /// Providers export to their customers, finishing the round
+    Customers,
+}
+
+impl PropagationPhase {
+    /// Phase that runs after this one, wrapping around to the next round
+    pub fn next(self) -> Self {
+        match self {
+            PropagationPhase::Providers => PropagationPhase::Peers,
+            PropagationPhase::Peers => PropagationPhase::Customers,
+            PropagationPhase::Customers => PropagationPhase::Providers,
+        }
+    }
+}
+
+pub struct SimulationEngine<'a> {
+    pub as_graph: &'a ASGraph,
+    pub policy_store: PolicyStore,
+    pub origin_export_restrictions: ExportRestrictions,
+    pub storage_mode: PolicyStorageMode,
+    pub next_hop_verification: NextHopVerification,
+    /// What `NextHopVerification::Strict` caught since the last `setup`
+    next_hop_errors: Vec<String>,
+    observers: Vec<Box<dyn EngineObserver>>,
+    rejection_stats: Option<RejectionStats>,
+    /// Number of announcements `deliver` queued for each prefix that haven't been processed yet
+    /// Phases with none for an active prefix are skipped
+    pending_anns: HashMap<Prefix, usize>,
+    /// If set, phases only process announcements for these prefixes
+    active_prefixes: Option<HashSet<Prefix>>,
+    /// ASPA objects shared by every policy, handed to them in `setup`
+    aspa_validator: Option<Arc<AspaValidator>>,
+    /// IRR route objects shared by every policy, handed to them in `setup`
+    irr_validator: Option<Arc<IrrValidator>>,
+    /// ROAs shared with every ROV-family policy
+    route_validator: Option<Arc<RouteValidator>>,
+    /// Local RIB changes of each round since the last `setup`, if enabled with `with_round_diffs`
+    round_diffs: Option<Vec<RoundDiff>>,
+    /// Local RIBs when the current round started, while recording round diffs
+    round_start_snapshot: Option<LocalRIBSnapshot>,
+    /// Phase `step` runs next; `run` finishes a round `step` left partway
+    next_phase: PropagationPhase,
+    /// Rounds finished since the last `setup`, numbering the rounds `step` runs
+    rounds_finished: u32,
+    /// How every policy breaks ties, handed to them in `setup`
+    pub tie_breaker: TieBreaker,
+    /// Links taken down since the last `setup`, as (lower ASN, higher ASN)
+    failed_links: HashSet<(ASN, ASN)>,
+    /// ASes taken down since the last `setup`
+    failed_asns: HashSet<ASN>,
+}
+
+impl<'a> SimulationEngine<'a> {
+    pub fn new(as_graph: &'a ASGraph) -> Self {
+        let mut policy_store = PolicyStore::new();
+        
+        // Create policies for all ASes
+        for asn in as_graph.asns() {
+            let policy = policy_store.create_policy(asn);
+            if let Some(as_obj) = as_graph.get(&asn) {
+                policy.ribs_in.set_neighbor_capacity(as_obj.neighbors().count());
+            }
+        }
+        
+        SimulationEngine {
+            as_graph,
+            policy_store,
+            origin_export_restrictions: HashMap::new(),
+            storage_mode: PolicyStorageMode::Full,
+            next_hop_verification: NextHopVerification::default(),
+            next_hop_errors: Vec::new(),
+            observers: Vec::new(),
+            rejection_stats: None,
+            pending_anns: HashMap::new(),
+            active_prefixes: None,
+            aspa_validator: None,
+            irr_validator: None,
+            route_validator: None,
+            round_diffs: None,
+            round_start_snapshot: None,
+            next_phase: PropagationPhase::Providers,
+            rounds_finished: 0,
+            tie_breaker: TieBreaker::default(),
+            failed_links: HashSet::new(),
+            failed_asns: HashSet::new(),
+        }
+    }
+    
+    pub fn with_storage_mode(mut self, storage_mode: PolicyStorageMode) -> Self {
+        self.storage_mode = storage_mode;
+        self
+    }
+    
+    pub fn with_tie_breaker(mut self, tie_breaker: TieBreaker) -> Self {
+        self.tie_breaker = tie_breaker;
+        self
+    }
+    
+    pub fn with_next_hop_verification(mut self, verification: NextHopVerification) -> Self {
+        self.next_hop_verification = verification;
+        self
+    }
+    
+    /// Count rejected announcements per AS and reason while propagating, see `rejection_stats`
+    pub fn with_rejection_stats(mut self) -> Self {
+        self.rejection_stats = Some(HashMap::new());
+        self
+    }
+    
+    /// Rejections counted since the last `setup`, if enabled with `with_rejection_stats`
+    /// ASes that rejected nothing have no entry
+    pub fn rejection_stats(&self) -> Option<&RejectionStats> {
+        self.rejection_stats.as_ref()
+    }
+    
+    /// Record how every AS's local RIB changes in each round, see `get_round_diffs`
+    /// Snapshots every local RIB twice a round, so it's meant for debugging small graphs
+    pub fn with_round_diffs(mut self) -> Self {
+        self.round_diffs = Some(Vec::new());
+        self
+    }
+    
+    /// Diffs of the rounds run since the last `setup`, if enabled with `with_round_diffs`
+    pub fn get_round_diffs(&self) -> Option<&[RoundDiff]> {
+        self.round_diffs.as_deref()
+    }
+    
+    /// Only propagate announcements for `prefixes`, see `set_active_prefixes`
+    pub fn with_active_prefixes(mut self, prefixes: HashSet<Prefix>) -> Self {
+        self.active_prefixes = Some(prefixes);
+        self
+    }
+    
+    /// Restrict propagation to `prefixes`, or lift the restriction with None
+    /// Announcements for other prefixes stay queued until their prefix is active again
+    pub fn set_active_prefixes(&mut self, prefixes: Option<HashSet<Prefix>>) {
+        self.active_prefixes = prefixes;
+    }
+    
+    /// Prefixes with announcements still queued, i.e. that haven't converged
+    pub fn pending_prefixes(&self) -> HashSet<Prefix> {
+        self.pending_anns.keys().copied().collect()
+    }
+    
+    /// Validate ASPA adopters' routes against `aspa_validator`, see `set_aspa_validator`
+    pub fn with_aspa_validator(mut self, aspa_validator: Arc<AspaValidator>) -> Self {
+        self.aspa_validator = Some(aspa_validator);
+        self
+    }
+    
+    /// Share `aspa_validator` with every policy, from the next `setup` on
+    pub fn set_aspa_validator(&mut self, aspa_validator: Arc<AspaValidator>) {
+        self.aspa_validator = Some(aspa_validator);
+    }
+    
+    pub fn aspa_validator(&self) -> Option<&AspaValidator> {
+        self.aspa_validator.as_deref()
+    }
+    
+    /// Filter IRR filtering adopters' customer routes against `irr_validator`, see `set_irr_validator`
+    pub fn with_irr_validator(mut self, irr_validator: Arc<IrrValidator>) -> Self {
+        self.irr_validator = Some(irr_validator);
+        self
+    }
+    
+    /// Share `irr_validator` with every policy, from the next `setup` on
+    pub fn set_irr_validator(&mut self, irr_validator: Arc<IrrValidator>) {
+        self.irr_validator = Some(irr_validator);
+    }
+    
+    pub fn irr_validator(&self) -> Option<&IrrValidator> {
+        self.irr_validator.as_deref()
+    }
+    
+    /// Validate ROV adopters' routes against `route_validator`, see `set_route_validator`
+    pub fn with_route_validator(mut self, route_validator: Arc<RouteValidator>) -> Self {
+        self.set_route_validator(route_validator);
+        self
+    }
+    
+    /// Share `route_validator` with every policy, replacing the ROAs they validated against so far
+    ///
+    /// Takes effect immediately, so calling it between rounds changes validation mid-run and
+    /// every AS reselects its routes at the start of the next phase;
+    /// policies whose extension is replaced later receive it on the next `setup`
+    pub fn set_route_validator(&mut self, route_validator: Arc<RouteValidator>) {
+        for (_, policy) in self.policy_store.iter_mut() {
+            policy.extension.set_route_validator(Arc::clone(&route_validator));
+            // Routes ranked by validity, e.g. under `ROVMode::DeprefInvalid`, may rank differently now
+            policy.selection_stale = true;
+        }
+        self.route_validator = Some(route_validator);
+    }
+    
+    pub fn route_validator(&self) -> Option<&RouteValidator> {
+        self.route_validator.as_deref()
+    }
+    
+    /// Have `observer` called back during propagation, after any observers added before it
+    pub fn add_observer(&mut self, observer: Box<dyn EngineObserver>) {
+        self.observers.push(observer);
+    }
+    
+    /// Only export `prefix` from `origin_asn` to the given neighbors (e.g. a subset of its providers)
+    /// Must be called before `setup` seeds the announcement
+    pub fn restrict_origin_export(&mut self, origin_asn: ASN, prefix: Prefix, neighbor_asns: HashSet<ASN>) {
+        self.origin_export_restrictions.insert((origin_asn, prefix), neighbor_asns);
+    }
+
+    pub fn setup(&mut self, initial_announcements: Vec<(ASN, Announcement)>) {
+        // Clear all policies so a reused engine doesn't leak state between trials
+        for (_, policy) in self.policy_store.iter_mut() {
+            policy.reset();
+            policy.tie_breaker = self.tie_breaker;
+            if let Some(aspa_validator) = &self.aspa_validator {
+                policy.extension.set_aspa_validator(Arc::clone(aspa_validator));
+            }
+            if let Some(irr_validator) = &self.irr_validator {
+                policy.extension.set_irr_validator(Arc::clone(irr_validator));
+            }
+            if let Some(route_validator) = &self.route_validator {
+                policy.extension.set_route_validator(Arc::clone(route_validator));
+            }
+        }
+        if let Some(stats) = self.rejection_stats.as_mut() {
+            stats.clear();
+        }
+        if let Some(diffs) = self.round_diffs.as_mut() {
+            diffs.clear();
+        }
+        self.round_start_snapshot = None;
+        self.next_phase = PropagationPhase::Providers;
+        self.rounds_finished = 0;
+        self.pending_anns.clear();
+        self.failed_links.clear();
+        self.failed_asns.clear();
+        self.next_hop_errors.clear();
+
+        // Seed initial announcements
+        for (asn, ann) in initial_announcements {
+            match self.policy_store.get_mut(&asn) {
+                Some(policy) => policy.seed_ann(ann),
+                None => {
+                    tracing::warn!(asn, prefix = %ann.prefix, "Ignoring seed at an AS not in the graph");
+                    if self.next_hop_verification == NextHopVerification::Strict {
+                        self.next_hop_errors.push(format!("Seed for {} at AS {}, which is not in the graph", ann.prefix, asn));
+                    }
+                }
+            }
+        }
+        
+        // Do initial propagation of seeded announcements
+        self.propagate_seeded_announcements();
+    }
+    
+    fn propagate_seeded_announcements(&mut self) {
+        // Collect ASes that have announcements to propagate
+        let mut asns_with_anns = Vec::new();
+        for (asn, policy) in self.policy_store.iter() {
+            if !policy.local_rib.is_empty() {
+                asns_with_anns.push(*asn);
+            }
+        }
+        
+        // Propagate from each AS
+        for asn in asns_with_anns {
+            let mut anns_to_propagate = Vec::new();
+            
+            if let Some(policy) = self.policy_store.get(&asn) {
+                // For each announcement in local RIB, propagate to neighbors
+                for ann in policy.local_rib.values() {
+                    anns_to_propagate.extend(self.seed_exports(asn, ann));
+                }
+            }
+            
+            self.deliver(anns_to_propagate);
+        }
+    }
+    
+    /// Copies of a seeded announcement for each neighbor the origin exports it to
+    fn seed_exports(&self, asn: ASN, ann: &Announcement) -> Vec<Outgoing> {
+        let as_obj = match self.as_graph.get(&asn) {
+            Some(obj) => obj,
+            None => return Vec::new(),
+        };
+        let export_restriction = self.origin_export_restrictions.get(&(asn, ann.prefix));
+        let mut exports = Vec::new();
+        
+        // Check propagation to each relationship type
+        for rel in [Relationships::Customers, Relationships::Peers, Relationships::Providers] {
+            for neighbor_as in as_obj.get_neighbors(rel) {
+                let neighbor_asn = neighbor_as.asn;
+                if export_restriction.is_some_and(|allowed| !allowed.contains(&neighbor_asn)) {
+                    continue;
+                }
+                if link_failed(&self.failed_links, asn, neighbor_asn) {
+                    continue;
+                }
+                let recv_rel_for_neighbor = rel.invert();
+                
+                let new_ann = ann.copy_for_export(as_obj.asn, recv_rel_for_neighbor);
+                exports.push((neighbor_asn, new_ann, recv_rel_for_neighbor));
+            }
+        }
+        exports
+    }
+    
+    /// Queue announcements at their receivers
+    fn deliver(&mut self, anns_to_propagate: Vec<Outgoing>) {
+        for (neighbor_asn, new_ann, rel) in anns_to_propagate {
+            if let Some(neighbor_policy) = self.policy_store.get_mut(&neighbor_asn) {
+                *self.pending_anns.entry(new_ann.prefix).or_insert(0) += 1;
+                neighbor_policy.receive_ann(new_ann, rel);
+            }
+        }
+    }
+    
+    /// Originate or withdraw announcements on an engine that has already run, keeping its state unlike `setup`
+    /// Withdrawals need `PolicyStorageMode::Full`; call `run` afterwards to propagate
+    pub fn inject(&mut self, announcements: Vec<(ASN, Announcement)>) -> Result<(), String> {
+        if self.storage_mode == PolicyStorageMode::LocalRibOnly && announcements.iter().any(|(_, ann)| ann.withdraw) {
+            return Err("Withdrawals need PolicyStorageMode::Full".to_string());
+        }
+        
+        for (asn, ann) in announcements {
+            if self.failed_asns.contains(&asn) {
+                return Err(format!("AS {} has failed", asn));
+            }
+            let policy = self.policy_store.get_mut(&asn)
+                .ok_or_else(|| format!("AS {} is not in the graph", asn))?;
+            let prefix = ann.prefix;
+            policy.seed_ann(ann.clone());
+            
+            let exports = match policy.local_rib.get(&prefix) {
+                Some(seeded) if !ann.withdraw => {
+                    let seeded = seeded.clone();
+                    self.seed_exports(asn, &seeded)
+                }
+                // Neighbors without a route from us ignore the withdrawal
+                _ => self.seed_exports(asn, &ann),
+            };
+            self.deliver(exports);
+        }
+        Ok(())
+    }
+
+    /// Take down the link between two neighbors, as if the session was torn down
+    ///
+    /// Routes learned over the link are withdrawn implicitly: each side gets a withdrawal from the
+    /// other for every route in its RIBs-in, processed in the next round. Announcements still
+    /// queued over the link are lost. Needs `PolicyStorageMode::Full`, and lasts until `setup`
+    pub fn fail_link(&mut self, asn1: ASN, asn2: ASN) -> Result<(), String> {
+        if self.storage_mode == PolicyStorageMode::LocalRibOnly {
+            return Err("Link failures need PolicyStorageMode::Full".to_string());
+        }
+        let as_obj = self.as_graph.get(&asn1).ok_or_else(|| format!("AS {} is not in the graph", asn1))?;
+        if !as_obj.neighbors().any(|neighbor| neighbor.asn == asn2) {
+            return Err(format!("AS {} and AS {} are not neighbors", asn1, asn2));
+        }
+        if !self.failed_links.insert((asn1.min(asn2), asn1.max(asn2))) {
+            return Ok(());
+        }
+        
+        for (asn, neighbor_asn) in [(asn1, asn2), (asn2, asn1)] {
+            let policy = match self.policy_store.get_mut(&asn) {
+                Some(policy) => policy,
+                None => continue,
+            };
+            let (lost, kept): (Vec<AnnInfo>, Vec<AnnInfo>) = policy.recv_q.drain(..)
+                .partition(|ann_info| ann_info.ann.next_hop_asn == neighbor_asn);
+            policy.recv_q = kept.into();
+            policy.ribs_out.remove(&neighbor_asn);
+            let withdrawals: Vec<Outgoing> = policy.ribs_in.neighbor_routes(neighbor_asn)
+                .map(|ann| {
+                    let mut withdrawal = ann.clone();
+                    withdrawal.withdraw = true;
+                    (asn, withdrawal, ann.recv_relationship)
+                })
+                .collect();
+            
+            for ann_info in lost {
+                self.unqueue(&ann_info.ann.prefix);
+            }
+            self.deliver(withdrawals);
+        }
+        tracing::debug!(asn1, asn2, "Link failed");
+        Ok(())
+    }
+    
+    /// Take down an AS with all its links, dropping its routes including those it originated
+    /// Its neighbors withdraw what they learned from it as in `fail_link`
+    pub fn fail_as(&mut self, asn: ASN) -> Result<(), String> {
+        let as_obj = self.as_graph.get(&asn).ok_or_else(|| format!("AS {} is not in the graph", asn))?;
+        let neighbor_asns: Vec<ASN> = as_obj.neighbors().map(|neighbor| neighbor.asn).collect();
+        for neighbor_asn in neighbor_asns {
+            self.fail_link(asn, neighbor_asn)?;
+        }
+        // Its links are down, so whatever is still queued at it only came from the withdrawals above
+        if let Some(policy) = self.policy_store.get_mut(&asn) {
+            let queued: Vec<Prefix> = policy.recv_q.iter().map(|ann_info| ann_info.ann.prefix).collect();
+            policy.reset();
+            for prefix in queued {
+                self.unqueue(&prefix);
+            }
+        }
+        self.failed_asns.insert(asn);
+        Ok(())
+    }
+    
+    pub fn is_link_failed(&self, asn1: ASN, asn2: ASN) -> bool {
+        link_failed(&self.failed_links, asn1, asn2)
+    }
+    
+    pub fn is_as_failed(&self, asn: ASN) -> bool {
+        self.failed_asns.contains(&asn)
+    }
+    
+    /// Forget one queued announcement for `prefix` that won't be processed
+    fn unqueue(&mut self, prefix: &Prefix) {
+        if let Some(count) = self.pending_anns.get_mut(prefix) {
+            *count -= 1;
+            if *count == 0 {
+                self.pending_anns.remove(prefix);
+            }
+        }
+    }
+
+    pub fn run(&mut self, rounds: u32) {
+        for round in 0..rounds {
+            self.propagate_round(round);
+        }
+    }
+
+    /// Run one round, numbered by the rounds finished since `setup`, e.g. between scheduled events
+    pub fn run_round(&mut self) {
+        self.propagate_round(self.rounds_finished);
+    }
+    
+    /// Rounds finished since the last `setup`
+    pub fn rounds_finished(&self) -> u32 {
+        self.rounds_finished
+    }
+
+    /// Run the engine, stopping after the first round in which a guarded policy extension broke its limits
+    /// or, with `NextHopVerification::Strict`, an announcement came from a next hop that isn't a neighbor
+    pub fn try_run(&mut self, rounds: u32) -> Result<(), String> {
+        self.check_next_hops()?;
+        for round in 0..rounds {
+            self.propagate_round(round);
+            self.check_policy_guards()?;
+            self.check_next_hops()?;
+        }
+        Ok(())
+    }
+    
+    /// Fail with every unresolvable next hop and misplaced seed `NextHopVerification::Strict` caught since `setup`
+    pub fn check_next_hops(&self) -> Result<(), String> {
+        if self.next_hop_errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.next_hop_errors.join("\n"))
+        }
+    }
+    
+    /// Fail with the diagnostic of every guarded policy extension that broke its limits, by ASN
+    pub fn check_policy_guards(&self) -> Result<(), String> {
+        let mut violations: Vec<(ASN, String)> = self.policy_store.iter()
+            .filter_map(|(&asn, policy)| {
+                policy.extension.violation()
+                    .map(|violation| (asn, format!("AS {} ({}): {}", asn, policy.extension.name(), violation)))
+            })
+            .collect();
+        if violations.is_empty() {
+            return Ok(());
+        }
+        violations.sort();
+        Err(violations.into_iter().map(|(_, violation)| violation).collect::<Vec<_>>().join("\n"))
+    }
+
+    /// Run the engine, recording how every AS's local RIB changed in each round
+    /// Diffs that never become empty point to oscillation or non-convergence
+    pub fn diff_rounds(&mut self, rounds: u32) -> Vec<RoundDiff> {
+        let mut diffs = Vec::new();
+        let mut before = self.get_local_rib_snapshot();
+        
+        for round in 0..rounds {
+            self.propagate_round(round);
+            let after = self.get_local_rib_snapshot();
+            diffs.push(RoundDiff::between(round, &before, &after));
+            before = after;
+        }
+        
+        diffs
+    }
+
+    /// Run exactly one propagation phase, returning which one ran
+    /// Lets notebooks and GUIs watch propagation between phases, see `pending_announcements`
+    pub fn step(&mut self) -> PropagationPhase {
+        self.propagate_phase(self.rounds_finished)
+    }
+    
+    /// Phase the next `step` runs
+    pub fn next_phase(&self) -> PropagationPhase {
+        self.next_phase
+    }
+    
+    /// Announcements queued at each AS, in the order the next phase processes them
+    /// ASes with nothing queued have no entry
+    pub fn pending_announcements(&self) -> BTreeMap<ASN, Vec<&AnnInfo>> {
+        self.policy_store.iter()
+            .filter(|(_, policy)| !policy.recv_q.is_empty())
+            .map(|(&asn, policy)| (asn, policy.recv_q.iter().collect()))
+            .collect()
+    }
+
+    /// Run the phases left in the current round, i.e. all three unless `step` stopped partway
+    fn propagate_round(&mut self, round: u32) {
+        while self.propagate_phase(round) != PropagationPhase::Customers {}
+    }
+    
+    fn propagate_phase(&mut self, round: u32) -> PropagationPhase {
+        let _span = tracing::debug_span!("round", round).entered();
+        let phase = self.next_phase;
+        if phase == PropagationPhase::Providers {
+            for observer in self.observers.iter_mut() {
+                observer.on_round_start(round);
+            }
+            if self.round_diffs.is_some() {
+                self.round_start_snapshot = Some(self.get_local_rib_snapshot());
+            }
+        }
+        
+        self.reselect_stale_policies();
+        
+        // Three-phase propagation following Gao-Rexford model
+        // Phases with nothing queued for an active prefix are skipped, so converged prefixes cost nothing
+        if let Some(prefixes) = self.phase_prefixes() {
+            match phase {
+                PropagationPhase::Providers => {
+                    tracing::trace_span!("providers").in_scope(|| self.propagate_to_providers(&prefixes))
+                }
+                PropagationPhase::Peers => tracing::trace_span!("peers").in_scope(|| self.propagate_to_peers(&prefixes)),
+                PropagationPhase::Customers => {
+                    tracing::trace_span!("customers").in_scope(|| self.propagate_to_customers(&prefixes))
+                }
+            }
+        }
+        
+        if phase == PropagationPhase::Customers {
+            if let Some(before) = self.round_start_snapshot.take() {
+                let diff = RoundDiff::between(round, &before, &self.get_local_rib_snapshot());
+                self.round_diffs.get_or_insert_with(Vec::new).push(diff);
+            }
+            self.rounds_finished += 1;
+            tracing::debug!("Round complete");
+        }
+        self.next_phase = phase.next();
+        phase
+    }
+    
+    /// Reselect every prefix of the policies marked `selection_stale`, e.g. by a LOCAL_PREF or ROA change,
+    /// and queue what changed for their neighbors
+    /// Only full storage keeps the routes to reselect from, so otherwise the mark is just cleared
+    fn reselect_stale_policies(&mut self) {
+        let mut stale_asns: Vec<ASN> = self.policy_store.iter()
+            .filter(|(_, policy)| policy.selection_stale)
+            .map(|(&asn, _)| asn)
+            .collect();
+        // Sorted so the queued updates don't depend on hash order
+        stale_asns.sort_unstable();
+        let as_graph = self.as_graph;
+        for asn in stale_asns {
+            let Some(policy) = self.policy_store.get_mut(&asn) else { continue };
+            policy.selection_stale = false;
+            let Some(as_obj) = as_graph.get(&asn) else { continue };
+            if self.storage_mode != PolicyStorageMode::Full {
+                continue;
+            }
+            let prefix_table = Arc::clone(&policy.prefix_table);
+            for prefix_id in 0..prefix_table.len() as PrefixId {
+                let Some(policy) = self.policy_store.get(&asn) else { break };
+                if policy.originates_id(prefix_id) {
+                    continue;
+                }
+                let old_best = policy.local_rib.get_by_id(prefix_id).cloned();
+                let best_ann = policy.get_best_ann_for_id(prefix_id, as_obj).map(|best| best.copy_and_process(asn));
+                if best_ann == old_best {
+                    continue;
+                }
+                let Some(prefix) = prefix_table.prefix(prefix_id) else { continue };
+                let recv_relationship = best_ann.as_ref().map_or(Relationships::Unknown, |best| best.recv_relationship);
+                let anns_to_propagate = self.install_and_export(as_obj, prefix_id, prefix, best_ann, old_best, recv_relationship);
+                self.deliver(anns_to_propagate);
+            }
+        }
+    }
+    
+    /// Active prefixes with queued announcements, or None if there are none and the phase can be skipped
+    fn phase_prefixes(&self) -> Option<HashSet<Prefix>> {
+        let prefixes: HashSet<Prefix> = self.pending_anns.keys()
+            .filter(|prefix| self.active_prefixes.as_ref().is_none_or(|active| active.contains(prefix)))
+            .copied()
+            .collect();
+        (!prefixes.is_empty()).then_some(prefixes)
+    }
+
+    fn propagate_to_providers(&mut self, prefixes: &HashSet<Prefix>) {
+        // Process in reverse propagation rank order (leaves to roots)
+        let ranks = self.as_graph.propagation_ranks.clone();
+        
+        for rank_asns in ranks.iter().rev() {
+            self.process_asns_for_relationship(rank_asns, prefixes);
+        }
+    }
+
+    fn propagate_to_peers(&mut self, prefixes: &HashSet<Prefix>) {
+        // Process all ASes for peer relationships
+        let all_asns: Vec<ASN> = self.as_graph.asns().collect();
+        self.process_asns_for_relationship(&all_asns, prefixes);
+    }
+
+    fn propagate_to_customers(&mut self, prefixes: &HashSet<Prefix>) {
+        // Process in propagation rank order (roots to leaves)
+        let ranks = self.as_graph.propagation_ranks.clone();
+        
+        for rank_asns in ranks.iter() {
+            self.process_asns_for_relationship(rank_asns, prefixes);
+        }
+    }
+
+    /// Process the queued announcements of each AS in `asns`, only for `prefixes` if prefixes are restricted
+    fn process_asns_for_relationship(&mut self, asns: &[ASN], prefixes: &HashSet<Prefix>) {
+        let filter_prefixes = self.active_prefixes.is_some();
+        let as_graph = self.as_graph;
+        
+        // Process each AS's incoming announcements
+        for &asn in asns {
+            // Get AS object reference - no cloning needed
+            let as_obj = match as_graph.get(&asn) {
+                Some(obj) => obj,
+                None => continue,
+            };
+            
+            // Collect announcements from recv_q
+            let anns_to_process: Vec<AnnInfo> = match self.policy_store.get_mut(&asn) {
+                Some(policy) if filter_prefixes => {
+                    let (selected, deferred): (Vec<AnnInfo>, Vec<AnnInfo>) = policy.recv_q.drain(..)
+                        .partition(|ann_info| prefixes.contains(&ann_info.ann.prefix));
+                    policy.recv_q = deferred.into();
+                    selected
+                }
+                Some(policy) => policy.recv_q.drain(..).collect(),
+                None => continue,
+            };
+            
+            // Process the announcements, then send what they changed
+            for ann_info in anns_to_process {
+                self.unqueue(&ann_info.ann.prefix);
+                if self.next_hop_verification != NextHopVerification::Off && !next_hop_resolves(as_obj, &ann_info) {
+                    self.reject_unresolvable_next_hop(asn, &ann_info);
+                    continue;
+                }
+                let anns_to_propagate = match self.storage_mode {
+                    PolicyStorageMode::Full => self.process_ann_full(as_obj, ann_info),
+                    PolicyStorageMode::LocalRibOnly => self.process_ann_local_rib_only(as_obj, ann_info),
+                };
+                self.deliver(anns_to_propagate);
+            }
+        }
+    }
+    
+    /// Process a received announcement using RIBs-in and RIBs-out
+    /// Withdrawals, and updates the policy rejects, remove the neighbor's previous route for the prefix;
+    /// neighbors that were sent a route that is no longer exported to them get a withdrawal
+    fn process_ann_full(&mut self, as_obj: &AS, ann_info: AnnInfo) -> Vec<Outgoing> {
+        let asn = as_obj.asn;
+        let policy = match self.policy_store.get_mut(&asn) {
+            Some(policy) => policy,
+            None => return Vec::new(),
+        };
+        let AnnInfo { ann, recv_relationship } = ann_info;
+        let prefix = ann.prefix;
+        // Every RIB access below goes by id, so the prefix is only hashed here
+        let prefix_id = policy.prefix_table.intern(prefix);
+        // Only observers need the previous route
+        let old_best = if self.observers.is_empty() { None } else { policy.local_rib.get_by_id(prefix_id).cloned() };
+        
+        let valid = if ann.withdraw {
+            false
+        } else {
+            let result = policy.valid_ann_by_id(&ann, prefix_id, recv_relationship, as_obj);
+            Self::record_validation(&mut self.observers, &mut self.rejection_stats, asn, &ann, result);
+            result.is_accepted()
+        };
+        let sender_asn = ann.next_hop_asn;
+        let previous = if valid {
+            let mut ann = ann;
+            policy.apply_local_pref(&mut ann);
+            policy.ribs_in.insert_by_id(sender_asn, prefix_id, ann)
+        } else {
+            policy.ribs_in.remove_by_id(sender_asn, prefix_id)
+        };
+        if policy.originates_id(prefix_id) {
+            return Vec::new();
+        }
+        
+        let best_ann = policy.updated_best_for_id(prefix_id, sender_asn, previous.as_ref(), as_obj)
+            .map(|best| best.copy_and_process(asn));
+        self.install_and_export(as_obj, prefix_id, prefix, best_ann, old_best, recv_relationship)
+    }
+    
+    /// Install `best_ann` as the AS's route for the prefix, or drop its learned route if there is none,
+    /// then export the change: the route to the neighbors it's sent to, and withdrawals to those sent one before
+    fn install_and_export(
+        &mut self,
+        as_obj: &AS,
+        prefix_id: PrefixId,
+        prefix: Prefix,
+        best_ann: Option<Announcement>,
+        old_best: Option<Announcement>,
+        recv_relationship: Relationships,
+    ) -> Vec<Outgoing> {
+        let asn = as_obj.asn;
+        let policy = match self.policy_store.get_mut(&asn) {
+            Some(policy) => policy,
+            None => return Vec::new(),
+        };
+        if let Some(best) = &best_ann {
+            policy.local_rib.insert_by_id(prefix_id, prefix, best.clone());
+        }
+        // Seeded routes aren't in RIBs-in, so only learned routes are dropped
+        if best_ann.is_none() && policy.local_rib.get_by_id(prefix_id).is_some_and(|current| current.next_hop_asn != asn) {
+            policy.local_rib.remove_by_id(prefix_id);
+        }
+        let new_best = policy.local_rib.get_by_id(prefix_id);
+        if !self.observers.is_empty() && new_best != old_best.as_ref() {
+            for observer in self.observers.iter_mut() {
+                observer.on_rib_change(asn, &prefix, old_best.as_ref(), new_best);
+            }
+        }
+        
+        let mut anns_to_propagate = Vec::new();
+        let mut exported_to = HashSet::new();
+        
+        if let Some(best) = best_ann.filter(|best| policy.should_propagate(best, recv_relationship)) {
+            for rel in [Relationships::Customers, Relationships::Peers, Relationships::Providers] {
+                if !policy.should_propagate_to_rel(&best, rel) {
+                    continue;
+                }
+                
+                for neighbor_as in as_obj.get_neighbors(rel) {
+                    let neighbor_asn = neighbor_as.asn;
+                    if link_failed(&self.failed_links, asn, neighbor_asn) {
+                        continue;
+                    }
+                    let recv_rel_for_neighbor = rel.invert();
+                    let new_ann = match policy.export_ann(&best, neighbor_asn, rel) {
+                        Some(new_ann) => new_ann,
+                        None => continue,
+                    };
+                    exported_to.insert(neighbor_asn);
+                    
+                    // Resending an unchanged route is a no-op for the neighbor but
+                    // multiplies the messages in flight every round
+                    let already_sent = policy.ribs_out.get(&neighbor_asn)
+                        .and_then(|rib_out| rib_out.get_by_id(prefix_id))
+                        == Some(&new_ann);
+                    if already_sent {
+                        continue;
+                    }
+                    
+                    anns_to_propagate.push((neighbor_asn, new_ann.clone(), recv_rel_for_neighbor));
+                    
+                    // Update ribs_out
+                    policy.ribs_out.entry(neighbor_asn)
+                        .or_insert_with(|| PrefixMap::new(policy.prefix_table.clone()))
+                        .insert_by_id(prefix_id, prefix, new_ann);
+                }
+            }
+        }
+        
+        for (&neighbor_asn, rib_out) in policy.ribs_out.iter_mut() {
+            if exported_to.contains(&neighbor_asn) {
+                continue;
+            }
+            if let Some(mut withdrawal) = rib_out.remove_by_id(prefix_id) {
+                withdrawal.withdraw = true;
+                let recv_rel_for_neighbor = withdrawal.recv_relationship;
+                anns_to_propagate.push((neighbor_asn, withdrawal, recv_rel_for_neighbor));
+            }
+        }
+        
+        anns_to_propagate
+    }
+    
+    fn reject_unresolvable_next_hop(&mut self, asn: ASN, ann_info: &AnnInfo) {
+        let ann = &ann_info.ann;
+        Self::record_validation(&mut self.observers, &mut self.rejection_stats, asn, ann, ValidationResult::RejectedUnresolvableNextHop);
+        if self.next_hop_verification == NextHopVerification::Strict {
+            self.next_hop_errors.push(format!(
+                "AS {} received {} from AS {} over {}, which is not its neighbor over that relationship",
+                asn, ann.prefix, ann.next_hop_asn, ann_info.recv_relationship
+            ));
+        }
+    }
+    
+    /// Tell observers whether `asn` accepted `ann`, and count rejections if enabled
+    /// Takes the fields rather than `&mut self` since callers hold a borrow of the policy store
+    fn record_validation(
+        observers: &mut [Box<dyn EngineObserver>],
+        rejection_stats: &mut Option<RejectionStats>,
+        asn: ASN,
+        ann: &Announcement,
+        result: ValidationResult,
+    ) {
+        for observer in observers.iter_mut() {
+            if result.is_accepted() {
+                observer.on_ann_accepted(asn, ann);
+            } else {
+                observer.on_ann_rejected(asn, ann, result);
+            }
+        }
+        if let Some(stats) = rejection_stats.as_mut().filter(|_| !result.is_accepted()) {
+            *stats.entry(asn).or_default().entry(result).or_insert(0) += 1;
+        }
+    }
+    
+    /// Process a received announcement using only the local RIB, as in the Python simulator's BGPSimplePolicy
+    /// Withdrawals are ignored, and only a changed local RIB is exported since there are no RIBs-out
+    fn process_ann_local_rib_only(&mut self, as_obj: &AS, ann_info: AnnInfo) -> Vec<Outgoing> {
+        let policy = match self.policy_store.get_mut(&as_obj.asn) {
+            Some(policy) => policy,
+            None => return Vec::new(),
+        };
+        if ann_info.ann.withdraw {
+            return Vec::new();
+        }
+        let prefix_id = policy.prefix_table.intern(ann_info.ann.prefix);
+        let result = policy.valid_ann_by_id(&ann_info.ann, prefix_id, ann_info.recv_relationship, as_obj);
+        Self::record_validation(&mut self.observers, &mut self.rejection_stats, as_obj.asn, &ann_info.ann, result);
+        if !result.is_accepted() {
+            return Vec::new();
+        }
+        
+        let old_best = if self.observers.is_empty() { None } else { policy.local_rib.get(&ann_info.ann.prefix).cloned() };
+        let new_best = policy.update_local_rib(&ann_info.ann, as_obj);
+        if let Some(new_best) = &new_best {
+            for observer in self.observers.iter_mut() {
+                observer.on_rib_change(as_obj.asn, &new_best.prefix, old_best.as_ref(), Some(new_best));
+            }
+        }
+        
+        let best = match new_best {
+            Some(best) if policy.should_propagate(&best, ann_info.recv_relationship) => best,
+            _ => return Vec::new(),
+        };
+        
+        let mut anns_to_propagate = Vec::new();
+        for rel in [Relationships::Customers, Relationships::Peers, Relationships::Providers] {
+            if !policy.should_propagate_to_rel(&best, rel) {
+                continue;
+            }
+            for neighbor_as in as_obj.get_neighbors(rel) {
+                if let Some(new_ann) = policy.export_ann(&best, neighbor_as.asn, rel) {
+                    anns_to_propagate.push((neighbor_as.asn, new_ann, rel.invert()));
+                }
+            }
+        }
+        anns_to_propagate
+    }
+
+    pub fn get_local_rib_snapshot(&self) -> HashMap<ASN, HashMap<String, Vec<ASN>>> {
+        let mut snapshot = HashMap::new();
+        
+        for (asn, policy) in self.policy_store.iter() {
+            let mut as_ribs = HashMap::new();
+            
+            for (prefix, ann) in &policy.local_rib {
+                as_ribs.insert(prefix.to_string(), ann.as_path.to_vec());
+            }
+            
+            snapshot.insert(*asn, as_ribs);
+        }
+        
+        snapshot
+    }
+
+    /// Stable digest of every AS's local RIB, for cheaply comparing runs
+    ///
+    /// 64-bit FNV-1a over the sorted `EngineSnapshot` encoding, so it doesn't depend on hash map
+    /// order, the Rust version or the platform. Engines with the same routes hash the same
+    pub fn state_hash(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+        EngineSnapshot::to_bytes(self).iter()
+            .fold(FNV_OFFSET_BASIS, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+    }
+}
+
+/// Whether the AS `ann_info` came from is one of `as_obj`'s neighbors over the relationship it arrived on
+fn next_hop_resolves(as_obj: &AS, ann_info: &AnnInfo) -> bool {
+    as_obj.get_neighbors(ann_info.recv_relationship).iter().any(|neighbor| neighbor.asn == ann_info.ann.next_hop_asn)
+}
+
+fn link_failed(failed_links: &HashSet<(ASN, ASN)>, asn1: ASN, asn2: ASN) -> bool {
+    !failed_links.is_empty() && failed_links.contains(&(asn1.min(asn2), asn1.max(asn2)))
+}
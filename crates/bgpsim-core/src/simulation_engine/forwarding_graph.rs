@@ -0,0 +1,121 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Write;
+
+use crate::as_graphs::as_graph::ASN;
+use crate::shared::Relationships;
+use crate::simulation_engine::{Prefix, SimulationEngine};
+
+/// Fill colors of the ASes forwarding to each origin in `ForwardingGraph::to_dot`, reused past the last
+const ORIGIN_COLORS: [&str; 6] = ["palegreen", "lightcoral", "lightskyblue", "khaki", "plum", "lightsalmon"];
+
+/// Where every AS forwards traffic for one prefix, as the AS -> next-hop AS edges of its selected route
+///
+/// Only exact matches count: a more specific hijack of the prefix has a graph of its own
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwardingGraph {
+    pub prefix: Prefix,
+    /// Next hop of every AS forwarding the prefix to a neighbor, so origins and blackholes have none
+    pub next_hops: BTreeMap<ASN, ASN>,
+    /// ASes that originate the prefix, so traffic ends there
+    pub origins: BTreeSet<ASN>,
+    /// ASes that drop the prefix's traffic because of an RTBH request
+    pub blackholes: BTreeSet<ASN>,
+}
+
+impl ForwardingGraph {
+    /// Every AS `asn`'s traffic passes through, starting with `asn` itself
+    /// Stops at an origin, a blackhole, an AS without a route or the first AS of a forwarding loop
+    pub fn forwarding_path(&self, asn: ASN) -> Vec<ASN> {
+        let mut path = vec![asn];
+        let mut visited = HashSet::from([asn]);
+        let mut current = asn;
+        while let Some(&next_hop) = self.next_hops.get(&current) {
+            if !visited.insert(next_hop) {
+                break;
+            }
+            path.push(next_hop);
+            current = next_hop;
+        }
+        path
+    }
+
+    /// The origin `asn`'s traffic ends at, if it isn't dropped or looped on the way
+    pub fn destination(&self, asn: ASN) -> Option<ASN> {
+        let last = *self.forwarding_path(asn).last()?;
+        self.origins.contains(&last).then_some(last)
+    }
+
+    /// Every AS with a route, by the origin its traffic ends at
+    pub fn destinations(&self) -> BTreeMap<ASN, Option<ASN>> {
+        self.next_hops.keys().chain(&self.origins).chain(&self.blackholes)
+            .map(|&asn| (asn, self.destination(asn)))
+            .collect()
+    }
+
+    /// `{"prefix": ..., "edges": [{"asn": ..., "next_hop_asn": ...}, ...], "origins": [...], "blackholes": [...], "destinations": {"<asn>": origin}}`
+    pub fn to_json(&self) -> serde_json::Value {
+        let edges: Vec<serde_json::Value> = self.next_hops.iter()
+            .map(|(asn, next_hop)| serde_json::json!({"asn": asn, "next_hop_asn": next_hop}))
+            .collect();
+        let destinations: serde_json::Map<String, serde_json::Value> = self.destinations().into_iter()
+            .map(|(asn, origin)| (asn.to_string(), serde_json::json!(origin)))
+            .collect();
+        serde_json::json!({
+            "prefix": self.prefix.to_string(),
+            "edges": edges,
+            "origins": self.origins,
+            "blackholes": self.blackholes,
+            "destinations": destinations,
+        })
+    }
+
+    /// Graphviz digraph with ASes filled by the origin their traffic ends at, so a hijacker's
+    /// catchment stands out. Origins are double circles and blackholes are black
+    pub fn to_dot(&self) -> String {
+        let mut dot = format!("digraph \"{}\" {{\n", self.prefix);
+        let colors: BTreeMap<ASN, &str> = self.origins.iter().copied().zip(ORIGIN_COLORS.iter().copied().cycle()).collect();
+        for (asn, destination) in self.destinations() {
+            let shape = if self.origins.contains(&asn) { "doublecircle" } else { "circle" };
+            let (fill, font) = match destination.and_then(|origin| colors.get(&origin)) {
+                _ if self.blackholes.contains(&asn) => ("black", "white"),
+                Some(color) => (*color, "black"),
+                None => ("white", "black"),
+            };
+            let _ = writeln!(
+                dot,
+                "  {} [label=\"AS {}\", shape={}, style=filled, fillcolor={}, fontcolor={}];",
+                asn, asn, shape, fill, font
+            );
+        }
+        for (asn, next_hop) in &self.next_hops {
+            let _ = writeln!(dot, "  {} -> {};", asn, next_hop);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl SimulationEngine<'_> {
+    /// Forwarding graph of `prefix` from every AS's selected route, e.g. after convergence
+    pub fn forwarding_graph(&self, prefix: &Prefix) -> ForwardingGraph {
+        let mut graph = ForwardingGraph {
+            prefix: *prefix,
+            next_hops: BTreeMap::new(),
+            origins: BTreeSet::new(),
+            blackholes: BTreeSet::new(),
+        };
+        for (&asn, policy) in self.policy_store.iter() {
+            let Some(ann) = policy.local_rib.get(prefix).filter(|ann| !ann.withdraw) else {
+                continue;
+            };
+            if ann.is_blackholed_at(asn) {
+                graph.blackholes.insert(asn);
+            } else if ann.recv_relationship == Relationships::Origin {
+                graph.origins.insert(asn);
+            } else {
+                graph.next_hops.insert(asn, ann.next_hop_asn);
+            }
+        }
+        graph
+    }
+}
@@ -0,0 +1,26 @@
+pub mod announcement;
+pub mod as_path;
+pub mod communities;
+pub mod engine;
+pub mod forwarding_graph;
+pub mod observer;
+pub mod policy;
+pub mod prefix_table;
+pub mod rib_in;
+pub mod rib_export;
+pub mod round_diff;
+pub mod snapshot;
+
+pub use announcement::{AnnInfo, Announcement, Prefix};
+pub use as_path::{ASPath, PathASNs};
+pub use communities::{ActionCommunity, CommunityAction};
+pub use engine::{ExportRestrictions, NextHopVerification, PolicyStorageMode, PropagationPhase, RejectionStats, SimulationEngine};
+pub use forwarding_graph::ForwardingGraph;
+pub use observer::EngineObserver;
+pub use policy::{TieBreaker, ValidationResult};
+pub use prefix_table::{PrefixId, PrefixMap, PrefixTable};
+pub use rib_in::RIBsIn;
+pub use rib_export::RibFormat;
+pub use announcement::PolicyStore;
+pub use round_diff::{RoundDiff, RouteChange};
+pub use snapshot::{EngineSnapshot, SnapshotReader, SnapshotRoute};
\ No newline at end of file
@@ -0,0 +1,21 @@
+use crate::as_graphs::as_graph::ASN;
+
+use super::announcement::{Announcement, Prefix};
+use super::policy::ValidationResult;
+
+/// Callbacks the engine makes while propagating, e.g. for custom metrics, animation or debugging
+///
+/// Register with `SimulationEngine::add_observer`. Seeding in `setup` is not observed.
+/// Every callback defaults to doing nothing
+pub trait EngineObserver {
+    fn on_round_start(&mut self, _round: u32) {}
+
+    /// `asn` accepted `ann` from `ann.next_hop_asn` into its routing tables
+    fn on_ann_accepted(&mut self, _asn: ASN, _ann: &Announcement) {}
+
+    /// `asn`'s policy rejected `ann` from `ann.next_hop_asn`, for `reason`
+    fn on_ann_rejected(&mut self, _asn: ASN, _ann: &Announcement, _reason: ValidationResult) {}
+
+    /// `asn`'s local RIB route for `prefix` changed; None means no route
+    fn on_rib_change(&mut self, _asn: ASN, _prefix: &Prefix, _old: Option<&Announcement>, _new: Option<&Announcement>) {}
+}
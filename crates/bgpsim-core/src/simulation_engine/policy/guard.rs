@@ -0,0 +1,215 @@
+use std::cmp::Ordering;
+use std::sync::{Arc, OnceLock};
+
+use crate::as_graphs::as_graph::{AS, ASGraph};
+use crate::aspa_validator::AspaValidator;
+use crate::irr_validator::IrrValidator;
+use crate::route_validator::RouteValidator;
+use crate::shared::Relationships;
+use crate::simulation_engine::announcement::Announcement;
+use crate::simulation_engine::prefix_table::PrefixId;
+
+use super::policy_extensions::BGPPolicy;
+use super::{PolicyExtension, ProcessingResult, ValidationResult};
+
+/// Limits a guarded policy extension must stay within
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuardLimits {
+    /// Most ASNs `process_announcement` may add to an AS path
+    pub max_path_growth: usize,
+    /// Most action communities an announcement may carry after `process_announcement`
+    pub max_communities: usize,
+    /// Check that comparisons are reflexive and antisymmetric, at the cost of comparing twice
+    pub check_comparisons: bool,
+}
+
+impl Default for GuardLimits {
+    fn default() -> Self {
+        GuardLimits {
+            max_path_growth: 16,
+            max_communities: 64,
+            check_comparisons: true,
+        }
+    }
+}
+
+/// Wraps a third-party policy extension and records the first time it misbehaves
+///
+/// Misbehaving calls fall back to plain BGP so the run stays well-defined (route selection
+/// would otherwise panic or loop on an inconsistent ordering); the engine's `try_run` then
+/// fails the trial with the recorded diagnostic
+pub struct GuardedPolicy {
+    inner: Box<dyn PolicyExtension>,
+    limits: GuardLimits,
+    violation: OnceLock<String>,
+}
+
+impl GuardedPolicy {
+    pub fn new(inner: Box<dyn PolicyExtension>, limits: GuardLimits) -> Self {
+        GuardedPolicy {
+            inner,
+            limits,
+            violation: OnceLock::new(),
+        }
+    }
+
+    fn record(&self, diagnostic: String) {
+        let _ = self.violation.set(diagnostic);
+    }
+
+    /// Run `compare` (given whether its arguments are swapped) both ways if enabled,
+    /// returning None after recording an inconsistency
+    fn checked_comparison(
+        &self,
+        name: &str,
+        ann1: &Announcement,
+        ann2: &Announcement,
+        compare: impl Fn(&Announcement, &Announcement, bool) -> Ordering,
+    ) -> Option<Ordering> {
+        let ordering = compare(ann1, ann2, false);
+        if !self.limits.check_comparisons {
+            return Some(ordering);
+        }
+
+        let reversed = compare(ann2, ann1, true);
+        let consistent = if ann1 == ann2 {
+            ordering == Ordering::Equal
+        } else {
+            ordering == reversed.reverse()
+        };
+        if consistent {
+            return Some(ordering);
+        }
+
+        self.record(format!(
+            "{} is inconsistent for {} via AS {} and AS {}: {:?} one way, {:?} the other",
+            name, ann1.prefix, ann1.next_hop_asn, ann2.next_hop_asn, ordering, reversed
+        ));
+        None
+    }
+}
+
+impl PolicyExtension for GuardedPolicy {
+    fn validate_announcement(
+        &self,
+        ann: &Announcement,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        self.inner.validate_announcement(ann, recv_relationship, as_obj, route_validator)
+    }
+
+    fn validate_announcement_by_id(
+        &self,
+        ann: &Announcement,
+        prefix_id: PrefixId,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        self.inner.validate_announcement_by_id(ann, prefix_id, recv_relationship, as_obj, route_validator)
+    }
+
+    fn process_announcement(
+        &mut self,
+        ann: &mut Announcement,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+    ) -> ProcessingResult {
+        let path_len = ann.as_path.len();
+        let result = self.inner.process_announcement(ann, recv_relationship, as_obj);
+
+        let path_growth = ann.as_path.len().saturating_sub(path_len);
+        let num_communities = ann.action_communities.as_ref().map_or(0, Vec::len);
+        if path_growth > self.limits.max_path_growth {
+            self.record(format!(
+                "process_announcement grew the path of {} by {} ASNs (limit {})",
+                ann.prefix, path_growth, self.limits.max_path_growth
+            ));
+            return ProcessingResult::Reject;
+        }
+        if num_communities > self.limits.max_communities {
+            self.record(format!(
+                "process_announcement left {} communities on {} (limit {})",
+                num_communities, ann.prefix, self.limits.max_communities
+            ));
+            return ProcessingResult::Reject;
+        }
+        result
+    }
+
+    fn should_propagate(
+        &self,
+        ann: &Announcement,
+        recv_relationship: Relationships,
+        send_relationship: Relationships,
+    ) -> bool {
+        self.inner.should_propagate(ann, recv_relationship, send_relationship)
+    }
+
+    fn compare_announcements(
+        &self,
+        ann1: &Announcement,
+        ann2: &Announcement,
+        rel1: Relationships,
+        rel2: Relationships,
+        as_obj: &AS,
+    ) -> Ordering {
+        self.checked_comparison("compare_announcements", ann1, ann2, |a, b, flipped| {
+            let (rel_a, rel_b) = if flipped { (rel2, rel1) } else { (rel1, rel2) };
+            self.inner.compare_announcements(a, b, rel_a, rel_b, as_obj)
+        }).unwrap_or_else(|| BGPPolicy.compare_announcements(ann1, ann2, rel1, rel2, as_obj))
+    }
+
+    fn compare_preference(
+        &self,
+        ann1: &Announcement,
+        ann2: &Announcement,
+        rel1: Relationships,
+        rel2: Relationships,
+        as_obj: &AS,
+    ) -> Ordering {
+        self.checked_comparison("compare_preference", ann1, ann2, |a, b, flipped| {
+            let (rel_a, rel_b) = if flipped { (rel2, rel1) } else { (rel1, rel2) };
+            self.inner.compare_preference(a, b, rel_a, rel_b, as_obj)
+        }).unwrap_or_else(|| BGPPolicy.compare_preference(ann1, ann2, rel1, rel2, as_obj))
+    }
+
+    fn get_gao_rexford_preference(&self, rel: Relationships) -> u8 {
+        self.inner.get_gao_rexford_preference(rel)
+    }
+
+    fn local_pref(&self, ann: &Announcement, rel: Relationships) -> u32 {
+        self.inner.local_pref(ann, rel)
+    }
+
+    fn setup(&mut self, as_obj: &AS, as_graph: &ASGraph) {
+        self.inner.setup(as_obj, as_graph);
+    }
+
+    fn reset(&mut self) {
+        self.violation = OnceLock::new();
+        self.inner.reset();
+    }
+
+    fn set_aspa_validator(&mut self, aspa_validator: Arc<AspaValidator>) {
+        self.inner.set_aspa_validator(aspa_validator);
+    }
+
+    fn set_irr_validator(&mut self, irr_validator: Arc<IrrValidator>) {
+        self.inner.set_irr_validator(irr_validator);
+    }
+
+    fn set_route_validator(&mut self, route_validator: Arc<RouteValidator>) {
+        self.inner.set_route_validator(route_validator);
+    }
+
+    fn violation(&self) -> Option<&str> {
+        self.violation.get().map(String::as_str)
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
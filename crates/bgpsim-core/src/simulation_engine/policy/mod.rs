@@ -0,0 +1,340 @@
+pub mod guard;
+pub mod policy_extensions;
+pub mod policy_kind;
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+use crate::shared::{Relationships, Settings};
+use crate::as_graphs::as_graph::{AS, ASN, ASGraph};
+use crate::simulation_engine::announcement::{Announcement, Prefix};
+use crate::simulation_engine::prefix_table::PrefixId;
+use crate::route_validator::RouteValidator;
+use crate::aspa_validator::AspaValidator;
+use crate::irr_validator::IrrValidator;
+
+pub use guard::{GuardLimits, GuardedPolicy};
+pub use policy_kind::PolicyKind;
+
+/// Whether a policy accepted an announcement, and why not if it didn't
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ValidationResult {
+    Accepted,
+    /// The AS path already contains the receiving AS
+    RejectedLoop,
+    /// Empty AS path from a neighbor rather than an origin
+    RejectedEmptyPath,
+    /// The first ASN on the path isn't the neighbor that sent it
+    RejectedNextHopMismatch,
+    /// The first ASN on the path isn't a neighbor at all
+    RejectedFirstAsNotNeighbor,
+    /// ROV found the route invalid by length, origin, or both
+    RejectedRoaInvalid,
+    /// No ROA covers the route and the policy requires one
+    RejectedRoaUnknown,
+    RejectedAspa,
+    RejectedBgpsec,
+    /// The origin isn't among the ASes the Path-End records allow
+    RejectedPathEnd,
+    /// No IRR route object registers the customer route's prefix and origin
+    RejectedIrr,
+    /// The sender isn't a neighbor of the receiving AS over the relationship it arrived on, see `NextHopVerification`
+    RejectedUnresolvableNextHop,
+    /// Rejected for any other policy-specific reason, e.g. by a third-party extension
+    RejectedByPolicy,
+}
+
+impl ValidationResult {
+    pub fn is_accepted(&self) -> bool {
+        *self == ValidationResult::Accepted
+    }
+
+    /// Accepted when `valid`, otherwise `rejection`
+    pub fn from_bool(valid: bool, rejection: ValidationResult) -> Self {
+        if valid { ValidationResult::Accepted } else { rejection }
+    }
+}
+
+impl std::fmt::Display for ValidationResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ValidationResult::Accepted => "ACCEPTED",
+            ValidationResult::RejectedLoop => "REJECTED_LOOP",
+            ValidationResult::RejectedEmptyPath => "REJECTED_EMPTY_PATH",
+            ValidationResult::RejectedNextHopMismatch => "REJECTED_NEXT_HOP_MISMATCH",
+            ValidationResult::RejectedFirstAsNotNeighbor => "REJECTED_FIRST_AS_NOT_NEIGHBOR",
+            ValidationResult::RejectedRoaInvalid => "REJECTED_ROA_INVALID",
+            ValidationResult::RejectedRoaUnknown => "REJECTED_ROA_UNKNOWN",
+            ValidationResult::RejectedAspa => "REJECTED_ASPA",
+            ValidationResult::RejectedBgpsec => "REJECTED_BGPSEC",
+            ValidationResult::RejectedPathEnd => "REJECTED_PATH_END",
+            ValidationResult::RejectedIrr => "REJECTED_IRR",
+            ValidationResult::RejectedUnresolvableNextHop => "REJECTED_UNRESOLVABLE_NEXT_HOP",
+            ValidationResult::RejectedByPolicy => "REJECTED_BY_POLICY",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessingResult {
+    Accept,
+    Reject,
+    Modified,
+}
+
+/// Core trait for BGP policy extensions
+pub trait PolicyExtension: Send + Sync {
+    /// Validate an incoming announcement
+    fn validate_announcement(
+        &self,
+        ann: &Announcement,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        // Default validation - no loops, correct next hop
+        if ann.as_path.is_empty() && recv_relationship != Relationships::Origin {
+            return ValidationResult::RejectedEmptyPath;
+        }
+
+        if ann.as_path.contains(&as_obj.asn) {
+            return ValidationResult::RejectedLoop;
+        }
+        
+        if !ann.as_path.is_empty() {
+            if let Some(first_asn) = ann.as_path.first() {
+                if *first_asn != ann.next_hop_asn {
+                    return ValidationResult::RejectedNextHopMismatch;
+                }
+            }
+        }
+        
+        ValidationResult::Accepted
+    }
+    
+    /// `validate_announcement` for a route whose prefix has `prefix_id` in the engine's `PrefixTable`
+    /// ROV-family policies use the id to find the ROA outcomes frozen for the seeded routes without hashing the prefix
+    fn validate_announcement_by_id(
+        &self,
+        ann: &Announcement,
+        prefix_id: PrefixId,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        self.validate_announcement(ann, recv_relationship, as_obj, route_validator)
+    }
+    
+    /// Process and potentially modify an announcement
+    fn process_announcement(
+        &mut self,
+        ann: &mut Announcement,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+    ) -> ProcessingResult {
+        // Default processing - accept without modification
+        ProcessingResult::Accept
+    }
+    
+    /// Determine if announcement should be propagated to a specific relationship
+    fn should_propagate(
+        &self,
+        ann: &Announcement,
+        recv_relationship: Relationships,
+        send_relationship: Relationships,
+    ) -> bool {
+        // Default Gao-Rexford propagation rules
+        match (recv_relationship, send_relationship) {
+            (Relationships::Origin, _) => true,
+            (Relationships::Customers, _) => true,
+            (Relationships::Peers, Relationships::Customers) => true,
+            (Relationships::Providers, Relationships::Customers) => true,
+            _ => false,
+        }
+    }
+    
+    /// Compare two announcements for route selection, `Less` meaning `ann1` is better
+    /// Ties are broken by the lowest next hop ASN; see `TieBreaker` for the other strategies
+    fn compare_announcements(
+        &self,
+        ann1: &Announcement,
+        ann2: &Announcement,
+        rel1: Relationships,
+        rel2: Relationships,
+        as_obj: &AS,
+    ) -> Ordering {
+        self.compare_preference(ann1, ann2, rel1, rel2, as_obj)
+            .then_with(|| ann1.next_hop_asn.cmp(&ann2.next_hop_asn))
+    }
+    
+    /// `compare_announcements` up to the tie-break: `Equal` when only the tie-breaker could separate the routes
+    fn compare_preference(
+        &self,
+        ann1: &Announcement,
+        ann2: &Announcement,
+        rel1: Relationships,
+        rel2: Relationships,
+        _as_obj: &AS,
+    ) -> Ordering {
+        decision_process(ann1, ann2, self.local_pref(ann1, rel1), self.local_pref(ann2, rel2))
+    }
+    
+    /// LOCAL_PREF of a received route: the one its AS configured, or else 100 times the relationship preference
+    fn local_pref(&self, ann: &Announcement, rel: Relationships) -> u32 {
+        ann.local_pref.unwrap_or_else(|| 100 * self.get_gao_rexford_preference(rel) as u32)
+    }
+    
+    /// Get Gao-Rexford preference value for a relationship
+    fn get_gao_rexford_preference(&self, rel: Relationships) -> u8 {
+        match rel {
+            Relationships::Customers => 3,
+            Relationships::Peers => 2,
+            Relationships::Providers => 1,
+            _ => 0,
+        }
+    }
+    
+    /// Setup policy-specific state
+    fn setup(&mut self, as_obj: &AS, as_graph: &ASGraph) {}
+    
+    /// Clear state accumulated while running, called by `SimulationEngine::setup` before every run
+    /// Configuration such as ROAs or Path-End records is kept
+    fn reset(&mut self) {}
+    
+    /// Receive the ASPA objects the engine shares across policies, ignored by policies that don't use them
+    fn set_aspa_validator(&mut self, _aspa_validator: Arc<AspaValidator>) {}
+    
+    /// Receive the IRR route objects the engine shares across policies, ignored by policies that don't use them
+    fn set_irr_validator(&mut self, _irr_validator: Arc<IrrValidator>) {}
+    
+    /// Receive the ROAs the engine shares across policies, ignored by policies that don't use them
+    fn set_route_validator(&mut self, _route_validator: Arc<RouteValidator>) {}
+    
+    /// Diagnostic for the first limit a guarded extension broke, if any
+    fn violation(&self) -> Option<&str> {
+        None
+    }
+    
+    /// Get the policy name/type
+    fn name(&self) -> &str;
+}
+
+/// BGP decision process up to the tie-break: highest LOCAL_PREF, then shortest AS path, then lowest MED
+///
+/// Origin type and IGP cost don't exist at AS level. MEDs are compared between routes from any neighbors
+/// (`always-compare-med`), since each neighbor sends at most one route per prefix
+pub fn decision_process(ann1: &Announcement, ann2: &Announcement, local_pref1: u32, local_pref2: u32) -> Ordering {
+    local_pref2.cmp(&local_pref1)
+        .then_with(|| ann1.as_path.len().cmp(&ann2.as_path.len()))
+        .then_with(|| ann1.med.unwrap_or(0).cmp(&ann2.med.unwrap_or(0)))
+}
+
+/// How an AS picks between routes the decision process ranks equally
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TieBreaker {
+    /// Lowest next hop ASN, matching bgpy's lowest neighbor ASN tiebreaker
+    #[default]
+    LowestNextHopAsn,
+    /// Prefer the oldest route, i.e. the earliest `Timestamps`, then lowest next hop ASN
+    ///
+    /// Like routers that keep the oldest eBGP path (RFC 5004), so a later wave doesn't displace an equal route
+    OldestAnnouncement,
+    /// Pseudo-random but fixed preference between neighbors, from the seed and the deciding AS
+    SeededRandom(u64),
+}
+
+impl TieBreaker {
+    /// Order two routes `asn` ranks equally, `Less` meaning `ann1` wins
+    pub fn compare(&self, ann1: &Announcement, ann2: &Announcement, asn: ASN) -> Ordering {
+        let lowest_next_hop = ann1.next_hop_asn.cmp(&ann2.next_hop_asn);
+        match self {
+            TieBreaker::LowestNextHopAsn => lowest_next_hop,
+            TieBreaker::OldestAnnouncement => ann1.timestamp.cmp(&ann2.timestamp).then(lowest_next_hop),
+            TieBreaker::SeededRandom(seed) => {
+                let rank = |next_hop_asn: ASN| splitmix64(seed ^ ((asn as u64) << 32 | next_hop_asn as u64));
+                rank(ann1.next_hop_asn).cmp(&rank(ann2.next_hop_asn)).then(lowest_next_hop)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for TieBreaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TieBreaker::LowestNextHopAsn => write!(f, "LowestNextHopAsn"),
+            TieBreaker::OldestAnnouncement => write!(f, "OldestAnnouncement"),
+            TieBreaker::SeededRandom(seed) => write!(f, "SeededRandom:{}", seed),
+        }
+    }
+}
+
+impl std::str::FromStr for TieBreaker {
+    type Err = String;
+
+    /// Parses what `Display` writes, e.g. `SeededRandom:42`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "LowestNextHopAsn" => Ok(TieBreaker::LowestNextHopAsn),
+            None if s == "OldestAnnouncement" => Ok(TieBreaker::OldestAnnouncement),
+            Some(("SeededRandom", seed)) => seed.parse()
+                .map(TieBreaker::SeededRandom)
+                .map_err(|_| format!("Invalid tie-breaker seed: {}", seed)),
+            _ => Err(format!("Unknown tie-breaker: {}", s)),
+        }
+    }
+}
+
+/// SplitMix64 finalizer, stable across platforms and Rust versions unlike `DefaultHasher`
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// The type of `Policy::extension`: `PolicyKind` with the `enum-dispatch` feature, a trait object without it
+#[cfg(feature = "enum-dispatch")]
+pub type PolicyExtensionHandle = PolicyKind;
+#[cfg(not(feature = "enum-dispatch"))]
+pub type PolicyExtensionHandle = Box<dyn PolicyExtension>;
+
+/// Hold `extension` in whichever form `Policy::extension` takes
+pub fn extension_handle(extension: impl PolicyExtension + 'static) -> PolicyExtensionHandle {
+    #[cfg(feature = "enum-dispatch")]
+    return PolicyKind::custom(extension);
+    #[cfg(not(feature = "enum-dispatch"))]
+    return Box::new(extension);
+}
+
+/// Create a policy extension based on settings
+#[cfg(feature = "enum-dispatch")]
+pub fn create_policy_extension(settings: Settings) -> PolicyExtensionHandle {
+    PolicyKind::new(settings)
+}
+
+/// Create a policy extension based on settings
+#[cfg(not(feature = "enum-dispatch"))]
+pub fn create_policy_extension(settings: Settings) -> PolicyExtensionHandle {
+    use policy_extensions::*;
+    
+    match settings {
+        Settings::BaseDefense => Box::new(bgp::BGPPolicy),
+        Settings::Rov => Box::new(rov::ROVPolicy::default()),
+        Settings::RovDepref => Box::new(rov::ROVPolicy::default().with_mode(rov::ROVMode::DeprefInvalid)),
+        Settings::PeerRov => Box::new(peer_rov::PeerROVPolicy::default()),
+        Settings::OnlyToCustomers => Box::new(only_to_customers::OnlyToCustomersPolicy),
+        Settings::PathEnd => Box::new(path_end::PathEndPolicy::new()),
+        Settings::EnforceFirstAs => Box::new(enforce_first_as::EnforceFirstASPolicy),
+        #[cfg(feature = "aspa")]
+        Settings::Aspa => Box::new(aspa::ASPAPolicy::new()),
+        #[cfg(feature = "bgpsec")]
+        Settings::Bgpsec => Box::new(bgpsec::BGPSecPolicy),
+        #[cfg(feature = "rovpp")]
+        Settings::RovppV1Lite => Box::new(rovppv1_lite::ROVPPV1LitePolicy::default()),
+        Settings::PeerLockLite => Box::new(peerlock_lite::PeerlockLitePolicy),
+        Settings::EdgeFilter => Box::new(as_path_edge_filter::ASPathEdgeFilterPolicy),
+        Settings::IrrFilter => Box::new(irr_filter::IrrFilterPolicy::new()),
+        // Default to BGP for unimplemented policies and those compiled out by features
+        _ => Box::new(bgp::BGPPolicy),
+    }
+}
\ No newline at end of file
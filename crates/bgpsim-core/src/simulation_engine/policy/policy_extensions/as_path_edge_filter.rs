@@ -1,7 +1,7 @@
 use crate::as_graphs::as_graph::{AS};
 use crate::shared::{Relationships};
 use crate::simulation_engine::announcement::Announcement;
-use crate::simulation_engine::policy::{PolicyExtension};
+use crate::simulation_engine::policy::{PolicyExtension, ValidationResult};
 use crate::route_validator::RouteValidator;
 
 /// AS Path Edge Filter policy - filters based on AS path edges
@@ -14,27 +14,27 @@ impl PolicyExtension for ASPathEdgeFilterPolicy {
         recv_relationship: Relationships,
         as_obj: &AS,
         _route_validator: Option<&RouteValidator>,
-    ) -> bool {
+    ) -> ValidationResult {
         // First do standard validation
         if ann.as_path.is_empty() && recv_relationship != Relationships::Origin {
-            return false;
+            return ValidationResult::RejectedEmptyPath;
         }
 
         if ann.as_path.contains(&as_obj.asn) {
-            return false;
+            return ValidationResult::RejectedLoop;
         }
         
         if !ann.as_path.is_empty() {
             if let Some(first_asn) = ann.as_path.first() {
                 if *first_asn != ann.next_hop_asn {
-                    return false;
+                    return ValidationResult::RejectedNextHopMismatch;
                 }
             }
         }
         
         // TODO: Implement AS path edge filtering logic
         // This requires checking if consecutive AS pairs in the path are valid
-        true
+        ValidationResult::Accepted
     }
     
     fn name(&self) -> &str {
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use crate::as_graphs::as_graph::{AS, ASN};
+use crate::aspa_validator::{AspaValidator, ProviderAuth};
+use crate::shared::{Relationships};
+use crate::simulation_engine::announcement::Announcement;
+use crate::simulation_engine::as_path::PathASNs;
+use crate::simulation_engine::policy::{PolicyExtension, ValidationResult};
+use crate::route_validator::RouteValidator;
+
+/// ASPA (AS Provider Authorization) policy
+/// Checks paths against the ASPA objects the engine shares, see `SimulationEngine::with_aspa_validator`
+#[derive(Default)]
+pub struct ASPAPolicy {
+    aspa_validator: Option<Arc<AspaValidator>>,
+}
+
+impl ASPAPolicy {
+    pub fn new() -> Self {
+        ASPAPolicy {
+            aspa_validator: None,
+        }
+    }
+
+    fn next_hop_valid(&self, ann: &Announcement, as_obj: &AS) -> bool {
+        // Next hop should be first ASN in path (unless we're an IXP/route server)
+        ann.as_path.first() == Some(&ann.next_hop_asn) || as_obj.ixp
+    }
+    
+    /// False only if asn1 attests providers and asn2 isn't one of them
+    fn provider_check(&self, asn1: ASN, asn2: ASN) -> bool {
+        self.aspa_validator.as_ref()
+            .is_none_or(|validator| validator.provider_auth(asn1, asn2) != ProviderAuth::NotProviderPlus)
+    }
+    
+    fn get_max_up_ramp_length(&self, as_path: &[ASN]) -> usize {
+        let reversed_path: PathASNs = as_path.iter().copied().rev().collect();
+        
+        for i in 0..reversed_path.len() - 1 {
+            if !self.provider_check(reversed_path[i], reversed_path[i + 1]) {
+                return i + 1;
+            }
+        }
+        as_path.len()
+    }
+    
+    fn get_max_down_ramp_length(&self, as_path: &[ASN]) -> usize {
+        let reversed_path: PathASNs = as_path.iter().copied().rev().collect();
+        
+        for i in (1..reversed_path.len()).rev() {
+            if !self.provider_check(reversed_path[i], reversed_path[i - 1]) {
+                let j = i + 1; // Adjust for 1-indexing in RFC
+                return reversed_path.len() - j + 1;
+            }
+        }
+        as_path.len()
+    }
+}
+
+impl PolicyExtension for ASPAPolicy {
+    fn validate_announcement(
+        &self,
+        ann: &Announcement,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        _route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        // Basic validation
+        if ann.as_path.is_empty() && recv_relationship != Relationships::Origin {
+            return ValidationResult::RejectedEmptyPath;
+        }
+
+        if ann.as_path.contains(&as_obj.asn) {
+            return ValidationResult::RejectedLoop;
+        }
+        
+        // ASPA specific validation
+        if !self.next_hop_valid(ann, as_obj) {
+            return ValidationResult::RejectedAspa;
+        }
+        // Prepending isn't a hop, so it is collapsed as RFC 9582 requires
+        let as_path = ann.as_path.without_prepending().to_path_asns();
+        if as_path.len() <= 1 {
+            return ValidationResult::Accepted;
+        }
+        
+        // Routes from customers and peers must only have gone up, routes from providers up then down
+        let valid = if recv_relationship == Relationships::Providers {
+            self.get_max_up_ramp_length(&as_path) + self.get_max_down_ramp_length(&as_path) >= as_path.len()
+        } else {
+            self.get_max_up_ramp_length(&as_path) >= as_path.len()
+        };
+        ValidationResult::from_bool(valid, ValidationResult::RejectedAspa)
+    }
+    
+    fn set_aspa_validator(&mut self, aspa_validator: Arc<AspaValidator>) {
+        self.aspa_validator = Some(aspa_validator);
+    }
+    
+    fn name(&self) -> &str {
+        "ASPA"
+    }
+}
@@ -1,4 +1,4 @@
-use crate::simulation_engine::policy::PolicyExtension;
+use crate::simulation_engine::policy::{PolicyExtension, ValidationResult};
 use crate::simulation_engine::announcement::Announcement;
 use crate::as_graphs::as_graph::AS;
 use crate::shared::Relationships;
@@ -18,25 +18,25 @@ impl PolicyExtension for BGPPolicy {
         recv_relationship: Relationships,
         as_obj: &AS,
         _route_validator: Option<&RouteValidator>,
-    ) -> bool {
+    ) -> ValidationResult {
         // Basic BGP validation:
         // 1. Check if AS is not already in the AS path (loop prevention)
         if ann.as_path.contains(&as_obj.asn) {
-            return false;
+            return ValidationResult::RejectedLoop;
         }
         
         // 2. Withdrawn announcements are always valid
         if ann.withdraw {
-            return true;
+            return ValidationResult::Accepted;
         }
         
         // 3. Empty AS path is only valid from origin
         if ann.as_path.is_empty() && recv_relationship != Relationships::Origin {
-            return false;
+            return ValidationResult::RejectedEmptyPath;
         }
         
         // All other announcements are valid
-        true
+        ValidationResult::Accepted
     }
     
     fn should_propagate(
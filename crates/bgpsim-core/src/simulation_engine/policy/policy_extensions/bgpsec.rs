@@ -2,7 +2,7 @@ use std::cmp::Ordering;
 use crate::as_graphs::as_graph::{AS};
 use crate::shared::{Relationships};
 use crate::simulation_engine::announcement::Announcement;
-use crate::simulation_engine::policy::{PolicyExtension, ProcessingResult};
+use crate::simulation_engine::policy::{PolicyExtension, ProcessingResult, ValidationResult, decision_process};
 use crate::route_validator::RouteValidator;
 
 /// BGPSec policy - cryptographic path validation
@@ -15,21 +15,22 @@ impl PolicyExtension for BGPSecPolicy {
         recv_relationship: Relationships,
         as_obj: &AS,
         _route_validator: Option<&RouteValidator>,
-    ) -> bool {
+    ) -> ValidationResult {
         // Basic validation
         if ann.as_path.is_empty() && recv_relationship != Relationships::Origin {
-            return false;
+            return ValidationResult::RejectedEmptyPath;
         }
 
         if ann.as_path.contains(&as_obj.asn) {
-            return false;
+            return ValidationResult::RejectedLoop;
         }
         
         // BGPSec validation - check if secure path matches AS path
         if let Some(bgpsec_path) = &ann.bgpsec_as_path {
-            ann.bgpsec_next_asn == Some(as_obj.asn) && bgpsec_path == &ann.as_path
+            let valid = ann.bgpsec_next_asn == Some(as_obj.asn) && ann.as_path == bgpsec_path[..];
+            ValidationResult::from_bool(valid, ValidationResult::RejectedBgpsec)
         } else {
-            true // No BGPSec path means regular BGP validation
+            ValidationResult::Accepted // No BGPSec path means regular BGP validation
         }
     }
     
@@ -42,9 +43,9 @@ impl PolicyExtension for BGPSecPolicy {
         // If BGPSec is valid, maintain the secure path
         if let Some(bgpsec_path) = &ann.bgpsec_as_path {
             if ann.bgpsec_next_asn == Some(as_obj.asn) && 
-               bgpsec_path.get(1..) == Some(&ann.as_path[1..]) {
+               bgpsec_path.get(1..).is_some_and(|rest| ann.as_path.without_first() == *rest) {
                 // Valid BGPSec, update with our ASN
-                ann.bgpsec_as_path = Some(ann.as_path.clone());
+                ann.bgpsec_as_path = Some(ann.as_path.to_vec());
                 ProcessingResult::Modified
             } else {
                 // Invalid BGPSec, clear the path
@@ -72,37 +73,24 @@ impl PolicyExtension for BGPSecPolicy {
         }
     }
     
-    fn compare_announcements(
+    fn compare_preference(
         &self,
         ann1: &Announcement,
         ann2: &Announcement,
         rel1: Relationships,
         rel2: Relationships,
-        as_obj: &AS,
+        _as_obj: &AS,
     ) -> Ordering {
         // Prefer BGPSec valid announcements
-        let ann1_valid = ann1.bgpsec_as_path.is_some() && 
-                        ann1.bgpsec_as_path.as_ref() == Some(&ann1.as_path);
-        let ann2_valid = ann2.bgpsec_as_path.is_some() && 
-                        ann2.bgpsec_as_path.as_ref() == Some(&ann2.as_path);
+        let ann1_valid = ann1.bgpsec_as_path.as_ref().is_some_and(|bgpsec_path| ann1.as_path == bgpsec_path[..]);
+        let ann2_valid = ann2.bgpsec_as_path.as_ref().is_some_and(|bgpsec_path| ann2.as_path == bgpsec_path[..]);
         
         match (ann1_valid, ann2_valid) {
             (true, false) => Ordering::Less, // ann1 is better
             (false, true) => Ordering::Greater, // ann2 is better
             _ => {
                 // Both valid or both invalid, use standard comparison
-                let pref1 = self.get_gao_rexford_preference(rel1);
-                let pref2 = self.get_gao_rexford_preference(rel2);
-                
-                match pref2.cmp(&pref1) {
-                    Ordering::Equal => {
-                        match ann1.as_path.len().cmp(&ann2.as_path.len()) {
-                            Ordering::Equal => ann1.next_hop_asn.cmp(&ann2.next_hop_asn),
-                            other => other,
-                        }
-                    }
-                    other => other,
-                }
+                decision_process(ann1, ann2, self.local_pref(ann1, rel1), self.local_pref(ann2, rel2))
             }
         }
     }
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use crate::as_graphs::as_graph::{AS};
+use crate::irr_validator::IrrValidator;
+use crate::route_validator::RouteValidator;
+use crate::shared::{Relationships};
+use crate::simulation_engine::announcement::Announcement;
+use crate::simulation_engine::policy::{PolicyExtension, ValidationResult};
+
+/// IRR-based customer filtering, as with prefix lists generated from route objects
+/// Routes from customers are dropped unless a route object the engine shares registers their
+/// prefix and origin, see `SimulationEngine::with_irr_validator`; routes from peers and providers
+/// aren't filtered. Without any route objects, nothing is
+#[derive(Default)]
+pub struct IrrFilterPolicy {
+    irr_validator: Option<Arc<IrrValidator>>,
+}
+
+impl IrrFilterPolicy {
+    pub fn new() -> Self {
+        IrrFilterPolicy {
+            irr_validator: None,
+        }
+    }
+}
+
+impl PolicyExtension for IrrFilterPolicy {
+    fn validate_announcement(
+        &self,
+        ann: &Announcement,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        _route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        if ann.as_path.is_empty() && recv_relationship != Relationships::Origin {
+            return ValidationResult::RejectedEmptyPath;
+        }
+
+        if ann.as_path.contains(&as_obj.asn) {
+            return ValidationResult::RejectedLoop;
+        }
+
+        if ann.as_path.first().is_some_and(|first_asn| *first_asn != ann.next_hop_asn) {
+            return ValidationResult::RejectedNextHopMismatch;
+        }
+
+        match (&self.irr_validator, recv_relationship) {
+            (Some(validator), Relationships::Customers) => ValidationResult::from_bool(
+                validator.is_covered(&ann.prefix, ann.origin()),
+                ValidationResult::RejectedIrr,
+            ),
+            _ => ValidationResult::Accepted,
+        }
+    }
+
+    fn set_irr_validator(&mut self, irr_validator: Arc<IrrValidator>) {
+        self.irr_validator = Some(irr_validator);
+    }
+
+    fn name(&self) -> &str {
+        "IrrFilter"
+    }
+}
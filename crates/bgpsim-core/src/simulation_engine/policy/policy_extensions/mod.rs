@@ -4,20 +4,28 @@ pub mod peer_rov;
 pub mod only_to_customers;
 pub mod path_end;
 pub mod enforce_first_as;
+#[cfg(feature = "aspa")]
 pub mod aspa;
+#[cfg(feature = "bgpsec")]
 pub mod bgpsec;
+#[cfg(feature = "rovpp")]
 pub mod rovppv1_lite;
 pub mod peerlock_lite;
 pub mod as_path_edge_filter;
+pub mod irr_filter;
 
+#[cfg(feature = "aspa")]
 pub use aspa::ASPAPolicy;
 pub use as_path_edge_filter::ASPathEdgeFilterPolicy;
 pub use bgp::BGPPolicy;
+#[cfg(feature = "bgpsec")]
 pub use bgpsec::BGPSecPolicy;
 pub use enforce_first_as::EnforceFirstASPolicy;
+pub use irr_filter::IrrFilterPolicy;
 pub use only_to_customers::OnlyToCustomersPolicy;
 pub use path_end::PathEndPolicy;
 pub use peer_rov::PeerROVPolicy;
 pub use peerlock_lite::PeerlockLitePolicy;
-pub use rov::ROVPolicy;
+pub use rov::{ROVMode, ROVPolicy};
+#[cfg(feature = "rovpp")]
 pub use rovppv1_lite::ROVPPV1LitePolicy;
\ No newline at end of file
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 use crate::as_graphs::as_graph::{AS, ASN};
 use crate::shared::{Relationships};
 use crate::simulation_engine::announcement::Announcement;
-use crate::simulation_engine::policy::{PolicyExtension};
+use crate::simulation_engine::policy::{PolicyExtension, ValidationResult};
 use crate::route_validator::RouteValidator;
 
 /// Path-End policy
@@ -29,24 +29,24 @@ impl PolicyExtension for PathEndPolicy {
         recv_relationship: Relationships,
         as_obj: &AS,
         _route_validator: Option<&RouteValidator>,
-    ) -> bool {
+    ) -> ValidationResult {
         // First do standard validation
         if ann.as_path.is_empty() && recv_relationship != Relationships::Origin {
-            return false;
+            return ValidationResult::RejectedEmptyPath;
         }
 
         if ann.as_path.contains(&as_obj.asn) {
-            return false;
+            return ValidationResult::RejectedLoop;
         }
         
         // Check path-end
         if let Some(&origin) = ann.as_path.last() {
             if !self.legitimate_asns.contains(&origin) {
-                return false;
+                return ValidationResult::RejectedPathEnd;
             }
         }
         
-        true
+        ValidationResult::Accepted
     }
     
     fn name(&self) -> &str {
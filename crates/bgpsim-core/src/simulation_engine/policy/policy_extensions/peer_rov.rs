@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use crate::as_graphs::as_graph::{AS};
+use crate::route_validator::RouteValidator;
+use crate::shared::{Relationships, ROAValidity};
+use crate::simulation_engine::announcement::Announcement;
+use crate::simulation_engine::policy::{PolicyExtension, ValidationResult};
+use crate::simulation_engine::prefix_table::PrefixId;
+
+use super::rov::roa_validity;
+
+/// Peer ROV policy - stricter ROV that rejects unknown prefixes
+/// Checks origins against the ROAs the engine shares, see `SimulationEngine::with_route_validator`
+#[derive(Default)]
+pub struct PeerROVPolicy {
+    route_validator: Option<Arc<RouteValidator>>,
+}
+
+impl PeerROVPolicy {
+    fn default_validate(&self, ann: &Announcement, recv_relationship: Relationships, as_obj: &AS) -> ValidationResult {
+        if ann.as_path.is_empty() && recv_relationship != Relationships::Origin {
+            return ValidationResult::RejectedEmptyPath;
+        }
+
+        if ann.as_path.contains(&as_obj.asn) {
+            return ValidationResult::RejectedLoop;
+        }
+        
+        if !ann.as_path.is_empty() {
+            if let Some(first_asn) = ann.as_path.first() {
+                if *first_asn != ann.next_hop_asn {
+                    return ValidationResult::RejectedNextHopMismatch;
+                }
+            }
+        }
+        
+        ValidationResult::Accepted
+    }
+    
+    fn validate(&self, ann: &Announcement, prefix_id: Option<PrefixId>, recv_relationship: Relationships, as_obj: &AS) -> ValidationResult {
+        // First do standard validation
+        let result = self.default_validate(ann, recv_relationship, as_obj);
+        if !result.is_accepted() {
+            return result;
+        }
+        
+        // Then check ROA validity
+        match roa_validity(self.route_validator.as_deref(), ann, prefix_id) {
+            ROAValidity::Valid => ValidationResult::Accepted,
+            ROAValidity::Unknown => ValidationResult::RejectedRoaUnknown,  // Reject unknown in Peer ROV
+            _ => ValidationResult::RejectedRoaInvalid,  // Reject all invalid types
+        }
+    }
+}
+
+impl PolicyExtension for PeerROVPolicy {
+    fn validate_announcement(
+        &self,
+        ann: &Announcement,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        _route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        self.validate(ann, None, recv_relationship, as_obj)
+    }
+    
+    fn validate_announcement_by_id(
+        &self,
+        ann: &Announcement,
+        prefix_id: PrefixId,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        _route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        self.validate(ann, Some(prefix_id), recv_relationship, as_obj)
+    }
+    
+    fn set_route_validator(&mut self, route_validator: Arc<RouteValidator>) {
+        self.route_validator = Some(route_validator);
+    }
+    
+    fn name(&self) -> &str {
+        "PeerROV"
+    }
+}
\ No newline at end of file
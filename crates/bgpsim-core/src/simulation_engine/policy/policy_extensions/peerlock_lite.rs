@@ -1,7 +1,7 @@
 use crate::as_graphs::as_graph::{AS};
 use crate::shared::{Relationships};
 use crate::simulation_engine::announcement::Announcement;
-use crate::simulation_engine::policy::{PolicyExtension};
+use crate::simulation_engine::policy::{PolicyExtension, ValidationResult};
 use crate::route_validator::RouteValidator;
 
 /// Peerlock Lite policy - prevents route leaks from Tier-1 ASes
@@ -14,20 +14,20 @@ impl PolicyExtension for PeerlockLitePolicy {
         recv_relationship: Relationships,
         as_obj: &AS,
         _route_validator: Option<&RouteValidator>,
-    ) -> bool {
+    ) -> ValidationResult {
         // First do standard validation
         if ann.as_path.is_empty() && recv_relationship != Relationships::Origin {
-            return false;
+            return ValidationResult::RejectedEmptyPath;
         }
 
         if ann.as_path.contains(&as_obj.asn) {
-            return false;
+            return ValidationResult::RejectedLoop;
         }
         
         if !ann.as_path.is_empty() {
             if let Some(first_asn) = ann.as_path.first() {
                 if *first_asn != ann.next_hop_asn {
-                    return false;
+                    return ValidationResult::RejectedNextHopMismatch;
                 }
             }
         }
@@ -36,10 +36,10 @@ impl PolicyExtension for PeerlockLitePolicy {
         if recv_relationship == Relationships::Customers {
             // Check if any AS in the path is Tier-1
             // TODO: Need access to ASGraph to check tier-1 status
-            // For now, return true
-            true
+            // For now, accept
+            ValidationResult::Accepted
         } else {
-            true
+            ValidationResult::Accepted
         }
     }
     
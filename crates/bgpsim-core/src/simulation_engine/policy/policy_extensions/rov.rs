@@ -0,0 +1,155 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::as_graphs::as_graph::{AS};
+use crate::route_validator::RouteValidator;
+use crate::shared::{Relationships, ROAValidity};
+use crate::simulation_engine::announcement::Announcement;
+use crate::simulation_engine::prefix_table::PrefixId;
+use crate::simulation_engine::policy::{PolicyExtension, ValidationResult, decision_process};
+
+/// What an ROV adopter does with ROA-invalid routes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ROVMode {
+    /// Reject them, as RFC 6811 recommends
+    #[default]
+    DropInvalid,
+    /// Accept them, but only select one when no valid or unknown route is left,
+    /// like deployments that lower LOCAL_PREF for invalids
+    DeprefInvalid,
+}
+
+impl fmt::Display for ROVMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ROVMode::DropInvalid => "DropInvalid",
+            ROVMode::DeprefInvalid => "DeprefInvalid",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for ROVMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DropInvalid" => Ok(ROVMode::DropInvalid),
+            "DeprefInvalid" => Ok(ROVMode::DeprefInvalid),
+            _ => Err(format!("Unknown ROV mode: {}", s)),
+        }
+    }
+}
+
+/// ROA validity of `ann`'s origin, unknown without ROAs
+/// With the id of `ann`'s prefix in the engine's `PrefixTable`, frozen outcomes are found without hashing the prefix
+pub(crate) fn roa_validity(route_validator: Option<&RouteValidator>, ann: &Announcement, prefix_id: Option<PrefixId>) -> ROAValidity {
+    let origin = ann.as_path.last().copied().unwrap_or(ann.next_hop_asn);
+    route_validator.map_or(ROAValidity::Unknown, |validator| match prefix_id {
+        Some(prefix_id) => validator.get_roa_outcome_by_id(prefix_id, &ann.prefix, origin).0,
+        None => validator.get_roa_outcome(&ann.prefix, origin).0,
+    })
+}
+
+/// Route Origin Validation (ROV) policy
+/// Checks origins against the ROAs the engine shares, see `SimulationEngine::with_route_validator`;
+/// without any, every route is unknown and accepted
+#[derive(Default)]
+pub struct ROVPolicy {
+    route_validator: Option<Arc<RouteValidator>>,
+    pub mode: ROVMode,
+}
+
+impl ROVPolicy {
+    pub fn with_mode(mut self, mode: ROVMode) -> Self {
+        self.mode = mode;
+        self
+    }
+    
+    /// Whether a ROA makes the route invalid, by length, origin or both
+    fn is_invalid(&self, ann: &Announcement, prefix_id: Option<PrefixId>) -> bool {
+        let validity = roa_validity(self.route_validator.as_deref(), ann, prefix_id);
+        !matches!(validity, ROAValidity::Valid | ROAValidity::Unknown)
+    }
+    
+    fn validate(&self, ann: &Announcement, prefix_id: Option<PrefixId>, recv_relationship: Relationships, as_obj: &AS) -> ValidationResult {
+        // First do standard validation
+        let result = self.default_validate(ann, recv_relationship, as_obj);
+        if !result.is_accepted() {
+            return result;
+        }
+        
+        // Then check ROA validity, accepting unknown in basic ROV; invalids are only deprefed in route selection
+        let drop_invalid = self.mode == ROVMode::DropInvalid;
+        ValidationResult::from_bool(!(drop_invalid && self.is_invalid(ann, prefix_id)), ValidationResult::RejectedRoaInvalid)
+    }
+    
+    fn default_validate(&self, ann: &Announcement, recv_relationship: Relationships, as_obj: &AS) -> ValidationResult {
+        if ann.as_path.is_empty() && recv_relationship != Relationships::Origin {
+            return ValidationResult::RejectedEmptyPath;
+        }
+
+        if ann.as_path.contains(&as_obj.asn) {
+            return ValidationResult::RejectedLoop;
+        }
+        
+        if !ann.as_path.is_empty() {
+            if let Some(first_asn) = ann.as_path.first() {
+                if *first_asn != ann.next_hop_asn {
+                    return ValidationResult::RejectedNextHopMismatch;
+                }
+            }
+        }
+        
+        ValidationResult::Accepted
+    }
+}
+
+impl PolicyExtension for ROVPolicy {
+    fn validate_announcement(
+        &self,
+        ann: &Announcement,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        _route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        self.validate(ann, None, recv_relationship, as_obj)
+    }
+    
+    fn validate_announcement_by_id(
+        &self,
+        ann: &Announcement,
+        prefix_id: PrefixId,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        _route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        self.validate(ann, Some(prefix_id), recv_relationship, as_obj)
+    }
+    
+    fn compare_preference(
+        &self,
+        ann1: &Announcement,
+        ann2: &Announcement,
+        rel1: Relationships,
+        rel2: Relationships,
+        _as_obj: &AS,
+    ) -> Ordering {
+        let preference = || decision_process(ann1, ann2, self.local_pref(ann1, rel1), self.local_pref(ann2, rel2));
+        match self.mode {
+            // Invalid routes lose to any other before LOCAL_PREF is even compared
+            ROVMode::DeprefInvalid => self.is_invalid(ann1, None).cmp(&self.is_invalid(ann2, None)).then_with(preference),
+            ROVMode::DropInvalid => preference(),
+        }
+    }
+    
+    fn set_route_validator(&mut self, route_validator: Arc<RouteValidator>) {
+        self.route_validator = Some(route_validator);
+    }
+    
+    fn name(&self) -> &str {
+        "ROV"
+    }
+}
\ No newline at end of file
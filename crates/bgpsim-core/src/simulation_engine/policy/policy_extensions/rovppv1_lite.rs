@@ -1,57 +1,70 @@
+use std::sync::Arc;
+
 use crate::as_graphs::as_graph::{AS};
 use crate::route_validator::RouteValidator;
 use crate::shared::{Relationships, ROAValidity};
 use crate::simulation_engine::announcement::Announcement;
-use crate::simulation_engine::policy::{PolicyExtension, ProcessingResult};
+use crate::simulation_engine::policy::{PolicyExtension, ProcessingResult, ValidationResult};
+use crate::simulation_engine::prefix_table::PrefixId;
+
+use super::rov::roa_validity;
 
 /// ROV++ V1 Lite policy - extends ROV with blackholing
+/// Checks origins against the ROAs the engine shares, see `SimulationEngine::with_route_validator`
+#[derive(Default)]
 pub struct ROVPPV1LitePolicy {
-    pub route_validator: RouteValidator,
+    route_validator: Option<Arc<RouteValidator>>,
 }
 
 impl ROVPPV1LitePolicy {
-    pub fn new() -> Self {
-        ROVPPV1LitePolicy {
-            route_validator: RouteValidator::new(),
-        }
-    }
-}
-
-impl PolicyExtension for ROVPPV1LitePolicy {
-    fn validate_announcement(
-        &self,
-        ann: &Announcement,
-        recv_relationship: Relationships,
-        as_obj: &AS,
-        _route_validator: Option<&RouteValidator>,
-    ) -> bool {
+    fn validate(&self, ann: &Announcement, prefix_id: Option<PrefixId>, recv_relationship: Relationships, as_obj: &AS) -> ValidationResult {
         // First do standard validation
         if ann.as_path.is_empty() && recv_relationship != Relationships::Origin {
-            return false;
+            return ValidationResult::RejectedEmptyPath;
         }
 
         if ann.as_path.contains(&as_obj.asn) {
-            return false;
+            return ValidationResult::RejectedLoop;
         }
         
         if !ann.as_path.is_empty() {
             if let Some(first_asn) = ann.as_path.first() {
                 if *first_asn != ann.next_hop_asn {
-                    return false;
+                    return ValidationResult::RejectedNextHopMismatch;
                 }
             }
         }
         
         // Then check ROA validity
-        let origin = ann.as_path.last().copied().unwrap_or(ann.next_hop_asn);
-        let (validity, _) = self.route_validator.get_roa_outcome(&ann.prefix, origin);
-        
-        match validity {
-            ROAValidity::Valid => true,
-            ROAValidity::Unknown => true,
-            _ => false, // Reject invalid announcements
+        match roa_validity(self.route_validator.as_deref(), ann, prefix_id) {
+            ROAValidity::Valid => ValidationResult::Accepted,
+            ROAValidity::Unknown => ValidationResult::Accepted,
+            _ => ValidationResult::RejectedRoaInvalid, // Reject invalid announcements
         }
     }
+}
+
+impl PolicyExtension for ROVPPV1LitePolicy {
+    fn validate_announcement(
+        &self,
+        ann: &Announcement,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        _route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        self.validate(ann, None, recv_relationship, as_obj)
+    }
+    
+    fn validate_announcement_by_id(
+        &self,
+        ann: &Announcement,
+        prefix_id: PrefixId,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        _route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        self.validate(ann, Some(prefix_id), recv_relationship, as_obj)
+    }
     
     fn process_announcement(
         &mut self,
@@ -88,6 +101,10 @@ impl PolicyExtension for ROVPPV1LitePolicy {
         }
     }
     
+    fn set_route_validator(&mut self, route_validator: Arc<RouteValidator>) {
+        self.route_validator = Some(route_validator);
+    }
+    
     fn name(&self) -> &str {
         "ROVPPV1Lite"
     }
@@ -0,0 +1,207 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use crate::as_graphs::as_graph::{AS, ASGraph};
+use crate::aspa_validator::AspaValidator;
+use crate::irr_validator::IrrValidator;
+use crate::route_validator::RouteValidator;
+use crate::shared::{Relationships, Settings};
+use crate::simulation_engine::announcement::Announcement;
+use crate::simulation_engine::prefix_table::PrefixId;
+
+use super::policy_extensions::*;
+use super::{PolicyExtension, ProcessingResult, ValidationResult};
+
+/// Declares `PolicyKind` with one variant per built-in policy, and the `match` that forwards a call to it
+///
+/// `$d` is a literal `$`, which the nested `dispatch!` needs for its own metavariables
+macro_rules! policy_kinds {
+    ($d:tt $($(#[$attr:meta])* $variant:ident($policy:ty),)*) => {
+        /// A built-in policy held inline, or a user extension behind a `Box`
+        ///
+        /// Calls to built-in policies are a `match` rather than a virtual call, so they can be inlined
+        /// into the engine's validation and selection loops. With the `enum-dispatch` feature this is
+        /// the type of `Policy::extension`; without it, the enum is still usable as an extension
+        pub enum PolicyKind {
+            $($(#[$attr])* $variant($policy),)*
+            /// Any other `PolicyExtension`, dispatched dynamically
+            Custom(Box<dyn PolicyExtension>),
+        }
+
+        macro_rules! dispatch {
+            ($d kind:expr, $d extension:ident => $d call:expr) => {
+                match $d kind {
+                    $($(#[$attr])* PolicyKind::$variant($d extension) => $d call,)*
+                    PolicyKind::Custom($d extension) => $d call,
+                }
+            };
+        }
+    };
+}
+
+policy_kinds! {
+    $
+    Bgp(BGPPolicy),
+    Rov(ROVPolicy),
+    PeerRov(PeerROVPolicy),
+    OnlyToCustomers(OnlyToCustomersPolicy),
+    PathEnd(PathEndPolicy),
+    EnforceFirstAs(EnforceFirstASPolicy),
+    #[cfg(feature = "aspa")]
+    Aspa(ASPAPolicy),
+    #[cfg(feature = "bgpsec")]
+    Bgpsec(BGPSecPolicy),
+    #[cfg(feature = "rovpp")]
+    RovppV1Lite(ROVPPV1LitePolicy),
+    PeerLockLite(PeerlockLitePolicy),
+    EdgeFilter(ASPathEdgeFilterPolicy),
+    IrrFilter(IrrFilterPolicy),
+}
+
+impl PolicyKind {
+    /// The built-in policy for `settings`, falling back to BGP like `create_policy_extension`
+    pub fn new(settings: Settings) -> Self {
+        match settings {
+            Settings::BaseDefense => PolicyKind::Bgp(BGPPolicy),
+            Settings::Rov => PolicyKind::Rov(ROVPolicy::default()),
+            Settings::RovDepref => PolicyKind::Rov(ROVPolicy::default().with_mode(ROVMode::DeprefInvalid)),
+            Settings::PeerRov => PolicyKind::PeerRov(PeerROVPolicy::default()),
+            Settings::OnlyToCustomers => PolicyKind::OnlyToCustomers(OnlyToCustomersPolicy),
+            Settings::PathEnd => PolicyKind::PathEnd(PathEndPolicy::new()),
+            Settings::EnforceFirstAs => PolicyKind::EnforceFirstAs(EnforceFirstASPolicy),
+            #[cfg(feature = "aspa")]
+            Settings::Aspa => PolicyKind::Aspa(ASPAPolicy::new()),
+            #[cfg(feature = "bgpsec")]
+            Settings::Bgpsec => PolicyKind::Bgpsec(BGPSecPolicy),
+            #[cfg(feature = "rovpp")]
+            Settings::RovppV1Lite => PolicyKind::RovppV1Lite(ROVPPV1LitePolicy::default()),
+            Settings::PeerLockLite => PolicyKind::PeerLockLite(PeerlockLitePolicy),
+            Settings::EdgeFilter => PolicyKind::EdgeFilter(ASPathEdgeFilterPolicy),
+            Settings::IrrFilter => PolicyKind::IrrFilter(IrrFilterPolicy::new()),
+            _ => PolicyKind::Bgp(BGPPolicy),
+        }
+    }
+
+    /// Wrap a user extension
+    pub fn custom(extension: impl PolicyExtension + 'static) -> Self {
+        PolicyKind::Custom(Box::new(extension))
+    }
+
+    /// Whether calls go through a virtual call rather than a `match`
+    pub fn is_custom(&self) -> bool {
+        matches!(self, PolicyKind::Custom(_))
+    }
+}
+
+impl From<Box<dyn PolicyExtension>> for PolicyKind {
+    fn from(extension: Box<dyn PolicyExtension>) -> Self {
+        PolicyKind::Custom(extension)
+    }
+}
+
+impl PolicyExtension for PolicyKind {
+    #[inline]
+    fn validate_announcement(
+        &self,
+        ann: &Announcement,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        dispatch!(self, extension => extension.validate_announcement(ann, recv_relationship, as_obj, route_validator))
+    }
+
+    #[inline]
+    fn validate_announcement_by_id(
+        &self,
+        ann: &Announcement,
+        prefix_id: PrefixId,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+        route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        dispatch!(self, extension => extension.validate_announcement_by_id(ann, prefix_id, recv_relationship, as_obj, route_validator))
+    }
+
+    #[inline]
+    fn process_announcement(
+        &mut self,
+        ann: &mut Announcement,
+        recv_relationship: Relationships,
+        as_obj: &AS,
+    ) -> ProcessingResult {
+        dispatch!(self, extension => extension.process_announcement(ann, recv_relationship, as_obj))
+    }
+
+    #[inline]
+    fn should_propagate(
+        &self,
+        ann: &Announcement,
+        recv_relationship: Relationships,
+        send_relationship: Relationships,
+    ) -> bool {
+        dispatch!(self, extension => extension.should_propagate(ann, recv_relationship, send_relationship))
+    }
+
+    #[inline]
+    fn compare_announcements(
+        &self,
+        ann1: &Announcement,
+        ann2: &Announcement,
+        rel1: Relationships,
+        rel2: Relationships,
+        as_obj: &AS,
+    ) -> Ordering {
+        dispatch!(self, extension => extension.compare_announcements(ann1, ann2, rel1, rel2, as_obj))
+    }
+
+    #[inline]
+    fn compare_preference(
+        &self,
+        ann1: &Announcement,
+        ann2: &Announcement,
+        rel1: Relationships,
+        rel2: Relationships,
+        as_obj: &AS,
+    ) -> Ordering {
+        dispatch!(self, extension => extension.compare_preference(ann1, ann2, rel1, rel2, as_obj))
+    }
+
+    #[inline]
+    fn local_pref(&self, ann: &Announcement, rel: Relationships) -> u32 {
+        dispatch!(self, extension => extension.local_pref(ann, rel))
+    }
+
+    #[inline]
+    fn get_gao_rexford_preference(&self, rel: Relationships) -> u8 {
+        dispatch!(self, extension => extension.get_gao_rexford_preference(rel))
+    }
+
+    fn setup(&mut self, as_obj: &AS, as_graph: &ASGraph) {
+        dispatch!(self, extension => extension.setup(as_obj, as_graph))
+    }
+
+    fn reset(&mut self) {
+        dispatch!(self, extension => extension.reset())
+    }
+
+    fn set_aspa_validator(&mut self, aspa_validator: Arc<AspaValidator>) {
+        dispatch!(self, extension => extension.set_aspa_validator(aspa_validator))
+    }
+
+    fn set_irr_validator(&mut self, irr_validator: Arc<IrrValidator>) {
+        dispatch!(self, extension => extension.set_irr_validator(irr_validator))
+    }
+
+    fn set_route_validator(&mut self, route_validator: Arc<RouteValidator>) {
+        dispatch!(self, extension => extension.set_route_validator(route_validator))
+    }
+
+    fn violation(&self) -> Option<&str> {
+        dispatch!(self, extension => extension.violation())
+    }
+
+    fn name(&self) -> &str {
+        dispatch!(self, extension => extension.name())
+    }
+}
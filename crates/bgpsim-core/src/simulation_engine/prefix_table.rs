@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::ops::Index;
+use std::sync::{Arc, RwLock};
+
+use super::announcement::Prefix;
+
+/// Dense id of an interned prefix, see `PrefixTable`
+pub type PrefixId = u32;
+
+/// Interns the prefixes of a simulation so RIBs can be indexed by `PrefixId`
+///
+/// The engine's policies share one table, so a prefix is hashed once per announcement processed
+/// instead of once per RIB lookup. Ids are handed out in first-seen order and never reused.
+#[derive(Debug, Default)]
+pub struct PrefixTable {
+    inner: RwLock<PrefixTableInner>,
+}
+
+#[derive(Debug, Default)]
+struct PrefixTableInner {
+    ids: HashMap<Prefix, PrefixId>,
+    prefixes: Vec<Prefix>,
+}
+
+impl PrefixTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id of `prefix`, assigning the next one if it's new
+    pub fn intern(&self, prefix: Prefix) -> PrefixId {
+        if let Some(id) = self.id(&prefix) {
+            return id;
+        }
+        let mut inner = self.inner.write().unwrap();
+        if let Some(&id) = inner.ids.get(&prefix) {
+            return id;
+        }
+        let id = inner.prefixes.len() as PrefixId;
+        inner.prefixes.push(prefix);
+        inner.ids.insert(prefix, id);
+        id
+    }
+
+    /// The id of `prefix`, if it was interned
+    pub fn id(&self, prefix: &Prefix) -> Option<PrefixId> {
+        self.inner.read().unwrap().ids.get(prefix).copied()
+    }
+
+    pub fn prefix(&self, id: PrefixId) -> Option<Prefix> {
+        self.inner.read().unwrap().prefixes.get(id as usize).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().prefixes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Interned prefixes in id order
+    pub fn prefixes(&self) -> Vec<Prefix> {
+        self.inner.read().unwrap().prefixes.clone()
+    }
+}
+
+/// Map from prefix to `T` stored in a vector indexed by `PrefixId`
+///
+/// The `_by_id` methods skip hashing entirely; the others look the prefix up in the table first,
+/// and otherwise behave like the `HashMap<Prefix, T>` this replaces
+#[derive(Debug, Clone)]
+pub struct PrefixMap<T> {
+    table: Arc<PrefixTable>,
+    entries: Vec<Option<(Prefix, T)>>,
+    len: usize,
+}
+
+impl<T> PrefixMap<T> {
+    pub fn new(table: Arc<PrefixTable>) -> Self {
+        PrefixMap { table, entries: Vec::new(), len: 0 }
+    }
+
+    pub fn table(&self) -> &Arc<PrefixTable> {
+        &self.table
+    }
+
+    pub fn get_by_id(&self, id: PrefixId) -> Option<&T> {
+        self.entries.get(id as usize)?.as_ref().map(|(_, value)| value)
+    }
+
+    pub fn get_mut_by_id(&mut self, id: PrefixId) -> Option<&mut T> {
+        self.entries.get_mut(id as usize)?.as_mut().map(|(_, value)| value)
+    }
+
+    pub fn contains_id(&self, id: PrefixId) -> bool {
+        self.get_by_id(id).is_some()
+    }
+
+    /// Insert under `id`, which must be `prefix`'s id in this map's table
+    pub fn insert_by_id(&mut self, id: PrefixId, prefix: Prefix, value: T) -> Option<T> {
+        let index = id as usize;
+        if index >= self.entries.len() {
+            self.entries.resize_with(index + 1, || None);
+        }
+        let old = self.entries[index].replace((prefix, value)).map(|(_, old)| old);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn remove_by_id(&mut self, id: PrefixId) -> Option<T> {
+        let old = self.entries.get_mut(id as usize)?.take().map(|(_, old)| old);
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old
+    }
+
+    pub fn get(&self, prefix: &Prefix) -> Option<&T> {
+        self.table.id(prefix).and_then(|id| self.get_by_id(id))
+    }
+
+    pub fn get_mut(&mut self, prefix: &Prefix) -> Option<&mut T> {
+        let id = self.table.id(prefix)?;
+        self.get_mut_by_id(id)
+    }
+
+    pub fn contains_key(&self, prefix: &Prefix) -> bool {
+        self.get(prefix).is_some()
+    }
+
+    pub fn insert(&mut self, prefix: Prefix, value: T) -> Option<T> {
+        let id = self.table.intern(prefix);
+        self.insert_by_id(id, prefix, value)
+    }
+
+    pub fn remove(&mut self, prefix: &Prefix) -> Option<T> {
+        let id = self.table.id(prefix)?;
+        self.remove_by_id(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove every entry, keeping the allocation for the next run
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.len = 0;
+    }
+
+    /// Entries in `PrefixId` order
+    pub fn iter(&self) -> impl Iterator<Item = (&Prefix, &T)> {
+        self.entries.iter().filter_map(|entry| entry.as_ref().map(|(prefix, value)| (prefix, value)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Prefix> {
+        self.iter().map(|(prefix, _)| prefix)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.entries.iter_mut().filter_map(|entry| entry.as_mut().map(|(_, value)| value))
+    }
+}
+
+impl<T> Index<&Prefix> for PrefixMap<T> {
+    type Output = T;
+
+    fn index(&self, prefix: &Prefix) -> &T {
+        self.get(prefix).expect("prefix not in map")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PrefixMap<T> {
+    type Item = (&'a Prefix, &'a T);
+    type IntoIter = Box<dyn Iterator<Item = (&'a Prefix, &'a T)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::as_graphs::as_graph::ASN;
+use crate::simulation_engine::{Announcement, Prefix, SimulationEngine};
+
+/// Format of `SimulationEngine::export_rib`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RibFormat {
+    #[default]
+    Json,
+    /// Table like a router's `show ip bgp`
+    Text,
+}
+
+impl fmt::Display for RibFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            RibFormat::Json => "json",
+            RibFormat::Text => "text",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for RibFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(RibFormat::Json),
+            "text" => Ok(RibFormat::Text),
+            _ => Err(format!("Unknown RIB format: {}", s)),
+        }
+    }
+}
+
+/// A route in an exported RIB, `best` if it is the one the AS selected
+struct RibRoute<'a> {
+    ann: &'a Announcement,
+    best: bool,
+    /// Path as the next hop sent it, without the AS's own ASN the local RIB adds
+    as_path: Vec<ASN>,
+    validation: Option<String>,
+}
+
+impl<'a> RibRoute<'a> {
+    fn new(engine: &SimulationEngine, asn: ASN, ann: &'a Announcement, best: bool) -> Self {
+        let mut as_path = ann.as_path.to_vec();
+        if as_path.first() == Some(&asn) {
+            as_path.remove(0);
+        }
+        let validation = engine.route_validator()
+            .map(|validator| validator.get_roa_outcome(&ann.prefix, ann.origin()).0.to_string());
+        RibRoute { ann, best, as_path, validation }
+    }
+}
+
+impl SimulationEngine<'_> {
+    /// Every route `asn` knows, by prefix with the selected one first
+    ///
+    /// Alternatives come from the RIBs in, so they need `PolicyStorageMode::Full`. Paths are shown as
+    /// received, so routes the AS originates have an empty one. Validation is the route's ROV state
+    /// against the engine's route validator, if it has one
+    pub fn export_rib(&self, asn: ASN, format: RibFormat) -> Result<String, String> {
+        let policy = self.policy_store.get(&asn).ok_or_else(|| format!("AS {} is not in the graph", asn))?;
+
+        let mut prefixes: Vec<Prefix> = policy.local_rib.iter().map(|(prefix, _)| *prefix)
+            .chain(policy.ribs_in.prefixes().copied())
+            .collect();
+        prefixes.sort_by_key(|prefix| (prefix.is_ipv6(), prefix.network(), prefix.prefix()));
+        prefixes.dedup();
+
+        let mut routes = Vec::new();
+        for prefix in prefixes {
+            let best = policy.local_rib.get(&prefix).filter(|ann| !ann.withdraw);
+            if let Some(ann) = best {
+                routes.push(RibRoute::new(self, asn, ann, true));
+            }
+            // RIBs-in keep each prefix's routes sorted by neighbor, so the export doesn't depend on arrival order
+            let alternatives = policy.ribs_in.best_candidates(&prefix)
+                .filter(|ann| best.is_none_or(|best| best.next_hop_asn != ann.next_hop_asn));
+            routes.extend(alternatives.map(|ann| RibRoute::new(self, asn, ann, false)));
+        }
+
+        Ok(match format {
+            RibFormat::Json => rib_json(asn, policy.settings, &routes),
+            RibFormat::Text => rib_text(asn, &routes),
+        })
+    }
+}
+
+fn rib_json(asn: ASN, settings: crate::shared::Settings, routes: &[RibRoute]) -> String {
+    let routes: Vec<serde_json::Value> = routes.iter()
+        .map(|route| serde_json::json!({
+            "prefix": route.ann.prefix.to_string(),
+            "best": route.best,
+            "as_path": route.as_path,
+            "next_hop_asn": route.ann.next_hop_asn,
+            "relationship": route.ann.recv_relationship.to_string(),
+            "validation": route.validation,
+            "timestamp": route.ann.timestamp.0,
+        }))
+        .collect();
+    let rib = serde_json::json!({"asn": asn, "settings": settings, "routes": routes});
+    serde_json::to_string_pretty(&rib).unwrap()
+}
+
+fn rib_text(asn: ASN, routes: &[RibRoute]) -> String {
+    let mut text = format!("BGP table of AS {}\nStatus codes: * valid, > best\n\n", asn);
+    let _ = writeln!(text, "   {:<20} {:<10} {:<13} {:<26} Path", "Network", "Next Hop", "Relationship", "Validation");
+    for route in routes {
+        let path: Vec<String> = route.as_path.iter().map(|hop| hop.to_string()).collect();
+        let _ = writeln!(
+            text,
+            "{:<3}{:<20} {:<10} {:<13} {:<26} {}",
+            if route.best { "*>" } else { "*" },
+            route.ann.prefix.to_string(),
+            route.ann.next_hop_asn,
+            route.ann.recv_relationship.to_string(),
+            route.validation.as_deref().unwrap_or("-"),
+            path.join(" "),
+        );
+    }
+    text
+}
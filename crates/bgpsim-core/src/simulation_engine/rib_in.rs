@@ -0,0 +1,169 @@
+use std::ops::Index;
+use std::sync::Arc;
+
+use crate::as_graphs::as_graph::ASN;
+use super::announcement::{Announcement, Prefix};
+use super::prefix_table::{PrefixId, PrefixTable};
+
+/// Routes received from each neighbor, keyed by (neighbor, prefix)
+///
+/// Stored as one vector per `PrefixId`, sorted by neighbor, so the routes best path selection
+/// compares sit next to each other and a lookup is an index plus a binary search, with no hashing.
+/// Each prefix's vector starts with room for `neighbor_capacity` routes, since an AS gets at most
+/// one route per neighbor for it, capped at `MAX_RESERVED_ROUTES` so high-degree ASes don't reserve
+/// thousands of slots for prefixes only a few neighbors send
+#[derive(Debug, Clone)]
+pub struct RIBsIn {
+    table: Arc<PrefixTable>,
+    routes: Vec<Vec<(ASN, Announcement)>>,
+    len: usize,
+    neighbor_capacity: usize,
+}
+
+/// Most routes reserved up front for a prefix; vectors for prefixes with more senders grow as usual
+const MAX_RESERVED_ROUTES: usize = 4;
+
+impl RIBsIn {
+    pub fn new(table: Arc<PrefixTable>) -> Self {
+        RIBsIn { table, routes: Vec::new(), len: 0, neighbor_capacity: 0 }
+    }
+
+    pub fn table(&self) -> &Arc<PrefixTable> {
+        &self.table
+    }
+
+    /// Room to reserve for each prefix's routes, usually the AS's number of neighbors, up to `MAX_RESERVED_ROUTES`
+    pub fn set_neighbor_capacity(&mut self, neighbor_capacity: usize) {
+        self.neighbor_capacity = neighbor_capacity;
+    }
+
+    pub fn neighbor_capacity(&self) -> usize {
+        self.neighbor_capacity
+    }
+
+    /// Every route for the prefix, withdrawals included, sorted by neighbor
+    pub fn routes_by_id(&self, id: PrefixId) -> &[(ASN, Announcement)] {
+        self.routes.get(id as usize).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn routes(&self, prefix: &Prefix) -> &[(ASN, Announcement)] {
+        self.table.id(prefix).map_or(&[], |id| self.routes_by_id(id))
+    }
+
+    /// Routes best path selection chooses between for the prefix, in neighbor order
+    pub fn best_candidates_by_id(&self, id: PrefixId) -> impl Iterator<Item = &Announcement> {
+        self.routes_by_id(id).iter()
+            .map(|(_, ann)| ann)
+            .filter(|ann| !ann.withdraw)
+    }
+
+    pub fn best_candidates(&self, prefix: &Prefix) -> impl Iterator<Item = &Announcement> {
+        self.routes(prefix).iter()
+            .map(|(_, ann)| ann)
+            .filter(|ann| !ann.withdraw)
+    }
+
+    pub fn get_by_id(&self, neighbor_asn: ASN, id: PrefixId) -> Option<&Announcement> {
+        let routes = self.routes_by_id(id);
+        routes.binary_search_by_key(&neighbor_asn, |(asn, _)| *asn).ok().map(|index| &routes[index].1)
+    }
+
+    pub fn get(&self, neighbor_asn: ASN, prefix: &Prefix) -> Option<&Announcement> {
+        self.table.id(prefix).and_then(|id| self.get_by_id(neighbor_asn, id))
+    }
+
+    pub fn contains(&self, neighbor_asn: ASN, prefix: &Prefix) -> bool {
+        self.get(neighbor_asn, prefix).is_some()
+    }
+
+    /// Insert under `id`, which must be the id of `ann.prefix` in this store's table
+    pub fn insert_by_id(&mut self, neighbor_asn: ASN, id: PrefixId, ann: Announcement) -> Option<Announcement> {
+        let index = id as usize;
+        if index >= self.routes.len() {
+            self.routes.resize_with(index + 1, Vec::new);
+        }
+        let routes = &mut self.routes[index];
+        match routes.binary_search_by_key(&neighbor_asn, |(asn, _)| *asn) {
+            Ok(position) => Some(std::mem::replace(&mut routes[position].1, ann)),
+            Err(position) => {
+                if routes.capacity() == 0 {
+                    routes.reserve_exact(self.neighbor_capacity.clamp(1, MAX_RESERVED_ROUTES));
+                }
+                routes.insert(position, (neighbor_asn, ann));
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, neighbor_asn: ASN, ann: Announcement) -> Option<Announcement> {
+        let id = self.table.intern(ann.prefix);
+        self.insert_by_id(neighbor_asn, id, ann)
+    }
+
+    pub fn remove_by_id(&mut self, neighbor_asn: ASN, id: PrefixId) -> Option<Announcement> {
+        let routes = self.routes.get_mut(id as usize)?;
+        let position = routes.binary_search_by_key(&neighbor_asn, |(asn, _)| *asn).ok()?;
+        self.len -= 1;
+        Some(routes.remove(position).1)
+    }
+
+    pub fn remove(&mut self, neighbor_asn: ASN, prefix: &Prefix) -> Option<Announcement> {
+        let id = self.table.id(prefix)?;
+        self.remove_by_id(neighbor_asn, id)
+    }
+
+    /// Routes from `neighbor_asn`, in `PrefixId` order
+    pub fn neighbor_routes(&self, neighbor_asn: ASN) -> impl Iterator<Item = &Announcement> {
+        self.routes.iter().filter_map(move |routes| {
+            routes.binary_search_by_key(&neighbor_asn, |(asn, _)| *asn).ok().map(|index| &routes[index].1)
+        })
+    }
+
+    /// `neighbor_routes` for updating in place, e.g. with a new LOCAL_PREF
+    pub fn neighbor_routes_mut(&mut self, neighbor_asn: ASN) -> impl Iterator<Item = &mut Announcement> {
+        self.routes.iter_mut().filter_map(move |routes| {
+            routes.binary_search_by_key(&neighbor_asn, |(asn, _)| *asn).ok().map(|index| &mut routes[index].1)
+        })
+    }
+
+    /// Every route with the neighbor it came from, by `PrefixId` and then neighbor
+    pub fn iter(&self) -> impl Iterator<Item = (ASN, &Announcement)> {
+        self.routes.iter().flatten().map(|(asn, ann)| (*asn, ann))
+    }
+
+    /// Prefixes with at least one route, in `PrefixId` order
+    pub fn prefixes(&self) -> impl Iterator<Item = &Prefix> {
+        self.routes.iter().filter_map(|routes| routes.first().map(|(_, ann)| &ann.prefix))
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove every route, keeping the allocations for the next run
+    pub fn clear(&mut self) {
+        self.routes.iter_mut().for_each(Vec::clear);
+        self.len = 0;
+    }
+
+    /// Release the memory of prefixes without routes and the spare room of the rest
+    pub fn shrink_to_fit(&mut self) {
+        self.routes.iter_mut().for_each(Vec::shrink_to_fit);
+        let used = self.routes.iter().rposition(|routes| !routes.is_empty()).map_or(0, |index| index + 1);
+        self.routes.truncate(used);
+        self.routes.shrink_to_fit();
+    }
+}
+
+impl Index<(ASN, &Prefix)> for RIBsIn {
+    type Output = Announcement;
+
+    fn index(&self, (neighbor_asn, prefix): (ASN, &Prefix)) -> &Announcement {
+        self.get(neighbor_asn, prefix).expect("no route from neighbor for prefix")
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::as_graphs::as_graph::ASN;
+
+/// Local RIB snapshot as produced by `SimulationEngine::get_local_rib_snapshot`
+pub type LocalRIBSnapshot = HashMap<ASN, HashMap<String, Vec<ASN>>>;
+
+/// Change to a single prefix in an AS's local RIB between two rounds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteChange {
+    Installed { prefix: String, as_path: Vec<ASN> },
+    Removed { prefix: String, as_path: Vec<ASN> },
+    Replaced { prefix: String, old_as_path: Vec<ASN>, new_as_path: Vec<ASN> },
+}
+
+impl RouteChange {
+    pub fn prefix(&self) -> &str {
+        match self {
+            RouteChange::Installed { prefix, .. }
+            | RouteChange::Removed { prefix, .. }
+            | RouteChange::Replaced { prefix, .. } => prefix,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            RouteChange::Installed { prefix, as_path } => serde_json::json!({
+                "change": "installed",
+                "prefix": prefix,
+                "as_path": as_path,
+            }),
+            RouteChange::Removed { prefix, as_path } => serde_json::json!({
+                "change": "removed",
+                "prefix": prefix,
+                "as_path": as_path,
+            }),
+            RouteChange::Replaced { prefix, old_as_path, new_as_path } => serde_json::json!({
+                "change": "replaced",
+                "prefix": prefix,
+                "old_as_path": old_as_path,
+                "new_as_path": new_as_path,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for RouteChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteChange::Installed { prefix, as_path } => write!(f, "+ {} {:?}", prefix, as_path),
+            RouteChange::Removed { prefix, as_path } => write!(f, "- {} {:?}", prefix, as_path),
+            RouteChange::Replaced { prefix, old_as_path, new_as_path } => {
+                write!(f, "~ {} {:?} -> {:?}", prefix, old_as_path, new_as_path)
+            }
+        }
+    }
+}
+
+/// Per-AS local RIB changes caused by a single propagation round
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoundDiff {
+    /// Round that produced these changes (0-indexed)
+    pub round: u32,
+    pub changes: BTreeMap<ASN, Vec<RouteChange>>,
+}
+
+impl RoundDiff {
+    /// Compute the changes from one snapshot to the next
+    pub fn between(round: u32, before: &LocalRIBSnapshot, after: &LocalRIBSnapshot) -> Self {
+        let empty = HashMap::new();
+        let mut changes = BTreeMap::new();
+
+        for asn in before.keys().chain(after.keys()) {
+            if changes.contains_key(asn) {
+                continue;
+            }
+            let old_ribs = before.get(asn).unwrap_or(&empty);
+            let new_ribs = after.get(asn).unwrap_or(&empty);
+
+            let mut as_changes = Vec::new();
+            for (prefix, new_as_path) in new_ribs {
+                match old_ribs.get(prefix) {
+                    None => as_changes.push(RouteChange::Installed {
+                        prefix: prefix.clone(),
+                        as_path: new_as_path.clone(),
+                    }),
+                    Some(old_as_path) if old_as_path != new_as_path => as_changes.push(RouteChange::Replaced {
+                        prefix: prefix.clone(),
+                        old_as_path: old_as_path.clone(),
+                        new_as_path: new_as_path.clone(),
+                    }),
+                    _ => {}
+                }
+            }
+            for (prefix, old_as_path) in old_ribs {
+                if !new_ribs.contains_key(prefix) {
+                    as_changes.push(RouteChange::Removed {
+                        prefix: prefix.clone(),
+                        as_path: old_as_path.clone(),
+                    });
+                }
+            }
+
+            as_changes.sort_by(|a, b| a.prefix().cmp(b.prefix()));
+            changes.insert(*asn, as_changes);
+        }
+
+        changes.retain(|_, as_changes| !as_changes.is_empty());
+        RoundDiff { round, changes }
+    }
+
+    /// No AS changed its routes this round
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Total number of route changes across all ASes
+    pub fn num_changes(&self) -> usize {
+        self.changes.values().map(|as_changes| as_changes.len()).sum()
+    }
+
+    /// `{"round": n, "changes": {"<asn>": [change, ...]}}`
+    pub fn to_json(&self) -> serde_json::Value {
+        let changes: serde_json::Map<String, serde_json::Value> = self.changes.iter()
+            .map(|(asn, as_changes)| {
+                let as_changes = as_changes.iter().map(RouteChange::to_json).collect();
+                (asn.to_string(), serde_json::Value::Array(as_changes))
+            })
+            .collect();
+        serde_json::json!({ "round": self.round, "changes": changes })
+    }
+}
+
+impl fmt::Display for RoundDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Round {}: {} change(s)", self.round, self.num_changes())?;
+        for (asn, as_changes) in &self.changes {
+            for change in as_changes {
+                writeln!(f, "  AS {}: {}", asn, change)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+#[cfg(feature = "fs")]
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::as_graphs::as_graph::ASN;
+use crate::shared::Relationships;
+
+use super::announcement::Prefix;
+use super::engine::SimulationEngine;
+
+/// Read-only binary snapshot of every AS's converged local RIB
+///
+/// The file is a sequence of little-endian u32 words so readers (in Rust or over FFI) can index
+/// it in place, without parsing JSON or keeping the engine alive:
+///
+/// - header: magic `BGPS`, version, num_prefixes, num_ases, num_routes, num_path_asns
+/// - prefixes: 5 words each, `(ip_version << 8) | prefix_len` then the address in 4 big-endian words
+///   (IPv4 uses the first), sorted so prefix ids follow prefix order
+/// - asns: sorted
+/// - route_offsets: num_ases + 1 words; AS `i`'s routes are `route_offsets[i]..route_offsets[i + 1]`
+/// - routes: 5 words each, `prefix_id, next_hop_asn, recv_relationship, path_offset, path_len`,
+///   sorted by prefix id within each AS
+/// - path_asns: every route's AS path, back to back
+pub struct EngineSnapshot;
+
+impl EngineSnapshot {
+    pub const MAGIC: u32 = u32::from_le_bytes(*b"BGPS");
+    pub const VERSION: u32 = 1;
+    const HEADER_WORDS: usize = 6;
+    const PREFIX_WORDS: usize = 5;
+    const ROUTE_WORDS: usize = 5;
+
+    /// Encode the engine's local RIBs
+    pub fn to_bytes(engine: &SimulationEngine) -> Vec<u8> {
+        let prefixes: Vec<Prefix> = engine.policy_store.iter()
+            .flat_map(|(_, policy)| policy.local_rib.keys().copied())
+            .collect::<BTreeSet<Prefix>>()
+            .into_iter()
+            .collect();
+        let mut asns: Vec<ASN> = engine.policy_store.iter().map(|(&asn, _)| asn).collect();
+        asns.sort_unstable();
+
+        let mut route_offsets = Vec::with_capacity(asns.len() + 1);
+        let mut routes = Vec::new();
+        let mut path_asns = Vec::new();
+        for asn in &asns {
+            route_offsets.push((routes.len() / Self::ROUTE_WORDS) as u32);
+            let policy = match engine.policy_store.get(asn) {
+                Some(policy) => policy,
+                None => continue,
+            };
+            let mut anns: Vec<_> = policy.local_rib.values().filter(|ann| !ann.withdraw).collect();
+            anns.sort_by_key(|ann| ann.prefix);
+            for ann in anns {
+                let prefix_id = prefixes.binary_search(&ann.prefix).unwrap_or_default();
+                routes.extend([
+                    prefix_id as u32,
+                    ann.next_hop_asn,
+                    ann.recv_relationship as u32,
+                    path_asns.len() as u32,
+                    ann.as_path.len() as u32,
+                ]);
+                path_asns.extend(ann.as_path.iter().copied());
+            }
+        }
+        route_offsets.push((routes.len() / Self::ROUTE_WORDS) as u32);
+
+        let mut words = vec![
+            Self::MAGIC,
+            Self::VERSION,
+            prefixes.len() as u32,
+            asns.len() as u32,
+            (routes.len() / Self::ROUTE_WORDS) as u32,
+            path_asns.len() as u32,
+        ];
+        for prefix in &prefixes {
+            words.extend(encode_prefix(prefix));
+        }
+        words.extend(asns);
+        words.extend(route_offsets);
+        words.extend(routes);
+        words.extend(path_asns);
+
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn save_to_file(engine: &SimulationEngine, path: &Path) -> std::io::Result<()> {
+        fs::write(path, Self::to_bytes(engine))
+    }
+}
+
+fn encode_prefix(prefix: &Prefix) -> [u32; EngineSnapshot::PREFIX_WORDS] {
+    let mut words = [0; EngineSnapshot::PREFIX_WORDS];
+    match prefix.ip() {
+        IpAddr::V4(ip) => {
+            words[0] = (4 << 8) | prefix.prefix() as u32;
+            words[1] = u32::from(ip);
+        }
+        IpAddr::V6(ip) => {
+            words[0] = (6 << 8) | prefix.prefix() as u32;
+            let bits = u128::from(ip);
+            for (i, word) in words[1..].iter_mut().enumerate() {
+                *word = (bits >> (96 - 32 * i)) as u32;
+            }
+        }
+    }
+    words
+}
+
+/// Zero-copy view of a snapshot written by `EngineSnapshot`
+///
+/// Only the header is validated up front; every lookup reads the underlying bytes in place
+#[derive(Debug, Clone, Copy)]
+pub struct SnapshotReader<'a> {
+    bytes: &'a [u8],
+    num_prefixes: usize,
+    num_ases: usize,
+    num_routes: usize,
+    prefixes_start: usize,
+    asns_start: usize,
+    route_offsets_start: usize,
+    routes_start: usize,
+    path_asns_start: usize,
+}
+
+/// One AS's best route for a prefix, borrowed from the snapshot
+#[derive(Debug, Clone, Copy)]
+pub struct SnapshotRoute<'a> {
+    reader: SnapshotReader<'a>,
+    pub prefix_id: u32,
+    pub next_hop_asn: ASN,
+    pub recv_relationship: Relationships,
+    path_offset: usize,
+    path_len: usize,
+}
+
+impl<'a> SnapshotReader<'a> {
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, String> {
+        let word = |index: usize| -> Result<u32, String> {
+            bytes.get(index * 4..index * 4 + 4)
+                .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+                .ok_or_else(|| "Snapshot header is truncated".to_string())
+        };
+        if word(0)? != EngineSnapshot::MAGIC {
+            return Err("Not an engine snapshot".to_string());
+        }
+        if word(1)? != EngineSnapshot::VERSION {
+            return Err(format!("Unsupported snapshot version {}", word(1)?));
+        }
+        let num_prefixes = word(2)? as usize;
+        let num_ases = word(3)? as usize;
+        let num_routes = word(4)? as usize;
+        let num_path_asns = word(5)? as usize;
+
+        let prefixes_start = EngineSnapshot::HEADER_WORDS;
+        let asns_start = prefixes_start + num_prefixes * EngineSnapshot::PREFIX_WORDS;
+        let route_offsets_start = asns_start + num_ases;
+        let routes_start = route_offsets_start + num_ases + 1;
+        let path_asns_start = routes_start + num_routes * EngineSnapshot::ROUTE_WORDS;
+        let num_words = path_asns_start + num_path_asns;
+        if bytes.len() != num_words * 4 {
+            return Err(format!("Snapshot is {} bytes, expected {}", bytes.len(), num_words * 4));
+        }
+
+        Ok(SnapshotReader {
+            bytes,
+            num_prefixes,
+            num_ases,
+            num_routes,
+            prefixes_start,
+            asns_start,
+            route_offsets_start,
+            routes_start,
+            path_asns_start,
+        })
+    }
+
+    fn word(&self, index: usize) -> u32 {
+        u32::from_le_bytes(self.bytes[index * 4..index * 4 + 4].try_into().unwrap())
+    }
+
+    pub fn num_prefixes(&self) -> usize {
+        self.num_prefixes
+    }
+
+    pub fn num_ases(&self) -> usize {
+        self.num_ases
+    }
+
+    pub fn num_routes(&self) -> usize {
+        self.num_routes
+    }
+
+    /// ASNs in the snapshot, sorted
+    pub fn asns(&self) -> impl Iterator<Item = ASN> + 'a {
+        let reader = *self;
+        (0..self.num_ases).map(move |i| reader.word(reader.asns_start + i))
+    }
+
+    pub fn prefix(&self, prefix_id: u32) -> Option<Prefix> {
+        if prefix_id as usize >= self.num_prefixes {
+            return None;
+        }
+        let start = self.prefixes_start + prefix_id as usize * EngineSnapshot::PREFIX_WORDS;
+        let header = self.word(start);
+        let ip = match header >> 8 {
+            4 => IpAddr::V4(Ipv4Addr::from(self.word(start + 1))),
+            6 => {
+                let bits = (1..5).fold(0u128, |bits, i| (bits << 32) | self.word(start + i) as u128);
+                IpAddr::V6(Ipv6Addr::from(bits))
+            }
+            _ => return None,
+        };
+        Prefix::new(ip, (header & 0xff) as u8).ok()
+    }
+
+    /// Id of `prefix`, if any AS has a route for it
+    pub fn prefix_id(&self, prefix: &Prefix) -> Option<u32> {
+        let (mut low, mut high) = (0, self.num_prefixes);
+        while low < high {
+            let mid = (low + high) / 2;
+            match self.prefix(mid as u32)?.cmp(prefix) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some(mid as u32),
+            }
+        }
+        None
+    }
+
+    /// Every route in the AS's local RIB, sorted by prefix id
+    pub fn routes(&self, asn: ASN) -> impl Iterator<Item = SnapshotRoute<'a>> + 'a {
+        let reader = *self;
+        let (low, high) = match self.as_index(asn) {
+            Some(index) => (
+                self.word(self.route_offsets_start + index) as usize,
+                self.word(self.route_offsets_start + index + 1) as usize,
+            ),
+            None => (0, 0),
+        };
+        (low..high).map(move |route| reader.route_at(route))
+    }
+
+    /// The AS's route for `prefix`, if it has one
+    pub fn route(&self, asn: ASN, prefix: &Prefix) -> Option<SnapshotRoute<'a>> {
+        let prefix_id = self.prefix_id(prefix)?;
+        self.routes(asn).find(|route| route.prefix_id == prefix_id)
+    }
+
+    fn as_index(&self, asn: ASN) -> Option<usize> {
+        let (mut low, mut high) = (0, self.num_ases);
+        while low < high {
+            let mid = (low + high) / 2;
+            match self.word(self.asns_start + mid).cmp(&asn) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some(mid),
+            }
+        }
+        None
+    }
+
+    fn route_at(&self, route: usize) -> SnapshotRoute<'a> {
+        let start = self.routes_start + route * EngineSnapshot::ROUTE_WORDS;
+        SnapshotRoute {
+            reader: *self,
+            prefix_id: self.word(start),
+            next_hop_asn: self.word(start + 1),
+            recv_relationship: relationship_from_u32(self.word(start + 2)),
+            path_offset: self.word(start + 3) as usize,
+            path_len: self.word(start + 4) as usize,
+        }
+    }
+}
+
+impl<'a> SnapshotRoute<'a> {
+    pub fn prefix(&self) -> Option<Prefix> {
+        self.reader.prefix(self.prefix_id)
+    }
+
+    pub fn as_path_len(&self) -> usize {
+        self.path_len
+    }
+
+    /// AS path, starting with the AS that holds the route
+    pub fn as_path(&self) -> impl Iterator<Item = ASN> + 'a {
+        let reader = self.reader;
+        let start = reader.path_asns_start + self.path_offset;
+        (start..start + self.path_len).map(move |index| reader.word(index))
+    }
+
+    pub fn origin(&self) -> Option<ASN> {
+        self.as_path().last()
+    }
+}
+
+fn relationship_from_u32(value: u32) -> Relationships {
+    match value {
+        1 => Relationships::Providers,
+        2 => Relationships::Peers,
+        3 => Relationships::Customers,
+        4 => Relationships::Origin,
+        _ => Relationships::Unknown,
+    }
+}
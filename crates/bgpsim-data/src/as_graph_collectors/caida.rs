@@ -0,0 +1,320 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use scraper::{Html, Selector};
+
+use bgpsim_core::Error;
+
+/// Downloads CAIDA serial-2 AS relationship files and caches them decompressed
+///
+/// CAIDA does not publish a snapshot for every day, so the index is probed for the
+/// snapshot nearest to the requested date rather than building the URL blindly
+#[derive(Debug, Clone)]
+pub struct CAIDAASGraphCollector {
+    /// Date of the snapshot to download (the nearest available one is used)
+    pub dl_time: NaiveDate,
+
+    /// Directory that holds decompressed snapshots and their checksums
+    pub cache_dir: PathBuf,
+
+    /// URL of the serial-2 index
+    pub base_url: String,
+
+    /// Number of attempts for each HTTP request
+    pub max_retries: u32,
+
+    /// Delay before the first retry; doubled on every subsequent retry
+    pub backoff: Duration,
+}
+
+impl CAIDAASGraphCollector {
+    pub const DEFAULT_BASE_URL: &'static str = "https://publicdata.caida.org/datasets/as-relationships/serial-2/";
+
+    /// Decompressed serial-2 files are tens of MB; anything this small is truncated or an error page
+    pub const MIN_FILE_SIZE: usize = 1024;
+
+    /// Minimum number of relationship lines expected in a valid snapshot
+    pub const MIN_RELATIONSHIPS: usize = 10;
+
+    pub fn new(dl_time: NaiveDate, cache_dir: PathBuf) -> Self {
+        CAIDAASGraphCollector {
+            dl_time,
+            cache_dir,
+            base_url: Self::DEFAULT_BASE_URL.to_string(),
+            max_retries: 5,
+            backoff: Duration::from_secs(1),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries.max(1);
+        self
+    }
+
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Get the path of a validated, decompressed snapshot, downloading it if necessary
+    pub fn run(&self) -> Result<PathBuf, Error> {
+        if let Some(path) = self.get_cached_exact()? {
+            tracing::debug!(path = %path.display(), "Using cached CAIDA snapshot");
+            return Ok(path);
+        }
+
+        let index = self.parse_index_bytes(self.get_with_retry(&self.base_url)?)?;
+        let date = self.pick_date(&index)?;
+
+        let path = self.cache_path(date);
+        if Self::cached_file_valid(&path) {
+            tracing::debug!(path = %path.display(), "Using cached CAIDA snapshot");
+            return Ok(path);
+        }
+
+        tracing::info!(%date, "Downloading CAIDA snapshot");
+        let compressed = self.get_with_retry(&self.file_url(date))?;
+        self.store(&path, &compressed)
+    }
+
+    /// Async version of `run` for callers already inside a tokio runtime,
+    /// where `reqwest::blocking` would panic
+    /// Cache reads and writes and decompressing the snapshot run on tokio's blocking pool
+    #[cfg(feature = "async")]
+    pub async fn run_async(&self) -> Result<PathBuf, Error> {
+        let collector = self.clone();
+        if let Some(path) = Self::spawn_blocking(move || collector.get_cached_exact()).await? {
+            tracing::debug!(path = %path.display(), "Using cached CAIDA snapshot");
+            return Ok(path);
+        }
+
+        let index = self.parse_index_bytes(self.get_with_retry_async(&self.base_url).await?)?;
+        let date = self.pick_date(&index)?;
+
+        let path = self.cache_path(date);
+        let cached_path = path.clone();
+        if Self::spawn_blocking(move || Ok(Self::cached_file_valid(&cached_path))).await? {
+            tracing::debug!(path = %path.display(), "Using cached CAIDA snapshot");
+            return Ok(path);
+        }
+
+        tracing::info!(%date, "Downloading CAIDA snapshot");
+        let compressed = self.get_with_retry_async(&self.file_url(date)).await?;
+        let collector = self.clone();
+        Self::spawn_blocking(move || collector.store(&path, &compressed)).await
+    }
+
+    /// Run `f` on tokio's blocking pool
+    #[cfg(feature = "async")]
+    async fn spawn_blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, Error> + Send + 'static) -> Result<T, Error> {
+        tokio::task::spawn_blocking(f).await.map_err(|e| Error::Io(std::io::Error::other(e)))?
+    }
+
+    /// Skip the network entirely if the exact snapshot is already cached
+    fn get_cached_exact(&self) -> Result<Option<PathBuf>, Error> {
+        fs::create_dir_all(&self.cache_dir)?;
+        let exact_path = self.cache_path(self.dl_time);
+        Ok(Some(exact_path).filter(|path| Self::cached_file_valid(path)))
+    }
+
+    fn parse_index_bytes(&self, index: Vec<u8>) -> Result<String, Error> {
+        String::from_utf8(index).map_err(|e| Error::Parse(format!("CAIDA index at {} is not UTF-8: {}", self.base_url, e)))
+    }
+
+    fn pick_date(&self, index: &str) -> Result<NaiveDate, Error> {
+        let dates = Self::parse_index(index);
+        Self::nearest_available_date(&dates, self.dl_time)
+            .ok_or_else(|| Error::Download(format!("No CAIDA snapshots listed at {}", self.base_url)))
+    }
+
+    fn file_url(&self, date: NaiveDate) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), Self::file_name(date))
+    }
+
+    /// Decompress and validate a downloaded snapshot, then cache it
+    fn store(&self, path: &Path, compressed: &[u8]) -> Result<PathBuf, Error> {
+        let mut contents = String::new();
+        bzip2::read::BzDecoder::new(compressed).read_to_string(&mut contents)
+            .map_err(|e| Error::Parse(format!("Failed to decompress CAIDA snapshot: {}", e)))?;
+        Self::validate_contents(&contents)?;
+
+        Self::write_cache(path, &contents)?;
+        Ok(path.to_path_buf())
+    }
+
+    /// Name of the compressed snapshot for a date, e.g. 20240101.as-rel2.txt.bz2
+    pub fn file_name(date: NaiveDate) -> String {
+        format!("{}.as-rel2.txt.bz2", date.format("%Y%m%d"))
+    }
+
+    /// Path of the decompressed snapshot for a date in the cache
+    pub fn cache_path(&self, date: NaiveDate) -> PathBuf {
+        self.cache_dir.join(format!("{}.as-rel2.txt", date.format("%Y%m%d")))
+    }
+
+    /// Extract the snapshot dates linked from the serial-2 index page, sorted
+    pub fn parse_index(html: &str) -> Vec<NaiveDate> {
+        let document = Html::parse_document(html);
+        let selector = Selector::parse("a").unwrap();
+
+        let mut dates: Vec<NaiveDate> = document.select(&selector)
+            .filter_map(|link| link.value().attr("href"))
+            .filter_map(|href| href.strip_suffix(".as-rel2.txt.bz2"))
+            .filter_map(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+            .collect();
+        dates.sort();
+        dates.dedup();
+        dates
+    }
+
+    /// Pick the latest snapshot on or before the target date, falling back to the earliest one after it
+    pub fn nearest_available_date(dates: &[NaiveDate], target: NaiveDate) -> Option<NaiveDate> {
+        dates.iter()
+            .filter(|&&date| date <= target)
+            .max()
+            .or_else(|| dates.iter().filter(|&&date| date > target).min())
+            .copied()
+    }
+
+    /// Check that decompressed contents look like a complete serial-2 file
+    pub fn validate_contents(contents: &str) -> Result<(), Error> {
+        if contents.len() < Self::MIN_FILE_SIZE {
+            return Err(Error::Parse(format!(
+                "CAIDA file is only {} bytes, expected at least {}",
+                contents.len(),
+                Self::MIN_FILE_SIZE
+            )));
+        }
+
+        let mut num_relationships = 0;
+        for (line_num, line) in contents.lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('|').collect();
+            let valid = fields.len() >= 3
+                && fields[0].parse::<u32>().is_ok()
+                && fields[1].parse::<u32>().is_ok()
+                && matches!(fields[2], "-1" | "0");
+            if !valid {
+                return Err(Error::Parse(format!("Malformed CAIDA line {}: {}", line_num + 1, line)));
+            }
+            num_relationships += 1;
+        }
+
+        if num_relationships < Self::MIN_RELATIONSHIPS {
+            return Err(Error::Parse(format!("CAIDA file only has {} relationships", num_relationships)));
+        }
+        Ok(())
+    }
+
+    /// FNV-1a checksum stored next to cached files to detect truncated or corrupted caches
+    pub fn checksum(contents: &[u8]) -> String {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in contents {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{:016x}:{}", hash, contents.len())
+    }
+
+    fn checksum_path(path: &Path) -> PathBuf {
+        let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".checksum");
+        path.with_file_name(file_name)
+    }
+
+    /// A cached file is only trusted if its checksum file matches its contents
+    pub fn cached_file_valid(path: &Path) -> bool {
+        let (contents, expected) = match (fs::read(path), fs::read_to_string(Self::checksum_path(path))) {
+            (Ok(contents), Ok(expected)) => (contents, expected),
+            _ => return false,
+        };
+        Self::checksum(&contents) == expected.trim()
+    }
+
+    fn write_cache(path: &Path, contents: &str) -> std::io::Result<()> {
+        // Write to a temporary file first so an interrupted run never leaves a partial cache
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, contents)?;
+        fs::rename(&tmp_path, path)?;
+        fs::write(Self::checksum_path(path), Self::checksum(contents.as_bytes()))?;
+        Ok(())
+    }
+
+    /// GET a URL, retrying transient failures with exponential backoff
+    fn get_with_retry(&self, url: &str) -> Result<Vec<u8>, Error> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(300))
+            .build()
+            .map_err(|e| Error::Download(e.to_string()))?;
+
+        let mut delay = self.backoff;
+        let mut last_error = String::new();
+        for attempt in 1..=self.max_retries {
+            match client.get(url).send() {
+                Ok(response) if response.status().is_success() => {
+                    return response.bytes()
+                        .map(|bytes| bytes.to_vec())
+                        .map_err(|e| Error::Download(format!("GET {} failed reading the body: {}", url, e)));
+                }
+                // Client errors such as 404 won't fix themselves
+                Ok(response) if response.status().is_client_error() => {
+                    return Err(Error::Download(format!("GET {} failed with {}", url, response.status())));
+                }
+                Ok(response) => last_error = format!("status {}", response.status()),
+                Err(e) => last_error = e.to_string(),
+            }
+
+            if attempt < self.max_retries {
+                tracing::warn!(url, attempt, error = %last_error, retry_in = ?delay, "CAIDA request failed");
+                thread::sleep(delay);
+                delay *= 2;
+            }
+        }
+
+        Err(Error::Download(format!("GET {} failed after {} attempts: {}", url, self.max_retries, last_error)))
+    }
+
+    #[cfg(feature = "async")]
+    async fn get_with_retry_async(&self, url: &str) -> Result<Vec<u8>, Error> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(300))
+            .build()
+            .map_err(|e| Error::Download(e.to_string()))?;
+
+        let mut delay = self.backoff;
+        let mut last_error = String::new();
+        for attempt in 1..=self.max_retries {
+            match client.get(url).send().await {
+                Ok(response) if response.status().is_success() => {
+                    return response.bytes().await
+                        .map(|bytes| bytes.to_vec())
+                        .map_err(|e| Error::Download(format!("GET {} failed reading the body: {}", url, e)));
+                }
+                Ok(response) if response.status().is_client_error() => {
+                    return Err(Error::Download(format!("GET {} failed with {}", url, response.status())));
+                }
+                Ok(response) => last_error = format!("status {}", response.status()),
+                Err(e) => last_error = e.to_string(),
+            }
+
+            if attempt < self.max_retries {
+                tracing::warn!(url, attempt, error = %last_error, retry_in = ?delay, "CAIDA request failed");
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+
+        Err(Error::Download(format!("GET {} failed after {} attempts: {}", url, self.max_retries, last_error)))
+    }
+}
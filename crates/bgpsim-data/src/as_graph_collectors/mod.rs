@@ -0,0 +1,3 @@
+pub mod caida;
+
+pub use caida::CAIDAASGraphCollector;
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASMetadata, ASN, RIR};
+use bgpsim_core::Error;
+
+/// Converter for CAIDA AS-to-organization files, giving each AS its country, RIR and organization
+pub struct CAIDAASOrgConverter {
+    file_path: PathBuf,
+}
+
+/// Organization record: name and country
+type OrgInfo = (Option<String>, Option<String>);
+
+impl CAIDAASOrgConverter {
+    pub fn new(file_path: &Path) -> Self {
+        CAIDAASOrgConverter {
+            file_path: file_path.to_path_buf(),
+        }
+    }
+
+    pub fn convert(&self) -> Result<HashMap<ASN, ASMetadata>, Error> {
+        let contents = fs::read_to_string(&self.file_path)?;
+        Self::parse(&contents).map_err(Error::Parse)
+    }
+
+    /// Attach the file's metadata to the ASes of `as_graph`, returning how many got some
+    pub fn apply(&self, as_graph: &mut ASGraph) -> Result<usize, Error> {
+        let num_added = as_graph.add_metadata(self.convert()?);
+        tracing::debug!(num_added, "Added AS-to-organization metadata");
+        Ok(num_added)
+    }
+
+    /// Parse an as2org file, either the pipe-separated `as-org2info.txt` or the newer JSON lines format
+    ///
+    /// The text format has an organization section (`org_id|changed|org_name|country|source`)
+    /// and an AS section (`aut|changed|aut_name|org_id|opaque_id|source`), each introduced by a
+    /// `# format:` comment. An AS's RIR is the source of its own record, since organizations can span RIRs
+    pub fn parse(contents: &str) -> Result<HashMap<ASN, ASMetadata>, String> {
+        let mut orgs: HashMap<String, OrgInfo> = HashMap::new();
+        // (ASN, organization ID, source)
+        let mut auts: Vec<(ASN, String, String)> = Vec::new();
+
+        let is_json = contents.trim_start().starts_with('{');
+        let mut in_aut_section = false;
+        for (line_num, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some(format) = comment.trim().strip_prefix("format:") {
+                    in_aut_section = format.starts_with("aut");
+                }
+                continue;
+            }
+
+            if is_json {
+                Self::parse_json_line(line, &mut orgs, &mut auts)
+                    .map_err(|e| format!("Line {}: {}", line_num + 1, e))?;
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split('|').collect();
+            if in_aut_section {
+                if fields.len() < 6 {
+                    return Err(format!("Line {}: expected 6 fields in AS record, got {}", line_num + 1, fields.len()));
+                }
+                let asn = fields[0].parse()
+                    .map_err(|_| format!("Line {}: invalid ASN {}", line_num + 1, fields[0]))?;
+                auts.push((asn, fields[3].to_string(), fields[5].to_string()));
+            } else {
+                if fields.len() < 5 {
+                    return Err(format!("Line {}: expected 5 fields in organization record, got {}", line_num + 1, fields.len()));
+                }
+                orgs.insert(fields[0].to_string(), (non_empty(fields[2]), non_empty(fields[3])));
+            }
+        }
+
+        let metadata = auts.into_iter()
+            .map(|(asn, org_id, source)| {
+                let (org_name, country) = orgs.get(&org_id).cloned().unwrap_or_default();
+                let mut metadata = ASMetadata::new();
+                if let Some(country) = country {
+                    metadata = metadata.with_country(&country);
+                }
+                metadata.rir = source.parse::<RIR>().ok();
+                if !org_id.is_empty() {
+                    metadata = metadata.with_org(org_id, org_name);
+                }
+                (asn, metadata)
+            })
+            .collect();
+        Ok(metadata)
+    }
+
+    fn parse_json_line(
+        line: &str,
+        orgs: &mut HashMap<String, OrgInfo>,
+        auts: &mut Vec<(ASN, String, String)>,
+    ) -> Result<(), String> {
+        let value: serde_json::Value = serde_json::from_str(line).map_err(|e| format!("invalid JSON: {}", e))?;
+        let field = |key: &str| value.get(key).and_then(serde_json::Value::as_str).unwrap_or_default().to_string();
+
+        match value.get("type").and_then(serde_json::Value::as_str) {
+            Some("Organization") => {
+                orgs.insert(field("organizationId"), (non_empty(&field("name")), non_empty(&field("country"))));
+            }
+            Some("ASN") => {
+                let asn = match value.get("asn") {
+                    Some(serde_json::Value::Number(number)) => number.as_u64().and_then(|asn| ASN::try_from(asn).ok()),
+                    Some(serde_json::Value::String(asn)) => asn.parse().ok(),
+                    _ => None,
+                };
+                let asn = asn.ok_or_else(|| format!("invalid ASN in {}", line))?;
+                auts.push((asn, field("organizationId"), field("source")));
+            }
+            // Other record types don't describe ASes
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn non_empty(field: &str) -> Option<String> {
+    let field = field.trim();
+    (!field.is_empty()).then(|| field.to_string())
+}
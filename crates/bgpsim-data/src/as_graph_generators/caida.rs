@@ -0,0 +1,132 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bgpsim_core::as_graphs::as_graph::{ASBuilder, ASGraph, ASN};
+use bgpsim_core::Error;
+use crate::as_graph_collectors::CAIDAASGraphCollector;
+
+/// Converter for CAIDA AS graph files
+pub struct CAIDAASGraphJSONConverter {
+    file_path: PathBuf,
+}
+
+impl CAIDAASGraphJSONConverter {
+    /// Bump whenever the cached graph format changes so stale caches are ignored
+    pub const CACHE_VERSION: u32 = 1;
+
+    pub fn new(file_path: &Path) -> Self {
+        CAIDAASGraphJSONConverter {
+            file_path: file_path.to_path_buf(),
+        }
+    }
+
+    pub fn convert(&self) -> Result<ASGraph, Error> {
+        let contents = fs::read_to_string(&self.file_path)?;
+        Self::parse(&contents)
+    }
+
+    /// Convert the file, reusing a cached JSON graph built from identical source contents
+    pub fn convert_cached(&self, cache_dir: &Path) -> Result<ASGraph, Error> {
+        let contents = fs::read_to_string(&self.file_path)?;
+        let source_checksum = CAIDAASGraphCollector::checksum(contents.as_bytes());
+        let cache_path = Self::graph_cache_path(cache_dir, &source_checksum);
+
+        // A missing, stale or corrupted cache just falls back to parsing
+        if let Some(as_graph) = Self::load_cached_graph(&cache_path, &source_checksum) {
+            return Ok(as_graph);
+        }
+
+        let as_graph = Self::parse(&contents)?;
+        let cached = serde_json::json!({
+            "version": Self::CACHE_VERSION,
+            "source_checksum": source_checksum,
+            "as_graph": as_graph.to_json(),
+        });
+        fs::create_dir_all(cache_dir)?;
+        let tmp_path = cache_path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_string(&cached)?)?;
+        fs::rename(&tmp_path, &cache_path)?;
+        Ok(as_graph)
+    }
+
+    /// Path of the cached graph for a source file checksum
+    pub fn graph_cache_path(cache_dir: &Path, source_checksum: &str) -> PathBuf {
+        cache_dir.join(format!(
+            "as_graph.v{}.{}.json",
+            Self::CACHE_VERSION,
+            source_checksum.replace(':', "-")
+        ))
+    }
+
+    fn load_cached_graph(cache_path: &Path, source_checksum: &str) -> Option<ASGraph> {
+        let cached: serde_json::Value = serde_json::from_str(&fs::read_to_string(cache_path).ok()?).ok()?;
+        if cached.get("version")?.as_u64()? != Self::CACHE_VERSION as u64
+            || cached.get("source_checksum")?.as_str()? != source_checksum
+        {
+            return None;
+        }
+        ASGraph::from_json(cached.get("as_graph")?).ok()
+    }
+
+    /// Parse a serial-2 file
+    /// `a|b|-1|src` means a is a provider of b, `a|b|0|src` means a and b are peers
+    pub fn parse(contents: &str) -> Result<ASGraph, Error> {
+        let mut builders: BTreeMap<ASN, ASBuilder> = BTreeMap::new();
+        let mut tier_1_asns = HashSet::new();
+        let mut ixp_asns = HashSet::new();
+
+        for (line_num, line) in contents.lines().enumerate() {
+            if let Some(comment) = line.strip_prefix('#') {
+                // Header comments list the tier-1 clique and the IXPs
+                let comment = comment.trim();
+                if let Some(asns) = comment.strip_prefix("input clique:") {
+                    tier_1_asns.extend(asns.split_whitespace().filter_map(|asn| asn.parse::<ASN>().ok()));
+                } else if let Some(asns) = comment.strip_prefix("IXP ASes:") {
+                    ixp_asns.extend(asns.split_whitespace().filter_map(|asn| asn.parse::<ASN>().ok()));
+                }
+                continue;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split('|').collect();
+            let parsed = match fields.as_slice() {
+                [asn1, asn2, rel, ..] => asn1.parse::<ASN>().ok().zip(asn2.parse::<ASN>().ok()).map(|asns| (asns, *rel)),
+                _ => None,
+            };
+            let ((asn1, asn2), rel) = parsed
+                .ok_or_else(|| Error::Parse(format!("Malformed CAIDA line {}: {}", line_num + 1, line)))?;
+
+            match rel {
+                "-1" => {
+                    builders.entry(asn1).or_insert_with(|| ASBuilder::new(asn1)).customer_asns.push(asn2);
+                    builders.entry(asn2).or_insert_with(|| ASBuilder::new(asn2)).provider_asns.push(asn1);
+                }
+                "0" => {
+                    builders.entry(asn1).or_insert_with(|| ASBuilder::new(asn1)).peer_asns.push(asn2);
+                    builders.entry(asn2).or_insert_with(|| ASBuilder::new(asn2)).peer_asns.push(asn1);
+                }
+                _ => return Err(Error::Parse(format!("Unknown relationship {} on CAIDA line {}", rel, line_num + 1))),
+            }
+        }
+
+        let builders: Vec<ASBuilder> = builders.into_values()
+            .map(|mut builder| {
+                builder.tier_1 = tier_1_asns.contains(&builder.asn);
+                builder.ixp = ixp_asns.contains(&builder.asn);
+                builder
+            })
+            .collect();
+
+        let mut as_graph = ASGraph::build(builders);
+        // Real snapshots occasionally contain provider cycles; ranking collapses them rather than failing
+        let cycles = as_graph.find_provider_cycles();
+        if !cycles.is_empty() {
+            tracing::warn!(num_cycles = cycles.len(), ?cycles, "CAIDA data has provider cycles, ranked as single units");
+        }
+        as_graph.assign_as_propagation_rank();
+        Ok(as_graph)
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+use bgpsim_core::as_graphs::as_graph::{ASBuilder, ASGraph, ASN};
+use bgpsim_core::Error;
+
+use super::ASGraphGenerator;
+
+/// AS relationships inferred from observed AS paths with Gao's heuristic, for RIB dumps without a CAIDA file
+///
+/// Each path climbs from the vantage point to its highest-degree AS and descends to the origin, so
+/// links before that top provider are customer to provider and links after it provider to customer.
+/// Links seen both ways more than `transit_threshold` times are siblings, which `ASBuilder` has no
+/// relationship for, so they become peers like the remaining ties. A link next to a top provider
+/// whose endpoints' degrees are within `peer_degree_ratio` of each other is a peering link,
+/// unless another path shows transit over it
+#[derive(Debug, Clone)]
+pub struct GaoRelationshipInference {
+    paths: Vec<Vec<ASN>>,
+    /// Gao's R: maximum ratio between the degrees of two peers
+    pub peer_degree_ratio: f64,
+    /// Gao's L: times a link must be seen as transit in each direction to be a sibling link
+    pub transit_threshold: usize,
+}
+
+impl GaoRelationshipInference {
+    /// Infer from `paths`, each from the vantage point to the origin
+    /// Prepending is collapsed and paths with loops are dropped
+    pub fn new(paths: Vec<Vec<ASN>>) -> Self {
+        let paths = paths.into_iter()
+            .map(|mut path| {
+                path.dedup();
+                path
+            })
+            .filter(|path| path.len() > 1 && path.iter().collect::<HashSet<_>>().len() == path.len())
+            .collect();
+        GaoRelationshipInference {
+            paths,
+            peer_degree_ratio: 60.0,
+            transit_threshold: 1,
+        }
+    }
+
+    pub fn with_peer_degree_ratio(mut self, ratio: f64) -> Self {
+        self.peer_degree_ratio = ratio;
+        self
+    }
+
+    pub fn with_transit_threshold(mut self, threshold: usize) -> Self {
+        self.transit_threshold = threshold;
+        self
+    }
+
+    /// Parse one AS path per line, either space separated ASNs or `bgpdump -m` output
+    ///
+    /// From `bgpdump -m` lines (`TABLE_DUMP2|time|B|peer_ip|peer_asn|prefix|as_path|...`) only the
+    /// path is used. Blank lines and `#` comments are skipped, as are paths with AS sets (`{1,2}`)
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut paths = Vec::new();
+        for (line_num, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let path = match line.contains('|') {
+                true => line.split('|').nth(6)
+                    .ok_or_else(|| format!("Line {}: expected an AS path in the 7th field", line_num + 1))?,
+                false => line,
+            };
+            if path.contains('{') {
+                continue;
+            }
+            let path = path.split_whitespace()
+                .map(|asn| asn.parse::<ASN>().map_err(|_| format!("Line {}: invalid ASN {}", line_num + 1, asn)))
+                .collect::<Result<Vec<ASN>, String>>()?;
+            paths.push(path);
+        }
+        Ok(Self::new(paths))
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Number of distinct neighbors of each AS across the paths
+    pub fn degrees(&self) -> BTreeMap<ASN, usize> {
+        let mut neighbors: BTreeMap<ASN, BTreeSet<ASN>> = BTreeMap::new();
+        for path in &self.paths {
+            for link in path.windows(2) {
+                neighbors.entry(link[0]).or_default().insert(link[1]);
+                neighbors.entry(link[1]).or_default().insert(link[0]);
+            }
+        }
+        neighbors.into_iter().map(|(asn, neighbors)| (asn, neighbors.len())).collect()
+    }
+
+    /// Builders for `ASGraph::build`, with ASes that have customers but no providers as tier-1s
+    pub fn infer(&self) -> Vec<ASBuilder> {
+        let degrees = self.degrees();
+        let top_index = |path: &[ASN]| {
+            // The first of equally high degree ASes, so ties don't depend on anything but the path
+            (0..path.len()).rev().max_by_key(|&i| degrees[&path[i]]).unwrap()
+        };
+
+        // Times each (customer, provider) link was seen carrying transit
+        let mut transit: BTreeMap<(ASN, ASN), usize> = BTreeMap::new();
+        // Links that can't be peering links, keyed with the lower ASN first
+        let mut not_peering: HashSet<(ASN, ASN)> = HashSet::new();
+        for path in &self.paths {
+            let top = top_index(path);
+            for i in 0..path.len() - 1 {
+                let (customer, provider) = match i < top {
+                    true => (path[i], path[i + 1]),
+                    false => (path[i + 1], path[i]),
+                };
+                *transit.entry((customer, provider)).or_insert(0) += 1;
+                if i + 1 < top || i > top {
+                    not_peering.insert(link_key(path[i], path[i + 1]));
+                }
+            }
+            // Only the top provider's link to its higher-degree neighbor can be a peering link
+            let before = top.checked_sub(1).map(|i| path[i]);
+            let after = path.get(top + 1).copied();
+            if let (Some(before), Some(after)) = (before, after) {
+                match degrees[&before] > degrees[&after] {
+                    true => not_peering.insert(link_key(path[top], after)),
+                    false => not_peering.insert(link_key(before, path[top])),
+                };
+            }
+        }
+
+        let links: BTreeSet<(ASN, ASN)> = transit.keys().map(|&(asn1, asn2)| link_key(asn1, asn2)).collect();
+        let mut builders: BTreeMap<ASN, ASBuilder> = degrees.keys().map(|&asn| (asn, ASBuilder::new(asn))).collect();
+        for (asn1, asn2) in links {
+            let up = transit.get(&(asn1, asn2)).copied().unwrap_or(0);
+            let down = transit.get(&(asn2, asn1)).copied().unwrap_or(0);
+            let (degree1, degree2) = (degrees[&asn1] as f64, degrees[&asn2] as f64);
+            let similar_degrees = degree1.max(degree2) / degree1.min(degree2) <= self.peer_degree_ratio;
+            let is_sibling = up > self.transit_threshold && down > self.transit_threshold;
+            let is_peer = is_sibling || up == down || (!not_peering.contains(&(asn1, asn2)) && similar_degrees);
+
+            if is_peer {
+                builders.get_mut(&asn1).unwrap().peer_asns.push(asn2);
+                builders.get_mut(&asn2).unwrap().peer_asns.push(asn1);
+            } else {
+                let (customer, provider) = if up > down { (asn1, asn2) } else { (asn2, asn1) };
+                builders.get_mut(&customer).unwrap().provider_asns.push(provider);
+                builders.get_mut(&provider).unwrap().customer_asns.push(customer);
+            }
+        }
+
+        builders.into_values()
+            .map(|mut builder| {
+                builder.tier_1 = builder.provider_asns.is_empty() && !builder.customer_asns.is_empty();
+                builder
+            })
+            .collect()
+    }
+}
+
+fn link_key(asn1: ASN, asn2: ASN) -> (ASN, ASN) {
+    (asn1.min(asn2), asn1.max(asn2))
+}
+
+impl ASGraphGenerator for GaoRelationshipInference {
+    fn generate(&self) -> Result<ASGraph, Error> {
+        let mut as_graph = ASGraph::build(self.infer());
+        // Inference from conflicting paths can create provider cycles; ranking collapses them like CAIDA's
+        let cycles = as_graph.find_provider_cycles();
+        if !cycles.is_empty() {
+            tracing::warn!(num_cycles = cycles.len(), ?cycles, "Inferred relationships have provider cycles, ranked as single units");
+        }
+        as_graph.assign_as_propagation_rank();
+        tracing::debug!(num_paths = self.paths.len(), num_ases = as_graph.len(), "Inferred AS graph from paths");
+        Ok(as_graph)
+    }
+}
@@ -0,0 +1,52 @@
+pub mod as_org;
+pub mod caida;
+pub mod gao;
+pub mod synthetic;
+
+use std::path::{Path, PathBuf};
+
+use bgpsim_core::as_graphs::as_graph::ASGraph;
+use bgpsim_core::Error;
+use crate::as_graph_collectors::CAIDAASGraphCollector;
+
+pub use as_org::CAIDAASOrgConverter;
+pub use caida::CAIDAASGraphJSONConverter;
+pub use gao::GaoRelationshipInference;
+pub use synthetic::SyntheticASGraphGenerator;
+
+pub trait ASGraphGenerator {
+    fn generate(&self) -> Result<ASGraph, Error>;
+}
+
+pub struct CAIDAASGraphGenerator {
+    pub days_ago: u32,
+    pub cache_dir: String,
+}
+
+impl CAIDAASGraphGenerator {
+    pub fn new() -> Self {
+        CAIDAASGraphGenerator {
+            days_ago: 10,
+            cache_dir: ".bgp_simulator_cache".to_string(),
+        }
+    }
+
+    pub fn with_days_ago(mut self, days: u32) -> Self {
+        self.days_ago = days;
+        self
+    }
+
+    pub fn with_cache_dir(mut self, dir: String) -> Self {
+        self.cache_dir = dir;
+        self
+    }
+}
+
+impl ASGraphGenerator for CAIDAASGraphGenerator {
+    fn generate(&self) -> Result<ASGraph, Error> {
+        let dl_time = chrono::Utc::now().date_naive() - chrono::Duration::days(self.days_ago as i64);
+        let collector = CAIDAASGraphCollector::new(dl_time, PathBuf::from(&self.cache_dir));
+        let file_path = collector.run()?;
+        CAIDAASGraphJSONConverter::new(&file_path).convert_cached(Path::new(&self.cache_dir))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,200 @@
+use std::collections::{BTreeMap, HashSet};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use bgpsim_core::as_graphs::as_graph::{ASBuilder, ASGraph, NeighborASNs, ASN};
+use bgpsim_core::Error;
+
+use super::ASGraphGenerator;
+
+/// Random hierarchical AS topology, for experiments without CAIDA data or with a controlled shape
+///
+/// A clique of tier-1s sits on top of `num_transit_levels` levels of transit ASes, with stubs at
+/// the bottom. Each AS buys transit from 1 to `max_providers` ASes in the levels above it,
+/// favoring ASes that already have many customers, which gives the heavy-tailed customer cones
+/// of the real Internet. Transit ASes peer with others in their level. ASNs are 1 to `num_ases`,
+/// tier-1s first, and the same seed always generates the same graph
+#[derive(Debug, Clone)]
+pub struct SyntheticASGraphGenerator {
+    pub num_ases: usize,
+    pub num_tier_1: usize,
+    pub num_transit_levels: usize,
+    /// Fraction of the non-tier-1 ASes in the stub level
+    /// Transit ASes in the lowest level can also end up without customers
+    pub stub_fraction: f64,
+    /// Average number of peers of each transit AS within its level
+    pub peering_density: f64,
+    pub max_providers: usize,
+    pub seed: u64,
+}
+
+impl SyntheticASGraphGenerator {
+    pub fn new(num_ases: usize) -> Self {
+        SyntheticASGraphGenerator {
+            num_ases,
+            num_tier_1: 10.min(num_ases),
+            num_transit_levels: 2,
+            stub_fraction: 0.85,
+            peering_density: 2.0,
+            max_providers: 3,
+            seed: 0,
+        }
+    }
+
+    pub fn with_num_tier_1(mut self, num_tier_1: usize) -> Self {
+        self.num_tier_1 = num_tier_1;
+        self
+    }
+
+    pub fn with_num_transit_levels(mut self, num_levels: usize) -> Self {
+        self.num_transit_levels = num_levels;
+        self
+    }
+
+    pub fn with_stub_fraction(mut self, stub_fraction: f64) -> Self {
+        self.stub_fraction = stub_fraction;
+        self
+    }
+
+    pub fn with_peering_density(mut self, peering_density: f64) -> Self {
+        self.peering_density = peering_density;
+        self
+    }
+
+    pub fn with_max_providers(mut self, max_providers: usize) -> Self {
+        self.max_providers = max_providers;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.num_tier_1 == 0 || self.num_tier_1 > self.num_ases {
+            return Err(format!("Need between 1 and {} tier-1 ASes, got {}", self.num_ases, self.num_tier_1));
+        }
+        if !(0.0..=1.0).contains(&self.stub_fraction) {
+            return Err(format!("Stub fraction must be between 0 and 1, got {}", self.stub_fraction));
+        }
+        if self.peering_density < 0.0 {
+            return Err(format!("Peering density must not be negative, got {}", self.peering_density));
+        }
+        if self.max_providers == 0 {
+            return Err("ASes need at least one provider".to_string());
+        }
+        if self.num_ases > ASN::MAX as usize {
+            return Err(format!("Can't number {} ASes", self.num_ases));
+        }
+        Ok(())
+    }
+
+    /// ASNs of each level, from the tier-1s down to the stubs
+    fn levels(&self) -> Vec<Vec<ASN>> {
+        let num_rest = self.num_ases - self.num_tier_1;
+        let num_stubs = (num_rest as f64 * self.stub_fraction).round() as usize;
+        let num_transit = num_rest - num_stubs;
+        // Stubs absorb the transit ASes when there are no transit levels
+        let (num_transit, num_stubs) = match self.num_transit_levels {
+            0 => (0, num_rest),
+            _ => (num_transit, num_stubs),
+        };
+
+        let mut sizes = vec![self.num_tier_1];
+        for level in 0..self.num_transit_levels {
+            // Spread the remainder over the upper levels
+            let size = num_transit / self.num_transit_levels
+                + usize::from(level < num_transit % self.num_transit_levels);
+            sizes.push(size);
+        }
+        sizes.push(num_stubs);
+
+        let mut next_asn: ASN = 1;
+        sizes.into_iter()
+            .map(|size| {
+                let level: Vec<ASN> = (next_asn..next_asn + size as ASN).collect();
+                next_asn += size as ASN;
+                level
+            })
+            .collect()
+    }
+
+    /// Peer random pairs within a transit level until it averages `peering_density` peers per AS
+    fn add_peering(&self, level: &[ASN], builders: &mut BTreeMap<ASN, ASBuilder>, rng: &mut StdRng) {
+        if level.len() < 2 {
+            return;
+        }
+        let max_links = level.len() * (level.len() - 1) / 2;
+        let num_links = ((level.len() as f64 * self.peering_density / 2.0).round() as usize).min(max_links);
+        let mut links = HashSet::new();
+        while links.len() < num_links {
+            let pair = level.choose_multiple(rng, 2).copied().collect::<Vec<ASN>>();
+            links.insert((pair[0].min(pair[1]), pair[0].max(pair[1])));
+        }
+
+        let mut links: Vec<(ASN, ASN)> = links.into_iter().collect();
+        links.sort_unstable();
+        for (asn1, asn2) in links {
+            builders.get_mut(&asn1).unwrap().peer_asns.push(asn2);
+            builders.get_mut(&asn2).unwrap().peer_asns.push(asn1);
+        }
+    }
+}
+
+impl ASGraphGenerator for SyntheticASGraphGenerator {
+    fn generate(&self) -> Result<ASGraph, Error> {
+        self.validate().map_err(Error::ConfigInvalid)?;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let levels = self.levels();
+        let mut builders: BTreeMap<ASN, ASBuilder> = levels.iter().flatten()
+            .map(|&asn| (asn, ASBuilder::new(asn)))
+            .collect();
+
+        // Tier-1s form a full peering clique
+        for &asn in &levels[0] {
+            let builder = builders.get_mut(&asn).unwrap();
+            builder.tier_1 = true;
+            builder.peer_asns = levels[0].iter().copied().filter(|&peer| peer != asn).collect();
+        }
+
+        // Each AS appears once per customer plus once, so sampling favors big providers
+        let mut provider_tickets: Vec<ASN> = levels[0].clone();
+        let last_level = levels.len() - 1;
+        for (level_num, level) in levels.iter().enumerate().skip(1) {
+            let mut new_tickets = Vec::new();
+            for &asn in level {
+                let num_providers = rng.gen_range(1..=self.max_providers);
+                let mut providers = NeighborASNs::with_capacity(num_providers);
+                // Bounded so ASes with few candidate providers can't spin forever
+                for _ in 0..num_providers * 4 {
+                    if providers.len() == num_providers {
+                        break;
+                    }
+                    let provider_asn = *provider_tickets.choose(&mut rng).unwrap();
+                    if !providers.contains(&provider_asn) {
+                        providers.push(provider_asn);
+                    }
+                }
+                for &provider_asn in &providers {
+                    builders.get_mut(&provider_asn).unwrap().customer_asns.push(asn);
+                    new_tickets.push(provider_asn);
+                }
+                builders.get_mut(&asn).unwrap().provider_asns = providers;
+            }
+            provider_tickets.extend(new_tickets);
+
+            if level_num < last_level {
+                self.add_peering(level, &mut builders, &mut rng);
+                provider_tickets.extend(level);
+            }
+        }
+
+        let mut as_graph = ASGraph::build(builders.into_values().collect());
+        as_graph.assign_as_propagation_rank();
+        tracing::debug!(num_ases = as_graph.len(), seed = self.seed, "Generated synthetic AS graph");
+        Ok(as_graph)
+    }
+}
@@ -0,0 +1,4 @@
+//! Ingestion of external data (CAIDA AS relationships and AS-to-organization) into `bgpsim_core` types,
+//! plus synthetic AS graphs for experiments without that data
+pub mod as_graph_collectors;
+pub mod as_graph_generators;
@@ -0,0 +1,89 @@
+/*
+ * C API of bgpsimulator, see crates/bgpsim-ffi/src/lib.rs for the full documentation.
+ *
+ * Keep in sync with the Rust sources; regenerate with
+ *   cbindgen --config crates/bgpsim-ffi/cbindgen.toml --crate bgpsim-ffi --output crates/bgpsim-ffi/include/bgpsim.h
+ * Link against the cdylib (libbgpsim_ffi.so / .dylib / .dll) or the staticlib (libbgpsim_ffi.a).
+ */
+
+#ifndef BGPSIM_H
+#define BGPSIM_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/* Outcomes discriminants returned by bgpsim_outcomes_get and bgpsim_outcome_for_asn */
+#define BGPSIM_ATTACKER_SUCCESS 0
+#define BGPSIM_VICTIM_SUCCESS 1
+#define BGPSIM_DISCONNECTED_ORIGIN 2
+#define BGPSIM_DISCONNECTED_ATTACKER 3
+#define BGPSIM_DISCONNECTED_VICTIM 4
+#define BGPSIM_DISCONNECTED_NOT_AS_SOMEHOW 5
+#define BGPSIM_HIJACKED_SAME_PATH 6
+#define BGPSIM_HIJACKED_BUT_BLACKHOLED 7
+#define BGPSIM_HIJACKED_BUT_NOT_DETECTED 8
+#define BGPSIM_BLACKHOLED 9
+
+/* An AS graph ready to run scenarios on */
+typedef struct BgpsimGraph BgpsimGraph;
+
+/* Outcomes of a scenario run, one entry per AS sorted by ASN */
+typedef struct BgpsimOutcomes BgpsimOutcomes;
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Build a graph from provider-customer edges (provider, customer pairs) and peer edges (asn, asn pairs).
+ * Tier-1 ASes can be listed in tier_1_asns. Returns NULL if the edges have a provider cycle.
+ */
+BgpsimGraph *bgpsim_graph_from_edges(const uint32_t *provider_customer_edges,
+                                     size_t num_provider_customer_edges,
+                                     const uint32_t *peer_edges,
+                                     size_t num_peer_edges,
+                                     const uint32_t *tier_1_asns,
+                                     size_t num_tier_1_asns);
+
+/* Number of ASes in the graph */
+size_t bgpsim_graph_num_ases(const BgpsimGraph *graph);
+
+void bgpsim_graph_free(BgpsimGraph *graph);
+
+/*
+ * Run a scenario for propagation_rounds rounds and return every AS's outcome.
+ * scenario_config_json is a scenario config such as
+ *   {"scenario_name": "SubprefixHijack", "attacker_asns": [666], "legitimate_origin_asns": [777]}
+ * Returns NULL on an invalid config or a failed run.
+ */
+BgpsimOutcomes *bgpsim_run_scenario(const BgpsimGraph *graph,
+                                    const char *scenario_config_json,
+                                    uint32_t propagation_rounds);
+
+/* Number of ASes with an outcome */
+size_t bgpsim_outcomes_len(const BgpsimOutcomes *outcomes);
+
+/*
+ * The index-th AS, sorted by ASN, with where its traffic went and whose route it chose.
+ * Returns false if index is out of range.
+ */
+bool bgpsim_outcomes_get(const BgpsimOutcomes *outcomes,
+                         size_t index,
+                         uint32_t *asn,
+                         uint8_t *data_plane_outcome,
+                         uint8_t *control_plane_outcome);
+
+/* Data-plane outcome of asn, or -1 if it has none */
+int32_t bgpsim_outcome_for_asn(const BgpsimOutcomes *outcomes, uint32_t asn);
+
+void bgpsim_outcomes_free(BgpsimOutcomes *outcomes);
+
+/* Message of the last failed call on this thread, or NULL. Valid until the next failing call */
+const char *bgpsim_last_error(void);
+
+#ifdef __cplusplus
+} /* extern "C" */
+#endif
+
+#endif /* BGPSIM_H */
@@ -0,0 +1,297 @@
+//! C API for embedding the simulator in non-Rust tooling such as network emulators
+//!
+//! Graphs and outcomes are opaque handles owned by the caller, who frees them with the matching
+//! `*_free` function. Functions that fail return null or false and leave a message for
+//! `bgpsim_last_error`. The declarations are in `include/bgpsim.h`
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use bgpsim_core::as_graphs::as_graph::{ASBuilder, ASGraph, ASN};
+use bgpsim_core::route_validator::RouteValidator;
+use bgpsim_core::shared::{CommonASNs, Outcomes, ROARouted};
+use bgpsim_core::simulation_engine::SimulationEngine;
+use bgpsim_framework::simulation_framework::scenario::Scenario;
+use bgpsim_framework::simulation_framework::scenario_config::ScenarioConfig;
+use bgpsim_framework::simulation_framework::scenarios::{control_plane_outcomes, create_scenario, data_plane_outcomes, preprocess_roas};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// `Outcomes` discriminants, as `bgpsim_outcomes_get` and `bgpsim_outcome_for_asn` return them
+pub const BGPSIM_ATTACKER_SUCCESS: u8 = Outcomes::AttackerSuccess as u8;
+pub const BGPSIM_VICTIM_SUCCESS: u8 = Outcomes::VictimSuccess as u8;
+pub const BGPSIM_DISCONNECTED_ORIGIN: u8 = Outcomes::DisconnectedOrigin as u8;
+pub const BGPSIM_DISCONNECTED_ATTACKER: u8 = Outcomes::DisconnectedAttacker as u8;
+pub const BGPSIM_DISCONNECTED_VICTIM: u8 = Outcomes::DisconnectedVictim as u8;
+pub const BGPSIM_DISCONNECTED_NOT_AS_SOMEHOW: u8 = Outcomes::DisconnectedNotAsSomehow as u8;
+pub const BGPSIM_HIJACKED_SAME_PATH: u8 = Outcomes::HijackedSamePath as u8;
+pub const BGPSIM_HIJACKED_BUT_BLACKHOLED: u8 = Outcomes::HijackedButBlackholed as u8;
+pub const BGPSIM_HIJACKED_BUT_NOT_DETECTED: u8 = Outcomes::HijackedButNotDetected as u8;
+pub const BGPSIM_BLACKHOLED: u8 = Outcomes::Blackholed as u8;
+
+/// An AS graph ready to run scenarios on
+pub struct BgpsimGraph {
+    as_graph: ASGraph,
+}
+
+/// Outcomes of a scenario run, one entry per AS sorted by ASN
+pub struct BgpsimOutcomes {
+    entries: Vec<(ASN, Outcomes, Outcomes)>,
+}
+
+/// Record `message` for `bgpsim_last_error`
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// Run `f`, turning errors and panics into `None` with a recorded message, since neither may cross into C
+fn ffi_call<T>(f: impl FnOnce() -> Result<T, String>) -> Option<T> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(message)) => {
+            set_last_error(message);
+            None
+        }
+        Err(_) => {
+            set_last_error("bgpsimulator panicked".to_string());
+            None
+        }
+    }
+}
+
+/// ASN pairs from a flat array of `2 * num_pairs` ASNs
+///
+/// # Safety
+/// `asns` must point to `2 * num_pairs` ASNs, or may be null when `num_pairs` is 0
+unsafe fn asn_pairs<'a>(asns: *const u32, num_pairs: usize) -> Result<&'a [u32], String> {
+    if num_pairs == 0 {
+        return Ok(&[]);
+    }
+    if asns.is_null() {
+        return Err("Edge array is null".to_string());
+    }
+    Ok(std::slice::from_raw_parts(asns, 2 * num_pairs))
+}
+
+/// Build a graph from provider-customer edges (`provider, customer` pairs) and peer edges (`asn, asn` pairs)
+///
+/// Tier-1 ASes can be listed in `tier_1_asns`. Returns null if the edges have a provider cycle
+///
+/// # Safety
+/// Each array must hold as many ASNs as its length says (two per edge), or be null when empty
+#[no_mangle]
+pub unsafe extern "C" fn bgpsim_graph_from_edges(
+    provider_customer_edges: *const u32,
+    num_provider_customer_edges: usize,
+    peer_edges: *const u32,
+    num_peer_edges: usize,
+    tier_1_asns: *const u32,
+    num_tier_1_asns: usize,
+) -> *mut BgpsimGraph {
+    ffi_call(|| {
+        let provider_customer_edges = asn_pairs(provider_customer_edges, num_provider_customer_edges)?;
+        let peer_edges = asn_pairs(peer_edges, num_peer_edges)?;
+        let tier_1_asns: HashSet<ASN> = match num_tier_1_asns {
+            0 => HashSet::new(),
+            _ if tier_1_asns.is_null() => return Err("Tier-1 ASN array is null".to_string()),
+            _ => std::slice::from_raw_parts(tier_1_asns, num_tier_1_asns).iter().copied().collect(),
+        };
+
+        let mut builders: BTreeMap<ASN, ASBuilder> = BTreeMap::new();
+        for edge in provider_customer_edges.chunks_exact(2) {
+            let (provider, customer) = (edge[0], edge[1]);
+            builders.entry(provider).or_insert_with(|| ASBuilder::new(provider)).customer_asns.push(customer);
+            builders.entry(customer).or_insert_with(|| ASBuilder::new(customer)).provider_asns.push(provider);
+        }
+        for edge in peer_edges.chunks_exact(2) {
+            let (asn1, asn2) = (edge[0], edge[1]);
+            builders.entry(asn1).or_insert_with(|| ASBuilder::new(asn1)).peer_asns.push(asn2);
+            builders.entry(asn2).or_insert_with(|| ASBuilder::new(asn2)).peer_asns.push(asn1);
+        }
+        for &asn in &tier_1_asns {
+            builders.entry(asn).or_insert_with(|| ASBuilder::new(asn));
+        }
+        let builders = builders.into_values()
+            .map(|mut builder| {
+                builder.tier_1 = tier_1_asns.contains(&builder.asn);
+                builder
+            })
+            .collect();
+
+        let mut as_graph = ASGraph::build(builders);
+        as_graph.check_for_cycles().map_err(|e| e.to_string())?;
+        as_graph.assign_as_propagation_rank();
+        as_graph.add_asn_groups();
+        Ok(Box::into_raw(Box::new(BgpsimGraph { as_graph })))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Number of ASes in the graph
+///
+/// # Safety
+/// `graph` must come from `bgpsim_graph_from_edges` and not have been freed
+#[no_mangle]
+pub unsafe extern "C" fn bgpsim_graph_num_ases(graph: *const BgpsimGraph) -> usize {
+    graph.as_ref().map_or(0, |graph| graph.as_graph.len())
+}
+
+/// # Safety
+/// `graph` must come from `bgpsim_graph_from_edges` and not have been freed, or be null
+#[no_mangle]
+pub unsafe extern "C" fn bgpsim_graph_free(graph: *mut BgpsimGraph) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+/// Run a scenario for `propagation_rounds` rounds and return every AS's outcome
+///
+/// `scenario_config_json` is a scenario config as `ScenarioConfig::from_json` reads it, e.g.
+/// `{"scenario_name": "SubprefixHijack", "attacker_asns": [666], "legitimate_origin_asns": [777]}`.
+/// Like engine runs, attackers and legitimate origins default to `CommonASNs` and only the
+/// config's adopting ASes adopt. Returns null on an invalid config or a failed run
+///
+/// # Safety
+/// `graph` must be a live graph and `scenario_config_json` a NUL-terminated UTF-8 string
+#[no_mangle]
+pub unsafe extern "C" fn bgpsim_run_scenario(
+    graph: *const BgpsimGraph,
+    scenario_config_json: *const c_char,
+    propagation_rounds: u32,
+) -> *mut BgpsimOutcomes {
+    ffi_call(|| {
+        let graph = graph.as_ref().ok_or("Graph is null")?;
+        if scenario_config_json.is_null() {
+            return Err("Scenario config is null".to_string());
+        }
+        let json = CStr::from_ptr(scenario_config_json).to_str()
+            .map_err(|e| format!("Scenario config is not UTF-8: {}", e))?;
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| format!("Invalid scenario config JSON: {}", e))?;
+        let scenario_config = ScenarioConfig::from_json(&value).map_err(|e| e.to_string())?;
+        let entries = run_scenario(&graph.as_graph, &scenario_config, propagation_rounds)?;
+        Ok(Box::into_raw(Box::new(BgpsimOutcomes { entries })))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+fn run_scenario(
+    as_graph: &ASGraph,
+    scenario_config: &ScenarioConfig,
+    propagation_rounds: u32,
+) -> Result<Vec<(ASN, Outcomes, Outcomes)>, String> {
+    let attacker_asns = scenario_config.override_attacker_asns.clone()
+        .unwrap_or_else(|| HashSet::from([CommonASNs::ATTACKER]));
+    let legitimate_origin_asns = scenario_config.override_legitimate_origin_asns.clone()
+        .unwrap_or_else(|| HashSet::from([CommonASNs::VICTIM]));
+
+    let mut engine = SimulationEngine::new(as_graph)
+        .with_storage_mode(scenario_config.storage_mode)
+        .with_tie_breaker(scenario_config.tie_breaker)
+        .with_next_hop_verification(scenario_config.next_hop_verification);
+    let adoption = Scenario::new(
+        scenario_config.clone()
+            .with_attacker_asns(attacker_asns.clone())
+            .with_legitimate_origin_asns(legitimate_origin_asns.clone()),
+        as_graph,
+        0.0,
+    );
+    adoption.apply_adoption_settings(&mut engine);
+
+    let scenario = create_scenario(&scenario_config.scenario_name, attacker_asns.clone(), legitimate_origin_asns.clone())
+        .map_err(|e| e.to_string())?;
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    preprocess_roas(scenario_config, scenario.as_ref(), &mut engine, &mut route_validator);
+    let schedule = scenario.get_events(engine.as_graph).merged(&scenario_config.event_schedule);
+    schedule.run(&mut engine, &mut route_validator, propagation_rounds)?;
+
+    // Non-routed space has no legitimate origin, as in engine runs
+    let dest_ip_addr = scenario.get_dest_ip_addr();
+    let legitimate_origin_asns = if route_validator.get_roa_routed(&dest_ip_addr.into()) == ROARouted::NonRouted {
+        HashSet::new()
+    } else {
+        legitimate_origin_asns
+    };
+    let data_plane = data_plane_outcomes(&engine, &attacker_asns, &legitimate_origin_asns, dest_ip_addr);
+    let control_plane: HashMap<ASN, Outcomes> = control_plane_outcomes(&engine, &attacker_asns, &legitimate_origin_asns, dest_ip_addr);
+
+    let mut entries: Vec<(ASN, Outcomes, Outcomes)> = data_plane.into_iter()
+        .map(|(asn, outcome)| {
+            let control_plane_outcome = control_plane.get(&asn).copied().unwrap_or(Outcomes::DisconnectedOrigin);
+            (asn, outcome, control_plane_outcome)
+        })
+        .collect();
+    entries.sort_unstable_by_key(|&(asn, _, _)| asn);
+    Ok(entries)
+}
+
+/// Number of ASes with an outcome
+///
+/// # Safety
+/// `outcomes` must come from `bgpsim_run_scenario` and not have been freed
+#[no_mangle]
+pub unsafe extern "C" fn bgpsim_outcomes_len(outcomes: *const BgpsimOutcomes) -> usize {
+    outcomes.as_ref().map_or(0, |outcomes| outcomes.entries.len())
+}
+
+/// The `index`th AS, sorted by ASN, with where its traffic went and whose route it chose
+///
+/// Outcomes are the `Outcomes` discriminants, e.g. 0 for `AttackerSuccess`. Returns false if `index` is out of range
+///
+/// # Safety
+/// `outcomes` must be live and each out pointer valid or null
+#[no_mangle]
+pub unsafe extern "C" fn bgpsim_outcomes_get(
+    outcomes: *const BgpsimOutcomes,
+    index: usize,
+    asn: *mut u32,
+    data_plane_outcome: *mut u8,
+    control_plane_outcome: *mut u8,
+) -> bool {
+    let Some(&(entry_asn, data_plane, control_plane)) = outcomes.as_ref().and_then(|outcomes| outcomes.entries.get(index)) else {
+        set_last_error(format!("No outcome at index {}", index));
+        return false;
+    };
+    if let Some(asn) = asn.as_mut() {
+        *asn = entry_asn;
+    }
+    if let Some(data_plane_outcome) = data_plane_outcome.as_mut() {
+        *data_plane_outcome = data_plane as u8;
+    }
+    if let Some(control_plane_outcome) = control_plane_outcome.as_mut() {
+        *control_plane_outcome = control_plane as u8;
+    }
+    true
+}
+
+/// Data-plane outcome of `asn`, or -1 if it has none
+///
+/// # Safety
+/// `outcomes` must come from `bgpsim_run_scenario` and not have been freed
+#[no_mangle]
+pub unsafe extern "C" fn bgpsim_outcome_for_asn(outcomes: *const BgpsimOutcomes, asn: u32) -> i32 {
+    outcomes.as_ref()
+        .and_then(|outcomes| outcomes.entries.binary_search_by_key(&asn, |&(entry_asn, _, _)| entry_asn).ok().map(|index| outcomes.entries[index].1))
+        .map_or(-1, |outcome| outcome as i32)
+}
+
+/// # Safety
+/// `outcomes` must come from `bgpsim_run_scenario` and not have been freed, or be null
+#[no_mangle]
+pub unsafe extern "C" fn bgpsim_outcomes_free(outcomes: *mut BgpsimOutcomes) {
+    if !outcomes.is_null() {
+        drop(Box::from_raw(outcomes));
+    }
+}
+
+/// Message of the last failed call on this thread, or null. Valid until the next failing call
+#[no_mangle]
+pub extern "C" fn bgpsim_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| last_error.borrow().as_ref().map_or(std::ptr::null(), |message| message.as_ptr()))
+}
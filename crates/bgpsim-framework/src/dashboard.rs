@@ -0,0 +1,338 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bgpsim_core::Error;
+
+/// How deep below the root to look for run directories
+const MAX_DEPTH: usize = 4;
+
+/// Line colors of the summary plots, cycled per scenario
+const COLORS: [&str; 6] = ["#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b"];
+
+/// A directory of simulation output, i.e. an `EngineRunner` storage dir or a `Simulation` output dir
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunEntry {
+    /// Path relative to the dashboard root with `/` separators, empty for the root itself
+    pub name: String,
+    /// Output files directly in the directory, sorted
+    pub files: Vec<String>,
+}
+
+impl RunEntry {
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() { "." } else { &self.name }
+    }
+
+    /// Whether `EngineRunner` wrote this directory
+    pub fn is_engine_run(&self) -> bool {
+        self.files.iter().any(|file| file == "config.json")
+    }
+
+    /// `Simulation` summaries, one per scenario label
+    pub fn summary_files(&self) -> impl Iterator<Item = &String> {
+        self.files.iter().filter(|file| file.ends_with("_summary.json"))
+    }
+}
+
+/// Response to a dashboard request
+#[derive(Debug, Clone, PartialEq)]
+pub struct DashboardResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl DashboardResponse {
+    fn html(title: &str, body: &str) -> Self {
+        let page = format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title>\
+             <style>body{{font-family:sans-serif;margin:2em}}table{{border-collapse:collapse}}\
+             td,th{{border:1px solid #ccc;padding:.3em .6em}}pre{{background:#f6f6f6;padding:1em;overflow:auto}}</style>\
+             </head><body>{body}</body></html>\n",
+            title = escape_html(title),
+            body = body,
+        );
+        Self::ok("text/html; charset=utf-8", page.into_bytes())
+    }
+
+    fn ok(content_type: &'static str, body: Vec<u8>) -> Self {
+        DashboardResponse { status: 200, content_type, body }
+    }
+
+    fn not_found(what: &str) -> Self {
+        DashboardResponse {
+            status: 404,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("Not found: {}\n", what).into_bytes(),
+        }
+    }
+}
+
+/// Browsable view of an output directory: runs, their configs and outcomes, summary plots and diagrams
+///
+/// Files are only served if indexed under the root, so requests can't reach anything else
+pub struct Dashboard {
+    pub root: PathBuf,
+}
+
+impl Dashboard {
+    pub fn new(root: PathBuf) -> Self {
+        Dashboard { root }
+    }
+
+    /// Directories under the root that contain output files, sorted by name
+    pub fn runs(&self) -> Vec<RunEntry> {
+        let mut runs = Vec::new();
+        Self::scan(&self.root, "", 0, &mut runs);
+        runs.sort_by(|a, b| a.name.cmp(&b.name));
+        runs
+    }
+
+    fn scan(dir: &Path, name: &str, depth: usize, runs: &mut Vec<RunEntry>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut files = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if path.is_dir() && depth < MAX_DEPTH {
+                let child = if name.is_empty() { file_name } else { format!("{}/{}", name, file_name) };
+                Self::scan(&path, &child, depth + 1, runs);
+            } else if is_output_file(&file_name) {
+                files.push(file_name);
+            }
+        }
+        if !files.is_empty() {
+            files.sort();
+            runs.push(RunEntry { name: name.to_string(), files });
+        }
+    }
+
+    fn find_run(&self, name: &str) -> Option<RunEntry> {
+        self.runs().into_iter().find(|run| run.name == name)
+    }
+
+    /// Route a request path: `/`, `/run/<run>`, `/file/<run>/<file>` or `/plot/<run>`
+    pub fn handle(&self, url: &str) -> DashboardResponse {
+        let path = url.split('?').next().unwrap_or("");
+        let path = percent_decode(path);
+        if path == "/" || path.is_empty() {
+            return self.index_page();
+        }
+        if let Some(name) = path.strip_prefix("/run/") {
+            return match self.find_run(name) {
+                Some(run) => self.run_page(&run),
+                None => DashboardResponse::not_found(name),
+            };
+        }
+        if let Some(name) = path.strip_prefix("/plot/") {
+            return match self.find_run(name) {
+                Some(run) => DashboardResponse::ok("image/svg+xml", self.summary_plot(&run).into_bytes()),
+                None => DashboardResponse::not_found(name),
+            };
+        }
+        if let Some(rest) = path.strip_prefix("/file/") {
+            if let Some((name, file)) = rest.rsplit_once('/') {
+                if let Some(run) = self.find_run(name).filter(|run| run.files.iter().any(|f| f == file)) {
+                    return match fs::read(self.run_dir(&run).join(file)) {
+                        Ok(body) => DashboardResponse::ok(content_type(file), body),
+                        Err(_) => DashboardResponse::not_found(rest),
+                    };
+                }
+            }
+            return DashboardResponse::not_found(rest);
+        }
+        DashboardResponse::not_found(&path)
+    }
+
+    /// Serve the dashboard at `addr` (e.g. `127.0.0.1:8000`) until the process exits
+    pub fn serve(&self, addr: &str) -> Result<(), Error> {
+        let server = tiny_http::Server::http(addr).map_err(std::io::Error::other)?;
+        tracing::info!(addr, root = ?self.root, "Serving dashboard");
+        for request in server.incoming_requests() {
+            let response = self.handle(request.url());
+            tracing::debug!(url = request.url(), status = response.status, "Dashboard request");
+            let header = tiny_http::Header::from_bytes(&b"Content-Type"[..], response.content_type.as_bytes())
+                .expect("content types are valid header values");
+            let reply = tiny_http::Response::from_data(response.body)
+                .with_status_code(response.status)
+                .with_header(header);
+            if let Err(e) = request.respond(reply) {
+                tracing::warn!(error = %e, "Failed to answer dashboard request");
+            }
+        }
+        Ok(())
+    }
+
+    fn run_dir(&self, run: &RunEntry) -> PathBuf {
+        if run.name.is_empty() { self.root.clone() } else { self.root.join(&run.name) }
+    }
+
+    fn read_json(&self, run: &RunEntry, file: &str) -> Option<serde_json::Value> {
+        let contents = fs::read_to_string(self.run_dir(run).join(file)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn index_page(&self) -> DashboardResponse {
+        let runs = self.runs();
+        let mut body = format!("<h1>Runs in {}</h1>", escape_html(&self.root.display().to_string()));
+        if runs.is_empty() {
+            body.push_str("<p>No output files found.</p>");
+        } else {
+            body.push_str("<table><tr><th>Run</th><th>Kind</th><th>Files</th></tr>");
+            for run in &runs {
+                let kind = if run.is_engine_run() { "engine run" } else { "simulation" };
+                let _ = write!(
+                    body,
+                    "<tr><td><a href=\"/run/{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+                    percent_encode(&run.name), escape_html(run.display_name()), kind, run.files.len(),
+                );
+            }
+            body.push_str("</table>");
+        }
+        DashboardResponse::html("bgpsimulator runs", &body)
+    }
+
+    fn run_page(&self, run: &RunEntry) -> DashboardResponse {
+        let mut body = format!("<p><a href=\"/\">All runs</a></p><h1>{}</h1>", escape_html(run.display_name()));
+
+        if run.summary_files().next().is_some() {
+            let _ = write!(body, "<h2>Summary</h2><img src=\"/plot/{}\" alt=\"success rate by adoption\">", percent_encode(&run.name));
+        }
+        if let Some(outcomes) = self.read_json(run, "outcomes_guess.json") {
+            body.push_str("<h2>Outcomes</h2><table><tr><th>Outcome</th><th>ASes</th></tr>");
+            let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+            for outcome in outcomes.as_object().into_iter().flat_map(|outcomes| outcomes.values()) {
+                *counts.entry(outcome.to_string().trim_matches('"').to_string()).or_insert(0) += 1;
+            }
+            for (outcome, count) in counts {
+                let _ = write!(body, "<tr><td>{}</td><td>{}</td></tr>", escape_html(&outcome), count);
+            }
+            body.push_str("</table>");
+        }
+        for (file, title) in [("analysis_guess.json", "Analysis"), ("config.json", "Config")] {
+            if let Some(value) = self.read_json(run, file) {
+                let pretty = serde_json::to_string_pretty(&value).unwrap_or_default();
+                let _ = write!(body, "<h2>{}</h2><pre>{}</pre>", title, escape_html(&pretty));
+            }
+        }
+        if let Ok(diagram) = fs::read_to_string(self.run_dir(run).join("diagram.txt")) {
+            let _ = write!(body, "<h2>Diagram</h2><pre>{}</pre>", escape_html(&diagram));
+        }
+        for file in run.files.iter().filter(|file| file.ends_with(".svg")) {
+            let _ = write!(body, "<h2>{}</h2><img src=\"/file/{}/{}\">", escape_html(file), percent_encode(&run.name), percent_encode(file));
+        }
+
+        body.push_str("<h2>Files</h2><ul>");
+        for file in &run.files {
+            let _ = write!(
+                body,
+                "<li><a href=\"/file/{}/{}\">{}</a></li>",
+                percent_encode(&run.name), percent_encode(file), escape_html(file),
+            );
+        }
+        body.push_str("</ul>");
+        DashboardResponse::html(run.display_name(), &body)
+    }
+
+    /// Success rate against adoption percentage, one line per `*_summary.json` in the run
+    pub fn summary_plot(&self, run: &RunEntry) -> String {
+        let (width, height, margin) = (640.0, 400.0, 50.0);
+        let x = |percent: f64| margin + percent / 100.0 * (width - 2.0 * margin);
+        let y = |rate: f64| height - margin - rate / 100.0 * (height - 2.0 * margin);
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" font-family=\"sans-serif\" font-size=\"12\">\
+             <rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\
+             <line x1=\"{m}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"black\"/>\
+             <line x1=\"{m}\" y1=\"{m}\" x2=\"{m}\" y2=\"{b}\" stroke=\"black\"/>\
+             <text x=\"{cx}\" y=\"{xl}\" text-anchor=\"middle\">Percent adoption</text>\
+             <text x=\"15\" y=\"{cy}\" text-anchor=\"middle\" transform=\"rotate(-90 15 {cy})\">Attacker success rate (%)</text>",
+            w = width, h = height, m = margin, r = width - margin, b = height - margin,
+            cx = width / 2.0, cy = height / 2.0, xl = height - 15.0,
+        );
+        for tick in [0.0, 25.0, 50.0, 75.0, 100.0] {
+            let _ = write!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text><text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+                x(tick), height - margin + 15.0, tick, margin - 5.0, y(tick) + 4.0, tick,
+            );
+        }
+
+        for (i, file) in run.summary_files().enumerate() {
+            let Some(summary) = self.read_json(run, file) else {
+                continue;
+            };
+            let values = |key: &str| -> Vec<f64> {
+                summary[key].as_array().into_iter().flatten().filter_map(serde_json::Value::as_f64).collect()
+            };
+            let mut points: Vec<(f64, f64)> = values("adoption_percentages").into_iter().zip(values("success_rates")).collect();
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let color = COLORS[i % COLORS.len()];
+            let polyline: Vec<String> = points.iter().map(|&(percent, rate)| format!("{:.1},{:.1}", x(percent), y(rate))).collect();
+            let label = summary["scenario_label"].as_str().unwrap_or(file);
+            let _ = write!(
+                svg,
+                "<polyline fill=\"none\" stroke=\"{color}\" stroke-width=\"2\" points=\"{points}\"/>\
+                 <text x=\"{lx}\" y=\"{ly}\" fill=\"{color}\">{label}</text>",
+                color = color, points = polyline.join(" "), lx = width - margin - 150.0,
+                ly = margin + 15.0 * (i as f64 + 1.0), label = escape_html(label),
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+fn is_output_file(file_name: &str) -> bool {
+    [".json", ".csv", ".txt", ".svg", ".yaml"].iter().any(|ext| file_name.ends_with(ext))
+}
+
+fn content_type(file_name: &str) -> &'static str {
+    match file_name.rsplit('.').next() {
+        Some("json") => "application/json",
+        Some("csv") => "text/csv; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Encode everything but unreserved characters and `/`, which separates run path segments
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::new();
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+        match (bytes[i], hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
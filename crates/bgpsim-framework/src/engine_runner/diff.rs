@@ -0,0 +1,164 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::shared::Outcomes;
+use bgpsim_core::Error;
+use bgpsim_core::simulation_engine::round_diff::{LocalRIBSnapshot, RoundDiff, RouteChange};
+use crate::simulation_framework::scenario_config::ScenarioConfig;
+
+use super::engine_runner::EngineRunner;
+
+/// How one AS fared in run B compared to run A
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ASDiff {
+    /// Local RIB changes from run A to run B, by prefix
+    pub route_changes: Vec<RouteChange>,
+    pub outcome_a: Option<Outcomes>,
+    pub outcome_b: Option<Outcomes>,
+}
+
+impl ASDiff {
+    pub fn outcome_changed(&self) -> bool {
+        self.outcome_a != self.outcome_b
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let route_changes: Vec<serde_json::Value> = self.route_changes.iter().map(RouteChange::to_json).collect();
+        serde_json::json!({
+            "route_changes": route_changes,
+            "outcome_a": self.outcome_a,
+            "outcome_b": self.outcome_b,
+        })
+    }
+}
+
+/// ASes whose selected routes or outcomes differ between two runs of the same scenario
+///
+/// Run B repeats run A with different adoption settings, so every difference is down to them.
+/// ASes that fared the same in both runs are left out
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EngineRunDiff {
+    pub changes: BTreeMap<ASN, ASDiff>,
+}
+
+impl EngineRunDiff {
+    /// Compare run B's outcomes and local RIBs against run A's
+    pub fn between(
+        outcomes_a: &HashMap<ASN, Outcomes>,
+        local_ribs_a: &LocalRIBSnapshot,
+        outcomes_b: &HashMap<ASN, Outcomes>,
+        local_ribs_b: &LocalRIBSnapshot,
+    ) -> Self {
+        let mut route_changes = RoundDiff::between(0, local_ribs_a, local_ribs_b).changes;
+        let asns: BTreeSet<ASN> = outcomes_a.keys().chain(outcomes_b.keys()).chain(route_changes.keys()).copied().collect();
+        let mut changes = BTreeMap::new();
+        for asn in asns {
+            let diff = ASDiff {
+                route_changes: route_changes.remove(&asn).unwrap_or_default(),
+                outcome_a: outcomes_a.get(&asn).copied(),
+                outcome_b: outcomes_b.get(&asn).copied(),
+            };
+            if !diff.route_changes.is_empty() || diff.outcome_changed() {
+                changes.insert(asn, diff);
+            }
+        }
+        EngineRunDiff { changes }
+    }
+
+    /// Run `runner`'s config as run A, then again with `scenario_config_b` as run B, and diff them
+    ///
+    /// The runs are stored in `a` and `b` under the runner's storage directory, next to `diff.json`.
+    /// Only the adoption settings of `scenario_config_b` should differ for the diff to be attributable
+    pub fn run(runner: &EngineRunner, scenario_config_b: ScenarioConfig) -> Result<Self, Error> {
+        let mut config_b = runner.config.clone();
+        config_b.scenario_config = scenario_config_b;
+
+        let (outcomes_a, local_ribs_a) = sub_runner(runner, runner.config.clone(), "a").run_with_local_ribs()?;
+        let (outcomes_b, local_ribs_b) = sub_runner(runner, config_b, "b").run_with_local_ribs()?;
+        let diff = EngineRunDiff::between(&outcomes_a, &local_ribs_a, &outcomes_b, &local_ribs_b);
+        tracing::info!(
+            num_changed = diff.changes.len(),
+            num_protected = diff.num_protected(),
+            num_harmed = diff.num_harmed(),
+            "Diffed engine runs"
+        );
+
+        fs::write(runner.storage_dir.join("diff.json"), serde_json::to_string_pretty(&diff.to_json())?)?;
+        Ok(diff)
+    }
+
+    /// ASes the attacker reached in run A but not in run B
+    pub fn protected_asns(&self) -> Vec<ASN> {
+        self.asns_where(|diff| {
+            diff.outcome_a == Some(Outcomes::AttackerSuccess) && diff.outcome_b != Some(Outcomes::AttackerSuccess)
+        })
+    }
+
+    /// ASes the attacker reached in run B but not in run A
+    pub fn harmed_asns(&self) -> Vec<ASN> {
+        self.asns_where(|diff| {
+            diff.outcome_a != Some(Outcomes::AttackerSuccess) && diff.outcome_b == Some(Outcomes::AttackerSuccess)
+        })
+    }
+
+    pub fn num_protected(&self) -> usize {
+        self.protected_asns().len()
+    }
+
+    pub fn num_harmed(&self) -> usize {
+        self.harmed_asns().len()
+    }
+
+    fn asns_where(&self, predicate: impl Fn(&ASDiff) -> bool) -> Vec<ASN> {
+        self.changes.iter().filter(|(_, diff)| predicate(diff)).map(|(&asn, _)| asn).collect()
+    }
+
+    /// `{"protected": [asn, ...], "harmed": [asn, ...], "changes": {"<asn>": diff}}`
+    pub fn to_json(&self) -> serde_json::Value {
+        let changes: serde_json::Map<String, serde_json::Value> = self.changes.iter()
+            .map(|(asn, diff)| (asn.to_string(), diff.to_json()))
+            .collect();
+        serde_json::json!({
+            "protected": self.protected_asns(),
+            "harmed": self.harmed_asns(),
+            "changes": changes,
+        })
+    }
+}
+
+impl fmt::Display for EngineRunDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} AS(es) changed: {} protected, {} harmed",
+            self.changes.len(),
+            self.num_protected(),
+            self.num_harmed()
+        )?;
+        for (asn, diff) in &self.changes {
+            if diff.outcome_changed() {
+                writeln!(f, "  AS {}: {:?} -> {:?}", asn, diff.outcome_a, diff.outcome_b)?;
+            }
+            for change in &diff.route_changes {
+                writeln!(f, "  AS {}: {}", asn, change)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `runner` with another config, storing into `dir_name` under its storage directory
+fn sub_runner(runner: &EngineRunner, config: super::EngineRunConfig, dir_name: &str) -> EngineRunner {
+    EngineRunner {
+        config,
+        base_dir: runner.storage_dir.clone(),
+        overwrite: runner.overwrite,
+        compare_against_ground_truth: runner.compare_against_ground_truth,
+        write_diagrams: runner.write_diagrams,
+        record_round_diffs: runner.record_round_diffs,
+        write_policies: runner.write_policies,
+        storage_dir: runner.storage_dir.join(dir_name),
+    }
+}
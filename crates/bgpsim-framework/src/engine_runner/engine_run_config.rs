@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use bgpsim_core::as_graphs::as_graph::{ASBuilder, ASGraph, ASN};
+use bgpsim_core::Error;
+use crate::simulation_framework::scenario_config::{ScenarioConfig, config_field, read_config_file};
+
+/// Configuration for a single engine run
+/// Clones keep the name, so they write to the same directory; see `ConfigNamespace` to keep names apart
+#[derive(Debug, Clone)]
+pub struct EngineRunConfig {
+    /// Name of this engine run, which its output directory is named after
+    pub name: String,
+    
+    /// Scenario configuration
+    pub scenario_config: ScenarioConfig,
+    
+    /// AS graph to use
+    pub as_graph: ASGraph,
+    
+    /// Description for diagram generation
+    pub diagram_desc: String,
+    
+    /// Additional text description
+    pub text: String,
+    
+    /// Ranks for diagram layout
+    pub diagram_ranks: Vec<Vec<u32>>,
+    
+    /// Propagation rounds to run the engine for
+    pub propagation_rounds: u32,
+}
+
+/// Names of engine runs that share a base directory, so that no two write to the same output directory
+///
+/// Owned by whoever groups the runs, e.g. `EngineTestSuite::load_dir`, rather than global, so
+/// configs built elsewhere (or by tests running in parallel) never collide with it
+#[derive(Debug, Clone, Default)]
+pub struct ConfigNamespace {
+    names: HashSet<String>,
+}
+
+impl ConfigNamespace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Claim `config`'s name, failing if a run in the namespace already has it
+    pub fn register(&mut self, config: &EngineRunConfig) -> Result<(), Error> {
+        if !self.names.insert(config.name.clone()) {
+            return Err(Error::ConfigInvalid(format!("Name '{}' already used", config.name)));
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Release every name, e.g. before loading the same runs again
+    pub fn reset(&mut self) {
+        self.names.clear();
+    }
+}
+
+impl EngineRunConfig {
+    pub fn new(
+        name: String,
+        scenario_config: ScenarioConfig,
+        as_graph: ASGraph,
+    ) -> Self {
+        EngineRunConfig {
+            name,
+            scenario_config,
+            as_graph,
+            diagram_desc: String::new(),
+            text: String::new(),
+            diagram_ranks: Vec::new(),
+            propagation_rounds: 100,
+        }
+    }
+    
+    /// Load a run from a JSON or YAML file (see `from_json` for the fields)
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        EngineRunConfig::from_json(&read_config_file(path)?)
+    }
+    
+    /// Parse a declarative run, in the format `to_json` writes
+    ///
+    /// `name`, `scenario_config` (see `ScenarioConfig::from_json`) and `as_graph` are required.
+    /// `as_graph` lists ASes as `asn` with optional `peers`, `providers`, `customers`, `tier_1` and `ixp`
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, Error> {
+        let scenario_config = value.get("scenario_config")
+            .ok_or_else(|| Error::ConfigInvalid("Engine run config is missing scenario_config".to_string()))?;
+        let scenario_config = ScenarioConfig::from_json(scenario_config)?;
+        EngineRunConfig::from_json_fields(value, scenario_config).map_err(Error::ConfigInvalid)
+    }
+    
+    /// Every field of `from_json` but the scenario config
+    fn from_json_fields(value: &serde_json::Value, scenario_config: ScenarioConfig) -> Result<Self, String> {
+        let name = config_field(value, "name")?.ok_or("Engine run config is missing name")?;
+        let as_values: Vec<serde_json::Value> = config_field(value, "as_graph")?
+            .ok_or("Engine run config is missing as_graph")?;
+        let builders = as_values.iter().map(as_builder_from_json).collect::<Result<Vec<ASBuilder>, String>>()?;
+        let mut as_graph = ASGraph::build(builders);
+        as_graph.assign_as_propagation_rank();
+        
+        let mut config = EngineRunConfig::new(name, scenario_config, as_graph);
+        config.diagram_desc = config_field(value, "diagram_desc")?.unwrap_or_default();
+        config.text = config_field(value, "text")?.unwrap_or_default();
+        config.diagram_ranks = config_field(value, "diagram_ranks")?.unwrap_or_default();
+        if let Some(rounds) = config_field(value, "propagation_rounds")? {
+            config.propagation_rounds = rounds;
+        }
+        Ok(config)
+    }
+    
+    pub fn with_diagram_desc(mut self, desc: String) -> Self {
+        self.diagram_desc = desc;
+        self
+    }
+    
+    pub fn with_text(mut self, text: String) -> Self {
+        self.text = text;
+        self
+    }
+    
+    pub fn with_diagram_ranks(mut self, ranks: Vec<Vec<u32>>) -> Self {
+        self.diagram_ranks = ranks;
+        self
+    }
+    
+    pub fn with_propagation_rounds(mut self, rounds: u32) -> Self {
+        self.propagation_rounds = rounds;
+        self
+    }
+    
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "diagram_desc": self.diagram_desc,
+            "text": self.text,
+            "scenario_config": self.scenario_config.to_json(),
+            "diagram_ranks": self.diagram_ranks,
+            "propagation_rounds": self.propagation_rounds,
+            "as_graph": self.as_graph.iter().map(|as_obj| serde_json::json!({
+                "asn": as_obj.asn,
+                "peers": as_obj.peers().iter().map(|peer| peer.asn).collect::<Vec<ASN>>(),
+                "providers": as_obj.providers().iter().map(|provider| provider.asn).collect::<Vec<ASN>>(),
+                "customers": as_obj.customers().iter().map(|customer| customer.asn).collect::<Vec<ASN>>(),
+                "tier_1": as_obj.tier_1,
+                "ixp": as_obj.ixp,
+            })).collect::<Vec<serde_json::Value>>(),
+        })
+    }
+}
+
+pub(crate) fn as_builder_from_json(value: &serde_json::Value) -> Result<ASBuilder, String> {
+    let asn = config_field(value, "asn")?.ok_or("AS in as_graph is missing asn")?;
+    let mut builder = ASBuilder::new(asn)
+        .with_peers(config_field(value, "peers")?.unwrap_or_default())
+        .with_providers(config_field(value, "providers")?.unwrap_or_default())
+        .with_customers(config_field(value, "customers")?.unwrap_or_default());
+    builder.tier_1 = config_field(value, "tier_1")?.unwrap_or(false);
+    builder.ixp = config_field(value, "ixp")?.unwrap_or(false);
+    Ok(builder)
+}
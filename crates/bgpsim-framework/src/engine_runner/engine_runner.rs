@@ -0,0 +1,315 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::IpAddr;
+
+use bgpsim_core::simulation_engine::{ForwardingGraph, Prefix, SimulationEngine};
+use bgpsim_core::simulation_engine::round_diff::LocalRIBSnapshot;
+use bgpsim_core::route_validator::RouteValidator;
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::shared::{CommonASNs, Outcomes, ROARouted, Settings};
+use bgpsim_core::Error;
+use crate::simulation_framework::scenario::{Scenario, ScenarioTrait};
+use crate::simulation_framework::scenarios::{control_plane_outcomes, create_scenario, data_plane_outcomes, preprocess_roas};
+
+use super::engine_run_config::EngineRunConfig;
+
+/// Runs a single engine run with specific configuration
+pub struct EngineRunner {
+    /// Configuration for this engine run
+    pub config: EngineRunConfig,
+    
+    /// Base directory for storing results
+    pub base_dir: PathBuf,
+    
+    /// Whether to overwrite existing results
+    pub overwrite: bool,
+    
+    /// Whether to compare against ground truth (for testing)
+    pub compare_against_ground_truth: bool,
+    
+    /// Whether to write diagram files
+    pub write_diagrams: bool,
+    
+    /// Whether to record each round's local RIB changes, for stepping through propagation
+    pub record_round_diffs: bool,
+    
+    /// Whether to write the policy every AS ran, adopting or not
+    pub write_policies: bool,
+    
+    /// Storage directory for this specific run
+    pub storage_dir: PathBuf,
+}
+
+impl EngineRunner {
+    pub fn new(config: EngineRunConfig) -> Self {
+        let base_dir = dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("Desktop")
+            .join("bgpsimulator_engine_runs");
+            
+        let storage_dir = base_dir.join(&config.name);
+        
+        EngineRunner {
+            config,
+            base_dir,
+            overwrite: false,
+            compare_against_ground_truth: false,
+            write_diagrams: true,
+            record_round_diffs: false,
+            write_policies: false,
+            storage_dir,
+        }
+    }
+    
+    pub fn with_base_dir(mut self, dir: PathBuf) -> Self {
+        self.base_dir = dir;
+        self.storage_dir = self.base_dir.join(&self.config.name);
+        self
+    }
+    
+    pub fn with_overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+    
+    pub fn with_compare_against_ground_truth(mut self, compare: bool) -> Self {
+        self.compare_against_ground_truth = compare;
+        self
+    }
+    
+    pub fn with_write_diagrams(mut self, write: bool) -> Self {
+        self.write_diagrams = write;
+        self
+    }
+    
+    /// Write every round's local RIB changes to `round_diffs_guess.json`
+    pub fn with_record_round_diffs(mut self, record: bool) -> Self {
+        self.record_round_diffs = record;
+        self
+    }
+    
+    /// Write every AS's settings and policy to `policies_guess.json`, see `policies_json`
+    pub fn with_write_policies(mut self, write: bool) -> Self {
+        self.write_policies = write;
+        self
+    }
+    
+    /// Run the engine with the configured scenario
+    pub fn run(&self) -> Result<HashMap<u32, Outcomes>, Error> {
+        self.run_with_local_ribs().map(|(outcomes, _)| outcomes)
+    }
+    
+    /// Run like `run`, also returning every AS's local RIB after propagation
+    pub fn run_with_local_ribs(&self) -> Result<(HashMap<u32, Outcomes>, LocalRIBSnapshot), Error> {
+        let _span = tracing::info_span!("engine_run", name = %self.config.name).entered();
+        tracing::info!(storage_dir = ?self.storage_dir, rounds = self.config.propagation_rounds, "Running engine");
+        
+        // Create storage directory
+        fs::create_dir_all(&self.storage_dir)?;
+        
+        // Create engine and scenario
+        let (mut engine, scenario, mut route_validator) = self.get_engine_and_scenario()?;
+        
+        // Run engine for the configured rounds, with any events scheduled between them
+        let schedule = scenario.get_events(engine.as_graph).merged(&self.config.scenario_config.event_schedule);
+        schedule.run(&mut engine, &mut route_validator, self.config.propagation_rounds).map_err(Error::Propagation)?;
+        
+        // Calculate data plane outcomes
+        let outcomes = self.calculate_data_plane_outcomes(&engine, &scenario, &route_validator);
+        let control_plane_outcomes = self.calculate_control_plane_outcomes(&engine, &scenario, &route_validator);
+        let analysis = scenario.analyze(&engine, &route_validator);
+        
+        // Store results
+        self.store_data(&engine, &outcomes, &control_plane_outcomes, analysis.as_ref())?;
+        tracing::debug!(num_outcomes = outcomes.len(), "Stored results");
+        
+        // Generate diagrams if requested
+        if self.write_diagrams {
+            self.generate_diagrams(&engine, scenario.as_ref())?;
+        }
+        
+        // Compare against ground truth if requested
+        if self.compare_against_ground_truth {
+            self.compare_against_ground_truth(&engine, &outcomes)?;
+        }
+        
+        Ok((outcomes, engine.get_local_rib_snapshot()))
+    }
+    
+    fn get_engine_and_scenario(&self) -> Result<(SimulationEngine, Box<dyn ScenarioTrait>, RouteValidator), Error> {
+        let scenario_config = &self.config.scenario_config;
+        let mut engine = SimulationEngine::new(&self.config.as_graph)
+            .with_storage_mode(scenario_config.storage_mode)
+            .with_tie_breaker(scenario_config.tie_breaker)
+            .with_next_hop_verification(scenario_config.next_hop_verification);
+        if self.record_round_diffs {
+            engine = engine.with_round_diffs();
+        }
+        
+        // Only the config's adopting ASes and per-AS settings adopt, never a random sample
+        let adoption = Scenario::new(
+            scenario_config.clone()
+                .with_attacker_asns(self.get_attacker_asns())
+                .with_legitimate_origin_asns(self.get_legitimate_origin_asns()),
+            &self.config.as_graph,
+            0.0,
+        );
+        adoption.apply_adoption_settings(&mut engine);
+        
+        // Create scenario based on scenario name
+        let scenario = create_scenario(
+            &scenario_config.scenario_name,
+            self.get_attacker_asns(),
+            self.get_legitimate_origin_asns(),
+        )?;
+        
+        // Setup scenario in engine
+        let mut route_validator = RouteValidator::new();
+        scenario.setup_engine(&mut engine, &mut route_validator);
+        preprocess_roas(scenario_config, scenario.as_ref(), &mut engine, &mut route_validator);
+        
+        Ok((engine, scenario, route_validator))
+    }
+    
+    /// Attackers from the scenario config, defaulting to `CommonASNs::ATTACKER`
+    fn get_attacker_asns(&self) -> HashSet<ASN> {
+        self.config.scenario_config.override_attacker_asns.clone()
+            .unwrap_or_else(|| HashSet::from([CommonASNs::ATTACKER]))
+    }
+    
+    /// Legitimate origins from the scenario config, defaulting to `CommonASNs::VICTIM`
+    fn get_legitimate_origin_asns(&self) -> HashSet<ASN> {
+        self.config.scenario_config.override_legitimate_origin_asns.clone()
+            .unwrap_or_else(|| HashSet::from([CommonASNs::VICTIM]))
+    }
+    
+    fn calculate_data_plane_outcomes(
+        &self,
+        engine: &SimulationEngine,
+        scenario: &Box<dyn ScenarioTrait>,
+        route_validator: &RouteValidator,
+    ) -> HashMap<u32, Outcomes> {
+        // Follow each AS's traffic to the scenario's destination by longest prefix match, so a
+        // less or more specific hijack only wins where it is actually the best match.
+        let dest_ip_addr = scenario.get_dest_ip_addr();
+        let legitimate_origin_asns = self.routed_legitimate_origin_asns(dest_ip_addr, route_validator);
+        data_plane_outcomes(engine, &self.get_attacker_asns(), &legitimate_origin_asns, dest_ip_addr)
+    }
+    
+    /// Whose route each AS selected for the scenario's destination, which can differ from where its traffic goes
+    fn calculate_control_plane_outcomes(
+        &self,
+        engine: &SimulationEngine,
+        scenario: &Box<dyn ScenarioTrait>,
+        route_validator: &RouteValidator,
+    ) -> HashMap<u32, Outcomes> {
+        let dest_ip_addr = scenario.get_dest_ip_addr();
+        let legitimate_origin_asns = self.routed_legitimate_origin_asns(dest_ip_addr, route_validator);
+        control_plane_outcomes(engine, &self.get_attacker_asns(), &legitimate_origin_asns, dest_ip_addr)
+    }
+    
+    /// Non-routed space has no legitimate origin: its traffic should end nowhere
+    fn routed_legitimate_origin_asns(&self, dest_ip_addr: IpAddr, route_validator: &RouteValidator) -> HashSet<ASN> {
+        if route_validator.get_roa_routed(&dest_ip_addr.into()) == ROARouted::NonRouted {
+            HashSet::new()
+        } else {
+            self.get_legitimate_origin_asns()
+        }
+    }
+    
+    fn store_data(
+        &self,
+        engine: &SimulationEngine,
+        outcomes: &HashMap<u32, Outcomes>,
+        control_plane_outcomes: &HashMap<u32, Outcomes>,
+        analysis: Option<&serde_json::Value>,
+    ) -> Result<(), Error> {
+        // Store engine state
+        let engine_path = self.storage_dir.join("engine_guess.json");
+        let engine_json = serde_json::json!({
+            "as_graph_size": engine.as_graph.len(),
+            "policy_count": engine.policy_store.iter().count(),
+            // Add more engine state as needed
+        });
+        fs::write(engine_path, serde_json::to_string_pretty(&engine_json)?)?;
+        
+        // Store outcomes
+        let outcomes_path = self.storage_dir.join("outcomes_guess.json");
+        fs::write(outcomes_path, serde_json::to_string_pretty(&outcomes)?)?;
+        let control_plane_outcomes_path = self.storage_dir.join("control_plane_outcomes_guess.json");
+        fs::write(control_plane_outcomes_path, serde_json::to_string_pretty(&control_plane_outcomes)?)?;
+        
+        // Store the scenario's own analysis, if it has one
+        if let Some(analysis) = analysis {
+            let analysis_path = self.storage_dir.join("analysis_guess.json");
+            fs::write(analysis_path, serde_json::to_string_pretty(analysis)?)?;
+        }
+        
+        // Store what changed in each round, if recorded
+        if let Some(round_diffs) = engine.get_round_diffs() {
+            let round_diffs_path = self.storage_dir.join("round_diffs_guess.json");
+            let round_diffs: Vec<serde_json::Value> = round_diffs.iter().map(|diff| diff.to_json()).collect();
+            fs::write(round_diffs_path, serde_json::to_string_pretty(&round_diffs)?)?;
+        }
+        
+        // Store which policy each AS ran, if requested
+        if self.write_policies {
+            let policies_path = self.storage_dir.join("policies_guess.json");
+            fs::write(policies_path, serde_json::to_string_pretty(&policies_json(engine))?)?;
+        }
+        
+        // Store config
+        let config_path = self.storage_dir.join("config.json");
+        fs::write(config_path, serde_json::to_string_pretty(&self.config.to_json())?)?;
+        
+        Ok(())
+    }
+    
+    /// Write the forwarding graph of every prefix the scenario announces, as Graphviz digraphs to
+    /// `diagram.txt` and as JSON to `forwarding_graphs.json`
+    fn generate_diagrams(
+        &self,
+        engine: &SimulationEngine,
+        scenario: &dyn ScenarioTrait,
+    ) -> Result<(), Error> {
+        let mut prefixes: Vec<Prefix> = scenario.get_seed_asn_ann_dict(engine.as_graph).into_values()
+            .flatten()
+            .filter(|ann| !ann.withdraw)
+            .map(|ann| ann.prefix)
+            .collect();
+        prefixes.sort_by_key(|prefix| (prefix.is_ipv6(), prefix.network(), prefix.prefix()));
+        prefixes.dedup();
+        let graphs: Vec<ForwardingGraph> = prefixes.iter().map(|prefix| engine.forwarding_graph(prefix)).collect();
+        
+        let diagram: String = graphs.iter().map(ForwardingGraph::to_dot).collect();
+        fs::write(self.storage_dir.join("diagram.txt"), diagram)?;
+        let graphs_json: Vec<serde_json::Value> = graphs.iter().map(ForwardingGraph::to_json).collect();
+        fs::write(self.storage_dir.join("forwarding_graphs.json"), serde_json::to_string_pretty(&graphs_json)?)?;
+        Ok(())
+    }
+    
+    fn compare_against_ground_truth(
+        &self,
+        _engine: &SimulationEngine,
+        _outcomes: &HashMap<u32, Outcomes>,
+    ) -> Result<(), Error> {
+        // Ground truth comparison would be implemented here
+        // This is mainly used for testing
+        Ok(())
+    }
+}
+
+/// Every AS's settings and policy name by ASN, and whether it adopted anything beyond plain BGP
+///
+/// e.g. `{"1": {"settings": "Rov", "policy": "ROV", "adopting": true}}`
+pub fn policies_json(engine: &SimulationEngine) -> serde_json::Value {
+    let policies: BTreeMap<ASN, serde_json::Value> = engine.policy_store.iter()
+        .map(|(&asn, policy)| (asn, serde_json::json!({
+            "settings": policy.settings,
+            "policy": policy.name(),
+            "adopting": policy.settings != Settings::BaseDefense,
+        })))
+        .collect();
+    serde_json::json!(policies)
+}
@@ -0,0 +1,9 @@
+pub mod diff;
+pub mod engine_run_config;
+pub mod engine_runner;
+pub mod test_suite;
+
+pub use diff::{ASDiff, EngineRunDiff};
+pub use engine_run_config::{ConfigNamespace, EngineRunConfig};
+pub use engine_runner::EngineRunner;
+pub use test_suite::{EngineTestCase, EngineTestReport, EngineTestResult, EngineTestSuite};
\ No newline at end of file
@@ -0,0 +1,226 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::shared::Outcomes;
+use bgpsim_core::simulation_engine::round_diff::LocalRIBSnapshot;
+use crate::simulation_framework::scenario_config::{config_field, read_config_file};
+
+use super::engine_run_config::{ConfigNamespace, EngineRunConfig};
+use super::engine_runner::EngineRunner;
+
+/// Small topology and scenario with the results it must produce, as in bgpy's EngineTester
+///
+/// The file is an `EngineRunConfig` plus an `expected` object with `local_ribs`
+/// (ASN to prefix to AS path) and/or `outcomes` (ASN to outcome name).
+/// ASes missing from `local_ribs` are expected to have no routes
+#[derive(Debug)]
+pub struct EngineTestCase {
+    pub path: PathBuf,
+    pub config: EngineRunConfig,
+    pub expected_local_ribs: Option<LocalRIBSnapshot>,
+    pub expected_outcomes: Option<HashMap<ASN, Outcomes>>,
+}
+
+impl EngineTestCase {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let value = read_config_file(path).map_err(|e| e.to_string())?;
+        let config = EngineRunConfig::from_json(&value)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let expected = value.get("expected")
+            .ok_or_else(|| format!("{}: Engine test is missing expected", path.display()))?;
+
+        let expected_local_ribs = config_field::<HashMap<String, HashMap<String, Vec<ASN>>>>(expected, "local_ribs")?
+            .map(|ribs| ribs.into_iter().map(|(asn, ribs)| Ok((parse_asn(&asn)?, ribs))).collect())
+            .transpose()
+            .map_err(|e: String| format!("{}: {}", path.display(), e))?;
+        let expected_outcomes = config_field::<HashMap<String, Outcomes>>(expected, "outcomes")?
+            .map(|outcomes| outcomes.into_iter().map(|(asn, outcome)| Ok((parse_asn(&asn)?, outcome))).collect())
+            .transpose()
+            .map_err(|e: String| format!("{}: {}", path.display(), e))?;
+        if expected_local_ribs.is_none() && expected_outcomes.is_none() {
+            return Err(format!("{}: Engine test expects neither local_ribs nor outcomes", path.display()));
+        }
+
+        Ok(EngineTestCase {
+            path: path.to_path_buf(),
+            config,
+            expected_local_ribs,
+            expected_outcomes,
+        })
+    }
+
+    /// Run the case, writing its output under `base_dir`
+    pub fn run(&self, base_dir: &Path) -> EngineTestResult {
+        let runner = EngineRunner::new(self.config.clone())
+            .with_base_dir(base_dir.to_path_buf())
+            .with_overwrite(true)
+            .with_write_diagrams(false);
+
+        let (outcomes, local_ribs) = match runner.run_with_local_ribs() {
+            Ok(result) => result,
+            Err(e) => return EngineTestResult::error(&self.config.name, e.to_string()),
+        };
+
+        let mut diffs = Vec::new();
+        if let Some(expected) = &self.expected_local_ribs {
+            diffs.extend(local_rib_diffs(expected, &local_ribs));
+        }
+        if let Some(expected) = &self.expected_outcomes {
+            diffs.extend(outcome_diffs(expected, &outcomes));
+        }
+        EngineTestResult {
+            name: self.config.name.clone(),
+            diffs,
+            error: None,
+        }
+    }
+}
+
+/// Outcome of one engine test
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineTestResult {
+    pub name: String,
+    /// One line per mismatch, e.g. `AS 3 1.2.3.0/24: expected [3, 1, 4], got none`
+    pub diffs: Vec<String>,
+    /// Why the case couldn't run at all
+    pub error: Option<String>,
+}
+
+impl EngineTestResult {
+    fn error(name: &str, error: String) -> Self {
+        EngineTestResult {
+            name: name.to_string(),
+            diffs: Vec::new(),
+            error: Some(error),
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.diffs.is_empty()
+    }
+}
+
+/// Directory of engine tests, run together to check policy changes against known-good cases
+#[derive(Debug, Default)]
+pub struct EngineTestSuite {
+    pub cases: Vec<EngineTestCase>,
+}
+
+impl EngineTestSuite {
+    /// Load every `.yaml`, `.yml` and `.json` file in `dir`, sorted by file name
+    /// Fails if two cases share a name, since they would write to the same output directory
+    pub fn load_dir(dir: &Path) -> Result<Self, String> {
+        let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                matches!(path.extension().and_then(|extension| extension.to_str()), Some("yaml" | "yml" | "json"))
+            })
+            .collect();
+        paths.sort();
+
+        let cases: Vec<EngineTestCase> = paths.iter().map(|path| EngineTestCase::from_file(path)).collect::<Result<_, String>>()?;
+        let mut namespace = ConfigNamespace::new();
+        for case in &cases {
+            namespace.register(&case.config).map_err(|e| format!("{}: {}", case.path.display(), e))?;
+        }
+        Ok(EngineTestSuite { cases })
+    }
+
+    /// Run every case, writing their output under `base_dir`
+    pub fn run(&self, base_dir: &Path) -> EngineTestReport {
+        let results = self.cases.iter()
+            .map(|case| {
+                let result = case.run(base_dir);
+                tracing::debug!(name = %result.name, passed = result.passed(), "Ran engine test");
+                result
+            })
+            .collect();
+        EngineTestReport { results }
+    }
+}
+
+/// Results of an `EngineTestSuite` run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EngineTestReport {
+    pub results: Vec<EngineTestResult>,
+}
+
+impl EngineTestReport {
+    pub fn num_passed(&self) -> usize {
+        self.results.iter().filter(|result| result.passed()).count()
+    }
+
+    pub fn num_failed(&self) -> usize {
+        self.results.len() - self.num_passed()
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.num_failed() == 0
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &EngineTestResult> {
+        self.results.iter().filter(|result| !result.passed())
+    }
+}
+
+impl fmt::Display for EngineTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} passed, {} failed", self.num_passed(), self.num_failed())?;
+        for result in self.failures() {
+            writeln!(f, "FAILED {}", result.name)?;
+            if let Some(error) = &result.error {
+                writeln!(f, "  error: {}", error)?;
+            }
+            for diff in &result.diffs {
+                writeln!(f, "  {}", diff)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_asn(asn: &str) -> Result<ASN, String> {
+    asn.parse().map_err(|_| format!("Invalid ASN in expected: {}", asn))
+}
+
+/// Differences by ASN, then prefix; ASes missing from either snapshot have no routes
+fn local_rib_diffs(expected: &LocalRIBSnapshot, actual: &LocalRIBSnapshot) -> Vec<String> {
+    let empty = HashMap::new();
+    let asns: BTreeSet<ASN> = expected.keys().chain(actual.keys()).copied().collect();
+    let mut diffs = Vec::new();
+
+    for asn in asns {
+        let expected_ribs = expected.get(&asn).unwrap_or(&empty);
+        let actual_ribs = actual.get(&asn).unwrap_or(&empty);
+        let prefixes: BTreeSet<&String> = expected_ribs.keys().chain(actual_ribs.keys()).collect();
+        for prefix in prefixes {
+            let expected_path = expected_ribs.get(prefix);
+            let actual_path = actual_ribs.get(prefix);
+            if expected_path != actual_path {
+                diffs.push(format!("AS {} {}: expected {}, got {}", asn, prefix, format_path(expected_path), format_path(actual_path)));
+            }
+        }
+    }
+    diffs
+}
+
+fn format_path(path: Option<&Vec<ASN>>) -> String {
+    path.map_or_else(|| "none".to_string(), |path| format!("{:?}", path))
+}
+
+/// Differences by ASN for every AS with an expected or actual outcome
+fn outcome_diffs(expected: &HashMap<ASN, Outcomes>, actual: &HashMap<ASN, Outcomes>) -> Vec<String> {
+    let asns: BTreeSet<ASN> = expected.keys().chain(actual.keys()).copied().collect();
+    asns.into_iter()
+        .filter(|asn| expected.get(asn) != actual.get(asn))
+        .map(|asn| format!("AS {} outcome: expected {}, got {}", asn, format_outcome(expected.get(&asn)), format_outcome(actual.get(&asn))))
+        .collect()
+}
+
+fn format_outcome(outcome: Option<&Outcomes>) -> String {
+    outcome.map_or_else(|| "none".to_string(), |outcome| format!("{:?}", outcome))
+}
@@ -0,0 +1,9 @@
+//! Scenarios, multi-trial simulations, engine test runs and reporting on top of `bgpsim_core`
+pub mod simulation_framework;
+pub mod engine_runner;
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "live-replay")]
+pub mod live_replay;
@@ -0,0 +1,302 @@
+//! Replay of real BGP updates from RIS Live or BGPStream against a running engine
+//!
+//! Updates are read line by line from any reader, e.g. the RIS Live websocket piped through
+//! `websocat` or the output of `bgpreader`, so real incidents can be replayed against
+//! counterfactual defense deployments without a network client built in
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use ipnetwork::IpNetwork;
+
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::route_validator::RouteValidator;
+use bgpsim_core::shared::{Relationships, Timestamps};
+use bgpsim_core::simulation_engine::{Announcement, Prefix, SimulationEngine};
+
+use crate::simulation_framework::ScenarioEvent;
+
+/// Format of the updates being replayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateFormat {
+    /// One RIS Live `ris_message` JSON object per line
+    RisLive,
+    /// `bgpreader` elems, e.g. `U|A|1499385779.000000|routeviews|route-views.eqix|||2914|206.126.236.24|210.180.224.0/19|...`
+    BgpStream,
+}
+
+impl std::fmt::Display for UpdateFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            UpdateFormat::RisLive => "RisLive",
+            UpdateFormat::BgpStream => "BgpStream",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for UpdateFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "RisLive" => Ok(UpdateFormat::RisLive),
+            "BgpStream" => Ok(UpdateFormat::BgpStream),
+            _ => Err(format!("Unknown update format: {}", s)),
+        }
+    }
+}
+
+/// A route a collector peer announced or withdrew
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerUpdate {
+    /// Seconds since the epoch
+    pub timestamp: u64,
+    pub peer_asn: ASN,
+    pub prefix: Prefix,
+    /// Path from the peer to the origin, None for withdrawals
+    pub as_path: Option<Vec<ASN>>,
+}
+
+impl PeerUpdate {
+    /// Origin of an announcement, the last AS on its path
+    pub fn origin(&self) -> Option<ASN> {
+        self.as_path.as_ref().and_then(|path| path.last().copied())
+    }
+}
+
+/// Turns collector updates into origin announcements and withdrawals on a running engine
+///
+/// The engine propagates each announcement from its origin itself, so the outcome reflects the
+/// engine's policies rather than the defenses deployed when the updates were collected. An origin
+/// withdraws a prefix once no collector peer routes it to that origin anymore
+#[derive(Debug, Clone)]
+pub struct LiveReplay {
+    pub format: UpdateFormat,
+    /// Only replay prefixes overlapping one of these, e.g. the incident's prefix; all if empty
+    pub prefix_filter: Vec<Prefix>,
+    /// Updates within this many seconds of the first one of a batch are applied together
+    pub batch_seconds: u64,
+    /// Propagation rounds run after applying each batch
+    pub rounds_per_batch: u32,
+    /// Origin each collector peer last routed each prefix to
+    peer_origins: HashMap<(ASN, Prefix), ASN>,
+    /// Number of collector peers routing each prefix to each origin
+    num_peers: HashMap<(Prefix, ASN), usize>,
+    /// Updates skipped because of AS sets or origins outside the graph
+    pub num_skipped: usize,
+}
+
+impl LiveReplay {
+    pub fn new(format: UpdateFormat) -> Self {
+        LiveReplay {
+            format,
+            prefix_filter: Vec::new(),
+            batch_seconds: 60,
+            rounds_per_batch: 5,
+            peer_origins: HashMap::new(),
+            num_peers: HashMap::new(),
+            num_skipped: 0,
+        }
+    }
+
+    pub fn with_prefix_filter(mut self, prefix: Prefix) -> Self {
+        self.prefix_filter.push(prefix);
+        self
+    }
+
+    pub fn with_batch_seconds(mut self, seconds: u64) -> Self {
+        self.batch_seconds = seconds;
+        self
+    }
+
+    pub fn with_rounds_per_batch(mut self, rounds: u32) -> Self {
+        self.rounds_per_batch = rounds;
+        self
+    }
+
+    /// Whether updates for `prefix` pass the prefix filter
+    pub fn replays(&self, prefix: &Prefix) -> bool {
+        self.prefix_filter.is_empty() || self.prefix_filter.iter().any(|filter| {
+            (filter.contains(prefix.network()) && prefix.prefix() >= filter.prefix())
+                || (prefix.contains(filter.network()) && filter.prefix() >= prefix.prefix())
+        })
+    }
+
+    /// Updates in one line, none for keepalives, peer state changes and other messages
+    pub fn parse_line(&self, line: &str) -> Result<Vec<PeerUpdate>, String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(Vec::new());
+        }
+        match self.format {
+            UpdateFormat::RisLive => parse_ris_live(line),
+            UpdateFormat::BgpStream => parse_bgpstream(line),
+        }
+    }
+
+    /// Events the update causes at the origins, given the updates seen before it
+    pub fn events(&mut self, update: &PeerUpdate, engine: &SimulationEngine) -> Vec<ScenarioEvent> {
+        if !self.replays(&update.prefix) {
+            return Vec::new();
+        }
+        let new_origin = match &update.as_path {
+            Some(path) => match path.last().filter(|origin| engine.as_graph.get(origin).is_some()) {
+                Some(&origin) => Some(origin),
+                None => {
+                    self.num_skipped += 1;
+                    return Vec::new();
+                }
+            },
+            None => None,
+        };
+
+        let key = (update.peer_asn, update.prefix);
+        let old_origin = match new_origin {
+            Some(origin) => self.peer_origins.insert(key, origin),
+            None => self.peer_origins.remove(&key),
+        };
+        if old_origin == new_origin {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        if let Some(origin) = old_origin {
+            let num_peers = self.num_peers.entry((update.prefix, origin)).or_insert(1);
+            *num_peers -= 1;
+            if *num_peers == 0 {
+                self.num_peers.remove(&(update.prefix, origin));
+                events.push(ScenarioEvent::Withdraw(origin, update.prefix));
+            }
+        }
+        if let Some(origin) = new_origin {
+            let num_peers = self.num_peers.entry((update.prefix, origin)).or_insert(0);
+            *num_peers += 1;
+            if *num_peers == 1 {
+                let ann = Announcement::new_with_path(
+                    update.prefix,
+                    vec![],
+                    origin,
+                    Relationships::Origin,
+                    Timestamps(update.timestamp),
+                );
+                events.push(ScenarioEvent::Announce(origin, ann));
+            }
+        }
+        events
+    }
+
+    /// Replay every update in `reader` against `engine`, returning how many events were applied
+    /// Withdrawals need `PolicyStorageMode::Full`
+    pub fn replay<R: BufRead>(
+        &mut self,
+        reader: R,
+        engine: &mut SimulationEngine,
+        route_validator: &mut RouteValidator,
+    ) -> Result<usize, String> {
+        let mut num_events = 0;
+        let mut batch_start: Option<u64> = None;
+        for (line_num, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| format!("Failed to read line {}: {}", line_num + 1, e))?;
+            let updates = self.parse_line(&line).map_err(|e| format!("Line {}: {}", line_num + 1, e))?;
+            for update in updates {
+                let events = self.events(&update, engine);
+                if events.is_empty() {
+                    continue;
+                }
+                match batch_start {
+                    Some(start) if update.timestamp >= start + self.batch_seconds => {
+                        engine.run(self.rounds_per_batch);
+                        batch_start = Some(update.timestamp);
+                    }
+                    Some(_) => {}
+                    None => batch_start = Some(update.timestamp),
+                }
+                for event in events {
+                    event.apply(engine, route_validator)?;
+                    num_events += 1;
+                }
+            }
+        }
+        if batch_start.is_some() {
+            engine.run(self.rounds_per_batch);
+        }
+        tracing::info!(num_events, num_skipped = self.num_skipped, "Replayed updates");
+        Ok(num_events)
+    }
+}
+
+fn parse_prefix(prefix: &str) -> Result<Prefix, String> {
+    IpNetwork::from_str(prefix).map_err(|e| format!("Invalid prefix {}: {}", prefix, e))
+}
+
+/// Updates of a RIS Live `ris_message`, skipping announcements whose path has an AS set
+fn parse_ris_live(line: &str) -> Result<Vec<PeerUpdate>, String> {
+    let message: serde_json::Value = serde_json::from_str(line).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let data = &message["data"];
+    if message["type"] != "ris_message" || data["type"] != "UPDATE" {
+        return Ok(Vec::new());
+    }
+    let timestamp = data["timestamp"].as_f64().ok_or("RIS Live update is missing timestamp")? as u64;
+    let peer_asn: ASN = data["peer_asn"].as_str()
+        .and_then(|asn| asn.parse().ok())
+        .ok_or("RIS Live update is missing peer_asn")?;
+
+    let mut updates = Vec::new();
+    if let Some(withdrawals) = data["withdrawals"].as_array() {
+        for prefix in withdrawals {
+            let prefix = parse_prefix(prefix.as_str().ok_or("RIS Live withdrawal is not a string")?)?;
+            updates.push(PeerUpdate { timestamp, peer_asn, prefix, as_path: None });
+        }
+    }
+    // AS sets are nested arrays, which don't convert to ASNs
+    let as_path: Option<Vec<ASN>> = data["path"].as_array()
+        .and_then(|path| path.iter().map(|asn| asn.as_u64().map(|asn| asn as ASN)).collect());
+    if let Some(announcements) = data["announcements"].as_array() {
+        for prefix in announcements.iter().filter_map(|ann| ann["prefixes"].as_array()).flatten() {
+            let prefix = parse_prefix(prefix.as_str().ok_or("RIS Live prefix is not a string")?)?;
+            if let Some(path) = &as_path {
+                updates.push(PeerUpdate { timestamp, peer_asn, prefix, as_path: Some(dedup(path)) });
+            }
+        }
+    }
+    Ok(updates)
+}
+
+/// The update of a `bgpreader` elem line, skipping paths with AS sets and elems other than announcements,
+/// RIB entries and withdrawals
+fn parse_bgpstream(line: &str) -> Result<Vec<PeerUpdate>, String> {
+    let fields: Vec<&str> = line.split('|').collect();
+    if fields.len() < 10 {
+        return Err(format!("Expected at least 10 fields, got {}", fields.len()));
+    }
+    let is_announcement = match fields[1] {
+        "A" | "R" => true,
+        "W" => false,
+        _ => return Ok(Vec::new()),
+    };
+    let timestamp = fields[2].parse::<f64>().map_err(|_| format!("Invalid timestamp {}", fields[2]))? as u64;
+    let peer_asn: ASN = fields[7].parse().map_err(|_| format!("Invalid peer ASN {}", fields[7]))?;
+    let prefix = parse_prefix(fields[9])?;
+    let as_path = match is_announcement {
+        true => {
+            let path = fields.get(11).ok_or("Announcement is missing its AS path")?;
+            if path.contains('{') {
+                return Ok(Vec::new());
+            }
+            let path = path.split_whitespace()
+                .map(|asn| asn.parse::<ASN>().map_err(|_| format!("Invalid ASN {} in AS path", asn)))
+                .collect::<Result<Vec<ASN>, String>>()?;
+            Some(dedup(&path))
+        }
+        false => None,
+    };
+    Ok(vec![PeerUpdate { timestamp, peer_asn, prefix, as_path }])
+}
+
+/// `path` without prepending
+fn dedup(path: &[ASN]) -> Vec<ASN> {
+    let mut path = path.to_vec();
+    path.dedup();
+    path
+}
@@ -0,0 +1,322 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::Router;
+use tokio::sync::Semaphore;
+
+use bgpsim_core::as_graphs::as_graph::{ASBuilder, ASGraph};
+use bgpsim_core::Error;
+
+use crate::engine_runner::engine_run_config::as_builder_from_json;
+use crate::simulation_framework::scenario_config::{config_field, ScenarioConfig};
+use crate::simulation_framework::Simulation;
+
+/// Status code and message of a failed request, answered as `{"error": message}`
+type ApiError = (StatusCode, String);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    /// Waiting for one of the server's `max_concurrent_runs` slots
+    Queued,
+    Running,
+    Finished,
+    Failed,
+}
+
+impl std::fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            RunStatus::Queued => "queued",
+            RunStatus::Running => "running",
+            RunStatus::Finished => "finished",
+            RunStatus::Failed => "failed",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A simulation started through the server
+struct RunHandle {
+    output_dir: PathBuf,
+    total_trials: usize,
+    completed_trials: Arc<AtomicUsize>,
+    /// Set once the run holds a slot and its simulation started
+    started: Arc<AtomicBool>,
+    /// Set once the run's simulation is done, with its error if it failed
+    outcome: Arc<Mutex<Option<Result<(), String>>>>,
+}
+
+impl RunHandle {
+    fn status(&self) -> (RunStatus, Option<String>) {
+        match &*self.outcome.lock().unwrap() {
+            None if !self.started.load(Ordering::Acquire) => (RunStatus::Queued, None),
+            None => (RunStatus::Running, None),
+            Some(Ok(())) => (RunStatus::Finished, None),
+            Some(Err(e)) => (RunStatus::Failed, Some(e.clone())),
+        }
+    }
+}
+
+/// JSON API for driving simulations remotely, served with axum
+///
+/// - `PUT /topologies/<name>`: an AS graph, as `ASGraph::to_json` writes it or as a list of
+///   `{"asn", "peers", "providers", "customers", "tier_1", "ixp"}` like engine run configs
+/// - `PUT /scenario_configs/<name>`: a scenario config, see `ScenarioConfig::from_json`; configs here and in
+///   runs can't name files for the server to read, see `ScenarioConfig::from_json_without_files`
+/// - `POST /runs`: `{"topology", "scenario_configs", "percent_ases_randomly_adopting", "num_trials"}`,
+///   where scenario configs are uploaded names or inline configs; answers with the run's id
+/// - `GET /runs` and `GET /runs/<id>`: status and progress in trials
+/// - `GET /runs/<id>/results` and `GET /runs/<id>/results/<file>`: the run's output files
+///
+/// Each run writes to its own directory under the root. At most `max_concurrent_runs` simulations
+/// run at once, on tokio's blocking pool; later runs are queued until one finishes
+pub struct SimulationServer {
+    pub root: PathBuf,
+    topologies: Mutex<HashMap<String, Arc<ASGraph>>>,
+    scenario_configs: Mutex<HashMap<String, ScenarioConfig>>,
+    runs: Mutex<BTreeMap<usize, RunHandle>>,
+    run_slots: Arc<Semaphore>,
+}
+
+impl SimulationServer {
+    /// Server running one simulation at a time, since each already uses every core for its trials
+    pub fn new(root: PathBuf) -> Self {
+        SimulationServer {
+            root,
+            topologies: Mutex::new(HashMap::new()),
+            scenario_configs: Mutex::new(HashMap::new()),
+            runs: Mutex::new(BTreeMap::new()),
+            run_slots: Arc::new(Semaphore::new(1)),
+        }
+    }
+
+    /// Run up to `max_concurrent_runs` simulations at once (at least one)
+    pub fn with_max_concurrent_runs(mut self, max_concurrent_runs: usize) -> Self {
+        self.run_slots = Arc::new(Semaphore::new(max_concurrent_runs.max(1)));
+        self
+    }
+
+    /// The API's routes, for serving with `axum::serve` or nesting in a larger app
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/topologies/{name}", put(add_topology))
+            .route("/scenario_configs/{name}", put(add_scenario_config))
+            .route("/runs", post(start_run).get(list_runs))
+            .route("/runs/{id}", get(get_run))
+            .route("/runs/{id}/results", get(list_results))
+            .route("/runs/{id}/results/{file}", get(get_result))
+            .fallback(|method: axum::http::Method, uri: axum::http::Uri| async move {
+                api_error((StatusCode::NOT_FOUND, format!("No endpoint {} {}", method, uri.path())))
+            })
+            .with_state(self)
+    }
+
+    /// Serve the API at `addr` (e.g. `127.0.0.1:8080`) until the process exits
+    pub async fn serve(self, addr: &str) -> Result<(), Error> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        tracing::info!(addr, root = ?self.root, "Serving simulation API");
+        axum::serve(listener, Arc::new(self).router()).await?;
+        Ok(())
+    }
+
+    fn start_run(&self, value: &serde_json::Value) -> Result<serde_json::Value, ApiError> {
+        let bad_request = |e: String| (StatusCode::BAD_REQUEST, e);
+        let topology: String = config_field(value, "topology").map_err(bad_request)?
+            .ok_or((StatusCode::BAD_REQUEST, "Run is missing topology".to_string()))?;
+        let as_graph = self.topologies.lock().unwrap().get(&topology).cloned()
+            .ok_or_else(|| (StatusCode::NOT_FOUND, format!("No topology {}", topology)))?;
+        let config_values: Vec<serde_json::Value> = config_field(value, "scenario_configs").map_err(bad_request)?
+            .ok_or((StatusCode::BAD_REQUEST, "Run is missing scenario_configs".to_string()))?;
+        let scenario_configs = config_values.iter()
+            .map(|config| match config.as_str() {
+                Some(name) => self.scenario_configs.lock().unwrap().get(name).cloned()
+                    .ok_or_else(|| (StatusCode::NOT_FOUND, format!("No scenario config {}", name))),
+                None => ScenarioConfig::from_json_without_files(config).map_err(|e| bad_request(e.to_string())),
+            })
+            .collect::<Result<Vec<ScenarioConfig>, _>>()?;
+
+        // The topology is copied in once the run starts, see below
+        let completed_trials = Arc::new(AtomicUsize::new(0));
+        let mut simulation = Simulation::new(ASGraph::new())
+            .with_scenario_configs(scenario_configs)
+            .with_progress(completed_trials.clone());
+        if let Some(percentages) = config_field(value, "percent_ases_randomly_adopting").map_err(bad_request)? {
+            simulation = simulation.with_adoption_percentages(percentages);
+        }
+        if let Some(num_trials) = config_field(value, "num_trials").map_err(bad_request)? {
+            simulation = simulation.with_num_trials(num_trials);
+        }
+
+        let total_trials = simulation.total_trials();
+        let started = Arc::new(AtomicBool::new(false));
+        let outcome = Arc::new(Mutex::new(None));
+        // The runs are only locked to claim an id, so status requests never wait on a run being set up
+        let id = {
+            let mut runs = self.runs.lock().unwrap();
+            let id = runs.keys().next_back().map_or(1, |id| id + 1);
+            let output_dir = self.root.join(format!("run_{}", id));
+            simulation = simulation.with_output_dir(output_dir.clone());
+            runs.insert(id, RunHandle {
+                output_dir,
+                total_trials,
+                completed_trials,
+                started: started.clone(),
+                outcome: outcome.clone(),
+            });
+            id
+        };
+        let run_slots = self.run_slots.clone();
+        tokio::spawn(async move {
+            let _permit = run_slots.acquire_owned().await.expect("run slots are never closed");
+            started.store(true, Ordering::Release);
+            tracing::info!(id, "Starting run");
+            let result = tokio::task::spawn_blocking(move || {
+                // Copying a full topology takes a while, so it's done here rather than on an async worker
+                simulation.as_graph = ASGraph::clone(&as_graph);
+                simulation.run().map_err(|e| e.to_string())
+            });
+            let result = result.await.unwrap_or_else(|e| Err(format!("Run panicked: {}", e)));
+            if let Err(e) = &result {
+                tracing::warn!(id, error = %e, "Run failed");
+            }
+            *outcome.lock().unwrap() = Some(result);
+        });
+        Ok(serde_json::json!({ "id": id }))
+    }
+
+    /// Apply `f` to run `id`, or answer 404
+    fn run<T>(&self, id: &str, f: impl FnOnce(usize, &RunHandle) -> T) -> Result<T, ApiError> {
+        let runs = self.runs.lock().unwrap();
+        let run = id.parse().ok().and_then(|id| runs.get(&id).map(|run| (id, run)));
+        let (id, run) = run.ok_or_else(|| (StatusCode::NOT_FOUND, format!("No run {}", id)))?;
+        Ok(f(id, run))
+    }
+}
+
+type ServerState = State<Arc<SimulationServer>>;
+
+async fn add_topology(State(server): ServerState, Path(name): Path<String>, body: Bytes) -> Response {
+    let result = async {
+        let value = parse_body(&body)?;
+        // Building the graph of a full CAIDA topology takes a while, so it's kept off the async workers
+        let as_graph = tokio::task::spawn_blocking(move || topology_from_json(&value))
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))??;
+        let num_ases = as_graph.len();
+        server.topologies.lock().unwrap().insert(name.clone(), Arc::new(as_graph));
+        tracing::info!(name, num_ases, "Uploaded topology");
+        Ok(serde_json::json!({ "name": name, "num_ases": num_ases }))
+    };
+    json_response(result.await)
+}
+
+fn topology_from_json(value: &serde_json::Value) -> Result<ASGraph, ApiError> {
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, e);
+    let mut as_graph = match value.as_array() {
+        Some(as_values) => {
+            let builders = as_values.iter().map(as_builder_from_json).collect::<Result<Vec<ASBuilder>, String>>()
+                .map_err(bad_request)?;
+            let mut as_graph = ASGraph::build(builders);
+            as_graph.check_for_cycles().map_err(|e| bad_request(e.to_string()))?;
+            as_graph.assign_as_propagation_rank();
+            as_graph
+        }
+        None => ASGraph::from_json(value).map_err(bad_request)?,
+    };
+    as_graph.add_asn_groups();
+    Ok(as_graph)
+}
+
+async fn add_scenario_config(State(server): ServerState, Path(name): Path<String>, body: Bytes) -> Response {
+    let result = parse_body(&body).and_then(|value| {
+        let config = ScenarioConfig::from_json_without_files(&value).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        let label = config.label.clone();
+        server.scenario_configs.lock().unwrap().insert(name.clone(), config);
+        Ok(serde_json::json!({ "name": name, "label": label }))
+    });
+    json_response(result)
+}
+
+async fn start_run(State(server): ServerState, body: Bytes) -> Response {
+    json_response(parse_body(&body).and_then(|value| server.start_run(&value)))
+}
+
+async fn list_runs(State(server): ServerState) -> Response {
+    json_response(Ok(server.runs.lock().unwrap().iter().map(|(&id, run)| run_json(id, run)).collect()))
+}
+
+async fn get_run(State(server): ServerState, Path(id): Path<String>) -> Response {
+    json_response(server.run(&id, run_json))
+}
+
+async fn list_results(State(server): ServerState, Path(id): Path<String>) -> Response {
+    let result = async {
+        let output_dir = server.run(&id, |_, run| run.output_dir.clone())?;
+        Ok(serde_json::json!(result_files(output_dir).await))
+    };
+    json_response(result.await)
+}
+
+async fn get_result(State(server): ServerState, Path((id, file)): Path<(String, String)>) -> Response {
+    let result = async {
+        let output_dir = server.run(&id, |_, run| run.output_dir.clone())?;
+        // Only files the run wrote are served, so requests can't reach anything else
+        if !result_files(output_dir.clone()).await.contains(&file) {
+            return Err((StatusCode::NOT_FOUND, format!("No result file {}", file)));
+        }
+        tokio::fs::read(output_dir.join(&file)).await.map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))
+    };
+    match result.await {
+        Ok(body) => ([(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(e) => api_error(e),
+    }
+}
+
+fn parse_body(body: &[u8]) -> Result<serde_json::Value, ApiError> {
+    serde_json::from_slice(body).map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid JSON body: {}", e)))
+}
+
+fn json_response(result: Result<serde_json::Value, ApiError>) -> Response {
+    match result {
+        Ok(value) => axum::Json(value).into_response(),
+        Err(e) => api_error(e),
+    }
+}
+
+fn api_error((status, message): ApiError) -> Response {
+    (status, axum::Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn run_json(id: usize, run: &RunHandle) -> serde_json::Value {
+    let (status, error) = run.status();
+    serde_json::json!({
+        "id": id,
+        "status": status.to_string(),
+        "completed_trials": run.completed_trials.load(Ordering::Relaxed),
+        "total_trials": run.total_trials,
+        "error": error,
+    })
+}
+
+/// JSON files the run has written so far, sorted
+async fn result_files(output_dir: PathBuf) -> Vec<String> {
+    let mut files = Vec::new();
+    if let Ok(mut entries) = tokio::fs::read_dir(&output_dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let is_file = entry.file_type().await.is_ok_and(|file_type| file_type.is_file());
+            let file = entry.file_name().to_string_lossy().into_owned();
+            if is_file && file.ends_with(".json") {
+                files.push(file);
+            }
+        }
+    }
+    files.sort();
+    files
+}
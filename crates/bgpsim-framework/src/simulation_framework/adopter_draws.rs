@@ -0,0 +1,108 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+
+use super::scenario::Scenario;
+
+/// Randomly drawn adopting ASes per (adoption percentage, trial)
+///
+/// Shared by every scenario config of a simulation so that trial N at a given percentage
+/// has the same adopters under every defense, making the results paired
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AdopterDraws {
+    /// Draws by the bits of the adoption percentage, indexed by trial
+    draws: HashMap<u64, Vec<HashSet<ASN>>>,
+}
+
+impl AdopterDraws {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw adopters for `num_trials` trials at each percentage up front
+    pub fn generate(as_graph: &ASGraph, percentages: &[f64], num_trials: usize) -> Self {
+        let mut draws = Self::new();
+        for &percent in percentages {
+            if num_trials > 0 {
+                draws.get_or_draw(as_graph, percent, num_trials - 1);
+            }
+        }
+        draws
+    }
+
+    pub fn get(&self, percent: f64, trial: usize) -> Option<&HashSet<ASN>> {
+        self.draws.get(&percent.to_bits())?.get(trial)
+    }
+
+    /// Adopters for `trial` at `percent`, drawing them (and any earlier trials') if not drawn yet
+    pub fn get_or_draw(&mut self, as_graph: &ASGraph, percent: f64, trial: usize) -> &HashSet<ASN> {
+        let trials = self.draws.entry(percent.to_bits()).or_default();
+        while trials.len() <= trial {
+            trials.push(Scenario::get_random_adopting_asns(as_graph, percent));
+        }
+        &trials[trial]
+    }
+
+    /// Number of trials drawn at `percent`
+    pub fn num_trials(&self, percent: f64) -> usize {
+        self.draws.get(&percent.to_bits()).map_or(0, Vec::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.draws.values().all(Vec::is_empty)
+    }
+
+    /// Fail if any drawn adopter isn't in `as_graph`, e.g. when draws were saved for another graph
+    pub fn check_graph(&self, as_graph: &ASGraph) -> Result<(), String> {
+        for trials in self.draws.values() {
+            if let Some(asn) = trials.iter().flatten().find(|asn| as_graph.get(asn).is_none()) {
+                return Err(format!("Adopter draws include AS {}, which is not in the graph", asn));
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws as `{"draws": {"<percent>": [[asn, ...], ...]}}`, with sorted ASNs so files diff cleanly
+    pub fn to_json(&self) -> serde_json::Value {
+        let draws: BTreeMap<String, Vec<Vec<ASN>>> = self.draws.iter()
+            .map(|(&bits, trials)| {
+                let trials = trials.iter()
+                    .map(|asns| {
+                        let mut asns: Vec<ASN> = asns.iter().copied().collect();
+                        asns.sort_unstable();
+                        asns
+                    })
+                    .collect();
+                (f64::from_bits(bits).to_string(), trials)
+            })
+            .collect();
+        serde_json::json!({ "draws": draws })
+    }
+
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        let draws: HashMap<String, Vec<HashSet<ASN>>> = serde_json::from_value(value["draws"].clone())
+            .map_err(|e| format!("Invalid adopter draws: {}", e))?;
+        let draws = draws.into_iter()
+            .map(|(percent, trials)| {
+                let percent: f64 = percent.parse()
+                    .map_err(|_| format!("Invalid adoption percentage in adopter draws: {}", percent))?;
+                Ok((percent.to_bits(), trials))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(AdopterDraws { draws })
+    }
+
+    pub fn save_to_file(&self, path: &Path) -> std::io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(&self.to_json())?)
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let value: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+        Self::from_json(&value)
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::shared::Settings;
+
+use super::scenario_config::{ScenarioConfig, config_field};
+
+/// Map a bgpy policy class (e.g. `ROVSimplePolicy`, `ASPAFull`) or bgpy `Settings` name (e.g. `PEER_ROV`)
+/// to the setting that selects the equivalent policy here
+pub fn settings_from_bgpy_name(name: &str) -> Option<Settings> {
+    let mut normalized = name.replace('_', "").to_uppercase();
+    // bgpy suffixes the same policy by implementation (BGPSimplePolicy, BGPFull, ROVPolicy, ...)
+    for suffix in ["SIMPLEPOLICY", "POLICY", "FULL", "SIMPLE"] {
+        if normalized.len() > suffix.len() && normalized.ends_with(suffix) {
+            normalized.truncate(normalized.len() - suffix.len());
+            break;
+        }
+    }
+
+    let setting = match normalized.as_str() {
+        "BGP" | "BASEDEFENSE" => Settings::BaseDefense,
+        "ROV" => Settings::Rov,
+        "PEERROV" => Settings::PeerRov,
+        "BGPISEC" => Settings::Bgpisec,
+        "BGPISECTRANSITIVE" => Settings::BgpisecTransitive,
+        "BGPISECTRANSITIVEPROCONID" => Settings::BgpisecTransitiveProConId,
+        "BGPISECTRANSITIVEONLYTOCUSTOMERS" => Settings::BgpisecTransitiveOnlyToCustomers,
+        "PROVIDERCONEID" => Settings::ProviderConeId,
+        "ONLYTOCUSTOMERS" => Settings::OnlyToCustomers,
+        "EDGEFILTER" | "ASPATHEDGEFILTER" => Settings::EdgeFilter,
+        "ROVEDGEFILTER" => Settings::RovEdgeFilter,
+        "BGPSEC" => Settings::Bgpsec,
+        "PATHEND" => Settings::PathEnd,
+        "ROVPPV1LITE" => Settings::RovppV1Lite,
+        "ROVPPV2LITE" => Settings::RovppV2Lite,
+        "ROVPPV2ILITE" => Settings::RovppV2iLite,
+        "ENFORCEFIRSTAS" => Settings::EnforceFirstAs,
+        "ROVENFORCEFIRSTAS" => Settings::RovEnforceFirstAs,
+        "ASPA" => Settings::Aspa,
+        "ASPAWN" => Settings::Aspawn,
+        "ASRA" => Settings::Asra,
+        "PEERLOCKLITE" => Settings::PeerLockLite,
+        "ROST" => Settings::Rost,
+        _ => return None,
+    };
+    Some(setting)
+}
+
+fn setting_for(name: &str) -> Result<Settings, String> {
+    settings_from_bgpy_name(name).ok_or_else(|| format!("No equivalent for bgpy policy {}", name))
+}
+
+/// Convert a bgpy scenario config (as JSON) into a `ScenarioConfig`
+///
+/// Understands both the class-based fields (`ScenarioCls`, `AdoptPolicyCls`, `BasePolicyCls`,
+/// `override_non_default_asn_cls_dict`) and the settings-based ones (`default_adoption_settings`,
+/// `override_adoption_settings`), plus the `override_*_asns` lists.
+/// Policies without an equivalent here, and non-BGP base policies, are errors rather than silently run as BGP
+pub fn scenario_config_from_bgpy_json(value: &serde_json::Value) -> Result<ScenarioConfig, String> {
+    let scenario_name: String = ["ScenarioCls", "scenario_cls", "scenario_name"].iter()
+        .find_map(|key| config_field(value, key).transpose())
+        .transpose()?
+        .ok_or("bgpy scenario config is missing ScenarioCls")?;
+    let label = config_field(value, "label")?.unwrap_or_else(|| scenario_name.clone());
+    let mut config = ScenarioConfig::new(label, scenario_name);
+
+    for key in ["BasePolicyCls", "base_policy_cls"] {
+        if let Some(name) = config_field::<String>(value, key)? {
+            if setting_for(&name)? != Settings::BaseDefense {
+                return Err(format!("Base policy {} is not supported, only BGP", name));
+            }
+        }
+    }
+    for key in ["AdoptPolicyCls", "adopt_policy_cls"] {
+        if let Some(name) = config_field::<String>(value, key)? {
+            config = config.with_adoption_setting(setting_for(&name)?, true);
+        }
+    }
+    if let Some(settings) = config_field::<HashMap<String, bool>>(value, "default_adoption_settings")? {
+        for (name, enabled) in settings {
+            config = config.with_adoption_setting(setting_for(&name)?, enabled);
+        }
+    }
+
+    if let Some(asns) = config_field(value, "override_attacker_asns")? {
+        config = config.with_attacker_asns(asns);
+    }
+    // bgpy calls legitimate origins victims
+    for key in ["override_victim_asns", "override_legitimate_origin_asns"] {
+        if let Some(asns) = config_field(value, key)? {
+            config = config.with_legitimate_origin_asns(asns);
+        }
+    }
+    if let Some(asns) = config_field(value, "override_adopting_asns")? {
+        config = config.with_adopting_asns(asns);
+    }
+
+    for key in ["override_non_default_asn_cls_dict", "hardcoded_asn_cls_dict"] {
+        if let Some(asn_classes) = config_field::<HashMap<String, String>>(value, key)? {
+            for (asn, name) in asn_classes {
+                config = config.with_asn_setting(parse_asn(&asn, key)?, setting_for(&name)?);
+            }
+        }
+    }
+    // Per-AS settings dicts; an AS with every setting off runs BGP
+    if let Some(asn_settings) = config_field::<HashMap<String, HashMap<String, bool>>>(value, "override_adoption_settings")? {
+        for (asn, settings) in asn_settings {
+            let enabled = settings.iter()
+                .filter(|(_, &on)| on)
+                .map(|(name, _)| setting_for(name))
+                .collect::<Result<Vec<Settings>, String>>()?;
+            let setting = combine_settings(&enabled)
+                .ok_or_else(|| format!("AS {} enables {:?}, which no single policy here combines", asn, enabled))?;
+            config = config.with_asn_setting(parse_asn(&asn, "override_adoption_settings")?, setting);
+        }
+    }
+
+    Ok(config)
+}
+
+/// The one setting that runs all of `settings` together, if there is one
+fn combine_settings(settings: &[Settings]) -> Option<Settings> {
+    let mut settings: Vec<Settings> = settings.iter().copied().filter(|&s| s != Settings::BaseDefense).collect();
+    settings.sort_by_key(|&s| s as u32);
+    settings.dedup();
+    match settings.as_slice() {
+        [] => Some(Settings::BaseDefense),
+        [setting] => Some(*setting),
+        [Settings::Rov, Settings::EdgeFilter] => Some(Settings::RovEdgeFilter),
+        [Settings::Rov, Settings::EnforceFirstAs] => Some(Settings::RovEnforceFirstAs),
+        _ => None,
+    }
+}
+
+fn parse_asn(asn: &str, key: &str) -> Result<ASN, String> {
+    asn.parse().map_err(|_| format!("Invalid ASN in {}: {}", key, asn))
+}
@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::shared::{Outcomes, Settings};
+
+use super::adopter_draws::AdopterDraws;
+use super::data_tracker::DataTracker;
+
+/// Everything a finished trial added to its `DataTracker`, so a resumed run can replay it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrialRecord {
+    pub outcome: Outcomes,
+    /// Set when victims are sampled
+    pub victim_asn: Option<ASN>,
+    pub adoption_data: HashMap<Settings, f64>,
+    pub custom_metrics: HashMap<String, f64>,
+    /// Per-round series the trial added with `DataTracker::add_time_series`
+    pub time_series: HashMap<String, Vec<f64>>,
+    pub control_plane_outcomes: HashMap<Outcomes, usize>,
+    pub data_plane_outcomes: HashMap<Outcomes, usize>,
+}
+
+impl TrialRecord {
+    /// Record of the only trial in `trial_tracker`
+    pub fn from_tracker(trial_tracker: &DataTracker, outcome: Outcomes, victim_asn: Option<ASN>) -> Self {
+        let last = |values: &Vec<f64>| values.last().copied().unwrap_or_default();
+        TrialRecord {
+            outcome,
+            victim_asn,
+            adoption_data: trial_tracker.adoption_data.iter().map(|(&setting, values)| (setting, last(values))).collect(),
+            custom_metrics: trial_tracker.custom_metrics.iter().map(|(name, values)| (name.clone(), last(values))).collect(),
+            time_series: trial_tracker.time_series_trials.keys()
+                .map(|name| (name.clone(), trial_tracker.time_series_data[name].clone()))
+                .collect(),
+            control_plane_outcomes: trial_tracker.control_plane_outcomes.last().cloned().unwrap_or_default(),
+            data_plane_outcomes: trial_tracker.data_plane_outcomes.last().cloned().unwrap_or_default(),
+        }
+    }
+
+    /// Add the trial to `tracker` as if it had just run
+    pub fn add_to(&self, tracker: &mut DataTracker) {
+        for (&setting, &value) in &self.adoption_data {
+            tracker.add_adoption_metric(setting, value);
+        }
+        for (name, &value) in &self.custom_metrics {
+            tracker.add_custom_metric(name.clone(), value);
+        }
+        for (name, series) in &self.time_series {
+            tracker.add_time_series(name, series);
+        }
+        tracker.control_plane_outcomes.push(self.control_plane_outcomes.clone());
+        tracker.data_plane_outcomes.push(self.data_plane_outcomes.clone());
+        match self.victim_asn {
+            Some(victim_asn) => tracker.add_victim_outcome(victim_asn, self.outcome),
+            None => tracker.add_outcome(self.outcome),
+        }
+    }
+}
+
+/// Finished (scenario, percentage, trial) tuples of a simulation, saved to its output directory as it runs
+///
+/// Victims and attackers are drawn from the thread RNG, whose state can't be saved, so the
+/// adopter draws stand in for it: a resumed run reuses them for the trials it still has to run
+#[derive(Debug, Clone, Default)]
+pub struct Checkpoint {
+    /// Records by scenario label and the bits of the adoption percentage, by trial
+    trials: HashMap<(String, u64), BTreeMap<usize, TrialRecord>>,
+    pub adopter_draws: Option<AdopterDraws>,
+    /// Trials recorded since the last save
+    num_unsaved: usize,
+}
+
+impl Checkpoint {
+    pub const FILE_NAME: &'static str = "checkpoint.json";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn path(output_dir: &Path) -> PathBuf {
+        output_dir.join(Self::FILE_NAME)
+    }
+
+    pub fn get(&self, scenario_label: &str, percent: f64, trial: usize) -> Option<&TrialRecord> {
+        self.trials.get(&(scenario_label.to_string(), percent.to_bits()))?.get(&trial)
+    }
+
+    pub fn record(&mut self, scenario_label: &str, percent: f64, trial: usize, record: TrialRecord) {
+        self.trials.entry((scenario_label.to_string(), percent.to_bits())).or_default().insert(trial, record);
+        self.num_unsaved += 1;
+    }
+
+    /// Number of finished trials of a scenario at `percent`
+    pub fn num_trials(&self, scenario_label: &str, percent: f64) -> usize {
+        self.trials.get(&(scenario_label.to_string(), percent.to_bits())).map_or(0, BTreeMap::len)
+    }
+
+    pub fn num_unsaved(&self) -> usize {
+        self.num_unsaved
+    }
+
+    /// Trials as `{"trials": [{"scenario_label", "percent", "trial", "outcome", ...}], "adopter_draws": ...}`
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut keys: Vec<&(String, u64)> = self.trials.keys().collect();
+        keys.sort();
+        let trials: Vec<serde_json::Value> = keys.into_iter()
+            .flat_map(|key @ (label, bits)| self.trials[key].iter().map(move |(trial, record)| serde_json::json!({
+                "scenario_label": label,
+                "percent": f64::from_bits(*bits),
+                "trial": trial,
+                "outcome": record.outcome,
+                "victim_asn": record.victim_asn,
+                "adoption_data": record.adoption_data,
+                "custom_metrics": record.custom_metrics,
+                "time_series": record.time_series,
+                "control_plane_outcomes": record.control_plane_outcomes,
+                "data_plane_outcomes": record.data_plane_outcomes,
+            })))
+            .collect();
+        serde_json::json!({
+            "trials": trials,
+            "adopter_draws": self.adopter_draws.as_ref().map(AdopterDraws::to_json),
+        })
+    }
+
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        let field = |trial: &serde_json::Value, key: &str| trial.get(key).cloned().unwrap_or_default();
+        let mut checkpoint = Checkpoint::new();
+        for trial in value["trials"].as_array().ok_or("Checkpoint is missing trials")? {
+            let parse = |key: &str| format!("Invalid {} in checkpoint trial {}", key, trial);
+            let label: String = serde_json::from_value(field(trial, "scenario_label")).map_err(|_| parse("scenario_label"))?;
+            let percent: f64 = serde_json::from_value(field(trial, "percent")).map_err(|_| parse("percent"))?;
+            let trial_num: usize = serde_json::from_value(field(trial, "trial")).map_err(|_| parse("trial"))?;
+            let record = TrialRecord {
+                outcome: serde_json::from_value(field(trial, "outcome")).map_err(|_| parse("outcome"))?,
+                victim_asn: serde_json::from_value(field(trial, "victim_asn")).map_err(|_| parse("victim_asn"))?,
+                adoption_data: serde_json::from_value(field(trial, "adoption_data")).map_err(|_| parse("adoption_data"))?,
+                custom_metrics: serde_json::from_value(field(trial, "custom_metrics")).map_err(|_| parse("custom_metrics"))?,
+                // Absent from checkpoints written before time series were recorded
+                time_series: match trial.get("time_series") {
+                    Some(time_series) => serde_json::from_value(time_series.clone()).map_err(|_| parse("time_series"))?,
+                    None => HashMap::new(),
+                },
+                control_plane_outcomes: serde_json::from_value(field(trial, "control_plane_outcomes"))
+                    .map_err(|_| parse("control_plane_outcomes"))?,
+                data_plane_outcomes: serde_json::from_value(field(trial, "data_plane_outcomes"))
+                    .map_err(|_| parse("data_plane_outcomes"))?,
+            };
+            checkpoint.record(&label, percent, trial_num, record);
+        }
+        if !value["adopter_draws"].is_null() {
+            checkpoint.adopter_draws = Some(AdopterDraws::from_json(&value["adopter_draws"])?);
+        }
+        checkpoint.num_unsaved = 0;
+        Ok(checkpoint)
+    }
+
+    /// Write through a temporary file so a crash mid-save keeps the previous checkpoint
+    pub fn save_to_file(&mut self, path: &Path) -> std::io::Result<()> {
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(&self.to_json())?)?;
+        fs::rename(&tmp_path, path)?;
+        self.num_unsaved = 0;
+        Ok(())
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let value: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+        Self::from_json(&value)
+    }
+}
@@ -0,0 +1,451 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::shared::{Outcomes, Settings};
+use bgpsim_core::simulation_engine::SimulationEngine;
+
+use super::scenario::CustomMetrics;
+
+#[derive(Debug, Default)]
+pub struct DataTracker {
+    /// Track outcomes for each trial
+    pub outcomes: Vec<Outcomes>,
+    
+    /// Track which ASes adopted which settings
+    pub adoption_data: HashMap<Settings, Vec<f64>>,
+    
+    /// Track metrics over time; series added with `add_time_series` hold the mean over trials of each round
+    pub time_series_data: HashMap<String, Vec<f64>>,
+    
+    /// Number of trials averaged into each value of `time_series_data`
+    pub time_series_trials: HashMap<String, Vec<usize>>,
+    
+    /// Scenario-specific metrics, one value per trial
+    pub custom_metrics: HashMap<String, Vec<f64>>,
+    
+    /// Outcomes of each victim's trials when victims are sampled
+    pub victim_outcomes: BTreeMap<ASN, Vec<Outcomes>>,
+    
+    /// Number of ASes whose selected route had each outcome, one map per trial
+    pub control_plane_outcomes: Vec<HashMap<Outcomes, usize>>,
+    
+    /// Number of ASes whose traffic had each outcome, one map per trial
+    pub data_plane_outcomes: Vec<HashMap<Outcomes, usize>>,
+    
+    /// Scenario label
+    pub scenario_label: String,
+    
+    /// Percentage of ASes adopting
+    pub percent_adopting: f64,
+    
+    /// Percentage of ASes registering ASPA objects, when drawn separately from adopters
+    pub percent_aspa_objects: Option<f64>,
+    
+    /// Sweep dimension values of the scenario config, see `ScenarioConfig::sweep_parameters`
+    pub parameters: Vec<(String, serde_json::Value)>,
+}
+
+impl DataTracker {
+    pub fn new(scenario_label: String, percent_adopting: f64) -> Self {
+        DataTracker {
+            outcomes: Vec::new(),
+            adoption_data: HashMap::new(),
+            time_series_data: HashMap::new(),
+            time_series_trials: HashMap::new(),
+            custom_metrics: HashMap::new(),
+            victim_outcomes: BTreeMap::new(),
+            control_plane_outcomes: Vec::new(),
+            data_plane_outcomes: Vec::new(),
+            scenario_label,
+            percent_adopting,
+            percent_aspa_objects: None,
+            parameters: Vec::new(),
+        }
+    }
+    
+    /// Break the results down by ASPA object registration too, see `ScenarioConfig::percent_aspa_objects`
+    pub fn with_percent_aspa_objects(mut self, percent: Option<f64>) -> Self {
+        self.percent_aspa_objects = percent;
+        self
+    }
+    
+    pub fn with_parameters(mut self, parameters: Vec<(String, serde_json::Value)>) -> Self {
+        self.parameters = parameters;
+        self
+    }
+    
+    /// Full parameter tuple of these results: the sweep dimension values and the adoption percentage
+    pub fn parameters_json(&self) -> serde_json::Value {
+        let mut parameters = parameters_json(&self.parameters);
+        parameters["percent_adopting"] = serde_json::json!(self.percent_adopting);
+        parameters
+    }
+    
+    pub fn add_outcome(&mut self, outcome: Outcomes) {
+        self.outcomes.push(outcome);
+    }
+    
+    /// Record the outcome of a trial against a sampled victim
+    pub fn add_victim_outcome(&mut self, victim_asn: ASN, outcome: Outcomes) {
+        self.victim_outcomes.entry(victim_asn).or_default().push(outcome);
+        self.add_outcome(outcome);
+    }
+    
+    /// Record a trial's per-AS outcomes for the routes ASes chose and for where their traffic went
+    pub fn add_plane_outcomes(&mut self, control_plane: &HashMap<ASN, Outcomes>, data_plane: &HashMap<ASN, Outcomes>) {
+        self.control_plane_outcomes.push(outcome_counts(control_plane));
+        self.data_plane_outcomes.push(outcome_counts(data_plane));
+    }
+    
+    /// Mean percentage of ASes per trial whose selected route had `outcome`
+    pub fn control_plane_rate(&self, outcome: Outcomes) -> f64 {
+        mean_outcome_rate(&self.control_plane_outcomes, outcome)
+    }
+    
+    /// Mean percentage of ASes per trial whose traffic had `outcome`
+    pub fn data_plane_rate(&self, outcome: Outcomes) -> f64 {
+        mean_outcome_rate(&self.data_plane_outcomes, outcome)
+    }
+    
+    pub fn add_adoption_metric(&mut self, setting: Settings, value: f64) {
+        self.adoption_data.entry(setting).or_insert_with(Vec::new).push(value);
+    }
+    
+    /// Mean percentage of ASes running each setting, the policy mix the trials ended up with
+    pub fn adoption_mix(&self) -> HashMap<Settings, f64> {
+        self.adoption_data.iter()
+            .map(|(&setting, values)| (setting, values.iter().sum::<f64>() / values.len().max(1) as f64))
+            .collect()
+    }
+    
+    pub fn add_time_series_metric(&mut self, metric_name: String, value: f64) {
+        self.time_series_data.entry(metric_name).or_insert_with(Vec::new).push(value);
+    }
+    
+    /// Average one trial's per-round values into the metric's series
+    /// Trials can run for different numbers of rounds, so each round averages the trials that reached it
+    pub fn add_time_series(&mut self, metric_name: &str, series: &[f64]) {
+        let means = self.time_series_data.entry(metric_name.to_string()).or_default();
+        let trials = self.time_series_trials.entry(metric_name.to_string()).or_default();
+        for (round, &value) in series.iter().enumerate() {
+            if round == means.len() {
+                means.push(0.0);
+                trials.push(0);
+            }
+            trials[round] += 1;
+            means[round] += (value - means[round]) / trials[round] as f64;
+        }
+    }
+    
+    /// Record how many ASes a trial's hijack had polluted after each round, see `PollutionObserver`
+    pub fn add_polluted_ases(&mut self, per_round: &[usize]) {
+        let series: Vec<f64> = per_round.iter().map(|&count| count as f64).collect();
+        self.add_time_series("polluted_ases", &series);
+    }
+    
+    pub fn add_custom_metric(&mut self, metric_name: String, value: f64) {
+        self.custom_metrics.entry(metric_name).or_default().push(value);
+    }
+    
+    /// Evaluate a scenario's custom metrics against a finished engine and record them
+    pub fn record_custom_metrics(&mut self, metrics: &CustomMetrics, engine: &SimulationEngine) {
+        for (metric_name, value) in metrics.evaluate(engine) {
+            self.add_custom_metric(metric_name, value);
+        }
+    }
+    
+    /// Record how many of a trial's legitimate prefixes ROAs with loose max lengths leave open to
+    /// forged-origin subprefix hijacks, see `max_length_vulnerable_prefixes`
+    pub fn add_max_length_vulnerability(&mut self, num_vulnerable: usize, num_prefixes: usize) {
+        self.add_custom_metric("max_length_vulnerable_prefixes".to_string(), num_vulnerable as f64);
+        let percent = num_vulnerable as f64 / num_prefixes.max(1) as f64 * 100.0;
+        self.add_custom_metric("percent_max_length_vulnerable_prefixes".to_string(), percent);
+    }
+    
+    /// Mean percentage of legitimate prefixes per trial open to forged-origin subprefix hijacks
+    pub fn percent_max_length_vulnerable(&self) -> Option<f64> {
+        self.custom_metric_mean("percent_max_length_vulnerable_prefixes")
+    }
+    
+    /// Mean of a custom metric across trials
+    pub fn custom_metric_mean(&self, metric_name: &str) -> Option<f64> {
+        let values = self.custom_metrics.get(metric_name)?;
+        if values.is_empty() {
+            return None;
+        }
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+    
+    /// 100 for each trial the attacker won and 0 for the others, so their mean is `success_rate`
+    pub fn trial_success_values(&self) -> Vec<f64> {
+        self.outcomes.iter()
+            .map(|&outcome| if outcome == Outcomes::AttackerSuccess { 100.0 } else { 0.0 })
+            .collect()
+    }
+    
+    pub fn success_rate(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+        
+        let successes = self.outcomes.iter()
+            .filter(|&outcome| matches!(outcome, Outcomes::AttackerSuccess))
+            .count();
+            
+        (successes as f64) / (self.outcomes.len() as f64) * 100.0
+    }
+    
+    /// Percentage of each sampled victim's trials in which the victim kept its traffic
+    pub fn victim_protection_rates(&self) -> BTreeMap<ASN, f64> {
+        self.victim_outcomes.iter()
+            .map(|(&asn, outcomes)| {
+                let protected = outcomes.iter()
+                    .filter(|&outcome| matches!(outcome, Outcomes::VictimSuccess))
+                    .count();
+                (asn, protected as f64 / outcomes.len() as f64 * 100.0)
+            })
+            .collect()
+    }
+    
+    /// Mean and standard error of the per-victim protection rates
+    pub fn victim_protection_summary(&self) -> Option<(f64, f64)> {
+        let rates: Vec<f64> = self.victim_protection_rates().into_values().collect();
+        if rates.is_empty() {
+            return None;
+        }
+        let n = rates.len() as f64;
+        let mean = rates.iter().sum::<f64>() / n;
+        if rates.len() == 1 {
+            return Some((mean, 0.0));
+        }
+        let variance = rates.iter().map(|rate| (rate - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        Some((mean, (variance / n).sqrt()))
+    }
+    
+    pub fn save_to_file(&self, output_dir: &Path) -> std::io::Result<()> {
+        let file_name = format!("{}_{}_percent.json", self.scenario_label, self.percent_adopting);
+        let file_path = output_dir.join(file_name);
+        
+        let mut data = serde_json::json!({
+            "scenario_label": self.scenario_label,
+            "percent_adopting": self.percent_adopting,
+            "percent_aspa_objects": self.percent_aspa_objects,
+            "parameters": self.parameters_json(),
+            "success_rate": self.success_rate(),
+            "num_trials": self.outcomes.len(),
+            "outcomes": self.outcomes,
+            "adoption_data": self.adoption_data,
+            "adoption_mix": self.adoption_mix(),
+            "time_series_data": self.time_series_data,
+            "time_series_trials": self.time_series_trials,
+            "custom_metrics": self.custom_metrics,
+        });
+        
+        if !self.data_plane_outcomes.is_empty() {
+            data["control_plane_outcomes"] = outcome_rates_json(&self.control_plane_outcomes);
+            data["data_plane_outcomes"] = outcome_rates_json(&self.data_plane_outcomes);
+        }
+        
+        if let Some((mean, std_err)) = self.victim_protection_summary() {
+            let per_victim: serde_json::Map<String, serde_json::Value> = self.victim_protection_rates().into_iter()
+                .map(|(asn, rate)| (asn.to_string(), serde_json::json!(rate)))
+                .collect();
+            data["victims"] = serde_json::json!({
+                "num_victims": self.victim_outcomes.len(),
+                "mean_protection_rate": mean,
+                "protection_rate_std_err": std_err,
+                "protection_rates": per_victim,
+            });
+        }
+        
+        let json = serde_json::to_string_pretty(&data)?;
+        fs::write(file_path, json)?;
+        
+        Ok(())
+    }
+}
+
+fn outcome_counts(outcomes: &HashMap<ASN, Outcomes>) -> HashMap<Outcomes, usize> {
+    let mut counts = HashMap::new();
+    for &outcome in outcomes.values() {
+        *counts.entry(outcome).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Mean over trials of the percentage of ASes with `outcome`; trials without ASes count as 0
+fn mean_outcome_rate(trials: &[HashMap<Outcomes, usize>], outcome: Outcomes) -> f64 {
+    if trials.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = trials.iter()
+        .map(|counts| {
+            let num_ases = counts.values().sum::<usize>().max(1) as f64;
+            counts.get(&outcome).copied().unwrap_or(0) as f64 / num_ases * 100.0
+        })
+        .sum();
+    total / trials.len() as f64
+}
+
+/// Mean rate of every outcome that occurred in any trial, keyed by outcome name
+fn outcome_rates_json(trials: &[HashMap<Outcomes, usize>]) -> serde_json::Value {
+    let outcomes: BTreeMap<String, Outcomes> = trials.iter()
+        .flat_map(|counts| counts.keys())
+        .map(|&outcome| (format!("{:?}", outcome), outcome))
+        .collect();
+    outcomes.into_iter()
+        .map(|(name, outcome)| (name, serde_json::json!(mean_outcome_rate(trials, outcome))))
+        .collect::<serde_json::Map<String, serde_json::Value>>()
+        .into()
+}
+
+/// Sweep dimension values keyed by dimension name
+fn parameters_json(parameters: &[(String, serde_json::Value)]) -> serde_json::Value {
+    parameters.iter()
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect::<serde_json::Map<String, serde_json::Value>>()
+        .into()
+}
+
+/// Confidence level of an interval around a mean
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfidenceLevel {
+    Ninety,
+    NinetyFive,
+}
+
+impl ConfidenceLevel {
+    /// Two-sided critical value of the normal distribution
+    pub fn z_score(&self) -> f64 {
+        match self {
+            ConfidenceLevel::Ninety => 1.645,
+            ConfidenceLevel::NinetyFive => 1.96,
+        }
+    }
+}
+
+/// Mean and spread of one adoption percentage's per-trial values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrialStats {
+    pub num_trials: usize,
+    pub mean: f64,
+    /// Sample standard deviation, 0 for a single trial
+    pub std_dev: f64,
+}
+
+impl TrialStats {
+    pub fn from_values(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let std_dev = if values.len() == 1 {
+            0.0
+        } else {
+            (values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        };
+        Some(TrialStats { num_trials: values.len(), mean, std_dev })
+    }
+    
+    pub fn std_err(&self) -> f64 {
+        self.std_dev / (self.num_trials as f64).sqrt()
+    }
+    
+    /// Normal approximation interval around the mean, as (low, high)
+    pub fn confidence_interval(&self, level: ConfidenceLevel) -> (f64, f64) {
+        let half_width = level.z_score() * self.std_err();
+        (self.mean - half_width, self.mean + half_width)
+    }
+}
+
+/// Summary data for a complete simulation run
+#[derive(Debug)]
+pub struct SimulationSummary {
+    pub scenario_label: String,
+    pub adoption_percentages: Vec<f64>,
+    pub success_rates: Vec<f64>,
+    /// Per-trial values behind each success rate; empty for points added without them
+    pub trial_values: Vec<Vec<f64>>,
+    /// Sweep dimension values shared by every data point
+    pub parameters: Vec<(String, serde_json::Value)>,
+}
+
+impl SimulationSummary {
+    pub fn new(scenario_label: String) -> Self {
+        SimulationSummary {
+            scenario_label,
+            adoption_percentages: Vec::new(),
+            success_rates: Vec::new(),
+            trial_values: Vec::new(),
+            parameters: Vec::new(),
+        }
+    }
+    
+    pub fn with_parameters(mut self, parameters: Vec<(String, serde_json::Value)>) -> Self {
+        self.parameters = parameters;
+        self
+    }
+    
+    pub fn add_data_point(&mut self, percent: f64, success_rate: f64) {
+        self.adoption_percentages.push(percent);
+        self.success_rates.push(success_rate);
+        self.trial_values.push(Vec::new());
+    }
+    
+    /// Add a data point from its per-trial values, whose mean is the success rate
+    pub fn add_trials(&mut self, percent: f64, trial_values: Vec<f64>) {
+        let success_rate = TrialStats::from_values(&trial_values).map_or(0.0, |stats| stats.mean);
+        self.adoption_percentages.push(percent);
+        self.success_rates.push(success_rate);
+        self.trial_values.push(trial_values);
+    }
+    
+    /// Statistics of each data point, None for points without per-trial values
+    pub fn trial_stats(&self) -> Vec<Option<TrialStats>> {
+        self.trial_values.iter().map(|values| TrialStats::from_values(values)).collect()
+    }
+    
+    /// (adoption percentage, low, high) for each data point with per-trial values, for plotting error bars
+    pub fn error_bars(&self, level: ConfidenceLevel) -> Vec<(f64, f64, f64)> {
+        self.adoption_percentages.iter().zip(self.trial_stats())
+            .filter_map(|(&percent, stats)| {
+                let (low, high) = stats?.confidence_interval(level);
+                Some((percent, low, high))
+            })
+            .collect()
+    }
+    
+    pub fn save_to_file(&self, output_dir: &Path) -> std::io::Result<()> {
+        let file_name = format!("{}_summary.json", self.scenario_label);
+        let file_path = output_dir.join(file_name);
+        
+        let stats = self.trial_stats();
+        let interval = |level: ConfidenceLevel| -> Vec<Option<[f64; 2]>> {
+            stats.iter()
+                .map(|stats| stats.map(|stats| {
+                    let (low, high) = stats.confidence_interval(level);
+                    [low, high]
+                }))
+                .collect()
+        };
+        let data = serde_json::json!({
+            "scenario_label": self.scenario_label,
+            "parameters": parameters_json(&self.parameters),
+            "adoption_percentages": self.adoption_percentages,
+            "success_rates": self.success_rates,
+            "trial_values": self.trial_values,
+            "num_trials": stats.iter().map(|stats| stats.map_or(0, |stats| stats.num_trials)).collect::<Vec<usize>>(),
+            "std_devs": stats.iter().map(|stats| stats.map(|stats| stats.std_dev)).collect::<Vec<Option<f64>>>(),
+            "ci_90": interval(ConfidenceLevel::Ninety),
+            "ci_95": interval(ConfidenceLevel::NinetyFive),
+        });
+        
+        let json = serde_json::to_string_pretty(&data)?;
+        fs::write(file_path, json)?;
+        
+        Ok(())
+    }
+}
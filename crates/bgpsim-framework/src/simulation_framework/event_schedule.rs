@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use ipnetwork::IpNetwork;
+
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::route_validator::{ROA, RouteValidator};
+use bgpsim_core::shared::{Relationships, Timestamps};
+use bgpsim_core::simulation_engine::{Announcement, Prefix, SimulationEngine};
+
+use super::scenario_config::{config_field, roa_from_json};
+
+/// Something that happens to a running scenario between propagation rounds
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScenarioEvent {
+    /// The AS originates the announcement
+    Announce(ASN, Announcement),
+    /// The AS withdraws the prefix it originated, which needs `PolicyStorageMode::Full`
+    Withdraw(ASN, Prefix),
+    /// ROA changes update the scenario's `RouteValidator` and the copy the engine shares with ROV adopters
+    AddRoa(ROA),
+    RemoveRoa(ROA),
+    /// The link between two neighbors goes down, see `SimulationEngine::fail_link`
+    FailLink(ASN, ASN),
+    /// The AS and all its links go down, see `SimulationEngine::fail_as`
+    FailAs(ASN),
+}
+
+impl ScenarioEvent {
+    pub fn apply(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator) -> Result<(), String> {
+        match self {
+            ScenarioEvent::Announce(asn, ann) => engine.inject(vec![(*asn, ann.clone())]),
+            ScenarioEvent::Withdraw(asn, prefix) => {
+                let mut withdrawal = Announcement::new(*prefix, *asn, Relationships::Origin);
+                withdrawal.withdraw = true;
+                engine.inject(vec![(*asn, withdrawal)])
+            }
+            ScenarioEvent::AddRoa(roa) => {
+                route_validator.add_roa(roa.clone());
+                engine.set_route_validator(Arc::new(route_validator.clone()));
+                Ok(())
+            }
+            ScenarioEvent::RemoveRoa(roa) => {
+                if route_validator.remove_roa(roa) {
+                    engine.set_route_validator(Arc::new(route_validator.clone()));
+                }
+                Ok(())
+            }
+            ScenarioEvent::FailLink(asn, neighbor_asn) => engine.fail_link(*asn, *neighbor_asn),
+            ScenarioEvent::FailAs(asn) => engine.fail_as(*asn),
+        }
+    }
+
+    /// Parse an event with its round, e.g. `{"round": 5, "type": "announce", "asn": 666, "prefix": "1.2.3.0/24"}`
+    ///
+    /// Types are `announce` and `withdraw` (`asn`, `prefix`), `add_roa` and `remove_roa`
+    /// (`prefix`, `origin`, optional `max_length`), `fail_link` (`asn`, `neighbor_asn`)
+    /// and `fail_as` (`asn`). Announcements can set `as_path` and
+    /// `timestamp`, which defaults to the round so later waves are younger
+    pub fn from_json(value: &serde_json::Value) -> Result<(u32, Self), String> {
+        let round: u32 = config_field(value, "round")?.ok_or("Event is missing round")?;
+        let event_type: String = config_field(value, "type")?.ok_or("Event is missing type")?;
+        let asn = || config_field::<ASN>(value, "asn")?.ok_or_else(|| format!("{} event is missing asn", event_type));
+        let prefix = || -> Result<Prefix, String> {
+            let prefix: String = config_field(value, "prefix")?.ok_or_else(|| format!("{} event is missing prefix", event_type))?;
+            IpNetwork::from_str(&prefix).map_err(|e| format!("Invalid event prefix {}: {}", prefix, e))
+        };
+
+        let event = match event_type.as_str() {
+            "announce" => {
+                let as_path: Vec<ASN> = config_field(value, "as_path")?.unwrap_or_default();
+                let timestamp = config_field(value, "timestamp")?.unwrap_or(round as u64);
+                let asn = asn()?;
+                let ann = Announcement::new_with_path(prefix()?, as_path, asn, Relationships::Origin, Timestamps(timestamp));
+                ScenarioEvent::Announce(asn, ann)
+            }
+            "withdraw" => ScenarioEvent::Withdraw(asn()?, prefix()?),
+            "add_roa" => ScenarioEvent::AddRoa(roa_from_json(value)?),
+            "remove_roa" => ScenarioEvent::RemoveRoa(roa_from_json(value)?),
+            "fail_link" => {
+                let neighbor_asn = config_field(value, "neighbor_asn")?.ok_or("fail_link event is missing neighbor_asn")?;
+                ScenarioEvent::FailLink(asn()?, neighbor_asn)
+            }
+            "fail_as" => ScenarioEvent::FailAs(asn()?),
+            _ => return Err(format!("Unknown event type: {}", event_type)),
+        };
+        Ok((round, event))
+    }
+
+    /// Inverse of `from_json`
+    pub fn to_json(&self, round: u32) -> serde_json::Value {
+        let roa_json = |event_type: &str, roa: &ROA| serde_json::json!({
+            "round": round,
+            "type": event_type,
+            "prefix": roa.prefix.to_string(),
+            "origin": roa.origin,
+            "max_length": roa.max_length,
+        });
+        match self {
+            ScenarioEvent::Announce(asn, ann) => serde_json::json!({
+                "round": round,
+                "type": "announce",
+                "asn": asn,
+                "prefix": ann.prefix.to_string(),
+                "as_path": ann.as_path.to_vec(),
+                "timestamp": ann.timestamp.0,
+            }),
+            ScenarioEvent::Withdraw(asn, prefix) => serde_json::json!({
+                "round": round,
+                "type": "withdraw",
+                "asn": asn,
+                "prefix": prefix.to_string(),
+            }),
+            ScenarioEvent::AddRoa(roa) => roa_json("add_roa", roa),
+            ScenarioEvent::RemoveRoa(roa) => roa_json("remove_roa", roa),
+            ScenarioEvent::FailLink(asn, neighbor_asn) => serde_json::json!({
+                "round": round,
+                "type": "fail_link",
+                "asn": asn,
+                "neighbor_asn": neighbor_asn,
+            }),
+            ScenarioEvent::FailAs(asn) => serde_json::json!({
+                "round": round,
+                "type": "fail_as",
+                "asn": asn,
+            }),
+        }
+    }
+}
+
+/// Events of a scenario by the round they happen before, counting rounds from `setup`
+///
+/// Expresses what a single `setup` can't, e.g. an attacker announcing before round 5
+/// and the victim withdrawing before round 10
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventSchedule {
+    events: BTreeMap<u32, Vec<ScenarioEvent>>,
+}
+
+impl EventSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedule an event; events of the same round happen in the order they were added
+    pub fn add(&mut self, round: u32, event: ScenarioEvent) {
+        self.events.entry(round).or_default().push(event);
+    }
+
+    pub fn with_event(mut self, round: u32, event: ScenarioEvent) -> Self {
+        self.add(round, event);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// This schedule's events followed, within each round, by `other`'s
+    pub fn merged(&self, other: &EventSchedule) -> EventSchedule {
+        let mut schedule = self.clone();
+        for (round, event) in other.iter() {
+            schedule.add(round, event.clone());
+        }
+        schedule
+    }
+
+    pub fn events_at(&self, round: u32) -> &[ScenarioEvent] {
+        self.events.get(&round).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every event with its round, in the order they happen
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &ScenarioEvent)> {
+        self.events.iter().flat_map(|(&round, events)| events.iter().map(move |event| (round, event)))
+    }
+
+    pub fn last_round(&self) -> Option<u32> {
+        self.events.keys().next_back().copied()
+    }
+
+    /// Run `rounds` rounds of a freshly set up engine, applying each round's events before it propagates
+    /// Like `SimulationEngine::try_run`, stops after a round in which a guarded policy broke its limits
+    /// or strict next hop verification caught something
+    pub fn run(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator, rounds: u32) -> Result<(), String> {
+        if let Some(last_round) = self.last_round().filter(|&last_round| last_round >= rounds) {
+            return Err(format!("Event at round {} would never happen in {} rounds", last_round, rounds));
+        }
+        engine.check_next_hops()?;
+        for round in 0..rounds {
+            for event in self.events_at(round) {
+                event.apply(engine, route_validator)?;
+            }
+            engine.run_round();
+            engine.check_policy_guards()?;
+            engine.check_next_hops()?;
+        }
+        Ok(())
+    }
+
+    pub fn from_json(values: &[serde_json::Value]) -> Result<Self, String> {
+        let mut schedule = EventSchedule::new();
+        for value in values {
+            let (round, event) = ScenarioEvent::from_json(value)?;
+            schedule.add(round, event);
+        }
+        Ok(schedule)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        self.iter().map(|(round, event)| event.to_json(round)).collect()
+    }
+}
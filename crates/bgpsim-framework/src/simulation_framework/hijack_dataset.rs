@@ -0,0 +1,200 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::route_validator::RouteValidator;
+use bgpsim_core::shared::{ROARouted, ROAValidity, Relationships};
+use bgpsim_core::simulation_engine::{Prefix, SimulationEngine};
+
+/// Ground truth for a route, taken from the scenario that produced it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HijackLabel {
+    Legitimate,
+    Hijack,
+}
+
+impl HijackLabel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HijackLabel::Legitimate => "legitimate",
+            HijackLabel::Hijack => "hijack",
+        }
+    }
+}
+
+/// Features of a single route as seen by one AS, with its label
+#[derive(Debug, Clone, PartialEq)]
+pub struct HijackSample {
+    /// AS that received the route
+    pub observer_asn: ASN,
+    pub prefix: Prefix,
+    pub origin_asn: ASN,
+    pub next_hop_asn: ASN,
+    pub as_path_length: usize,
+    /// Relationship of the neighbor the route was received from
+    pub recv_relationship: Relationships,
+    pub roa_validity: ROAValidity,
+    pub roa_routed: ROARouted,
+    pub origin_customer_cone_size: usize,
+    pub origin_num_providers: usize,
+    pub observer_customer_cone_size: usize,
+    /// Whether the observer picked this route as its best route
+    pub selected: bool,
+    pub label: HijackLabel,
+}
+
+/// Labeled (route features, legitimate/hijack) dataset for hijack detection research
+///
+/// Every route in every AS's ribs_in is a sample, not just the selected ones,
+/// so detectors see the hijacks that lost route selection as well
+#[derive(Debug, Clone, Default)]
+pub struct HijackDataset {
+    pub samples: Vec<HijackSample>,
+}
+
+impl HijackDataset {
+    pub const CSV_HEADER: &'static str = "observer_asn,prefix,prefix_length,origin_asn,next_hop_asn,as_path_length,\
+recv_relationship,roa_validity,roa_routed,origin_customer_cone_size,origin_num_providers,\
+observer_customer_cone_size,selected,label";
+
+    pub fn new() -> Self {
+        HijackDataset::default()
+    }
+
+    /// Collect samples after a run
+    /// Routes whose origin is neither a legitimate origin nor an attacker are skipped
+    /// Needs `PolicyStorageMode::Full`, since samples come from ribs_in
+    pub fn from_engine(
+        engine: &SimulationEngine,
+        route_validator: &RouteValidator,
+        legitimate_origin_asns: &HashSet<ASN>,
+        attacker_asns: &HashSet<ASN>,
+    ) -> Self {
+        let mut samples = Vec::new();
+
+        for (&observer_asn, policy) in engine.policy_store.iter() {
+            let observer = match engine.as_graph.get(&observer_asn) {
+                Some(observer) => observer,
+                None => continue,
+            };
+
+            for (next_hop_asn, ann) in policy.ribs_in.iter().filter(|(_, ann)| !ann.withdraw) {
+                let origin_asn = ann.origin();
+                let label = if attacker_asns.contains(&origin_asn) {
+                    HijackLabel::Hijack
+                } else if legitimate_origin_asns.contains(&origin_asn) {
+                    HijackLabel::Legitimate
+                } else {
+                    continue;
+                };
+
+                let origin = engine.as_graph.get(&origin_asn);
+                let (roa_validity, roa_routed) = route_validator.get_roa_outcome(&ann.prefix, origin_asn);
+                let selected = policy.local_rib.get(&ann.prefix)
+                    .is_some_and(|best| best.next_hop_asn == next_hop_asn);
+
+                samples.push(HijackSample {
+                    observer_asn,
+                    prefix: ann.prefix,
+                    origin_asn,
+                    next_hop_asn,
+                    as_path_length: ann.as_path.len(),
+                    recv_relationship: ann.recv_relationship,
+                    roa_validity,
+                    roa_routed,
+                    origin_customer_cone_size: origin.map_or(0, |origin| origin.customer_cone_size()),
+                    origin_num_providers: origin.map_or(0, |origin| origin.providers().len()),
+                    observer_customer_cone_size: observer.customer_cone_size(),
+                    selected,
+                    label,
+                });
+            }
+        }
+
+        let mut dataset = HijackDataset { samples };
+        dataset.sort();
+        dataset
+    }
+
+    /// Append the samples of another run, e.g. to build one dataset across trials
+    pub fn extend(&mut self, other: HijackDataset) {
+        self.samples.extend(other.samples);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn num_with_label(&self, label: HijackLabel) -> usize {
+        self.samples.iter().filter(|sample| sample.label == label).count()
+    }
+
+    fn sort(&mut self) {
+        self.samples.sort_by(|a, b| {
+            (a.observer_asn, a.prefix, a.next_hop_asn).cmp(&(b.observer_asn, b.prefix, b.next_hop_asn))
+        });
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", Self::CSV_HEADER);
+        for sample in &self.samples {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+                sample.observer_asn,
+                sample.prefix,
+                sample.prefix.prefix(),
+                sample.origin_asn,
+                sample.next_hop_asn,
+                sample.as_path_length,
+                sample.recv_relationship,
+                sample.roa_validity,
+                sample.roa_routed,
+                sample.origin_customer_cone_size,
+                sample.origin_num_providers,
+                sample.observer_customer_cone_size,
+                sample.selected,
+                sample.label.name(),
+            ));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let samples: Vec<serde_json::Value> = self.samples.iter()
+            .map(|sample| serde_json::json!({
+                "observer_asn": sample.observer_asn,
+                "prefix": sample.prefix.to_string(),
+                "prefix_length": sample.prefix.prefix(),
+                "origin_asn": sample.origin_asn,
+                "next_hop_asn": sample.next_hop_asn,
+                "as_path_length": sample.as_path_length,
+                "recv_relationship": sample.recv_relationship.to_string(),
+                "roa_validity": sample.roa_validity.to_string(),
+                "roa_routed": sample.roa_routed.to_string(),
+                "origin_customer_cone_size": sample.origin_customer_cone_size,
+                "origin_num_providers": sample.origin_num_providers,
+                "observer_customer_cone_size": sample.observer_customer_cone_size,
+                "selected": sample.selected,
+                "label": sample.label.name(),
+            }))
+            .collect();
+
+        serde_json::json!({
+            "num_samples": self.len(),
+            "num_hijacks": self.num_with_label(HijackLabel::Hijack),
+            "samples": samples,
+        })
+    }
+
+    pub fn save_to_file(&self, output_dir: &Path, label: &str) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.to_json())?;
+        fs::write(output_dir.join(format!("{}_hijack_dataset.json", label)), json)?;
+        fs::write(output_dir.join(format!("{}_hijack_dataset.csv", label)), self.to_csv())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rand::Rng;
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+
+/// Per-AS adoption probabilities from a measurement, e.g. a RoVISTA or APNIC list of ROV adopters
+///
+/// Each trial, every listed AS adopts independently with its probability, so adopters follow the
+/// measured deployment instead of a uniform random percentage. Unlisted ASes never adopt
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeasuredAdoption {
+    probabilities: HashMap<ASN, f64>,
+    /// File the probabilities were loaded from, written back by `ScenarioConfig::to_json`
+    pub source: Option<PathBuf>,
+}
+
+impl MeasuredAdoption {
+    /// Probabilities must be between 0 and 1
+    pub fn new(probabilities: HashMap<ASN, f64>) -> Result<Self, String> {
+        if let Some((asn, probability)) = probabilities.iter().find(|(_, p)| !(0.0..=1.0).contains(*p)) {
+            return Err(format!("Adoption probability {} of AS {} is not between 0 and 1", probability, asn));
+        }
+        Ok(MeasuredAdoption { probabilities, source: None })
+    }
+
+    /// Parse `asn,probability` lines, e.g. `13335,0.98`
+    ///
+    /// A header line, blank lines, `#` comments and further columns are skipped, and ASNs may be
+    /// written `AS13335`. Probabilities are fractions, so percentages need dividing by 100 first
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut probabilities = HashMap::new();
+        let mut is_first_line = true;
+        for (line_num, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let Ok(asn) = fields[0].strip_prefix("AS").unwrap_or(fields[0]).parse::<ASN>() else {
+                if std::mem::take(&mut is_first_line) {
+                    continue;
+                }
+                return Err(format!("Line {}: invalid ASN {}", line_num + 1, fields[0]));
+            };
+            let probability: f64 = fields.get(1)
+                .ok_or_else(|| format!("Line {}: expected asn,probability", line_num + 1))?
+                .parse()
+                .map_err(|_| format!("Line {}: invalid probability {}", line_num + 1, fields[1]))?;
+            probabilities.insert(asn, probability);
+            is_first_line = false;
+        }
+        Self::new(probabilities)
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let mut adoption = Self::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+        adoption.source = Some(path.to_path_buf());
+        Ok(adoption)
+    }
+
+    /// Chance that `asn` adopts in a trial
+    pub fn probability(&self, asn: ASN) -> f64 {
+        self.probabilities.get(&asn).copied().unwrap_or(0.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.probabilities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.probabilities.is_empty()
+    }
+
+    /// Expected number of adopters among the ASes of `as_graph`
+    pub fn expected_adopters(&self, as_graph: &ASGraph) -> f64 {
+        as_graph.asns().map(|asn| self.probability(asn)).sum()
+    }
+
+    /// Draw one trial's adopters among the ASes of `as_graph`
+    pub fn draw(&self, as_graph: &ASGraph) -> HashSet<ASN> {
+        // Sorted so only the draws, not hash order, decide who adopts
+        let mut asns: Vec<ASN> = as_graph.asns().filter(|&asn| self.probability(asn) > 0.0).collect();
+        asns.sort_unstable();
+        let mut rng = rand::thread_rng();
+        asns.into_iter()
+            .filter(|&asn| rng.gen_bool(self.probability(asn)))
+            .collect()
+    }
+}
@@ -0,0 +1,30 @@
+pub mod adopter_draws;
+pub mod checkpoint;
+pub mod scenario;
+pub mod scenario_config;
+pub mod simulation;
+pub mod data_tracker;
+pub mod scenarios;
+pub mod transit_flows;
+pub mod pollution;
+pub mod hijack_dataset;
+pub mod bgpy_compat;
+pub mod event_schedule;
+pub mod measured_adoption;
+pub mod run_metadata;
+pub mod sweep;
+
+pub use adopter_draws::AdopterDraws;
+pub use checkpoint::{Checkpoint, TrialRecord};
+pub use scenario::{CustomMetrics, MetricFn, Scenario, ScenarioTrait};
+pub use scenario_config::{ASNCandidates, AdopterSelector, AdoptionRule, AttackerPlacement, RoaGeneration, ScenarioConfig, VictimSampling};
+pub use simulation::Simulation;
+pub use data_tracker::{ConfidenceLevel, DataTracker, SimulationSummary, TrialStats};
+pub use transit_flows::TransitFlows;
+pub use pollution::{PollutionCounts, PollutionObserver};
+pub use hijack_dataset::{HijackDataset, HijackLabel, HijackSample};
+pub use bgpy_compat::{scenario_config_from_bgpy_json, settings_from_bgpy_name};
+pub use event_schedule::{EventSchedule, ScenarioEvent};
+pub use measured_adoption::MeasuredAdoption;
+pub use run_metadata::RunMetadata;
+pub use sweep::SweepDimension;
\ No newline at end of file
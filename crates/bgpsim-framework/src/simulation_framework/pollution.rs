@@ -0,0 +1,90 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::simulation_engine::{Announcement, EngineObserver, Prefix};
+
+/// Polluted AS counts a `PollutionObserver` shares while the engine owns it
+#[derive(Debug, Clone, Default)]
+pub struct PollutionCounts(Arc<Mutex<PollutionState>>);
+
+#[derive(Debug, Default)]
+struct PollutionState {
+    /// Count when each round started, so after the round before it
+    at_round_start: Vec<usize>,
+    current: usize,
+}
+
+impl PollutionCounts {
+    /// Number of polluted ASes after each round, the last being the current count
+    pub fn per_round(&self) -> Vec<usize> {
+        let state = self.0.lock().unwrap();
+        state.at_round_start.iter().skip(1).copied().chain(std::iter::once(state.current)).collect()
+    }
+
+    pub fn current(&self) -> usize {
+        self.0.lock().unwrap().current
+    }
+}
+
+/// Counts the ASes whose best route towards `dest_ip_addr`, by longest prefix match, leads to an
+/// attacker, round by round, so hijack propagation can be plotted rather than just its end state
+///
+/// Register with `SimulationEngine::add_observer` before running and read the counts through
+/// `counts`. Attackers themselves never count
+pub struct PollutionObserver {
+    attacker_asns: HashSet<ASN>,
+    dest_ip_addr: IpAddr,
+    /// Whether each AS's route leads to an attacker, by prefix length, for prefixes covering the address
+    routes: HashMap<ASN, BTreeMap<u8, bool>>,
+    polluted: HashSet<ASN>,
+    counts: PollutionCounts,
+}
+
+impl PollutionObserver {
+    pub fn new(attacker_asns: HashSet<ASN>, dest_ip_addr: IpAddr) -> Self {
+        PollutionObserver {
+            attacker_asns,
+            dest_ip_addr,
+            routes: HashMap::new(),
+            polluted: HashSet::new(),
+            counts: PollutionCounts::default(),
+        }
+    }
+
+    /// Handle to the counts, which stays readable after the observer is handed to the engine
+    pub fn counts(&self) -> PollutionCounts {
+        self.counts.clone()
+    }
+}
+
+impl EngineObserver for PollutionObserver {
+    fn on_round_start(&mut self, _round: u32) {
+        self.counts.0.lock().unwrap().at_round_start.push(self.polluted.len());
+    }
+
+    fn on_rib_change(&mut self, asn: ASN, prefix: &Prefix, _old: Option<&Announcement>, new: Option<&Announcement>) {
+        if !prefix.contains(self.dest_ip_addr) || self.attacker_asns.contains(&asn) {
+            return;
+        }
+        let routes = self.routes.entry(asn).or_default();
+        match new.filter(|ann| !ann.withdraw) {
+            Some(ann) => {
+                let via_attacker = ann.as_path.iter().any(|hop| self.attacker_asns.contains(hop));
+                routes.insert(prefix.prefix(), via_attacker);
+            }
+            None => {
+                routes.remove(&prefix.prefix());
+            }
+        }
+
+        let is_polluted = routes.last_key_value().is_some_and(|(_, &via_attacker)| via_attacker);
+        if is_polluted {
+            self.polluted.insert(asn);
+        } else {
+            self.polluted.remove(&asn);
+        }
+        self.counts.0.lock().unwrap().current = self.polluted.len();
+    }
+}
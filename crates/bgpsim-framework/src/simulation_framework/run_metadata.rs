@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bgpsim_core::simulation_engine::TieBreaker;
+
+use super::scenario_config::config_field;
+use super::simulation::Simulation;
+
+/// What produced a simulation's results, written to its output directory so runs can be audited and reproduced
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunMetadata {
+    pub crate_version: String,
+    /// Commit of the source checkout the binary was built from, if it is still around
+    pub git_hash: Option<String>,
+    pub caida_snapshot_date: Option<String>,
+    /// The simulation's settings, with each scenario config as `ScenarioConfig::to_json` writes it
+    pub config: serde_json::Value,
+    /// Seeds by what they seed, e.g. `victim_sampling` or `<label> tie_breaker`
+    pub seeds: BTreeMap<String, u64>,
+    /// Seconds since the Unix epoch
+    pub started_at: u64,
+    /// Unset until the run finishes
+    pub wall_clock_seconds: Option<f64>,
+    pub hostname: Option<String>,
+    pub os: String,
+    pub arch: String,
+    pub num_cpus: usize,
+}
+
+impl RunMetadata {
+    pub const FILE_NAME: &'static str = "run_metadata.json";
+
+    /// Metadata of `simulation` starting now
+    pub fn collect(simulation: &Simulation) -> Self {
+        let mut seeds = BTreeMap::new();
+        if let Some(sampling) = &simulation.victim_sampling {
+            seeds.insert("victim_sampling".to_string(), sampling.seed);
+        }
+        for scenario_config in &simulation.scenario_configs {
+            if let TieBreaker::SeededRandom(seed) = scenario_config.tie_breaker {
+                seeds.insert(format!("{} tie_breaker", scenario_config.label), seed);
+            }
+        }
+
+        let config = serde_json::json!({
+            "percent_ases_randomly_adopting": simulation.percent_ases_randomly_adopting,
+            "num_trials": simulation.num_trials,
+            "sweep_dimensions": simulation.sweep_dimensions.iter().map(|dimension| dimension.to_json()).collect::<Vec<_>>(),
+            "scenario_configs": simulation.scenario_configs.iter().map(|config| config.to_json()).collect::<Vec<_>>(),
+            "victim_sampling": simulation.victim_sampling.as_ref().map(|sampling| serde_json::json!({
+                "num_victims": sampling.num_victims,
+                "victim_group": sampling.victim_group.to_string(),
+                "attacker_placement": sampling.attacker_placement.to_string(),
+                "trials_per_victim": sampling.trials_per_victim,
+            })),
+            "storage_mode": simulation.storage_mode.map(|storage_mode| format!("{:?}", storage_mode)),
+            "paired_adoption": simulation.adopter_draws.is_some(),
+            "adopter_draws_file": simulation.adopter_draws_file,
+            "num_ases": simulation.as_graph.len(),
+        });
+
+        RunMetadata {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_hash: git_hash(),
+            caida_snapshot_date: simulation.caida_snapshot_date.clone(),
+            config,
+            seeds,
+            started_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()),
+            wall_clock_seconds: None,
+            hostname: hostname(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            num_cpus: num_cpus::get(),
+        }
+    }
+
+    pub fn finish(&mut self, duration: Duration) {
+        self.wall_clock_seconds = Some(duration.as_secs_f64());
+    }
+
+    pub fn path(output_dir: &Path) -> PathBuf {
+        output_dir.join(Self::FILE_NAME)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "crate_version": self.crate_version,
+            "git_hash": self.git_hash,
+            "caida_snapshot_date": self.caida_snapshot_date,
+            "config": self.config,
+            "seeds": self.seeds,
+            "started_at": self.started_at,
+            "wall_clock_seconds": self.wall_clock_seconds,
+            "host": {
+                "hostname": self.hostname,
+                "os": self.os,
+                "arch": self.arch,
+                "num_cpus": self.num_cpus,
+            },
+        })
+    }
+
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        let missing = |key: &str| format!("Run metadata is missing {}", key);
+        let host = &value["host"];
+        Ok(RunMetadata {
+            crate_version: config_field(value, "crate_version")?.ok_or_else(|| missing("crate_version"))?,
+            git_hash: config_field(value, "git_hash")?,
+            caida_snapshot_date: config_field(value, "caida_snapshot_date")?,
+            config: value.get("config").cloned().unwrap_or_default(),
+            seeds: config_field(value, "seeds")?.unwrap_or_default(),
+            started_at: config_field(value, "started_at")?.ok_or_else(|| missing("started_at"))?,
+            wall_clock_seconds: config_field(value, "wall_clock_seconds")?,
+            hostname: config_field(host, "hostname")?,
+            os: config_field(host, "os")?.ok_or_else(|| missing("os"))?,
+            arch: config_field(host, "arch")?.ok_or_else(|| missing("arch"))?,
+            num_cpus: config_field(host, "num_cpus")?.ok_or_else(|| missing("num_cpus"))?,
+        })
+    }
+
+    pub fn save_to_file(&self, output_dir: &Path) -> std::io::Result<()> {
+        fs::write(Self::path(output_dir), serde_json::to_string_pretty(&self.to_json())?)
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let value: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+        Self::from_json(&value)
+    }
+}
+
+/// HEAD of the checkout this crate was built from, if git and the checkout are available
+fn git_hash() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .ok()?;
+    let hash = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !hash.is_empty()).then_some(hash)
+}
+
+fn hostname() -> Option<String> {
+    std::env::var("HOSTNAME").ok()
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+}
@@ -0,0 +1,486 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::aspa_validator::AspaValidator;
+use bgpsim_core::simulation_engine::{ExportRestrictions, SimulationEngine};
+use bgpsim_core::route_validator::{ROA, RouteValidator};
+use bgpsim_core::shared::{Outcomes, ROAValidity, Settings};
+use bgpsim_core::simulation_engine::{Announcement, Prefix};
+
+use super::event_schedule::EventSchedule;
+use super::scenario_config::{ASNCandidates, ScenarioConfig};
+
+/// Closure evaluated against the engine after a trial has run
+pub type MetricFn = Box<dyn Fn(&SimulationEngine) -> f64 + Send + Sync>;
+
+/// Named metrics a scenario wants recorded after each trial
+#[derive(Default)]
+pub struct CustomMetrics {
+    metrics: Vec<(String, MetricFn)>,
+}
+
+impl CustomMetrics {
+    pub fn new() -> Self {
+        CustomMetrics {
+            metrics: Vec::new(),
+        }
+    }
+    
+    /// Register a metric under `name`; registering the same name again replaces it
+    pub fn register<F>(&mut self, name: String, metric: F)
+    where
+        F: Fn(&SimulationEngine) -> f64 + Send + Sync + 'static,
+    {
+        self.metrics.retain(|(existing, _)| *existing != name);
+        self.metrics.push((name, Box::new(metric)));
+    }
+    
+    /// Evaluate every registered metric against the engine
+    pub fn evaluate(&self, engine: &SimulationEngine) -> Vec<(String, f64)> {
+        self.metrics.iter()
+            .map(|(name, metric)| (name.clone(), metric(engine)))
+            .collect()
+    }
+    
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.metrics.iter().map(|(name, _)| name.as_str())
+    }
+    
+    pub fn len(&self) -> usize {
+        self.metrics.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.metrics.is_empty()
+    }
+}
+
+/// Base trait for all scenarios
+pub trait ScenarioTrait: Send + Sync {
+    /// Minimum number of propagation rounds for this scenario
+    fn min_propagation_rounds(&self) -> u32 {
+        1
+    }
+    
+    /// Get the name of this scenario type
+    fn name(&self) -> &str;
+    
+    /// Get attacker ASNs for this scenario
+    fn get_attacker_asns(&self, as_graph: &ASGraph) -> HashSet<ASN>;
+    
+    /// Get legitimate origin ASNs
+    fn get_legitimate_origin_asns(&self, as_graph: &ASGraph) -> HashSet<ASN>;
+    
+    /// Get announcements to seed the simulation with
+    fn get_seed_asn_ann_dict(&self, as_graph: &ASGraph) -> HashMap<ASN, Vec<Announcement>>;
+    
+    /// Get ROAs for the simulation
+    fn get_roas(&self, as_graph: &ASGraph) -> Vec<ROA>;
+    
+    /// Get the neighbors each origin exports its seeded prefixes to (empty means export everywhere)
+    fn get_seed_export_restrictions(&self, _as_graph: &ASGraph) -> ExportRestrictions {
+        ExportRestrictions::new()
+    }
+    
+    /// Get destination IP address for testing
+    fn get_dest_ip_addr(&self) -> IpAddr {
+        // Default implementation returns a test IP
+        "1.2.3.4".parse().unwrap()
+    }
+    
+    /// Events the scenario itself schedules, e.g. the victim reacting to a hijack
+    /// Run sites merge them with the config's `event_schedule`, see `EventSchedule::merged`
+    fn get_events(&self, _as_graph: &ASGraph) -> EventSchedule {
+        EventSchedule::new()
+    }
+    
+    /// Setup the scenario in the engine
+    fn setup_engine(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator);
+    
+    /// Check if the scenario outcome is successful
+    fn is_successful(&self, engine: &SimulationEngine) -> bool;
+    
+    /// Scenario-specific results judged after the run, with the ROAs the scenario set up
+    /// `EngineRunner` stores them next to the outcomes; None if the scenario has nothing to add
+    fn analyze(&self, _engine: &SimulationEngine, _route_validator: &RouteValidator) -> Option<serde_json::Value> {
+        None
+    }
+    
+    /// Register scenario-specific metrics that are evaluated after each run
+    fn register_custom_metrics(&self, _metrics: &mut CustomMetrics) {}
+    
+    /// Get the registered custom metrics
+    fn custom_metrics(&self) -> CustomMetrics {
+        let mut metrics = CustomMetrics::new();
+        self.register_custom_metrics(&mut metrics);
+        metrics
+    }
+}
+
+/// Follow each AS's local RIB towards `dest_ip_addr`, using the longest matching prefix at every hop
+///
+/// Traffic ends at an attacker (`AttackerSuccess`), at a legitimate origin (`VictimSuccess`),
+/// at an AS that blackholes it (`Blackholed`), or in a forwarding loop (`DisconnectedNotAsSomehow`).
+/// ASes without a route are `DisconnectedOrigin`, and traffic forwarded to one is disconnected from
+/// whoever the route it followed there leads to, see `disconnected_outcome`
+pub fn data_plane_outcomes(
+    engine: &SimulationEngine,
+    attacker_asns: &HashSet<ASN>,
+    legitimate_origin_asns: &HashSet<ASN>,
+    dest_ip_addr: IpAddr,
+) -> HashMap<ASN, Outcomes> {
+    let mut outcomes: HashMap<ASN, Outcomes> = HashMap::new();
+
+    for start_asn in engine.as_graph.asns() {
+        // Walk until reaching an AS whose outcome is known, then assign it to the whole walk
+        let mut walk = Vec::new();
+        let mut on_walk = HashSet::new();
+        let mut asn = start_asn;
+        // Route the previous hop forwarded the traffic to `asn` along
+        let mut followed_route: Option<&Announcement> = None;
+
+        let outcome = loop {
+            if let Some(&outcome) = outcomes.get(&asn) {
+                break match (outcome, followed_route) {
+                    (Outcomes::DisconnectedOrigin, Some(route)) => disconnected_outcome(route, attacker_asns, legitimate_origin_asns),
+                    _ => outcome,
+                };
+            }
+            if !on_walk.insert(asn) {
+                break Outcomes::DisconnectedNotAsSomehow;
+            }
+            walk.push(asn);
+
+            if attacker_asns.contains(&asn) {
+                break Outcomes::AttackerSuccess;
+            }
+            if legitimate_origin_asns.contains(&asn) {
+                break Outcomes::VictimSuccess;
+            }
+            let route = engine.policy_store.get(&asn).and_then(|policy| {
+                policy.local_rib.values()
+                    .filter(|ann| !ann.withdraw && ann.prefix.contains(dest_ip_addr))
+                    .max_by_key(|ann| ann.prefix.prefix())
+            });
+            match (route, followed_route) {
+                (None, None) => break Outcomes::DisconnectedOrigin,
+                (None, Some(followed_route)) => {
+                    outcomes.insert(walk.pop().unwrap(), Outcomes::DisconnectedOrigin);
+                    break disconnected_outcome(followed_route, attacker_asns, legitimate_origin_asns);
+                }
+                (Some(route), _) if route.is_blackholed_at(asn) => break Outcomes::Blackholed,
+                (Some(route), _) => {
+                    followed_route = Some(route);
+                    asn = route.next_hop_asn;
+                }
+            }
+        };
+
+        for asn in walk {
+            outcomes.insert(asn, outcome);
+        }
+    }
+
+    outcomes
+}
+
+/// Outcome of traffic dropped for lack of a route after following `route` towards the origin
+///
+/// `DisconnectedAttacker` if the route led to an attacker, `DisconnectedVictim` if it led to a
+/// legitimate origin and `DisconnectedNotAsSomehow` otherwise
+pub fn disconnected_outcome(route: &Announcement, attacker_asns: &HashSet<ASN>, legitimate_origin_asns: &HashSet<ASN>) -> Outcomes {
+    if route.as_path.iter().any(|hop| attacker_asns.contains(hop)) || attacker_asns.contains(&route.origin()) {
+        Outcomes::DisconnectedAttacker
+    } else if legitimate_origin_asns.contains(&route.origin()) {
+        Outcomes::DisconnectedVictim
+    } else {
+        Outcomes::DisconnectedNotAsSomehow
+    }
+}
+
+/// Whose route each AS selected for `dest_ip_addr`, judged by its most specific matching route
+///
+/// Routes through an attacker are the attacker's even if they claim a legitimate origin.
+/// Unlike `data_plane_outcomes` this ignores what the ASes along the path do with the traffic,
+/// so the two differ when an AS on the way blackholes it or prefers a more specific hijack.
+/// ASes without a route get `DisconnectedOrigin`; attackers and legitimate origins get their own outcome
+pub fn control_plane_outcomes(
+    engine: &SimulationEngine,
+    attacker_asns: &HashSet<ASN>,
+    legitimate_origin_asns: &HashSet<ASN>,
+    dest_ip_addr: IpAddr,
+) -> HashMap<ASN, Outcomes> {
+    engine.as_graph.asns()
+        .map(|asn| {
+            if attacker_asns.contains(&asn) {
+                return (asn, Outcomes::AttackerSuccess);
+            }
+            if legitimate_origin_asns.contains(&asn) {
+                return (asn, Outcomes::VictimSuccess);
+            }
+            let route = engine.policy_store.get(&asn).and_then(|policy| {
+                policy.local_rib.values()
+                    .filter(|ann| !ann.withdraw && ann.prefix.contains(dest_ip_addr))
+                    .max_by_key(|ann| ann.prefix.prefix())
+            });
+            let outcome = match route {
+                None => Outcomes::DisconnectedOrigin,
+                Some(route) if route.as_path.iter().any(|hop| attacker_asns.contains(hop)) => Outcomes::AttackerSuccess,
+                Some(route) if legitimate_origin_asns.contains(&route.origin()) => Outcomes::VictimSuccess,
+                Some(_) => Outcomes::DisconnectedNotAsSomehow,
+            };
+            (asn, outcome)
+        })
+        .collect()
+}
+
+/// Share the scenario's ROAs with the engine's policies, precomputing the outcomes of the seeded routes
+///
+/// The outcomes are frozen in `route_validator` itself, keyed by the engine's prefix ids, so ROA events
+/// recompute them before sharing it again
+pub fn share_route_validator(engine: &mut SimulationEngine, route_validator: &mut RouteValidator, seeds: &[(ASN, Announcement)]) {
+    let prefix_table = engine.policy_store.prefix_table.clone();
+    route_validator.freeze(prefix_table, seeds.iter().map(|(_, ann)| (ann.prefix, ann.origin())));
+    engine.set_route_validator(Arc::new(route_validator.clone()));
+}
+
+/// Replace the ROAs `scenario` set up with the ones `config` generates for this run, if it generates any
+///
+/// Called after `setup_engine`, so every scenario gets the same ROA strategies without implementing them
+pub fn preprocess_roas(
+    config: &ScenarioConfig,
+    scenario: &dyn ScenarioTrait,
+    engine: &mut SimulationEngine,
+    route_validator: &mut RouteValidator,
+) {
+    if !config.preprocesses_roas() {
+        return;
+    }
+    let seed_dict = scenario.get_seed_asn_ann_dict(engine.as_graph);
+    let legitimate_origin_asns = scenario.get_legitimate_origin_asns(engine.as_graph);
+    route_validator.replace_roas(config.roas_for_run(route_validator.roas(), &seed_dict, &legitimate_origin_asns));
+    
+    let seeds: Vec<(ASN, Announcement)> = seed_dict.into_iter()
+        .flat_map(|(asn, anns)| anns.into_iter().map(move |ann| (asn, ann)))
+        .collect();
+    share_route_validator(engine, route_validator, &seeds);
+}
+
+/// How many ASes route `prefix` with each ROA validity, and how many have no route for it
+/// e.g. because ROV dropped the only announcements they received
+pub fn route_validity_counts(engine: &SimulationEngine, route_validator: &RouteValidator, prefix: &Prefix) -> serde_json::Value {
+    let mut by_validity: BTreeMap<String, usize> = BTreeMap::new();
+    let mut no_route = 0;
+    for (_, policy) in engine.policy_store.iter() {
+        match policy.local_rib.get(prefix) {
+            Some(ann) => {
+                let (validity, _) = route_validator.get_roa_outcome(prefix, ann.origin());
+                *by_validity.entry(validity.to_string()).or_insert(0) += 1;
+            }
+            None => no_route += 1,
+        }
+    }
+    serde_json::json!({
+        "prefix": prefix.to_string(),
+        "routes_by_validity": by_validity,
+        "no_route": no_route,
+    })
+}
+
+/// How many of the prefixes the legitimate origins announce in `seeds` a forged-origin subprefix hijack
+/// could take, as (vulnerable, announced)
+///
+/// A prefix is vulnerable when a ROA makes its next longer subprefix valid from the same origin,
+/// i.e. its max length is longer than announced, so ROV can't stop an attacker forging the origin
+pub fn max_length_vulnerable_prefixes(
+    route_validator: &RouteValidator,
+    seeds: &HashMap<ASN, Vec<Announcement>>,
+    legitimate_origin_asns: &HashSet<ASN>,
+) -> (usize, usize) {
+    let announced: HashSet<(Prefix, ASN)> = seeds.iter()
+        .filter(|(asn, _)| legitimate_origin_asns.contains(asn))
+        .flat_map(|(&asn, anns)| anns.iter().filter(|ann| !ann.withdraw).map(move |ann| (ann.prefix, asn)))
+        .collect();
+    let num_vulnerable = announced.iter()
+        .filter(|(prefix, origin)| {
+            Prefix::new(prefix.network(), prefix.prefix() + 1)
+                .is_ok_and(|subprefix| route_validator.get_roa_outcome(&subprefix, *origin).0 == ROAValidity::Valid)
+        })
+        .count();
+    (num_vulnerable, announced.len())
+}
+
+/// One ROA per legitimate origin, so every site of an anycast (MOAS) prefix is ROV valid
+/// Sorted by origin so the ROA order doesn't depend on hash order
+pub fn origin_roas(prefix: Prefix, origin_asns: &HashSet<ASN>, max_length: Option<u8>) -> Vec<ROA> {
+    let mut origin_asns: Vec<ASN> = origin_asns.iter().copied().collect();
+    origin_asns.sort_unstable();
+    origin_asns.into_iter()
+        .map(|asn| ROA::new(prefix, asn, max_length))
+        .collect()
+}
+
+/// Base scenario struct that holds common data
+pub struct Scenario {
+    pub config: ScenarioConfig,
+    pub percent_ases_randomly_adopting: f64,
+    pub attacker_asns: HashSet<ASN>,
+    pub legitimate_origin_asns: HashSet<ASN>,
+    pub adopting_asns: HashSet<ASN>,
+    /// ASes registering ASPA objects when drawn separately from validators, see `ScenarioConfig::percent_aspa_objects`
+    pub aspa_object_asns: Option<HashSet<ASN>>,
+    pub seed_asn_ann_dict: HashMap<ASN, Vec<Announcement>>,
+    pub roas: Vec<ROA>,
+    pub dest_ip_addr: IpAddr,
+}
+
+impl Scenario {
+    pub fn new(
+        config: ScenarioConfig,
+        as_graph: &ASGraph,
+        percent_ases_randomly_adopting: f64,
+    ) -> Self {
+        // Get attacker ASNs
+        let attacker_asns = if let Some(override_asns) = &config.override_attacker_asns {
+            override_asns.clone()
+        } else {
+            Self::default_attacker_asns(as_graph, config.attacker_candidates.as_ref(), config.num_attackers)
+        };
+        
+        // Get legitimate origin ASNs
+        let legitimate_origin_asns = if let Some(override_asns) = &config.override_legitimate_origin_asns {
+            override_asns.clone()
+        } else {
+            Self::default_legitimate_origin_asns(
+                as_graph,
+                config.legitimate_origin_candidates.as_ref(),
+                config.num_legitimate_origins,
+                &attacker_asns,
+            )
+        };
+        
+        // Get adopting ASNs from measured probabilities if given, otherwise based on percentage
+        let adopting_asns = if let Some(override_asns) = &config.override_adopting_asns {
+            override_asns.clone()
+        } else if let Some(adoption) = &config.measured_adoption {
+            adoption.draw(as_graph)
+        } else {
+            Self::get_random_adopting_asns(as_graph, percent_ases_randomly_adopting)
+        };
+        
+        let aspa_object_asns = config.percent_aspa_objects
+            .map(|percent| Self::get_random_adopting_asns(as_graph, percent));
+        
+        // Initialize with empty seed dict and ROAs - these will be populated by specific scenarios
+        let seed_asn_ann_dict = HashMap::new();
+        let roas = Vec::new();
+        
+        Scenario {
+            config,
+            percent_ases_randomly_adopting,
+            attacker_asns,
+            legitimate_origin_asns,
+            adopting_asns,
+            aspa_object_asns,
+            seed_asn_ann_dict,
+            roas,
+            dest_ip_addr: "1.2.3.4".parse().unwrap(),
+        }
+    }
+    
+    /// Switch adopting ASes to the settings the config assigns them
+    /// Returns how many ASes ended up running each setting
+    pub fn apply_adoption_settings(&self, engine: &mut SimulationEngine) -> HashMap<Settings, usize> {
+        let settings = self.config.adoption_settings(engine.as_graph, &self.adopting_asns);
+        let mut counts = HashMap::new();
+        
+        for (asn, policy) in engine.policy_store.iter_mut() {
+            let setting = settings.get(asn).copied();
+            if let Some(setting) = setting {
+                policy.settings = setting;
+                policy.extension = bgpsim_core::simulation_engine::policy::create_policy_extension(setting);
+            }
+            *counts.entry(policy.settings).or_insert(0) += 1;
+        }
+        
+        // ASPA adopters publish their providers, so the others can validate against them,
+        // unless who registers objects is drawn separately
+        let aspa_validator = match &self.aspa_object_asns {
+            Some(asns) => AspaValidator::from_as_graph(engine.as_graph, asns.iter().copied()),
+            None => {
+                let aspa_asns = settings.iter().filter(|(_, &setting)| setting == Settings::Aspa).map(|(&asn, _)| asn);
+                AspaValidator::from_as_graph(engine.as_graph, aspa_asns)
+            }
+        };
+        engine.set_aspa_validator(Arc::new(aspa_validator));
+        if let Some(route_objects) = &self.config.irr_route_objects {
+            engine.set_irr_validator(Arc::clone(route_objects));
+        }
+        
+        counts
+    }
+    
+    /// Candidates from the config, or by default every stub AS that isn't an IXP
+    fn candidate_asns(as_graph: &ASGraph, candidates: Option<&ASNCandidates>) -> Vec<ASN> {
+        match candidates {
+            Some(candidates) => candidates.asns(as_graph),
+            None => as_graph.iter()
+                .filter(|as_obj| as_obj.customers().is_empty() && !as_obj.ixp)
+                .map(|as_obj| as_obj.asn)
+                .collect(),
+        }
+    }
+    
+    fn default_attacker_asns(as_graph: &ASGraph, candidates: Option<&ASNCandidates>, num_attackers: usize) -> HashSet<ASN> {
+        // Pick random candidates as attackers, several for a coordinated attack
+        let mut stubs = Self::candidate_asns(as_graph, candidates);
+        let mut attackers = HashSet::new();
+        while attackers.len() < num_attackers && !stubs.is_empty() {
+            let idx = rand::random::<usize>() % stubs.len();
+            attackers.insert(stubs.swap_remove(idx));
+        }
+        attackers
+    }
+    
+    fn default_legitimate_origin_asns(
+        as_graph: &ASGraph,
+        candidates: Option<&ASNCandidates>,
+        num_origins: usize,
+        attacker_asns: &HashSet<ASN>,
+    ) -> HashSet<ASN> {
+        // Pick random candidates other than the attackers, several for an anycast prefix
+        let mut stubs = Self::candidate_asns(as_graph, candidates);
+        stubs.retain(|asn| !attacker_asns.contains(asn));
+        
+        let mut origins = HashSet::new();
+        while origins.len() < num_origins && !stubs.is_empty() {
+            let idx = rand::random::<usize>() % stubs.len();
+            origins.insert(stubs.swap_remove(idx));
+        }
+        origins
+    }
+    
+    pub(crate) fn get_random_adopting_asns(as_graph: &ASGraph, percent: f64) -> HashSet<ASN> {
+        let all_asns: Vec<ASN> = as_graph.asns().collect();
+        let num_to_adopt = ((all_asns.len() as f64) * (percent / 100.0)) as usize;
+        
+        let mut adopting = HashSet::new();
+        let mut remaining = all_asns;
+        
+        for _ in 0..num_to_adopt.min(remaining.len()) {
+            if remaining.is_empty() {
+                break;
+            }
+            let idx = rand::random::<usize>() % remaining.len();
+            let asn = remaining.swap_remove(idx);
+            adopting.insert(asn);
+        }
+        
+        adopting
+    }
+}
+
+// External crate for random number generation
+extern crate rand;
\ No newline at end of file
@@ -0,0 +1,825 @@
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use ipnetwork::IpNetwork;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::de::DeserializeOwned;
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::irr_validator::IrrValidator;
+use bgpsim_core::route_validator::ROA;
+use bgpsim_core::shared::{ASNGroups, Settings};
+use bgpsim_core::simulation_engine::{Announcement, NextHopVerification, PolicyStorageMode, TieBreaker};
+use bgpsim_core::Error;
+
+use super::event_schedule::{EventSchedule, ScenarioEvent};
+use super::measured_adoption::MeasuredAdoption;
+
+/// Standard attacker positions relative to the victim, as used in the literature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttackerPlacement {
+    /// Any stub AS other than the victim
+    RandomStub,
+    /// A peer of one of the victim's providers
+    PeerOfVictimProvider,
+    /// A direct customer of a tier-1 AS
+    Tier1Customer,
+    /// An AS inside the victim's customer cone
+    VictimCustomerCone,
+}
+
+impl AttackerPlacement {
+    pub const ALL: [AttackerPlacement; 4] = [
+        AttackerPlacement::RandomStub,
+        AttackerPlacement::PeerOfVictimProvider,
+        AttackerPlacement::Tier1Customer,
+        AttackerPlacement::VictimCustomerCone,
+    ];
+
+    /// Get every ASN that satisfies this placement for the given victim, sorted
+    pub fn candidate_asns(&self, as_graph: &ASGraph, victim_asn: ASN) -> Vec<ASN> {
+        let mut candidates: HashSet<ASN> = match self {
+            AttackerPlacement::RandomStub => as_graph.iter()
+                .filter(|as_obj| as_obj.is_stub() && !as_obj.ixp)
+                .map(|as_obj| as_obj.asn)
+                .collect(),
+            AttackerPlacement::PeerOfVictimProvider => {
+                let victim_providers: HashSet<ASN> = as_graph.get(&victim_asn)
+                    .map(|victim| victim.providers().iter().map(|p| p.asn).collect())
+                    .unwrap_or_default();
+                victim_providers.iter()
+                    .filter_map(|asn| as_graph.get(asn))
+                    .flat_map(|provider| provider.peers().iter().map(|peer| peer.asn))
+                    .filter(|asn| !victim_providers.contains(asn))
+                    .collect()
+            }
+            AttackerPlacement::Tier1Customer => as_graph.iter()
+                .filter(|as_obj| as_obj.tier_1)
+                .flat_map(|tier_1| tier_1.customers().iter().map(|c| c.asn))
+                .collect(),
+            AttackerPlacement::VictimCustomerCone => as_graph.customer_cone_asns(&victim_asn),
+        };
+        candidates.remove(&victim_asn);
+
+        let mut candidates: Vec<ASN> = candidates.into_iter().collect();
+        candidates.sort_unstable();
+        candidates
+    }
+
+    /// Pick a random ASN that satisfies this placement
+    pub fn select(&self, as_graph: &ASGraph, victim_asn: ASN) -> Option<ASN> {
+        let candidates = self.candidate_asns(as_graph, victim_asn);
+        if candidates.is_empty() {
+            return None;
+        }
+        let idx = rand::random::<usize>() % candidates.len();
+        Some(candidates[idx])
+    }
+
+    /// Pick up to `num_attackers` distinct random ASNs that satisfy this placement, for coordinated attacks
+    pub fn select_many(&self, as_graph: &ASGraph, victim_asn: ASN, num_attackers: usize) -> HashSet<ASN> {
+        let mut candidates = self.candidate_asns(as_graph, victim_asn);
+        let mut attackers = HashSet::new();
+        while attackers.len() < num_attackers && !candidates.is_empty() {
+            let idx = rand::random::<usize>() % candidates.len();
+            attackers.insert(candidates.swap_remove(idx));
+        }
+        attackers
+    }
+}
+
+impl std::fmt::Display for AttackerPlacement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            AttackerPlacement::RandomStub => "RANDOM_STUB",
+            AttackerPlacement::PeerOfVictimProvider => "PEER_OF_VICTIM_PROVIDER",
+            AttackerPlacement::Tier1Customer => "TIER_1_CUSTOMER",
+            AttackerPlacement::VictimCustomerCone => "VICTIM_CUSTOMER_CONE",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Study mode that runs each scenario once per sampled victim rather than with a single random victim
+///
+/// Single-victim results are noisy, so standard studies average protection over many victims
+#[derive(Debug, Clone, PartialEq)]
+pub struct VictimSampling {
+    /// Number of victims to sample
+    pub num_victims: usize,
+    
+    /// Group victims are sampled from
+    pub victim_group: ASNGroups,
+    
+    /// Where to place the attacker relative to each victim when the config doesn't fix one
+    pub attacker_placement: AttackerPlacement,
+    
+    /// Trials to run per victim, each with freshly drawn adopting ASes (and attacker, unless fixed)
+    pub trials_per_victim: usize,
+    
+    /// Seed for victim sampling, so every adoption percentage sees the same victims
+    pub seed: u64,
+}
+
+impl VictimSampling {
+    pub fn new(num_victims: usize) -> Self {
+        VictimSampling {
+            num_victims,
+            victim_group: ASNGroups::Stubs,
+            attacker_placement: AttackerPlacement::RandomStub,
+            trials_per_victim: 1,
+            seed: 0,
+        }
+    }
+    
+    pub fn with_victim_group(mut self, group: ASNGroups) -> Self {
+        self.victim_group = group;
+        self
+    }
+    
+    pub fn with_attacker_placement(mut self, placement: AttackerPlacement) -> Self {
+        self.attacker_placement = placement;
+        self
+    }
+    
+    pub fn with_trials_per_victim(mut self, trials: usize) -> Self {
+        self.trials_per_victim = trials;
+        self
+    }
+    
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+    
+    /// Sample victims from the group, sorted
+    /// Victims are excluded when they are fixed attackers; the whole group is used if it is smaller than `num_victims`
+    pub fn sample_victims(&self, as_graph: &ASGraph, attacker_asns: Option<&HashSet<ASN>>) -> Vec<ASN> {
+        let mut candidates: Vec<ASN> = self.victim_group.asns(as_graph).into_iter()
+            .filter(|asn| attacker_asns.is_none_or(|attackers| !attackers.contains(asn)))
+            .collect();
+        candidates.sort_unstable();
+        
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut victims: Vec<ASN> = candidates.choose_multiple(&mut rng, self.num_victims).copied().collect();
+        victims.sort_unstable();
+        victims
+    }
+}
+
+/// ASes a scenario randomly draws its attackers or legitimate origins from each trial
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ASNCandidates {
+    /// Every AS in the group, e.g. `ASNGroups::Transit` for transit victims
+    Group(ASNGroups),
+    /// These ASes, skipping any not in the graph
+    List(Vec<ASN>),
+}
+
+impl ASNCandidates {
+    /// The candidates in the graph, sorted so draws don't depend on hash order
+    pub fn asns(&self, as_graph: &ASGraph) -> Vec<ASN> {
+        let mut asns: Vec<ASN> = match self {
+            ASNCandidates::Group(group) => group.asns(as_graph).into_iter().collect(),
+            ASNCandidates::List(asns) => asns.iter().copied().filter(|asn| as_graph.get(asn).is_some()).collect(),
+        };
+        asns.sort_unstable();
+        asns.dedup();
+        asns
+    }
+}
+
+/// Adopters an `AdoptionRule` applies to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdopterSelector {
+    /// Adopters in the group
+    Group(ASNGroups),
+    /// This percentage of the adopters, drawn each trial
+    Percent(f64),
+}
+
+impl AdopterSelector {
+    /// The selected ASes among `adopting_asns`
+    pub fn select(&self, as_graph: &ASGraph, adopting_asns: &HashSet<ASN>) -> HashSet<ASN> {
+        match self {
+            AdopterSelector::Group(group) => group.asns(as_graph).intersection(adopting_asns).copied().collect(),
+            AdopterSelector::Percent(percent) => {
+                // Sorted so only the draws, not hash order, decide who is selected
+                let mut asns: Vec<ASN> = adopting_asns.iter().copied().collect();
+                asns.sort_unstable();
+                let num_selected = ((asns.len() as f64) * (percent / 100.0)) as usize;
+                asns.choose_multiple(&mut rand::thread_rng(), num_selected).copied().collect()
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for AdopterSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdopterSelector::Group(group) => write!(f, "{}", group),
+            AdopterSelector::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+/// Adopters matching `selector` run `setting`, e.g. tier-1 adopters run ASPA
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdoptionRule {
+    pub selector: AdopterSelector,
+    pub setting: Settings,
+}
+
+/// How the ROAs of a run are generated, so partial RPKI deployment doesn't need a custom scenario
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoaGeneration {
+    /// The scenario's own ROAs
+    #[default]
+    Scenario,
+    /// One ROA per prefix a legitimate origin announces, valid for exactly that prefix length
+    ExactMatch,
+    /// Like `ExactMatch`, but also covering subprefixes up to this length, e.g. 24 for a /16
+    MaxLength(u8),
+}
+
+impl RoaGeneration {
+    /// ROAs for every prefix the legitimate origins announce in `seeds`, sorted
+    pub fn roas(&self, seeds: &HashMap<ASN, Vec<Announcement>>, legitimate_origin_asns: &HashSet<ASN>) -> Vec<ROA> {
+        let mut roas: Vec<ROA> = seeds.iter()
+            .filter(|(asn, _)| legitimate_origin_asns.contains(asn))
+            .flat_map(|(&asn, anns)| anns.iter().filter(|ann| !ann.withdraw).map(move |ann| (ann.prefix, asn)))
+            .map(|(prefix, asn)| match self {
+                RoaGeneration::MaxLength(max_length) => ROA::new(prefix, asn, Some((*max_length).max(prefix.prefix()))),
+                _ => ROA::new(prefix, asn, None),
+            })
+            .collect();
+        roas.sort_by_key(|roa| (roa.origin, roa.prefix));
+        roas.dedup_by_key(|roa| (roa.origin, roa.prefix));
+        roas
+    }
+}
+
+impl std::fmt::Display for RoaGeneration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoaGeneration::Scenario => write!(f, "Scenario"),
+            RoaGeneration::ExactMatch => write!(f, "ExactMatch"),
+            RoaGeneration::MaxLength(max_length) => write!(f, "MaxLength:{}", max_length),
+        }
+    }
+}
+
+impl std::str::FromStr for RoaGeneration {
+    type Err = String;
+
+    /// Parses what `Display` writes, e.g. `MaxLength:24`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "Scenario" => Ok(RoaGeneration::Scenario),
+            None if s == "ExactMatch" => Ok(RoaGeneration::ExactMatch),
+            Some(("MaxLength", max_length)) => max_length.parse()
+                .map(RoaGeneration::MaxLength)
+                .map_err(|_| format!("Invalid ROA max length: {}", max_length)),
+            _ => Err(format!("Unknown ROA generation: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScenarioConfig {
+    /// Label for this scenario configuration
+    pub label: String,
+    
+    /// Name of the scenario class to use
+    pub scenario_name: String,
+    
+    /// Default adoption settings for ASes
+    pub default_adoption_settings: HashMap<Settings, bool>,
+    
+    /// Settings adopting ASes run based on their ASN group, taking precedence over the defaults
+    /// When an AS is in several groups, the group added last wins
+    pub group_adoption_settings: Vec<(ASNGroups, Settings)>,
+    
+    /// Ordered rules giving adopters different settings, applied after `group_adoption_settings`
+    /// When an AS matches several rules, the last one wins
+    pub adoption_rules: Vec<AdoptionRule>,
+    
+    /// Settings every AS in a group runs whether or not it was selected to adopt,
+    /// e.g. all ASes in a country deploying ROV. Applied after `group_adoption_settings`
+    pub group_settings: Vec<(ASNGroups, Settings)>,
+    
+    /// Override attacker ASNs (if None, will be randomly selected)
+    pub override_attacker_asns: Option<HashSet<ASN>>,
+    
+    /// Override legitimate origin ASNs (if None, will be randomly selected)
+    pub override_legitimate_origin_asns: Option<HashSet<ASN>>,
+    
+    /// Number of attackers to select when they aren't overridden
+    /// More than one is a coordinated attack, with every attacker announcing the hijack
+    pub num_attackers: usize,
+    
+    /// ASes randomly selected attackers come from (if None, any stub that isn't an IXP)
+    pub attacker_candidates: Option<ASNCandidates>,
+    
+    /// ASes randomly selected legitimate origins come from (if None, any stub that isn't an IXP)
+    pub legitimate_origin_candidates: Option<ASNCandidates>,
+    
+    /// Number of legitimate origins to select when they aren't overridden
+    /// More than one makes the victim prefix anycast, announced by every origin (MOAS)
+    pub num_legitimate_origins: usize,
+    
+    /// Override adopting ASNs (if None, will be randomly selected based on percentage)
+    pub override_adopting_asns: Option<HashSet<ASN>>,
+    
+    /// Percentage (0-100) of ASes that register ASPA objects, drawn each trial separately from the
+    /// ASes validating with ASPA (if None, exactly the ASPA adopters register)
+    pub percent_aspa_objects: Option<f64>,
+    
+    /// Measured per-AS adoption probabilities to draw adopters from each trial, instead of the percentage
+    pub measured_adoption: Option<Arc<MeasuredAdoption>>,
+    
+    /// IRR route objects that IRR filtering adopters check their customers' routes against
+    pub irr_route_objects: Option<Arc<IrrValidator>>,
+    
+    /// Settings for specific ASes, taking precedence over adoption and group settings
+    pub override_asn_settings: Option<HashMap<ASN, Settings>>,
+    
+    /// Override seed announcements (if None, scenario will generate them)
+    pub override_seed_asn_ann_dict: Option<HashMap<ASN, Vec<Announcement>>>,
+    
+    /// Override ROAs (if None, scenario will generate them)
+    pub override_roas: Option<Vec<ROA>>,
+    
+    /// Override destination IP address for testing
+    pub override_dest_ip_addr: Option<IpAddr>,
+    
+    /// How ROAs are generated, see `RoaGeneration`
+    pub roa_generation: RoaGeneration,
+    
+    /// Chance (0-100) that each legitimate origin has no ROAs, drawn every run
+    pub percent_victims_without_roa: f64,
+    
+    /// Chance (0-100) that each legitimate origin's ROAs have a max length up to the full address
+    /// length, drawn every run, so forged-origin subprefix hijacks of its prefixes are ROV valid
+    pub percent_loose_max_length: f64,
+    
+    /// Routing tables the engine keeps; `LocalRibOnly` suffices when only local RIBs are inspected
+    pub storage_mode: PolicyStorageMode,
+
+    /// How ASes choose between equally good routes
+    pub tie_breaker: TieBreaker,
+
+    /// What the engine does with announcements from ASes that aren't neighbors of the receiver, e.g. from bad seed data
+    pub next_hop_verification: NextHopVerification,
+
+    /// Announcements, withdrawals and ROA changes between propagation rounds
+    pub event_schedule: EventSchedule,
+
+    /// Values of the sweep dimensions this config was expanded with, see `SweepDimension`
+    pub sweep_parameters: Vec<(String, serde_json::Value)>,
+}
+
+impl ScenarioConfig {
+    pub fn new(label: String, scenario_name: String) -> Self {
+        ScenarioConfig {
+            label,
+            scenario_name,
+            default_adoption_settings: HashMap::new(),
+            group_adoption_settings: Vec::new(),
+            adoption_rules: Vec::new(),
+            group_settings: Vec::new(),
+            override_attacker_asns: None,
+            override_legitimate_origin_asns: None,
+            num_attackers: 1,
+            attacker_candidates: None,
+            legitimate_origin_candidates: None,
+            num_legitimate_origins: 1,
+            override_adopting_asns: None,
+            percent_aspa_objects: None,
+            measured_adoption: None,
+            irr_route_objects: None,
+            override_asn_settings: None,
+            override_seed_asn_ann_dict: None,
+            override_roas: None,
+            override_dest_ip_addr: None,
+            roa_generation: RoaGeneration::Scenario,
+            percent_victims_without_roa: 0.0,
+            percent_loose_max_length: 0.0,
+            storage_mode: PolicyStorageMode::Full,
+            tie_breaker: TieBreaker::default(),
+            next_hop_verification: NextHopVerification::default(),
+            event_schedule: EventSchedule::new(),
+            sweep_parameters: Vec::new(),
+        }
+    }
+    
+    /// Create a config with the victim fixed and the attacker placed according to `placement`
+    pub fn new_with_attacker_placement(
+        label: String,
+        scenario_name: String,
+        placement: AttackerPlacement,
+        victim_asn: ASN,
+        as_graph: &ASGraph,
+    ) -> Result<Self, String> {
+        let attacker_asn = placement.select(as_graph, victim_asn).ok_or_else(|| {
+            format!("No AS satisfies attacker placement {} for victim {}", placement, victim_asn)
+        })?;
+
+        Ok(ScenarioConfig::new(label, scenario_name)
+            .with_attacker_asns(HashSet::from([attacker_asn]))
+            .with_legitimate_origin_asns(HashSet::from([victim_asn])))
+    }
+    
+    pub fn with_adoption_setting(mut self, setting: Settings, enabled: bool) -> Self {
+        self.default_adoption_settings.insert(setting, enabled);
+        self
+    }
+    
+    /// Have adopting ASes in `group` run `setting`, e.g. transit ASes run ASPA while stubs run ROV
+    pub fn with_group_adoption_setting(mut self, group: ASNGroups, setting: Settings) -> Self {
+        self.group_adoption_settings.retain(|(existing, _)| *existing != group);
+        self.group_adoption_settings.push((group, setting));
+        self
+    }
+    
+    /// Have adopters matching `selector` run `setting`, overriding the rules added before
+    pub fn with_adoption_rule(mut self, selector: AdopterSelector, setting: Settings) -> Self {
+        self.adoption_rules.push(AdoptionRule { selector, setting });
+        self
+    }
+    
+    /// Have every AS in `group` run `setting`, e.g. `ASNGroups::Country(*b"NL")` with ROV
+    pub fn with_group_setting(mut self, group: ASNGroups, setting: Settings) -> Self {
+        self.group_settings.retain(|(existing, _)| *existing != group);
+        self.group_settings.push((group, setting));
+        self
+    }
+    
+    /// Get the settings each adopting AS runs, plus group-wide settings and any per-AS overrides
+    /// Adopting ASes outside every group and rule run the first enabled default setting, if any
+    pub fn adoption_settings(&self, as_graph: &ASGraph, adopting_asns: &HashSet<ASN>) -> HashMap<ASN, Settings> {
+        let default_setting = self.default_adoption_settings.iter()
+            .find(|(_, &enabled)| enabled)
+            .map(|(setting, _)| *setting);
+        let mut settings: HashMap<ASN, Settings> = match default_setting {
+            Some(setting) => adopting_asns.iter().map(|&asn| (asn, setting)).collect(),
+            None => HashMap::new(),
+        };
+        
+        // Later groups overwrite earlier ones
+        for (group, setting) in &self.group_adoption_settings {
+            for asn in group.asns(as_graph) {
+                if adopting_asns.contains(&asn) {
+                    settings.insert(asn, *setting);
+                }
+            }
+        }
+        for rule in &self.adoption_rules {
+            settings.extend(rule.selector.select(as_graph, adopting_asns).into_iter().map(|asn| (asn, rule.setting)));
+        }
+        for (group, setting) in &self.group_settings {
+            settings.extend(group.asns(as_graph).into_iter().map(|asn| (asn, *setting)));
+        }
+        
+        if let Some(asn_settings) = &self.override_asn_settings {
+            settings.extend(asn_settings.iter().map(|(&asn, &setting)| (asn, setting)));
+        }
+        settings
+    }
+    
+    pub fn with_roa_generation(mut self, roa_generation: RoaGeneration) -> Self {
+        self.roa_generation = roa_generation;
+        self
+    }
+    
+    /// Leave each legitimate origin without ROAs with `percent` chance, e.g. to study partial RPKI deployment
+    pub fn with_percent_victims_without_roa(mut self, percent: f64) -> Self {
+        self.percent_victims_without_roa = percent;
+        self
+    }
+    
+    /// Misconfigure each legitimate origin's ROAs with `percent` chance, see `percent_loose_max_length`
+    pub fn with_percent_loose_max_length(mut self, percent: f64) -> Self {
+        self.percent_loose_max_length = percent;
+        self
+    }
+    
+    /// Whether `roas_for_run` changes anything, so runs without ROA preprocessing skip it
+    pub fn preprocesses_roas(&self) -> bool {
+        self.roa_generation != RoaGeneration::Scenario
+            || self.percent_victims_without_roa > 0.0
+            || self.percent_loose_max_length > 0.0
+    }
+    
+    /// Whether runs can have ROAs allowing longer prefixes than announced, so trials track how many
+    /// prefixes that leaves open to forged-origin subprefix hijacks
+    pub fn models_loose_max_length(&self) -> bool {
+        matches!(self.roa_generation, RoaGeneration::MaxLength(_)) || self.percent_loose_max_length > 0.0
+    }
+    
+    /// The ROAs of one run: the scenario's (`scenario_roas`) or generated ones, minus those of the
+    /// legitimate origins randomly left without ROAs, with those randomly misconfigured loosened
+    pub fn roas_for_run(
+        &self,
+        scenario_roas: Vec<ROA>,
+        seeds: &HashMap<ASN, Vec<Announcement>>,
+        legitimate_origin_asns: &HashSet<ASN>,
+    ) -> Vec<ROA> {
+        let roas = match self.roa_generation {
+            RoaGeneration::Scenario => scenario_roas,
+            generation => generation.roas(seeds, legitimate_origin_asns),
+        };
+        let without_roa = draw_origins(legitimate_origin_asns, self.percent_victims_without_roa);
+        let loose_max_length = draw_origins(legitimate_origin_asns, self.percent_loose_max_length);
+        roas.into_iter()
+            .filter(|roa| !without_roa.contains(&roa.origin))
+            .map(|mut roa| {
+                if loose_max_length.contains(&roa.origin) {
+                    roa.max_length = if roa.prefix.is_ipv4() { 32 } else { 128 };
+                }
+                roa
+            })
+            .collect()
+    }
+    
+    pub fn with_storage_mode(mut self, storage_mode: PolicyStorageMode) -> Self {
+        self.storage_mode = storage_mode;
+        self
+    }
+    
+    pub fn with_tie_breaker(mut self, tie_breaker: TieBreaker) -> Self {
+        self.tie_breaker = tie_breaker;
+        self
+    }
+    
+    pub fn with_next_hop_verification(mut self, verification: NextHopVerification) -> Self {
+        self.next_hop_verification = verification;
+        self
+    }
+    
+    /// Schedule an event before propagation round `round`, see `EventSchedule`
+    pub fn with_event(mut self, round: u32, event: ScenarioEvent) -> Self {
+        self.event_schedule.add(round, event);
+        self
+    }
+    
+    pub fn with_attacker_asns(mut self, asns: HashSet<ASN>) -> Self {
+        self.override_attacker_asns = Some(asns);
+        self
+    }
+    
+    pub fn with_legitimate_origin_asns(mut self, asns: HashSet<ASN>) -> Self {
+        self.override_legitimate_origin_asns = Some(asns);
+        self
+    }
+    
+    /// Select `num_attackers` random attackers that all announce the hijack
+    pub fn with_num_attackers(mut self, num_attackers: usize) -> Self {
+        self.num_attackers = num_attackers;
+        self
+    }
+    
+    /// Draw attackers from `candidates` each trial, e.g. `ASNCandidates::Group(ASNGroups::Stubs)`
+    pub fn with_attacker_candidates(mut self, candidates: ASNCandidates) -> Self {
+        self.attacker_candidates = Some(candidates);
+        self
+    }
+    
+    /// Draw legitimate origins from `candidates` each trial, never picking an attacker
+    pub fn with_legitimate_origin_candidates(mut self, candidates: ASNCandidates) -> Self {
+        self.legitimate_origin_candidates = Some(candidates);
+        self
+    }
+    
+    /// Select `num_origins` random legitimate origins that all announce the victim prefix
+    pub fn with_num_legitimate_origins(mut self, num_origins: usize) -> Self {
+        self.num_legitimate_origins = num_origins;
+        self
+    }
+    
+    pub fn with_adopting_asns(mut self, asns: HashSet<ASN>) -> Self {
+        self.override_adopting_asns = Some(asns);
+        self
+    }
+    
+    /// Have a random `percent` of ASes register ASPA objects, whether or not they validate
+    pub fn with_percent_aspa_objects(mut self, percent: f64) -> Self {
+        self.percent_aspa_objects = Some(percent);
+        self
+    }
+    
+    /// Draw adopters from measured probabilities each trial, e.g. `MeasuredAdoption::load_from_file` of a ROV list
+    pub fn with_irr_route_objects(mut self, route_objects: Arc<IrrValidator>) -> Self {
+        self.irr_route_objects = Some(route_objects);
+        self
+    }
+    
+    pub fn with_measured_adoption(mut self, adoption: Arc<MeasuredAdoption>) -> Self {
+        self.measured_adoption = Some(adoption);
+        self
+    }
+    
+    /// Run `setting` at `asn` regardless of adoption, e.g. to hand-craft a test topology
+    pub fn with_asn_setting(mut self, asn: ASN, setting: Settings) -> Self {
+        self.override_asn_settings.get_or_insert_with(HashMap::new).insert(asn, setting);
+        self
+    }
+}
+
+/// Fields of a declarative config that name files `from_json` reads
+const FILE_FIELDS: [&str; 2] = ["measured_adoption_file", "irr_file"];
+
+impl ScenarioConfig {
+    /// Load a config from a JSON or YAML file (see `from_json` for the fields)
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        ScenarioConfig::from_json(&read_config_file(path)?)
+    }
+    
+    /// Parse a declarative config, using the keys `EngineRunConfig::to_json` writes
+    ///
+    /// Only `scenario_name` is required; `label` defaults to it. ASN lists, `num_attackers`, `num_legitimate_origins`, `asn_settings`
+    /// (ASN to setting name), `default_adoption_settings` (setting name to bool), `roas`
+    /// (`prefix`, `origin`, optional `max_length`), `roa_generation` (see `RoaGeneration`'s `Display`), `measured_adoption_file` (see `MeasuredAdoption::parse`), `irr_file` (RPSL, see `IrrValidator::from_rpsl`), `percent_aspa_objects`,
+    /// `percent_victims_without_roa`, `percent_loose_max_length`, `storage_mode`, `tie_breaker`, `next_hop_verification` and `events` (see `ScenarioEvent::from_json`) are optional
+    ///
+    /// Malformed or inconsistent fields are `Error::ConfigInvalid`, and files that can't be loaded `Error::Parse`
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, Error> {
+        let mut config = ScenarioConfig::from_json_fields(value).map_err(Error::ConfigInvalid)?;
+        if let Some(path) = config_field::<PathBuf>(value, "measured_adoption_file").map_err(Error::ConfigInvalid)? {
+            config.measured_adoption = Some(Arc::new(MeasuredAdoption::load_from_file(&path).map_err(Error::Parse)?));
+        }
+        if let Some(path) = config_field::<PathBuf>(value, "irr_file").map_err(Error::ConfigInvalid)? {
+            config.irr_route_objects = Some(Arc::new(IrrValidator::load_from_file(&path).map_err(Error::Parse)?));
+        }
+        Ok(config)
+    }
+
+    /// Every field of `from_json` but the files it loads
+    fn from_json_fields(value: &serde_json::Value) -> Result<Self, String> {
+        let scenario_name: String = config_field(value, "scenario_name")?
+            .ok_or("Scenario config is missing scenario_name")?;
+        let label = config_field(value, "label")?.unwrap_or_else(|| scenario_name.clone());
+        let mut config = ScenarioConfig::new(label, scenario_name);
+        
+        if let Some(settings) = config_field::<HashMap<Settings, bool>>(value, "default_adoption_settings")? {
+            config.default_adoption_settings = settings;
+        }
+        config.override_attacker_asns = config_field(value, "attacker_asns")?;
+        config.override_legitimate_origin_asns = config_field(value, "legitimate_origin_asns")?;
+        config.override_adopting_asns = config_field(value, "adopting_asns")?;
+        config.percent_aspa_objects = config_field(value, "percent_aspa_objects")?;
+        if let Some(num_attackers) = config_field(value, "num_attackers")? {
+            config.num_attackers = num_attackers;
+        }
+        if let Some(num_origins) = config_field(value, "num_legitimate_origins")? {
+            config.num_legitimate_origins = num_origins;
+        }
+        if let Some(asn_settings) = config_field::<HashMap<String, Settings>>(value, "asn_settings")? {
+            let asn_settings = asn_settings.into_iter()
+                .map(|(asn, setting)| {
+                    asn.parse().map(|asn| (asn, setting)).map_err(|_| format!("Invalid ASN in asn_settings: {}", asn))
+                })
+                .collect::<Result<HashMap<ASN, Settings>, String>>()?;
+            config.override_asn_settings = Some(asn_settings);
+        }
+        if let Some(roa_values) = config_field::<Vec<serde_json::Value>>(value, "roas")? {
+            let roas = roa_values.iter().map(roa_from_json).collect::<Result<Vec<ROA>, String>>()?;
+            config.override_roas = Some(roas);
+        }
+        if let Some(roa_generation) = config_field::<String>(value, "roa_generation")? {
+            config.roa_generation = roa_generation.parse()?;
+        }
+        if let Some(percent) = config_field(value, "percent_victims_without_roa")? {
+            config.percent_victims_without_roa = percent;
+        }
+        if let Some(percent) = config_field(value, "percent_loose_max_length")? {
+            config.percent_loose_max_length = percent;
+        }
+        if let Some(storage_mode) = config_field::<String>(value, "storage_mode")? {
+            config.storage_mode = match storage_mode.as_str() {
+                "Full" => PolicyStorageMode::Full,
+                "LocalRibOnly" => PolicyStorageMode::LocalRibOnly,
+                _ => return Err(format!("Unknown storage_mode: {}", storage_mode)),
+            };
+        }
+        if let Some(tie_breaker) = config_field::<String>(value, "tie_breaker")? {
+            config.tie_breaker = tie_breaker.parse()?;
+        }
+        if let Some(verification) = config_field::<String>(value, "next_hop_verification")? {
+            config.next_hop_verification = verification.parse()?;
+        }
+        if let Some(events) = config_field::<Vec<serde_json::Value>>(value, "events")? {
+            config.event_schedule = EventSchedule::from_json(&events)?;
+        }
+        Ok(config)
+    }
+
+    /// `from_json` for configs from untrusted sources, e.g. the server's clients, which never reads the filesystem
+    /// Configs naming a file to load (`measured_adoption_file` or `irr_file`) are rejected without opening it
+    pub fn from_json_without_files(value: &serde_json::Value) -> Result<Self, Error> {
+        if let Some(key) = FILE_FIELDS.iter().find(|key| value.get(**key).is_some_and(|field| !field.is_null())) {
+            return Err(Error::ConfigInvalid(format!("{} is not accepted here, since it names a file to read", key)));
+        }
+        ScenarioConfig::from_json(value)
+    }
+
+    /// Inverse of `from_json`, plus the adoption rules for the record
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "label": self.label,
+            "scenario_name": self.scenario_name,
+            "default_adoption_settings": self.default_adoption_settings,
+            "adoption_rules": self.adoption_rules.iter()
+                .map(|rule| serde_json::json!({"adopters": rule.selector.to_string(), "setting": rule.setting}))
+                .collect::<Vec<_>>(),
+            "attacker_asns": self.override_attacker_asns.as_ref().map(sorted),
+            "legitimate_origin_asns": self.override_legitimate_origin_asns.as_ref().map(sorted),
+            "num_attackers": self.num_attackers,
+            "num_legitimate_origins": self.num_legitimate_origins,
+            "adopting_asns": self.override_adopting_asns.as_ref().map(sorted),
+            "percent_aspa_objects": self.percent_aspa_objects,
+            "measured_adoption_file": self.measured_adoption.as_ref().and_then(|adoption| adoption.source.as_ref()),
+            "irr_file": self.irr_route_objects.as_ref().and_then(|route_objects| route_objects.source.as_ref()),
+            "roas": self.override_roas.as_ref().map(|roas| {
+                roas.iter()
+                    .map(|roa| serde_json::json!({
+                        "prefix": roa.prefix.to_string(),
+                        "origin": roa.origin,
+                        "max_length": roa.max_length,
+                    }))
+                    .collect::<Vec<serde_json::Value>>()
+            }),
+            "roa_generation": self.roa_generation.to_string(),
+            "percent_victims_without_roa": self.percent_victims_without_roa,
+            "percent_loose_max_length": self.percent_loose_max_length,
+            "storage_mode": format!("{:?}", self.storage_mode),
+            "tie_breaker": self.tie_breaker.to_string(),
+            "next_hop_verification": self.next_hop_verification.to_string(),
+            "events": self.event_schedule.to_json(),
+            "asn_settings": self.override_asn_settings.as_ref().map(|asn_settings| {
+                asn_settings.iter()
+                    .map(|(asn, setting)| (asn.to_string(), serde_json::json!(setting)))
+                    .collect::<serde_json::Map<String, serde_json::Value>>()
+            }),
+        })
+    }
+}
+
+fn sorted(asns: &HashSet<ASN>) -> Vec<ASN> {
+    let mut asns: Vec<ASN> = asns.iter().copied().collect();
+    asns.sort_unstable();
+    asns
+}
+
+/// Each of `asns` with `percent` chance, drawn in sorted order so hash order doesn't decide who is drawn
+fn draw_origins(asns: &HashSet<ASN>, percent: f64) -> HashSet<ASN> {
+    if percent <= 0.0 {
+        return HashSet::new();
+    }
+    let mut rng = rand::thread_rng();
+    let probability = (percent / 100.0).min(1.0);
+    sorted(asns).into_iter().filter(|_| rng.gen_bool(probability)).collect()
+}
+
+/// Read a config file into JSON, parsing it as YAML when the extension is `.yaml` or `.yml`
+pub fn read_config_file(path: &Path) -> Result<serde_json::Value, Error> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| std::io::Error::new(e.kind(), format!("Failed to read {}: {}", path.display(), e)))?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("yaml") | Some("yml") => serde_yaml::from_str(&contents)
+            .map_err(|e| Error::Parse(format!("Invalid YAML in {}: {}", path.display(), e))),
+        _ => serde_json::from_str(&contents)
+            .map_err(|e| Error::Parse(format!("Invalid JSON in {}: {}", path.display(), e))),
+    }
+}
+
+/// Optional field of a config object; present but malformed fields are errors
+pub(crate) fn config_field<T: DeserializeOwned>(value: &serde_json::Value, key: &str) -> Result<Option<T>, String> {
+    match value.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(field) => serde_json::from_value(field.clone())
+            .map(Some)
+            .map_err(|e| format!("Invalid {}: {}", key, e)),
+    }
+}
+
+pub(crate) fn roa_from_json(value: &serde_json::Value) -> Result<ROA, String> {
+    let prefix: String = config_field(value, "prefix")?.ok_or("ROA is missing prefix")?;
+    let prefix = IpNetwork::from_str(&prefix).map_err(|e| format!("Invalid ROA prefix {}: {}", prefix, e))?;
+    let origin = config_field(value, "origin")?.ok_or("ROA is missing origin")?;
+    Ok(ROA::new(prefix, origin, config_field(value, "max_length")?))
+}
+
+impl Default for ScenarioConfig {
+    fn default() -> Self {
+        ScenarioConfig::new(
+            "Default Scenario".to_string(),
+            "SubprefixHijack".to_string(),
+        )
+    }
+}
\ No newline at end of file
@@ -0,0 +1,180 @@
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+
+use ipnetwork::IpNetwork;
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::simulation_engine::SimulationEngine;
+use bgpsim_core::route_validator::{ROA, RouteValidator};
+use bgpsim_core::shared::{Outcomes, Relationships, Timestamps};
+use bgpsim_core::simulation_engine::{Announcement, ExportRestrictions, Prefix};
+use crate::simulation_framework::event_schedule::{EventSchedule, ScenarioEvent};
+use crate::simulation_framework::scenario::{CustomMetrics, ScenarioTrait, data_plane_outcomes};
+
+use super::SubprefixHijack;
+
+/// Subprefix hijack where the victims react by de-aggregating their prefix (announcing more specifics)
+///
+/// Before `response_round` the victims announce every subprefix of their prefix at `deaggregation_length`,
+/// which wins back traffic by longest prefix match where it propagates. Unless `update_roas` is set, the
+/// subprefixes exceed the ROA's max length, so ROV adopters drop them just like the hijack
+pub struct DeaggregationSubprefixHijack {
+    pub hijack: SubprefixHijack,
+    /// Round the victims announce their subprefixes before
+    pub response_round: u32,
+    /// Length of the subprefixes the victims announce, at least the hijacked prefix's
+    pub deaggregation_length: u8,
+    /// Whether the victims also publish ROAs for their subprefixes when they announce them
+    pub update_roas: bool,
+}
+
+impl DeaggregationSubprefixHijack {
+    pub fn new(attacker_asns: HashSet<ASN>, legitimate_origin_asns: HashSet<ASN>) -> Self {
+        let hijack = SubprefixHijack::new(attacker_asns, legitimate_origin_asns);
+        let deaggregation_length = hijack.hijacked_prefix.prefix();
+        DeaggregationSubprefixHijack {
+            hijack,
+            response_round: 10,
+            deaggregation_length,
+            update_roas: false,
+        }
+    }
+
+    pub fn with_response_round(mut self, round: u32) -> Self {
+        self.response_round = round;
+        self
+    }
+
+    /// Announce subprefixes of this length, e.g. one longer than the hijack to beat it outright
+    pub fn with_deaggregation_length(mut self, length: u8) -> Self {
+        self.deaggregation_length = length;
+        self
+    }
+
+    pub fn with_update_roas(mut self, update_roas: bool) -> Self {
+        self.update_roas = update_roas;
+        self
+    }
+
+    /// Subprefixes of the victims' prefix the victims announce in response, in address order
+    pub fn deaggregated_prefixes(&self) -> Vec<Prefix> {
+        subprefixes(self.hijack.legitimate_prefix, self.deaggregation_length.max(self.hijack.hijacked_prefix.prefix()))
+    }
+
+    /// Where each AS's traffic to the hijacked subprefix ends up
+    pub fn data_plane_outcomes(&self, engine: &SimulationEngine) -> HashMap<ASN, Outcomes> {
+        data_plane_outcomes(
+            engine,
+            &self.hijack.attacker_asns,
+            &self.hijack.legitimate_origin_asns,
+            self.get_dest_ip_addr(),
+        )
+    }
+}
+
+/// Every subprefix of `prefix` with `length`, which must be at most the address width
+fn subprefixes(prefix: Prefix, length: u8) -> Vec<Prefix> {
+    let extra_bits = u32::from(length.saturating_sub(prefix.prefix()));
+    match prefix.network() {
+        IpAddr::V4(network) => (0..1u32 << extra_bits)
+            .map(|index| {
+                let step = 1u64 << (32 - u32::from(length));
+                let addr = (u64::from(u32::from(network)) + u64::from(index) * step) as u32;
+                IpNetwork::new(IpAddr::V4(addr.into()), length).unwrap()
+            })
+            .collect(),
+        IpAddr::V6(network) => (0..1u32 << extra_bits)
+            .map(|index| {
+                let step = 1u128.checked_shl(128 - u32::from(length)).unwrap_or(0);
+                let addr = u128::from(network) + u128::from(index) * step;
+                IpNetwork::new(IpAddr::V6(addr.into()), length).unwrap()
+            })
+            .collect(),
+    }
+}
+
+impl ScenarioTrait for DeaggregationSubprefixHijack {
+    fn min_propagation_rounds(&self) -> u32 {
+        self.response_round + 1
+    }
+
+    fn name(&self) -> &str {
+        "DeaggregationSubprefixHijack"
+    }
+
+    fn get_attacker_asns(&self, as_graph: &ASGraph) -> HashSet<ASN> {
+        self.hijack.get_attacker_asns(as_graph)
+    }
+
+    fn get_legitimate_origin_asns(&self, as_graph: &ASGraph) -> HashSet<ASN> {
+        self.hijack.get_legitimate_origin_asns(as_graph)
+    }
+
+    fn get_seed_asn_ann_dict(&self, as_graph: &ASGraph) -> HashMap<ASN, Vec<Announcement>> {
+        self.hijack.get_seed_asn_ann_dict(as_graph)
+    }
+
+    fn get_roas(&self, as_graph: &ASGraph) -> Vec<ROA> {
+        self.hijack.get_roas(as_graph)
+    }
+
+    fn get_seed_export_restrictions(&self, as_graph: &ASGraph) -> ExportRestrictions {
+        self.hijack.get_seed_export_restrictions(as_graph)
+    }
+
+    fn get_events(&self, _as_graph: &ASGraph) -> EventSchedule {
+        let mut victims: Vec<ASN> = self.hijack.legitimate_origin_asns.iter().copied().collect();
+        victims.sort_unstable();
+
+        let mut schedule = EventSchedule::new();
+        for prefix in self.deaggregated_prefixes() {
+            for &asn in &victims {
+                if self.update_roas {
+                    schedule.add(self.response_round, ScenarioEvent::AddRoa(ROA::new(prefix, asn, None)));
+                }
+                let ann = Announcement::new_with_path(
+                    prefix,
+                    vec![],
+                    asn,
+                    Relationships::Origin,
+                    Timestamps(u64::from(self.response_round)),
+                ).with_action_communities(self.hijack.victim_communities.clone());
+                schedule.add(self.response_round, ScenarioEvent::Announce(asn, ann));
+            }
+        }
+        schedule
+    }
+
+    fn setup_engine(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator) {
+        self.hijack.setup_engine(engine, route_validator);
+    }
+
+    fn is_successful(&self, engine: &SimulationEngine) -> bool {
+        // Success if attacker traffic still reaches the attacker from more than 50% of ASes
+        let outcomes = self.data_plane_outcomes(engine);
+        let hijacked = outcomes.values().filter(|&&outcome| outcome == Outcomes::AttackerSuccess).count();
+        hijacked as f64 / engine.as_graph.len() as f64 > 0.5
+    }
+
+    fn analyze(&self, engine: &SimulationEngine, route_validator: &RouteValidator) -> Option<serde_json::Value> {
+        self.hijack.analyze(engine, route_validator)
+    }
+
+    fn register_custom_metrics(&self, metrics: &mut CustomMetrics) {
+        for (name, outcome) in [
+            ("hijacked_fraction", Outcomes::AttackerSuccess),
+            ("victim_reached_fraction", Outcomes::VictimSuccess),
+            ("disconnected_fraction", Outcomes::DisconnectedOrigin),
+        ] {
+            let attacker_asns = self.hijack.attacker_asns.clone();
+            let legitimate_origin_asns = self.hijack.legitimate_origin_asns.clone();
+            let dest_ip_addr = self.get_dest_ip_addr();
+
+            metrics.register(name.to_string(), move |engine| {
+                let outcomes = data_plane_outcomes(engine, &attacker_asns, &legitimate_origin_asns, dest_ip_addr);
+                let count = outcomes.values().filter(|&&o| o == outcome || (o.is_disconnected() && outcome.is_disconnected())).count();
+                count as f64 / engine.as_graph.len().max(1) as f64
+            });
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use ipnetwork::IpNetwork;
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::simulation_engine::SimulationEngine;
+use bgpsim_core::route_validator::{ROA, RouteValidator};
+use bgpsim_core::shared::{Relationships, Timestamps};
+use bgpsim_core::simulation_engine::{Announcement, Prefix};
+use crate::simulation_framework::scenario::{ScenarioTrait, origin_roas, route_validity_counts, share_route_validator};
+
+/// Forged-origin subprefix hijack
+/// The attacker announces a more specific prefix with the legitimate origin appended to its path,
+/// which ROV only stops when the ROA's max length is the announced length. Set `roa_max_length`
+/// (or `ScenarioConfig::percent_loose_max_length`) longer to model the misconfiguration
+pub struct ForgedOriginSubprefixHijack {
+    pub attacker_asns: HashSet<ASN>,
+    pub legitimate_origin_asns: HashSet<ASN>,
+    pub legitimate_prefix: Prefix,
+    pub hijacked_prefix: Prefix,
+    /// Max length of the legitimate origins' ROAs
+    pub roa_max_length: u8,
+}
+
+impl ForgedOriginSubprefixHijack {
+    pub fn new(attacker_asns: HashSet<ASN>, legitimate_origin_asns: HashSet<ASN>) -> Self {
+        // Legitimate announces /24, attacker announces /25, like SubprefixHijack
+        ForgedOriginSubprefixHijack {
+            attacker_asns,
+            legitimate_origin_asns,
+            legitimate_prefix: IpNetwork::from_str("1.2.3.0/24").unwrap(),
+            hijacked_prefix: IpNetwork::from_str("1.2.3.0/25").unwrap(),
+            roa_max_length: 24,
+        }
+    }
+
+    pub fn with_roa_max_length(mut self, max_length: u8) -> Self {
+        self.roa_max_length = max_length;
+        self
+    }
+
+    /// The origin the attackers forge, the same for every attacker so the result doesn't depend on hash order
+    fn forged_origin(&self) -> Option<ASN> {
+        self.legitimate_origin_asns.iter().min().copied()
+    }
+}
+
+impl ScenarioTrait for ForgedOriginSubprefixHijack {
+    fn name(&self) -> &str {
+        "ForgedOriginSubprefixHijack"
+    }
+
+    fn get_attacker_asns(&self, _as_graph: &ASGraph) -> HashSet<ASN> {
+        self.attacker_asns.clone()
+    }
+
+    fn get_legitimate_origin_asns(&self, _as_graph: &ASGraph) -> HashSet<ASN> {
+        self.legitimate_origin_asns.clone()
+    }
+
+    fn get_seed_asn_ann_dict(&self, _as_graph: &ASGraph) -> HashMap<ASN, Vec<Announcement>> {
+        let mut seed_dict = HashMap::new();
+
+        for &asn in &self.legitimate_origin_asns {
+            let ann = Announcement::new_with_path(
+                self.legitimate_prefix,
+                vec![],  // Empty AS path for origin
+                asn,
+                Relationships::Origin,
+                Timestamps::VICTIM,
+            );
+            seed_dict.insert(asn, vec![ann]);
+        }
+
+        for &asn in &self.attacker_asns {
+            let ann = Announcement::new_with_path(
+                self.hijacked_prefix,
+                [asn].into_iter().chain(self.forged_origin()).collect::<Vec<ASN>>(),
+                asn,
+                Relationships::Origin,
+                Timestamps::ATTACKER,
+            );
+            seed_dict.insert(asn, vec![ann]);
+        }
+
+        seed_dict
+    }
+
+    fn get_roas(&self, _as_graph: &ASGraph) -> Vec<ROA> {
+        origin_roas(self.legitimate_prefix, &self.legitimate_origin_asns, Some(self.roa_max_length))
+    }
+
+    fn setup_engine(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator) {
+        *route_validator = RouteValidator::new();
+        route_validator.add_roas(self.get_roas(engine.as_graph));
+
+        let seeds: Vec<(ASN, Announcement)> = self.get_seed_asn_ann_dict(engine.as_graph).into_iter()
+            .flat_map(|(asn, anns)| anns.into_iter().map(move |ann| (asn, ann)))
+            .collect();
+        share_route_validator(engine, route_validator, &seeds);
+        engine.setup(seeds);
+    }
+
+    fn is_successful(&self, engine: &SimulationEngine) -> bool {
+        // The forged routes keep the legitimate origin, so count routes through an attacker instead
+        let attacker_reach = engine.policy_store.iter()
+            .filter(|(asn, policy)| {
+                policy.local_rib.get(&self.hijacked_prefix).is_some_and(|ann| {
+                    self.attacker_asns.contains(asn) || ann.as_path.iter().any(|hop| self.attacker_asns.contains(hop))
+                })
+            })
+            .count();
+
+        // Same threshold as SubprefixHijack
+        let success_ratio = attacker_reach as f64 / engine.as_graph.len() as f64;
+        success_ratio > 0.5
+    }
+
+    fn analyze(&self, engine: &SimulationEngine, route_validator: &RouteValidator) -> Option<serde_json::Value> {
+        Some(route_validity_counts(engine, route_validator, &self.hijacked_prefix))
+    }
+}
@@ -1,25 +1,43 @@
 use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 use ipnetwork::IpNetwork;
 use std::str::FromStr;
 
-use crate::as_graphs::as_graph::{ASGraph, ASN};
-use crate::simulation_engine::SimulationEngine;
-use crate::route_validator::{ROA, RouteValidator};
-use crate::shared::{CommonASNs, Relationships, Timestamps};
-use crate::simulation_engine::Announcement;
-use crate::simulation_framework::scenario::ScenarioTrait;
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::simulation_engine::SimulationEngine;
+use bgpsim_core::route_validator::{ROA, RouteValidator};
+use bgpsim_core::shared::{CommonASNs, Relationships, Timestamps};
+use bgpsim_core::simulation_engine::{ActionCommunity, Announcement, ExportRestrictions};
+use crate::simulation_framework::scenario::{ScenarioTrait, origin_roas, share_route_validator};
 
 /// Scenario where only legitimate prefix is announced (no attack)
 pub struct LegitimatePrefixOnly {
     legitimate_origin_asns: HashSet<ASN>,
+    /// Neighbors the legitimate origins export to (None exports to all neighbors)
+    victim_export_asns: Option<HashSet<ASN>>,
+    /// Action communities the legitimate origins attach, e.g. to steer traffic engineering at their providers
+    victim_communities: Vec<ActionCommunity>,
 }
 
 impl LegitimatePrefixOnly {
     pub fn new(legitimate_origin_asns: HashSet<ASN>) -> Self {
         LegitimatePrefixOnly {
             legitimate_origin_asns,
+            victim_export_asns: None,
+            victim_communities: Vec::new(),
         }
     }
+    
+    /// Only announce the prefix to these neighbors, e.g. a subset of the victim's providers
+    pub fn with_victim_export_asns(mut self, asns: HashSet<ASN>) -> Self {
+        self.victim_export_asns = Some(asns);
+        self
+    }
+    
+    pub fn with_victim_communities(mut self, communities: Vec<ActionCommunity>) -> Self {
+        self.victim_communities = communities;
+        self
+    }
 }
 
 impl ScenarioTrait for LegitimatePrefixOnly {
@@ -47,8 +65,8 @@ impl ScenarioTrait for LegitimatePrefixOnly {
                 vec![],
                 asn,
                 Relationships::Origin,
-                Timestamps::Victim,
-            );
+                Timestamps::VICTIM,
+            ).with_action_communities(self.victim_communities.clone());
             seed_dict.insert(asn, vec![ann]);
         }
         
@@ -56,25 +74,28 @@ impl ScenarioTrait for LegitimatePrefixOnly {
     }
     
     fn get_roas(&self, _as_graph: &ASGraph) -> Vec<ROA> {
-        let mut roas = Vec::new();
-        
-        // Create ROA for legitimate prefix
-        for &asn in &self.legitimate_origin_asns {
-            roas.push(ROA::new(
-                IpNetwork::from_str("10.0.0.0/24").unwrap(),
-                asn,
-                Some(24),
-            ));
+        origin_roas(IpNetwork::from_str("10.0.0.0/24").unwrap(), &self.legitimate_origin_asns, Some(24))
+    }
+    
+    /// An address in the legitimate prefix
+    fn get_dest_ip_addr(&self) -> IpAddr {
+        "10.0.0.1".parse().unwrap()
+    }
+    
+    fn get_seed_export_restrictions(&self, _as_graph: &ASGraph) -> ExportRestrictions {
+        let mut restrictions = ExportRestrictions::new();
+        if let Some(export_asns) = &self.victim_export_asns {
+            let prefix = IpNetwork::from_str("10.0.0.0/24").unwrap();
+            for &asn in &self.legitimate_origin_asns {
+                restrictions.insert((asn, prefix), export_asns.clone());
+            }
         }
-        
-        roas
+        restrictions
     }
     
     fn setup_engine(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator) {
         // Add ROAs
-        for roa in self.get_roas(&engine.as_graph) {
-            route_validator.add_roa(roa);
-        }
+        route_validator.add_roas(self.get_roas(&engine.as_graph));
         
         // Seed announcements
         let seed_dict = self.get_seed_asn_ann_dict(&engine.as_graph);
@@ -82,6 +103,8 @@ impl ScenarioTrait for LegitimatePrefixOnly {
             .flat_map(|(asn, anns)| anns.into_iter().map(move |ann| (asn, ann)))
             .collect();
         
+        engine.origin_export_restrictions = self.get_seed_export_restrictions(engine.as_graph);
+        share_route_validator(engine, route_validator, &seeds);
         engine.setup(seeds);
     }
     
@@ -90,7 +113,7 @@ impl ScenarioTrait for LegitimatePrefixOnly {
         let legitimate_prefix = IpNetwork::from_str("10.0.0.0/24").unwrap();
         
         let mut has_routes = 0;
-        let total_ases = engine.as_graph.as_dict.len();
+        let total_ases = engine.as_graph.len();
         
         for (_, policy) in engine.policy_store.iter() {
             if policy.local_rib.contains_key(&legitimate_prefix) {
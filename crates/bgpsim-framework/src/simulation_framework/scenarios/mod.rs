@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::simulation_engine::SimulationEngine;
+use bgpsim_core::Error;
+use crate::simulation_framework::scenario::ScenarioTrait;
+pub use crate::simulation_framework::scenario::{
+    control_plane_outcomes, data_plane_outcomes, disconnected_outcome, max_length_vulnerable_prefixes, preprocess_roas, share_route_validator,
+};
+
+pub mod subprefix_hijack;
+pub mod prefix_hijack;
+pub mod legitimate_prefix_only;
+pub mod rtbh_subprefix_hijack;
+pub mod shortest_path_prefix_hijack;
+pub mod non_routed_superprefix_hijack;
+pub mod superprefix_hijack;
+pub mod prepending_prefix_hijack;
+pub mod deaggregation_subprefix_hijack;
+pub mod forged_origin_subprefix_hijack;
+
+pub use subprefix_hijack::SubprefixHijack;
+pub use prefix_hijack::PrefixHijack;
+pub use legitimate_prefix_only::LegitimatePrefixOnly;
+pub use rtbh_subprefix_hijack::RTBHSubprefixHijack;
+pub use shortest_path_prefix_hijack::ShortestPathPrefixHijack;
+pub use non_routed_superprefix_hijack::NonRoutedSuperprefixHijack;
+pub use superprefix_hijack::SuperprefixHijack;
+pub use prepending_prefix_hijack::PrependingPrefixHijack;
+pub use deaggregation_subprefix_hijack::DeaggregationSubprefixHijack;
+pub use forged_origin_subprefix_hijack::ForgedOriginSubprefixHijack;
+
+/// Share of the graph's ASes an attacker must reach for `PrefixHijack` and its variants to succeed
+pub const PREFIX_HIJACK_SUCCESS_SHARE: f64 = 0.25;
+
+/// Whether `attacker_reach` ASes are more than `PREFIX_HIJACK_SUCCESS_SHARE` of `engine`'s graph
+pub fn prefix_hijack_succeeded(engine: &SimulationEngine, attacker_reach: usize) -> bool {
+    attacker_reach as f64 / engine.as_graph.len() as f64 > PREFIX_HIJACK_SUCCESS_SHARE
+}
+
+/// Create a scenario by name
+pub fn create_scenario(
+    scenario_name: &str,
+    attacker_asns: HashSet<ASN>,
+    legitimate_origin_asns: HashSet<ASN>,
+) -> Result<Box<dyn ScenarioTrait>, Error> {
+    match scenario_name {
+        "SubprefixHijack" => Ok(Box::new(SubprefixHijack::new(attacker_asns, legitimate_origin_asns))),
+        "PrefixHijack" => Ok(Box::new(PrefixHijack::new(attacker_asns, legitimate_origin_asns))),
+        "LegitimatePrefixOnly" => Ok(Box::new(LegitimatePrefixOnly::new(legitimate_origin_asns))),
+        "RTBHSubprefixHijack" => Ok(Box::new(RTBHSubprefixHijack::new(attacker_asns, legitimate_origin_asns))),
+        "ShortestPathPrefixHijack" => Ok(Box::new(ShortestPathPrefixHijack::new(attacker_asns, legitimate_origin_asns))),
+        "NonRoutedSuperprefixHijack" => Ok(Box::new(NonRoutedSuperprefixHijack::new(attacker_asns))),
+        "SuperprefixHijack" => Ok(Box::new(SuperprefixHijack::new(attacker_asns, legitimate_origin_asns))),
+        "PrependingPrefixHijack" => Ok(Box::new(PrependingPrefixHijack::new(attacker_asns, legitimate_origin_asns))),
+        "DeaggregationSubprefixHijack" => Ok(Box::new(DeaggregationSubprefixHijack::new(attacker_asns, legitimate_origin_asns))),
+        "ForgedOriginSubprefixHijack" => Ok(Box::new(ForgedOriginSubprefixHijack::new(attacker_asns, legitimate_origin_asns))),
+        _ => Err(Error::ScenarioUnknown(scenario_name.to_string())),
+    }
+}
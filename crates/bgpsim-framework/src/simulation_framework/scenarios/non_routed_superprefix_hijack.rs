@@ -0,0 +1,123 @@
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use ipnetwork::IpNetwork;
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::simulation_engine::SimulationEngine;
+use bgpsim_core::route_validator::{ROA, RouteValidator};
+use bgpsim_core::shared::{Outcomes, Relationships, Timestamps};
+use bgpsim_core::simulation_engine::{Announcement, Prefix};
+use crate::simulation_framework::scenario::{CustomMetrics, ScenarioTrait, route_validity_counts, share_route_validator};
+
+use super::{data_plane_outcomes, prefix_hijack_succeeded};
+
+/// Attacker squats on non-routed space by announcing a superprefix of it
+///
+/// The space has an AS0 ROA, so nobody may originate it, but the superprefix isn't covered by
+/// that ROA and is ROV unknown. There is no legitimate origin: traffic to the space should go nowhere
+pub struct NonRoutedSuperprefixHijack {
+    pub attacker_asns: HashSet<ASN>,
+    /// Space covered by the AS0 ROA
+    pub non_routed_prefix: Prefix,
+    /// Prefix the attackers announce, covering `non_routed_prefix`
+    pub superprefix: Prefix,
+}
+
+impl NonRoutedSuperprefixHijack {
+    pub fn new(attacker_asns: HashSet<ASN>) -> Self {
+        NonRoutedSuperprefixHijack {
+            attacker_asns,
+            non_routed_prefix: IpNetwork::from_str("1.2.0.0/16").unwrap(),
+            superprefix: IpNetwork::from_str("1.0.0.0/8").unwrap(),
+        }
+    }
+
+    /// Announce another prefix, e.g. `non_routed_prefix` itself for a non-routed prefix hijack that ROV catches
+    pub fn with_superprefix(mut self, superprefix: Prefix) -> Self {
+        self.superprefix = superprefix;
+        self
+    }
+
+    /// Where each AS's traffic to the non-routed space ends up
+    /// `DisconnectedOrigin` is the good outcome here, since the space has no legitimate origin
+    pub fn data_plane_outcomes(&self, engine: &SimulationEngine) -> HashMap<ASN, Outcomes> {
+        data_plane_outcomes(engine, &self.attacker_asns, &HashSet::new(), self.get_dest_ip_addr())
+    }
+}
+
+impl ScenarioTrait for NonRoutedSuperprefixHijack {
+    fn name(&self) -> &str {
+        "NonRoutedSuperprefixHijack"
+    }
+
+    fn get_attacker_asns(&self, _as_graph: &ASGraph) -> HashSet<ASN> {
+        self.attacker_asns.clone()
+    }
+
+    fn get_legitimate_origin_asns(&self, _as_graph: &ASGraph) -> HashSet<ASN> {
+        HashSet::new()
+    }
+
+    fn get_seed_asn_ann_dict(&self, _as_graph: &ASGraph) -> HashMap<ASN, Vec<Announcement>> {
+        self.attacker_asns.iter()
+            .map(|&asn| {
+                let ann = Announcement::new_with_path(
+                    self.superprefix,
+                    vec![],
+                    asn,
+                    Relationships::Origin,
+                    Timestamps::ATTACKER,
+                );
+                (asn, vec![ann])
+            })
+            .collect()
+    }
+
+    fn get_roas(&self, _as_graph: &ASGraph) -> Vec<ROA> {
+        vec![ROA::new(self.non_routed_prefix, 0, None)]
+    }
+
+    /// An address in the non-routed space
+    fn get_dest_ip_addr(&self) -> IpAddr {
+        "1.2.3.4".parse().unwrap()
+    }
+
+    fn setup_engine(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator) {
+        *route_validator = RouteValidator::new();
+        route_validator.add_roas(self.get_roas(engine.as_graph));
+
+        let seeds: Vec<(ASN, Announcement)> = self.get_seed_asn_ann_dict(engine.as_graph).into_iter()
+            .flat_map(|(asn, anns)| anns.into_iter().map(move |ann| (asn, ann)))
+            .collect();
+        share_route_validator(engine, route_validator, &seeds);
+        engine.setup(seeds);
+    }
+
+    fn is_successful(&self, engine: &SimulationEngine) -> bool {
+        let outcomes = self.data_plane_outcomes(engine);
+        let hijacked = outcomes.values().filter(|&&outcome| outcome == Outcomes::AttackerSuccess).count();
+        prefix_hijack_succeeded(engine, hijacked)
+    }
+
+    fn register_custom_metrics(&self, metrics: &mut CustomMetrics) {
+        for (name, outcome) in [
+            ("hijacked_fraction", Outcomes::AttackerSuccess),
+            ("disconnected_fraction", Outcomes::DisconnectedOrigin),
+        ] {
+            let attacker_asns = self.attacker_asns.clone();
+            let dest_ip_addr = self.get_dest_ip_addr();
+
+            metrics.register(name.to_string(), move |engine| {
+                let outcomes = data_plane_outcomes(engine, &attacker_asns, &HashSet::new(), dest_ip_addr);
+                let count = outcomes.values().filter(|&&o| o == outcome || (o.is_disconnected() && outcome.is_disconnected())).count();
+                count as f64 / engine.as_graph.len().max(1) as f64
+            });
+        }
+    }
+
+    fn analyze(&self, engine: &SimulationEngine, route_validator: &RouteValidator) -> Option<serde_json::Value> {
+        Some(route_validity_counts(engine, route_validator, &self.superprefix))
+    }
+}
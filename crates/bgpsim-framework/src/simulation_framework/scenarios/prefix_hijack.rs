@@ -4,12 +4,14 @@ use std::str::FromStr;
 
 use ipnetwork::IpNetwork;
 
-use crate::as_graphs::as_graph::{ASGraph, ASN};
-use crate::simulation_engine::SimulationEngine;
-use crate::route_validator::{ROA, RouteValidator};
-use crate::shared::{Relationships, Timestamps};
-use crate::simulation_engine::{Announcement, Prefix};
-use crate::simulation_framework::scenario::ScenarioTrait;
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::simulation_engine::SimulationEngine;
+use bgpsim_core::route_validator::{ROA, RouteValidator};
+use bgpsim_core::shared::{Relationships, Timestamps};
+use bgpsim_core::simulation_engine::{ActionCommunity, Announcement, ExportRestrictions, Prefix};
+use crate::simulation_framework::scenario::{ScenarioTrait, origin_roas, route_validity_counts, share_route_validator};
+
+use super::prefix_hijack_succeeded;
 
 /// Prefix hijack scenario
 /// Attacker announces the same prefix as the legitimate origin
@@ -17,6 +19,12 @@ pub struct PrefixHijack {
     pub attacker_asns: HashSet<ASN>,
     pub legitimate_origin_asns: HashSet<ASN>,
     pub target_prefix: Prefix,
+    /// Neighbors the legitimate origins export to (None exports to all neighbors)
+    pub victim_export_asns: Option<HashSet<ASN>>,
+    /// Action communities the legitimate origins attach, e.g. to steer traffic engineering at their providers
+    pub victim_communities: Vec<ActionCommunity>,
+    /// Action communities the attackers attach, e.g. to abuse traffic engineering at their providers
+    pub attacker_communities: Vec<ActionCommunity>,
 }
 
 impl PrefixHijack {
@@ -28,8 +36,27 @@ impl PrefixHijack {
             attacker_asns,
             legitimate_origin_asns,
             target_prefix,
+            victim_export_asns: None,
+            victim_communities: Vec::new(),
+            attacker_communities: Vec::new(),
         }
     }
+    
+    /// Only announce the prefix to these neighbors, e.g. a subset of the victim's providers
+    pub fn with_victim_export_asns(mut self, asns: HashSet<ASN>) -> Self {
+        self.victim_export_asns = Some(asns);
+        self
+    }
+    
+    pub fn with_victim_communities(mut self, communities: Vec<ActionCommunity>) -> Self {
+        self.victim_communities = communities;
+        self
+    }
+    
+    pub fn with_attacker_communities(mut self, communities: Vec<ActionCommunity>) -> Self {
+        self.attacker_communities = communities;
+        self
+    }
 }
 
 impl ScenarioTrait for PrefixHijack {
@@ -55,8 +82,8 @@ impl ScenarioTrait for PrefixHijack {
                 vec![],  // Empty AS path for origin
                 asn,
                 Relationships::Origin,
-                Timestamps::Victim,
-            );
+                Timestamps::VICTIM,
+            ).with_action_communities(self.victim_communities.clone());
             seed_dict.insert(asn, vec![ann]);
         }
         
@@ -67,8 +94,8 @@ impl ScenarioTrait for PrefixHijack {
                 vec![],  // Empty AS path for origin
                 asn,
                 Relationships::Origin,
-                Timestamps::Victim,  // Same timestamp
-            );
+                Timestamps::VICTIM,  // Same timestamp
+            ).with_action_communities(self.attacker_communities.clone());
             seed_dict.insert(asn, vec![ann]);
         }
         
@@ -76,27 +103,24 @@ impl ScenarioTrait for PrefixHijack {
     }
     
     fn get_roas(&self, _as_graph: &ASGraph) -> Vec<ROA> {
-        let mut roas = Vec::new();
-        
-        // Create ROA for legitimate prefix
-        for &asn in &self.legitimate_origin_asns {
-            let roa = ROA::new(
-                self.target_prefix,
-                asn,
-                Some(24),  // Max length 24
-            );
-            roas.push(roa);
+        // Max length 24, for every legitimate origin
+        origin_roas(self.target_prefix, &self.legitimate_origin_asns, Some(24))
+    }
+    
+    fn get_seed_export_restrictions(&self, _as_graph: &ASGraph) -> ExportRestrictions {
+        let mut restrictions = ExportRestrictions::new();
+        if let Some(export_asns) = &self.victim_export_asns {
+            for &asn in &self.legitimate_origin_asns {
+                restrictions.insert((asn, self.target_prefix), export_asns.clone());
+            }
         }
-        
-        roas
+        restrictions
     }
     
     fn setup_engine(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator) {
         // Clear and add ROAs
         *route_validator = RouteValidator::new();
-        for roa in self.get_roas(&engine.as_graph) {
-            route_validator.add_roa(roa);
-        }
+        route_validator.add_roas(self.get_roas(&engine.as_graph));
         
         // Seed announcements
         let seed_dict = self.get_seed_asn_ann_dict(&engine.as_graph);
@@ -107,13 +131,14 @@ impl ScenarioTrait for PrefixHijack {
             }
         }
         
+        engine.origin_export_restrictions = self.get_seed_export_restrictions(engine.as_graph);
+        share_route_validator(engine, route_validator, &initial_anns);
         engine.setup(initial_anns);
     }
     
     fn is_successful(&self, engine: &SimulationEngine) -> bool {
         // Check if attacker's announcement reached significant portion of network
         let mut attacker_reach = 0;
-        let total_ases = engine.as_graph.as_dict.len();
         
         // Check each AS's routing table
         for (_asn, policy) in engine.policy_store.iter() {
@@ -125,8 +150,11 @@ impl ScenarioTrait for PrefixHijack {
             }
         }
         
-        // Success if attacker reached more than 25% of ASes (lower threshold than subprefix)
-        let success_ratio = attacker_reach as f64 / total_ases as f64;
-        success_ratio > 0.25
+        // Lower threshold than subprefix hijacks, since the attacker competes with the legitimate route
+        prefix_hijack_succeeded(engine, attacker_reach)
+    }
+    
+    fn analyze(&self, engine: &SimulationEngine, route_validator: &RouteValidator) -> Option<serde_json::Value> {
+        Some(route_validity_counts(engine, route_validator, &self.target_prefix))
     }
 }
\ No newline at end of file
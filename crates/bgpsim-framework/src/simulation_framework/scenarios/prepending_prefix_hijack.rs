@@ -0,0 +1,113 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use ipnetwork::IpNetwork;
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::simulation_engine::SimulationEngine;
+use bgpsim_core::route_validator::{ROA, RouteValidator};
+use bgpsim_core::shared::{Outcomes, Relationships, Timestamps};
+use bgpsim_core::simulation_engine::{Announcement, Prefix};
+use crate::simulation_framework::scenario::{ScenarioTrait, origin_roas, route_validity_counts, share_route_validator};
+
+use super::{data_plane_outcomes, prefix_hijack_succeeded};
+
+/// Prefix hijack against a victim that prepends its ASN for traffic engineering
+///
+/// Every prepend lengthens the victim's route by a hop while the attacker's stays short,
+/// so the attacker wins ties the victim would otherwise have won
+pub struct PrependingPrefixHijack {
+    pub attacker_asns: HashSet<ASN>,
+    pub legitimate_origin_asns: HashSet<ASN>,
+    pub target_prefix: Prefix,
+    /// Extra copies of its ASN each legitimate origin puts on its path
+    pub victim_prepends: usize,
+}
+
+impl PrependingPrefixHijack {
+    pub fn new(attacker_asns: HashSet<ASN>, legitimate_origin_asns: HashSet<ASN>) -> Self {
+        PrependingPrefixHijack {
+            attacker_asns,
+            legitimate_origin_asns,
+            target_prefix: IpNetwork::from_str("1.2.3.0/24").unwrap(),
+            victim_prepends: 2,
+        }
+    }
+
+    pub fn with_victim_prepends(mut self, prepends: usize) -> Self {
+        self.victim_prepends = prepends;
+        self
+    }
+
+    /// Where each AS's traffic to the target prefix ends up
+    pub fn data_plane_outcomes(&self, engine: &SimulationEngine) -> HashMap<ASN, Outcomes> {
+        data_plane_outcomes(engine, &self.attacker_asns, &self.legitimate_origin_asns, self.get_dest_ip_addr())
+    }
+}
+
+impl ScenarioTrait for PrependingPrefixHijack {
+    fn name(&self) -> &str {
+        "PrependingPrefixHijack"
+    }
+
+    fn get_attacker_asns(&self, _as_graph: &ASGraph) -> HashSet<ASN> {
+        self.attacker_asns.clone()
+    }
+
+    fn get_legitimate_origin_asns(&self, _as_graph: &ASGraph) -> HashSet<ASN> {
+        self.legitimate_origin_asns.clone()
+    }
+
+    fn get_seed_asn_ann_dict(&self, _as_graph: &ASGraph) -> HashMap<ASN, Vec<Announcement>> {
+        let mut seed_dict = HashMap::new();
+
+        for &asn in &self.legitimate_origin_asns {
+            let ann = Announcement::new_with_path(
+                self.target_prefix,
+                vec![],
+                asn,
+                Relationships::Origin,
+                Timestamps::VICTIM,
+            ).with_prepending(self.victim_prepends);
+            seed_dict.insert(asn, vec![ann]);
+        }
+
+        for &asn in &self.attacker_asns {
+            let ann = Announcement::new_with_path(
+                self.target_prefix,
+                vec![],
+                asn,
+                Relationships::Origin,
+                Timestamps::ATTACKER,
+            );
+            seed_dict.insert(asn, vec![ann]);
+        }
+
+        seed_dict
+    }
+
+    fn get_roas(&self, _as_graph: &ASGraph) -> Vec<ROA> {
+        origin_roas(self.target_prefix, &self.legitimate_origin_asns, Some(24))
+    }
+
+    fn setup_engine(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator) {
+        *route_validator = RouteValidator::new();
+        route_validator.add_roas(self.get_roas(engine.as_graph));
+
+        let seeds: Vec<(ASN, Announcement)> = self.get_seed_asn_ann_dict(engine.as_graph).into_iter()
+            .flat_map(|(asn, anns)| anns.into_iter().map(move |ann| (asn, ann)))
+            .collect();
+        share_route_validator(engine, route_validator, &seeds);
+        engine.setup(seeds);
+    }
+
+    fn is_successful(&self, engine: &SimulationEngine) -> bool {
+        let outcomes = self.data_plane_outcomes(engine);
+        let hijacked = outcomes.values().filter(|&&outcome| outcome == Outcomes::AttackerSuccess).count();
+        prefix_hijack_succeeded(engine, hijacked)
+    }
+
+    fn analyze(&self, engine: &SimulationEngine, route_validator: &RouteValidator) -> Option<serde_json::Value> {
+        Some(route_validity_counts(engine, route_validator, &self.target_prefix))
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::{HashMap, HashSet};
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::simulation_engine::SimulationEngine;
+use bgpsim_core::route_validator::{ROA, RouteValidator};
+use bgpsim_core::shared::{Outcomes, Relationships, Timestamps};
+use bgpsim_core::simulation_engine::{ActionCommunity, Announcement, ExportRestrictions};
+use crate::simulation_framework::scenario::{CustomMetrics, ScenarioTrait, data_plane_outcomes, share_route_validator};
+
+use super::SubprefixHijack;
+
+/// Subprefix hijack where the victim asks ASes to blackhole the hijacked subprefix (RTBH)
+///
+/// The victim announces the subprefix only to the blackholing ASes, tagged with a blackhole community.
+/// Since the subprefix is longer than the ROA's max length, ROV-adopting blackholing ASes reject the request
+pub struct RTBHSubprefixHijack {
+    pub hijack: SubprefixHijack,
+    /// ASes asked to blackhole (None asks every provider of the victims)
+    pub blackhole_asns: Option<HashSet<ASN>>,
+}
+
+impl RTBHSubprefixHijack {
+    pub fn new(attacker_asns: HashSet<ASN>, legitimate_origin_asns: HashSet<ASN>) -> Self {
+        RTBHSubprefixHijack {
+            hijack: SubprefixHijack::new(attacker_asns, legitimate_origin_asns),
+            blackhole_asns: None,
+        }
+    }
+
+    pub fn with_blackhole_asns(mut self, asns: HashSet<ASN>) -> Self {
+        self.blackhole_asns = Some(asns);
+        self
+    }
+
+    /// ASes a victim sends its RTBH request to
+    pub fn get_blackhole_asns(&self, as_graph: &ASGraph, victim_asn: ASN) -> HashSet<ASN> {
+        match &self.blackhole_asns {
+            Some(asns) => asns.clone(),
+            None => as_graph.get(&victim_asn)
+                .map(|victim| victim.providers().iter().map(|provider| provider.asn).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Where each AS's traffic to the hijacked subprefix ends up
+    pub fn data_plane_outcomes(&self, engine: &SimulationEngine) -> HashMap<ASN, Outcomes> {
+        data_plane_outcomes(
+            engine,
+            &self.hijack.attacker_asns,
+            &self.hijack.legitimate_origin_asns,
+            self.get_dest_ip_addr(),
+        )
+    }
+}
+
+impl ScenarioTrait for RTBHSubprefixHijack {
+    fn name(&self) -> &str {
+        "RTBHSubprefixHijack"
+    }
+
+    fn get_attacker_asns(&self, as_graph: &ASGraph) -> HashSet<ASN> {
+        self.hijack.get_attacker_asns(as_graph)
+    }
+
+    fn get_legitimate_origin_asns(&self, as_graph: &ASGraph) -> HashSet<ASN> {
+        self.hijack.get_legitimate_origin_asns(as_graph)
+    }
+
+    fn get_seed_asn_ann_dict(&self, as_graph: &ASGraph) -> HashMap<ASN, Vec<Announcement>> {
+        let mut seed_dict = self.hijack.get_seed_asn_ann_dict(as_graph);
+
+        // Victims also announce the hijacked subprefix, asking for it to be blackholed
+        for &asn in &self.hijack.legitimate_origin_asns {
+            let communities = self.get_blackhole_asns(as_graph, asn).into_iter()
+                .map(ActionCommunity::blackhole)
+                .collect();
+            let ann = Announcement::new_with_path(
+                self.hijack.hijacked_prefix,
+                vec![],
+                asn,
+                Relationships::Origin,
+                Timestamps::VICTIM,
+            ).with_action_communities(communities);
+            seed_dict.entry(asn).or_default().push(ann);
+        }
+
+        seed_dict
+    }
+
+    fn get_roas(&self, as_graph: &ASGraph) -> Vec<ROA> {
+        self.hijack.get_roas(as_graph)
+    }
+
+    fn get_seed_export_restrictions(&self, as_graph: &ASGraph) -> ExportRestrictions {
+        let mut restrictions = self.hijack.get_seed_export_restrictions(as_graph);
+        for &asn in &self.hijack.legitimate_origin_asns {
+            restrictions.insert((asn, self.hijack.hijacked_prefix), self.get_blackhole_asns(as_graph, asn));
+        }
+        restrictions
+    }
+
+    fn setup_engine(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator) {
+        // Clear and add ROAs
+        *route_validator = RouteValidator::new();
+        route_validator.add_roas(self.get_roas(engine.as_graph));
+
+        // Seed announcements
+        let mut initial_anns = Vec::new();
+        for (asn, anns) in self.get_seed_asn_ann_dict(engine.as_graph) {
+            for ann in anns {
+                initial_anns.push((asn, ann));
+            }
+        }
+
+        engine.origin_export_restrictions = self.get_seed_export_restrictions(engine.as_graph);
+        share_route_validator(engine, route_validator, &initial_anns);
+        engine.setup(initial_anns);
+    }
+
+    fn is_successful(&self, engine: &SimulationEngine) -> bool {
+        // Success if attacker traffic still reaches the attacker from more than 50% of ASes
+        let outcomes = self.data_plane_outcomes(engine);
+        let hijacked = outcomes.values().filter(|&&outcome| outcome == Outcomes::AttackerSuccess).count();
+        hijacked as f64 / engine.as_graph.len() as f64 > 0.5
+    }
+
+    fn register_custom_metrics(&self, metrics: &mut CustomMetrics) {
+        for (name, outcome) in [
+            ("hijacked_fraction", Outcomes::AttackerSuccess),
+            ("victim_reached_fraction", Outcomes::VictimSuccess),
+            ("blackholed_fraction", Outcomes::Blackholed),
+            ("disconnected_fraction", Outcomes::DisconnectedOrigin),
+        ] {
+            let attacker_asns = self.hijack.attacker_asns.clone();
+            let legitimate_origin_asns = self.hijack.legitimate_origin_asns.clone();
+            let dest_ip_addr = self.get_dest_ip_addr();
+
+            metrics.register(name.to_string(), move |engine| {
+                let outcomes = data_plane_outcomes(engine, &attacker_asns, &legitimate_origin_asns, dest_ip_addr);
+                let count = outcomes.values().filter(|&&o| o == outcome || (o.is_disconnected() && outcome.is_disconnected())).count();
+                count as f64 / engine.as_graph.len().max(1) as f64
+            });
+        }
+    }
+}
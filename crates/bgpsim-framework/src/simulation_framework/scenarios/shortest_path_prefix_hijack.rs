@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+
+use ipnetwork::IpNetwork;
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::aspa_validator::AspaValidator;
+use bgpsim_core::simulation_engine::SimulationEngine;
+use bgpsim_core::route_validator::{ROA, RouteValidator};
+use bgpsim_core::shared::{Relationships, Settings, Timestamps};
+use bgpsim_core::simulation_engine::{Announcement, Prefix};
+use crate::simulation_framework::scenario::{ScenarioTrait, origin_roas, route_validity_counts, share_route_validator};
+
+use super::prefix_hijack_succeeded;
+
+/// Shortest path export-all hijack, as in bgpy's ShortestPathPrefixHijack
+/// The attacker announces the legitimate prefix with the shortest forged path the deployed
+/// defenses accept: the legitimate origin alone against ROV, one of its neighbors before it
+/// against Path-End, and its attested provider chain up to an AS without an ASPA against ASPA
+pub struct ShortestPathPrefixHijack {
+    pub attacker_asns: HashSet<ASN>,
+    pub legitimate_origin_asns: HashSet<ASN>,
+    pub target_prefix: Prefix,
+}
+
+impl ShortestPathPrefixHijack {
+    pub fn new(attacker_asns: HashSet<ASN>, legitimate_origin_asns: HashSet<ASN>) -> Self {
+        ShortestPathPrefixHijack {
+            attacker_asns,
+            legitimate_origin_asns,
+            target_prefix: IpNetwork::from_str("1.2.3.0/24").unwrap(),
+        }
+    }
+
+    /// Path the attackers claim after themselves, ending at the legitimate origin with the lowest ASN
+    /// Chosen against the policies `engine` runs, so call after the adoption settings are applied
+    pub fn forged_path(&self, engine: &SimulationEngine) -> Vec<ASN> {
+        let Some(&victim) = self.legitimate_origin_asns.iter().min() else {
+            return Vec::new();
+        };
+        let deployed: HashSet<Settings> = engine.policy_store.iter().map(|(_, policy)| policy.settings).collect();
+        // Path-End checks the origin's neighbor, so it must be followed by a real one
+        let min_hops = usize::from(deployed.contains(&Settings::PathEnd));
+        let aspa_validator = engine.aspa_validator().filter(|_| deployed.contains(&Settings::Aspa));
+        self.shortest_valid_chain(engine.as_graph, aspa_validator, victim, min_hops)
+    }
+
+    /// Breadth-first search up from the victim for the shortest chain whose top AS
+    /// has no ASPA (or attests no providers at all), so no check fails on the attacker's hop
+    fn shortest_valid_chain(
+        &self,
+        as_graph: &ASGraph,
+        aspa_validator: Option<&AspaValidator>,
+        victim: ASN,
+        min_hops: usize,
+    ) -> Vec<ASN> {
+        let mut parents: HashMap<ASN, ASN> = HashMap::new();
+        let mut queue = VecDeque::from([(victim, 0)]);
+        let mut visited = HashSet::from([victim]);
+
+        while let Some((asn, hops)) = queue.pop_front() {
+            let attested = aspa_validator.and_then(|validator| validator.providers(asn));
+            let terminal = attested.is_none_or(HashSet::is_empty);
+            if terminal && hops >= min_hops {
+                // Walk back down to the victim, top of the chain first
+                let mut chain = vec![asn];
+                while let Some(&parent) = parents.get(chain.last().unwrap()) {
+                    chain.push(parent);
+                }
+                return chain;
+            }
+
+            let mut next: Vec<ASN> = match attested {
+                Some(providers) => providers.iter().copied().collect(),
+                // Only the victim before Path-End's hop; any neighbor will do if it has no providers
+                None => as_graph.get(&asn).map_or_else(Vec::new, |as_obj| {
+                    if as_obj.providers().is_empty() {
+                        as_obj.neighbors().map(|neighbor| neighbor.asn).collect()
+                    } else {
+                        as_obj.providers().iter().map(|provider| provider.asn).collect()
+                    }
+                }),
+            };
+            next.sort_unstable();
+            for neighbor in next {
+                if !self.attacker_asns.contains(&neighbor) && visited.insert(neighbor) {
+                    parents.insert(neighbor, asn);
+                    queue.push_back((neighbor, hops + 1));
+                }
+            }
+        }
+        // Nothing passes every check, so fall back to spoofing the origin
+        vec![victim]
+    }
+
+    fn seed_asn_ann_dict(&self, forged_path: &[ASN]) -> HashMap<ASN, Vec<Announcement>> {
+        let mut seed_dict = HashMap::new();
+
+        for &asn in &self.legitimate_origin_asns {
+            let ann = Announcement::new_with_path(
+                self.target_prefix,
+                vec![],  // Empty AS path for origin
+                asn,
+                Relationships::Origin,
+                Timestamps::VICTIM,
+            );
+            seed_dict.insert(asn, vec![ann]);
+        }
+
+        for &asn in &self.attacker_asns {
+            let mut as_path = vec![asn];
+            as_path.extend_from_slice(forged_path);
+            let ann = Announcement::new_with_path(
+                self.target_prefix,
+                as_path,
+                asn,
+                Relationships::Origin,
+                Timestamps::ATTACKER,
+            );
+            seed_dict.insert(asn, vec![ann]);
+        }
+
+        seed_dict
+    }
+
+    fn routes_through_attacker(&self, asn: ASN, ann: &Announcement) -> bool {
+        self.attacker_asns.contains(&asn) || ann.as_path.iter().any(|hop| self.attacker_asns.contains(hop))
+    }
+}
+
+impl ScenarioTrait for ShortestPathPrefixHijack {
+    fn name(&self) -> &str {
+        "ShortestPathPrefixHijack"
+    }
+
+    fn get_attacker_asns(&self, _as_graph: &ASGraph) -> HashSet<ASN> {
+        self.attacker_asns.clone()
+    }
+
+    fn get_legitimate_origin_asns(&self, _as_graph: &ASGraph) -> HashSet<ASN> {
+        self.legitimate_origin_asns.clone()
+    }
+
+    /// Seeds with a spoofed origin, the strongest attack against BGP and ROV
+    /// `setup_engine` picks the path against the policies actually deployed
+    fn get_seed_asn_ann_dict(&self, _as_graph: &ASGraph) -> HashMap<ASN, Vec<Announcement>> {
+        let forged_path: Vec<ASN> = self.legitimate_origin_asns.iter().min().copied().into_iter().collect();
+        self.seed_asn_ann_dict(&forged_path)
+    }
+
+    fn get_roas(&self, _as_graph: &ASGraph) -> Vec<ROA> {
+        origin_roas(self.target_prefix, &self.legitimate_origin_asns, Some(24))
+    }
+
+    fn setup_engine(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator) {
+        *route_validator = RouteValidator::new();
+        route_validator.add_roas(self.get_roas(engine.as_graph));
+
+        let forged_path = self.forged_path(engine);
+        tracing::debug!(?forged_path, "Shortest path the defenses accept");
+        let seeds: Vec<(ASN, Announcement)> = self.seed_asn_ann_dict(&forged_path).into_iter()
+            .flat_map(|(asn, anns)| anns.into_iter().map(move |ann| (asn, ann)))
+            .collect();
+        share_route_validator(engine, route_validator, &seeds);
+        engine.setup(seeds);
+    }
+
+    fn is_successful(&self, engine: &SimulationEngine) -> bool {
+        // The forged routes keep the legitimate origin, so count routes through an attacker instead
+        let attacker_reach = engine.policy_store.iter()
+            .filter(|(asn, policy)| {
+                policy.local_rib.get(&self.target_prefix)
+                    .is_some_and(|ann| self.routes_through_attacker(**asn, ann))
+            })
+            .count();
+        prefix_hijack_succeeded(engine, attacker_reach)
+    }
+
+    fn analyze(&self, engine: &SimulationEngine, route_validator: &RouteValidator) -> Option<serde_json::Value> {
+        Some(route_validity_counts(engine, route_validator, &self.target_prefix))
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use ipnetwork::IpNetwork;
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::simulation_engine::SimulationEngine;
+use bgpsim_core::route_validator::{ROA, RouteValidator};
+use bgpsim_core::shared::{Outcomes, Relationships, Timestamps};
+use bgpsim_core::simulation_engine::{Announcement, ExportRestrictions, Prefix};
+use crate::simulation_framework::scenario::{ScenarioTrait, origin_roas, route_validity_counts, share_route_validator};
+
+use super::{data_plane_outcomes, prefix_hijack_succeeded};
+
+/// Attacker announces a less specific prefix covering the victim's
+///
+/// The superprefix has no ROA, so ROV can't filter it. It only wins traffic at ASes
+/// without a route to the victim's more specific prefix, e.g. when the victim restricts its exports
+pub struct SuperprefixHijack {
+    pub attacker_asns: HashSet<ASN>,
+    pub legitimate_origin_asns: HashSet<ASN>,
+    pub victim_prefix: Prefix,
+    /// Prefix the attackers announce, covering `victim_prefix`
+    pub superprefix: Prefix,
+    /// Neighbors the legitimate origins export to (None exports to all neighbors)
+    victim_export_asns: Option<HashSet<ASN>>,
+}
+
+impl SuperprefixHijack {
+    pub fn new(attacker_asns: HashSet<ASN>, legitimate_origin_asns: HashSet<ASN>) -> Self {
+        SuperprefixHijack {
+            attacker_asns,
+            legitimate_origin_asns,
+            victim_prefix: IpNetwork::from_str("1.2.3.0/24").unwrap(),
+            superprefix: IpNetwork::from_str("1.2.0.0/16").unwrap(),
+            victim_export_asns: None,
+        }
+    }
+
+    /// Only announce the victim's prefix to these neighbors, leaving the rest to the superprefix
+    pub fn with_victim_export_asns(mut self, asns: HashSet<ASN>) -> Self {
+        self.victim_export_asns = Some(asns);
+        self
+    }
+
+    /// Where each AS's traffic to the victim's prefix ends up under longest prefix match
+    pub fn data_plane_outcomes(&self, engine: &SimulationEngine) -> HashMap<ASN, Outcomes> {
+        data_plane_outcomes(engine, &self.attacker_asns, &self.legitimate_origin_asns, self.get_dest_ip_addr())
+    }
+}
+
+impl ScenarioTrait for SuperprefixHijack {
+    fn name(&self) -> &str {
+        "SuperprefixHijack"
+    }
+
+    fn get_attacker_asns(&self, _as_graph: &ASGraph) -> HashSet<ASN> {
+        self.attacker_asns.clone()
+    }
+
+    fn get_legitimate_origin_asns(&self, _as_graph: &ASGraph) -> HashSet<ASN> {
+        self.legitimate_origin_asns.clone()
+    }
+
+    fn get_seed_asn_ann_dict(&self, _as_graph: &ASGraph) -> HashMap<ASN, Vec<Announcement>> {
+        let mut seed_dict = HashMap::new();
+
+        for &asn in &self.legitimate_origin_asns {
+            let ann = Announcement::new_with_path(
+                self.victim_prefix,
+                vec![],
+                asn,
+                Relationships::Origin,
+                Timestamps::VICTIM,
+            );
+            seed_dict.insert(asn, vec![ann]);
+        }
+
+        for &asn in &self.attacker_asns {
+            let ann = Announcement::new_with_path(
+                self.superprefix,
+                vec![],
+                asn,
+                Relationships::Origin,
+                Timestamps::ATTACKER,
+            );
+            seed_dict.insert(asn, vec![ann]);
+        }
+
+        seed_dict
+    }
+
+    fn get_roas(&self, _as_graph: &ASGraph) -> Vec<ROA> {
+        origin_roas(self.victim_prefix, &self.legitimate_origin_asns, Some(24))
+    }
+
+    fn get_seed_export_restrictions(&self, _as_graph: &ASGraph) -> ExportRestrictions {
+        let mut restrictions = ExportRestrictions::new();
+        if let Some(export_asns) = &self.victim_export_asns {
+            for &asn in &self.legitimate_origin_asns {
+                restrictions.insert((asn, self.victim_prefix), export_asns.clone());
+            }
+        }
+        restrictions
+    }
+
+    fn setup_engine(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator) {
+        *route_validator = RouteValidator::new();
+        route_validator.add_roas(self.get_roas(engine.as_graph));
+
+        let seeds: Vec<(ASN, Announcement)> = self.get_seed_asn_ann_dict(engine.as_graph).into_iter()
+            .flat_map(|(asn, anns)| anns.into_iter().map(move |ann| (asn, ann)))
+            .collect();
+        engine.origin_export_restrictions = self.get_seed_export_restrictions(engine.as_graph);
+        share_route_validator(engine, route_validator, &seeds);
+        engine.setup(seeds);
+    }
+
+    fn is_successful(&self, engine: &SimulationEngine) -> bool {
+        let outcomes = self.data_plane_outcomes(engine);
+        let hijacked = outcomes.values().filter(|&&outcome| outcome == Outcomes::AttackerSuccess).count();
+        prefix_hijack_succeeded(engine, hijacked)
+    }
+
+    fn analyze(&self, engine: &SimulationEngine, route_validator: &RouteValidator) -> Option<serde_json::Value> {
+        Some(route_validity_counts(engine, route_validator, &self.superprefix))
+    }
+}
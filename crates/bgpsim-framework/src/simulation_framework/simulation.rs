@@ -0,0 +1,587 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use bgpsim_core::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsim_core::as_graphs::metrics::GraphMetrics;
+use bgpsim_core::simulation_engine::{PolicyStorageMode, SimulationEngine};
+use bgpsim_core::route_validator::RouteValidator;
+use bgpsim_core::shared::{Outcomes, Settings};
+use bgpsim_core::Error;
+
+use super::adopter_draws::AdopterDraws;
+use super::checkpoint::{Checkpoint, TrialRecord};
+use super::run_metadata::RunMetadata;
+use super::data_tracker::{DataTracker, SimulationSummary};
+use super::pollution::PollutionObserver;
+use super::scenario::{Scenario, ScenarioTrait};
+use super::scenario_config::{ScenarioConfig, VictimSampling};
+use super::scenarios::{control_plane_outcomes, create_scenario, data_plane_outcomes, max_length_vulnerable_prefixes, preprocess_roas};
+use super::sweep::{SweepDimension, expand_configs};
+
+pub struct Simulation {
+    /// Output directory for results
+    pub output_dir: PathBuf,
+    
+    /// Percentages of ASes randomly adopting for each run
+    pub percent_ases_randomly_adopting: Vec<f64>,
+    
+    /// Scenario configurations to run
+    pub scenario_configs: Vec<ScenarioConfig>,
+    
+    /// Number of trials per configuration
+    pub num_trials: usize,
+    
+    /// Number of CPU cores to use for parallel processing
+    pub parse_cpus: usize,
+    
+    /// AS graph to use for simulations
+    pub as_graph: ASGraph,
+    
+    /// Iterate the victim over sampled ASes instead of running `num_trials` random trials
+    pub victim_sampling: Option<VictimSampling>,
+    
+    /// Routing tables every trial keeps, overriding each scenario config's storage mode
+    /// `LocalRibOnly` is much faster on scenarios without withdrawals
+    pub storage_mode: Option<PolicyStorageMode>,
+    
+    /// Adopters drawn per (percentage, trial) and reused by every scenario config, for paired comparisons
+    /// Configs that override their adopting ASNs keep them
+    pub adopter_draws: Option<Arc<Mutex<AdopterDraws>>>,
+    
+    /// File the adopter draws are loaded from before running, if it exists, and saved to afterwards
+    pub adopter_draws_file: Option<PathBuf>,
+    
+    /// Save finished trials to the output directory's checkpoint after every this many, and after each percentage
+    pub checkpoint_every: Option<usize>,
+    
+    /// Finished trials, which are replayed instead of run again, see `resume`
+    pub checkpoint: Mutex<Checkpoint>,
+    
+    /// Date of the CAIDA snapshot the AS graph was built from, recorded in the run metadata
+    pub caida_snapshot_date: Option<String>,
+    
+    /// Incremented after every finished trial, for watching a run from another thread
+    pub progress: Option<Arc<AtomicUsize>>,
+    
+    /// Percentages of ASes registering ASPA objects to sweep, crossed with the adoption percentages
+    /// Each scenario config runs once per percentage; empty leaves registration to the configs
+    pub sweep_dimensions: Vec<SweepDimension>,
+    
+    /// Origins propagated from for the topology report's path lengths; 0 skips them
+    pub path_length_samples: usize,
+    
+    /// Record how many ASes each trial's hijack polluted after every round, as the `polluted_ases` time series
+    /// Off by default, since observing propagation slows trials down
+    pub track_pollution: bool,
+}
+
+impl Simulation {
+    /// Checkpoint interval of resumed simulations that didn't set one
+    pub const DEFAULT_CHECKPOINT_EVERY: usize = 10;
+    
+    /// Written to the output directory by `run`, see `GraphMetrics`
+    pub const TOPOLOGY_REPORT_FILE: &'static str = "topology_report.json";
+    
+    pub fn new(as_graph: ASGraph) -> Self {
+        let output_dir = dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("Desktop")
+            .join("sims")
+            .join("bgpsimulator_rust");
+            
+        Simulation {
+            output_dir,
+            percent_ases_randomly_adopting: vec![10.0, 20.0, 50.0, 80.0, 99.0],
+            scenario_configs: vec![
+                ScenarioConfig::new(
+                    "Subprefix Hijack; ROV Adopting".to_string(),
+                    "SubprefixHijack".to_string(),
+                ).with_adoption_setting(Settings::Rov, true)
+            ],
+            num_trials: 10,
+            parse_cpus: num_cpus::get().max(1) - 1,
+            as_graph,
+            victim_sampling: None,
+            storage_mode: None,
+            adopter_draws: None,
+            adopter_draws_file: None,
+            checkpoint_every: None,
+            checkpoint: Mutex::new(Checkpoint::new()),
+            caida_snapshot_date: None,
+            progress: None,
+            sweep_dimensions: Vec::new(),
+            path_length_samples: 10,
+            track_pollution: false,
+        }
+    }
+    
+    pub fn with_output_dir(mut self, dir: PathBuf) -> Self {
+        self.output_dir = dir;
+        self
+    }
+    
+    pub fn with_adoption_percentages(mut self, percentages: Vec<f64>) -> Self {
+        self.percent_ases_randomly_adopting = percentages;
+        self
+    }
+    
+    pub fn with_scenario_configs(mut self, configs: Vec<ScenarioConfig>) -> Self {
+        self.scenario_configs = configs;
+        self
+    }
+    
+    /// Sweep ASPA object registration as a second axis, separately from who validates
+    pub fn with_aspa_object_percentages(self, percentages: Vec<f64>) -> Self {
+        self.with_sweep_dimension(SweepDimension::AspaObjects(percentages))
+    }
+    
+    /// Also sweep `dimension`, running every combination of the dimensions' values
+    pub fn with_sweep_dimension(mut self, dimension: SweepDimension) -> Self {
+        self.sweep_dimensions.push(dimension);
+        self
+    }
+    
+    /// Scenario configs to run, one per combination of sweep dimension values
+    /// Each gets its own label, so its results, summary and checkpointed trials stay separate
+    pub fn expanded_scenario_configs(&self) -> Vec<ScenarioConfig> {
+        expand_configs(&self.scenario_configs, &self.sweep_dimensions)
+    }
+    
+    pub fn with_num_trials(mut self, trials: usize) -> Self {
+        self.num_trials = trials;
+        self
+    }
+    
+    pub fn with_victim_sampling(mut self, sampling: VictimSampling) -> Self {
+        self.victim_sampling = Some(sampling);
+        self
+    }
+    
+    pub fn with_storage_mode(mut self, storage_mode: PolicyStorageMode) -> Self {
+        self.storage_mode = Some(storage_mode);
+        self
+    }
+    
+    /// Reuse the same adopter draws across scenario configs
+    pub fn with_paired_adoption(self) -> Self {
+        self.with_adopter_draws(Arc::new(Mutex::new(AdopterDraws::new())))
+    }
+    
+    /// Draw adopters from `draws`, which can be shared with other simulations on the same graph
+    pub fn with_adopter_draws(mut self, draws: Arc<Mutex<AdopterDraws>>) -> Self {
+        self.adopter_draws = Some(draws);
+        self
+    }
+    
+    /// Reuse adopter draws across scenario configs and across runs through `path`
+    pub fn with_adopter_draws_file(mut self, path: PathBuf) -> Self {
+        self.adopter_draws_file = Some(path);
+        if self.adopter_draws.is_none() {
+            self = self.with_paired_adoption();
+        }
+        self
+    }
+    
+    pub fn with_caida_snapshot_date(mut self, date: String) -> Self {
+        self.caida_snapshot_date = Some(date);
+        self
+    }
+    
+    pub fn with_progress(mut self, progress: Arc<AtomicUsize>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+    
+    pub fn with_path_length_samples(mut self, num_origins: usize) -> Self {
+        self.path_length_samples = num_origins;
+        self
+    }
+    
+    pub fn with_pollution_tracking(mut self, track: bool) -> Self {
+        self.track_pollution = track;
+        self
+    }
+    
+    /// Number of trials `run` goes through, unless victim sampling skips some
+    pub fn total_trials(&self) -> usize {
+        let trials_per_percentage = match &self.victim_sampling {
+            Some(sampling) => sampling.sample_victims(&self.as_graph, None).len() * sampling.trials_per_victim,
+            None => self.num_trials,
+        };
+        self.expanded_scenario_configs().len() * self.percent_ases_randomly_adopting.len() * trials_per_percentage
+    }
+    
+    /// Checkpoint finished trials so a crashed run can be picked up with `resume`
+    pub fn with_checkpointing(mut self, every: usize) -> Self {
+        self.checkpoint_every = Some(every.max(1));
+        self
+    }
+    
+    /// Continue the simulation checkpointed in `output_dir`, skipping the trials it already finished
+    /// The simulation must be configured as it was for the first run
+    pub fn resume(mut self, output_dir: PathBuf) -> Result<Self, Error> {
+        let path = Checkpoint::path(&output_dir);
+        let checkpoint = Checkpoint::load_from_file(&path).map_err(Error::Parse)?;
+        if let Some(draws) = &checkpoint.adopter_draws {
+            draws.check_graph(&self.as_graph).map_err(Error::ConfigInvalid)?;
+            self = self.with_adopter_draws(Arc::new(Mutex::new(draws.clone())));
+        }
+        tracing::info!(path = ?path, "Resuming from checkpoint");
+        self.output_dir = output_dir;
+        self.checkpoint = Mutex::new(checkpoint);
+        self.checkpoint_every.get_or_insert(Self::DEFAULT_CHECKPOINT_EVERY);
+        Ok(self)
+    }
+    
+    /// Run the complete simulation
+    pub fn run(&self) -> Result<(), Error> {
+        tracing::info!(output_dir = ?self.output_dir, "Running BGP simulations");
+        std::fs::create_dir_all(&self.output_dir)?;
+        
+        let start_time = Instant::now();
+        self.load_adopter_draws()?;
+        
+        // Written up front so crashed runs leave a manifest too, then again with the wall-clock time
+        let mut metadata = RunMetadata::collect(self);
+        metadata.save_to_file(&self.output_dir)?;
+        self.save_topology_report()?;
+        
+        // Run each scenario configuration
+        for scenario_config in &self.expanded_scenario_configs() {
+            let _span = tracing::info_span!("scenario", label = %scenario_config.label).entered();
+            tracing::info!("Running scenario");
+            self.run_scenario(scenario_config)?;
+        }
+        self.save_adopter_draws()?;
+        
+        let duration = start_time.elapsed();
+        metadata.finish(duration);
+        metadata.save_to_file(&self.output_dir)?;
+        tracing::info!(seconds = duration.as_secs_f64(), "Simulation complete");
+        
+        Ok(())
+    }
+    
+    /// Write the AS graph's `GraphMetrics` to the output directory, so odd input graphs stand out
+    fn save_topology_report(&self) -> Result<(), Error> {
+        let mut metrics = GraphMetrics::compute(&self.as_graph);
+        // Fixed seed so every run on the same graph reports the same path lengths
+        metrics.sample_path_lengths(&self.as_graph, self.path_length_samples, 0);
+        std::fs::write(
+            self.output_dir.join(Self::TOPOLOGY_REPORT_FILE),
+            serde_json::to_string_pretty(&metrics.to_json())?,
+        )?;
+        Ok(())
+    }
+    
+    /// Replace the adopter draws with the saved ones, if the file exists
+    fn load_adopter_draws(&self) -> Result<(), Error> {
+        let (Some(draws), Some(path)) = (&self.adopter_draws, &self.adopter_draws_file) else {
+            return Ok(());
+        };
+        if !path.exists() {
+            return Ok(());
+        }
+        let loaded = AdopterDraws::load_from_file(path).map_err(Error::Parse)?;
+        loaded.check_graph(&self.as_graph).map_err(Error::ConfigInvalid)?;
+        tracing::info!(path = ?path, "Loaded adopter draws");
+        *draws.lock().unwrap() = loaded;
+        Ok(())
+    }
+    
+    fn save_adopter_draws(&self) -> Result<(), Error> {
+        if let (Some(draws), Some(path)) = (&self.adopter_draws, &self.adopter_draws_file) {
+            draws.lock().unwrap().save_to_file(path)?;
+        }
+        Ok(())
+    }
+    
+    fn save_checkpoint(&self) -> Result<(), Error> {
+        let mut checkpoint = self.checkpoint.lock().unwrap();
+        checkpoint.adopter_draws = self.adopter_draws.as_ref().map(|draws| draws.lock().unwrap().clone());
+        checkpoint.save_to_file(&Checkpoint::path(&self.output_dir))?;
+        Ok(())
+    }
+    
+    /// Add `trial` from the checkpoint to `tracker`, returning false if it hasn't finished yet
+    fn replay_trial(&self, scenario_label: &str, percent: f64, trial: usize, tracker: &mut DataTracker) -> bool {
+        match self.checkpoint.lock().unwrap().get(scenario_label, percent, trial) {
+            Some(record) => {
+                record.add_to(tracker);
+                self.count_finished_trial();
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Add a trial that just ran to `tracker` and to the checkpoint, saving it when due
+    fn finish_trial(
+        &self,
+        scenario_label: &str,
+        percent: f64,
+        trial: usize,
+        record: TrialRecord,
+        tracker: &mut DataTracker,
+    ) -> Result<(), Error> {
+        record.add_to(tracker);
+        self.count_finished_trial();
+        let Some(every) = self.checkpoint_every else {
+            return Ok(());
+        };
+        let num_unsaved = {
+            let mut checkpoint = self.checkpoint.lock().unwrap();
+            checkpoint.record(scenario_label, percent, trial, record);
+            checkpoint.num_unsaved()
+        };
+        if num_unsaved >= every {
+            self.save_checkpoint()?;
+        }
+        Ok(())
+    }
+    
+    fn count_finished_trial(&self) {
+        if let Some(progress) = &self.progress {
+            progress.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    
+    /// The config with the shared adopters for `trial` at `percent`, if adopters are shared
+    /// Configs with measured adoption draw their own adopters, since shared draws are uniform
+    fn with_drawn_adopters(&self, scenario_config: &ScenarioConfig, percent: f64, trial: usize) -> ScenarioConfig {
+        let draws_own_adopters = scenario_config.override_adopting_asns.is_some() || scenario_config.measured_adoption.is_some();
+        match &self.adopter_draws {
+            Some(draws) if !draws_own_adopters => {
+                let adopting_asns = draws.lock().unwrap().get_or_draw(&self.as_graph, percent, trial).clone();
+                scenario_config.clone().with_adopting_asns(adopting_asns)
+            }
+            _ => scenario_config.clone(),
+        }
+    }
+    
+    /// Run a single scenario with all adoption percentages
+    fn run_scenario(&self, scenario_config: &ScenarioConfig) -> Result<(), Error> {
+        let mut summary = SimulationSummary::new(scenario_config.label.clone())
+            .with_parameters(scenario_config.sweep_parameters.clone());
+        
+        // Run for each adoption percentage
+        for &percent in &self.percent_ases_randomly_adopting {
+            let _span = tracing::info_span!("adoption", percent).entered();
+            tracing::info!("Running trials");
+            
+            let tracker = self.run_trials_for_percentage(scenario_config, percent)?;
+            let success_rate = tracker.success_rate();
+            
+            tracing::info!(success_rate, "Trials complete");
+            if let Some((mean, std_err)) = tracker.victim_protection_summary() {
+                tracing::info!(mean, std_err, num_victims = tracker.victim_outcomes.len(), "Victim protection");
+            }
+            summary.add_trials(percent, tracker.trial_success_values());
+            
+            // Save individual results
+            tracker.save_to_file(&self.output_dir)?;
+            if self.checkpoint_every.is_some() {
+                self.save_checkpoint()?;
+            }
+        }
+        
+        // Save summary
+        summary.save_to_file(&self.output_dir)?;
+        
+        Ok(())
+    }
+    
+    /// Run multiple trials for a specific adoption percentage
+    pub fn run_trials_for_percentage(
+        &self,
+        scenario_config: &ScenarioConfig,
+        percent: f64,
+    ) -> Result<DataTracker, Error> {
+        if let Some(sampling) = &self.victim_sampling {
+            return self.run_victim_trials(scenario_config, sampling, percent);
+        }
+        
+        let label = &scenario_config.label;
+        let mut tracker = DataTracker::new(label.clone(), percent)
+            .with_percent_aspa_objects(scenario_config.percent_aspa_objects)
+            .with_parameters(scenario_config.sweep_parameters.clone());
+        
+        // Create progress bar
+        let pb = progress_bar(self.num_trials, "trials");
+        
+        // Run trials
+        for trial_num in 0..self.num_trials {
+            pb.inc(1);
+            if self.replay_trial(label, percent, trial_num, &mut tracker) {
+                continue;
+            }
+            
+            // Create scenario for this trial
+            let scenario = Scenario::new(
+                self.with_drawn_adopters(scenario_config, percent, trial_num),
+                &self.as_graph,
+                percent,
+            );
+            
+            // Run the trial
+            let mut trial_tracker = DataTracker::new(label.clone(), percent);
+            let outcome = self.run_single_trial(&scenario, &mut trial_tracker)?;
+            let record = TrialRecord::from_tracker(&trial_tracker, outcome, None);
+            self.finish_trial(label, percent, trial_num, record, &mut tracker)?;
+        }
+        
+        pb.finish();
+        
+        Ok(tracker)
+    }
+    
+    /// Run `trials_per_victim` trials against each sampled victim
+    /// The attacker is the config's, if set, and is otherwise placed relative to each victim per trial
+    fn run_victim_trials(
+        &self,
+        scenario_config: &ScenarioConfig,
+        sampling: &VictimSampling,
+        percent: f64,
+    ) -> Result<DataTracker, Error> {
+        let label = &scenario_config.label;
+        let mut tracker = DataTracker::new(label.clone(), percent)
+            .with_percent_aspa_objects(scenario_config.percent_aspa_objects)
+            .with_parameters(scenario_config.sweep_parameters.clone());
+        let fixed_attacker_asns = scenario_config.override_attacker_asns.as_ref();
+        let victims = sampling.sample_victims(&self.as_graph, fixed_attacker_asns);
+        if victims.is_empty() {
+            return Err(Error::ConfigInvalid(format!("No victims to sample from {}", sampling.victim_group)));
+        }
+        
+        let pb = progress_bar(victims.len() * sampling.trials_per_victim, "victim trials");
+        
+        let mut num_unplaced = 0;
+        for (victim_index, &victim_asn) in victims.iter().enumerate() {
+            for victim_trial in 0..sampling.trials_per_victim {
+                pb.inc(1);
+                let trial = victim_index * sampling.trials_per_victim + victim_trial;
+                if self.replay_trial(label, percent, trial, &mut tracker) {
+                    continue;
+                }
+                
+                let attacker_asns = match fixed_attacker_asns {
+                    Some(asns) => asns.clone(),
+                    None => {
+                        let attacker_asns = sampling.attacker_placement
+                            .select_many(&self.as_graph, victim_asn, scenario_config.num_attackers);
+                        if attacker_asns.is_empty() {
+                            num_unplaced += 1;
+                            continue;
+                        }
+                        attacker_asns
+                    }
+                };
+                let config = self.with_drawn_adopters(scenario_config, percent, trial)
+                    .with_attacker_asns(attacker_asns)
+                    .with_legitimate_origin_asns(HashSet::from([victim_asn]));
+                let scenario = Scenario::new(config, &self.as_graph, percent);
+                
+                let mut trial_tracker = DataTracker::new(label.clone(), percent);
+                let outcome = self.run_single_trial(&scenario, &mut trial_tracker)?;
+                let record = TrialRecord::from_tracker(&trial_tracker, outcome, Some(victim_asn));
+                self.finish_trial(label, percent, trial, record, &mut tracker)?;
+            }
+        }
+        
+        pb.finish();
+        if num_unplaced > 0 {
+            tracing::warn!(num_unplaced, placement = %sampling.attacker_placement, "Skipped trials with no AS satisfying the attacker placement");
+        }
+        
+        Ok(tracker)
+    }
+    
+    /// Run a single trial of a scenario
+    fn run_single_trial(
+        &self,
+        scenario: &Scenario,
+        tracker: &mut DataTracker,
+    ) -> Result<Outcomes, Error> {
+        // Create a fresh engine for this trial
+        let storage_mode = self.storage_mode.unwrap_or(scenario.config.storage_mode);
+        let mut engine = SimulationEngine::new(&self.as_graph)
+            .with_storage_mode(storage_mode)
+            .with_tie_breaker(scenario.config.tie_breaker)
+            .with_next_hop_verification(scenario.config.next_hop_verification);
+        
+        // Apply adoption settings to policies, possibly varying by ASN group
+        let setting_counts = scenario.apply_adoption_settings(&mut engine);
+        let num_ases = setting_counts.values().sum::<usize>().max(1) as f64;
+        for (setting, count) in setting_counts {
+            tracker.add_adoption_metric(setting, count as f64 / num_ases * 100.0);
+        }
+        
+        // Setup the scenario in the engine
+        let scenario_impl = create_scenario(
+            &scenario.config.scenario_name,
+            scenario.attacker_asns.clone(),
+            scenario.legitimate_origin_asns.clone(),
+        )?;
+        let mut route_validator = RouteValidator::new();
+        scenario_impl.setup_engine(&mut engine, &mut route_validator);
+        preprocess_roas(&scenario.config, scenario_impl.as_ref(), &mut engine, &mut route_validator);
+        if scenario.config.models_loose_max_length() {
+            let seeds = scenario_impl.get_seed_asn_ann_dict(engine.as_graph);
+            let (num_vulnerable, num_prefixes) =
+                max_length_vulnerable_prefixes(&route_validator, &seeds, &scenario.legitimate_origin_asns);
+            tracker.add_max_length_vulnerability(num_vulnerable, num_prefixes);
+        }
+        
+        let dest_ip_addr = scenario_impl.get_dest_ip_addr();
+        let pollution = self.track_pollution.then(|| {
+            let observer = PollutionObserver::new(scenario.attacker_asns.clone(), dest_ip_addr);
+            let counts = observer.counts();
+            engine.add_observer(Box::new(observer));
+            counts
+        });
+        
+        // Run the simulation
+        let schedule = scenario_impl.get_events(engine.as_graph).merged(&scenario.config.event_schedule);
+        schedule.run(&mut engine, &mut route_validator, 100).map_err(Error::Propagation)?; // Run for up to 100 rounds
+        
+        tracker.record_custom_metrics(&scenario_impl.custom_metrics(), &engine);
+        if let Some(pollution) = pollution {
+            tracker.add_polluted_ases(&pollution.per_round());
+        }
+        
+        // Attackers and legitimate origins trivially reach themselves, so only the others are counted
+        let is_origin = |asn: &ASN| scenario.attacker_asns.contains(asn) || scenario.legitimate_origin_asns.contains(asn);
+        let mut control_plane = control_plane_outcomes(&engine, &scenario.attacker_asns, &scenario.legitimate_origin_asns, dest_ip_addr);
+        let mut data_plane = data_plane_outcomes(&engine, &scenario.attacker_asns, &scenario.legitimate_origin_asns, dest_ip_addr);
+        control_plane.retain(|asn, _| !is_origin(asn));
+        data_plane.retain(|asn, _| !is_origin(asn));
+        tracker.add_plane_outcomes(&control_plane, &data_plane);
+        
+        if scenario_impl.is_successful(&engine) {
+            Ok(Outcomes::AttackerSuccess)
+        } else {
+            Ok(Outcomes::VictimSuccess)
+        }
+    }
+}
+
+/// Progress bar over `len` of `what`, e.g. trials
+fn progress_bar(len: usize, what: &str) -> ProgressBar {
+    let style = ProgressStyle::default_bar()
+        .template(&format!("[{{elapsed_precise}}] {{bar:40}} {{pos}}/{{len}} {}", what))
+        .expect("progress bar templates are valid")
+        .progress_chars("##-");
+    ProgressBar::new(len as u64).with_style(style)
+}
+
+// External crates
+extern crate dirs;
+extern crate num_cpus;
+extern crate indicatif;
+extern crate serde_json;
\ No newline at end of file
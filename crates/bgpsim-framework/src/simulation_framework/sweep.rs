@@ -0,0 +1,101 @@
+use bgpsim_core::shared::Settings;
+
+use super::scenario_config::ScenarioConfig;
+
+/// One axis of a parameter sweep, swept on top of the adoption percentages
+///
+/// `Simulation` runs every scenario config once per combination of the values of its dimensions,
+/// and each run records the combination it came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum SweepDimension {
+    /// Number of attackers drawn per trial
+    NumAttackers(Vec<usize>),
+    /// Percentage of ASes registering ASPA objects, separately from who validates
+    AspaObjects(Vec<f64>),
+    /// Scenario run with the config's settings, e.g. "SubprefixHijack" and "PrefixHijack"
+    Scenarios(Vec<String>),
+    /// Named sets of settings the adopters get, replacing the config's default adoption settings
+    PolicyMixes(Vec<(String, Vec<Settings>)>),
+}
+
+impl SweepDimension {
+    /// Key of the dimension in the output files
+    pub fn name(&self) -> &'static str {
+        match self {
+            SweepDimension::NumAttackers(_) => "num_attackers",
+            SweepDimension::AspaObjects(_) => "percent_aspa_objects",
+            SweepDimension::Scenarios(_) => "scenario_name",
+            SweepDimension::PolicyMixes(_) => "policy_mix",
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            SweepDimension::NumAttackers(values) => values.len(),
+            SweepDimension::AspaObjects(values) => values.len(),
+            SweepDimension::Scenarios(values) => values.len(),
+            SweepDimension::PolicyMixes(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Value at `index`, as written to the output files
+    pub fn value_json(&self, index: usize) -> serde_json::Value {
+        match self {
+            SweepDimension::NumAttackers(values) => serde_json::json!(values[index]),
+            SweepDimension::AspaObjects(values) => serde_json::json!(values[index]),
+            SweepDimension::Scenarios(values) => serde_json::json!(values[index]),
+            SweepDimension::PolicyMixes(values) => serde_json::json!(values[index].0),
+        }
+    }
+
+    /// Every value, with the settings of each policy mix
+    pub fn to_json(&self) -> serde_json::Value {
+        let values = match self {
+            SweepDimension::PolicyMixes(mixes) => mixes.iter()
+                .map(|(name, settings)| serde_json::json!({"name": name, "settings": settings}))
+                .collect(),
+            _ => (0..self.len()).map(|index| self.value_json(index)).collect::<Vec<_>>(),
+        };
+        serde_json::json!({"name": self.name(), "values": values})
+    }
+
+    /// `config` with the value at `index` applied, labeled and recorded in its sweep parameters
+    pub fn apply(&self, config: &ScenarioConfig, index: usize) -> ScenarioConfig {
+        let mut config = config.clone();
+        let label = match self {
+            SweepDimension::NumAttackers(values) => {
+                config.num_attackers = values[index];
+                format!("{} attackers", values[index])
+            }
+            SweepDimension::AspaObjects(values) => {
+                config.percent_aspa_objects = Some(values[index]);
+                format!("{}% ASPA objects", values[index])
+            }
+            SweepDimension::Scenarios(values) => {
+                config.scenario_name = values[index].clone();
+                values[index].clone()
+            }
+            SweepDimension::PolicyMixes(values) => {
+                let (name, settings) = &values[index];
+                config.default_adoption_settings = settings.iter().map(|&setting| (setting, true)).collect();
+                name.clone()
+            }
+        };
+        config.label = format!("{}; {}", config.label, label);
+        config.sweep_parameters.push((self.name().to_string(), self.value_json(index)));
+        config
+    }
+}
+
+/// Every combination of `dimensions` applied to each of `configs`, the last dimension varying fastest
+pub fn expand_configs(configs: &[ScenarioConfig], dimensions: &[SweepDimension]) -> Vec<ScenarioConfig> {
+    dimensions.iter().fold(configs.to_vec(), |configs, dimension| {
+        configs.iter()
+            .flat_map(|config| (0..dimension.len()).map(move |index| dimension.apply(config, index)))
+            .collect()
+    })
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use bgpsim_core::as_graphs::as_graph::ASN;
+use bgpsim_core::simulation_engine::{Prefix, SimulationEngine};
+
+/// Aggregated forwarding flows towards a single prefix
+///
+/// Every AS with a route contributes one unit of flow along each hop of its best path,
+/// so `flows` can be fed directly into a sankey diagram and `transit_counts` shows
+/// which networks carry the most traffic (the chokepoints)
+#[derive(Debug, Clone)]
+pub struct TransitFlows {
+    pub prefix: Prefix,
+
+    /// Number of ASes whose best path traverses each (from, to) hop
+    pub flows: HashMap<(ASN, ASN), usize>,
+
+    /// Number of ASes whose best path traverses each AS as transit (excluding the AS itself and the origin)
+    pub transit_counts: HashMap<ASN, usize>,
+
+    /// Number of ASes that have any route to the prefix
+    pub num_routed_ases: usize,
+}
+
+impl TransitFlows {
+    pub fn from_engine(engine: &SimulationEngine, prefix: &Prefix) -> Self {
+        let mut flows = HashMap::new();
+        let mut transit_counts = HashMap::new();
+        let mut num_routed_ases = 0;
+
+        for (_, policy) in engine.policy_store.iter() {
+            let ann = match policy.local_rib.get(prefix) {
+                Some(ann) if !ann.withdraw => ann,
+                _ => continue,
+            };
+            num_routed_ases += 1;
+
+            let as_path = ann.as_path.to_vec();
+            for hop in as_path.windows(2) {
+                // Prepending can repeat an ASN, which is not a real hop
+                if hop[0] != hop[1] {
+                    *flows.entry((hop[0], hop[1])).or_insert(0) += 1;
+                }
+            }
+
+            if as_path.len() > 2 {
+                let mut transit_asns = as_path[1..as_path.len() - 1].to_vec();
+                transit_asns.dedup();
+                for asn in transit_asns {
+                    *transit_counts.entry(asn).or_insert(0) += 1;
+                }
+            }
+        }
+
+        TransitFlows {
+            prefix: *prefix,
+            flows,
+            transit_counts,
+            num_routed_ases,
+        }
+    }
+
+    /// Transit ASes sorted by how many ASes route through them, largest first
+    pub fn top_chokepoints(&self, n: usize) -> Vec<(ASN, usize)> {
+        let mut chokepoints: Vec<(ASN, usize)> = self.transit_counts.iter()
+            .map(|(&asn, &count)| (asn, count))
+            .collect();
+        chokepoints.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        chokepoints.truncate(n);
+        chokepoints
+    }
+
+    /// Flow table rows as (source, target, value), sorted by value, largest first
+    pub fn flow_rows(&self) -> Vec<(ASN, ASN, usize)> {
+        let mut rows: Vec<(ASN, ASN, usize)> = self.flows.iter()
+            .map(|(&(from, to), &value)| (from, to, value))
+            .collect();
+        rows.sort_by(|a, b| b.2.cmp(&a.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+        rows
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("source,target,value\n");
+        for (from, to, value) in self.flow_rows() {
+            csv.push_str(&format!("{},{},{}\n", from, to, value));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let flows: Vec<serde_json::Value> = self.flow_rows().into_iter()
+            .map(|(from, to, value)| serde_json::json!({
+                "source": from,
+                "target": to,
+                "value": value,
+            }))
+            .collect();
+
+        serde_json::json!({
+            "prefix": self.prefix.to_string(),
+            "num_routed_ases": self.num_routed_ases,
+            "flows": flows,
+            "transit_counts": self.transit_counts,
+        })
+    }
+
+    pub fn save_to_file(&self, output_dir: &Path, label: &str) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.to_json())?;
+        fs::write(output_dir.join(format!("{}_transit_flows.json", label)), json)?;
+        fs::write(output_dir.join(format!("{}_transit_flows.csv", label)), self.to_csv())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+//! Browse simulation output in a web browser
+//!
+//! cargo run --example dashboard --features dashboard -- [output_dir] [addr]
+//! Defaults to the current directory and 127.0.0.1:8000
+
+use std::path::PathBuf;
+
+use bgpsimulator::dashboard::Dashboard;
+
+fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    let mut args = std::env::args().skip(1);
+    let root = args.next().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("."));
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:8000".to_string());
+
+    println!("Serving {} at http://{}", root.display(), addr);
+    if let Err(e) = Dashboard::new(root).serve(&addr) {
+        eprintln!("Dashboard failed: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -16,32 +16,32 @@ fn main() {
             .with_providers(vec![2]),
     ];
     
-    // Build the AS graph - note the lifetime is managed by Box
+    // Build the AS graph - all ASes live in a single arena owned by the graph
     let as_graph = ASGraph::build(builders);
     
-    // Now we can use direct references with zero lookup overhead!
+    // Neighbor handles resolve to ASes with a single index lookup
     if let Some(as1) = as_graph.get(&1) {
-        println!("AS1 has {} customers", as1.customers.len());
+        println!("AS1 has {} customers", as1.customers().len());
         
-        for customer in &as1.customers {
+        for customer in as1.customers() {
             println!("  AS1 -> AS{} (customer)", customer.asn);
             
-            // We can traverse the graph with direct references
-            for provider in &customer.providers {
+            // We can traverse the graph through neighbor handles
+            for provider in as_graph[customer.index].providers() {
                 println!("    AS{} -> AS{} (provider)", customer.asn, provider.asn);
             }
         }
     }
     
-    // Verify circular references work
+    // Verify relationships are bidirectional
     if let Some(as2) = as_graph.get(&2) {
         println!("\nAS2's relationships:");
-        println!("  Providers: {:?}", as2.providers.iter().map(|p| p.asn).collect::<Vec<_>>());
-        println!("  Customers: {:?}", as2.customers.iter().map(|c| c.asn).collect::<Vec<_>>());
+        println!("  Providers: {:?}", as2.providers().iter().map(|p| p.asn).collect::<Vec<_>>());
+        println!("  Customers: {:?}", as2.customers().iter().map(|c| c.asn).collect::<Vec<_>>());
         
         // Check that AS2's provider (AS1) has AS2 as customer
-        for provider in &as2.providers {
-            let has_as_customer = provider.customers.iter().any(|c| c.asn == 2);
+        for provider in as2.providers() {
+            let has_as_customer = as_graph[provider.index].customers().iter().any(|c| c.asn == 2);
             println!("  AS{} has AS2 as customer: {}", provider.asn, has_as_customer);
         }
     }
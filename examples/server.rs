@@ -0,0 +1,28 @@
+//! Drive simulations over HTTP, see `SimulationServer` for the endpoints
+//!
+//! cargo run --example server --features server -- [output_dir] [addr]
+//! Defaults to ./server_runs and 127.0.0.1:8080
+
+use std::path::PathBuf;
+
+use bgpsimulator::server::SimulationServer;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    let mut args = std::env::args().skip(1);
+    let root = args.next().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("server_runs"));
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:8080".to_string());
+
+    println!("Serving the simulation API at http://{}, writing runs to {}", addr, root.display());
+    if let Err(e) = SimulationServer::new(root).serve(&addr).await {
+        eprintln!("Server failed: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -1,3 +0,0 @@
-pub mod as_graph;
-
-pub use as_graph::{AS, ASGraph, ASBuilder, ASN};
\ No newline at end of file
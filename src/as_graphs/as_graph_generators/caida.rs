@@ -1,22 +0,0 @@
-use std::path::{Path, PathBuf};
-
-use crate::as_graphs::as_graph::ASGraph;
-
-/// Converter for CAIDA AS graph files
-pub struct CAIDAASGraphJSONConverter {
-    file_path: PathBuf,
-}
-
-impl CAIDAASGraphJSONConverter {
-    pub fn new(file_path: &Path) -> Self {
-        CAIDAASGraphJSONConverter {
-            file_path: file_path.to_path_buf(),
-        }
-    }
-
-    pub fn convert(&self) -> Result<ASGraph, Box<dyn std::error::Error>> {
-        // TODO: Implement CAIDA loading with new AS graph API
-        // This requires updating to use ASBuilder pattern instead of direct AS construction
-        unimplemented!("CAIDA loading not yet implemented with new AS graph API")
-    }
-}
\ No newline at end of file
@@ -1,4 +1,2 @@
-pub mod as_graph;
-pub mod as_graph_generators;
-
-pub use as_graph::{AS, ASGraph, ASBuilder, ASN};
\ No newline at end of file
+pub use bgpsim_core::as_graphs::*;
+pub use bgpsim_data::{as_graph_collectors, as_graph_generators};
@@ -1,5 +0,0 @@
-pub mod engine_run_config;
-pub mod engine_runner;
-
-pub use engine_run_config::EngineRunConfig;
-pub use engine_runner::EngineRunner;
\ No newline at end of file
@@ -1,13 +1,19 @@
-// Re-export all public modules
-pub mod shared;
+// Re-export the workspace crates under the paths this crate has always used
+pub use bgpsim_core::{shared, simulation_engine, route_validator, aspa_validator, irr_validator, error};
 pub mod as_graphs;
-pub mod simulation_engine;
-pub mod route_validator;
-pub mod simulation_framework;
-pub mod engine_runner;
+pub use bgpsim_framework::{simulation_framework, engine_runner};
+#[cfg(feature = "dashboard")]
+pub use bgpsim_framework::dashboard;
+#[cfg(feature = "server")]
+pub use bgpsim_framework::server;
+#[cfg(feature = "live-replay")]
+pub use bgpsim_framework::live_replay;
 
 // Re-export commonly used types at the crate root
 pub use as_graphs::as_graph::{AS, ASGraph, ASN};
 pub use simulation_engine::{SimulationEngine, PolicyStore, Announcement, Prefix};
 pub use shared::{CommonASNs, Outcomes, Relationships, Settings, Timestamps};
-pub use route_validator::{ROA, RouteValidator};
\ No newline at end of file
+pub use route_validator::{ROA, RouteValidator};
+pub use aspa_validator::{AspaValidator, ProviderAuth};
+pub use irr_validator::IrrValidator;
+pub use error::Error;
@@ -12,18 +12,29 @@ use bgpsimulator::simulation_framework::scenario_config::ScenarioConfig;
 use bgpsimulator::shared::{CommonASNs, Relationships, Settings, Timestamps};
 
 fn main() {
+    // Verbosity comes from RUST_LOG, e.g. RUST_LOG=bgpsimulator=debug for per-round spans
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+    
     println!("BGP Simulator - Rust\n");
     
+    // --diff-rounds prints every AS's route changes after each propagation round
+    let diff_rounds = std::env::args().any(|arg| arg == "--diff-rounds");
+    
     // Run different examples
-    run_simple_propagation_example();
+    run_simple_propagation_example(diff_rounds);
     println!("\n{}\n", "=".repeat(80));
-    run_hijack_scenario_example();
+    run_hijack_scenario_example(diff_rounds);
     println!("\n{}\n", "=".repeat(80));
     run_defense_scenario_example();
 }
 
 /// Example 1: Simple BGP propagation
-fn run_simple_propagation_example() {
+fn run_simple_propagation_example(diff_rounds: bool) {
     println!("Example 1: Simple BGP Propagation");
     println!("---------------------------------");
     
@@ -45,7 +56,7 @@ fn run_simple_propagation_example() {
         vec![],       // Empty AS path for originated announcements
         65003,        // Next hop
         Relationships::Origin,
-        Timestamps::Victim,
+        Timestamps::VICTIM,
     );
     
     // Seed the announcement
@@ -53,7 +64,7 @@ fn run_simple_propagation_example() {
     
     // Run simulation for 5 rounds
     println!("\nRunning simulation for 5 rounds...");
-    engine.run(5);
+    run_engine(&mut engine, 5, diff_rounds);
     
     // Print results
     println!("\nFinal routing state:");
@@ -70,7 +81,7 @@ fn run_simple_propagation_example() {
 }
 
 /// Example 2: Subprefix hijack scenario
-fn run_hijack_scenario_example() {
+fn run_hijack_scenario_example(diff_rounds: bool) {
     println!("Example 2: Subprefix Hijack Attack");
     println!("----------------------------------");
     
@@ -86,7 +97,7 @@ fn run_hijack_scenario_example() {
         vec![],
         CommonASNs::VICTIM,
         Relationships::Origin,
-        Timestamps::Victim,
+        Timestamps::VICTIM,
     );
     
     let hijack_ann = Announcement::new_with_path(
@@ -94,7 +105,7 @@ fn run_hijack_scenario_example() {
         vec![],
         CommonASNs::ATTACKER,
         Relationships::Origin,
-        Timestamps::Victim,
+        Timestamps::VICTIM,
     );
     
     // Seed both announcements
@@ -107,7 +118,7 @@ fn run_hijack_scenario_example() {
     println!("Attacker AS {} announces: {} (more specific)", CommonASNs::ATTACKER, hijacked_prefix);
     
     // Run simulation
-    engine.run(10);
+    run_engine(&mut engine, 10, diff_rounds);
     
     // Check who prefers which route
     println!("\nAttack results:");
@@ -141,7 +152,7 @@ fn run_defense_scenario_example() {
     let mut engine = SimulationEngine::new(&as_graph);
     
     // Enable ROV for 50% of ASes
-    let all_asns: Vec<u32> = engine.as_graph.as_dict.keys().copied().collect();
+    let all_asns: Vec<u32> = engine.as_graph.asns().collect();
     let adopting_count = all_asns.len() / 2;
     
     println!("\nEnabling ROV for {} out of {} ASes", adopting_count, all_asns.len());
@@ -160,6 +171,7 @@ fn run_defense_scenario_example() {
         CommonASNs::VICTIM,
         Some(24),  // Max length 24 - subprefixes will be invalid
     ));
+    engine.set_route_validator(std::sync::Arc::new(route_validator));
     
     // Re-run the same attack
     let legitimate_prefix = IpNetwork::from_str("10.0.0.0/24").unwrap();
@@ -170,7 +182,7 @@ fn run_defense_scenario_example() {
         vec![],
         CommonASNs::VICTIM,
         Relationships::Origin,
-        Timestamps::Victim,
+        Timestamps::VICTIM,
     );
     
     let hijack_ann = Announcement::new_with_path(
@@ -178,7 +190,7 @@ fn run_defense_scenario_example() {
         vec![],
         CommonASNs::ATTACKER,
         Relationships::Origin,
-        Timestamps::Victim,
+        Timestamps::VICTIM,
     );
     
     engine.setup(vec![
@@ -219,6 +231,18 @@ fn run_defense_scenario_example() {
              (protected_count as f64 / (protected_count + vulnerable_count) as f64) * 100.0);
 }
 
+/// Run the engine, optionally printing the per-round RIB diffs
+fn run_engine(engine: &mut SimulationEngine, rounds: u32, diff_rounds: bool) {
+    if !diff_rounds {
+        engine.run(rounds);
+        return;
+    }
+    
+    for diff in engine.diff_rounds(rounds) {
+        print!("{}", diff);
+    }
+}
+
 fn create_simple_topology() -> ASGraph {
     // Create AS 65001 (Tier 1)
     let as1_builder = ASBuilder::new(65001)
@@ -1,7 +0,0 @@
-pub mod announcement;
-pub mod engine;
-pub mod policy;
-
-pub use announcement::{Announcement, Prefix};
-pub use engine::SimulationEngine;
-pub use announcement::PolicyStore;
\ No newline at end of file
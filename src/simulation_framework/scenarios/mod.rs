@@ -1,7 +0,0 @@
-pub mod subprefix_hijack;
-pub mod prefix_hijack;
-pub mod legitimate_prefix_only;
-
-pub use subprefix_hijack::SubprefixHijack;
-pub use prefix_hijack::PrefixHijack;
-pub use legitimate_prefix_only::LegitimatePrefixOnly;
\ No newline at end of file
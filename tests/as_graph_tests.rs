@@ -1,5 +1,9 @@
-use std::collections::HashSet;
-use bgpsimulator::as_graphs::as_graph::{ASBuilder, ASGraph};
+use std::collections::{BTreeMap, HashSet};
+use bgpsimulator::as_graphs::GraphMetrics;
+use bgpsimulator::as_graphs::as_graph::{ASBuilder, ASGraph, ASRef};
+use bgpsimulator::as_graphs::as_graph_generators::{ASGraphGenerator, GaoRelationshipInference, SyntheticASGraphGenerator};
+use bgpsimulator::shared::{ASNGroups, Relationships};
+use bgpsimulator::Error;
 
 #[test]
 fn test_as_graph_creation() {
@@ -25,17 +29,17 @@ fn test_as_graph_creation() {
     
     let as1 = as_graph.get(&1).unwrap();
     assert_eq!(as1.asn, 1);
-    assert_eq!(as1.customers.len(), 2);
-    assert_eq!(as1.providers.len(), 1);
+    assert_eq!(as1.customers().len(), 2);
+    assert_eq!(as1.providers().len(), 1);
     
     // Check relationships are bidirectional
     let as2 = as_graph.get(&2).unwrap();
-    assert_eq!(as2.providers.len(), 1);
-    assert_eq!(as2.providers[0].asn, 1);
+    assert_eq!(as2.providers().len(), 1);
+    assert_eq!(as2.providers()[0].asn, 1);
     
     let as4 = as_graph.get(&4).unwrap();
-    assert_eq!(as4.customers.len(), 1);
-    assert_eq!(as4.customers[0].asn, 1);
+    assert_eq!(as4.customers().len(), 1);
+    assert_eq!(as4.customers()[0].asn, 1);
 }
 
 #[test]
@@ -76,10 +80,10 @@ fn test_as_graph_peering() {
     let as1 = as_graph.get(&100).unwrap();
     let as2 = as_graph.get(&200).unwrap();
     
-    assert_eq!(as1.peers.len(), 1);
-    assert_eq!(as1.peers[0].asn, 200);
-    assert_eq!(as2.peers.len(), 1);
-    assert_eq!(as2.peers[0].asn, 100);
+    assert_eq!(as1.peers().len(), 1);
+    assert_eq!(as1.peers()[0].asn, 200);
+    assert_eq!(as2.peers().len(), 1);
+    assert_eq!(as2.peers()[0].asn, 100);
 }
 
 #[test]
@@ -99,8 +103,8 @@ fn test_as_graph_tier1() {
     
     let tier1 = as_graph.get(&1000).unwrap();
     assert!(tier1.tier_1);
-    assert!(tier1.providers.is_empty());
-    assert_eq!(tier1.customers.len(), 2);
+    assert!(tier1.providers().is_empty());
+    assert_eq!(tier1.customers().len(), 2);
 }
 
 #[test]
@@ -133,4 +137,397 @@ fn test_as_graph_propagation_ranks() {
     assert_eq!(as2.propagation_rank, Some(1)); // Direct customer of tier-1
     assert_eq!(as4.propagation_rank, Some(1)); // Direct customer of tier-1
     assert_eq!(as3.propagation_rank, Some(2)); // Customer of AS2
-}
\ No newline at end of file
+    assert_adjacency_in_rank_order(&as_graph);
+}
+
+/// Neighbor lists must sit in the shared arena in propagation rank order
+fn assert_adjacency_in_rank_order(as_graph: &ASGraph) {
+    let mut ases: Vec<_> = as_graph.iter().filter(|as_obj| as_obj.neighbors().next().is_some()).collect();
+    ases.sort_by_key(|as_obj| (as_obj.propagation_rank.unwrap_or(u32::MAX), as_obj.index));
+    let starts: Vec<*const ASRef> = ases.iter().map(|as_obj| as_obj.neighbors().next().unwrap() as *const ASRef).collect();
+    assert!(starts.windows(2).all(|pair| pair[0] < pair[1]), "arena not laid out by propagation rank");
+}
+#[test]
+fn test_as_graph_index_traversal() {
+    let as1_builder = ASBuilder::new(1)
+        .with_customers(vec![2]);
+    
+    let as2_builder = ASBuilder::new(2)
+        .with_providers(vec![1])
+        .with_customers(vec![3]);
+    
+    let as3_builder = ASBuilder::new(3)
+        .with_providers(vec![2]);
+    
+    let mut as_graph = ASGraph::build(vec![as1_builder, as2_builder, as3_builder]);
+    
+    // Walk down the customer chain through neighbor handles
+    let as1 = as_graph.get(&1).unwrap();
+    let as2 = &as_graph[as1.customers()[0].index];
+    assert_eq!(as2.asn, 2);
+    let as3 = as_graph.resolve(&as2.customers()[0]);
+    assert_eq!(as3.asn, 3);
+    assert_eq!(as_graph[as3.providers()[0].index].asn, 2);
+    
+    // The graph can be mutated after it has been built
+    as_graph.get_mut(&3).unwrap().ixp = true;
+    assert!(as_graph.get(&3).unwrap().ixp);
+    
+    // The graph is safe to share across threads
+    let as_graph = std::sync::Arc::new(as_graph);
+    let shared = std::sync::Arc::clone(&as_graph);
+    let num_ases = std::thread::spawn(move || shared.len()).join().unwrap();
+    assert_eq!(num_ases, 3);
+}
+
+#[test]
+fn test_as_graph_mutation() {
+    // Start with 1 -> 2 -> 3
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_customers(vec![2]),
+        ASBuilder::new(2).with_providers(vec![1]).with_customers(vec![3]),
+        ASBuilder::new(3).with_providers(vec![2]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    assert_eq!(as_graph.get(&3).unwrap().propagation_rank, Some(2));
+    
+    // New peering and a new AS are linked on both sides and re-ranked
+    as_graph.add_relationship(3, 1, Relationships::Providers).unwrap();
+    as_graph.add_as(ASBuilder::new(4).with_providers(vec![3]).with_peers(vec![2])).unwrap();
+    assert_eq!(as_graph.get(&1).unwrap().customers().len(), 2);
+    assert_eq!(as_graph.get(&2).unwrap().peers()[0].asn, 4);
+    assert_eq!(as_graph.get(&4).unwrap().propagation_rank, Some(3));
+    assert!(as_graph.add_as(ASBuilder::new(4)).is_err());
+    
+    // Changing a relationship replaces the old one
+    as_graph.add_relationship(2, 3, Relationships::Peers).unwrap();
+    let as3 = as_graph.get(&3).unwrap();
+    assert!(as3.providers().iter().all(|p| p.asn != 2));
+    assert_eq!(as3.peers()[0].asn, 2);
+    assert_eq!(as3.propagation_rank, Some(1));
+    assert_adjacency_in_rank_order(&as_graph);
+    
+    // Depeering
+    assert!(as_graph.remove_relationship(2, 4));
+    assert!(!as_graph.remove_relationship(2, 4));
+    assert!(as_graph.get(&4).unwrap().peers().is_empty());
+    
+    // Removing an AS drops every link to it and keeps handles valid
+    let removed = as_graph.remove_as(&1).unwrap();
+    assert_eq!(removed.asn, 1);
+    // The removed AS still sees the neighbors it had
+    assert_eq!(removed.customers().iter().map(|c| c.asn).collect::<HashSet<_>>(), HashSet::from([2, 3]));
+    assert_eq!(as_graph.len(), 3);
+    assert!(as_graph.get(&1).is_none());
+    for as_obj in as_graph.iter() {
+        assert!(as_obj.neighbors().all(|n| n.asn != 1));
+        let lists: Vec<_> = as_obj.peers().iter().chain(as_obj.providers()).chain(as_obj.customers()).collect();
+        assert_eq!(as_obj.neighbors().collect::<Vec<_>>(), lists);
+        for neighbor in as_obj.neighbors() {
+            assert_eq!(as_graph[neighbor.index].asn, neighbor.asn);
+        }
+    }
+    assert_eq!(as_graph.get(&2).unwrap().propagation_rank, Some(0));
+    assert_eq!(as_graph.get(&4).unwrap().propagation_rank, Some(1));
+    assert_adjacency_in_rank_order(&as_graph);
+}
+
+#[test]
+fn test_as_graph_incremental_construction() {
+    let mut as_graph = ASGraph::new();
+    as_graph.add_as_from_builder(ASBuilder::new(1).with_customers(vec![2]));
+    as_graph.add_as_from_builder(ASBuilder::new(2).with_providers(vec![1]));
+    as_graph.establish_relationships();
+    
+    assert_eq!(as_graph.get(&1).unwrap().customers()[0].asn, 2);
+    assert_eq!(as_graph.get(&2).unwrap().propagation_rank, Some(1));
+}
+
+#[test]
+fn test_customer_cone_sizes() {
+    // 4 is reachable from 1 through both 2 and 3, but only counted once
+    //
+    //       1
+    //      / \
+    //     2   3
+    //      \ / \
+    //       4   5
+    //       |
+    //       6
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_customers(vec![2, 3]),
+        ASBuilder::new(2).with_providers(vec![1]).with_customers(vec![4]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![4, 5]),
+        ASBuilder::new(4).with_providers(vec![2, 3]).with_customers(vec![6]),
+        ASBuilder::new(5).with_providers(vec![3]),
+        ASBuilder::new(6).with_providers(vec![4]),
+    ]);
+
+    let cone_size = |as_graph: &ASGraph, asn| as_graph.get(&asn).unwrap().customer_cone_size();
+    assert_eq!(cone_size(&as_graph, 1), 5);
+    assert_eq!(cone_size(&as_graph, 2), 2);
+    assert_eq!(cone_size(&as_graph, 3), 3);
+    assert_eq!(cone_size(&as_graph, 6), 0);
+    assert_eq!(as_graph.customer_cone_asns(&3), HashSet::from([4, 5, 6]));
+    assert_eq!(as_graph.top_asns_by_customer_cone(3), vec![1, 3, 2]);
+    assert_eq!(ASNGroups::TopByCustomerCone(2).asns(&as_graph), HashSet::from([1, 3]));
+
+    // Cone sizes follow graph mutations
+    as_graph.remove_relationship(3, 4);
+    assert_eq!(cone_size(&as_graph, 3), 1);
+    assert_eq!(cone_size(&as_graph, 1), 5);
+    as_graph.remove_as(&4);
+    assert_eq!(cone_size(&as_graph, 1), 3);
+    assert_eq!(cone_size(&as_graph, 2), 0);
+}
+
+#[test]
+fn test_graph_metrics() {
+    //       1
+    //      / \
+    //     2 - 3
+    //      \ / \
+    //       4   5
+    //       |
+    //       6
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_customers(vec![2, 3]),
+        ASBuilder::new(2).with_providers(vec![1]).with_customers(vec![4]).with_peers(vec![3]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![4, 5]).with_peers(vec![2]),
+        ASBuilder::new(4).with_providers(vec![2, 3]).with_customers(vec![6]),
+        ASBuilder::new(5).with_providers(vec![3]),
+        ASBuilder::new(6).with_providers(vec![4]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+
+    let mut metrics = GraphMetrics::compute(&as_graph);
+    assert_eq!(metrics.num_ases, 6);
+    assert_eq!((metrics.num_customer_provider_links, metrics.num_peer_links), (6, 1));
+    assert_eq!((metrics.num_tier_1, metrics.num_stubs, metrics.num_multihomed, metrics.num_transit), (1, 2, 0, 4));
+    assert_eq!(metrics.degree_distribution, BTreeMap::from([(1, 2), (2, 1), (3, 2), (4, 1)]));
+    assert_eq!(metrics.customer_cone_distribution, BTreeMap::from([(0, 2), (1, 1), (2, 1), (3, 1), (5, 1)]));
+    assert_eq!(metrics.rank_distribution, vec![1, 2, 2, 1]);
+    assert_eq!(metrics.mean_path_length(), None);
+
+    // More origins than ASes propagates from every AS
+    metrics.sample_path_lengths(&as_graph, 10, 0);
+    assert_eq!(metrics.num_path_origins, 6);
+    assert_eq!(metrics.path_length_distribution, BTreeMap::from([(1, 14), (2, 12), (3, 4)]));
+    assert_eq!(metrics.mean_path_length(), Some(50.0 / 30.0));
+
+    let json = metrics.to_json();
+    assert_eq!(json["degree_distribution"]["3"], 2);
+    assert_eq!(json["mean_degree"], 14.0 / 6.0);
+}
+
+#[test]
+fn test_customer_cone_deep_chain() {
+    // A long provider chain would overflow the stack with a recursive implementation
+    let depth = 10_000;
+    let builders = (1..=depth)
+        .map(|asn| {
+            let mut builder = ASBuilder::new(asn);
+            if asn > 1 {
+                builder = builder.with_providers(vec![asn - 1]);
+            }
+            if asn < depth {
+                builder = builder.with_customers(vec![asn + 1]);
+            }
+            builder
+        })
+        .collect();
+    let as_graph = ASGraph::build(builders);
+
+    assert_eq!(as_graph.get(&1).unwrap().customer_cone_size(), (depth - 1) as usize);
+}
+
+#[test]
+fn test_propagation_rank_with_provider_cycle() {
+    // 2 -> 3 -> 4 -> 2 are each other's providers; 5 is a customer of the cycle
+    //
+    //       1
+    //       |
+    //       2 <-- 4
+    //       |     ^
+    //       3 ----+
+    //       |
+    //       5
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_customers(vec![2]),
+        ASBuilder::new(2).with_providers(vec![1, 4]).with_customers(vec![3]),
+        ASBuilder::new(3).with_providers(vec![2]).with_customers(vec![4, 5]),
+        ASBuilder::new(4).with_providers(vec![3]).with_customers(vec![2]),
+        ASBuilder::new(5).with_providers(vec![3]),
+    ]);
+    assert_eq!(as_graph.find_provider_cycles(), vec![vec![2, 3, 4]]);
+    let err = as_graph.check_for_cycles().unwrap_err();
+    assert!(matches!(&err, Error::GraphCycle(cycles) if *cycles == vec![vec![2, 3, 4]]));
+    assert_eq!(err.to_string(), "Provider cycles between ASes: [[2, 3, 4]]");
+
+    // Every AS is ranked and the cycle shares a single rank
+    as_graph.assign_as_propagation_rank();
+    let rank = |asn| as_graph.get(&asn).unwrap().propagation_rank;
+    assert_eq!(rank(1), Some(0));
+    assert_eq!(rank(2), Some(1));
+    assert_eq!(rank(3), Some(1));
+    assert_eq!(rank(4), Some(1));
+    assert_eq!(rank(5), Some(2));
+    assert_eq!(as_graph.propagation_ranks, vec![vec![1], vec![2, 3, 4], vec![5]]);
+
+    // Breaking the cycle restores a normal hierarchy
+    as_graph.remove_relationship(2, 4);
+    assert!(as_graph.find_provider_cycles().is_empty());
+    assert_eq!(as_graph.get(&4).unwrap().propagation_rank, Some(3));
+}
+
+#[test]
+fn test_subgraph_around() {
+    //       1 (tier-1) --- 2 (tier-1)
+    //      / \              \
+    //     3   4 --- peer --- 5
+    //     |                  |
+    //     6                  7
+    let as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_peers(vec![2]).with_customers(vec![3, 4]),
+        ASBuilder::new(2).as_tier_1().with_peers(vec![1]).with_customers(vec![5]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![6]),
+        ASBuilder::new(4).with_providers(vec![1]).with_peers(vec![5]),
+        ASBuilder::new(5).with_providers(vec![2]).with_peers(vec![4]).with_customers(vec![7]),
+        ASBuilder::new(6).with_providers(vec![3]),
+        ASBuilder::new(7).with_providers(vec![5]),
+    ]);
+
+    let subgraph = as_graph.subgraph_around(&HashSet::from([4]), 1);
+    let asns: HashSet<u32> = subgraph.asns().collect();
+    assert_eq!(asns, HashSet::from([1, 4, 5]));
+    // Relationships to dropped ASes are gone and ranks are recomputed, so AS 5 becomes a root
+    assert_eq!(subgraph.get(&1).unwrap().customers().iter().map(|c| c.asn).collect::<Vec<_>>(), vec![4]);
+    assert_eq!(subgraph.get(&4).unwrap().peers()[0].asn, 5);
+    assert!(subgraph.get(&1).unwrap().tier_1);
+    assert_eq!(subgraph.get(&5).unwrap().propagation_rank, Some(0));
+    assert_eq!(subgraph.get(&4).unwrap().propagation_rank, Some(1));
+    assert_eq!(subgraph.get(&1).unwrap().customer_cone_size(), 1);
+
+    assert_eq!(as_graph.subgraph_around(&HashSet::from([6]), 0).len(), 1);
+    assert_eq!(as_graph.subgraph_around(&HashSet::from([6]), 10).len(), 7);
+    assert!(as_graph.subgraph_around(&HashSet::from([99]), 2).is_empty());
+}
+
+#[test]
+fn test_random_subgraph() {
+    // Two components: a chain of 20 ASes and a lone pair
+    let mut builders: Vec<ASBuilder> = (1..=20)
+        .map(|asn| {
+            let builder = ASBuilder::new(asn).with_customers(if asn < 20 { vec![asn + 1] } else { vec![] });
+            if asn > 1 { builder.with_providers(vec![asn - 1]) } else { builder }
+        })
+        .collect();
+    builders.push(ASBuilder::new(100).with_peers(vec![101]));
+    builders.push(ASBuilder::new(101).with_peers(vec![100]));
+    let as_graph = ASGraph::build(builders);
+
+    for seed in 0..10 {
+        let subgraph = as_graph.random_subgraph(5, seed);
+        assert!(subgraph.len() == 5 || subgraph.len() == 2, "seed {} kept {} ASes", seed, subgraph.len());
+
+        // Every AS is connected to the rest and each AS is ranked
+        let mut asns: Vec<u32> = subgraph.asns().collect();
+        asns.sort_unstable();
+        if subgraph.len() == 5 {
+            assert!(asns.windows(2).all(|pair| pair[1] == pair[0] + 1), "{:?} is not connected", asns);
+        }
+        assert!(subgraph.iter().all(|as_obj| as_obj.propagation_rank.is_some()));
+
+        let again: HashSet<u32> = as_graph.random_subgraph(5, seed).asns().collect();
+        assert_eq!(again, asns.into_iter().collect::<HashSet<u32>>());
+    }
+    // Asking for more ASes than the component has keeps the whole component
+    assert!([2, 20].contains(&as_graph.random_subgraph(1000, 1).len()));
+    assert!(as_graph.random_subgraph(0, 1).is_empty());
+}
+
+#[test]
+fn test_synthetic_as_graph() {
+    let generator = SyntheticASGraphGenerator::new(1000)
+        .with_num_tier_1(5)
+        .with_stub_fraction(0.8)
+        .with_peering_density(3.0)
+        .with_seed(7);
+    let as_graph = generator.generate().unwrap();
+    assert_eq!(as_graph.len(), 1000);
+    assert!(as_graph.find_provider_cycles().is_empty());
+
+    // The tier-1s form a clique on top and everything else has providers
+    let tier_1s: HashSet<u32> = as_graph.iter().filter(|as_obj| as_obj.tier_1).map(|as_obj| as_obj.asn).collect();
+    assert_eq!(tier_1s, (1..=5).collect());
+    assert!(as_graph.iter().filter(|as_obj| as_obj.tier_1).all(|as_obj| as_obj.peers().len() == 4));
+    assert_eq!(as_graph.propagation_ranks[0].iter().copied().collect::<HashSet<u32>>(), tier_1s);
+    assert!(as_graph.iter().filter(|as_obj| !as_obj.tier_1).all(|as_obj| !as_obj.providers().is_empty()));
+
+    // 796 ASes are in the stub level; low transit ASes may also end up without customers
+    let stubs = ASNGroups::StubsOrMh.asns(&as_graph).len();
+    assert!((796..850).contains(&stubs), "{} ASes have no customers", stubs);
+    let peer_links: usize = as_graph.iter().filter(|as_obj| !as_obj.tier_1).map(|as_obj| as_obj.peers().len()).sum();
+    // 199 transit ASes split into levels of 100 and 99, each averaging 3 peers
+    assert_eq!(peer_links, 2 * (150 + 149));
+
+    // The same seed gives the same graph, another seed a different one
+    assert_eq!(generator.generate().unwrap().to_json(), as_graph.to_json());
+    assert_ne!(generator.clone().with_seed(8).generate().unwrap().to_json(), as_graph.to_json());
+
+    assert!(matches!(SyntheticASGraphGenerator::new(10).with_num_tier_1(11).generate(), Err(Error::ConfigInvalid(_))));
+    assert!(matches!(SyntheticASGraphGenerator::new(10).with_stub_fraction(1.5).generate(), Err(Error::ConfigInvalid(_))));
+}
+
+/// Paths between the stubs of a two tier-1 topology, as a route collector would see them
+///
+///   1 (Tier-1) --peer-- 2 (Tier-1)
+///   /   \              /   \
+///  3     4            5     8
+///  |                  |
+///  6                  7
+const OBSERVED_PATHS: &str = "\
+# vantage point first
+6 3 1 4
+6 3 3 1 2 5 7
+6 3 1 2 8
+4 1 3 6
+4 1 2 5 7
+4 1 2 8
+7 5 2 1 3 6
+7 5 2 1 4
+7 5 2 8
+8 2 1 3 6
+8 2 1 4
+8 2 5 7
+TABLE_DUMP2|1700000000|B|192.0.2.1|8|198.51.100.0/24|8 2 5 7|IGP|192.0.2.1|0|0||NAG||
+8 2 {5,9}
+";
+
+#[test]
+fn test_gao_relationship_inference() {
+    let inference = GaoRelationshipInference::parse(OBSERVED_PATHS).unwrap();
+    // Prepending is collapsed and the path with an AS set is skipped
+    assert_eq!(inference.degrees().len(), 8);
+    assert_eq!(inference.infer().len(), 8);
+
+    let as_graph = inference.generate().unwrap();
+    // Providers, peers and customers of `asn`
+    let relationships = |asn| {
+        let as_obj = as_graph.get(&asn).unwrap();
+        let mut neighbors = [as_obj.providers(), as_obj.peers(), as_obj.customers()]
+            .map(|neighbors| neighbors.iter().map(|neighbor| neighbor.asn).collect::<Vec<u32>>());
+        for asns in &mut neighbors {
+            asns.sort_unstable();
+        }
+        neighbors
+    };
+    assert_eq!(relationships(1), [vec![], vec![2], vec![3, 4]]);
+    assert_eq!(relationships(2), [vec![], vec![1], vec![5, 8]]);
+    assert_eq!(relationships(3), [vec![1], vec![], vec![6]]);
+    assert_eq!(relationships(7), [vec![5], vec![], vec![]]);
+    let tier_1s: HashSet<u32> = as_graph.iter().filter(|as_obj| as_obj.tier_1).map(|as_obj| as_obj.asn).collect();
+    assert_eq!(tier_1s, HashSet::from([1, 2]));
+
+    assert!(GaoRelationshipInference::parse("1 2 x").unwrap_err().contains("invalid ASN x"));
+}
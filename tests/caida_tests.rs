@@ -0,0 +1,301 @@
+use std::fs;
+
+use chrono::NaiveDate;
+
+use std::collections::HashSet;
+
+use bgpsimulator::as_graphs::as_graph::{ASGraph, RIR};
+use bgpsimulator::as_graphs::as_graph_collectors::CAIDAASGraphCollector;
+use bgpsimulator::as_graphs::as_graph_generators::{CAIDAASGraphJSONConverter, CAIDAASOrgConverter};
+use bgpsimulator::shared::ASNGroups;
+use bgpsimulator::Error;
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+/// Small serial-2 file: 1 and 2 are the clique, 3 is an IXP
+fn sample_caida_file() -> String {
+    let mut contents = String::from("# source:topology|BGP|20240101|ris|rrc00\n");
+    contents.push_str("# input clique: 1 2\n");
+    contents.push_str("# IXP ASes: 3\n");
+    contents.push_str("1|2|0|bgp\n");
+    contents.push_str("1|3|-1|bgp\n");
+    contents.push_str("2|4|-1|bgp\n");
+    contents.push_str("3|4|0|bgp\n");
+    for customer in 10..30 {
+        contents.push_str(&format!("4|{}|-1|bgp\n", customer));
+    }
+    // Pad the header like real files so the size check passes
+    while contents.len() < CAIDAASGraphCollector::MIN_FILE_SIZE {
+        contents.insert_str(0, "# padding\n");
+    }
+    contents
+}
+
+#[test]
+fn test_parse_index() {
+    let html = r#"<html><body>
+        <a href="../">Parent Directory</a>
+        <a href="20240201.as-rel2.txt.bz2">20240201.as-rel2.txt.bz2</a>
+        <a href="20240101.as-rel2.txt.bz2">20240101.as-rel2.txt.bz2</a>
+        <a href="20240101.ppdc-ases.txt.bz2">20240101.ppdc-ases.txt.bz2</a>
+        <a href="README.txt">README.txt</a>
+    </body></html>"#;
+
+    assert_eq!(
+        CAIDAASGraphCollector::parse_index(html),
+        vec![date("2024-01-01"), date("2024-02-01")]
+    );
+}
+
+#[test]
+fn test_nearest_available_date() {
+    let dates = vec![date("2024-01-01"), date("2024-02-01"), date("2024-03-01")];
+
+    // Latest snapshot that isn't after the requested date
+    assert_eq!(
+        CAIDAASGraphCollector::nearest_available_date(&dates, date("2024-02-15")),
+        Some(date("2024-02-01"))
+    );
+    assert_eq!(
+        CAIDAASGraphCollector::nearest_available_date(&dates, date("2024-03-01")),
+        Some(date("2024-03-01"))
+    );
+    // Before the first snapshot, fall forward
+    assert_eq!(
+        CAIDAASGraphCollector::nearest_available_date(&dates, date("2023-06-01")),
+        Some(date("2024-01-01"))
+    );
+    assert_eq!(CAIDAASGraphCollector::nearest_available_date(&[], date("2024-01-01")), None);
+}
+
+#[test]
+fn test_validate_contents() {
+    assert!(CAIDAASGraphCollector::validate_contents(&sample_caida_file()).is_ok());
+
+    // Truncated or error pages are rejected
+    assert!(matches!(CAIDAASGraphCollector::validate_contents("1|2|0|bgp\n"), Err(Error::Parse(_))));
+    let mut malformed = sample_caida_file();
+    malformed.push_str("<html>404 Not Found</html>\n");
+    assert!(CAIDAASGraphCollector::validate_contents(&malformed).is_err());
+}
+
+#[test]
+fn test_cached_file_checksum() {
+    let cache_dir = std::env::temp_dir().join("bgpsimulator_caida_checksum_test");
+    fs::create_dir_all(&cache_dir).unwrap();
+    let collector = CAIDAASGraphCollector::new(date("2024-01-01"), cache_dir.clone());
+    let path = collector.cache_path(date("2024-01-01"));
+
+    let contents = sample_caida_file();
+    fs::write(&path, &contents).unwrap();
+    assert!(!CAIDAASGraphCollector::cached_file_valid(&path));
+
+    let checksum_path = cache_dir.join("20240101.as-rel2.txt.checksum");
+    fs::write(&checksum_path, CAIDAASGraphCollector::checksum(contents.as_bytes())).unwrap();
+    assert!(CAIDAASGraphCollector::cached_file_valid(&path));
+
+    // A truncated cache no longer matches its checksum
+    fs::write(&path, &contents[..contents.len() / 2]).unwrap();
+    assert!(!CAIDAASGraphCollector::cached_file_valid(&path));
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+}
+
+/// Serve `routes` (path to body) over HTTP on a local port until `num_requests` were answered
+#[cfg(feature = "async")]
+fn serve_fixtures(routes: Vec<(&'static str, Vec<u8>)>, num_requests: usize) -> String {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(num_requests) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut header = String::new();
+            while reader.read_line(&mut header).unwrap() > 2 {
+                header.clear();
+            }
+            let path = request_line.split_whitespace().nth(1).unwrap_or("");
+            let (status, body) = match routes.iter().find(|(route, _)| *route == path) {
+                Some((_, body)) => ("200 OK", body.clone()),
+                None => ("404 Not Found", Vec::new()),
+            };
+            let head = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, body.len());
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+        }
+    });
+    format!("http://{}/serial-2/", addr)
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_run_async_downloads_and_caches() {
+    use std::io::Write;
+
+    let contents = fs::read("tests/fixtures/caida_sample.as-rel2.txt").unwrap();
+    let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::fast());
+    encoder.write_all(&contents).unwrap();
+    let compressed = encoder.finish().unwrap();
+    let index = r#"<a href="20240101.as-rel2.txt.bz2">20240101.as-rel2.txt.bz2</a>"#;
+    // The second run finds the exact snapshot cached, so only the first one makes requests
+    let base_url = serve_fixtures(vec![
+        ("/serial-2/", index.as_bytes().to_vec()),
+        ("/serial-2/20240101.as-rel2.txt.bz2", compressed),
+    ], 2);
+
+    let cache_dir = std::env::temp_dir().join(format!("bgpsimulator_caida_async_test_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&cache_dir);
+    // Nearest snapshot to the 15th is the 1st
+    let collector = CAIDAASGraphCollector::new(date("2024-01-15"), cache_dir.clone()).with_base_url(base_url.clone());
+    let path = collector.run_async().await.unwrap();
+    assert_eq!(path, collector.cache_path(date("2024-01-01")));
+    assert_eq!(fs::read(&path).unwrap(), contents);
+    assert!(CAIDAASGraphCollector::cached_file_valid(&path));
+
+    let cached = CAIDAASGraphCollector::new(date("2024-01-01"), cache_dir.clone()).with_base_url(base_url);
+    assert_eq!(cached.run_async().await.unwrap(), path);
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+}
+
+#[test]
+fn test_caida_converter_parse() {
+    let as_graph = CAIDAASGraphJSONConverter::parse(&sample_caida_file()).unwrap();
+    assert_eq!(as_graph.len(), 24);
+
+    let as1 = as_graph.get(&1).unwrap();
+    assert!(as1.tier_1);
+    assert_eq!(as1.peers()[0].asn, 2);
+    assert_eq!(as1.customers()[0].asn, 3);
+    assert!(as_graph.get(&3).unwrap().ixp);
+
+    let as4 = as_graph.get(&4).unwrap();
+    assert_eq!(as4.providers()[0].asn, 2);
+    assert_eq!(as4.customers().len(), 20);
+    assert_eq!(as_graph.get(&10).unwrap().propagation_rank, Some(2));
+
+    assert!(matches!(CAIDAASGraphJSONConverter::parse("1|2|7|bgp\n"), Err(Error::Parse(_))));
+}
+
+#[test]
+fn test_as_graph_json_round_trip() {
+    let as_graph = CAIDAASGraphJSONConverter::parse(&sample_caida_file()).unwrap();
+    let loaded = ASGraph::from_json(&as_graph.to_json()).unwrap();
+
+    assert_eq!(loaded.to_json(), as_graph.to_json());
+    assert_eq!(loaded.propagation_ranks, as_graph.propagation_ranks);
+    assert!(loaded.get(&1).unwrap().tier_1);
+    assert!(loaded.get(&3).unwrap().ixp);
+    assert_eq!(loaded.get(&10).unwrap().propagation_rank, Some(2));
+    assert_eq!(loaded.get(&4).unwrap().index, as_graph.get(&4).unwrap().index);
+
+    assert!(ASGraph::from_json(&serde_json::json!({"ases": []})).is_err());
+}
+
+#[test]
+fn test_convert_cached() {
+    let cache_dir = std::env::temp_dir().join("bgpsimulator_caida_graph_cache_test");
+    fs::create_dir_all(&cache_dir).unwrap();
+    let source_path = cache_dir.join("20240101.as-rel2.txt");
+    let contents = sample_caida_file();
+    fs::write(&source_path, &contents).unwrap();
+
+    let converter = CAIDAASGraphJSONConverter::new(&source_path);
+    let as_graph = converter.convert_cached(&cache_dir).unwrap();
+    let cache_path = CAIDAASGraphJSONConverter::graph_cache_path(
+        &cache_dir,
+        &CAIDAASGraphCollector::checksum(contents.as_bytes()),
+    );
+    assert!(cache_path.exists());
+
+    // Second run loads the cached graph
+    let cached = converter.convert_cached(&cache_dir).unwrap();
+    assert_eq!(cached.to_json(), as_graph.to_json());
+
+    // A corrupted cache is rebuilt rather than trusted
+    fs::write(&cache_path, "{ truncated").unwrap();
+    assert_eq!(converter.convert_cached(&cache_dir).unwrap().len(), 24);
+    assert!(serde_json::from_str::<serde_json::Value>(&fs::read_to_string(&cache_path).unwrap()).is_ok());
+
+    // Changing the source file uses a different cache entry
+    fs::write(&source_path, contents.replace("4|29|-1|bgp\n", "")).unwrap();
+    assert_eq!(converter.convert_cached(&cache_dir).unwrap().len(), 23);
+
+    // A malformed source fails to parse and a missing one to read
+    fs::write(&source_path, "1|2|7|bgp\n").unwrap();
+    assert!(matches!(converter.convert_cached(&cache_dir), Err(Error::Parse(_))));
+    fs::remove_file(&source_path).unwrap();
+    assert!(matches!(converter.convert(), Err(Error::Io(_))));
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+}
+
+#[test]
+fn test_as_org_text_format() {
+    let contents = "\
+# format:org_id|changed|org_name|country|source
+LVLT-ARIN|20120130|Level 3 Parent, LLC|us|ARIN
+ORG-RN1-RIPE|20230301|Example Net B.V.|NL|RIPE
+# format:aut|changed|aut_name|org_id|opaque_id|source
+1|20120224|LVLT-1|LVLT-ARIN|e5e3b9c13678dfc483fb1f819d70883c_ARIN|ARIN
+3356|20170320|LEVEL3|LVLT-ARIN|e5e3b9c13678dfc483fb1f819d70883c_ARIN|ARIN
+1103|20230301|EXAMPLE-NET|ORG-RN1-RIPE||RIPE
+4|20230301|NO-ORG|MISSING-ORG||JPNIC
+";
+    let metadata = CAIDAASOrgConverter::parse(contents).unwrap();
+    assert_eq!(metadata.len(), 4);
+
+    let level3 = &metadata[&3356];
+    assert_eq!(level3.country.as_deref(), Some("US"));
+    assert_eq!(level3.rir, Some(RIR::Arin));
+    assert_eq!(level3.org_id.as_deref(), Some("LVLT-ARIN"));
+    assert_eq!(level3.org_name.as_deref(), Some("Level 3 Parent, LLC"));
+    assert_eq!(metadata[&1103].rir, Some(RIR::Ripe));
+
+    // An AS whose organization isn't listed still keeps its own record
+    assert_eq!(metadata[&4].country, None);
+    assert_eq!(metadata[&4].rir, None);
+    assert_eq!(metadata[&4].org_id.as_deref(), Some("MISSING-ORG"));
+
+    assert!(CAIDAASOrgConverter::parse("# format:aut|changed|aut_name|org_id|opaque_id|source\nx|1|a|b|c|ARIN\n").is_err());
+}
+
+#[test]
+fn test_as_org_jsonl_format_and_groups() {
+    let contents = r#"{"changed":"20120130","country":"US","name":"Level 3 Parent, LLC","organizationId":"LVLT-ARIN","source":"ARIN","type":"Organization"}
+{"changed":"20230301","country":"NL","name":"Example Net B.V.","organizationId":"ORG-RN1-RIPE","source":"RIPE","type":"Organization"}
+{"asn":"1","changed":"20120224","name":"LVLT-1","opaqueId":"e5e3b9c13678dfc483fb1f819d70883c_ARIN","organizationId":"LVLT-ARIN","source":"ARIN","type":"ASN"}
+{"asn":"2","changed":"20170320","name":"LEVEL3","organizationId":"LVLT-ARIN","source":"ARIN","type":"ASN"}
+{"asn":4,"changed":"20230301","name":"EXAMPLE-NET","organizationId":"ORG-RN1-RIPE","source":"RIPE","type":"ASN"}
+{"asn":"99","changed":"20230301","name":"NOT-IN-GRAPH","organizationId":"ORG-RN1-RIPE","source":"RIPE","type":"ASN"}
+"#;
+    let cache_dir = std::env::temp_dir().join("bgpsimulator_as_org_test");
+    fs::create_dir_all(&cache_dir).unwrap();
+    let path = cache_dir.join("20240101.as-org2info.jsonl");
+    fs::write(&path, contents).unwrap();
+
+    let mut as_graph = CAIDAASGraphJSONConverter::parse(&sample_caida_file()).unwrap();
+    let num_added = CAIDAASOrgConverter::new(&path).apply(&mut as_graph).unwrap();
+    // AS 99 isn't in the graph
+    assert_eq!(num_added, 3);
+    assert_eq!(as_graph.metadata(&4).unwrap().country.as_deref(), Some("NL"));
+    assert!(as_graph.metadata(&10).is_none());
+
+    assert_eq!(as_graph.asns_in_country("us"), HashSet::from([1, 2]));
+    assert_eq!(as_graph.asns_in_org("LVLT-ARIN"), HashSet::from([1, 2]));
+    assert_eq!(ASNGroups::Country(*b"NL").asns(&as_graph), HashSet::from([4]));
+    assert_eq!(ASNGroups::Rir(RIR::Arin).asns(&as_graph), HashSet::from([1, 2]));
+    assert_eq!(ASNGroups::Rir(RIR::Lacnic).to_string(), "RIR_LACNIC");
+
+    // Removed ASes lose their metadata
+    as_graph.remove_as(&4);
+    assert!(as_graph.asns_in_country("NL").is_empty());
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "dashboard")]
+
+use std::fs;
+
+use bgpsimulator::dashboard::Dashboard;
+
+fn create_output_dir(name: &str) -> std::path::PathBuf {
+    let root = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&root);
+    let run_dir = root.join("engine_runs").join("hijack run");
+    fs::create_dir_all(&run_dir).unwrap();
+    fs::write(run_dir.join("config.json"), r#"{"name": "hijack run", "propagation_rounds": 3}"#).unwrap();
+    fs::write(run_dir.join("outcomes_guess.json"), r#"{"1": "AttackerSuccess", "2": "VictimSuccess", "3": "AttackerSuccess"}"#).unwrap();
+    fs::write(run_dir.join("diagram.txt"), "1 -> <2>").unwrap();
+    fs::write(root.join("ROV_summary.json"), r#"{"scenario_label": "ROV", "adoption_percentages": [10.0, 50.0], "success_rates": [80.0, 20.0]}"#).unwrap();
+    fs::write(root.join("ROV_10_percent.json"), r#"{"success_rate": 80.0}"#).unwrap();
+    fs::write(root.join("notes.bin"), "not an output file").unwrap();
+    root
+}
+
+#[test]
+fn test_dashboard_indexes_runs() {
+    let dashboard = Dashboard::new(create_output_dir("bgpsimulator_dashboard_test_index"));
+    let runs = dashboard.runs();
+    let names: Vec<&str> = runs.iter().map(|run| run.name.as_str()).collect();
+    assert_eq!(names, vec!["", "engine_runs/hijack run"]);
+    assert_eq!(runs[0].files, vec!["ROV_10_percent.json", "ROV_summary.json"]);
+    assert!(!runs[0].is_engine_run());
+    assert!(runs[1].is_engine_run());
+
+    let index = String::from_utf8(dashboard.handle("/").body).unwrap();
+    assert!(index.contains("<a href=\"/run/engine_runs/hijack%20run\">engine_runs/hijack run</a>"));
+}
+
+#[test]
+fn test_dashboard_pages() {
+    let dashboard = Dashboard::new(create_output_dir("bgpsimulator_dashboard_test_pages"));
+
+    let run = dashboard.handle("/run/engine_runs/hijack%20run");
+    assert_eq!(run.status, 200);
+    let page = String::from_utf8(run.body).unwrap();
+    assert!(page.contains("<tr><td>AttackerSuccess</td><td>2</td></tr>"));
+    assert!(page.contains("&quot;propagation_rounds&quot;: 3"));
+    assert!(page.contains("1 -&gt; &lt;2&gt;"));
+
+    let simulation = String::from_utf8(dashboard.handle("/run/").body).unwrap();
+    assert!(simulation.contains("<img src=\"/plot/\""));
+    let plot = dashboard.handle("/plot/");
+    assert_eq!(plot.content_type, "image/svg+xml");
+    assert!(String::from_utf8(plot.body).unwrap().contains("points=\"104.0,110.0 320.0,290.0\""));
+
+    let file = dashboard.handle("/file/engine_runs/hijack%20run/diagram.txt");
+    assert_eq!((file.status, file.body), (200, b"1 -> <2>".to_vec()));
+    // Only indexed output files are served
+    assert_eq!(dashboard.handle("/file//notes.bin").status, 404);
+    assert_eq!(dashboard.handle("/file/engine_runs/../../../etc/passwd").status, 404);
+    assert_eq!(dashboard.handle("/run/missing").status, 404);
+}
@@ -1,11 +1,25 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
-use bgpsimulator::as_graphs::as_graph::{ASBuilder, ASGraph};
-use bgpsimulator::simulation_engine::{SimulationEngine, Announcement, Prefix};
-use bgpsimulator::engine_runner::{EngineRunConfig, EngineRunner};
-use bgpsimulator::shared::{CommonASNs, Outcomes, Settings, Relationships};
+use bgpsimulator::as_graphs::as_graph::{AS, ASBuilder, ASGraph, ASN};
+use bgpsimulator::simulation_engine::{
+    ActionCommunity, ASPath, EngineObserver, EngineSnapshot, SimulationEngine, SnapshotReader, Announcement, NextHopVerification, PolicyStorageMode,
+    Prefix, PrefixMap, PrefixTable, PropagationPhase, RIBsIn, RibFormat, RoundDiff, RouteChange, ValidationResult,
+};
+use bgpsimulator::engine_runner::{ConfigNamespace, EngineRunConfig, EngineRunDiff, EngineRunner, EngineTestSuite};
+use bgpsimulator::shared::{CommonASNs, Outcomes, ROAValidity, Settings, Relationships, Timestamps};
+use bgpsimulator::Error;
 use bgpsimulator::simulation_framework::scenario_config::ScenarioConfig;
+use bgpsimulator::route_validator::{ROA, RouteValidator};
+use bgpsimulator::simulation_engine::policy::{PolicyExtension, TieBreaker, create_policy_extension};
+use bgpsimulator::simulation_framework::scenarios::{
+    DeaggregationSubprefixHijack, ForgedOriginSubprefixHijack, LegitimatePrefixOnly, NonRoutedSuperprefixHijack, PrefixHijack, PrependingPrefixHijack, RTBHSubprefixHijack,
+    ShortestPathPrefixHijack, SubprefixHijack, SuperprefixHijack, control_plane_outcomes, create_scenario, data_plane_outcomes,
+};
+use bgpsimulator::simulation_framework::{EventSchedule, HijackDataset, HijackLabel, PollutionObserver, ScenarioEvent, ScenarioTrait, TransitFlows};
 
 /// Create a simple test AS graph
 fn create_test_as_graph_simple() -> ASGraph {
@@ -87,7 +101,7 @@ fn test_loop_prevention() {
         1,
         Relationships::Origin,
     );
-    ann.as_path = vec![1, 3, 4]; // Path already contains AS 3, but not AS 2
+    ann.as_path = vec![1, 3, 4].into(); // Path already contains AS 3, but not AS 2
     
     // Setup with initial announcement
     engine.setup(vec![(1, ann)]);
@@ -210,4 +224,1608 @@ fn test_withdrawal() {
     assert!(!snapshot2.get(&2).unwrap().contains_key("10.0.0.0/24"));
     assert!(!snapshot2.get(&3).unwrap().contains_key("10.0.0.0/24"));
     assert!(!snapshot2.get(&4).unwrap().contains_key("10.0.0.0/24"));
-}
\ No newline at end of file
+}
+#[test]
+fn test_withdrawal_falls_back_to_alternative_route() {
+    // AS 3 and AS 4 both originate the prefix; AS 1 prefers the shorter route through 3
+    let as_graph = create_test_as_graph_simple();
+    let prefix: Prefix = "10.0.0.0/24".parse().unwrap();
+    
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.setup(vec![
+        (3, Announcement::new(prefix, 3, Relationships::Origin)),
+        (4, Announcement::new(prefix, 4, Relationships::Origin)),
+    ]);
+    engine.run(5);
+    assert_eq!(engine.get_local_rib_snapshot()[&1]["10.0.0.0/24"], vec![1, 3]);
+    
+    let mut withdrawal = Announcement::new(prefix, 3, Relationships::Origin);
+    withdrawal.withdraw = true;
+    engine.inject(vec![(3, withdrawal.clone())]).unwrap();
+    engine.run(5);
+    
+    let snapshot = engine.get_local_rib_snapshot();
+    assert_eq!(snapshot[&1]["10.0.0.0/24"], vec![1, 2, 4]);
+    assert_eq!(snapshot[&2]["10.0.0.0/24"], vec![2, 4]);
+    assert_eq!(snapshot[&3]["10.0.0.0/24"], vec![3, 1, 2, 4]);
+    assert_eq!(snapshot[&4]["10.0.0.0/24"], vec![4]);
+    
+    // Withdrawing the last origin removes the route everywhere
+    let mut withdrawal_4 = Announcement::new(prefix, 4, Relationships::Origin);
+    withdrawal_4.withdraw = true;
+    engine.inject(vec![(4, withdrawal_4)]).unwrap();
+    engine.run(5);
+    assert!(engine.get_local_rib_snapshot().values().all(|ribs| ribs.is_empty()));
+    
+    // The simple path doesn't support withdrawals
+    let mut engine = SimulationEngine::new(&as_graph).with_storage_mode(PolicyStorageMode::LocalRibOnly);
+    engine.setup(vec![(3, Announcement::new(prefix, 3, Relationships::Origin))]);
+    engine.run(5);
+    assert!(engine.inject(vec![(3, withdrawal)]).is_err());
+}
+
+#[test]
+fn test_incremental_best_path_selection() {
+    // 1 serves 2 and 3, which both serve 4
+    let as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).with_customers(vec![2, 3]),
+        ASBuilder::new(2).with_providers(vec![1]).with_customers(vec![4]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![4]),
+        ASBuilder::new(4).with_providers(vec![2, 3]),
+    ]);
+    let prefix: Prefix = "10.0.0.0/24".parse().unwrap();
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.setup(vec![(4, Announcement::new(prefix, 4, Relationships::Origin))]);
+    engine.run(5);
+    assert_eq!(engine.get_local_rib_snapshot()[&1]["10.0.0.0/24"], vec![1, 2, 4]);
+
+    // The installed best's neighbor replacing it with a worse route makes 1 rescan its RIBs-in
+    let longer = Announcement::new_with_path(prefix, vec![2, 2, 2, 4], 2, Relationships::Origin, Timestamps::VICTIM);
+    engine.inject(vec![(2, longer)]).unwrap();
+    engine.run(5);
+    assert_eq!(engine.get_local_rib_snapshot()[&1]["10.0.0.0/24"], vec![1, 3, 4]);
+
+    // Preferring 2 mid-run reselects its route without waiting for 2 to send an update
+    engine.policy_store.get_mut(&1).unwrap().set_local_pref(2, 500);
+    engine.run(1);
+    assert_eq!(engine.get_local_rib_snapshot()[&1]["10.0.0.0/24"], vec![1, 2, 2, 2, 4]);
+    assert!(!engine.policy_store.get(&1).unwrap().selection_stale);
+
+    // A better route from the installed best's neighbor replaces it without a rescan
+    let shorter = Announcement::new_with_path(prefix, vec![2, 4], 2, Relationships::Origin, Timestamps::VICTIM);
+    engine.inject(vec![(2, shorter)]).unwrap();
+    engine.run(5);
+    assert_eq!(engine.get_local_rib_snapshot()[&1]["10.0.0.0/24"], vec![1, 2, 4]);
+
+    // Swapping the ROAs mid-run makes a depref adopter drop an installed best that became invalid
+    let as_graph = create_test_as_graph_simple();
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.policy_store.get_mut(&1).unwrap().extension = create_policy_extension(Settings::RovDepref);
+    engine.setup(vec![
+        (3, Announcement::new(prefix, 3, Relationships::Origin)),
+        (4, Announcement::new(prefix, 4, Relationships::Origin)),
+    ]);
+    engine.run(5);
+    assert_eq!(engine.get_local_rib_snapshot()[&1]["10.0.0.0/24"], vec![1, 3]);
+    let mut route_validator = RouteValidator::new();
+    route_validator.add_roa(ROA::new(prefix, 4, None));
+    engine.set_route_validator(Arc::new(route_validator));
+    engine.run(5);
+    let snapshot = engine.get_local_rib_snapshot();
+    assert_eq!(snapshot[&1]["10.0.0.0/24"], vec![1, 2, 4]);
+    // The reselected route is exported too
+    assert_eq!(snapshot[&3]["10.0.0.0/24"], vec![3]);
+    assert_eq!(engine.policy_store.get(&3).unwrap().ribs_in.get(1, &prefix).unwrap().as_path.to_vec(), vec![1, 2, 4]);
+
+    // Whatever the tie-breaker, every AS ends up with the route a full scan of its RIBs-in selects
+    let hijack_graph = create_test_as_graph_simple();
+    for tie_breaker in [TieBreaker::LowestNextHopAsn, TieBreaker::OldestAnnouncement, TieBreaker::SeededRandom(7)] {
+        let scenario = PrefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+        let mut engine = SimulationEngine::new(&hijack_graph).with_tie_breaker(tie_breaker);
+        let mut route_validator = RouteValidator::new();
+        scenario.setup_engine(&mut engine, &mut route_validator);
+        engine.run(5);
+        for (&asn, policy) in engine.policy_store.iter() {
+            let as_obj = hijack_graph.get(&asn).unwrap();
+            for (prefix, installed) in policy.local_rib.iter().filter(|(prefix, _)| !policy.originates(prefix)) {
+                let best = policy.get_best_ann_for_prefix(prefix, as_obj).unwrap();
+                assert_eq!(installed.next_hop_asn, best.next_hop_asn, "AS {} with {}", asn, tie_breaker);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_event_schedule() {
+    // AS 4 originates, AS 3 announces the same prefix before round 2 and AS 4 withdraws before round 4
+    let as_graph = create_test_as_graph_simple();
+    let prefix: Prefix = "10.0.0.0/24".parse().unwrap();
+    let roa = ROA::new(prefix, 3, None);
+    let announce = ScenarioEvent::Announce(3, Announcement::new_with_path(prefix, vec![], 3, Relationships::Origin, Timestamps(2)));
+    let schedule = EventSchedule::new()
+        .with_event(4, ScenarioEvent::Withdraw(4, prefix))
+        .with_event(2, announce.clone())
+        .with_event(2, ScenarioEvent::AddRoa(roa.clone()));
+    assert_eq!(schedule.iter().map(|(round, _)| round).collect::<Vec<u32>>(), vec![2, 2, 4]);
+    
+    let run = |rounds| {
+        let mut engine = SimulationEngine::new(&as_graph);
+        let mut route_validator = RouteValidator::new();
+        engine.setup(vec![(4, Announcement::new(prefix, 4, Relationships::Origin))]);
+        schedule.run(&mut engine, &mut route_validator, rounds).map(|()| (engine.get_local_rib_snapshot(), route_validator))
+    };
+    assert!(run(4).is_err());
+    
+    let (snapshot, route_validator) = run(8).unwrap();
+    assert_eq!(snapshot[&1]["10.0.0.0/24"], vec![1, 3]);
+    assert_eq!(snapshot[&2]["10.0.0.0/24"], vec![2, 1, 3]);
+    assert_eq!(snapshot[&4]["10.0.0.0/24"], vec![4, 2, 1, 3]);
+    assert_eq!(route_validator.roas(), vec![roa]);
+    
+    // Before the withdrawal AS 2 still has its customer's route
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.setup(vec![(4, Announcement::new(prefix, 4, Relationships::Origin))]);
+    EventSchedule::new().with_event(2, announce).run(&mut engine, &mut RouteValidator::new(), 4).unwrap();
+    assert_eq!(engine.rounds_finished(), 4);
+    assert_eq!(engine.get_local_rib_snapshot()[&2]["10.0.0.0/24"], vec![2, 4]);
+    
+    let value = serde_json::json!(schedule.to_json());
+    assert_eq!(EventSchedule::from_json(value.as_array().unwrap()), Ok(schedule));
+    let err = ScenarioEvent::from_json(&serde_json::json!({"round": 1, "type": "explode"})).unwrap_err();
+    assert!(err.starts_with("Unknown event type"), "{}", err);
+}
+
+#[test]
+fn test_rov_adopters_share_scenario_roas() {
+    // 1 runs ROV and is the attacker's only provider, so the invalid subprefix stops there
+    let as_graph = create_test_as_graph_simple();
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.policy_store.get_mut(&1).unwrap().extension = create_policy_extension(Settings::Rov);
+    let scenario = SubprefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(5);
+    assert_eq!(engine.route_validator().unwrap().roas(), route_validator.roas());
+    // The seeded routes' outcomes are precomputed for the policies, keyed by the engine's prefix ids
+    let frozen = engine.route_validator().unwrap().frozen().unwrap();
+    assert!(Arc::ptr_eq(frozen.table(), &engine.policy_store.prefix_table));
+    assert_eq!(frozen.prefix_id(&scenario.hijacked_prefix), engine.policy_store.prefix_table.id(&scenario.hijacked_prefix));
+    assert_eq!(frozen.get(&scenario.hijacked_prefix, 3).unwrap().0, ROAValidity::InvalidLengthAndOrigin);
+    assert_eq!(frozen.len(), 2);
+    assert!(!engine.policy_store.get(&1).unwrap().local_rib.contains_key(&scenario.hijacked_prefix));
+    assert!(!engine.policy_store.get(&2).unwrap().local_rib.contains_key(&scenario.hijacked_prefix));
+
+    // ROA events reach ROV adopters through the engine too
+    let legitimate_prefix = scenario.legitimate_prefix;
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.policy_store.get_mut(&1).unwrap().extension = create_policy_extension(Settings::Rov);
+    engine.setup(vec![(3, Announcement::new(legitimate_prefix, 3, Relationships::Origin))]);
+    let mut route_validator = RouteValidator::new();
+    EventSchedule::new()
+        .with_event(0, ScenarioEvent::AddRoa(ROA::new(legitimate_prefix, 4, None)))
+        .run(&mut engine, &mut route_validator, 3)
+        .unwrap();
+    assert!(!engine.policy_store.get(&1).unwrap().local_rib.contains_key(&legitimate_prefix));
+}
+
+#[test]
+fn test_link_and_as_failures() {
+    // AS 4 is multihomed to 2 and 3, which are both customers of 1
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_customers(vec![2, 3]),
+        ASBuilder::new(2).with_providers(vec![1]).with_customers(vec![4]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![4]),
+        ASBuilder::new(4).with_providers(vec![2, 3]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    let prefix: Prefix = "10.0.0.0/24".parse().unwrap();
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.setup(vec![(4, Announcement::new(prefix, 4, Relationships::Origin))]);
+    engine.run(5);
+    assert_eq!(engine.get_local_rib_snapshot()[&1]["10.0.0.0/24"], vec![1, 2, 4]);
+    
+    // Routes learned over the failed link are withdrawn, and both ends fall back to other routes
+    engine.fail_link(4, 2).unwrap();
+    assert!(engine.is_link_failed(2, 4));
+    engine.run(5);
+    let snapshot = engine.get_local_rib_snapshot();
+    assert_eq!(snapshot[&1]["10.0.0.0/24"], vec![1, 3, 4]);
+    assert_eq!(snapshot[&2]["10.0.0.0/24"], vec![2, 1, 3, 4]);
+    assert!(engine.policy_store.get(&4).unwrap().ribs_in.neighbor_routes(2).next().is_none());
+    
+    // Without AS 3 the origin is cut off
+    engine.fail_as(3).unwrap();
+    assert!(engine.is_as_failed(3));
+    engine.run(5);
+    let snapshot = engine.get_local_rib_snapshot();
+    for asn in [1, 2, 3] {
+        assert!(snapshot[&asn].is_empty(), "AS {} still has {:?}", asn, snapshot[&asn]);
+    }
+    assert_eq!(snapshot[&4]["10.0.0.0/24"], vec![4]);
+    assert!(engine.inject(vec![(3, Announcement::new(prefix, 3, Relationships::Origin))]).is_err());
+    
+    // A new setup brings everything back up
+    engine.setup(vec![(4, Announcement::new(prefix, 4, Relationships::Origin))]);
+    assert!(!engine.is_as_failed(3) && !engine.is_link_failed(2, 4));
+    
+    assert!(engine.fail_link(1, 4).is_err());
+    let mut engine = SimulationEngine::new(&as_graph).with_storage_mode(PolicyStorageMode::LocalRibOnly);
+    assert!(engine.fail_link(2, 4).is_err());
+    
+    // Failures can be scheduled like other events
+    let schedule = EventSchedule::new()
+        .with_event(3, ScenarioEvent::FailLink(2, 4))
+        .with_event(6, ScenarioEvent::FailAs(3));
+    let value = serde_json::json!(schedule.to_json());
+    assert_eq!(EventSchedule::from_json(value.as_array().unwrap()), Ok(schedule.clone()));
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.setup(vec![(4, Announcement::new(prefix, 4, Relationships::Origin))]);
+    schedule.run(&mut engine, &mut RouteValidator::new(), 10).unwrap();
+    assert!(engine.get_local_rib_snapshot()[&1].is_empty());
+}
+
+#[test]
+fn test_transit_flows() {
+    let as_graph = create_test_as_graph_simple();
+    let mut engine = SimulationEngine::new(&as_graph);
+    
+    let prefix: Prefix = "10.0.0.0/24".parse().unwrap();
+    let ann = Announcement::new(prefix, 4, Relationships::Origin);
+    
+    engine.setup(vec![(4, ann)]);
+    engine.run(5);
+    
+    // Paths: 4 -> [4], 2 -> [2, 4], 1 -> [1, 2, 4], 3 -> [3, 1, 2, 4]
+    let flows = TransitFlows::from_engine(&engine, &prefix);
+    assert_eq!(flows.num_routed_ases, 4);
+    assert_eq!(flows.flow_rows(), vec![(2, 4, 3), (1, 2, 2), (3, 1, 1)]);
+    
+    // AS 2 carries traffic for AS 1 and AS 3, AS 1 only for AS 3
+    assert_eq!(flows.top_chokepoints(1), vec![(2, 2)]);
+    assert_eq!(flows.transit_counts.get(&1), Some(&1));
+    assert_eq!(flows.to_csv().lines().nth(1), Some("2,4,3"));
+}
+
+#[test]
+fn test_diff_rounds() {
+    let as_graph = create_test_as_graph_simple();
+    let mut engine = SimulationEngine::new(&as_graph);
+    
+    let prefix: Prefix = "10.0.0.0/24".parse().unwrap();
+    let ann = Announcement::new(prefix, 4, Relationships::Origin);
+    engine.setup(vec![(4, ann)]);
+    
+    let diffs = engine.diff_rounds(3);
+    assert_eq!(diffs.len(), 3);
+    
+    // Everything converges in the first round
+    assert_eq!(diffs[0].num_changes(), 3);
+    assert_eq!(diffs[0].changes[&3], vec![RouteChange::Installed {
+        prefix: "10.0.0.0/24".to_string(),
+        as_path: vec![3, 1, 2, 4],
+    }]);
+    assert!(diffs[1].is_empty());
+    assert!(diffs[2].is_empty());
+    
+    // Withdrawing the prefix shows up as removals
+    let mut withdrawal = Announcement::new(prefix, 4, Relationships::Origin);
+    withdrawal.withdraw = true;
+    let before = engine.get_local_rib_snapshot();
+    engine.setup(vec![(4, withdrawal)]);
+    engine.run(5);
+    let diff = RoundDiff::between(0, &before, &engine.get_local_rib_snapshot());
+    assert_eq!(diff.num_changes(), 4);
+    assert!(diff.changes.values().flatten().all(|change| matches!(change, RouteChange::Removed { .. })));
+}
+
+#[test]
+fn test_step_through_phases() {
+    let as_graph = create_test_as_graph_simple();
+    let prefix: Prefix = "10.0.0.0/24".parse().unwrap();
+    let seeds = || vec![(4, Announcement::new(prefix, 4, Relationships::Origin))];
+    let mut engine = SimulationEngine::new(&as_graph).with_round_diffs();
+    engine.setup(seeds());
+    
+    // The seed waits at the origin's provider
+    let pending = engine.pending_announcements();
+    assert_eq!(pending.keys().copied().collect::<Vec<_>>(), vec![2]);
+    assert_eq!(pending[&2][0].recv_relationship, Relationships::Customers);
+    
+    // Going up reaches the tier-1, but not its other customer
+    assert_eq!(engine.next_phase(), PropagationPhase::Providers);
+    assert_eq!(engine.step(), PropagationPhase::Providers);
+    assert!(engine.policy_store.get(&1).unwrap().local_rib.contains_key(&prefix));
+    assert!(!engine.policy_store.get(&3).unwrap().local_rib.contains_key(&prefix));
+    assert!(engine.pending_announcements().contains_key(&3));
+    
+    assert_eq!(engine.step(), PropagationPhase::Peers);
+    assert_eq!(engine.step(), PropagationPhase::Customers);
+    assert!(engine.policy_store.get(&3).unwrap().local_rib.contains_key(&prefix));
+    assert_eq!(engine.next_phase(), PropagationPhase::Providers);
+    assert_eq!(engine.get_round_diffs().unwrap().len(), 1);
+    
+    // Three steps make a round, and run finishes a round left partway
+    let mut stepped = SimulationEngine::new(&as_graph);
+    stepped.setup(seeds());
+    stepped.step();
+    stepped.run(1);
+    assert_eq!(stepped.next_phase(), PropagationPhase::Providers);
+    assert_eq!(stepped.get_local_rib_snapshot(), engine.get_local_rib_snapshot());
+    
+    let mut ran = SimulationEngine::new(&as_graph);
+    ran.setup(seeds());
+    ran.run(1);
+    assert_eq!(ran.get_local_rib_snapshot(), engine.get_local_rib_snapshot());
+}
+
+#[test]
+fn test_recorded_round_diffs() {
+    let as_graph = create_test_as_graph_simple();
+    let engine = SimulationEngine::new(&as_graph);
+    assert!(engine.get_round_diffs().is_none());
+    
+    let mut engine = SimulationEngine::new(&as_graph).with_round_diffs();
+    let prefix: Prefix = "10.0.0.0/24".parse().unwrap();
+    engine.setup(vec![(4, Announcement::new(prefix, 4, Relationships::Origin))]);
+    engine.run(2);
+    
+    // Same diffs as diff_rounds, recorded by plain runs
+    let diffs = engine.get_round_diffs().unwrap();
+    assert_eq!(diffs.len(), 2);
+    assert_eq!(diffs[0].num_changes(), 3);
+    assert!(diffs[1].is_empty());
+    assert_eq!(diffs[0].to_json()["changes"]["3"], serde_json::json!([{
+        "change": "installed",
+        "prefix": "10.0.0.0/24",
+        "as_path": [3, 1, 2, 4],
+    }]));
+    
+    // A new setup starts a new recording
+    engine.setup(vec![(4, Announcement::new(prefix, 4, Relationships::Origin))]);
+    assert_eq!(engine.get_round_diffs().unwrap().len(), 0);
+}
+
+#[test]
+fn test_origin_export_restrictions() {
+    // AS 4 is multihomed to providers 2 and 3, which are both customers of AS 1
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_customers(vec![2, 3]),
+        ASBuilder::new(2).with_providers(vec![1]).with_customers(vec![4]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![4]),
+        ASBuilder::new(4).with_providers(vec![2, 3]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    
+    // Victim only announces to provider 2
+    let scenario = LegitimatePrefixOnly::new(HashSet::from([4]))
+        .with_victim_export_asns(HashSet::from([2]));
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(5);
+    
+    let snapshot = engine.get_local_rib_snapshot();
+    assert_eq!(snapshot[&2]["10.0.0.0/24"], vec![2, 4]);
+    assert_eq!(snapshot[&1]["10.0.0.0/24"], vec![1, 2, 4]);
+    // Provider 3 only learns the route the long way around
+    assert_eq!(snapshot[&3]["10.0.0.0/24"], vec![3, 1, 2, 4]);
+    
+    // Without the restriction both providers hear it directly
+    let scenario = LegitimatePrefixOnly::new(HashSet::from([4]));
+    let mut engine = SimulationEngine::new(&as_graph);
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(5);
+    assert_eq!(engine.get_local_rib_snapshot()[&3]["10.0.0.0/24"], vec![3, 4]);
+}
+
+#[test]
+fn test_hijack_dataset() {
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_customers(vec![2, 3]),
+        ASBuilder::new(2).with_providers(vec![1]).with_customers(vec![4]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![4]),
+        ASBuilder::new(4).with_providers(vec![2, 3]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+
+    // Attacker 3 hijacks a subprefix of victim 4
+    let scenario = SubprefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(5);
+
+    let dataset = HijackDataset::from_engine(&engine, &route_validator, &HashSet::from([4]), &HashSet::from([3]));
+    assert!(dataset.num_with_label(HijackLabel::Hijack) > 0);
+    assert!(dataset.num_with_label(HijackLabel::Legitimate) > 0);
+
+    for sample in &dataset.samples {
+        match sample.label {
+            HijackLabel::Hijack => {
+                assert_eq!(sample.origin_asn, 3);
+                assert_eq!(sample.roa_validity, ROAValidity::InvalidLengthAndOrigin);
+            }
+            HijackLabel::Legitimate => {
+                assert_eq!(sample.origin_asn, 4);
+                assert_eq!(sample.roa_validity, ROAValidity::Valid);
+                assert_eq!(sample.origin_customer_cone_size, 0);
+                assert_eq!(sample.origin_num_providers, 2);
+            }
+        }
+        assert_eq!(sample.as_path_length, engine.policy_store.get(&sample.observer_asn).unwrap()
+            .ribs_in[(sample.next_hop_asn, &sample.prefix)].as_path.len());
+    }
+
+    // AS 1 hears the victim from customer 2 and both routes from customer 3, and selects the more specific hijack
+    let as_1: Vec<_> = dataset.samples.iter().filter(|sample| sample.observer_asn == 1).collect();
+    assert_eq!(as_1.len(), 3);
+    assert!(as_1.iter().all(|sample| sample.recv_relationship == Relationships::Customers));
+    assert!(as_1.iter().filter(|sample| sample.label == HijackLabel::Hijack).all(|sample| sample.selected));
+
+    let csv = dataset.to_csv();
+    assert_eq!(csv.lines().count(), dataset.len() + 1);
+    assert!(csv.starts_with(HijackDataset::CSV_HEADER));
+    let json = dataset.to_json();
+    assert_eq!(json["num_samples"], dataset.len());
+    assert_eq!(json["samples"].as_array().unwrap().len(), dataset.len());
+}
+
+#[test]
+fn test_local_rib_only_storage() {
+    let as_graph = create_test_as_graph_simple();
+    let scenario = SubprefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+    
+    let mut full = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut full, &mut route_validator);
+    full.run(5);
+    
+    let mut local_rib_only = SimulationEngine::new(&as_graph).with_storage_mode(PolicyStorageMode::LocalRibOnly);
+    scenario.setup_engine(&mut local_rib_only, &mut route_validator);
+    local_rib_only.run(5);
+    
+    assert_eq!(local_rib_only.get_local_rib_snapshot(), full.get_local_rib_snapshot());
+    assert!(local_rib_only.policy_store.iter().all(|(_, policy)| policy.ribs_in.is_empty() && policy.ribs_out.is_empty()));
+    assert!(full.policy_store.iter().any(|(_, policy)| !policy.ribs_in.is_empty()));
+}
+
+#[test]
+fn test_action_communities() {
+    let as_graph = create_test_as_graph_simple();
+    let mut route_validator = RouteValidator::new();
+    
+    // Victim 4 asks its provider 2 to prepend twice towards 2's providers
+    let scenario = LegitimatePrefixOnly::new(HashSet::from([4]))
+        .with_victim_communities(vec![ActionCommunity::prepend(2, 2, Relationships::Providers)]);
+    for storage_mode in [PolicyStorageMode::Full, PolicyStorageMode::LocalRibOnly] {
+        let mut engine = SimulationEngine::new(&as_graph).with_storage_mode(storage_mode);
+        scenario.setup_engine(&mut engine, &mut route_validator);
+        engine.run(5);
+        
+        let snapshot = engine.get_local_rib_snapshot();
+        assert_eq!(snapshot[&2]["10.0.0.0/24"], vec![2, 4]);
+        assert_eq!(snapshot[&1]["10.0.0.0/24"], vec![1, 2, 2, 2, 4]);
+        assert_eq!(snapshot[&3]["10.0.0.0/24"], vec![3, 1, 2, 2, 2, 4]);
+    }
+    
+    // AS 1 honors a no-export request from its customer's customer, so 3 never hears the route
+    let scenario = LegitimatePrefixOnly::new(HashSet::from([4]))
+        .with_victim_communities(vec![ActionCommunity::no_export_to(1, 3)]);
+    let mut engine = SimulationEngine::new(&as_graph);
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(5);
+    let snapshot = engine.get_local_rib_snapshot();
+    assert_eq!(snapshot[&1]["10.0.0.0/24"], vec![1, 2, 4]);
+    assert!(snapshot[&3].is_empty());
+    
+    // Attacker 3 keeps its hijack away from AS 2, so the victim's provider isn't hijacked
+    let scenario = SubprefixHijack::new(HashSet::from([3]), HashSet::from([4]))
+        .with_attacker_communities(vec![ActionCommunity::no_export_to(1, 2)]);
+    let mut engine = SimulationEngine::new(&as_graph);
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(5);
+    let snapshot = engine.get_local_rib_snapshot();
+    assert_eq!(snapshot[&1]["1.2.3.0/25"], vec![1, 3]);
+    assert!(!snapshot[&2].contains_key("1.2.3.0/25"));
+}
+
+#[test]
+fn test_rtbh_subprefix_hijack() {
+    //   1 (Tier-1) --peer-- 2 (Tier-1)
+    //   |      \             |
+    //   3       6            5 (attacker)
+    //   |  \
+    //   4   7
+    //   (victim)
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_peers(vec![2]).with_customers(vec![3, 6]),
+        ASBuilder::new(2).as_tier_1().with_peers(vec![1]).with_customers(vec![5]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![4, 7]),
+        ASBuilder::new(4).with_providers(vec![3]),
+        ASBuilder::new(5).with_providers(vec![2]),
+        ASBuilder::new(6).with_providers(vec![1]),
+        ASBuilder::new(7).with_providers(vec![3]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    
+    let scenario = RTBHSubprefixHijack::new(HashSet::from([5]), HashSet::from([4]));
+    assert_eq!(scenario.get_blackhole_asns(&as_graph, 4), HashSet::from([3]));
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(5);
+    
+    // The blackhole route stays at the victim's provider
+    let snapshot = engine.get_local_rib_snapshot();
+    assert_eq!(snapshot[&3]["1.2.3.0/25"], vec![3, 4]);
+    assert!(!snapshot[&7].contains_key("1.2.3.0/25"));
+    
+    let outcomes = scenario.data_plane_outcomes(&engine);
+    assert_eq!(outcomes[&4], Outcomes::VictimSuccess);
+    assert_eq!(outcomes[&3], Outcomes::Blackholed);
+    // 7 only has the covering prefix, which leads into the blackhole
+    assert_eq!(outcomes[&7], Outcomes::Blackholed);
+    for asn in [1, 2, 5, 6] {
+        assert_eq!(outcomes[&asn], Outcomes::AttackerSuccess, "AS {}", asn);
+    }
+    assert!(scenario.is_successful(&engine));
+    
+    // The blackholing AS selected the victim's route even though the traffic goes nowhere
+    let control_plane = control_plane_outcomes(&engine, &HashSet::from([5]), &HashSet::from([4]), scenario.get_dest_ip_addr());
+    assert_eq!(control_plane[&3], Outcomes::VictimSuccess);
+    assert_eq!(control_plane[&1], Outcomes::AttackerSuccess);
+    
+    let metrics: HashMap<String, f64> = scenario.custom_metrics().evaluate(&engine).into_iter().collect();
+    assert_eq!(metrics["blackholed_fraction"], 2.0 / 7.0);
+    assert_eq!(metrics["hijacked_fraction"], 4.0 / 7.0);
+    assert_eq!(metrics["disconnected_fraction"], 0.0);
+}
+
+#[test]
+fn test_deaggregation_response() {
+    let as_graph = create_test_as_graph_simple();
+    let run = |update_roas: bool| {
+        // 2 runs ROV, so unless the victim publishes ROAs its /26s never get past it to 1
+        let mut engine = SimulationEngine::new(&as_graph);
+        engine.policy_store.get_mut(&2).unwrap().extension = create_policy_extension(Settings::Rov);
+        let scenario = DeaggregationSubprefixHijack::new(HashSet::from([3]), HashSet::from([4]))
+            .with_response_round(2)
+            .with_deaggregation_length(26)
+            .with_update_roas(update_roas);
+        let mut route_validator = RouteValidator::new();
+        scenario.setup_engine(&mut engine, &mut route_validator);
+        scenario.get_events(&as_graph).run(&mut engine, &mut route_validator, 5).unwrap();
+        scenario.data_plane_outcomes(&engine)
+    };
+    
+    let scenario = DeaggregationSubprefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+    let prefixes: Vec<String> = scenario.with_deaggregation_length(26).deaggregated_prefixes().iter().map(|p| p.to_string()).collect();
+    assert_eq!(prefixes, vec!["1.2.3.0/26", "1.2.3.64/26", "1.2.3.128/26", "1.2.3.192/26"]);
+    
+    let outcomes = run(false);
+    assert_eq!(outcomes[&1], Outcomes::AttackerSuccess);
+    assert_eq!(outcomes[&2], Outcomes::VictimSuccess);
+    let outcomes = run(true);
+    assert_eq!(outcomes[&1], Outcomes::VictimSuccess);
+    assert_eq!(outcomes[&2], Outcomes::VictimSuccess);
+    assert_eq!(outcomes[&3], Outcomes::AttackerSuccess);
+}
+
+#[test]
+fn test_forged_origin_subprefix_hijack() {
+    let as_graph = create_test_as_graph_simple();
+    let run = |roa_max_length: u8| {
+        // 1 runs ROV, which only catches the forged /25 when the ROA's max length is /24
+        let mut engine = SimulationEngine::new(&as_graph);
+        engine.policy_store.get_mut(&1).unwrap().extension = create_policy_extension(Settings::Rov);
+        let scenario = ForgedOriginSubprefixHijack::new(HashSet::from([3]), HashSet::from([4])).with_roa_max_length(roa_max_length);
+        let mut route_validator = RouteValidator::new();
+        scenario.setup_engine(&mut engine, &mut route_validator);
+        engine.run(5);
+        let hijacked = engine.policy_store.get(&1).unwrap().local_rib.get(&scenario.hijacked_prefix).map(|ann| ann.as_path.to_vec());
+        (hijacked, scenario.is_successful(&engine))
+    };
+    
+    assert_eq!(run(24), (None, false));
+    assert_eq!(run(32), (Some(vec![1, 3, 4]), true));
+    
+    let scenario = ForgedOriginSubprefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+    let seeds = scenario.get_seed_asn_ann_dict(&as_graph);
+    assert_eq!(seeds[&3][0].as_path, vec![3, 4]);
+    assert_eq!(seeds[&3][0].origin(), 4);
+    assert!(create_scenario("ForgedOriginSubprefixHijack", HashSet::from([3]), HashSet::from([4])).is_ok());
+    let unknown = create_scenario("ForgedOriginHijack", HashSet::from([3]), HashSet::from([4]));
+    assert!(matches!(unknown, Err(Error::ScenarioUnknown(name)) if name == "ForgedOriginHijack"));
+}
+
+#[test]
+fn test_engine_runner_uses_configured_roles() {
+    // Attacker 3 and victim 4 instead of the default 666 and 777
+    let scenario_config = ScenarioConfig::new("Roles".to_string(), "SubprefixHijack".to_string())
+        .with_attacker_asns(HashSet::from([3]))
+        .with_legitimate_origin_asns(HashSet::from([4]))
+        .with_asn_setting(1, Settings::Rov);
+    let config = EngineRunConfig::new(
+        "test_engine_runner_uses_configured_roles".to_string(),
+        scenario_config,
+        create_test_as_graph_simple(),
+    );
+    let base_dir = std::env::temp_dir().join("bgpsimulator_engine_runner_test");
+    let runner = EngineRunner::new(config)
+        .with_base_dir(base_dir)
+        .with_overwrite(true)
+        .with_write_diagrams(false)
+        .with_write_policies(true);
+    
+    let outcomes = runner.run().unwrap();
+    let mut asns: Vec<u32> = outcomes.keys().copied().collect();
+    asns.sort();
+    assert_eq!(asns, vec![1, 2, 3, 4]);
+    assert_eq!(outcomes[&3], Outcomes::AttackerSuccess);
+    
+    let stored: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(runner.storage_dir.join("config.json")).unwrap()
+    ).unwrap();
+    assert_eq!(stored["scenario_config"]["attacker_asns"], serde_json::json!([3]));
+    assert_eq!(stored["scenario_config"]["asn_settings"]["1"], serde_json::json!(Settings::Rov));
+    
+    // The scenario's analysis judges the installed subprefix routes against its ROAs, which ROV at 1
+    // also validated against, so the invalid subprefix never gets past the attacker's only provider
+    let analysis: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(runner.storage_dir.join("analysis_guess.json")).unwrap()
+    ).unwrap();
+    assert_eq!(analysis["prefix"], "1.2.3.0/25");
+    assert_eq!(analysis["routes_by_validity"], serde_json::json!({"INVALID_LENGTH_AND_ORIGIN": 1}));
+    assert_eq!(analysis["no_route"], 3);
+    
+    let policies: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(runner.storage_dir.join("policies_guess.json")).unwrap()
+    ).unwrap();
+    assert_eq!(policies.as_object().unwrap().len(), 4);
+    assert_eq!(policies["1"], serde_json::json!({"settings": Settings::Rov, "policy": "ROV", "adopting": true}));
+    assert_eq!(policies["2"]["settings"], serde_json::json!(Settings::BaseDefense));
+    assert_eq!(policies["2"]["adopting"], false);
+}
+
+#[test]
+fn test_engine_runner_coordinated_attack() {
+    // Both attackers announce the subprefix, and every AS routing to either counts as hijacked
+    let scenario_config = ScenarioConfig::new("Coordinated".to_string(), "SubprefixHijack".to_string())
+        .with_attacker_asns(HashSet::from([1, 3]))
+        .with_legitimate_origin_asns(HashSet::from([4]));
+    let config = EngineRunConfig::new(
+        "test_engine_runner_coordinated_attack".to_string(),
+        scenario_config,
+        create_test_as_graph_simple(),
+    );
+    let base_dir = std::env::temp_dir().join("bgpsimulator_engine_runner_test");
+    let runner = EngineRunner::new(config)
+        .with_base_dir(base_dir)
+        .with_overwrite(true)
+        .with_write_diagrams(false);
+    
+    let outcomes = runner.run().unwrap();
+    assert_eq!(outcomes[&1], Outcomes::AttackerSuccess);
+    assert_eq!(outcomes[&2], Outcomes::AttackerSuccess);
+    assert_eq!(outcomes[&3], Outcomes::AttackerSuccess);
+    assert_eq!(outcomes[&4], Outcomes::VictimSuccess);
+}
+
+#[test]
+fn test_engine_runner_victims_without_roa() {
+    // With no ROA for the victim, ROV at 1 has nothing to drop the subprefix hijack for
+    let scenario_config = ScenarioConfig::new("NoRoa".to_string(), "SubprefixHijack".to_string())
+        .with_attacker_asns(HashSet::from([3]))
+        .with_legitimate_origin_asns(HashSet::from([4]))
+        .with_asn_setting(1, Settings::Rov)
+        .with_percent_victims_without_roa(100.0);
+    let config = EngineRunConfig::new(
+        "test_engine_runner_victims_without_roa".to_string(),
+        scenario_config,
+        create_test_as_graph_simple(),
+    );
+    let base_dir = std::env::temp_dir().join("bgpsimulator_engine_runner_test");
+    let runner = EngineRunner::new(config)
+        .with_base_dir(base_dir)
+        .with_overwrite(true)
+        .with_write_diagrams(false);
+    
+    runner.run().unwrap();
+    let analysis: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(runner.storage_dir.join("analysis_guess.json")).unwrap()
+    ).unwrap();
+    assert_eq!(analysis["routes_by_validity"], serde_json::json!({"UNKNOWN": 4}));
+    assert_eq!(analysis["no_route"], 0);
+}
+
+#[test]
+fn test_engine_runner_writes_round_diffs() {
+    let scenario_config = ScenarioConfig::new("Diffs".to_string(), "SubprefixHijack".to_string())
+        .with_attacker_asns(HashSet::from([3]))
+        .with_legitimate_origin_asns(HashSet::from([4]));
+    let config = EngineRunConfig::new(
+        "test_engine_runner_writes_round_diffs".to_string(),
+        scenario_config,
+        create_test_as_graph_simple(),
+    );
+    let runner = EngineRunner::new(config)
+        .with_base_dir(std::env::temp_dir().join("bgpsimulator_engine_runner_test"))
+        .with_overwrite(true)
+        .with_write_diagrams(false)
+        .with_record_round_diffs(true);
+    runner.run().unwrap();
+    
+    let round_diffs: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(runner.storage_dir.join("round_diffs_guess.json")).unwrap()
+    ).unwrap();
+    let round_diffs = round_diffs.as_array().unwrap();
+    assert_eq!(round_diffs.len(), runner.config.propagation_rounds as usize);
+    assert_eq!(round_diffs[0]["round"], 0);
+    // AS 1 first learns the hijacked subprefix from the attacker
+    assert!(round_diffs[0]["changes"]["1"].as_array().unwrap().iter()
+        .any(|change| change["prefix"] == "1.2.3.0/25" && change["change"] == "installed"));
+}
+
+#[test]
+fn test_engine_run_diff() {
+    let scenario_config = ScenarioConfig::new("Diff".to_string(), "SubprefixHijack".to_string())
+        .with_attacker_asns(HashSet::from([3]))
+        .with_legitimate_origin_asns(HashSet::from([4]));
+    let config = EngineRunConfig::new(
+        "test_engine_run_diff".to_string(),
+        scenario_config.clone(),
+        create_test_as_graph_simple(),
+    );
+    let runner = EngineRunner::new(config)
+        .with_base_dir(std::env::temp_dir().join("bgpsimulator_engine_runner_test"))
+        .with_overwrite(true)
+        .with_write_diagrams(false);
+    
+    // ROV at the attacker's only provider keeps the subprefix from everyone but the attacker
+    let diff = EngineRunDiff::run(&runner, scenario_config.with_asn_setting(1, Settings::Rov)).unwrap();
+    // The victim loses the subprefix route too, but it kept its own traffic either way
+    assert_eq!(diff.changes.keys().copied().collect::<Vec<u32>>(), vec![1, 2, 4]);
+    assert!(!diff.changes[&4].outcome_changed() && diff.changes[&4].route_changes.len() == 1);
+    assert_eq!(diff.protected_asns(), vec![1, 2]);
+    assert!(diff.harmed_asns().is_empty());
+    assert_eq!(diff.changes[&2].outcome_b, Some(Outcomes::VictimSuccess));
+    assert_eq!(diff.changes[&1].route_changes, vec![RouteChange::Removed {
+        prefix: "1.2.3.0/25".to_string(),
+        as_path: vec![1, 3],
+    }]);
+    assert!(diff.to_string().starts_with("3 AS(es) changed: 2 protected, 0 harmed"));
+    
+    // Both runs are kept next to the diff
+    assert!(runner.storage_dir.join("a").join("outcomes_guess.json").exists());
+    assert!(runner.storage_dir.join("b").join("outcomes_guess.json").exists());
+    let stored: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(runner.storage_dir.join("diff.json")).unwrap()
+    ).unwrap();
+    assert_eq!(stored["protected"], serde_json::json!([1, 2]));
+    assert_eq!(stored["changes"]["2"]["outcome_a"], serde_json::json!(Outcomes::AttackerSuccess));
+    
+    // Identical runs don't differ anywhere
+    let outcomes = HashMap::from([(1, Outcomes::VictimSuccess)]);
+    let local_ribs = HashMap::from([(1, HashMap::from([("1.2.3.0/24".to_string(), vec![1, 2, 4])]))]);
+    assert!(EngineRunDiff::between(&outcomes, &local_ribs, &outcomes, &local_ribs).changes.is_empty());
+}
+
+#[test]
+fn test_engine_test_suite() {
+    let mut suite = EngineTestSuite::load_dir(Path::new("tests/fixtures/engine_tests")).unwrap();
+    assert_eq!(suite.cases.len(), 3);
+    let base_dir = std::env::temp_dir().join("bgpsimulator_engine_test_suite");
+    let report = suite.run(&base_dir);
+    assert!(report.all_passed(), "{}", report);
+    
+    // A wrong expectation is reported per AS
+    let case = &mut suite.cases[0];
+    case.expected_outcomes.as_mut().unwrap().insert(4, Outcomes::AttackerSuccess);
+    case.expected_local_ribs.as_mut().unwrap().remove(&3);
+    let result = case.run(&base_dir);
+    assert!(!result.passed());
+    assert_eq!(result.diffs, vec![
+        "AS 3 10.0.0.0/24: expected none, got [3, 1, 2, 4]".to_string(),
+        "AS 4 outcome: expected AttackerSuccess, got VictimSuccess".to_string(),
+    ]);
+    
+    // Loading again is fine, but not two cases with one name
+    assert_eq!(EngineTestSuite::load_dir(Path::new("tests/fixtures/engine_tests")).unwrap().cases.len(), 3);
+    let dir = std::env::temp_dir().join("bgpsimulator_engine_test_suite_duplicates");
+    std::fs::create_dir_all(&dir).unwrap();
+    for file_name in ["a.yaml", "b.yaml"] {
+        std::fs::copy("tests/fixtures/engine_tests/prefix_hijack_bgp.yaml", dir.join(file_name)).unwrap();
+    }
+    let err = EngineTestSuite::load_dir(&dir).unwrap_err();
+    assert!(err.ends_with("already used"), "{}", err);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_config_namespace() {
+    let config = || EngineRunConfig::new(
+        "test_config_namespace".to_string(),
+        ScenarioConfig::new("SubprefixHijack".to_string(), "SubprefixHijack".to_string()),
+        create_test_as_graph_simple(),
+    );
+    // Configs are free to share names; only a namespace keeps them apart
+    let (a, b) = (config(), config());
+    let mut namespace = ConfigNamespace::new();
+    assert!(namespace.register(&a).is_ok());
+    assert!(matches!(
+        namespace.register(&b),
+        Err(Error::ConfigInvalid(msg)) if msg == "Name 'test_config_namespace' already used"
+    ));
+    assert!(namespace.contains("test_config_namespace"));
+    assert_eq!(namespace.len(), 1);
+    
+    namespace.reset();
+    assert!(namespace.is_empty());
+    assert!(namespace.register(&b).is_ok());
+}
+
+#[test]
+fn test_engine_run_config_from_yaml_file() {
+    let path = std::env::temp_dir().join("bgpsimulator_engine_run_config_test.yaml");
+    std::fs::write(&path, "\
+name: test_engine_run_config_from_yaml_file
+propagation_rounds: 5
+scenario_config:
+  scenario_name: SubprefixHijack
+  attacker_asns: [3]
+  legitimate_origin_asns: [4]
+  asn_settings:
+    1: Rov
+as_graph:
+  - {asn: 1, customers: [2, 3], tier_1: true}
+  - {asn: 2, providers: [1], customers: [4]}
+  - {asn: 3, providers: [1]}
+  - {asn: 4, providers: [2]}
+").unwrap();
+
+    let config = EngineRunConfig::from_file(&path).unwrap();
+    assert_eq!(config.propagation_rounds, 5);
+    assert_eq!(config.as_graph.len(), 4);
+    assert!(config.as_graph.get(&1).unwrap().tier_1);
+    assert_eq!(config.scenario_config.override_asn_settings, Some(HashMap::from([(1, Settings::Rov)])));
+
+    let base_dir = std::env::temp_dir().join("bgpsimulator_engine_runner_test");
+    let runner = EngineRunner::new(config)
+        .with_base_dir(base_dir)
+        .with_overwrite(true)
+        .with_write_diagrams(false);
+    let outcomes = runner.run().unwrap();
+    assert_eq!(outcomes[&3], Outcomes::AttackerSuccess);
+
+    // The stored config describes the same run
+    let stored: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(runner.storage_dir.join("config.json")).unwrap()
+    ).unwrap();
+    let reloaded = EngineRunConfig::from_json(&stored).unwrap();
+    assert_eq!(reloaded.as_graph.to_json(), runner.config.as_graph.to_json());
+    assert_eq!(reloaded.scenario_config.override_attacker_asns, Some(HashSet::from([3])));
+    assert_eq!(reloaded.propagation_rounds, 5);
+}
+
+#[test]
+fn test_engine_snapshot_round_trip() {
+    let as_graph = create_test_as_graph_simple();
+    let mut engine = SimulationEngine::new(&as_graph);
+    let v4_prefix = Prefix::from_str("1.2.0.0/16").unwrap();
+    let v6_prefix = Prefix::from_str("2001:db8::/32").unwrap();
+    engine.setup(vec![
+        (4, Announcement::new_with_path(v4_prefix, vec![], 4, Relationships::Origin, Timestamps::VICTIM)),
+        (3, Announcement::new_with_path(v6_prefix, vec![], 3, Relationships::Origin, Timestamps::VICTIM)),
+    ]);
+    engine.run(3);
+
+    let bytes = EngineSnapshot::to_bytes(&engine);
+    let reader = SnapshotReader::from_bytes(&bytes).unwrap();
+    assert_eq!(reader.asns().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    assert_eq!(reader.num_prefixes(), 2);
+    assert_eq!(reader.num_routes(), 8);
+
+    for asn in reader.asns() {
+        let local_rib = &engine.policy_store.get(&asn).unwrap().local_rib;
+        assert_eq!(reader.routes(asn).count(), local_rib.len());
+        for route in reader.routes(asn) {
+            let ann = &local_rib[&route.prefix().unwrap()];
+            assert_eq!(route.next_hop_asn, ann.next_hop_asn);
+            assert_eq!(route.recv_relationship, ann.recv_relationship);
+            assert_eq!(route.as_path().collect::<Vec<_>>(), ann.as_path.to_vec());
+        }
+    }
+    let route = reader.route(1, &v6_prefix).unwrap();
+    assert_eq!(route.as_path().collect::<Vec<_>>(), vec![1, 3]);
+    assert_eq!(route.origin(), Some(3));
+    assert_eq!(route.recv_relationship, Relationships::Customers);
+    assert!(reader.route(1, &Prefix::from_str("9.9.9.0/24").unwrap()).is_none());
+    assert_eq!(reader.routes(999).count(), 0);
+
+    assert!(SnapshotReader::from_bytes(&bytes[..bytes.len() - 4]).is_err());
+    assert!(SnapshotReader::from_bytes(b"not a snapshot").is_err());
+}
+
+#[test]
+fn test_prefix_table_shared_by_policies() {
+    let as_graph = create_test_as_graph_simple();
+    let mut engine = SimulationEngine::new(&as_graph);
+    let prefixes: Vec<Prefix> = ["1.2.0.0/16", "1.2.3.0/24", "2001:db8::/32"].iter()
+        .map(|prefix| Prefix::from_str(prefix).unwrap())
+        .collect();
+    engine.setup(prefixes.iter()
+        .map(|&prefix| (4, Announcement::new_with_path(prefix, vec![], 4, Relationships::Origin, Timestamps::VICTIM)))
+        .collect());
+    engine.run(3);
+
+    // Every policy interns into the store's table, so ids agree across ASes
+    let table = engine.policy_store.prefix_table.clone();
+    assert_eq!(table.prefixes(), prefixes);
+    for (_, policy) in engine.policy_store.iter() {
+        assert!(Arc::ptr_eq(&policy.prefix_table, &table));
+        assert_eq!(policy.local_rib.keys().copied().collect::<Vec<_>>(), prefixes);
+        assert_eq!(policy.local_rib.get_by_id(1).unwrap().prefix, prefixes[1]);
+    }
+    assert_eq!(engine.policy_store.get(&1).unwrap().local_rib[&prefixes[2]].as_path.to_vec(), vec![1, 2, 4]);
+
+    let mut map = PrefixMap::new(Arc::new(PrefixTable::new()));
+    assert_eq!(map.insert(prefixes[2], "v6"), None);
+    assert_eq!(map.insert(prefixes[0], "v4"), None);
+    assert_eq!(map.insert(prefixes[2], "v6 again"), Some("v6"));
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.table().id(&prefixes[0]), Some(1));
+    assert!(!map.contains_key(&prefixes[1]));
+    assert_eq!(map.remove(&prefixes[1]), None);
+    assert_eq!(map.remove(&prefixes[2]), Some("v6 again"));
+    assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&prefixes[0], &"v4")]);
+    map.clear();
+    assert!(map.is_empty() && map.get(&prefixes[0]).is_none());
+}
+
+#[test]
+fn test_ribs_in() {
+    let prefix = Prefix::from_str("1.2.0.0/16").unwrap();
+    let route = |neighbor_asn: ASN, path: Vec<ASN>| Announcement::new_with_path(prefix, path, neighbor_asn, Relationships::Customers, Timestamps::VICTIM);
+    let mut ribs_in = RIBsIn::new(Arc::new(PrefixTable::new()));
+    ribs_in.set_neighbor_capacity(3);
+    assert_eq!(ribs_in.insert(5, route(5, vec![5, 9])), None);
+    assert_eq!(ribs_in.insert(2, route(2, vec![2, 9])), None);
+    let mut withdrawal = route(3, vec![3, 9]);
+    withdrawal.withdraw = true;
+    assert_eq!(ribs_in.insert(3, withdrawal), None);
+    assert!(ribs_in.insert(5, route(5, vec![5, 7, 9])).is_some());
+
+    // A prefix's routes are kept together in neighbor order, and candidates skip withdrawals
+    assert_eq!(ribs_in.len(), 3);
+    assert_eq!(ribs_in.routes(&prefix).iter().map(|(asn, _)| *asn).collect::<Vec<_>>(), vec![2, 3, 5]);
+    assert!(ribs_in.routes(&prefix).len() <= 3 && ribs_in.neighbor_capacity() == 3);
+    assert_eq!(ribs_in.best_candidates(&prefix).map(|ann| ann.next_hop_asn).collect::<Vec<_>>(), vec![2, 5]);
+    assert_eq!(ribs_in[(5, &prefix)].as_path, vec![5, 7, 9]);
+    assert_eq!(ribs_in.neighbor_routes(2).count(), 1);
+    assert!(!ribs_in.contains(4, &prefix));
+    assert_eq!(ribs_in.remove(2, &prefix).map(|ann| ann.next_hop_asn), Some(2));
+    assert_eq!(ribs_in.prefixes().collect::<Vec<_>>(), vec![&prefix]);
+    ribs_in.clear();
+    assert!(ribs_in.is_empty() && ribs_in.best_candidates(&prefix).next().is_none());
+    ribs_in.shrink_to_fit();
+    assert!(ribs_in.routes(&prefix).is_empty());
+
+    // Each AS sizes its per-prefix reservation by its number of neighbors
+    let as_graph = create_test_as_graph_simple();
+    let engine = SimulationEngine::new(&as_graph);
+    assert_eq!(engine.policy_store.get(&1).unwrap().ribs_in.neighbor_capacity(), 2);
+    assert_eq!(engine.policy_store.get(&3).unwrap().ribs_in.neighbor_capacity(), 1);
+}
+
+#[test]
+fn test_engine_state_hash() {
+    let as_graph = create_test_as_graph_simple();
+    let run = |scenario: &dyn ScenarioTrait, storage_mode| {
+        let mut engine = SimulationEngine::new(&as_graph).with_storage_mode(storage_mode);
+        scenario.setup_engine(&mut engine, &mut RouteValidator::new());
+        engine.run(5);
+        engine.state_hash()
+    };
+    let hijack = SubprefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+    let hash = run(&hijack, PolicyStorageMode::Full);
+
+    // Only the local RIBs count, not how the engine stored the rest
+    assert_eq!(run(&hijack, PolicyStorageMode::Full), hash);
+    assert_eq!(run(&hijack, PolicyStorageMode::LocalRibOnly), hash);
+    assert_ne!(run(&LegitimatePrefixOnly::new(HashSet::from([4])), PolicyStorageMode::Full), hash);
+    assert_ne!(SimulationEngine::new(&as_graph).state_hash(), hash);
+
+    // Pinned so a change in routing or in the digest itself shows up here
+    assert_eq!(hash, 0x809a_d6a4_3a62_40bd);
+}
+
+/// Observer that records what the engine reported, shared with the test
+#[derive(Default)]
+struct RecordingObserver {
+    events: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+impl EngineObserver for RecordingObserver {
+    fn on_round_start(&mut self, round: u32) {
+        self.events.lock().unwrap().push(format!("round {}", round));
+    }
+
+    fn on_ann_accepted(&mut self, asn: u32, ann: &Announcement) {
+        self.events.lock().unwrap().push(format!("{} accepted from {}", asn, ann.next_hop_asn));
+    }
+
+    fn on_ann_rejected(&mut self, asn: u32, ann: &Announcement, reason: ValidationResult) {
+        self.events.lock().unwrap().push(format!("{} rejected from {}: {}", asn, ann.next_hop_asn, reason));
+    }
+
+    fn on_rib_change(&mut self, asn: u32, _prefix: &Prefix, old: Option<&Announcement>, new: Option<&Announcement>) {
+        let path = |ann: Option<&Announcement>| ann.map(|ann| ann.as_path.to_vec());
+        self.events.lock().unwrap().push(format!("{} rib {:?} -> {:?}", asn, path(old), path(new)));
+    }
+}
+
+#[test]
+fn test_engine_observer() {
+    for storage_mode in [PolicyStorageMode::Full, PolicyStorageMode::LocalRibOnly] {
+        let as_graph = create_test_as_graph_simple();
+        let mut engine = SimulationEngine::new(&as_graph).with_storage_mode(storage_mode);
+        let observer = RecordingObserver::default();
+        let events = observer.events.clone();
+        engine.add_observer(Box::new(observer));
+
+        let prefix = Prefix::from_str("1.2.0.0/16").unwrap();
+        engine.setup(vec![(4, Announcement::new_with_path(prefix, vec![], 4, Relationships::Origin, Timestamps::VICTIM))]);
+        engine.run(2);
+
+        let events = events.lock().unwrap();
+        let position = |event: &str| events.iter().position(|e| e == event)
+            .unwrap_or_else(|| panic!("{:?}: no {:?} in {:?}", storage_mode, event, events));
+        assert!(position("round 0") < position("2 accepted from 4"));
+        assert!(position("2 accepted from 4") < position("2 rib None -> Some([2, 4])"));
+        assert!(position("round 1") > position("3 rib None -> Some([3, 1, 2, 4])"));
+        // 1 exports back to 2, which rejects the route since its path already contains 2
+        assert!(position("2 rejected from 1: REJECTED_LOOP") > position("1 accepted from 2"));
+        // Changes are only reported once per AS since nothing better arrives later
+        assert_eq!(events.iter().filter(|e| e.contains(" rib ")).count(), 3);
+    }
+}
+
+#[test]
+fn test_pollution_observer() {
+    // Victim 4 originates the /24, attacker 3 hijacks the /25 before round 2 and withdraws it before round 4
+    let as_graph = create_test_as_graph_simple();
+    let scenario = LegitimatePrefixOnly::new(HashSet::from([4]));
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    let observer = PollutionObserver::new(HashSet::from([3]), "1.2.3.4".parse().unwrap());
+    let counts = observer.counts();
+    engine.add_observer(Box::new(observer));
+
+    let hijacked_prefix: Prefix = "1.2.3.0/25".parse().unwrap();
+    let hijack = Announcement::new_with_path(hijacked_prefix, vec![], 3, Relationships::Origin, Timestamps::ATTACKER);
+    EventSchedule::new()
+        .with_event(2, ScenarioEvent::Announce(3, hijack))
+        .with_event(4, ScenarioEvent::Withdraw(3, hijacked_prefix))
+        .run(&mut engine, &mut route_validator, 6)
+        .unwrap();
+
+    // 1, 2 and the victim itself follow the /25 while it is announced
+    assert_eq!(counts.per_round(), vec![0, 0, 3, 3, 0, 0]);
+    assert_eq!(counts.current(), 0);
+}
+
+#[test]
+fn test_engine_rejection_stats() {
+    let as_graph = create_test_as_graph_simple();
+    let prefix = Prefix::from_str("1.2.0.0/16").unwrap();
+    let seed = || vec![(4, Announcement::new_with_path(prefix, vec![], 4, Relationships::Origin, Timestamps::VICTIM))];
+
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.setup(seed());
+    engine.run(2);
+    assert!(engine.rejection_stats().is_none());
+
+    let mut engine = SimulationEngine::new(&as_graph).with_rejection_stats();
+    engine.setup(seed());
+    engine.run(2);
+    let stats = engine.rejection_stats().unwrap();
+    // 1 exports the route back to 2, whose path already contains 2
+    assert!(stats[&2][&ValidationResult::RejectedLoop] >= 1);
+    assert!(stats.values().flat_map(|reasons| reasons.keys()).all(|reason| !reason.is_accepted()));
+
+    // A new setup starts counting from zero
+    engine.setup(Vec::new());
+    assert!(engine.rejection_stats().unwrap().is_empty());
+}
+
+#[test]
+fn test_next_hop_verification() {
+    let as_graph = create_test_as_graph_simple();
+    let prefix = Prefix::from_str("1.2.0.0/16").unwrap();
+    let bogus_prefix = Prefix::from_str("5.6.0.0/16").unwrap();
+    // AS 3 has no customers, so the route it's handed from "customer" 4 can't be real
+    let run = |verification| {
+        let mut engine = SimulationEngine::new(&as_graph).with_next_hop_verification(verification).with_rejection_stats();
+        engine.setup(vec![(4, Announcement::new_with_path(prefix, vec![], 4, Relationships::Origin, Timestamps::VICTIM))]);
+        let bogus = Announcement::new_with_path(bogus_prefix, vec![4], 4, Relationships::Customers, Timestamps::VICTIM);
+        engine.policy_store.get_mut(&3).unwrap().receive_ann(bogus, Relationships::Customers);
+        let result = engine.try_run(3);
+        let path_at_3 = engine.policy_store.get(&3).unwrap().local_rib.get(&bogus_prefix).map(|ann| ann.as_path.to_vec());
+        let rejected = engine.rejection_stats().unwrap().get(&3)
+            .and_then(|reasons| reasons.get(&ValidationResult::RejectedUnresolvableNextHop))
+            .copied();
+        (result, path_at_3, rejected)
+    };
+
+    assert_eq!(run(NextHopVerification::Off), (Ok(()), Some(vec![3, 4]), None));
+    assert_eq!(run(NextHopVerification::Reject), (Ok(()), None, Some(1)));
+    let (result, _, rejected) = run(NextHopVerification::Strict);
+    assert_eq!(result, Err("AS 3 received 5.6.0.0/16 from AS 4 over CUSTOMERS, which is not its neighbor over that relationship".to_string()));
+    assert_eq!(rejected, Some(1));
+    assert_eq!(NextHopVerification::default(), NextHopVerification::Reject);
+
+    // Strict mode fails before propagating seeds at ASes that aren't in the graph
+    let mut engine = SimulationEngine::new(&as_graph).with_next_hop_verification(NextHopVerification::Strict);
+    engine.setup(vec![(99, Announcement::new_with_path(prefix, vec![], 99, Relationships::Origin, Timestamps::VICTIM))]);
+    assert_eq!(engine.try_run(1), Err("Seed for 1.2.0.0/16 at AS 99, which is not in the graph".to_string()));
+    engine.next_hop_verification = NextHopVerification::Reject;
+    engine.setup(vec![(99, Announcement::new_with_path(prefix, vec![], 99, Relationships::Origin, Timestamps::VICTIM))]);
+    assert_eq!(engine.try_run(1), Ok(()));
+
+    for verification in [NextHopVerification::Off, NextHopVerification::Reject, NextHopVerification::Strict] {
+        assert_eq!(verification.to_string().parse::<NextHopVerification>(), Ok(verification));
+    }
+    assert!("Lenient".parse::<NextHopVerification>().is_err());
+}
+
+#[test]
+fn test_engine_active_prefixes() {
+    let as_graph = create_test_as_graph_simple();
+    let active = Prefix::from_str("1.2.0.0/16").unwrap();
+    let deferred = Prefix::from_str("5.6.0.0/16").unwrap();
+    let seed = |prefix| (4, Announcement::new_with_path(prefix, vec![], 4, Relationships::Origin, Timestamps::VICTIM));
+
+    let mut engine = SimulationEngine::new(&as_graph).with_active_prefixes(HashSet::from([active]));
+    engine.setup(vec![seed(active), seed(deferred)]);
+    engine.run(3);
+    let local_rib = &engine.policy_store.get(&3).unwrap().local_rib;
+    assert!(local_rib.contains_key(&active));
+    assert!(!local_rib.contains_key(&deferred));
+    assert_eq!(engine.pending_prefixes(), HashSet::from([deferred]));
+
+    // Lifting the restriction propagates the queued prefix, after which nothing is pending
+    engine.set_active_prefixes(None);
+    engine.run(3);
+    assert!(engine.policy_store.get(&3).unwrap().local_rib.contains_key(&deferred));
+    assert!(engine.pending_prefixes().is_empty());
+
+    // Same routes as an unrestricted run
+    let mut unrestricted = SimulationEngine::new(&as_graph);
+    unrestricted.setup(vec![seed(active), seed(deferred)]);
+    unrestricted.run(6);
+    assert_eq!(engine.get_local_rib_snapshot(), unrestricted.get_local_rib_snapshot());
+}
+
+/// BGP extension that counts the announcements it validated during a run
+struct CountingPolicy {
+    num_validated: Arc<AtomicUsize>,
+}
+
+impl PolicyExtension for CountingPolicy {
+    fn validate_announcement(
+        &self,
+        _ann: &Announcement,
+        _recv_relationship: Relationships,
+        _as_obj: &AS,
+        _route_validator: Option<&RouteValidator>,
+    ) -> ValidationResult {
+        self.num_validated.fetch_add(1, AtomicOrdering::SeqCst);
+        ValidationResult::Accepted
+    }
+    
+    fn reset(&mut self) {
+        self.num_validated.store(0, AtomicOrdering::SeqCst);
+    }
+    
+    fn name(&self) -> &str {
+        "Counting"
+    }
+}
+
+#[test]
+fn test_engine_reuse_is_independent() {
+    let as_graph = create_test_as_graph_simple();
+    let mut engine = SimulationEngine::new(&as_graph);
+    let num_validated = Arc::new(AtomicUsize::new(0));
+    engine.policy_store.get_mut(&1).unwrap().set_extension(CountingPolicy {
+        num_validated: num_validated.clone(),
+    });
+    
+    let hijack = SubprefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+    let mut route_validator = RouteValidator::new();
+    hijack.setup_engine(&mut engine, &mut route_validator);
+    engine.run(5);
+    assert!(num_validated.load(AtomicOrdering::SeqCst) > 0);
+    
+    // Setting up the next trial clears the extension's run state along with the RIBs
+    let legitimate_only = LegitimatePrefixOnly::new(HashSet::from([4]));
+    legitimate_only.setup_engine(&mut engine, &mut route_validator);
+    assert_eq!(num_validated.load(AtomicOrdering::SeqCst), 0);
+    engine.run(5);
+    
+    // The reused engine ends up exactly where a fresh one does
+    let mut fresh_engine = SimulationEngine::new(&as_graph);
+    legitimate_only.setup_engine(&mut fresh_engine, &mut RouteValidator::new());
+    fresh_engine.run(5);
+    assert_eq!(engine.get_local_rib_snapshot(), fresh_engine.get_local_rib_snapshot());
+    assert!(engine.policy_store.iter().all(|(_, policy)| !policy.local_rib.contains_key(&hijack.hijacked_prefix)));
+}
+
+#[test]
+fn test_shortest_path_prefix_hijack_adapts_to_defenses() {
+    use bgpsimulator::simulation_framework::Scenario;
+
+    let as_graph = create_test_as_graph_simple();
+    let scenario = ShortestPathPrefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+    let forged_path = |asn_settings: &[(u32, Settings)]| {
+        let mut config = ScenarioConfig::new("ShortestPath".to_string(), "ShortestPathPrefixHijack".to_string());
+        for &(asn, setting) in asn_settings {
+            config = config.with_asn_setting(asn, setting);
+        }
+        let mut engine = SimulationEngine::new(&as_graph);
+        Scenario::new(config, &as_graph, 0.0).apply_adoption_settings(&mut engine);
+        scenario.forged_path(&engine)
+    };
+
+    // ROV only sees the origin, so spoofing it is enough
+    assert_eq!(forged_path(&[]), vec![4]);
+    assert_eq!(forged_path(&[(1, Settings::Rov)]), vec![4]);
+    // Path-End needs a real neighbor of the origin next to it
+    assert_eq!(forged_path(&[(1, Settings::PathEnd)]), vec![2, 4]);
+    // ASPA adopters' attested providers must be claimed up to an AS without an ASPA
+    assert_eq!(forged_path(&[(4, Settings::Aspa)]), vec![2, 4]);
+    assert_eq!(forged_path(&[(4, Settings::Aspa), (2, Settings::Aspa)]), vec![1, 2, 4]);
+    // but not past an AS attesting that it has no providers
+    assert_eq!(forged_path(&[(4, Settings::Aspa), (2, Settings::Aspa), (1, Settings::Aspa)]), vec![1, 2, 4]);
+
+    // The forged route reaches the attacker's provider with the legitimate origin and a ROA-valid path
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+    let ann = &engine.policy_store.get(&1).unwrap().ribs_in[(3, &scenario.target_prefix)];
+    assert_eq!(ann.as_path.to_vec(), vec![3, 4]);
+    assert_eq!(route_validator.get_roa_outcome(&scenario.target_prefix, ann.origin()).0, ROAValidity::Valid);
+    // but loses the tie against the real route at AS 1, so only the attacker uses it
+    assert!(!scenario.is_successful(&engine));
+}
+
+#[test]
+fn test_non_routed_superprefix_hijack() {
+    let as_graph = create_test_as_graph_simple();
+    let scenario = NonRoutedSuperprefixHijack::new(HashSet::from([3]));
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+
+    // The superprefix is ROV unknown, so it spreads and draws the non-routed space's traffic
+    let analysis = scenario.analyze(&engine, &route_validator).unwrap();
+    assert_eq!(analysis["routes_by_validity"], serde_json::json!({"UNKNOWN": 4}));
+    assert!(scenario.data_plane_outcomes(&engine).values().all(|&outcome| outcome == Outcomes::AttackerSuccess));
+    assert!(scenario.is_successful(&engine));
+
+    // Announcing the space itself contradicts the AS0 ROA
+    let scenario = NonRoutedSuperprefixHijack::new(HashSet::from([3])).with_superprefix(scenario.non_routed_prefix);
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+    let analysis = scenario.analyze(&engine, &route_validator).unwrap();
+    assert_eq!(analysis["routes_by_validity"], serde_json::json!({"INVALID_ORIGIN": 4}));
+
+    // The engine runner has no victim to credit, only the attacker or disconnection
+    let scenario_config = ScenarioConfig::new("NonRouted".to_string(), "NonRoutedSuperprefixHijack".to_string())
+        .with_attacker_asns(HashSet::from([3]));
+    let config = EngineRunConfig::new("test_non_routed_superprefix_hijack".to_string(), scenario_config, as_graph.clone());
+    let runner = EngineRunner::new(config)
+        .with_base_dir(std::env::temp_dir().join("bgpsimulator_engine_runner_test"))
+        .with_overwrite(true)
+        .with_write_diagrams(false);
+    let outcomes = runner.run().unwrap();
+    assert_eq!(outcomes.len(), 4);
+    assert!(outcomes.values().all(|&outcome| outcome == Outcomes::AttackerSuccess));
+}
+
+#[test]
+fn test_superprefix_hijack_longest_prefix_match() {
+    let as_graph = create_test_as_graph_simple();
+    let scenario = SuperprefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+
+    // Both prefixes are everywhere, and the victim's more specific one wins the traffic
+    let policy = engine.policy_store.get(&1).unwrap();
+    assert!(policy.local_rib.contains_key(&scenario.victim_prefix));
+    assert!(policy.local_rib.contains_key(&scenario.superprefix));
+    let outcomes = scenario.data_plane_outcomes(&engine);
+    assert_eq!(outcomes[&1], Outcomes::VictimSuccess);
+    assert_eq!(outcomes[&2], Outcomes::VictimSuccess);
+    assert_eq!(outcomes[&3], Outcomes::AttackerSuccess);
+    assert!(!scenario.is_successful(&engine));
+
+    // Without the more specific route, the superprefix draws the traffic
+    let scenario = SuperprefixHijack::new(HashSet::from([3]), HashSet::from([4]))
+        .with_victim_export_asns(HashSet::new());
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+    let outcomes = scenario.data_plane_outcomes(&engine);
+    assert_eq!(outcomes[&1], Outcomes::AttackerSuccess);
+    assert_eq!(outcomes[&2], Outcomes::AttackerSuccess);
+    assert_eq!(outcomes[&4], Outcomes::VictimSuccess);
+    assert!(scenario.is_successful(&engine));
+
+    // The engine runner traces the same way
+    let scenario_config = ScenarioConfig::new("Superprefix".to_string(), "SuperprefixHijack".to_string())
+        .with_attacker_asns(HashSet::from([3]))
+        .with_legitimate_origin_asns(HashSet::from([4]));
+    let config = EngineRunConfig::new("test_superprefix_hijack".to_string(), scenario_config, as_graph.clone());
+    let runner = EngineRunner::new(config)
+        .with_base_dir(std::env::temp_dir().join("bgpsimulator_engine_runner_test"))
+        .with_overwrite(true)
+        .with_write_diagrams(false);
+    let outcomes = runner.run().unwrap();
+    assert_eq!(outcomes[&1], Outcomes::VictimSuccess);
+    assert_eq!(outcomes[&3], Outcomes::AttackerSuccess);
+}
+
+#[test]
+fn test_victim_prepending_helps_prefix_hijack() {
+    // Tier-1 AS 1 has customers 2, 3 and the victim 4; the attacker 5 is a customer of 3
+    // and AS 2 has stub customers 6, 7 and 8
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_customers(vec![2, 3, 4]),
+        ASBuilder::new(2).with_providers(vec![1]).with_customers(vec![6, 7, 8]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![5]),
+        ASBuilder::new(4).with_providers(vec![1]),
+        ASBuilder::new(5).with_providers(vec![3]),
+        ASBuilder::new(6).with_providers(vec![2]),
+        ASBuilder::new(7).with_providers(vec![2]),
+        ASBuilder::new(8).with_providers(vec![2]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+
+    // Without prepending the victim's one-hop route wins at AS 1
+    let scenario = PrependingPrefixHijack::new(HashSet::from([5]), HashSet::from([4])).with_victim_prepends(0);
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+    assert_eq!(scenario.data_plane_outcomes(&engine)[&6], Outcomes::VictimSuccess);
+    assert!(!scenario.is_successful(&engine));
+
+    // Two prepends make it longer than the attacker's, hijacking everything behind AS 1
+    let scenario = PrependingPrefixHijack::new(HashSet::from([5]), HashSet::from([4]));
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+    // The victim keeps its own route even though the attacker's is shorter
+    assert_eq!(engine.policy_store.get(&4).unwrap().local_rib[&scenario.target_prefix].as_path.to_vec(), vec![4, 4, 4]);
+    assert_eq!(engine.policy_store.get(&1).unwrap().local_rib[&scenario.target_prefix].as_path.to_vec(), vec![1, 3, 5]);
+    let outcomes = scenario.data_plane_outcomes(&engine);
+    assert_eq!(outcomes[&6], Outcomes::AttackerSuccess);
+    assert_eq!(outcomes[&4], Outcomes::VictimSuccess);
+    assert!(scenario.is_successful(&engine));
+
+    // A provider with no other route keeps the prepended path as is
+    let scenario = PrependingPrefixHijack::new(HashSet::new(), HashSet::from([4]));
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+    assert_eq!(engine.policy_store.get(&2).unwrap().local_rib[&scenario.target_prefix].as_path.to_vec(), vec![2, 1, 4, 4, 4]);
+}
+
+#[test]
+fn test_anycast_legitimate_origins() {
+    let as_graph = create_test_as_graph_simple();
+    let scenario = PrefixHijack::new(HashSet::new(), HashSet::from([3, 4]));
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+
+    // Every site gets its own ROA, so both origins are valid
+    let roas = scenario.get_roas(&as_graph);
+    assert_eq!(roas.iter().map(|roa| roa.origin).collect::<Vec<_>>(), vec![3, 4]);
+    for origin in [3, 4] {
+        assert_eq!(route_validator.get_roa_outcome(&scenario.target_prefix, origin).0, ROAValidity::Valid);
+    }
+
+    // Each AS picks its nearest site, and reaching either one is a victim success
+    assert_eq!(engine.policy_store.get(&1).unwrap().local_rib[&scenario.target_prefix].origin(), 3);
+    assert_eq!(engine.policy_store.get(&2).unwrap().local_rib[&scenario.target_prefix].origin(), 4);
+    let outcomes = data_plane_outcomes(&engine, &HashSet::new(), &HashSet::from([3, 4]), scenario.get_dest_ip_addr());
+    assert!(outcomes.values().all(|&outcome| outcome == Outcomes::VictimSuccess));
+}
+
+#[test]
+fn test_disconnected_outcomes() {
+    let as_graph = create_test_as_graph_simple();
+    let scenario = PrefixHijack::new(HashSet::new(), HashSet::from([4]));
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+
+    // 1 and 3 still route towards 4 through 2, which lost its route
+    engine.policy_store.get_mut(&2).unwrap().local_rib.remove(&scenario.target_prefix);
+    let dest_ip_addr = scenario.get_dest_ip_addr();
+    let outcomes = data_plane_outcomes(&engine, &HashSet::new(), &HashSet::from([4]), dest_ip_addr);
+    assert_eq!(outcomes, HashMap::from([
+        (1, Outcomes::DisconnectedVictim),
+        (2, Outcomes::DisconnectedOrigin),
+        (3, Outcomes::DisconnectedVictim),
+        (4, Outcomes::VictimSuccess),
+    ]));
+    let outcomes = data_plane_outcomes(&engine, &HashSet::from([4]), &HashSet::new(), dest_ip_addr);
+    assert_eq!((outcomes[&1], outcomes[&3]), (Outcomes::DisconnectedAttacker, Outcomes::DisconnectedAttacker));
+    let outcomes = data_plane_outcomes(&engine, &HashSet::new(), &HashSet::new(), dest_ip_addr);
+    assert_eq!((outcomes[&1], outcomes[&2]), (Outcomes::DisconnectedNotAsSomehow, Outcomes::DisconnectedOrigin));
+    assert!(outcomes.values().all(|outcome| outcome.is_disconnected()));
+}
+
+#[test]
+fn test_export_rib() {
+    let as_graph = create_test_as_graph_simple();
+    let scenario = PrefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+
+    // 1 selected the attacker's shorter path over the victim's, both as received from its customers
+    let rib: serde_json::Value = serde_json::from_str(&engine.export_rib(1, RibFormat::Json).unwrap()).unwrap();
+    let routes = rib["routes"].as_array().unwrap();
+    assert_eq!(routes.len(), 2);
+    assert_eq!(routes[0], serde_json::json!({
+        "prefix": "1.2.3.0/24",
+        "best": true,
+        "as_path": [3],
+        "next_hop_asn": 3,
+        "relationship": "CUSTOMERS",
+        "validation": "INVALID_ORIGIN",
+        "timestamp": 0,
+    }));
+    assert_eq!((&routes[1]["as_path"], &routes[1]["best"], &routes[1]["validation"]), (&serde_json::json!([2, 4]), &serde_json::json!(false), &serde_json::json!("VALID")));
+
+    let text = engine.export_rib(1, "text".parse().unwrap()).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "BGP table of AS 1");
+    assert!(lines[4].starts_with("*> 1.2.3.0/24           3          CUSTOMERS     INVALID_ORIGIN"), "{}", lines[4]);
+    assert!(lines[5].starts_with("*  1.2.3.0/24") && lines[5].ends_with(" 2 4"), "{}", lines[5]);
+    // The origin's own route has an empty path
+    let origin_rib = engine.export_rib(4, RibFormat::Json).unwrap();
+    assert!(origin_rib.contains("\"relationship\": \"ORIGIN\""));
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&origin_rib).unwrap()["routes"][0]["as_path"], serde_json::json!([]));
+    assert!(engine.export_rib(99, RibFormat::Text).is_err());
+}
+
+#[test]
+fn test_forwarding_graph() {
+    let as_graph = create_test_as_graph_simple();
+    let scenario = SubprefixHijack::new(HashSet::from([3]), HashSet::from([4]));
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(3);
+
+    // Every AS, the victim included, forwards the hijacked /25 towards the attacker
+    let hijacked = engine.forwarding_graph(&"1.2.3.0/25".parse().unwrap());
+    assert_eq!(hijacked.next_hops, BTreeMap::from([(1, 3), (2, 1), (4, 2)]));
+    assert_eq!(hijacked.origins, BTreeSet::from([3]));
+    assert_eq!(hijacked.forwarding_path(4), vec![4, 2, 1, 3]);
+    assert!(hijacked.destinations().values().all(|destination| *destination == Some(3)));
+    let dot = hijacked.to_dot();
+    assert!(dot.starts_with("digraph \"1.2.3.0/25\" {\n"), "{}", dot);
+    assert!(dot.contains("  3 [label=\"AS 3\", shape=doublecircle, style=filled, fillcolor=palegreen, fontcolor=black];\n"), "{}", dot);
+    assert!(dot.contains("  4 -> 2;\n"), "{}", dot);
+    let json = hijacked.to_json();
+    assert_eq!(json["edges"][0], serde_json::json!({"asn": 1, "next_hop_asn": 3}));
+    assert_eq!(json["destinations"]["4"], 3);
+
+    let legitimate = engine.forwarding_graph(&"1.2.3.0/24".parse().unwrap());
+    assert_eq!(legitimate.destination(3), Some(4));
+
+    // Loops and missing routes lead nowhere
+    let mut looped = legitimate.clone();
+    looped.next_hops.insert(2, 1);
+    assert_eq!(looped.forwarding_path(1), vec![1, 2]);
+    assert_eq!(looped.destination(1), None);
+    assert_eq!(legitimate.destination(99), None);
+
+    let scenario_config = ScenarioConfig::new("Forwarding".to_string(), "SubprefixHijack".to_string())
+        .with_attacker_asns(HashSet::from([3]))
+        .with_legitimate_origin_asns(HashSet::from([4]));
+    let config = EngineRunConfig::new("test_forwarding_graph".to_string(), scenario_config, create_test_as_graph_simple());
+    let runner = EngineRunner::new(config)
+        .with_base_dir(std::env::temp_dir().join("bgpsimulator_forwarding_graph_test"))
+        .with_overwrite(true);
+    runner.run().unwrap();
+    let diagram = std::fs::read_to_string(runner.storage_dir.join("diagram.txt")).unwrap();
+    assert_eq!(diagram.matches("digraph").count(), 2);
+    let graphs: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(runner.storage_dir.join("forwarding_graphs.json")).unwrap()
+    ).unwrap();
+    assert_eq!(graphs[0]["prefix"], "1.2.3.0/24");
+    assert_eq!(graphs[1]["origins"], serde_json::json!([3]));
+}
+
+#[test]
+fn test_as_paths_are_interned() {
+    let origin_path = ASPath::from(vec![4]);
+    let path = origin_path.prepend(2).prepend(1);
+    assert_eq!(path, vec![1, 2, 4]);
+    assert_eq!((path.len(), path.first(), path.last(), path.get(1)), (3, Some(&1), Some(&4), Some(&2)));
+    assert!(path.contains(&2) && !path.contains(&3));
+    // Built separately, the same path is the same nodes, and prepending shares the rest of the path
+    assert!(path.ptr_eq(&ASPath::from(vec![1, 2, 4])));
+    assert!(path.without_first().ptr_eq(&origin_path.prepend(2)));
+    assert!(!path.ptr_eq(&ASPath::from(vec![1, 3, 4])));
+    assert_eq!(ASPath::from(vec![1, 1, 2, 2, 4]).without_prepending(), path);
+    assert!(ASPath::new().without_first().is_empty());
+
+    // Every AS's path for the prefix ends in the path of the AS it learned the route from
+    let as_graph = create_test_as_graph_simple();
+    let mut engine = SimulationEngine::new(&as_graph);
+    let prefix = Prefix::from_str("1.2.0.0/16").unwrap();
+    engine.setup(vec![(4, Announcement::new_with_path(prefix, vec![], 4, Relationships::Origin, Timestamps::VICTIM))]);
+    engine.run(3);
+    let local_path = |asn| engine.policy_store.get(&asn).unwrap().local_rib[&prefix].as_path.clone();
+    assert!(local_path(3).without_first().ptr_eq(&local_path(1)));
+    assert!(local_path(1).without_first().ptr_eq(&local_path(2)));
+}
+
+#[test]
+fn test_as_paths_extended_once() {
+    let prefix = Prefix::from_str("1.2.0.0/16").unwrap();
+    // A received path grows by the receiver when stored, and is exported unchanged
+    let received = Announcement::new_with_path(prefix, vec![2, 4], 2, Relationships::Customers, Timestamps::VICTIM);
+    let stored = received.copy_and_process(1);
+    assert_eq!(stored.as_path, vec![1, 2, 4]);
+    let exported = stored.copy_for_export(1, Relationships::Providers);
+    assert!(exported.as_path.ptr_eq(&stored.as_path));
+    assert_eq!((exported.next_hop_asn, exported.recv_relationship), (1, Relationships::Providers));
+
+    let as_graph = create_test_as_graph_simple();
+    for storage_mode in [PolicyStorageMode::Full, PolicyStorageMode::LocalRibOnly] {
+        let mut engine = SimulationEngine::new(&as_graph).with_storage_mode(storage_mode);
+        engine.setup(vec![(4, Announcement::new(prefix, 4, Relationships::Origin))]);
+        engine.run(5);
+        let snapshot = engine.get_local_rib_snapshot();
+        for (asn, path) in [(4, vec![4]), (2, vec![2, 4]), (1, vec![1, 2, 4]), (3, vec![3, 1, 2, 4])] {
+            assert_eq!(snapshot[&asn]["1.2.0.0/16"], path, "{:?}", storage_mode);
+        }
+        if storage_mode == PolicyStorageMode::Full {
+            let policy_1 = engine.policy_store.get(&1).unwrap();
+            assert_eq!(policy_1.ribs_in[(2, &prefix)].as_path, vec![2, 4]);
+            assert_eq!(policy_1.ribs_out[&3][&prefix].as_path, vec![1, 2, 4]);
+        }
+    }
+
+    // Seed paths are kept as given, e.g. a forged one not starting with the seeding AS,
+    // and only extended with it on export
+    for storage_mode in [PolicyStorageMode::Full, PolicyStorageMode::LocalRibOnly] {
+        let mut engine = SimulationEngine::new(&as_graph).with_storage_mode(storage_mode);
+        engine.setup(vec![(3, Announcement::new_with_path(prefix, vec![4], 3, Relationships::Origin, Timestamps::ATTACKER))]);
+        engine.run(5);
+        let snapshot = engine.get_local_rib_snapshot();
+        for (asn, path) in [(3, vec![4]), (1, vec![1, 3, 4]), (2, vec![2, 1, 3, 4])] {
+            assert_eq!(snapshot[&asn]["1.2.0.0/16"], path, "{:?}", storage_mode);
+        }
+    }
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.setup(vec![(3, Announcement::new_with_path(prefix, vec![3, 4], 3, Relationships::Origin, Timestamps::ATTACKER))]);
+    engine.run(5);
+    let snapshot = engine.get_local_rib_snapshot();
+    assert_eq!(snapshot[&3]["1.2.0.0/16"], vec![3, 4]);
+    assert_eq!(snapshot[&1]["1.2.0.0/16"], vec![1, 3, 4]);
+}
@@ -0,0 +1,82 @@
+use std::ffi::{CStr, CString};
+
+use bgpsim_ffi::*;
+
+/// Same topology as the simulation tests: victim 4 under 3 under tier-1 1, attacker 5 under tier-1 2
+const PROVIDER_CUSTOMER_EDGES: [u32; 6] = [1, 3, 3, 4, 2, 5];
+const PEER_EDGES: [u32; 2] = [1, 2];
+const TIER_1_ASNS: [u32; 2] = [1, 2];
+
+fn last_error() -> String {
+    let message = bgpsim_last_error();
+    assert!(!message.is_null());
+    unsafe { CStr::from_ptr(message) }.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_ffi_run_scenario() {
+    unsafe {
+        let graph = bgpsim_graph_from_edges(
+            PROVIDER_CUSTOMER_EDGES.as_ptr(), 3,
+            PEER_EDGES.as_ptr(), 1,
+            TIER_1_ASNS.as_ptr(), 2,
+        );
+        assert!(!graph.is_null());
+        assert_eq!(bgpsim_graph_num_ases(graph), 5);
+
+        let config = CString::new(r#"{"scenario_name": "SubprefixHijack", "attacker_asns": [5], "legitimate_origin_asns": [4]}"#).unwrap();
+        let outcomes = bgpsim_run_scenario(graph, config.as_ptr(), 10);
+        assert!(!outcomes.is_null());
+        assert_eq!(bgpsim_outcomes_len(outcomes), 5);
+
+        // Nobody filters the more specific route, so all traffic goes to the attacker
+        let (mut asn, mut data_plane, mut control_plane) = (0, u8::MAX, u8::MAX);
+        assert!(bgpsim_outcomes_get(outcomes, 0, &mut asn, &mut data_plane, &mut control_plane));
+        assert_eq!((asn, data_plane, control_plane), (1, BGPSIM_ATTACKER_SUCCESS, BGPSIM_ATTACKER_SUCCESS));
+        assert!(bgpsim_outcomes_get(outcomes, 4, &mut asn, std::ptr::null_mut(), std::ptr::null_mut()));
+        assert_eq!(asn, 5);
+        assert!(!bgpsim_outcomes_get(outcomes, 5, &mut asn, std::ptr::null_mut(), std::ptr::null_mut()));
+        assert_eq!(bgpsim_outcome_for_asn(outcomes, 3), BGPSIM_ATTACKER_SUCCESS as i32);
+        assert_eq!(bgpsim_outcome_for_asn(outcomes, 42), -1);
+        bgpsim_outcomes_free(outcomes);
+
+        // With the same prefix, 3 prefers the victim's customer route
+        let config = CString::new(r#"{"scenario_name": "PrefixHijack", "attacker_asns": [5], "legitimate_origin_asns": [4]}"#).unwrap();
+        let outcomes = bgpsim_run_scenario(graph, config.as_ptr(), 10);
+        assert_eq!(bgpsim_outcome_for_asn(outcomes, 3), BGPSIM_VICTIM_SUCCESS as i32);
+        assert_eq!(bgpsim_outcome_for_asn(outcomes, 2), BGPSIM_ATTACKER_SUCCESS as i32);
+        bgpsim_outcomes_free(outcomes);
+
+        let config = CString::new(r#"{"scenario_name": "NoSuchHijack"}"#).unwrap();
+        assert!(bgpsim_run_scenario(graph, config.as_ptr(), 10).is_null());
+        assert!(last_error().contains("NoSuchHijack"));
+        let config = CString::new("not json").unwrap();
+        assert!(bgpsim_run_scenario(graph, config.as_ptr(), 10).is_null());
+        assert!(last_error().starts_with("Invalid scenario config JSON"));
+        bgpsim_graph_free(graph);
+
+        // 1 -> 3 -> 1 is a provider cycle
+        let cyclic = [1, 3, 3, 1];
+        assert!(bgpsim_graph_from_edges(cyclic.as_ptr(), 2, std::ptr::null(), 0, std::ptr::null(), 0).is_null());
+        assert!(bgpsim_graph_from_edges(std::ptr::null(), 1, std::ptr::null(), 0, std::ptr::null(), 0).is_null());
+        assert_eq!(last_error(), "Edge array is null");
+    }
+}
+
+#[test]
+fn test_ffi_header_declares_api() {
+    let header = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/crates/bgpsim-ffi/include/bgpsim.h")).unwrap();
+    let source = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/crates/bgpsim-ffi/src/lib.rs")).unwrap();
+    let exported: Vec<&str> = source.lines()
+        .filter_map(|line| line.split("extern \"C\" fn ").nth(1))
+        .map(|rest| rest.split('(').next().unwrap())
+        .collect();
+    assert_eq!(exported.len(), 9);
+    for name in exported {
+        assert!(header.contains(&format!("{}(", name)), "{} is missing from bgpsim.h", name);
+    }
+    for constant in source.lines().filter_map(|line| line.strip_prefix("pub const ")) {
+        let name = constant.split(':').next().unwrap();
+        assert!(header.contains(&format!("#define {} ", name)), "{} is missing from bgpsim.h", name);
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use bgpsimulator::as_graphs::as_graph::{ASGraph, ASN};
+use bgpsimulator::as_graphs::as_graph_generators::CAIDAASGraphJSONConverter;
+use bgpsimulator::route_validator::RouteValidator;
+use bgpsimulator::simulation_engine::{PolicyStorageMode, SimulationEngine};
+use bgpsimulator::simulation_framework::scenarios::create_scenario;
+
+/// Frozen topology in CAIDA serial-2 format
+const SAMPLE_PATH: &str = "tests/fixtures/caida_sample.as-rel2.txt";
+
+/// Expected local RIBs for every scenario; regenerate with `UPDATE_GOLDEN=1 cargo test --test golden_tests`
+const EXPECTED_PATH: &str = "tests/fixtures/caida_sample_expected.json";
+
+/// Multihomed stub
+const VICTIM_ASN: ASN = 30372;
+
+/// Single-homed stub in a different part of the graph
+const ATTACKER_ASN: ASN = 14301;
+
+const SCENARIOS: [&str; 3] = ["SubprefixHijack", "PrefixHijack", "LegitimatePrefixOnly"];
+
+fn load_sample() -> ASGraph {
+    let contents = fs::read_to_string(SAMPLE_PATH).unwrap();
+    CAIDAASGraphJSONConverter::parse(&contents).unwrap()
+}
+
+fn run_scenario(as_graph: &ASGraph, scenario_name: &str) -> serde_json::Value {
+    run_scenario_with_storage(as_graph, scenario_name, PolicyStorageMode::Full)
+}
+
+fn run_scenario_with_storage(as_graph: &ASGraph, scenario_name: &str, storage_mode: PolicyStorageMode) -> serde_json::Value {
+    let scenario = create_scenario(
+        scenario_name,
+        HashSet::from([ATTACKER_ASN]),
+        HashSet::from([VICTIM_ASN]),
+    ).unwrap();
+
+    let mut engine = SimulationEngine::new(as_graph).with_storage_mode(storage_mode);
+    let mut route_validator = RouteValidator::new();
+    scenario.setup_engine(&mut engine, &mut route_validator);
+    engine.run(10);
+
+    serde_json::json!({
+        "successful": scenario.is_successful(&engine),
+        "local_ribs": engine.get_local_rib_snapshot(),
+    })
+}
+
+#[test]
+fn test_caida_sample_golden() {
+    let as_graph = load_sample();
+    assert_eq!(as_graph.len(), 330);
+    assert!(as_graph.get(&VICTIM_ASN).unwrap().providers().len() > 1);
+
+    let actual: serde_json::Map<String, serde_json::Value> = SCENARIOS.iter()
+        .map(|&name| (name.to_string(), run_scenario(&as_graph, name)))
+        .collect();
+    let actual = serde_json::Value::Object(actual);
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(EXPECTED_PATH, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+        return;
+    }
+
+    assert!(Path::new(EXPECTED_PATH).exists(), "Missing {}; run with UPDATE_GOLDEN=1 to create it", EXPECTED_PATH);
+    let expected: serde_json::Value = serde_json::from_str(&fs::read_to_string(EXPECTED_PATH).unwrap()).unwrap();
+
+    for name in SCENARIOS {
+        assert_eq!(
+            actual[name]["successful"], expected[name]["successful"],
+            "{} outcome changed", name
+        );
+
+        // Report the first few differing ASes rather than dumping both snapshots
+        let actual_ribs = actual[name]["local_ribs"].as_object().unwrap();
+        let expected_ribs = expected[name]["local_ribs"].as_object().unwrap();
+        let mut asns: Vec<&String> = actual_ribs.keys().chain(expected_ribs.keys()).collect();
+        asns.sort();
+        asns.dedup();
+        let diffs: Vec<String> = asns.into_iter()
+            .filter(|asn| actual_ribs.get(*asn) != expected_ribs.get(*asn))
+            .map(|asn| format!("AS {}: expected {:?}, got {:?}", asn, expected_ribs.get(asn), actual_ribs.get(asn)))
+            .collect();
+        assert!(
+            diffs.is_empty(),
+            "{} local RIBs changed for {} AS(es):\n{}",
+            name,
+            diffs.len(),
+            diffs.iter().take(10).cloned().collect::<Vec<_>>().join("\n")
+        );
+    }
+}
+
+#[test]
+fn test_local_rib_only_matches_full_storage() {
+    let as_graph = load_sample();
+
+    for name in SCENARIOS {
+        let full = run_scenario_with_storage(&as_graph, name, PolicyStorageMode::Full);
+        let local_rib_only = run_scenario_with_storage(&as_graph, name, PolicyStorageMode::LocalRibOnly);
+        assert_eq!(full, local_rib_only, "{} differs without adj-RIBs", name);
+    }
+}
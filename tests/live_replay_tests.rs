@@ -0,0 +1,82 @@
+#![cfg(feature = "live-replay")]
+
+use bgpsimulator::as_graphs::as_graph::{ASBuilder, ASGraph};
+use bgpsimulator::live_replay::{LiveReplay, PeerUpdate, UpdateFormat};
+use bgpsimulator::route_validator::RouteValidator;
+use bgpsimulator::simulation_engine::SimulationEngine;
+use bgpsimulator::simulation_framework::ScenarioEvent;
+
+/// Victim 4 under 2 and attacker 5 under 3, both under tier-1 1
+fn create_replay_graph() -> ASGraph {
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_customers(vec![2, 3]),
+        ASBuilder::new(2).with_providers(vec![1]).with_customers(vec![4]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![5]),
+        ASBuilder::new(4).with_providers(vec![2]),
+        ASBuilder::new(5).with_providers(vec![3]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    as_graph
+}
+
+/// The victim's prefix seen by two peers, then a subprefix hijack a few minutes later
+const RIS_LIVE_UPDATES: &str = r#"{"type": "ris_message", "data": {"timestamp": 1000.5, "peer": "192.0.2.1", "peer_asn": "1", "host": "rrc00", "type": "UPDATE", "path": [1, 2, 4, 4], "announcements": [{"next_hop": "192.0.2.1", "prefixes": ["1.2.3.0/24", "8.8.8.0/24"]}]}}
+{"type": "ris_message", "data": {"timestamp": 1010.0, "peer": "192.0.2.3", "peer_asn": "3", "host": "rrc00", "type": "KEEPALIVE"}}
+{"type": "ris_message", "data": {"timestamp": 1020.0, "peer": "192.0.2.3", "peer_asn": "3", "host": "rrc00", "type": "UPDATE", "path": [3, 1, 2, 4], "announcements": [{"next_hop": "192.0.2.3", "prefixes": ["1.2.3.0/24"]}]}}
+{"type": "ris_message", "data": {"timestamp": 1300.0, "peer": "192.0.2.1", "peer_asn": "1", "host": "rrc00", "type": "UPDATE", "path": [1, 3, 5], "announcements": [{"next_hop": "192.0.2.1", "prefixes": ["1.2.3.0/25"]}]}}
+{"type": "ris_message", "data": {"timestamp": 1310.0, "peer": "192.0.2.1", "peer_asn": "1", "host": "rrc00", "type": "UPDATE", "path": [1, 999], "announcements": [{"next_hop": "192.0.2.1", "prefixes": ["1.2.3.128/25"]}]}}
+{"type": "ris_message", "data": {"timestamp": 1320.0, "peer": "192.0.2.1", "peer_asn": "1", "host": "rrc00", "type": "UPDATE", "path": [1, [3, 5]], "announcements": [{"next_hop": "192.0.2.1", "prefixes": ["1.2.3.0/26"]}]}}
+"#;
+
+#[test]
+fn test_ris_live_replay() {
+    let as_graph = create_replay_graph();
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut route_validator = RouteValidator::new();
+    let mut replay = LiveReplay::new(UpdateFormat::RisLive)
+        .with_prefix_filter("1.2.3.0/24".parse().unwrap())
+        .with_rounds_per_batch(4);
+
+    // Only the first sighting of each origin is replayed, and only for the filtered prefixes
+    let num_events = replay.replay(RIS_LIVE_UPDATES.as_bytes(), &mut engine, &mut route_validator).unwrap();
+    assert_eq!(num_events, 2);
+    assert_eq!(replay.num_skipped, 1);
+
+    // The engine propagated both from their origins, so 2 now sends the /25's traffic to the attacker
+    let local_rib = &engine.policy_store.get(&2).unwrap().local_rib;
+    let route = |prefix: &str| local_rib.get(&prefix.parse().unwrap()).map(|ann| ann.as_path.to_vec());
+    assert_eq!(route("1.2.3.0/24"), Some(vec![2, 4]));
+    assert_eq!(route("1.2.3.0/25"), Some(vec![2, 1, 3, 5]));
+    assert_eq!(route("8.8.8.0/24"), None);
+}
+
+#[test]
+fn test_bgpstream_withdrawals() {
+    let as_graph = create_replay_graph();
+    let mut engine = SimulationEngine::new(&as_graph);
+    let mut replay = LiveReplay::new(UpdateFormat::BgpStream);
+
+    let parse = |replay: &LiveReplay, line: &str| -> PeerUpdate { replay.parse_line(line).unwrap().remove(0) };
+    let from_1 = parse(&replay, "U|A|1000.000000|ris|rrc00|||1|192.0.2.1|1.2.3.0/25|192.0.2.1|1 3 5|5|||");
+    let from_2 = parse(&replay, "U|A|1001.000000|ris|rrc00|||2|192.0.2.2|1.2.3.0/25|192.0.2.2|2 1 3 5 5|5|||");
+    let withdrawn_1 = parse(&replay, "U|W|1002.000000|ris|rrc00|||1|192.0.2.1|1.2.3.0/25||||||");
+    let withdrawn_2 = parse(&replay, "U|W|1003.000000|ris|rrc00|||2|192.0.2.2|1.2.3.0/25||||||");
+    assert_eq!(from_2.as_path, Some(vec![2, 1, 3, 5]));
+    assert_eq!((from_2.origin(), withdrawn_1.origin()), (Some(5), None));
+    assert!(replay.parse_line("U|S|1004.000000|ris|rrc00|||1|192.0.2.1|1.2.3.0/25||||").unwrap().is_empty());
+    assert!(replay.parse_line("U|A|oops").unwrap_err().contains("at least 10 fields"));
+
+    // The origin withdraws once no peer routes to it anymore
+    assert!(matches!(replay.events(&from_1, &engine)[..], [ScenarioEvent::Announce(5, _)]));
+    assert!(replay.events(&from_2, &engine).is_empty());
+    assert!(replay.events(&withdrawn_1, &engine).is_empty());
+    assert!(matches!(replay.events(&withdrawn_2, &engine)[..], [ScenarioEvent::Withdraw(5, _)]));
+
+    // Replayed, the withdrawal clears the route again
+    let updates = "U|A|1000.000000|ris|rrc00|||1|192.0.2.1|1.2.3.0/25|192.0.2.1|1 3 5|5|||\n\
+                   U|W|1200.000000|ris|rrc00|||1|192.0.2.1|1.2.3.0/25||||||\n";
+    let mut replay = LiveReplay::new(UpdateFormat::BgpStream);
+    let num_events = replay.replay(updates.as_bytes(), &mut engine, &mut RouteValidator::new()).unwrap();
+    assert_eq!(num_events, 2);
+    assert!(engine.policy_store.get(&2).unwrap().local_rib.get(&"1.2.3.0/25".parse().unwrap()).is_none());
+}
@@ -1,12 +1,13 @@
+use std::cmp::Ordering;
 use std::collections::HashSet;
 use ipnetwork::IpNetwork;
 use std::str::FromStr;
 
-use bgpsimulator::as_graphs::as_graph::{ASBuilder, ASGraph};
+use bgpsimulator::as_graphs::as_graph::{AS, ASBuilder, ASGraph};
 use bgpsimulator::simulation_engine::policy::policy_extensions::*;
-use bgpsimulator::simulation_engine::policy::{PolicyExtension, ProcessingResult};
+use bgpsimulator::simulation_engine::policy::{GuardLimits, GuardedPolicy, PolicyExtension, PolicyKind, ProcessingResult, TieBreaker, ValidationResult, decision_process};
 use bgpsimulator::shared::{Relationships, Settings, Timestamps, ROAValidity};
-use bgpsimulator::simulation_engine::{Announcement, Prefix};
+use bgpsimulator::simulation_engine::{Announcement, ASPath, PolicyStorageMode, Prefix, SimulationEngine};
 use bgpsimulator::route_validator::RouteValidator;
 
 fn create_test_as_graph() -> ASGraph {
@@ -54,22 +55,22 @@ fn test_bgp_policy_validation() {
     let as_graph = create_test_as_graph();
     let as_obj = as_graph.get(&65001).unwrap();
     let mut ann = create_test_announcement();
-    ann.as_path = vec![65002, 65007]; // Doesn't contain 65001
+    ann.as_path = vec![65002, 65007].into(); // Doesn't contain 65001
     
     // Valid announcement
-    assert!(policy.validate_announcement(&ann, Relationships::Peers, as_obj, None));
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Peers, as_obj, None), ValidationResult::Accepted);
     
     // Invalid: loop detection (AS already in path)
-    ann.as_path.push(65001);
-    assert!(!policy.validate_announcement(&ann, Relationships::Peers, as_obj, None));
+    ann.as_path = vec![65002, 65007, 65001].into();
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Peers, as_obj, None), ValidationResult::RejectedLoop);
     
     // Invalid: empty AS path from non-origin
     let mut empty_path_ann = ann.clone();
-    empty_path_ann.as_path.clear();
-    assert!(!policy.validate_announcement(&empty_path_ann, Relationships::Peers, as_obj, None));
+    empty_path_ann.as_path = ASPath::new();
+    assert_eq!(policy.validate_announcement(&empty_path_ann, Relationships::Peers, as_obj, None), ValidationResult::RejectedEmptyPath);
     
     // Valid: empty AS path from origin
-    assert!(policy.validate_announcement(&empty_path_ann, Relationships::Origin, as_obj, None));
+    assert_eq!(policy.validate_announcement(&empty_path_ann, Relationships::Origin, as_obj, None), ValidationResult::Accepted);
 }
 
 #[test]
@@ -133,28 +134,74 @@ fn test_only_to_customers_policy() {
 
 #[test]
 fn test_rov_policy() {
-    let mut rov_policy = ROVPolicy::new();
+    let mut rov_policy = ROVPolicy::default();
     let as_graph = create_test_as_graph();
     let as_obj = as_graph.get(&65001).unwrap();
     
-    // Add a valid ROA
+    // Share a validator with a valid ROA
     let prefix: Prefix = "10.0.0.0/24".parse().unwrap();
-    let roa = bgpsimulator::route_validator::ROA::new(prefix, 65007, Some(24));
-    rov_policy.route_validator.add_roa(roa);
+    let mut route_validator = RouteValidator::new();
+    route_validator.add_roa(bgpsimulator::route_validator::ROA::new(prefix, 65007, Some(24)));
+    rov_policy.set_route_validator(std::sync::Arc::new(route_validator));
     
     // Valid: origin matches ROA
     let mut ann = create_test_announcement();
-    ann.as_path = vec![65002, 65007]; // Origin is 65007
-    assert!(rov_policy.validate_announcement(&ann, Relationships::Peers, as_obj, None));
+    ann.as_path = vec![65002, 65007].into(); // Origin is 65007
+    assert_eq!(rov_policy.validate_announcement(&ann, Relationships::Peers, as_obj, None), ValidationResult::Accepted);
     
     // Invalid: origin doesn't match ROA
-    ann.as_path = vec![65002, 65008]; // Origin is 65008
-    assert!(!rov_policy.validate_announcement(&ann, Relationships::Peers, as_obj, None));
+    ann.as_path = vec![65002, 65008].into(); // Origin is 65008
+    assert_eq!(rov_policy.validate_announcement(&ann, Relationships::Peers, as_obj, None), ValidationResult::RejectedRoaInvalid);
     
     // Valid: unknown prefix (no ROA)
     let unknown_prefix: Prefix = "20.0.0.0/24".parse().unwrap();
     ann.prefix = unknown_prefix;
-    assert!(rov_policy.validate_announcement(&ann, Relationships::Peers, as_obj, None));
+    assert_eq!(rov_policy.validate_announcement(&ann, Relationships::Peers, as_obj, None), ValidationResult::Accepted);
+}
+
+#[test]
+fn test_rov_depref_invalid() {
+    let as_graph = create_test_as_graph();
+    let as_obj = as_graph.get(&65001).unwrap();
+    let prefix: Prefix = "10.0.0.0/24".parse().unwrap();
+    let mut route_validator = RouteValidator::new();
+    route_validator.add_roa(bgpsimulator::route_validator::ROA::new(prefix, 65007, Some(24)));
+    let route_validator = std::sync::Arc::new(route_validator);
+    
+    // A hijack from a customer, and the valid route from a peer
+    let mut invalid = create_test_announcement();
+    invalid.next_hop_asn = 65005;
+    invalid.as_path = vec![65005].into();
+    let mut valid = create_test_announcement();
+    valid.as_path = vec![65002, 65007].into();
+    
+    let mut drop_policy = PolicyKind::new(Settings::Rov);
+    drop_policy.set_route_validator(std::sync::Arc::clone(&route_validator));
+    assert_eq!(drop_policy.validate_announcement(&invalid, Relationships::Customers, as_obj, None), ValidationResult::RejectedRoaInvalid);
+    // Without invalids to depref, route selection is plain BGP
+    assert_eq!(
+        drop_policy.compare_announcements(&invalid, &valid, Relationships::Customers, Relationships::Peers, as_obj),
+        Ordering::Less,
+    );
+    
+    let mut depref_policy = PolicyKind::new(Settings::RovDepref);
+    depref_policy.set_route_validator(route_validator);
+    assert_eq!(depref_policy.name(), "ROV");
+    assert_eq!(depref_policy.validate_announcement(&invalid, Relationships::Customers, as_obj, None), ValidationResult::Accepted);
+    // The invalid route loses despite the customer's higher LOCAL_PREF and shorter path
+    assert_eq!(
+        depref_policy.compare_announcements(&invalid, &valid, Relationships::Customers, Relationships::Peers, as_obj),
+        Ordering::Greater,
+    );
+    assert_eq!(
+        depref_policy.compare_preference(&valid, &invalid, Relationships::Peers, Relationships::Customers, as_obj),
+        Ordering::Less,
+    );
+    
+    assert_eq!(ROVPolicy::default().mode, ROVMode::DropInvalid);
+    assert_eq!("DeprefInvalid".parse::<ROVMode>().unwrap(), ROVMode::DeprefInvalid);
+    assert_eq!(ROVMode::DeprefInvalid.to_string(), "DeprefInvalid");
+    assert!("Depref".parse::<ROVMode>().is_err());
 }
 
 #[test]
@@ -163,17 +210,460 @@ fn test_enforce_first_as_policy() {
     let as_graph = create_test_as_graph();
     let as_obj = as_graph.get(&65001).unwrap();
     let mut ann = create_test_announcement();
-    ann.as_path = vec![65002, 65007];
+    ann.as_path = vec![65002, 65007].into();
     
     // Valid: first AS in path matches next hop and is a neighbor (peer)
-    assert!(policy.validate_announcement(&ann, Relationships::Peers, as_obj, None));
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Peers, as_obj, None), ValidationResult::Accepted);
     
     // Invalid: first AS doesn't match next hop
     ann.next_hop_asn = 65003;
-    assert!(!policy.validate_announcement(&ann, Relationships::Peers, as_obj, None));
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Peers, as_obj, None), ValidationResult::RejectedNextHopMismatch);
     
     // Invalid: next hop is not a neighbor
     ann.next_hop_asn = 65009;
-    ann.as_path = vec![65009, 65007];
-    assert!(!policy.validate_announcement(&ann, Relationships::Peers, as_obj, None));
-}
\ No newline at end of file
+    ann.as_path = vec![65009, 65007].into();
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Peers, as_obj, None), ValidationResult::RejectedFirstAsNotNeighbor);
+}
+/// Third-party policy whose comparison claims every route is worse than every other
+struct AlwaysLessPolicy;
+
+impl PolicyExtension for AlwaysLessPolicy {
+    fn compare_announcements(
+        &self,
+        _ann1: &Announcement,
+        _ann2: &Announcement,
+        _rel1: Relationships,
+        _rel2: Relationships,
+        _as_obj: &AS,
+    ) -> Ordering {
+        Ordering::Less
+    }
+
+    fn name(&self) -> &str {
+        "AlwaysLess"
+    }
+}
+
+/// Third-party policy that prepends its own ASN far too many times
+struct RunawayPrependPolicy;
+
+impl PolicyExtension for RunawayPrependPolicy {
+    fn process_announcement(
+        &mut self,
+        ann: &mut Announcement,
+        _recv_relationship: Relationships,
+        as_obj: &AS,
+    ) -> ProcessingResult {
+        for _ in 0..100 {
+            ann.as_path = ann.as_path.prepend(as_obj.asn);
+        }
+        ProcessingResult::Accept
+    }
+
+    fn name(&self) -> &str {
+        "RunawayPrepend"
+    }
+}
+
+#[test]
+fn test_guard_rejects_runaway_path_growth() {
+    let as_graph = create_test_as_graph();
+    let as_obj = as_graph.get(&65001).unwrap();
+    let mut guarded = GuardedPolicy::new(Box::new(RunawayPrependPolicy), GuardLimits::default());
+    let mut ann = create_test_announcement();
+    ann.as_path = vec![65002].into();
+
+    assert_eq!(guarded.violation(), None);
+    assert_eq!(guarded.process_announcement(&mut ann, Relationships::Peers, as_obj), ProcessingResult::Reject);
+    assert!(guarded.violation().unwrap().contains("grew the path"));
+
+    guarded.reset();
+    assert_eq!(guarded.violation(), None);
+}
+
+#[test]
+fn test_guard_fails_run_on_inconsistent_comparison() {
+    // 1 is multihomed to 2 and 3, which both serve 4, so 4 compares two routes to 1's prefix
+    let as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).with_providers(vec![2, 3]),
+        ASBuilder::new(2).with_customers(vec![1, 4]),
+        ASBuilder::new(3).with_customers(vec![1, 4]),
+        ASBuilder::new(4).with_providers(vec![2, 3]),
+    ]);
+    let prefix = Prefix::from_str("1.2.0.0/16").unwrap();
+    let seed = Announcement::new_with_path(prefix, vec![], 1, Relationships::Origin, Timestamps::VICTIM);
+
+    let mut engine = SimulationEngine::new(&as_graph);
+    let policy = engine.policy_store.get_mut(&4).unwrap();
+    policy.set_extension(AlwaysLessPolicy);
+    policy.guard_extension(GuardLimits::default());
+    engine.setup(vec![(1, seed.clone())]);
+
+    let err = engine.try_run(3).unwrap_err();
+    assert!(err.starts_with("AS 4 (AlwaysLess): compare_announcements is inconsistent"), "{}", err);
+    // The fallback comparison still leaves 4 with a route
+    assert!(engine.policy_store.get(&4).unwrap().local_rib.contains_key(&prefix));
+
+    // Without comparison checks the same run completes
+    let mut engine = SimulationEngine::new(&as_graph);
+    let policy = engine.policy_store.get_mut(&4).unwrap();
+    policy.set_extension(AlwaysLessPolicy);
+    policy.guard_extension(GuardLimits { check_comparisons: false, ..GuardLimits::default() });
+    engine.setup(vec![(1, seed)]);
+    assert!(engine.try_run(3).is_ok());
+}
+
+#[test]
+fn test_policy_factory_features() {
+    use bgpsimulator::simulation_engine::policy::create_policy_extension;
+
+    let name = |settings| create_policy_extension(settings).name().to_string();
+    assert_eq!(name(Settings::Rov), "ROV");
+    // Policies compiled out by their feature fall back to BGP
+    assert_eq!(name(Settings::Aspa), if cfg!(feature = "aspa") { "ASPA" } else { "BGP" });
+    assert_eq!(name(Settings::Bgpsec), if cfg!(feature = "bgpsec") { "BGPSec" } else { "BGP" });
+    assert_eq!(name(Settings::RovppV1Lite), if cfg!(feature = "rovpp") { "ROVPPV1Lite" } else { "BGP" });
+}
+
+#[test]
+fn test_policy_kind_matches_factory() {
+    use bgpsimulator::simulation_engine::policy::create_policy_extension;
+
+    let as_graph = create_test_as_graph();
+    let as_obj = as_graph.get(&65001).unwrap();
+    let mut ann = create_test_announcement();
+    ann.as_path = vec![65002].into();
+    for settings in [Settings::BaseDefense, Settings::Rov, Settings::OnlyToCustomers, Settings::Aspa, Settings::Rost, Settings::IrrFilter] {
+        let kind = PolicyKind::new(settings);
+        let extension = create_policy_extension(settings);
+        assert!(!kind.is_custom());
+        assert_eq!(kind.name(), extension.name());
+        assert_eq!(
+            kind.validate_announcement(&ann, Relationships::Peers, as_obj, None),
+            extension.validate_announcement(&ann, Relationships::Peers, as_obj, None),
+        );
+    }
+
+    // User extensions go through the boxed variant
+    let custom = PolicyKind::custom(RunawayPrependPolicy);
+    assert!(custom.is_custom());
+    assert_eq!(custom.name(), RunawayPrependPolicy.name());
+}
+
+#[test]
+fn test_aspa_validator_from_json() {
+    use bgpsimulator::aspa_validator::{AspaValidator, ProviderAuth};
+
+    // rpki-client and Routinator exports
+    let validator = AspaValidator::from_json(&serde_json::json!({"aspas": [
+        {"customer_asid": 65001, "providers": [65004, 65009]},
+        {"customer_asid": 65005, "provider_set": [{"asid": 65001, "afi_limit": "none"}]},
+        {"customer": "AS65006", "providers": ["AS65001"]},
+        {"customer": "AS65007", "providers": [0]},
+    ]})).unwrap();
+    assert_eq!(validator.len(), 4);
+    assert_eq!(validator.provider_auth(65001, 65004), ProviderAuth::ProviderPlus);
+    assert_eq!(validator.provider_auth(65001, 65002), ProviderAuth::NotProviderPlus);
+    assert_eq!(validator.provider_auth(65005, 65001), ProviderAuth::ProviderPlus);
+    assert_eq!(validator.provider_auth(65006, 65001), ProviderAuth::ProviderPlus);
+    // AS0 attests having no providers at all
+    assert_eq!(validator.provider_auth(65007, 65001), ProviderAuth::NotProviderPlus);
+    assert_eq!(validator.provider_auth(65002, 65001), ProviderAuth::NoAttestation);
+
+    assert!(AspaValidator::from_json(&serde_json::json!({"aspas": [{"providers": [1]}]})).is_err());
+    assert!(AspaValidator::from_json(&serde_json::json!({"aspas": [{"customer": "ASx", "providers": []}]})).is_err());
+
+    let synthetic = AspaValidator::from_as_graph(&create_test_as_graph(), [65001, 65004, 65005]);
+    assert_eq!(synthetic.providers(65001), Some(&HashSet::from([65004])));
+    assert_eq!(synthetic.providers(65004), Some(&HashSet::new()));
+    assert_eq!(synthetic.provider_auth(65005, 65001), ProviderAuth::ProviderPlus);
+}
+
+#[cfg(feature = "aspa")]
+#[test]
+fn test_aspa_policy_uses_engine_validator() {
+    use std::sync::Arc;
+    use bgpsimulator::aspa_validator::AspaValidator;
+
+    let as_graph = create_test_as_graph();
+    let as_obj = as_graph.get(&65001).unwrap();
+    let mut policy = ASPAPolicy::new();
+    let mut ann = create_test_announcement();
+    // 65007 leaked a route from its provider 65003 to its peer 65002
+    ann.as_path = vec![65002, 65007, 65003].into();
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Peers, as_obj, None), ValidationResult::Accepted);
+
+    let mut validator = AspaValidator::new();
+    validator.add_attestation(65003, [65007]);
+    validator.add_attestation(65007, [65008]);
+    validator.add_attestation(65002, [65010]);
+    policy.set_aspa_validator(Arc::new(validator));
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Peers, as_obj, None), ValidationResult::RejectedAspa);
+    // From a provider the path may go up and then down
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Providers, as_obj, None), ValidationResult::Accepted);
+    // but not down, then back up through 65009, which 65002 doesn't attest
+    ann.as_path = vec![65002, 65009, 65007, 65003].into();
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Providers, as_obj, None), ValidationResult::RejectedAspa);
+
+    // The engine hands its validator to every policy on setup
+    let mut engine = SimulationEngine::new(&as_graph).with_aspa_validator(Arc::new(AspaValidator::from_as_graph(&as_graph, [65005])));
+    engine.policy_store.get_mut(&65001).unwrap().set_extension(ASPAPolicy::new());
+    engine.setup(vec![]);
+    assert_eq!(engine.aspa_validator().unwrap().len(), 1);
+    let mut ann = create_test_announcement();
+    ann.next_hop_asn = 65006;
+    ann.as_path = vec![65006, 65005].into();
+    let policy = engine.policy_store.get(&65001).unwrap();
+    assert_eq!(policy.valid_ann(&ann, Relationships::Customers, as_obj), ValidationResult::RejectedAspa);
+}
+
+#[test]
+fn test_irr_validator_from_rpsl() {
+    use bgpsimulator::irr_validator::IrrValidator;
+
+    let rpsl = "\
+% RADB dump
+route:          10.0.0.0/24
+descr:          Example
+origin:         AS65005
+mnt-by:         MAINT-EXAMPLE
+source:         RADB
+
+aut-num:        AS65005
+as-name:        EXAMPLE
+remarks:        route: 10.9.0.0/16 is not a route object
+
+route6:         2001:db8::/32
+origin:         as65006 # comment
+origin:         AS65007
+source:         RIPE
+";
+    let validator = IrrValidator::from_rpsl(rpsl).unwrap();
+    assert_eq!(validator.len(), 3);
+    let prefix = IpNetwork::from_str("10.0.0.0/24").unwrap();
+    assert!(validator.is_covered(&prefix, 65005));
+    assert!(!validator.is_covered(&prefix, 65006));
+    // Route objects don't cover more specifics
+    assert!(!validator.is_covered(&IpNetwork::from_str("10.0.0.0/25").unwrap(), 65005));
+    assert!(validator.origins(&IpNetwork::from_str("10.9.0.0/16").unwrap()).is_none());
+    assert_eq!(validator.origins(&IpNetwork::from_str("2001:db8::/32").unwrap()), Some(&HashSet::from([65006, 65007])));
+
+    assert!(IrrValidator::from_rpsl("route: 10.0.0.0/24\nsource: RADB\n").unwrap_err().contains("without an origin"));
+    assert!(IrrValidator::from_rpsl("route: 10.0.0.0/24\norigin: ASx\n").is_err());
+}
+
+#[test]
+fn test_irr_filter_policy() {
+    use std::sync::Arc;
+    use bgpsimulator::irr_validator::IrrValidator;
+
+    let as_graph = create_test_as_graph();
+    let as_obj = as_graph.get(&65001).unwrap();
+    let mut validator = IrrValidator::new();
+    validator.add_route_object(IpNetwork::from_str("10.0.0.0/24").unwrap(), 65005);
+
+    let mut ann = create_test_announcement();
+    ann.next_hop_asn = 65006;
+    ann.as_path = vec![65006].into();
+    let mut policy = IrrFilterPolicy::new();
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Customers, as_obj, None), ValidationResult::Accepted);
+    policy.set_irr_validator(Arc::new(validator.clone()));
+    // 65006 has no route object for the prefix, but only customers are filtered
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Customers, as_obj, None), ValidationResult::RejectedIrr);
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Providers, as_obj, None), ValidationResult::Accepted);
+    // The origin is checked, not the customer that sent it
+    ann.as_path = vec![65006, 65005].into();
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Customers, as_obj, None), ValidationResult::Accepted);
+
+    // The engine hands its route objects to every policy on setup
+    let mut engine = SimulationEngine::new(&as_graph).with_irr_validator(Arc::new(validator));
+    engine.policy_store.get_mut(&65001).unwrap().set_extension(IrrFilterPolicy::new());
+    engine.setup(vec![]);
+    assert_eq!(engine.irr_validator().unwrap().len(), 1);
+    ann.as_path = vec![65006].into();
+    let policy = engine.policy_store.get(&65001).unwrap();
+    assert_eq!(policy.name(), "IrrFilter");
+    assert_eq!(policy.valid_ann(&ann, Relationships::Customers, as_obj), ValidationResult::RejectedIrr);
+}
+
+#[test]
+fn test_decision_process_local_pref_and_med() {
+    let as_graph = create_test_as_graph();
+    let as_obj = as_graph.get(&65001).unwrap();
+    let mut ann1 = create_test_announcement();
+    ann1.as_path = vec![65002, 65007].into();
+    let mut ann2 = create_test_announcement();
+    ann2.next_hop_asn = 65003;
+    ann2.as_path = vec![65003, 65008, 65007].into();
+
+    // Relationship preference first, then path length
+    let policy = BGPPolicy;
+    assert_eq!(policy.local_pref(&ann1, Relationships::Peers), 200);
+    assert_eq!(policy.compare_announcements(&ann1, &ann2, Relationships::Peers, Relationships::Peers, as_obj), Ordering::Less);
+    // A configured LOCAL_PREF beats the shorter path
+    ann2.local_pref = Some(250);
+    assert_eq!(decision_process(&ann1, &ann2, policy.local_pref(&ann1, Relationships::Peers), policy.local_pref(&ann2, Relationships::Peers)), Ordering::Greater);
+
+    // Equal paths fall to MED before the next hop
+    ann2.local_pref = None;
+    ann2.as_path = vec![65003, 65007].into();
+    ann1.med = Some(10);
+    assert_eq!(decision_process(&ann1, &ann2, 200, 200), Ordering::Greater);
+    // and then to the tie-breaker, by default the lowest next hop
+    ann2.med = Some(10);
+    assert_eq!(decision_process(&ann1, &ann2, 200, 200), Ordering::Equal);
+    assert_eq!(policy.compare_announcements(&ann1, &ann2, Relationships::Peers, Relationships::Peers, as_obj), Ordering::Less);
+}
+
+#[test]
+fn test_tie_breakers() {
+    let mut victim = create_test_announcement();
+    victim.next_hop_asn = 65003;
+    let attacker = Announcement::new_with_path(victim.prefix, vec![], 65002, Relationships::Peers, Timestamps::ATTACKER);
+
+    assert_eq!(TieBreaker::LowestNextHopAsn.compare(&victim, &attacker, 65001), Ordering::Greater);
+    assert_eq!(TieBreaker::OldestAnnouncement.compare(&victim, &attacker, 65001), Ordering::Less);
+    assert_eq!(TieBreaker::OldestAnnouncement.compare(&victim, &victim, 65001), Ordering::Equal);
+
+    // Seeded preferences are fixed per seed and deciding AS, and differ across them
+    let random = |seed, asn| TieBreaker::SeededRandom(seed).compare(&victim, &attacker, asn);
+    assert_eq!(random(1, 65001), random(1, 65001));
+    assert_eq!(random(1, 65001), random(1, 65001).reverse().reverse());
+    let orderings: HashSet<Ordering> = (0..16).map(|seed| random(seed, 65001)).chain((0..16).map(|asn| random(1, asn))).collect();
+    assert_eq!(orderings, HashSet::from([Ordering::Less, Ordering::Greater]));
+
+    for tie_breaker in [TieBreaker::LowestNextHopAsn, TieBreaker::OldestAnnouncement, TieBreaker::SeededRandom(42)] {
+        assert_eq!(tie_breaker.to_string().parse::<TieBreaker>(), Ok(tie_breaker));
+    }
+    assert!("SeededRandom:x".parse::<TieBreaker>().is_err());
+    assert!("Newest".parse::<TieBreaker>().is_err());
+}
+
+#[test]
+fn test_engine_tie_breaker() {
+    // AS 1 has equally good routes from its providers 2 and 3, which hear the prefix from 4
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).with_providers(vec![2, 3]),
+        ASBuilder::new(2).with_customers(vec![1, 4]),
+        ASBuilder::new(3).with_customers(vec![1, 4]),
+        ASBuilder::new(4).with_providers(vec![2, 3]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    let prefix: Prefix = IpNetwork::from_str("10.0.0.0/24").unwrap();
+    let next_hop_at_1 = |tie_breaker, storage_mode| {
+        let mut engine = SimulationEngine::new(&as_graph).with_storage_mode(storage_mode).with_tie_breaker(tie_breaker);
+        engine.setup(vec![(4, Announcement::new_with_path(prefix, vec![], 4, Relationships::Origin, Timestamps::VICTIM))]);
+        engine.run(3);
+        engine.policy_store.get(&1).unwrap().local_rib[&prefix].next_hop_asn
+    };
+
+    for storage_mode in [PolicyStorageMode::Full, PolicyStorageMode::LocalRibOnly] {
+        assert_eq!(next_hop_at_1(TieBreaker::LowestNextHopAsn, storage_mode), 2);
+        let chosen: HashSet<u32> = (0..16).map(|seed| next_hop_at_1(TieBreaker::SeededRandom(seed), storage_mode)).collect();
+        assert_eq!(chosen, HashSet::from([2, 3]));
+    }
+}
+
+#[test]
+fn test_prefer_oldest_announcement() {
+    // AS 1's providers 2 and 3 originate the same prefix, 3 long before 2
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).with_providers(vec![2, 3]),
+        ASBuilder::new(2).with_customers(vec![1]),
+        ASBuilder::new(3).with_customers(vec![1]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    let prefix: Prefix = IpNetwork::from_str("10.0.0.0/24").unwrap();
+    let next_hop_at_1 = |tie_breaker| {
+        let mut engine = SimulationEngine::new(&as_graph).with_tie_breaker(tie_breaker);
+        engine.setup(vec![
+            (2, Announcement::new_with_path(prefix, vec![], 2, Relationships::Origin, Timestamps(10))),
+            (3, Announcement::new_with_path(prefix, vec![], 3, Relationships::Origin, Timestamps(4))),
+        ]);
+        engine.run(3);
+        let ann = engine.policy_store.get(&1).unwrap().local_rib[&prefix].clone();
+        (ann.next_hop_asn, ann.timestamp)
+    };
+
+    assert_eq!(next_hop_at_1(TieBreaker::LowestNextHopAsn), (2, Timestamps(10)));
+    assert_eq!(next_hop_at_1(TieBreaker::OldestAnnouncement), (3, Timestamps(4)));
+
+    assert!(Timestamps::VICTIM < Timestamps::ATTACKER);
+    assert_eq!(Timestamps(4).age(Timestamps(10)), 6);
+    assert_eq!(Timestamps(10).age(Timestamps(4)), 0);
+}
+
+#[test]
+fn test_engine_applies_local_pref_and_med() {
+    // 1 and 10 are customers of 2; 1 is a customer of 3, which reaches 10 via its customer 4
+    // and 5 is a customer of 1
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).with_providers(vec![2, 3]).with_customers(vec![5]),
+        ASBuilder::new(2).with_customers(vec![1, 10]),
+        ASBuilder::new(3).with_customers(vec![1, 4]),
+        ASBuilder::new(4).with_providers(vec![3]).with_customers(vec![10]),
+        ASBuilder::new(5).with_providers(vec![1]),
+        ASBuilder::new(10).with_providers(vec![2, 4]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    let prefix: Prefix = IpNetwork::from_str("10.0.0.0/24").unwrap();
+    let seed = || vec![(10, Announcement::new_with_path(prefix, vec![], 10, Relationships::Origin, Timestamps::VICTIM))];
+
+    for storage_mode in [PolicyStorageMode::Full, PolicyStorageMode::LocalRibOnly] {
+        let mut engine = SimulationEngine::new(&as_graph).with_storage_mode(storage_mode);
+        engine.setup(seed());
+        engine.run(3);
+        assert_eq!(engine.policy_store.get(&1).unwrap().local_rib[&prefix].as_path.to_vec(), vec![1, 2, 10]);
+
+        // AS 1 prefers routes from 3 despite the longer path
+        let mut engine = SimulationEngine::new(&as_graph).with_storage_mode(storage_mode);
+        engine.policy_store.get_mut(&1).unwrap().set_local_pref(3, 150);
+        engine.setup(seed());
+        engine.run(3);
+        let best = &engine.policy_store.get(&1).unwrap().local_rib[&prefix];
+        assert_eq!(best.as_path.to_vec(), vec![1, 3, 4, 10]);
+        assert_eq!(best.local_pref, Some(150));
+        // Neither attribute leaves the AS
+        let at_5 = &engine.policy_store.get(&5).unwrap().local_rib[&prefix];
+        assert_eq!((at_5.local_pref, at_5.med), (None, None));
+    }
+
+    // With 3 reaching 10 directly, both paths tie until 2's MED makes AS 1 pick 3
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).with_providers(vec![2, 3]),
+        ASBuilder::new(2).with_customers(vec![1, 10]),
+        ASBuilder::new(3).with_customers(vec![1, 10]),
+        ASBuilder::new(10).with_providers(vec![2, 3]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    let mut engine = SimulationEngine::new(&as_graph);
+    engine.policy_store.get_mut(&2).unwrap().set_export_med(1, 50);
+    engine.setup(seed());
+    engine.run(3);
+    let best = &engine.policy_store.get(&1).unwrap().local_rib[&prefix];
+    assert_eq!(best.as_path.to_vec(), vec![1, 3, 10]);
+    assert_eq!(engine.policy_store.get(&1).unwrap().ribs_in[(2, &prefix)].med, Some(50));
+}
+
+#[cfg(feature = "aspa")]
+#[test]
+fn test_aspa_collapses_prepending() {
+    use std::sync::Arc;
+    use bgpsimulator::aspa_validator::AspaValidator;
+
+    let as_graph = create_test_as_graph();
+    let as_obj = as_graph.get(&65001).unwrap();
+    let mut validator = AspaValidator::new();
+    validator.add_attestation(65005, [65001]);
+    validator.add_attestation(65007, [65005]);
+    let mut policy = ASPAPolicy::new();
+    policy.set_aspa_validator(Arc::new(validator));
+
+    // 65007 and 65005 each prepend, which isn't a hop between an AS and itself
+    let mut ann = create_test_announcement();
+    ann.next_hop_asn = 65005;
+    ann.as_path = Announcement::new(ann.prefix, 65007, Relationships::Origin).with_prepending(2).as_path.prepend(65005).prepend(65005);
+    assert_eq!(ann.as_path.to_vec(), vec![65005, 65005, 65007, 65007, 65007]);
+    assert_eq!(ann.as_path.without_prepending().to_vec(), vec![65005, 65007]);
+    assert_eq!(policy.validate_announcement(&ann, Relationships::Customers, as_obj, None), ValidationResult::Accepted);
+}
@@ -1,5 +1,7 @@
-use bgpsimulator::route_validator::{ROA, RouteValidator};
-use bgpsimulator::shared::ROAValidity;
+use bgpsimulator::route_validator::{FrozenValidator, ROA, ROALint, ROALintKind, RouteValidator, TimedROA};
+use bgpsimulator::shared::{ROARouted, ROAValidity};
+use bgpsimulator::simulation_engine::PrefixTable;
+use std::sync::Arc;
 use ipnetwork::IpNetwork;
 use std::str::FromStr;
 
@@ -202,6 +204,71 @@ fn test_route_validator_cache() {
     assert_eq!(validity1, ROAValidity::Valid);
 }
 
+#[test]
+fn test_route_validator_cache_shared_across_threads() {
+    let mut validator = RouteValidator::new();
+    validator.add_roa(ROA::new(IpNetwork::from_str("10.0.0.0/8").unwrap(), 65001, Some(16)));
+    let queries: Vec<(IpNetwork, u32)> = (0..64u32)
+        .map(|i| (IpNetwork::from_str(&format!("10.{}.0.0/{}", i, 12 + i % 8)).unwrap(), 65000 + i % 3))
+        .collect();
+    let expected: Vec<ROAValidity> = queries.iter().map(|(prefix, origin)| validator.get_roa_outcome(prefix, *origin).0).collect();
+
+    // Every thread sees the same outcomes, whether computed or served by another thread's entries
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..10 {
+                    let outcomes: Vec<ROAValidity> = queries.iter().map(|(prefix, origin)| validator.get_roa_outcome(prefix, *origin).0).collect();
+                    assert_eq!(outcomes, expected);
+                }
+            });
+        }
+    });
+
+    // Changing the ROAs invalidates every shard
+    validator.add_roa(ROA::new(IpNetwork::from_str("10.0.0.0/8").unwrap(), 65002, Some(24)));
+    let (validity, _) = validator.get_roa_outcome(&queries[2].0, 65002);
+    assert_eq!(validity, ROAValidity::Valid);
+}
+
+#[test]
+fn test_frozen_validator() {
+    let prefix = IpNetwork::from_str("10.0.0.0/24").unwrap();
+    let subprefix = IpNetwork::from_str("10.0.0.0/25").unwrap();
+    let mut validator = RouteValidator::new();
+    validator.add_roa(ROA::new(prefix, 65001, Some(24)));
+    let routes = [(prefix, 65001), (subprefix, 65002), (prefix, 65001)];
+    
+    // Prefixes are keyed by their ids in the given table, which may already hold others
+    let table = Arc::new(PrefixTable::new());
+    let other = IpNetwork::from_str("192.0.2.0/24").unwrap();
+    table.intern(other);
+    let frozen = FrozenValidator::new(&validator, table.clone(), routes);
+    assert!(Arc::ptr_eq(frozen.table(), &table));
+    assert_eq!(frozen.len(), 2);
+    assert_eq!(frozen.get(&prefix, 65001), Some(validator.get_roa_outcome(&prefix, 65001)));
+    assert_eq!(frozen.get(&subprefix, 65002).unwrap().0, ROAValidity::InvalidLengthAndOrigin);
+    assert_eq!(frozen.get(&subprefix, 65001), None);
+    assert_eq!(frozen.get(&other, 65001), None);
+    assert_eq!((frozen.prefix_id(&prefix), frozen.prefix_id(&subprefix)), (table.id(&prefix), table.id(&subprefix)));
+    assert_eq!(frozen.prefix_id(&other), None);
+    assert_eq!(frozen.get_by_id(table.id(&subprefix).unwrap(), 65002), frozen.get(&subprefix, 65002));
+    assert_eq!(frozen.get_by_id(3, 65002), None);
+    assert_eq!(frozen.routes().collect::<Vec<_>>(), vec![(prefix, 65001), (subprefix, 65002)]);
+    
+    // Frozen outcomes are recomputed when the ROAs change, and copied along with the validator
+    validator.freeze(table.clone(), routes);
+    validator.add_roa(ROA::new(subprefix, 65002, None));
+    assert_eq!(validator.frozen().unwrap().get(&subprefix, 65002).unwrap().0, ROAValidity::Valid);
+    assert_eq!(validator.clone().frozen().unwrap().len(), 2);
+    // Lookups by id use the frozen outcome only if the id is the prefix's, and otherwise fall back to the trie
+    let subprefix_id = table.id(&subprefix).unwrap();
+    assert_eq!(validator.get_roa_outcome_by_id(subprefix_id, &subprefix, 65002).0, ROAValidity::Valid);
+    assert_eq!(validator.get_roa_outcome_by_id(subprefix_id, &prefix, 65001), validator.get_roa_outcome(&prefix, 65001));
+    validator.unfreeze();
+    assert_eq!(validator.get_roa_outcome(&subprefix, 65002).0, ROAValidity::Valid);
+}
+
 #[test]
 fn test_non_routed_roa() {
     let roa = ROA::new(
@@ -212,4 +279,122 @@ fn test_non_routed_roa() {
     
     assert!(!roa.is_routed());
     assert!(roa.is_non_routed());
-}
\ No newline at end of file
+}
+#[test]
+fn test_roa_audit_max_length() {
+    let roas = vec![
+        ROA::new(IpNetwork::from_str("10.0.0.0/16").unwrap(), 65001, Some(16)),
+        ROA::new(IpNetwork::from_str("20.0.0.0/16").unwrap(), 65001, Some(24)),
+        ROA::new(IpNetwork::from_str("30.0.0.0/16").unwrap(), 65001, Some(8)),
+    ];
+
+    let report = RouteValidator::audit_roas(&roas, 0);
+    assert_eq!(report.num_roas, 3);
+    assert_eq!(report.lints.len(), 2);
+    assert!(report.lints.iter().any(|lint| lint.kind == ROALintKind::LooseMaxLength { slack: 8 }));
+    assert!(report.lints.iter().any(|lint| lint.kind == ROALintKind::InvalidMaxLength));
+
+    // Within the allowed slack nothing is flagged
+    let report = RouteValidator::audit_roas(&roas[..2], 8);
+    assert!(report.is_clean());
+}
+
+#[test]
+fn test_roa_audit_conflicts() {
+    let covering = ROA::new(IpNetwork::from_str("10.0.0.0/16").unwrap(), 65001, Some(24));
+    let redundant = ROA::new(IpNetwork::from_str("10.0.1.0/24").unwrap(), 65001, Some(24));
+    let conflicting = ROA::new(IpNetwork::from_str("10.0.2.0/24").unwrap(), 65002, Some(24));
+    let overridden = ROA::new(IpNetwork::from_str("10.0.3.0/24").unwrap(), 0, Some(24));
+    let unrelated = ROA::new(IpNetwork::from_str("10.1.0.0/16").unwrap(), 0, Some(16));
+
+    let report = RouteValidator::audit_roas(
+        &[covering.clone(), redundant.clone(), conflicting.clone(), overridden.clone(), unrelated],
+        8,
+    );
+
+    assert_eq!(report.lints.len(), 3);
+    assert!(report.lints.contains(&ROALint {
+        roa: redundant,
+        kind: ROALintKind::Redundant { other: covering.clone() },
+    }));
+    assert!(report.lints.contains(&ROALint {
+        roa: conflicting,
+        kind: ROALintKind::OriginConflict { other: covering.clone() },
+    }));
+    assert!(report.lints.contains(&ROALint {
+        roa: overridden,
+        kind: ROALintKind::NonRoutedOverridden { other: covering },
+    }));
+    assert_eq!(report.counts()["REDUNDANT"], 1);
+}
+
+#[test]
+fn test_route_validator_add_roas() {
+    let net = |s: &str| IpNetwork::from_str(s).unwrap();
+    let mut validator = RouteValidator::new();
+    validator.add_roa(ROA::new(net("10.0.0.0/8"), 65001, Some(8)));
+    // Cached before the bulk insert, which must invalidate it
+    assert_eq!(validator.get_roa_outcome(&net("10.1.0.0/16"), 65002).0, ROAValidity::InvalidLengthAndOrigin);
+
+    validator.add_roas(vec![
+        ROA::new(net("10.1.0.0/16"), 65002, None),
+        ROA::new(net("2001:db8::/32"), 65003, Some(48)),
+        ROA::new(net("0.0.0.0/0"), 0, None),
+        ROA::new(net("255.255.255.255/32"), 65004, None),
+    ]);
+    assert_eq!(validator.roas().len(), 5);
+    assert_eq!(validator.get_roa_outcome(&net("10.1.0.0/16"), 65002).0, ROAValidity::Valid);
+    assert_eq!(validator.get_roa_outcome(&net("2001:db8:1::/48"), 65003).0, ROAValidity::Valid);
+    assert_eq!(validator.get_roa_outcome(&net("2001:db8:1::/64"), 65003).0, ROAValidity::InvalidLength);
+    assert_eq!(validator.get_roa_outcome(&net("255.255.255.255/32"), 65004).0, ROAValidity::Valid);
+    // The AS0 default covers everything in IPv4 but nothing in IPv6
+    assert_eq!(validator.get_roa_outcome(&net("192.0.2.0/24"), 65005).0, ROAValidity::InvalidLengthAndOrigin);
+    assert_eq!(validator.get_roa_outcome(&net("2001:db9::/32"), 65005).0, ROAValidity::Unknown);
+}
+
+#[test]
+fn test_route_validator_roa_removal_and_churn() {
+    let net = |s: &str| IpNetwork::from_str(s).unwrap();
+    let mut validator = RouteValidator::new();
+    let roa = ROA::new(net("10.0.0.0/8"), 65001, None);
+    validator.add_roa(roa.clone());
+    assert_eq!(validator.get_roa_outcome(&net("10.0.0.0/8"), 65001).0, ROAValidity::Valid);
+
+    assert!(validator.remove_roa(&roa));
+    assert!(!validator.remove_roa(&roa));
+    assert!(!validator.remove_roa(&ROA::new(net("10.1.0.0/16"), 65001, None)));
+    assert_eq!(validator.get_roa_outcome(&net("10.0.0.0/8"), 65001).0, ROAValidity::Unknown);
+
+    validator.add_roa(roa.clone());
+    validator.replace_roas(vec![ROA::new(net("10.0.0.0/8"), 65002, None)]);
+    assert_eq!(validator.roas().len(), 1);
+    assert_eq!(validator.get_roa_outcome(&net("10.0.0.0/8"), 65001).0, ROAValidity::InvalidOrigin);
+
+    // Published for rounds 1 and 2, then expires
+    validator.add_timed_roa(TimedROA::new(roa.clone(), 1, Some(3)));
+    assert!(!validator.advance_to(0));
+    assert_eq!(validator.get_roa_outcome(&net("10.0.0.0/8"), 65001).0, ROAValidity::InvalidOrigin);
+    assert!(validator.advance_to(1));
+    assert_eq!(validator.get_roa_outcome(&net("10.0.0.0/8"), 65001).0, ROAValidity::Valid);
+    assert!(!validator.advance_to(2));
+    assert!(validator.advance_to(3));
+    assert_eq!(validator.get_roa_outcome(&net("10.0.0.0/8"), 65001).0, ROAValidity::InvalidOrigin);
+    assert_eq!(validator.roas().len(), 1);
+}
+
+#[test]
+fn test_route_validator_non_routed_space() {
+    let net = |s: &str| IpNetwork::from_str(s).unwrap();
+    let mut validator = RouteValidator::new();
+    validator.add_roa(ROA::new(net("1.2.0.0/16"), 0, None));
+    assert_eq!(validator.get_roa_routed(&net("1.2.3.4/32")), ROARouted::NonRouted);
+    assert_eq!(validator.get_roa_routed(&net("1.3.0.0/16")), ROARouted::Unknown);
+    // A superprefix isn't covered by the AS0 ROA
+    assert_eq!(validator.get_roa_routed(&net("1.0.0.0/8")), ROARouted::Unknown);
+    assert_eq!(validator.get_roa_outcome(&net("1.2.0.0/16"), 65001), (ROAValidity::InvalidOrigin, ROARouted::NonRouted));
+
+    // A routed ROA for the same space overrides the AS0 one, including when both are equally invalid
+    validator.add_roa(ROA::new(net("1.2.0.0/16"), 65002, None));
+    assert_eq!(validator.get_roa_routed(&net("1.2.3.4/32")), ROARouted::Routed);
+    assert_eq!(validator.get_roa_outcome(&net("1.2.0.0/16"), 65001), (ROAValidity::InvalidOrigin, ROARouted::Routed));
+}
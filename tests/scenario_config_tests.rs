@@ -0,0 +1,418 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use bgpsimulator::as_graphs::as_graph::{ASBuilder, ASGraph, ASMetadata, RIR};
+use bgpsimulator::as_graphs::as_graph::as_metadata::country_code;
+use bgpsimulator::Error;
+use bgpsimulator::route_validator::RouteValidator;
+use bgpsimulator::shared::{ASNGroups, Settings, Timestamps};
+use bgpsimulator::simulation_engine::{NextHopVerification, PolicyStorageMode, SimulationEngine, TieBreaker};
+use bgpsimulator::simulation_framework::{DataTracker, MeasuredAdoption, Scenario, ScenarioEvent, scenario_config_from_bgpy_json, settings_from_bgpy_name};
+use bgpsimulator::simulation_framework::scenario::ScenarioTrait;
+use bgpsimulator::simulation_framework::scenario_config::{ASNCandidates, AdopterSelector, AttackerPlacement, RoaGeneration, ScenarioConfig};
+use bgpsimulator::simulation_framework::scenarios::{SubprefixHijack, max_length_vulnerable_prefixes};
+
+/// Create a small hierarchy for placement tests
+///
+///        1 (Tier-1) --- 2 (Tier-1)
+///       /  \             \
+///      3    4 ---peer--- 5
+///      |                 |
+///      6 (victim)        7
+///      |
+///      8
+fn create_placement_graph() -> ASGraph {
+    let builders = vec![
+        ASBuilder::new(1).as_tier_1().with_peers(vec![2]).with_customers(vec![3, 4]),
+        ASBuilder::new(2).as_tier_1().with_peers(vec![1]).with_customers(vec![5]),
+        ASBuilder::new(3).with_providers(vec![1]).with_peers(vec![4]).with_customers(vec![6]),
+        ASBuilder::new(4).with_providers(vec![1]).with_peers(vec![3, 5]),
+        ASBuilder::new(5).with_providers(vec![2]).with_peers(vec![4]).with_customers(vec![7]),
+        ASBuilder::new(6).with_providers(vec![3]).with_customers(vec![8]),
+        ASBuilder::new(7).with_providers(vec![5]),
+        ASBuilder::new(8).with_providers(vec![6]),
+    ];
+
+    let mut as_graph = ASGraph::build(builders);
+    as_graph.assign_as_propagation_rank();
+    as_graph
+}
+
+#[test]
+fn test_placement_candidates() {
+    let as_graph = create_placement_graph();
+
+    assert_eq!(AttackerPlacement::RandomStub.candidate_asns(&as_graph, 6), vec![4, 7, 8]);
+    assert_eq!(AttackerPlacement::PeerOfVictimProvider.candidate_asns(&as_graph, 6), vec![4]);
+    assert_eq!(AttackerPlacement::Tier1Customer.candidate_asns(&as_graph, 6), vec![3, 4, 5]);
+    assert_eq!(AttackerPlacement::VictimCustomerCone.candidate_asns(&as_graph, 6), vec![8]);
+
+    // The victim is never a candidate
+    assert!(!AttackerPlacement::RandomStub.candidate_asns(&as_graph, 8).contains(&8));
+}
+
+#[test]
+fn test_new_with_attacker_placement() {
+    let as_graph = create_placement_graph();
+
+    let config = ScenarioConfig::new_with_attacker_placement(
+        "Cone attacker".to_string(),
+        "SubprefixHijack".to_string(),
+        AttackerPlacement::VictimCustomerCone,
+        6,
+        &as_graph,
+    ).unwrap();
+
+    assert_eq!(config.override_attacker_asns, Some(HashSet::from([8])));
+    assert_eq!(config.override_legitimate_origin_asns, Some(HashSet::from([6])));
+
+    // AS 7 has no customers, so nothing can sit in its cone
+    let result = ScenarioConfig::new_with_attacker_placement(
+        "Empty cone".to_string(),
+        "SubprefixHijack".to_string(),
+        AttackerPlacement::VictimCustomerCone,
+        7,
+        &as_graph,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_group_adoption_settings() {
+    let as_graph = create_placement_graph();
+    let mut config = ScenarioConfig::new("Mixed".to_string(), "SubprefixHijack".to_string())
+        .with_group_adoption_setting(ASNGroups::Transit, Settings::Aspa)
+        .with_group_adoption_setting(ASNGroups::StubsOrMh, Settings::Rov)
+        .with_group_adoption_setting(ASNGroups::Tier1, Settings::PathEnd);
+    // AS 8 doesn't adopt anything
+    config.override_adopting_asns = Some((1..=7).collect());
+
+    let scenario = Scenario::new(config, &as_graph, 0.0);
+    let mut engine = SimulationEngine::new(&as_graph);
+    let counts = scenario.apply_adoption_settings(&mut engine);
+
+    let settings = |asn| engine.policy_store.get(&asn).unwrap().settings;
+    // Tier-1s are also transit ASes, but their group was added last
+    assert_eq!(settings(1), Settings::PathEnd);
+    assert_eq!(settings(3), Settings::Aspa);
+    assert_eq!(settings(6), Settings::Aspa);
+    assert_eq!(settings(4), Settings::Rov);
+    assert_eq!(settings(8), Settings::BaseDefense);
+    assert_eq!(counts, HashMap::from([
+        (Settings::PathEnd, 2),
+        (Settings::Aspa, 3),
+        (Settings::Rov, 2),
+        (Settings::BaseDefense, 1),
+    ]));
+
+    // ASes outside every group fall back to the default adoption setting
+    let config = ScenarioConfig::new("Default".to_string(), "SubprefixHijack".to_string())
+        .with_adoption_setting(Settings::Rov, true)
+        .with_group_adoption_setting(ASNGroups::Tier1, Settings::Aspa);
+    let settings = config.adoption_settings(&as_graph, &HashSet::from([1, 7]));
+    assert_eq!(settings, HashMap::from([(1, Settings::Aspa), (7, Settings::Rov)]));
+}
+
+#[test]
+fn test_group_settings_apply_without_adoption() {
+    let mut as_graph = create_placement_graph();
+    for asn in [3, 6, 8] {
+        as_graph.set_metadata(asn, ASMetadata::new().with_country("nl").with_rir(RIR::Ripe)).unwrap();
+    }
+    assert!(as_graph.set_metadata(9, ASMetadata::new()).is_err());
+
+    // Every AS in the country runs ROV at 0% adoption, while AS 6's own setting still wins
+    let config = ScenarioConfig::new("Country ROV".to_string(), "SubprefixHijack".to_string())
+        .with_adoption_setting(Settings::Aspa, true)
+        .with_group_setting(ASNGroups::Country(country_code("nl").unwrap()), Settings::Rov)
+        .with_asn_setting(6, Settings::PathEnd);
+    let settings = config.adoption_settings(&as_graph, &HashSet::from([1, 3]));
+    assert_eq!(settings, HashMap::from([
+        (1, Settings::Aspa),
+        (3, Settings::Rov),
+        (6, Settings::PathEnd),
+        (8, Settings::Rov),
+    ]));
+}
+
+#[test]
+fn test_asn_settings_override_adoption() {
+    let as_graph = create_placement_graph();
+    let config = ScenarioConfig::new("Hand-crafted".to_string(), "SubprefixHijack".to_string())
+        .with_adoption_setting(Settings::Rov, true)
+        .with_group_adoption_setting(ASNGroups::Tier1, Settings::Aspa)
+        .with_adopting_asns(HashSet::from([1, 3]))
+        .with_asn_setting(1, Settings::PathEnd)
+        .with_asn_setting(8, Settings::Aspa);
+
+    // Per-AS settings win over groups and apply to non-adopting ASes too
+    let settings = config.adoption_settings(&as_graph, config.override_adopting_asns.as_ref().unwrap());
+    assert_eq!(settings, HashMap::from([(1, Settings::PathEnd), (3, Settings::Rov), (8, Settings::Aspa)]));
+}
+
+#[test]
+fn test_adoption_rules_mix_policies() {
+    let as_graph = create_placement_graph();
+    let adopting_asns = HashSet::from([1, 2, 4, 7, 8]);
+
+    // Tier-1 adopters run ASPA and stub adopters ROV, while the others have no default setting
+    let config = ScenarioConfig::new("Mixed".to_string(), "SubprefixHijack".to_string())
+        .with_adoption_rule(AdopterSelector::Group(ASNGroups::Stubs), Settings::Rov)
+        .with_adoption_rule(AdopterSelector::Group(ASNGroups::Tier1), Settings::Aspa);
+    let settings = config.adoption_settings(&as_graph, &adopting_asns);
+    assert_eq!(settings, HashMap::from([(1, Settings::Aspa), (2, Settings::Aspa), (7, Settings::Rov), (8, Settings::Rov)]));
+    assert_eq!(config.to_json()["adoption_rules"], serde_json::json!([
+        {"adopters": "STUBS", "setting": "Rov"},
+        {"adopters": "TIER_1", "setting": "Aspa"},
+    ]));
+
+    // Later rules win, and percentage rules pick that share of the adopters
+    let config = ScenarioConfig::new("Mixed".to_string(), "SubprefixHijack".to_string())
+        .with_adoption_rule(AdopterSelector::Percent(100.0), Settings::PathEnd)
+        .with_adoption_rule(AdopterSelector::Group(ASNGroups::Tier1), Settings::Aspa);
+    let settings = config.adoption_settings(&as_graph, &adopting_asns);
+    assert_eq!(settings, HashMap::from([
+        (1, Settings::Aspa),
+        (2, Settings::Aspa),
+        (4, Settings::PathEnd),
+        (7, Settings::PathEnd),
+        (8, Settings::PathEnd),
+    ]));
+    let config = config.with_adoption_rule(AdopterSelector::Percent(40.0), Settings::Rov);
+    let settings = config.adoption_settings(&as_graph, &adopting_asns);
+    assert_eq!(settings.values().filter(|&&setting| setting == Settings::Rov).count(), 2);
+
+    // The resulting mix is recorded per trial
+    let scenario = Scenario::new(config.with_adopting_asns(adopting_asns), &as_graph, 0.0);
+    let mut tracker = DataTracker::new("Mixed".to_string(), 0.0);
+    let mut engine = SimulationEngine::new(&as_graph);
+    for (setting, count) in scenario.apply_adoption_settings(&mut engine) {
+        tracker.add_adoption_metric(setting, count as f64 / as_graph.len() as f64 * 100.0);
+    }
+    let mix = tracker.adoption_mix();
+    assert_eq!(mix[&Settings::BaseDefense], 3.0 / 8.0 * 100.0);
+    assert_eq!(mix.values().sum::<f64>(), 100.0);
+}
+
+#[test]
+fn test_scenario_selects_anycast_origins() {
+    let as_graph = create_placement_graph();
+
+    // Stubs 4, 7 and 8, minus the attacker
+    let config = ScenarioConfig::new("Anycast".to_string(), "PrefixHijack".to_string())
+        .with_attacker_asns(HashSet::from([8]))
+        .with_num_legitimate_origins(2);
+    let scenario = Scenario::new(config.clone(), &as_graph, 0.0);
+    assert_eq!(scenario.legitimate_origin_asns, HashSet::from([4, 7]));
+
+    // Asking for more origins than there are stubs takes them all
+    let scenario = Scenario::new(config.with_num_legitimate_origins(5), &as_graph, 0.0);
+    assert_eq!(scenario.legitimate_origin_asns, HashSet::from([4, 7]));
+}
+
+#[test]
+fn test_scenario_draws_from_candidates() {
+    let as_graph = create_placement_graph();
+
+    // Transit attackers, and victims from a list that includes an AS not in the graph
+    let config = ScenarioConfig::new("Candidates".to_string(), "PrefixHijack".to_string())
+        .with_attacker_candidates(ASNCandidates::Group(ASNGroups::Transit))
+        .with_legitimate_origin_candidates(ASNCandidates::List(vec![6, 8, 99]))
+        .with_num_legitimate_origins(2);
+    for _ in 0..20 {
+        let scenario = Scenario::new(config.clone(), &as_graph, 0.0);
+        let attacker = *scenario.attacker_asns.iter().next().unwrap();
+        assert!([1, 2, 3, 5, 6].contains(&attacker));
+        let expected: HashSet<u32> = [6, 8].into_iter().filter(|&asn| asn != attacker).collect();
+        assert_eq!(scenario.legitimate_origin_asns, expected);
+    }
+
+    // Coordinated attacks draw distinct attackers, and every one of them announces the hijack
+    let scenario = Scenario::new(config.clone().with_num_attackers(3), &as_graph, 0.0);
+    assert_eq!(scenario.attacker_asns.len(), 3);
+    let hijack = SubprefixHijack::new(scenario.attacker_asns.clone(), HashSet::from([8]));
+    let seeds = hijack.get_seed_asn_ann_dict(&as_graph);
+    assert!(scenario.attacker_asns.iter().all(|asn| seeds.contains_key(asn)));
+    let attackers = AttackerPlacement::Tier1Customer.select_many(&as_graph, 6, 5);
+    assert_eq!(attackers, HashSet::from([3, 4, 5]));
+
+    // Overrides still win over candidates
+    let scenario = Scenario::new(config.with_attacker_asns(HashSet::from([7])), &as_graph, 0.0);
+    assert_eq!(scenario.attacker_asns, HashSet::from([7]));
+    assert_eq!(ASNCandidates::List(vec![8, 6, 8]).asns(&as_graph), vec![6, 8]);
+}
+
+#[test]
+fn test_measured_adoption() {
+    let as_graph = create_placement_graph();
+    let adoption = MeasuredAdoption::load_from_file(Path::new("tests/fixtures/rov_adoption_sample.csv")).unwrap();
+    assert_eq!(adoption.len(), 4);
+    assert_eq!(adoption.probability(3), 1.0);
+    assert_eq!(adoption.probability(5), 0.5);
+    assert_eq!(adoption.probability(7), 0.0);
+    assert_eq!(adoption.expected_adopters(&as_graph), 2.5);
+
+    // Certain adopters always adopt, unlisted and zero-probability ASes never do
+    let config = ScenarioConfig::new("Measured".to_string(), "SubprefixHijack".to_string())
+        .with_attacker_asns(HashSet::from([7]))
+        .with_legitimate_origin_asns(HashSet::from([8]))
+        .with_measured_adoption(Arc::new(adoption));
+    for _ in 0..20 {
+        let scenario = Scenario::new(config.clone(), &as_graph, 50.0);
+        assert!(scenario.adopting_asns.is_superset(&HashSet::from([1, 3])));
+        assert!(scenario.adopting_asns.is_subset(&HashSet::from([1, 3, 5])));
+    }
+
+    let value = config.to_json();
+    assert_eq!(value["measured_adoption_file"], "tests/fixtures/rov_adoption_sample.csv");
+    assert_eq!(ScenarioConfig::from_json(&value).unwrap().measured_adoption, config.measured_adoption);
+
+    assert!(MeasuredAdoption::parse("1,1.5").unwrap_err().contains("not between 0 and 1"));
+    assert!(MeasuredAdoption::parse("1,0.5\nx,0.5").unwrap_err().starts_with("Line 2"));
+}
+
+#[test]
+fn test_scenario_config_from_json() {
+    let value = serde_json::json!({
+        "scenario_name": "SubprefixHijack",
+        "attacker_asns": [666],
+        "legitimate_origin_asns": [777],
+        "num_attackers": 3,
+        "num_legitimate_origins": 2,
+        "default_adoption_settings": {"Rov": true},
+        "asn_settings": {"3": "Aspa"},
+        "roas": [{"prefix": "1.2.0.0/16", "origin": 777}],
+        "storage_mode": "LocalRibOnly",
+        "tie_breaker": "SeededRandom:7",
+        "next_hop_verification": "Strict",
+        "roa_generation": "MaxLength:24",
+        "percent_victims_without_roa": 25.0,
+        "percent_loose_max_length": 10.0,
+        "events": [
+            {"round": 10, "type": "withdraw", "asn": 777, "prefix": "1.2.3.0/24"},
+            {"round": 5, "type": "announce", "asn": 666, "prefix": "1.2.3.0/24"},
+        ],
+    });
+    let config = ScenarioConfig::from_json(&value).unwrap();
+
+    assert_eq!(config.label, "SubprefixHijack");
+    assert_eq!(config.override_attacker_asns, Some(HashSet::from([666])));
+    assert_eq!(config.override_legitimate_origin_asns, Some(HashSet::from([777])));
+    assert_eq!(config.num_attackers, 3);
+    assert_eq!(config.num_legitimate_origins, 2);
+    assert_eq!(config.override_adopting_asns, None);
+    assert_eq!(config.default_adoption_settings, HashMap::from([(Settings::Rov, true)]));
+    assert_eq!(config.override_asn_settings, Some(HashMap::from([(3, Settings::Aspa)])));
+    let roas = config.override_roas.unwrap();
+    assert_eq!((roas[0].origin, roas[0].max_length), (777, 16));
+    assert_eq!(config.storage_mode, PolicyStorageMode::LocalRibOnly);
+    assert_eq!(config.tie_breaker, TieBreaker::SeededRandom(7));
+    assert_eq!(config.next_hop_verification, NextHopVerification::Strict);
+    assert_eq!(config.roa_generation, RoaGeneration::MaxLength(24));
+    assert_eq!(config.percent_victims_without_roa, 25.0);
+    assert_eq!(config.percent_loose_max_length, 10.0);
+    let events: Vec<(u32, &ScenarioEvent)> = config.event_schedule.iter().collect();
+    assert!(matches!(events[0], (5, ScenarioEvent::Announce(666, ann)) if ann.timestamp == Timestamps(5)));
+    assert!(matches!(events[1], (10, ScenarioEvent::Withdraw(777, _))));
+
+    assert!(matches!(ScenarioConfig::from_json(&serde_json::json!({"label": "No scenario"})), Err(Error::ConfigInvalid(_))));
+    let err = ScenarioConfig::from_json(&serde_json::json!({
+        "scenario_name": "SubprefixHijack",
+        "asn_settings": {"3": "NotASetting"},
+    })).unwrap_err();
+    assert!(matches!(&err, Error::ConfigInvalid(msg) if msg.starts_with("Invalid asn_settings")), "{}", err);
+}
+
+#[test]
+fn test_roa_generation() {
+    let graph = create_placement_graph();
+    let scenario = SubprefixHijack::new(HashSet::from([7]), HashSet::from([6]));
+    let seeds = scenario.get_seed_asn_ann_dict(&graph);
+    let origins = HashSet::from([6]);
+    let scenario_roas = scenario.get_roas(&graph);
+
+    // Only the legitimate /24 gets a ROA, never the attacker's /25
+    let exact = RoaGeneration::ExactMatch.roas(&seeds, &origins);
+    assert_eq!(exact.len(), 1);
+    assert_eq!((exact[0].prefix.to_string(), exact[0].origin, exact[0].max_length), ("1.2.3.0/24".to_string(), 6, 24));
+    let covering = RoaGeneration::MaxLength(28).roas(&seeds, &origins);
+    assert_eq!(covering[0].max_length, 28);
+    assert_eq!(RoaGeneration::MaxLength(16).roas(&seeds, &origins)[0].max_length, 24);
+
+    let config = ScenarioConfig::new("Roas".to_string(), "SubprefixHijack".to_string());
+    assert!(!config.preprocesses_roas());
+    assert_eq!(config.roas_for_run(scenario_roas.clone(), &seeds, &origins).len(), scenario_roas.len());
+    let config = config.with_roa_generation(RoaGeneration::MaxLength(28));
+    assert_eq!(config.roas_for_run(scenario_roas.clone(), &seeds, &origins)[0].max_length, 28);
+    let config = config.with_percent_victims_without_roa(100.0);
+    assert!(config.roas_for_run(scenario_roas.clone(), &seeds, &origins).is_empty());
+    
+    // Loose ROAs make the attacker's /25 valid when forged from the victim
+    let config = ScenarioConfig::new("Roas".to_string(), "SubprefixHijack".to_string());
+    assert!(!config.models_loose_max_length());
+    let loose = config.with_percent_loose_max_length(100.0);
+    assert!(loose.preprocesses_roas() && loose.models_loose_max_length());
+    let loose_roas = loose.roas_for_run(scenario_roas.clone(), &seeds, &origins);
+    assert_eq!(loose_roas[0].max_length, 32);
+    let mut route_validator = RouteValidator::new();
+    route_validator.add_roas(scenario_roas);
+    assert_eq!(max_length_vulnerable_prefixes(&route_validator, &seeds, &origins), (0, 1));
+    let mut route_validator = RouteValidator::new();
+    route_validator.add_roas(loose_roas);
+    assert_eq!(max_length_vulnerable_prefixes(&route_validator, &seeds, &origins), (1, 1));
+    
+    let mut tracker = DataTracker::new("Roas".to_string(), 0.0);
+    tracker.add_max_length_vulnerability(1, 1);
+    tracker.add_max_length_vulnerability(0, 1);
+    assert_eq!(tracker.percent_max_length_vulnerable(), Some(50.0));
+
+    for generation in [RoaGeneration::Scenario, RoaGeneration::ExactMatch, RoaGeneration::MaxLength(24)] {
+        assert_eq!(generation.to_string().parse::<RoaGeneration>(), Ok(generation));
+    }
+    assert!("MaxLength:x".parse::<RoaGeneration>().is_err());
+}
+
+#[test]
+fn test_bgpy_policy_names() {
+    assert_eq!(settings_from_bgpy_name("BGPSimplePolicy"), Some(Settings::BaseDefense));
+    assert_eq!(settings_from_bgpy_name("ROVSimplePolicy"), Some(Settings::Rov));
+    assert_eq!(settings_from_bgpy_name("ASPAFull"), Some(Settings::Aspa));
+    assert_eq!(settings_from_bgpy_name("BGPSecPolicy"), Some(Settings::Bgpsec));
+    assert_eq!(settings_from_bgpy_name("PEER_ROV"), Some(Settings::PeerRov));
+    assert_eq!(settings_from_bgpy_name("ROVEnforceFirstAS"), Some(Settings::RovEnforceFirstAs));
+    assert_eq!(settings_from_bgpy_name("NotAPolicy"), None);
+}
+
+#[test]
+fn test_scenario_config_from_bgpy_json() {
+    let config = scenario_config_from_bgpy_json(&serde_json::json!({
+        "ScenarioCls": "SubprefixHijack",
+        "BasePolicyCls": "BGPSimplePolicy",
+        "AdoptPolicyCls": "ROVSimplePolicy",
+        "override_attacker_asns": [666],
+        "override_victim_asns": [777],
+        "override_non_default_asn_cls_dict": {"3": "ASPASimplePolicy"},
+        "override_adoption_settings": {"4": {"ROV": true, "ENFORCE_FIRST_AS": true}, "5": {"ROV": false}},
+    })).unwrap();
+
+    assert_eq!(config.scenario_name, "SubprefixHijack");
+    assert_eq!(config.default_adoption_settings, HashMap::from([(Settings::Rov, true)]));
+    assert_eq!(config.override_attacker_asns, Some(HashSet::from([666])));
+    assert_eq!(config.override_legitimate_origin_asns, Some(HashSet::from([777])));
+    assert_eq!(config.override_asn_settings, Some(HashMap::from([
+        (3, Settings::Aspa),
+        (4, Settings::RovEnforceFirstAs),
+        (5, Settings::BaseDefense),
+    ])));
+
+    let err = scenario_config_from_bgpy_json(&serde_json::json!({
+        "ScenarioCls": "SubprefixHijack",
+        "BasePolicyCls": "ROVSimplePolicy",
+    })).unwrap_err();
+    assert!(err.contains("only BGP"), "{}", err);
+    assert!(scenario_config_from_bgpy_json(&serde_json::json!({
+        "ScenarioCls": "SubprefixHijack",
+        "override_adoption_settings": {"4": {"ROV": true, "ASPA": true}},
+    })).is_err());
+}
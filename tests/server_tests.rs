@@ -0,0 +1,154 @@
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::body::Body;
+use axum::http::Request;
+use axum::Router;
+use tower::ServiceExt;
+
+use bgpsimulator::server::SimulationServer;
+
+/// Victim 4 under 3 under tier-1 1, attacker 5 under tier-1 2
+const TOPOLOGY: &str = r#"[
+    {"asn": 1, "peers": [2], "customers": [3], "tier_1": true},
+    {"asn": 2, "peers": [1], "customers": [5], "tier_1": true},
+    {"asn": 3, "providers": [1], "customers": [4]},
+    {"asn": 4, "providers": [3]},
+    {"asn": 5, "providers": [2]}
+]"#;
+
+fn create_server(name: &str) -> SimulationServer {
+    let root = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    SimulationServer::new(root)
+}
+
+/// Status and JSON body of a request to the router
+async fn request(router: &Router, method: &str, uri: &str, body: &[u8]) -> (u16, serde_json::Value) {
+    let request = Request::builder().method(method).uri(uri).body(Body::from(body.to_vec())).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status().as_u16();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+}
+
+/// Poll run `id` until it's done or a minute passed, checking `check` against every run list seen
+async fn wait_for_run(router: &Router, id: usize, mut check: impl FnMut(&serde_json::Value)) -> serde_json::Value {
+    let deadline = Instant::now() + Duration::from_secs(60);
+    loop {
+        check(&request(router, "GET", "/runs", b"").await.1);
+        let status = request(router, "GET", &format!("/runs/{}", id), b"").await.1;
+        if !["queued", "running"].contains(&status["status"].as_str().unwrap()) || Instant::now() > deadline {
+            return status;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_server_run_lifecycle() {
+    let server = Arc::new(create_server("bgpsim_server_lifecycle"));
+    let root = server.root.clone();
+    let router = server.router();
+    let (status, topology) = request(&router, "PUT", "/topologies/small", TOPOLOGY.as_bytes()).await;
+    assert_eq!(status, 200);
+    assert_eq!(topology["num_ases"], 5);
+    let config = r#"{"label": "Hijack", "scenario_name": "SubprefixHijack", "attacker_asns": [5], "legitimate_origin_asns": [4]}"#;
+    assert_eq!(request(&router, "PUT", "/scenario_configs/hijack", config.as_bytes()).await.0, 200);
+
+    let run = r#"{"topology": "small", "scenario_configs": ["hijack"], "percent_ases_randomly_adopting": [0.0, 50.0], "num_trials": 2}"#;
+    let (status, started) = request(&router, "POST", "/runs", run.as_bytes()).await;
+    assert_eq!(status, 200);
+    assert_eq!(started["id"], 1);
+
+    let status = wait_for_run(&router, 1, |_| {}).await;
+    assert_eq!(status["status"], "finished", "{}", status);
+    assert_eq!(status["total_trials"], 4);
+    assert_eq!(status["completed_trials"], 4);
+    assert_eq!(request(&router, "GET", "/runs", b"").await.1[0], status);
+
+    let files = request(&router, "GET", "/runs/1/results", b"").await.1;
+    let files: Vec<&str> = files.as_array().unwrap().iter().map(|file| file.as_str().unwrap()).collect();
+    assert!(files.contains(&"run_metadata.json"), "{:?}", files);
+    let (status, metadata) = request(&router, "GET", "/runs/1/results/run_metadata.json", b"").await;
+    assert_eq!(status, 200);
+    assert_eq!(metadata["config"]["num_trials"], 2);
+    assert_eq!(request(&router, "GET", "/runs/1/results/secret.txt", b"").await.0, 404);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_server_bounds_concurrent_runs() {
+    let server = Arc::new(create_server("bgpsim_server_bounded").with_max_concurrent_runs(1));
+    let root = server.root.clone();
+    let router = server.router();
+    assert_eq!(request(&router, "PUT", "/topologies/small", TOPOLOGY.as_bytes()).await.0, 200);
+    let run = r#"{"topology": "small", "scenario_configs": [{"label": "Hijack", "scenario_name": "SubprefixHijack", "attacker_asns": [5], "legitimate_origin_asns": [4]}], "num_trials": 3}"#;
+    for id in 1..=3 {
+        assert_eq!(request(&router, "POST", "/runs", run.as_bytes()).await.1["id"], id);
+    }
+
+    // Runs past the limit wait for a slot rather than starting their own thread
+    let check = |runs: &serde_json::Value| {
+        let running = runs.as_array().unwrap().iter().filter(|run| run["status"] == "running").count();
+        assert!(running <= 1, "{}", runs);
+    };
+    for id in 1..=3 {
+        assert_eq!(wait_for_run(&router, id, check).await["status"], "finished");
+    }
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn test_server_rejects_bad_requests() {
+    let router = Arc::new(create_server("bgpsim_server_errors")).router();
+    assert_eq!(request(&router, "PUT", "/topologies/broken", b"not json").await.0, 400);
+    let cyclic = r#"[{"asn": 1, "providers": [2]}, {"asn": 2, "providers": [1]}]"#;
+    assert_eq!(request(&router, "PUT", "/topologies/cyclic", cyclic.as_bytes()).await.0, 400);
+    let (status, config) = request(&router, "PUT", "/scenario_configs/unnamed", br#"{"label": "x"}"#).await;
+    assert_eq!(status, 400);
+    assert_eq!(config["error"], "Invalid config: Scenario config is missing scenario_name");
+
+    let (status, missing) = request(&router, "POST", "/runs", br#"{"topology": "nope", "scenario_configs": []}"#).await;
+    assert_eq!(status, 404);
+    assert_eq!(missing["error"], "No topology nope");
+    assert_eq!(request(&router, "PUT", "/topologies/small", TOPOLOGY.as_bytes()).await.0, 200);
+    let (_, unknown_config) = request(&router, "POST", "/runs", br#"{"topology": "small", "scenario_configs": ["nope"]}"#).await;
+    assert_eq!(unknown_config["error"], "No scenario config nope");
+
+    assert_eq!(request(&router, "GET", "/runs/7", b"").await.0, 404);
+    assert_eq!(request(&router, "GET", "/runs/x/results", b"").await.0, 404);
+    let (status, unknown) = request(&router, "DELETE", "/runs/1/results", b"").await;
+    assert_eq!(status, 405);
+    assert_eq!(unknown, serde_json::Value::Null);
+    let (status, unknown) = request(&router, "GET", "/nowhere", b"").await;
+    assert_eq!(status, 404);
+    assert_eq!(unknown["error"], "No endpoint GET /nowhere");
+}
+
+#[tokio::test]
+async fn test_server_never_reads_config_files() {
+    let router = Arc::new(create_server("bgpsim_server_files")).router();
+    assert_eq!(request(&router, "PUT", "/topologies/small", TOPOLOGY.as_bytes()).await.0, 200);
+    // A file whose parse errors would quote it, were it opened
+    let secret = std::env::temp_dir().join(format!("bgpsim_server_secret_{}", std::process::id()));
+    std::fs::write(&secret, "top-secret-line\n").unwrap();
+
+    for field in ["measured_adoption_file", "irr_file"] {
+        let config = serde_json::json!({ "scenario_name": "SubprefixHijack", field: secret });
+        let (status, uploaded) = request(&router, "PUT", "/scenario_configs/files", config.to_string().as_bytes()).await;
+        assert_eq!(status, 400);
+        assert_eq!(uploaded["error"], format!("Invalid config: {} is not accepted here, since it names a file to read", field));
+        let run = serde_json::json!({ "topology": "small", "scenario_configs": [config] });
+        let (status, started) = request(&router, "POST", "/runs", run.to_string().as_bytes()).await;
+        assert_eq!(status, 400);
+        for body in [uploaded, started] {
+            assert!(!body.to_string().contains("top-secret"), "{}", body);
+        }
+    }
+    std::fs::remove_file(&secret).unwrap();
+}
@@ -0,0 +1,442 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use bgpsimulator::as_graphs::as_graph::{ASBuilder, ASGraph, ASN};
+use bgpsimulator::route_validator::{ROA, RouteValidator};
+use bgpsimulator::simulation_engine::{Announcement, SimulationEngine, TieBreaker};
+use bgpsimulator::simulation_framework::scenarios::SubprefixHijack;
+use bgpsimulator::shared::{ASNGroups, Outcomes, Settings};
+use bgpsimulator::simulation_framework::{
+    AdopterDraws, Checkpoint, ConfidenceLevel, CustomMetrics, DataTracker, Scenario, ScenarioConfig, ScenarioTrait, Simulation, SimulationSummary,
+    RunMetadata, SweepDimension, TrialRecord, TrialStats, VictimSampling,
+};
+
+/// Victim 4 and attacker 5 hang off different tier-1s
+///
+///   1 (Tier-1) --peer-- 2 (Tier-1)
+///      |                   |
+///      3                   5 (attacker)
+///      |
+///      4 (victim)
+fn create_test_as_graph() -> ASGraph {
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_peers(vec![2]).with_customers(vec![3]),
+        ASBuilder::new(2).as_tier_1().with_peers(vec![1]).with_customers(vec![5]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![4]),
+        ASBuilder::new(4).with_providers(vec![3]),
+        ASBuilder::new(5).with_providers(vec![2]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+    as_graph
+}
+
+/// Subprefix hijack that also tracks how many tier-1s were hijacked
+struct TrackedSubprefixHijack {
+    inner: SubprefixHijack,
+}
+
+impl ScenarioTrait for TrackedSubprefixHijack {
+    fn name(&self) -> &str {
+        "TrackedSubprefixHijack"
+    }
+
+    fn get_attacker_asns(&self, as_graph: &ASGraph) -> HashSet<ASN> {
+        self.inner.get_attacker_asns(as_graph)
+    }
+
+    fn get_legitimate_origin_asns(&self, as_graph: &ASGraph) -> HashSet<ASN> {
+        self.inner.get_legitimate_origin_asns(as_graph)
+    }
+
+    fn get_seed_asn_ann_dict(&self, as_graph: &ASGraph) -> HashMap<ASN, Vec<Announcement>> {
+        self.inner.get_seed_asn_ann_dict(as_graph)
+    }
+
+    fn get_roas(&self, as_graph: &ASGraph) -> Vec<ROA> {
+        self.inner.get_roas(as_graph)
+    }
+
+    fn setup_engine(&self, engine: &mut SimulationEngine, route_validator: &mut RouteValidator) {
+        self.inner.setup_engine(engine, route_validator)
+    }
+
+    fn is_successful(&self, engine: &SimulationEngine) -> bool {
+        self.inner.is_successful(engine)
+    }
+
+    fn register_custom_metrics(&self, metrics: &mut CustomMetrics) {
+        let hijacked_prefix = self.inner.hijacked_prefix;
+        let attacker_asns = self.inner.attacker_asns.clone();
+
+        metrics.register("tier_1_hijacked_fraction".to_string(), move |engine| {
+            let tier_1s: Vec<ASN> = engine.as_graph.iter()
+                .filter(|as_obj| as_obj.tier_1)
+                .map(|as_obj| as_obj.asn)
+                .collect();
+            let hijacked = tier_1s.iter()
+                .filter_map(|asn| engine.policy_store.get(asn))
+                .filter_map(|policy| policy.local_rib.get(&hijacked_prefix))
+                .filter(|ann| attacker_asns.contains(&ann.origin()))
+                .count();
+            hijacked as f64 / tier_1s.len() as f64
+        });
+        metrics.register("num_ases".to_string(), |engine| engine.as_graph.len() as f64);
+    }
+}
+
+#[test]
+fn test_custom_metrics_recorded() {
+    let as_graph = create_test_as_graph();
+    let scenario = TrackedSubprefixHijack {
+        inner: SubprefixHijack::new(HashSet::from([5]), HashSet::from([4])),
+    };
+
+    let metrics = scenario.custom_metrics();
+    assert_eq!(metrics.names().collect::<Vec<_>>(), vec!["tier_1_hijacked_fraction", "num_ases"]);
+
+    let mut tracker = DataTracker::new("Tracked".to_string(), 0.0);
+    for _ in 0..2 {
+        let mut engine = SimulationEngine::new(&as_graph);
+        let mut route_validator = RouteValidator::new();
+        scenario.setup_engine(&mut engine, &mut route_validator);
+        engine.run(5);
+        tracker.record_custom_metrics(&metrics, &engine);
+    }
+
+    // No AS runs ROV, so the more specific prefix wins everywhere
+    assert_eq!(tracker.custom_metrics["tier_1_hijacked_fraction"], vec![1.0, 1.0]);
+    assert_eq!(tracker.custom_metric_mean("num_ases"), Some(5.0));
+    assert_eq!(tracker.custom_metric_mean("missing"), None);
+}
+
+#[test]
+fn test_plane_outcome_rates() {
+    let mut tracker = DataTracker::new("Planes".to_string(), 0.0);
+    let control_plane = HashMap::from([(1, Outcomes::VictimSuccess), (2, Outcomes::VictimSuccess)]);
+    tracker.add_plane_outcomes(&control_plane, &HashMap::from([(1, Outcomes::Blackholed), (2, Outcomes::VictimSuccess)]));
+    tracker.add_plane_outcomes(&control_plane, &HashMap::from([(1, Outcomes::AttackerSuccess), (2, Outcomes::AttackerSuccess)]));
+
+    assert_eq!(tracker.control_plane_rate(Outcomes::VictimSuccess), 100.0);
+    assert_eq!(tracker.data_plane_rate(Outcomes::VictimSuccess), 25.0);
+    assert_eq!(tracker.data_plane_rate(Outcomes::Blackholed), 25.0);
+    assert_eq!(tracker.data_plane_rate(Outcomes::AttackerSuccess), 50.0);
+
+    let output_dir = std::env::temp_dir().join("bgpsimulator_plane_outcomes_test");
+    std::fs::create_dir_all(&output_dir).unwrap();
+    tracker.save_to_file(&output_dir).unwrap();
+    let saved: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(output_dir.join("Planes_0_percent.json")).unwrap()
+    ).unwrap();
+    assert_eq!(saved["control_plane_outcomes"], serde_json::json!({"VictimSuccess": 100.0}));
+    assert_eq!(saved["data_plane_outcomes"]["Blackholed"], 25.0);
+}
+
+#[test]
+fn test_pollution_time_series() {
+    // Trials of different lengths average round by round
+    let mut tracker = DataTracker::new("Series".to_string(), 0.0);
+    tracker.add_time_series("polluted_ases", &[3.0, 3.0]);
+    tracker.add_polluted_ases(&[1, 1, 4]);
+    assert_eq!(tracker.time_series_data["polluted_ases"], vec![2.0, 2.0, 4.0]);
+    assert_eq!(tracker.time_series_trials["polluted_ases"], vec![2, 2, 1]);
+
+    let config = ScenarioConfig::new("Polluted".to_string(), "SubprefixHijack".to_string())
+        .with_attacker_asns(HashSet::from([5]))
+        .with_legitimate_origin_asns(HashSet::from([4]));
+    let simulation = Simulation::new(create_test_as_graph()).with_num_trials(2).with_pollution_tracking(true);
+    let tracker = simulation.run_trials_for_percentage(&config, 0.0).unwrap();
+    // Without ROV the /25 reaches every other AS in the first round
+    let series = &tracker.time_series_data["polluted_ases"];
+    assert_eq!(series.len(), 100);
+    assert!(series.iter().all(|&count| count == 4.0), "{:?}", series);
+    assert_eq!(tracker.time_series_trials["polluted_ases"][0], 2);
+
+    // Checkpoints keep each trial's series
+    let mut trial_tracker = DataTracker::new("Polluted".to_string(), 0.0);
+    trial_tracker.add_polluted_ases(&[0, 4]);
+    let record = TrialRecord::from_tracker(&trial_tracker, Outcomes::AttackerSuccess, None);
+    assert_eq!(record.time_series["polluted_ases"], vec![0.0, 4.0]);
+    let mut checkpoint = Checkpoint::new();
+    checkpoint.record("Polluted", 0.0, 0, record.clone());
+    let reloaded = Checkpoint::from_json(&checkpoint.to_json()).unwrap();
+    assert_eq!(reloaded.get("Polluted", 0.0, 0), Some(&record));
+    assert!(!Simulation::new(create_test_as_graph()).track_pollution);
+}
+
+#[test]
+fn test_summary_confidence_intervals() {
+    let mut tracker = DataTracker::new("Summary".to_string(), 10.0);
+    for outcome in [Outcomes::AttackerSuccess, Outcomes::VictimSuccess, Outcomes::AttackerSuccess, Outcomes::VictimSuccess] {
+        tracker.add_outcome(outcome);
+    }
+    assert_eq!(tracker.trial_success_values(), vec![100.0, 0.0, 100.0, 0.0]);
+
+    let mut summary = SimulationSummary::new("Summary".to_string());
+    summary.add_trials(10.0, tracker.trial_success_values());
+    summary.add_data_point(50.0, 30.0);
+    assert_eq!(summary.success_rates, vec![50.0, 30.0]);
+
+    let stats = summary.trial_stats()[0].unwrap();
+    assert_eq!((stats.num_trials, stats.mean), (4, 50.0));
+    assert!((stats.std_dev - 57.735).abs() < 1e-3);
+    let (low, high) = stats.confidence_interval(ConfidenceLevel::NinetyFive);
+    assert!((high - 50.0 - 1.96 * stats.std_dev / 2.0).abs() < 1e-9 && (50.0 - low - (high - 50.0)).abs() < 1e-9);
+    let (low_90, high_90) = stats.confidence_interval(ConfidenceLevel::Ninety);
+    assert!(low < low_90 && high_90 < high);
+    // Points without per-trial values get no error bars
+    assert_eq!(summary.error_bars(ConfidenceLevel::NinetyFive), vec![(10.0, low, high)]);
+    assert_eq!(TrialStats::from_values(&[42.0]).unwrap().std_dev, 0.0);
+    assert!(TrialStats::from_values(&[]).is_none());
+
+    let output_dir = std::env::temp_dir().join("bgpsimulator_summary_test");
+    std::fs::create_dir_all(&output_dir).unwrap();
+    summary.save_to_file(&output_dir).unwrap();
+    let saved: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(output_dir.join("Summary_summary.json")).unwrap()
+    ).unwrap();
+    assert_eq!(saved["num_trials"], serde_json::json!([4, 0]));
+    let saved_ci = saved["ci_95"][0].as_array().unwrap();
+    assert!((saved_ci[0].as_f64().unwrap() - low).abs() < 1e-9 && (saved_ci[1].as_f64().unwrap() - high).abs() < 1e-9);
+    assert!(saved["ci_90"][1].is_null());
+}
+
+#[test]
+fn test_victim_sampling() {
+    // Stubs 4 and 5 plus a third stub 6 under AS 3
+    let mut as_graph = ASGraph::build(vec![
+        ASBuilder::new(1).as_tier_1().with_peers(vec![2]).with_customers(vec![3]),
+        ASBuilder::new(2).as_tier_1().with_peers(vec![1]).with_customers(vec![5]),
+        ASBuilder::new(3).with_providers(vec![1]).with_customers(vec![4, 6]),
+        ASBuilder::new(4).with_providers(vec![3]),
+        ASBuilder::new(5).with_providers(vec![2]),
+        ASBuilder::new(6).with_providers(vec![3]),
+    ]);
+    as_graph.assign_as_propagation_rank();
+
+    let sampling = VictimSampling::new(2).with_seed(7);
+    let victims = sampling.sample_victims(&as_graph, Some(&HashSet::from([5])));
+    assert_eq!(victims.len(), 2);
+    assert!(victims.iter().all(|asn| [4, 6].contains(asn)));
+    // Same seed, same victims
+    assert_eq!(victims, sampling.sample_victims(&as_graph, Some(&HashSet::from([5]))));
+    // More victims than candidates uses the whole group
+    let all_stubs = VictimSampling::new(100).with_victim_group(ASNGroups::Stubs);
+    assert_eq!(all_stubs.sample_victims(&as_graph, None), vec![4, 5, 6]);
+
+    // Fixed attacker, no adoption: every victim is hijacked in every trial
+    let config = ScenarioConfig::new("Victims".to_string(), "SubprefixHijack".to_string())
+        .with_adoption_setting(Settings::Rov, true)
+        .with_attacker_asns(HashSet::from([5]));
+    let simulation = Simulation::new(as_graph)
+        .with_victim_sampling(VictimSampling::new(3).with_trials_per_victim(2));
+    let tracker = simulation.run_trials_for_percentage(&config, 0.0).unwrap();
+
+    assert_eq!(tracker.victim_outcomes.keys().copied().collect::<Vec<ASN>>(), vec![4, 6]);
+    assert_eq!(tracker.outcomes.len(), 4);
+    assert_eq!(tracker.victim_protection_rates()[&4], 0.0);
+    assert_eq!(tracker.victim_protection_summary(), Some((0.0, 0.0)));
+    assert_eq!(tracker.success_rate(), 100.0);
+}
+
+#[test]
+fn test_aspa_objects_drawn_separately_from_validators() {
+    let as_graph = create_test_as_graph();
+    let config = ScenarioConfig::new("ASPA".to_string(), "SubprefixHijack".to_string())
+        .with_adoption_setting(Settings::Aspa, true);
+    let aspa_objects = |config: ScenarioConfig, percent: f64| {
+        let scenario = Scenario::new(config, &as_graph, percent);
+        let mut engine = SimulationEngine::new(&as_graph);
+        scenario.apply_adoption_settings(&mut engine);
+        engine.aspa_validator().unwrap().len()
+    };
+
+    // By default the validators are the ones registering, otherwise the two are independent
+    assert_eq!(aspa_objects(config.clone(), 0.0), 0);
+    assert_eq!(aspa_objects(config.clone(), 100.0), as_graph.len());
+    assert_eq!(aspa_objects(config.clone().with_percent_aspa_objects(100.0), 0.0), as_graph.len());
+    assert_eq!(aspa_objects(config.clone().with_percent_aspa_objects(0.0), 100.0), 0);
+
+    // Sweeping object registration runs every config once per percentage, tracked along both axes
+    let simulation = Simulation::new(create_test_as_graph())
+        .with_num_trials(2)
+        .with_scenario_configs(vec![config])
+        .with_aspa_object_percentages(vec![0.0, 50.0]);
+    let configs = simulation.expanded_scenario_configs();
+    let labels: Vec<&str> = configs.iter().map(|config| config.label.as_str()).collect();
+    assert_eq!(labels, vec!["ASPA; 0% ASPA objects", "ASPA; 50% ASPA objects"]);
+    let tracker = simulation.run_trials_for_percentage(&configs[1], 20.0).unwrap();
+    assert_eq!((tracker.percent_adopting, tracker.percent_aspa_objects), (20.0, Some(50.0)));
+}
+
+#[test]
+fn test_sweep_dimensions() {
+    let config = ScenarioConfig::new("Sweep".to_string(), "SubprefixHijack".to_string());
+    let output_dir = std::env::temp_dir().join(format!("bgpsim_sweep_{}", std::process::id()));
+    let simulation = Simulation::new(create_test_as_graph())
+        .with_output_dir(output_dir.clone())
+        .with_adoption_percentages(vec![0.0, 50.0])
+        .with_num_trials(1)
+        .with_scenario_configs(vec![config])
+        .with_sweep_dimension(SweepDimension::NumAttackers(vec![1, 2]))
+        .with_sweep_dimension(SweepDimension::PolicyMixes(vec![
+            ("ROV".to_string(), vec![Settings::Rov]),
+            ("ROV+ASPA".to_string(), vec![Settings::Rov, Settings::Aspa]),
+        ]));
+
+    let configs = simulation.expanded_scenario_configs();
+    let labels: Vec<&str> = configs.iter().map(|config| config.label.as_str()).collect();
+    assert_eq!(labels, vec![
+        "Sweep; 1 attackers; ROV",
+        "Sweep; 1 attackers; ROV+ASPA",
+        "Sweep; 2 attackers; ROV",
+        "Sweep; 2 attackers; ROV+ASPA",
+    ]);
+    assert_eq!(configs[3].num_attackers, 2);
+    assert_eq!(configs[3].default_adoption_settings.len(), 2);
+    assert_eq!(simulation.total_trials(), 4 * 2);
+
+    simulation.run().unwrap();
+    let results: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(output_dir.join("Sweep; 2 attackers; ROV_50_percent.json")).unwrap()
+    ).unwrap();
+    assert_eq!(
+        results["parameters"],
+        serde_json::json!({"num_attackers": 2, "policy_mix": "ROV", "percent_adopting": 50.0})
+    );
+    let summary: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(output_dir.join("Sweep; 1 attackers; ROV+ASPA_summary.json")).unwrap()
+    ).unwrap();
+    assert_eq!(summary["parameters"], serde_json::json!({"num_attackers": 1, "policy_mix": "ROV+ASPA"}));
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
+
+#[test]
+fn test_adopter_draws_shared_across_configs() {
+    let as_graph = create_test_as_graph();
+    let mut draws = AdopterDraws::generate(&as_graph, &[20.0, 60.0], 3);
+    assert_eq!(draws.num_trials(60.0), 3);
+    assert_eq!(draws.get(60.0, 2).unwrap().len(), 3);
+    assert!(draws.get(60.0, 3).is_none());
+    // Later trials are drawn on demand, earlier ones are kept
+    let first = draws.get(20.0, 0).unwrap().clone();
+    assert_eq!(draws.get_or_draw(&as_graph, 20.0, 4).len(), 1);
+    assert_eq!(draws.get(20.0, 0), Some(&first));
+    assert_eq!(draws.num_trials(20.0), 5);
+
+    let path = std::env::temp_dir().join(format!("bgpsim_adopter_draws_{}.json", std::process::id()));
+    draws.save_to_file(&path).unwrap();
+    assert_eq!(AdopterDraws::load_from_file(&path).unwrap(), draws);
+    let other_graph = ASGraph::build(vec![ASBuilder::new(1)]);
+    assert!(draws.check_graph(&other_graph).is_err());
+
+    // Every config draws from the same sets, so the second config draws nothing new
+    let shared = Arc::new(Mutex::new(AdopterDraws::new()));
+    let simulation = Simulation::new(create_test_as_graph())
+        .with_num_trials(4)
+        .with_adopter_draws(shared.clone());
+    let rov = ScenarioConfig::new("ROV".to_string(), "SubprefixHijack".to_string())
+        .with_adoption_setting(Settings::Rov, true);
+    let peer_rov = ScenarioConfig::new("Peer ROV".to_string(), "SubprefixHijack".to_string())
+        .with_adoption_setting(Settings::PeerRov, true);
+    simulation.run_trials_for_percentage(&rov, 60.0).unwrap();
+    let after_first = shared.lock().unwrap().clone();
+    assert_eq!(after_first.num_trials(60.0), 4);
+    simulation.run_trials_for_percentage(&peer_rov, 60.0).unwrap();
+    assert_eq!(*shared.lock().unwrap(), after_first);
+
+    // A fresh simulation picks the draws up from the file
+    let simulation = Simulation::new(create_test_as_graph())
+        .with_output_dir(std::env::temp_dir().join(format!("bgpsim_paired_{}", std::process::id())))
+        .with_adoption_percentages(vec![20.0])
+        .with_num_trials(2)
+        .with_scenario_configs(vec![rov])
+        .with_adopter_draws_file(path.clone());
+    simulation.run().unwrap();
+    let reloaded = AdopterDraws::load_from_file(&path).unwrap();
+    assert_eq!(reloaded.get(20.0, 0), Some(&first));
+    assert_eq!(reloaded.num_trials(60.0), 3);
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_dir_all(&simulation.output_dir).unwrap();
+}
+
+#[test]
+fn test_checkpoint_resume() {
+    let config = ScenarioConfig::new("Checkpointed".to_string(), "SubprefixHijack".to_string())
+        .with_adoption_setting(Settings::Rov, true);
+    let output_dir = std::env::temp_dir().join(format!("bgpsim_checkpoint_{}", std::process::id()));
+    let simulation = Simulation::new(create_test_as_graph())
+        .with_output_dir(output_dir.clone())
+        .with_adoption_percentages(vec![0.0])
+        .with_num_trials(2)
+        .with_scenario_configs(vec![config.clone()])
+        .with_paired_adoption()
+        .with_checkpointing(1);
+    simulation.run().unwrap();
+    let saved = Checkpoint::load_from_file(&Checkpoint::path(&output_dir)).unwrap();
+    assert_eq!(saved.num_trials("Checkpointed", 0.0), 2);
+    assert_eq!(saved.get("Checkpointed", 0.0, 1).unwrap().outcome, Outcomes::AttackerSuccess);
+    assert_eq!(saved.adopter_draws.as_ref().unwrap().num_trials(0.0), 2);
+
+    // Only the third trial runs; the first two are replayed, including a doctored one
+    let mut checkpoint = saved.clone();
+    let mut doctored = saved.get("Checkpointed", 0.0, 0).unwrap().clone();
+    doctored.outcome = Outcomes::VictimSuccess;
+    doctored.custom_metrics.insert("marker".to_string(), 7.0);
+    checkpoint.record("Checkpointed", 0.0, 0, doctored);
+    checkpoint.save_to_file(&Checkpoint::path(&output_dir)).unwrap();
+    assert_eq!(checkpoint.num_unsaved(), 0);
+
+    let resumed = Simulation::new(create_test_as_graph())
+        .with_num_trials(3)
+        .resume(output_dir.clone())
+        .unwrap();
+    assert_eq!(resumed.checkpoint_every, Some(Simulation::DEFAULT_CHECKPOINT_EVERY));
+    let tracker = resumed.run_trials_for_percentage(&config, 0.0).unwrap();
+    assert_eq!(tracker.outcomes, vec![Outcomes::VictimSuccess, Outcomes::AttackerSuccess, Outcomes::AttackerSuccess]);
+    assert_eq!(tracker.custom_metrics["marker"], vec![7.0]);
+    assert_eq!(tracker.data_plane_outcomes.len(), 3);
+    assert_eq!(resumed.checkpoint.lock().unwrap().num_trials("Checkpointed", 0.0), 3);
+
+    let record = TrialRecord::from_tracker(&tracker, Outcomes::AttackerSuccess, None);
+    assert_eq!(record.data_plane_outcomes, tracker.data_plane_outcomes[2]);
+    assert!(Simulation::new(create_test_as_graph()).resume(std::env::temp_dir().join("bgpsim_no_checkpoint")).is_err());
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
+
+#[test]
+fn test_run_metadata() {
+    let config = ScenarioConfig::new("Manifest".to_string(), "SubprefixHijack".to_string())
+        .with_tie_breaker(TieBreaker::SeededRandom(42));
+    let output_dir = std::env::temp_dir().join(format!("bgpsim_run_metadata_{}", std::process::id()));
+    let simulation = Simulation::new(create_test_as_graph())
+        .with_output_dir(output_dir.clone())
+        .with_adoption_percentages(vec![0.0])
+        .with_num_trials(1)
+        .with_scenario_configs(vec![config])
+        .with_victim_sampling(VictimSampling::new(1).with_seed(7))
+        .with_caida_snapshot_date("2024-01-01".to_string());
+    simulation.run().unwrap();
+
+    let metadata = RunMetadata::load_from_file(&RunMetadata::path(&output_dir)).unwrap();
+    assert_eq!(metadata.crate_version, "0.1.0");
+    assert_eq!(metadata.caida_snapshot_date.as_deref(), Some("2024-01-01"));
+    assert_eq!(metadata.seeds, BTreeMap::from([
+        ("Manifest tie_breaker".to_string(), 42),
+        ("victim_sampling".to_string(), 7),
+    ]));
+    assert_eq!(metadata.config["num_trials"], 1);
+    assert_eq!(metadata.config["scenario_configs"][0]["tie_breaker"], "SeededRandom:42");
+    assert_eq!(metadata.config["num_ases"], 5);
+    assert!(metadata.wall_clock_seconds.is_some());
+    assert!(metadata.num_cpus > 0);
+    assert_eq!(RunMetadata::from_json(&metadata.to_json()).unwrap(), metadata);
+    let report = std::fs::read_to_string(output_dir.join(Simulation::TOPOLOGY_REPORT_FILE)).unwrap();
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(report["num_ases"], 5);
+    assert_eq!(report["num_path_origins"], 5);
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}